@@ -0,0 +1,89 @@
+//! Creating new instances of a linked object by cloning an existing instance, compared to
+//! creating them from the family of the instance.
+
+#![allow(
+    missing_docs,
+    reason = "No need for API documentation in benchmark code"
+)]
+
+use std::{
+    hint::black_box,
+    sync::{Arc, atomic::AtomicUsize},
+};
+
+use benchmark_utils::{ThreadPool, bench_on_threadpool};
+use criterion::{Criterion, criterion_group, criterion_main};
+use linked::Object;
+
+criterion_group!(benches, entrypoint);
+criterion_main!(benches);
+
+#[expect(
+    dead_code,
+    reason = "We do not care about using all the fields but we want to pay the price of initializing them"
+)]
+#[linked::object]
+struct TestSubject {
+    local_state: AtomicUsize,
+    shared_state: Arc<AtomicUsize>,
+}
+
+impl TestSubject {
+    fn new() -> Self {
+        let shared_state = Arc::new(AtomicUsize::new(0));
+
+        linked::new!(Self {
+            local_state: AtomicUsize::new(0),
+            shared_state: Arc::clone(&shared_state),
+        })
+    }
+}
+
+fn entrypoint(c: &mut Criterion) {
+    let thread_pool = ThreadPool::default();
+
+    let mut g = c.benchmark_group("clone");
+
+    let subject = TestSubject::new();
+
+    g.bench_function("single-threaded", |b| {
+        b.iter(|| black_box(subject.clone()));
+    });
+
+    // This is how instances were cloned before cloning went directly through the `Link`.
+    g.bench_function("single-threaded-via-family", |b| {
+        b.iter(|| black_box(TestSubject::from(subject.family())));
+    });
+
+    g.bench_function("multi-threaded", |b| {
+        b.iter_custom(|iters| {
+            let family = subject.family();
+
+            bench_on_threadpool(
+                &thread_pool,
+                iters,
+                move || TestSubject::from(family.clone()),
+                |subject| {
+                    black_box(subject.clone());
+                },
+            )
+        });
+    });
+
+    g.bench_function("multi-threaded-via-family", |b| {
+        b.iter_custom(|iters| {
+            let family = subject.family();
+
+            bench_on_threadpool(
+                &thread_pool,
+                iters,
+                move || TestSubject::from(family.clone()),
+                |subject| {
+                    black_box(TestSubject::from(subject.family()));
+                },
+            )
+        });
+    });
+
+    g.finish();
+}
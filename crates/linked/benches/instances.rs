@@ -6,6 +6,7 @@
 )]
 
 use std::{
+    any::TypeId,
     hint::black_box,
     sync::{Arc, atomic::AtomicUsize},
 };
@@ -40,6 +41,18 @@ impl TestSubject {
 
 linked::instances!(static TARGET: TestSubject = TestSubject::new());
 
+// The same static variable without the per-variable thread-local slot generated by the macro,
+// with the slot looked up by the family key instead.
+struct RegistryTargetKey;
+
+const REGISTRY_TARGET: linked::StaticInstances<TestSubject> =
+    linked::StaticInstances::new(TypeId::of::<RegistryTargetKey>, TestSubject::new);
+
+thread_local! {
+    // The baseline - a per-thread instance accessed directly via thread-local storage.
+    static RAW_TARGET: TestSubject = TestSubject::new();
+}
+
 fn entrypoint(c: &mut Criterion) {
     let thread_pool = ThreadPool::default();
 
@@ -49,6 +62,10 @@ fn entrypoint(c: &mut Criterion) {
         b.iter(|| black_box(Arc::weak_count(&TARGET.get().shared_state)));
     });
 
+    g.bench_function("single-threaded-registry-backend", |b| {
+        b.iter(|| black_box(Arc::weak_count(&REGISTRY_TARGET.get().shared_state)));
+    });
+
     g.bench_function("multi-threaded", |b| {
         b.iter_custom(|iters| {
             bench_on_threadpool(
@@ -64,6 +81,35 @@ fn entrypoint(c: &mut Criterion) {
 
     g.finish();
 
+    let mut g = c.benchmark_group("instances::with");
+
+    g.bench_function("single-threaded", |b| {
+        b.iter(|| TARGET.with(|target| black_box(Arc::weak_count(&target.shared_state))));
+    });
+
+    g.bench_function("single-threaded-registry-backend", |b| {
+        b.iter(|| REGISTRY_TARGET.with(|target| black_box(Arc::weak_count(&target.shared_state))));
+    });
+
+    g.bench_function("single-threaded-raw-thread-local", |b| {
+        b.iter(|| RAW_TARGET.with(|target| black_box(Arc::weak_count(&target.shared_state))));
+    });
+
+    g.bench_function("multi-threaded", |b| {
+        b.iter_custom(|iters| {
+            bench_on_threadpool(
+                &thread_pool,
+                iters,
+                || (),
+                |()| {
+                    TARGET.with(|target| black_box(Arc::weak_count(&target.shared_state)));
+                },
+            )
+        });
+    });
+
+    g.finish();
+
     let mut g = c.benchmark_group("instances::get_1000");
 
     g.bench_function("single-threaded", |b| {
@@ -4,10 +4,15 @@
 //! This module contains logically private things that must be technically public
 //! because they are accessed from macro-generated code.
 
+use std::any::TypeId;
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
+use std::thread::{self, ThreadId};
 
-use crate::{Family, Object};
+#[cfg(feature = "thread_tracking")]
+use crate::CountedThread;
+use crate::sync::atomic::{self, AtomicUsize};
+use crate::{Family, InstancePerThread, Object, Ref, Shared, SharedFamilyStateRef, ThreadSafe};
 
 /// Re-export so we can use it via macros in projects that do not have a reference to `paste`.
 pub use ::paste::paste;
@@ -20,31 +25,229 @@ pub use ::paste::paste;
 /// The instance factory must be thread-safe, which implies that all captured state in this factory
 /// function must be `Send` + `Sync` + `'static`. The instances it returns do not need to be thread-
 /// safe, however.
+///
+/// The caller (i.e. the code invoking the macro) is recorded as the creation site of the family,
+/// if family checks are enabled.
 #[inline]
-pub fn new<T>(instance_factory: impl Fn(Link<T>) -> T + Send + Sync + 'static) -> T {
-    Link::new(Arc::new(instance_factory)).into_instance()
+#[track_caller]
+pub fn new<T>(instance_factory: impl Fn(Link<T>) -> T + Send + Sync + 'static) -> T
+where
+    T: Object + From<Family<T>>,
+{
+    // We go through the `From<Family<T>>` conversion generated by `#[linked::object]` so that
+    // the first instance is created the same way as all the others (e.g. to call hooks).
+    Family::new(InstanceFactory::Shared(Arc::new(instance_factory))).into()
+}
+
+/// This is meant to be used via the [`linked::new_try!`][crate::new_try] macro, never directly
+/// called.
+///
+/// Creates a family of linked objects from a fallible template, returning the error instead of the
+/// first instance if the template fails to create the first instance.
+///
+/// Instances other than the first are created from an existing family, which offers no way to
+/// return an error, so if the template fails to create any other instance, this panics.
+#[inline]
+#[track_caller]
+pub fn new_try<T, E>(
+    instance_factory: impl Fn(Link<T>) -> Result<T, E> + Send + Sync + 'static,
+) -> Result<T, E>
+where
+    T: Object + From<Family<T>>,
+    E: Debug,
+{
+    let instance_factory = Arc::new(instance_factory);
+
+    let family = Family::new(InstanceFactory::Shared(Arc::new({
+        let instance_factory = Arc::clone(&instance_factory);
+
+        move |link| {
+            instance_factory(link).unwrap_or_else(|e| {
+                panic!(
+                    "failed to create a new instance of {}, only the first instance of a family created via `linked::new_try!` may fail: {e:?}",
+                    std::any::type_name::<T>()
+                )
+            })
+        }
+    })));
+
+    family.try_into_instance(|link| instance_factory(link))
 }
 
 /// This is meant to be used via the `#[linked::object]` macro, never directly called.
 ///
 /// Clones a linked object. They require a specific pattern to clone, so the `#[linked::object]`
 /// macro wires up a suitable `Clone` implementation for all such types to avoid mistakes.
+///
+/// Takes the `Link` of the instance being cloned instead of the instance itself, so we can
+/// create the clone directly from the `Link` without going through a `Family`.
 #[inline]
-pub fn clone<T>(value: &T) -> T
+#[must_use]
+pub fn clone<T>(link: &Link<T>) -> T
 where
-    T: Object + From<Family<T>>,
+    T: Object,
+{
+    link.new_instance()
+}
+
+/// This is meant to be used via the [`linked::assert_object!`][crate::assert_object] macro, never
+/// directly called.
+///
+/// Only compiles if `T` is a linked object.
+#[inline]
+pub const fn assert_object<T>()
+where
+    T: Object,
 {
-    value.family().into()
 }
 
-pub(crate) type InstanceFactory<T> = Arc<dyn Fn(Link<T>) -> T + Send + Sync + 'static>;
+/// This is meant to be used via the [`linked::assert_thread_safe!`][crate::assert_thread_safe]
+/// macro, never directly called.
+///
+/// Only compiles if `T` is thread-safe.
+#[inline]
+pub const fn assert_thread_safe<T>()
+where
+    T: ThreadSafe + ?Sized,
+{
+}
+
+/// This is meant to be used via the [`linked::new_box!`][crate::new_box] macro, never directly
+/// called.
+///
+/// Returns the `TypeId` of the concrete type created by a boxed instance factory, before the
+/// box is converted to a trait object.
+#[inline]
+#[must_use]
+pub fn boxed_type_id<C, F>(_factory: &F) -> TypeId
+where
+    C: 'static,
+    F: Fn() -> Box<C>,
+{
+    TypeId::of::<C>()
+}
+
+/// This is meant to be used via the `#[linked::object]` macro, never directly called.
+///
+/// Initializes a field marked with `#[linked(shared)]`. The value is created by the first instance
+/// of the family and all other instances of the family reference the same value.
+#[inline]
+pub fn shared_field<T, V>(
+    link: &Link<T>,
+    name: &'static str,
+    create_value: impl FnOnce() -> V,
+) -> Shared<V>
+where
+    V: Send + Sync + 'static,
+{
+    link.shared_state.shared_field(name, create_value)
+}
+
+/// This is meant to be used via the `#[linked::object]` macro, never directly called.
+///
+/// Initializes a field marked with `#[linked(shared)]` in `linked::new_try!`. If creating the
+/// value fails, the error is returned and the next instance to initialize the field tries again.
+#[inline]
+pub fn try_shared_field<T, V, E>(
+    link: &Link<T>,
+    name: &'static str,
+    create_value: impl FnOnce() -> Result<V, E>,
+) -> Result<Shared<V>, E>
+where
+    V: Send + Sync + 'static,
+{
+    link.shared_state.try_shared_field(name, create_value)
+}
+
+/// This is meant to be used via the `#[linked::object]` macro, never directly called.
+///
+/// Initializes a field marked with `#[linked(parent)]`. The parent family is provided by the first
+/// instance of the family and every instance references the parent's instance on its own thread.
+#[inline]
+pub fn parent_field<T, P, V>(
+    link: &Link<T>,
+    name: &'static str,
+    create_value: impl FnOnce() -> V,
+) -> Ref<P>
+where
+    P: Object,
+    V: Into<InstancePerThread<P>>,
+{
+    link.shared_state
+        .shared_field(name, || create_value().into())
+        .acquire()
+}
+
+/// This is meant to be used via the `#[linked::object]` macro, never directly called.
+///
+/// Initializes a field marked with `#[linked(parent)]` in `linked::new_try!`. If providing the
+/// parent family fails, the error is returned and the next instance to initialize the field
+/// tries again.
+#[inline]
+pub fn try_parent_field<T, P, V, E>(
+    link: &Link<T>,
+    name: &'static str,
+    create_value: impl FnOnce() -> Result<V, E>,
+) -> Result<Ref<P>, E>
+where
+    P: Object,
+    V: Into<InstancePerThread<P>>,
+{
+    let parent = link
+        .shared_state
+        .try_shared_field(name, || create_value().map(Into::into))?;
+
+    Ok(parent.acquire())
+}
+
+/// Creates the instances of a family, wiring up the `Link` of each new instance.
+pub(crate) enum InstanceFactory<T> {
+    /// Created at runtime by [`linked::new!`][crate::new].
+    Shared(Arc<dyn Fn(Link<T>) -> T + Send + Sync + 'static>),
+
+    /// Defined at compile time by [`linked::new_static!`][crate::new_static].
+    #[cfg(not(loom))]
+    Static(fn(Link<T>) -> T),
+}
+
+impl<T> InstanceFactory<T> {
+    fn create(&self, link: Link<T>) -> T {
+        match self {
+            Self::Shared(factory) => factory(link),
+            #[cfg(not(loom))]
+            Self::Static(factory) => factory(link),
+        }
+    }
+}
+
+impl<T> Clone for InstanceFactory<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Shared(factory) => Self::Shared(Arc::clone(factory)),
+            #[cfg(not(loom))]
+            Self::Static(factory) => Self::Static(*factory),
+        }
+    }
+}
 
 /// An object that connects an instance to other instances in the same linked object family.
 ///
 /// This type serves the linked object infrastructure and is not meant to be used by user code.
 /// It is a private public type because it is used in macro-generated code.
+///
+/// Every instance of a linked object owns exactly one `Link`, so the lifetime of the `Link` is
+/// the lifetime of the instance, which we use to keep track of the number of instances.
 pub struct Link<T> {
-    pub(super) instance_factory: InstanceFactory<T>,
+    instance_factory: InstanceFactory<T>,
+    shared_state: SharedFamilyStateRef,
+
+    // The thread on which the instance was created, which the instance is counted against.
+    #[cfg(feature = "thread_tracking")]
+    thread: CountedThread,
+
+    // The version of the family that the instance was created at or last refreshed to. This is
+    // atomic only so that instances of `Sync` linked objects remain `Sync`.
+    observed_version: AtomicUsize,
 }
 
 impl<T> Debug for Link<T> {
@@ -64,29 +267,137 @@ impl<T> Debug for Link<T> {
 
 impl<T> Link<T> {
     #[must_use]
-    pub(super) fn new(instance_factory: InstanceFactory<T>) -> Self {
-        Self { instance_factory }
+    pub(super) fn new(
+        instance_factory: InstanceFactory<T>,
+        shared_state: SharedFamilyStateRef,
+    ) -> Self {
+        #[cfg(feature = "thread_tracking")]
+        let thread = shared_state.threads().increment(None);
+
+        Self::from_counted_parts(
+            instance_factory,
+            shared_state,
+            #[cfg(feature = "thread_tracking")]
+            thread,
+        )
+    }
+
+    // Assembles a `Link` for an instance that has already been counted against `thread`.
+    fn from_counted_parts(
+        instance_factory: InstanceFactory<T>,
+        shared_state: SharedFamilyStateRef,
+        #[cfg(feature = "thread_tracking")] thread: CountedThread,
+    ) -> Self {
+        shared_state.increment();
+
+        #[cfg(feature = "lifecycle_events")]
+        crate::lifecycle_events::publish(
+            crate::LifecycleEventKind::Created,
+            std::any::type_name::<T>(),
+            shared_state.id(),
+            thread.thread_id(),
+        );
+
+        let observed_version = AtomicUsize::new(shared_state.version());
+
+        Self {
+            instance_factory,
+            shared_state,
+            #[cfg(feature = "thread_tracking")]
+            thread,
+            observed_version,
+        }
     }
 
     #[must_use]
     pub(super) fn into_instance(self) -> T {
-        let instance_factory = Arc::clone(&self.instance_factory);
-        (instance_factory)(self)
+        let instance_factory = self.instance_factory.clone();
+        instance_factory.create(self)
+    }
+
+    /// Creates a new instance of the family on the current thread.
+    ///
+    /// This is cheaper than going through [`family()`][Self::family], as we call the instance
+    /// factory of this `Link` directly and, if the current thread is the thread of this `Link`,
+    /// count the new instance via our per-thread counter instead of looking up the thread (with
+    /// the `thread_tracking` Cargo feature).
+    #[must_use]
+    pub(super) fn new_instance(&self) -> T
+    where
+        T: Object,
+    {
+        // Our own instance is alive, so if it is on the current thread, the state of the thread
+        // cannot go away.
+        #[cfg(feature = "thread_tracking")]
+        let thread = self.shared_state.threads().increment(Some(&self.thread));
+
+        // The template of the family may have been replaced since our instance was created.
+        let replaced_template = self.shared_state.replaced_template();
+        let instance_factory = replaced_template.as_ref().unwrap_or(&self.instance_factory);
+
+        let link = Self::from_counted_parts(
+            instance_factory.clone(),
+            self.shared_state.clone(),
+            #[cfg(feature = "thread_tracking")]
+            thread,
+        );
+
+        let mut instance = instance_factory.create(link);
+        instance.__private_on_instance_created();
+        instance
     }
 
     // This type deliberately does not implement `Clone` to discourage accidental implementation of
     // cloning of type `T` via `#[derive(Clone)]`. The expected pattern is to use `#[linked::object]`
     // which generates both a `Linked` implementation and a specialized `Clone` implementation.
-    #[must_use]
-    fn clone(&self) -> Self {
-        Self {
-            instance_factory: Arc::clone(&self.instance_factory),
-        }
-    }
 
     #[inline]
     #[must_use]
     pub fn family(&self) -> Family<T> {
-        Family::new(self.clone())
+        Family::from_parts(self.instance_factory.clone(), self.shared_state.clone())
+    }
+
+    /// The current version of the family if the instance has not yet observed it, `None` if the
+    /// instance is up to date.
+    #[inline]
+    pub(crate) fn stale_version(&self) -> Option<usize> {
+        let version = self.shared_state.version();
+
+        (version != self.observed_version.load(atomic::Ordering::Relaxed)).then_some(version)
+    }
+
+    /// Records that the instance has been refreshed to `version` of the family.
+    #[inline]
+    pub(crate) fn observe_version(&self, version: usize) {
+        self.observed_version
+            .store(version, atomic::Ordering::Relaxed);
+    }
+}
+
+impl<T> Drop for Link<T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "lifecycle_events")]
+        crate::lifecycle_events::publish(
+            crate::LifecycleEventKind::Dropped,
+            std::any::type_name::<T>(),
+            self.shared_state.id(),
+            self.thread.thread_id(),
+        );
+
+        self.shared_state.decrement();
+
+        #[cfg(feature = "thread_tracking")]
+        self.shared_state.threads().decrement(&self.thread);
     }
 }
+
+// Looking up the current thread via `thread::current()` involves reference counting, so we
+// cache the ID of the thread, which we need whenever a new instance is created.
+pub(crate) fn current_thread_id() -> ThreadId {
+    thread_local!(static CURRENT_THREAD_ID: ThreadId = thread::current().id());
+
+    // If the thread-local storage is being destroyed, we fall back to the slow path.
+    CURRENT_THREAD_ID
+        .try_with(|thread_id| *thread_id)
+        .unwrap_or_else(|_| thread::current().id())
+}
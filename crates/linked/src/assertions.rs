@@ -0,0 +1,123 @@
+/// Marker trait for values that can be shared between the instances of a
+/// [linked object][crate] family, which requires them to be `Send` + `Sync` + `'static`.
+///
+/// This is implemented for every type that satisfies the requirements. Its purpose is to produce
+/// a clear compile error when a type that is meant to be shared state does not, for example via
+/// [`linked::assert_thread_safe!`][crate::assert_thread_safe].
+///
+/// The state captured by [`linked::new!`][crate::new] is shared between all instances of the
+/// family, so it must be thread-safe. This rules out capturing an instance of another linked
+/// object (instances are typically not `Sync`) - capture its [`Family<T>`][crate::Family] instead
+/// and create the nested instance from it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be shared between the instances of a linked object family",
+    label = "not `Send` + `Sync` + `'static`",
+    note = "state shared by a linked object family must be thread-safe - wrap it in `Arc<Mutex<_>>` or use atomics",
+    note = "to share a linked object with a family, capture its `linked::Family<T>` instead of an instance"
+)]
+pub trait ThreadSafe: Send + Sync + 'static {}
+
+impl<T> ThreadSafe for T where T: Send + Sync + 'static + ?Sized {}
+
+/// Asserts at compile time that each of the given types is a [linked object][crate].
+///
+/// This fails to compile if a type is not decorated with [`#[linked::object]`][crate::object],
+/// catching types that have lost the attribute (e.g. during refactoring) before any code tries
+/// to create a family of them.
+///
+/// The assertion can be used both at module level and inside functions and has no runtime cost.
+///
+/// # Example
+///
+/// ```
+/// #[linked::object]
+/// struct Counter {
+///     value: usize,
+/// }
+///
+/// linked::assert_object!(Counter);
+/// ```
+///
+/// A type that is not a linked object fails to compile:
+///
+/// ```compile_fail
+/// struct Counter {
+///     value: usize,
+/// }
+///
+/// linked::assert_object!(Counter);
+/// ```
+#[macro_export]
+macro_rules! assert_object {
+    ($($t:ty),+ $(,)?) => {
+        const _: () = {
+            $(
+                $crate::__private::assert_object::<$t>();
+            )+
+        };
+    };
+}
+
+/// Asserts at compile time that each of the given types is [thread-safe][crate::ThreadSafe],
+/// so it can be used as state shared between the instances of a [linked object][crate] family.
+///
+/// This fails to compile if a type is not `Send` + `Sync` + `'static`. Use it on the types of
+/// the state captured by [`linked::new!`][crate::new] to catch a `!Send` or `!Sync` field at its
+/// declaration, or on a linked object type itself if it is meant to be used via
+/// [`linked::thread_local_arc!`][crate::thread_local_arc] or
+/// [`linked::InstancePerThreadSync<T>`][crate::InstancePerThreadSync].
+///
+/// The assertion can be used both at module level and inside functions and has no runtime cost.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::sync::{Arc, Mutex};
+///
+/// type SharedTokens = Arc<Mutex<HashMap<String, String>>>;
+///
+/// linked::assert_thread_safe!(SharedTokens);
+/// ```
+///
+/// Shared state that is not thread-safe fails to compile:
+///
+/// ```compile_fail
+/// use std::cell::RefCell;
+/// use std::collections::HashMap;
+/// use std::rc::Rc;
+///
+/// type SharedTokens = Rc<RefCell<HashMap<String, String>>>;
+///
+/// linked::assert_thread_safe!(SharedTokens);
+/// ```
+#[macro_export]
+macro_rules! assert_thread_safe {
+    ($($t:ty),+ $(,)?) => {
+        const _: () = {
+            $(
+                $crate::__private::assert_thread_safe::<$t>();
+            )+
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+
+    use crate::Family;
+
+    #[linked::object]
+    struct Counter {}
+
+    crate::assert_object!(Counter);
+    crate::assert_thread_safe!(Arc<AtomicUsize>, Family<Counter>, str);
+
+    #[test]
+    fn assertions_compile_in_function_scope() {
+        crate::assert_object!(Counter, crate::Box<dyn Send>,);
+        crate::assert_thread_safe!(usize);
+    }
+}
@@ -1,9 +1,12 @@
 // Copyright (c) Microsoft Corporation.
 // Copyright (c) Folo authors.
 
+use std::any::{Any, TypeId};
 use std::boxed::Box as StdBox;
 use std::ops::{Deref, DerefMut};
 
+use crate::Object;
+
 /// A linked object that acts like a `std::boxed::Box<dyn MyTrait>`.
 ///
 /// Intended to represent linked instances of `T` where `T: MyTrait`. This is for use with types
@@ -61,6 +64,30 @@ use std::ops::{Deref, DerefMut};
 /// Any connections between the instances should be established via the captured state of this
 /// closure (e.g. sharing an `Arc` or setting up messaging channels).
 ///
+/// # Thread safety
+///
+/// The box is `Send` and `Sync` if the trait object inside it is. Add the auto traits you need to
+/// the trait object type to make them available, e.g. `linked::Box<dyn ConfigSource + Send>`:
+///
+/// * `linked::Box<dyn MyTrait>` can only be used on the thread where it was created. New
+///   instances for other threads can still be created via the [`Family`][7].
+/// * `linked::Box<dyn MyTrait + Send>` can additionally be moved to other threads, e.g. to be
+///   captured by a closure passed to `std::thread::spawn()`.
+/// * `linked::Box<dyn MyTrait + Send + Sync>` can additionally be used with
+///   [`linked::thread_local_arc!`][4] and [`linked::InstancePerThreadSync<T>`][6].
+///
+/// The same auto traits must be named in the [`linked::new_box!` macro][8] and the object
+/// created by the macro must implement them.
+///
+/// To convert a box into one with fewer auto traits (e.g. from `dyn MyTrait + Send` to
+/// `dyn MyTrait`) or into a supertrait object, use [`cast()`][Self::cast].
+///
+/// # Downcasting
+///
+/// The box remembers the concrete type of the object it contains, so you can recover the concrete
+/// type via [`downcast_ref()`][Self::downcast_ref], [`downcast_mut()`][Self::downcast_mut] and
+/// [`downcast()`][Self::downcast], similar to `std::boxed::Box<dyn Any>`.
+///
 /// # Example
 ///
 /// Using the linked objects as `linked::Box<dyn ConfigSource>`, without the user code knowing the
@@ -125,18 +152,156 @@ use std::ops::{Deref, DerefMut};
 #[derive(Debug)]
 pub struct Box<T: ?Sized + 'static> {
     value: StdBox<T>,
+
+    // The type of the object in `value` before it was converted to `T`.
+    concrete_type: TypeId,
 }
 
 impl<T: ?Sized> Box<T> {
     /// This is an implementation detail of the `linked::new_box!` macro and is not part of the
     /// public API. It is not meant to be used directly and may change or be removed at any time.
+    ///
+    /// `concrete_type` must be the type of the object returned by the instance factory, before
+    /// conversion to `T`. Downcasting relies on this being correct.
     #[doc(hidden)]
     #[must_use]
-    pub fn new(instance_factory: impl Fn() -> StdBox<T> + Send + Sync + 'static) -> Self {
+    pub fn new(
+        concrete_type: TypeId,
+        instance_factory: impl Fn() -> StdBox<T> + Send + Sync + 'static,
+    ) -> Self {
         linked::new!(Self {
             value: (instance_factory)(),
+            concrete_type,
         })
     }
+
+    /// Converts the box into a box of a different trait object type, such as a supertrait or
+    /// the same trait with fewer auto traits (e.g. `dyn MyTrait + Send` to `dyn MyTrait`).
+    ///
+    /// `cast` converts the contents of each instance and is typically just `|value| value`,
+    /// relying on the compiler to coerce the trait object to the target type.
+    ///
+    /// The returned box is the first instance of a new family that creates its instances from
+    /// the same template as the family of the original box, so all instances of both families
+    /// remain connected via any state captured by the template. The original instance is dropped
+    /// and its local state is not carried over.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// trait ConfigSource {
+    ///     fn config(&self) -> String;
+    /// }
+    ///
+    /// struct XmlConfig {}
+    ///
+    /// impl ConfigSource for XmlConfig {
+    ///     fn config(&self) -> String {
+    ///         "xml".to_string()
+    ///     }
+    /// }
+    ///
+    /// let config: linked::Box<dyn ConfigSource + Send> =
+    ///     linked::new_box!(dyn ConfigSource + Send, XmlConfig {});
+    ///
+    /// let config: linked::Box<dyn ConfigSource> = config.cast(|value| value);
+    /// assert_eq!(config.config(), "xml");
+    /// ```
+    #[must_use]
+    pub fn cast<U>(self, cast: fn(StdBox<T>) -> StdBox<U>) -> Box<U>
+    where
+        U: ?Sized + 'static,
+    {
+        let family = self.family();
+        let concrete_type = self.concrete_type;
+        drop(self);
+
+        Box::new(concrete_type, move || {
+            let instance: Self = family.clone().into();
+            cast(instance.value)
+        })
+    }
+
+    /// Whether the object in the box is of type `C`.
+    #[must_use]
+    pub fn is<C: Any>(&self) -> bool {
+        self.concrete_type == TypeId::of::<C>()
+    }
+
+    /// Returns a reference to the object in the box if it is of type `C`, or `None` if it is not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// trait ConfigSource {}
+    ///
+    /// struct XmlConfig {
+    ///     config: String,
+    /// }
+    ///
+    /// impl ConfigSource for XmlConfig {}
+    ///
+    /// let config: linked::Box<dyn ConfigSource> = linked::new_box!(
+    ///     dyn ConfigSource,
+    ///     XmlConfig {
+    ///         config: "xml".to_string(),
+    ///     }
+    /// );
+    ///
+    /// let xml_config = config.downcast_ref::<XmlConfig>().unwrap();
+    /// assert_eq!(xml_config.config, "xml");
+    ///
+    /// assert!(config.downcast_ref::<String>().is_none());
+    /// ```
+    #[must_use]
+    pub fn downcast_ref<C: Any>(&self) -> Option<&C> {
+        if !self.is::<C>() {
+            return None;
+        }
+
+        let ptr = (&raw const *self.value).cast::<C>();
+
+        // SAFETY: We just verified that the object in the box is of type `C`, so the data
+        // pointer of the (potentially wide) pointer to `T` points to a valid `C`.
+        Some(unsafe { &*ptr })
+    }
+
+    /// Returns an exclusive reference to the object in the box if it is of type `C`,
+    /// or `None` if it is not.
+    #[must_use]
+    pub fn downcast_mut<C: Any>(&mut self) -> Option<&mut C> {
+        if !self.is::<C>() {
+            return None;
+        }
+
+        let ptr = (&raw mut *self.value).cast::<C>();
+
+        // SAFETY: We just verified that the object in the box is of type `C`, so the data
+        // pointer of the (potentially wide) pointer to `T` points to a valid `C`.
+        Some(unsafe { &mut *ptr })
+    }
+
+    /// Takes the object out of the box if it is of type `C`, or returns the box unchanged
+    /// if it is not.
+    ///
+    /// The returned object is no longer part of the linked object family - this is a one-way
+    /// conversion, similar to placing a linked object into a `std::boxed::Box<dyn Xyz>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the box unchanged if the object in the box is not of type `C`.
+    pub fn downcast<C: Any>(self) -> Result<StdBox<C>, Self> {
+        if !self.is::<C>() {
+            return Err(self);
+        }
+
+        let ptr = StdBox::into_raw(self.value).cast::<C>();
+
+        // SAFETY: We just verified that the object in the box is of type `C`, so the data
+        // pointer of the (potentially wide) pointer to `T` points to a valid `C` that was
+        // originally allocated as a `StdBox<C>` by `linked::new_box!`.
+        Ok(unsafe { StdBox::from_raw(ptr) })
+    }
 }
 
 impl<T: ?Sized + 'static> Deref for Box<T> {
@@ -193,10 +358,17 @@ impl<T: ?Sized + 'static> DerefMut for Box<T> {
 /// See `examples/linked_box.rs` for a complete example.
 #[macro_export]
 macro_rules! new_box {
-    ($dyn_trait:ty, $ctor:expr) => {
+    ($dyn_trait:ty, $ctor:expr) => {{
+        let concrete_factory = move || ::std::boxed::Box::new($ctor);
+
+        // We remember the concrete type to support downcasting.
+        let concrete_type = ::linked::__private::boxed_type_id(&concrete_factory);
+
         #[expect(trivial_casts, reason = "clearly expresses intent")]
-        ::linked::Box::new(move || ::std::boxed::Box::new($ctor) as ::std::boxed::Box<$dyn_trait>)
-    };
+        let instance_factory = move || concrete_factory() as ::std::boxed::Box<$dyn_trait>;
+
+        ::linked::Box::new(concrete_type, instance_factory)
+    }};
 }
 
 #[cfg(test)]
@@ -205,6 +377,129 @@ mod tests {
 
     use crate::Object;
 
+    trait Named {
+        fn name(&self) -> &str;
+    }
+
+    struct Alpha {
+        name: String,
+    }
+
+    struct Beta {}
+
+    impl Named for Alpha {
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    impl Named for Beta {
+        fn name(&self) -> &str {
+            "beta"
+        }
+    }
+
+    fn new_alpha() -> linked::Box<dyn Named> {
+        linked::new_box!(
+            dyn Named,
+            Alpha {
+                name: "alpha".to_string(),
+            }
+        )
+    }
+
+    fn new_beta() -> linked::Box<dyn Named> {
+        linked::new_box!(dyn Named, Beta {})
+    }
+
+    #[test]
+    fn downcast_ref_and_mut() {
+        let mut items = [new_alpha(), new_beta()];
+
+        assert!(items[0].is::<Alpha>());
+        assert!(!items[0].is::<Beta>());
+        assert!(items[1].is::<Beta>());
+
+        assert_eq!(items[0].downcast_ref::<Alpha>().unwrap().name, "alpha");
+        assert!(items[1].downcast_ref::<Alpha>().is_none());
+
+        items[0].downcast_mut::<Alpha>().unwrap().name = "changed".to_string();
+        assert_eq!(items[0].name(), "changed");
+        assert!(items[1].downcast_mut::<Alpha>().is_none());
+    }
+
+    #[test]
+    fn downcast_preserved_across_family() {
+        let alpha = new_alpha();
+        let family = alpha.family();
+
+        thread::spawn(move || {
+            let alpha: linked::Box<dyn Named> = family.into();
+            assert!(alpha.is::<Alpha>());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn downcast_owned() {
+        let alpha = new_alpha();
+        let alpha = alpha.downcast::<Alpha>().ok().unwrap();
+        assert_eq!(alpha.name, "alpha");
+
+        let beta = new_beta();
+        let Err(beta) = beta.downcast::<Alpha>() else {
+            panic!("downcast to the wrong type must fail");
+        };
+        assert_eq!(beta.name(), "beta");
+    }
+
+    fn new_alpha_sync() -> linked::Box<dyn Named + Send + Sync> {
+        linked::new_box!(
+            dyn Named + Send + Sync,
+            Alpha {
+                name: "alpha".to_string(),
+            }
+        )
+    }
+
+    #[test]
+    fn send_and_sync_boxes() {
+        let alpha = new_alpha_sync();
+
+        let alpha = thread::spawn(move || {
+            assert_eq!(alpha.name(), "alpha");
+            alpha
+        })
+        .join()
+        .unwrap();
+
+        let per_thread = linked::InstancePerThreadSync::new(alpha);
+        let alpha = per_thread.acquire();
+
+        thread::scope(|s| {
+            s.spawn(|| assert_eq!(alpha.name(), "alpha"));
+        });
+    }
+
+    #[test]
+    fn cast_drops_auto_traits() {
+        let alpha = new_alpha_sync();
+        let family = alpha.family();
+
+        let alpha: linked::Box<dyn Named> = alpha.cast(|value| value);
+        assert_eq!(alpha.name(), "alpha");
+        assert!(alpha.is::<Alpha>());
+
+        // The original family is still alive, used as the template for the new family.
+        assert_eq!(family.instance_count(), 0);
+        assert_eq!(alpha.family().instance_count(), 1);
+
+        let clone = alpha.clone();
+        assert_eq!(clone.name(), "alpha");
+        assert_eq!(alpha.family().instance_count(), 2);
+    }
+
     #[test]
     fn linked_box() {
         trait ConfigSource {
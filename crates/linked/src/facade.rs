@@ -0,0 +1,277 @@
+/// Generates a zero-sized facade type that implements a trait by delegating every call to the
+/// current thread's instance of a [linked object][crate] in a static variable.
+///
+/// This allows call sites to use a thread-sharded service through a trait without knowing that
+/// the service is a linked object - there is no need to call `.with()` or `.to_rc()` on the
+/// static variable or to pass instances around. The facade is `Copy`, `Send` and `Sync`, so it can
+/// be freely shared, even if the linked object itself is not thread-safe.
+///
+/// The static variable must be declared via [`linked::thread_local_rc!`][1],
+/// [`linked::thread_local_arc!`][2] or [`linked::instances!`][3]. In the latter case, the facade
+/// uses the instance cached for the current thread by [`.with()`][4].
+///
+/// # Syntax
+///
+/// The first item declares the facade type, naming the static variable and the type of the
+/// linked object in it. The following items list the traits to implement for the facade type,
+/// with the signatures of the trait methods to delegate:
+///
+/// ```ignore
+/// linked::facade! {
+///     <visibility> struct <Facade> => <STATIC_VARIABLE>: <LinkedObjectType>;
+///
+///     impl <Trait> {
+///         fn <method>(&self, <arg>: <ArgType>, ...) -> <ReturnType>;
+///         ...
+///     }
+/// }
+/// ```
+///
+/// Only methods that take `&self` are supported, as the instance of the current thread is shared
+/// with all other callers on the same thread. Generic methods are not supported.
+///
+/// # Example
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// pub trait RequestCounter {
+///     fn record_request(&self);
+///     fn total_requests(&self) -> usize;
+/// }
+///
+/// #[linked::object]
+/// struct ShardedRequestCounter {
+///     total: Arc<AtomicUsize>,
+///     local: Cell<usize>,
+/// }
+///
+/// impl ShardedRequestCounter {
+///     pub fn new() -> Self {
+///         let total = Arc::new(AtomicUsize::new(0));
+///
+///         linked::new!(Self {
+///             total: Arc::clone(&total),
+///             local: Cell::new(0),
+///         })
+///     }
+/// }
+///
+/// impl RequestCounter for ShardedRequestCounter {
+///     fn record_request(&self) {
+///         self.local.set(self.local.get() + 1);
+///         self.total.fetch_add(1, Ordering::Relaxed);
+///     }
+///
+///     fn total_requests(&self) -> usize {
+///         self.total.load(Ordering::Relaxed)
+///     }
+/// }
+///
+/// linked::thread_local_rc!(static REQUEST_COUNTER: ShardedRequestCounter =
+///     ShardedRequestCounter::new());
+///
+/// linked::facade! {
+///     /// Counts requests using the current thread's shard of the request counter.
+///     pub struct Requests => REQUEST_COUNTER: ShardedRequestCounter;
+///
+///     impl RequestCounter {
+///         fn record_request(&self);
+///         fn total_requests(&self) -> usize;
+///     }
+/// }
+///
+/// // Call sites only see a `RequestCounter`.
+/// fn handle_request(counter: &impl RequestCounter) {
+///     counter.record_request();
+/// }
+///
+/// handle_request(&Requests);
+/// std::thread::spawn(|| handle_request(&Requests)).join().unwrap();
+///
+/// assert_eq!(Requests.total_requests(), 2);
+/// ```
+///
+/// [1]: crate::thread_local_rc
+/// [2]: crate::thread_local_arc
+/// [3]: crate::instances
+/// [4]: crate::StaticInstances::with
+#[macro_export]
+macro_rules! facade {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $facade:ident => $static:path : $t:ty;
+
+        $(
+            impl $trait:path {
+                $(
+                    $(#[$method_attr:meta])*
+                    fn $method:ident(&self $(, $arg:ident : $arg_ty:ty)* $(,)?) $(-> $ret:ty)?;
+                )*
+            }
+        )*
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default)]
+        $vis struct $facade;
+
+        $(
+            impl $trait for $facade {
+                $(
+                    $(#[$method_attr])*
+                    #[inline]
+                    fn $method(&self $(, $arg: $arg_ty)*) $(-> $ret)? {
+                        $static.with(|instance| <$t as $trait>::$method(instance $(, $arg)*))
+                    }
+                )*
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::Arc;
+    use std::sync::atomic::{self, AtomicUsize};
+    use std::thread;
+
+    trait Counter {
+        fn increment(&self, amount: usize);
+        fn local_value(&self) -> usize;
+        fn shared_value(&self) -> usize;
+    }
+
+    trait Named {
+        fn name(&self) -> String;
+    }
+
+    #[linked::object]
+    struct LocalCounter {
+        shared_value: Arc<AtomicUsize>,
+        local_value: Cell<usize>,
+    }
+
+    impl LocalCounter {
+        fn new() -> Self {
+            let shared_value = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                shared_value: Arc::clone(&shared_value),
+                local_value: Cell::new(0),
+            })
+        }
+    }
+
+    impl Counter for LocalCounter {
+        fn increment(&self, amount: usize) {
+            self.local_value
+                .set(self.local_value.get().wrapping_add(amount));
+            self.shared_value
+                .fetch_add(amount, atomic::Ordering::Relaxed);
+        }
+
+        fn local_value(&self) -> usize {
+            self.local_value.get()
+        }
+
+        fn shared_value(&self) -> usize {
+            self.shared_value.load(atomic::Ordering::Relaxed)
+        }
+    }
+
+    impl Named for LocalCounter {
+        fn name(&self) -> String {
+            "local".to_string()
+        }
+    }
+
+    linked::thread_local_rc!(static LOCAL_COUNTER: LocalCounter = LocalCounter::new());
+
+    linked::facade! {
+        struct LocalCounterFacade => LOCAL_COUNTER: LocalCounter;
+
+        impl Counter {
+            fn increment(&self, amount: usize);
+            fn local_value(&self) -> usize;
+            fn shared_value(&self) -> usize;
+        }
+
+        impl Named {
+            fn name(&self) -> String;
+        }
+    }
+
+    #[test]
+    fn delegates_to_current_thread_instance() {
+        let facade = LocalCounterFacade;
+        facade.increment(2);
+
+        thread::spawn(move || {
+            facade.increment(3);
+            assert_eq!(facade.local_value(), 3);
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(facade.local_value(), 2);
+        assert_eq!(facade.shared_value(), 5);
+        assert_eq!(facade.name(), "local");
+    }
+
+    #[linked::object]
+    struct SyncCounter {
+        shared_value: Arc<AtomicUsize>,
+    }
+
+    impl SyncCounter {
+        fn new() -> Self {
+            let shared_value = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                shared_value: Arc::clone(&shared_value),
+            })
+        }
+    }
+
+    impl Counter for SyncCounter {
+        fn increment(&self, amount: usize) {
+            self.shared_value
+                .fetch_add(amount, atomic::Ordering::Relaxed);
+        }
+
+        fn local_value(&self) -> usize {
+            0
+        }
+
+        fn shared_value(&self) -> usize {
+            self.shared_value.load(atomic::Ordering::Relaxed)
+        }
+    }
+
+    linked::thread_local_arc!(static SYNC_COUNTER: SyncCounter = SyncCounter::new());
+
+    linked::facade! {
+        struct SyncCounterFacade => SYNC_COUNTER: SyncCounter;
+
+        impl Counter {
+            fn increment(&self, amount: usize);
+            fn local_value(&self) -> usize;
+            fn shared_value(&self) -> usize;
+        }
+    }
+
+    #[test]
+    fn works_with_thread_local_arc() {
+        let counter: &dyn Counter = &SyncCounterFacade;
+        counter.increment(1);
+
+        thread::spawn(|| SyncCounterFacade.increment(1))
+            .join()
+            .unwrap();
+
+        assert_eq!(counter.shared_value(), 2);
+    }
+}
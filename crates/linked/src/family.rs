@@ -1,16 +1,33 @@
 // Copyright (c) Microsoft Corporation.
 // Copyright (c) Folo authors.
 
-use std::any::type_name;
+use std::any::{Any, type_name};
+use std::convert::Infallible;
 use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+#[cfg(any(debug_assertions, feature = "family_checks"))]
+use std::panic::Location;
+use std::{mem, ptr};
 
 use crate::__private::{InstanceFactory, Link};
+#[cfg(feature = "thread_tracking")]
+use crate::ThreadTracker;
+use crate::sync::atomic::{self, AtomicBool, AtomicUsize};
+use crate::sync::{Arc, Mutex};
+use crate::{ERR_POISONED_LOCK, FamilyToken, Object, Shared};
 
 /// Represents a family of [linked objects][crate] and allows you to create additional instances
 /// in the same family.
 ///
 /// Clones represent the same family and are functionally equivalent.
 ///
+/// # Identity
+///
+/// Two `Family` values are equal if they represent the same family, regardless of which instance
+/// they were obtained from. This makes it possible to de-duplicate families, e.g. by collecting
+/// them into a `HashSet`.
+///
 /// # When to use this type
 ///
 /// The family is a low-level primitive for creating instances of linked objects. You will need to
@@ -73,6 +90,8 @@ pub struct Family<T> {
     // For the family, we extract the factory from the `Link` because the `Link` is not thread-safe.
     // In other words, a `Link` exists only in interactions with a specific instance of `T`.
     instance_factory: InstanceFactory<T>,
+
+    shared_state: SharedFamilyStateRef,
 }
 
 impl<T> Debug for Family<T> {
@@ -83,23 +102,892 @@ impl<T> Debug for Family<T> {
                 "instance_factory",
                 &format_args!("Arc<dyn Fn(Link<{t}>) -> {t}>", t = type_name::<T>()),
             )
+            .field("instance_count", &self.instance_count())
             .finish()
     }
 }
 
+impl<T> PartialEq for Family<T> {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.shared_state.as_ptr(), other.shared_state.as_ptr())
+    }
+}
+
+impl<T> Eq for Family<T> {}
+
+impl<T> Hash for Family<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.shared_state.as_ptr(), state);
+    }
+}
+
 impl<T> Family<T> {
+    /// Creates a new family that does not yet have any instances.
+    ///
+    /// If family checks are enabled, the caller is recorded as the creation site of the family.
     #[must_use]
-    pub(super) fn new(link: Link<T>) -> Self {
+    #[track_caller]
+    pub(super) fn new(instance_factory: InstanceFactory<T>) -> Self {
+        let shared_state = SharedFamilyState::new();
+
+        #[cfg(any(debug_assertions, feature = "family_checks"))]
+        let shared_state = shared_state.with_creation_site(Location::caller());
+
+        let shared_state = Arc::new(shared_state);
+
+        #[cfg(all(feature = "family_snapshot", not(loom)))]
+        crate::family_snapshot::register_family(&shared_state, type_name::<T>());
+
+        Self::from_parts(instance_factory, SharedFamilyStateRef::Shared(shared_state))
+    }
+
+    #[must_use]
+    pub(super) fn from_parts(
+        instance_factory: InstanceFactory<T>,
+        shared_state: SharedFamilyStateRef,
+    ) -> Self {
         Self {
-            instance_factory: link.instance_factory,
+            instance_factory,
+            shared_state,
         }
     }
 
+    /// Creates an opaque [`FamilyToken`] for the family, which does not expose the type `T` and can
+    /// be redeemed back into a `Family<T>` with a runtime type check.
+    #[must_use]
+    pub fn to_token(&self) -> FamilyToken
+    where
+        T: Object,
+    {
+        FamilyToken::from(self.clone())
+    }
+
+    /// An identifier of the family, for use in diagnostics.
+    ///
+    /// Two `Family` values have the same identifier if they represent the same family. Once a
+    /// family reaches the end of its life, its identifier may be reused by a new family.
+    #[must_use]
+    pub fn id(&self) -> FamilyId {
+        self.shared_state.id()
+    }
+
+    /// Panics if `other` is a different family, describing where both families were created.
+    ///
+    /// `context` describes what the caller was doing that requires the same family.
+    #[cfg(any(
+        debug_assertions,
+        feature = "family_checks",
+        feature = "thread_tracking"
+    ))]
+    #[track_caller]
+    pub(crate) fn assert_same_family(&self, other: &Self, context: &str) {
+        assert!(
+            self == other,
+            "{context} - instances of different families of {} were mixed, one created at {} and the other created at {}",
+            type_name::<T>(),
+            self.shared_state.describe_creation_site(),
+            other.shared_state.describe_creation_site(),
+        );
+    }
+
+    /// The number of instances of the family that currently exist, across all threads.
+    ///
+    /// This is intended for diagnostics. Instances may be created and dropped on other threads
+    /// concurrently, so the value may be outdated by the time you look at it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[linked::object]
+    /// # struct Thing {}
+    /// # impl Thing {
+    /// #     pub fn new() -> Self {
+    /// #         linked::new!(Self {})
+    /// #     }
+    /// # }
+    /// use linked::Object; // This brings .family() into scope.
+    ///
+    /// let thing = Thing::new();
+    /// let family = thing.family();
+    /// assert_eq!(family.instance_count(), 1);
+    ///
+    /// let another_thing: Thing = family.clone().into();
+    /// assert_eq!(family.instance_count(), 2);
+    ///
+    /// drop(thing);
+    /// drop(another_thing);
+    /// assert_eq!(family.instance_count(), 0);
+    /// ```
+    #[must_use]
+    pub fn instance_count(&self) -> usize {
+        self.shared_state.instance_count()
+    }
+
+    /// The highest number of instances of the family that have existed at the same time,
+    /// across all threads.
+    ///
+    /// This is intended for diagnostics, e.g. to detect that a thread leak is causing the number
+    /// of per-thread instances to grow without bound.
+    #[must_use]
+    pub fn peak_instance_count(&self) -> usize {
+        self.shared_state.peak.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Keeps track of the instances of the family on each thread.
+    #[cfg(feature = "thread_tracking")]
+    pub(crate) fn threads(&self) -> &ThreadTracker {
+        self.shared_state.threads()
+    }
+
+    /// Marks all existing instances of the family as stale by incrementing the
+    /// [version][Self::version] of the family.
+    ///
+    /// This is a cheap way to let the instances on every thread learn that state they derived
+    /// from shared state (e.g. a per-thread cache of shared configuration) is outdated, without
+    /// sending anything to the threads. Each instance checks whether it is stale via
+    /// [`Object::is_stale()`] or refreshes its state via [`Object::refresh_if_stale()`] whenever
+    /// convenient, e.g. on every access.
+    ///
+    /// Instances created after the version was bumped are not stale. Bump the version after
+    /// updating the shared state, so that instances that see the new version also see the
+    /// updated state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use linked::Object; // This brings .family() and .refresh_if_stale() into scope.
+    ///
+    /// #[linked::object]
+    /// struct Config {
+    ///     shared: Arc<Mutex<String>>,
+    ///     cached: String,
+    /// }
+    ///
+    /// impl Config {
+    ///     pub fn new(value: &str) -> Self {
+    ///         let shared = Arc::new(Mutex::new(value.to_string()));
+    ///
+    ///         linked::new!(Self {
+    ///             shared: Arc::clone(&shared),
+    ///             cached: shared.lock().unwrap().clone(),
+    ///         })
+    ///     }
+    ///
+    ///     pub fn update(&self, value: &str) {
+    ///         *self.shared.lock().unwrap() = value.to_string();
+    ///         self.family().bump_version();
+    ///     }
+    ///
+    ///     pub fn value(&mut self) -> &str {
+    ///         self.refresh_if_stale(|config| {
+    ///             config.cached = config.shared.lock().unwrap().clone();
+    ///         });
+    ///
+    ///         &self.cached
+    ///     }
+    /// }
+    ///
+    /// let mut config = Config::new("old");
+    /// let mut other = config.clone();
+    ///
+    /// config.update("new");
+    ///
+    /// assert!(other.is_stale());
+    /// assert_eq!(other.value(), "new");
+    /// assert!(!other.is_stale());
+    /// ```
+    pub fn bump_version(&self) {
+        self.shared_state
+            .version
+            .fetch_add(1, atomic::Ordering::Release);
+    }
+
+    /// The current version of the family, incremented by every call to
+    /// [`bump_version()`][Self::bump_version].
+    ///
+    /// The version of a new family is zero.
+    #[must_use]
+    pub fn version(&self) -> usize {
+        self.shared_state.version()
+    }
+
+    /// Registers a callback that is called once the family reaches the end of its life, after the
+    /// last instance of the family has been dropped.
+    ///
+    /// Any [`Family`] can be used to create new instances, so the family only reaches the end of
+    /// its life when no instances and no `Family` handles remain (including the one used to
+    /// register the callback). Note that wrappers like [`InstancePerThread<T>`][1] and the
+    /// [`linked::instances!`][2] family of macros hold on to a `Family` internally.
+    ///
+    /// The callback is called exactly once, on the thread that drops the last instance
+    /// or `Family` handle. This makes it a suitable place to flush shared buffers or otherwise
+    /// finalize state shared by the family.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[linked::object]
+    /// # struct Thing {}
+    /// # impl Thing {
+    /// #     pub fn new() -> Self {
+    /// #         linked::new!(Self {})
+    /// #     }
+    /// # }
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// use linked::Object; // This brings .family() into scope.
+    ///
+    /// let flushed = Arc::new(AtomicBool::new(false));
+    ///
+    /// let thing = Thing::new();
+    /// thing.family().on_last_instance_dropped({
+    ///     let flushed = Arc::clone(&flushed);
+    ///     move || flushed.store(true, Ordering::Relaxed)
+    /// });
+    ///
+    /// let clone = thing.clone();
+    ///
+    /// drop(thing);
+    /// assert!(!flushed.load(Ordering::Relaxed));
+    ///
+    /// drop(clone);
+    /// assert!(flushed.load(Ordering::Relaxed));
+    /// ```
+    ///
+    /// [1]: crate::InstancePerThread
+    /// [2]: crate::instances
+    pub fn on_last_instance_dropped(&self, callback: impl FnOnce() + Send + 'static) {
+        self.shared_state
+            .on_last_instance_dropped
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .push(Box::new(callback));
+    }
+
+    /// Replaces the template of the family, so that all instances created from now on capture
+    /// the state captured by `new_template` instead of the state captured by the original
+    /// template of the family.
+    ///
+    /// `new_template` is an instance of a new family created via [`linked::new!`][1] (typically
+    /// by calling the same constructor that created the original family), whose template is
+    /// adopted by this family. The new family is discarded, together with `new_template`. This
+    /// allows state embedded in the template, such as credentials, to be rotated without
+    /// creating a new family and migrating every consumer to it.
+    ///
+    /// The replacement applies to every way of creating new instances of the family, including
+    /// cloning existing instances and obtaining instances from [`linked::instances!`][2] static
+    /// variables. Existing instances are not affected - to also update them, follow up with
+    /// `broadcast()` (requires the `thread_tracking` Cargo feature). Fields marked with `#[linked(shared)]` belong to the
+    /// family, not the template, so they keep their values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use linked::Object; // This brings .family() into scope.
+    ///
+    /// #[linked::object]
+    /// struct ApiClient {
+    ///     credentials: Arc<String>,
+    /// }
+    ///
+    /// impl ApiClient {
+    ///     pub fn new(credentials: &str) -> Self {
+    ///         let credentials = Arc::new(credentials.to_string());
+    ///
+    ///         linked::new!(Self {
+    ///             credentials: Arc::clone(&credentials),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let client = ApiClient::new("token-1");
+    ///
+    /// // Credential rotation - instances created from now on use the new credentials.
+    /// client.family().replace_template(ApiClient::new("token-2"));
+    ///
+    /// let new_client = client.clone();
+    /// assert_eq!(*new_client.credentials, "token-2");
+    /// assert!(new_client.family() == client.family());
+    ///
+    /// // Existing instances keep the credentials they were created with.
+    /// assert_eq!(*client.credentials, "token-1");
+    /// ```
+    ///
+    /// [1]: crate::new
+    /// [2]: crate::instances
+    pub fn replace_template(&self, new_template: T)
+    where
+        T: Object,
+    {
+        let instance_factory = new_template.family().instance_factory;
+        drop(new_template);
+
+        self.shared_state
+            .replace_template(Box::new(instance_factory));
+    }
+
     // Implementation of `From<Family<T>> for T`, called from macro-generated code for a specific T.
     #[doc(hidden)]
     #[inline]
     #[must_use]
-    pub fn __private_into(self) -> T {
-        Link::new(self.instance_factory).into_instance()
+    pub fn __private_into(self) -> T
+    where
+        T: Object,
+    {
+        let instance_factory = self
+            .shared_state
+            .replaced_template()
+            .unwrap_or(self.instance_factory);
+
+        let mut instance = Link::new(instance_factory, self.shared_state).into_instance();
+        instance.__private_on_instance_created();
+        instance
+    }
+
+    /// Creates an instance via a fallible template instead of the instance factory of the family,
+    /// for creating the first instance of a family via `linked::new_try!`.
+    pub(crate) fn try_into_instance<E>(
+        self,
+        template: impl FnOnce(Link<T>) -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        T: Object,
+    {
+        let mut instance = template(Link::new(self.instance_factory, self.shared_state))?;
+        instance.__private_on_instance_created();
+        Ok(instance)
+    }
+}
+
+/// Identifies a family of [linked objects][crate] for diagnostic purposes.
+///
+/// Obtained via [`Family::id()`]. Once a family reaches the end of its life, its identifier
+/// may be reused by a new family.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FamilyId(usize);
+
+/// State shared between the family and all its instances, dropped once the family reaches the
+/// end of its life (i.e. there are no more instances or `Family` handles).
+pub(crate) struct SharedFamilyState {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+
+    #[cfg(feature = "thread_tracking")]
+    threads: ThreadTracker,
+
+    // Values of fields marked with `#[linked(shared)]`, keyed by field name. Values inside are
+    // type-occluded `Shared<V>` where V may be different for each entry.
+    shared_fields: Mutex<Vec<(&'static str, Box<dyn Any + Send + Sync>)>>,
+
+    on_last_instance_dropped: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+
+    // Incremented by `Family::bump_version()`, which lets each instance detect that it is stale
+    // by comparing this with the version it last observed.
+    version: AtomicUsize,
+
+    // The template that replaced the original template of the family, if any. The value inside
+    // is a type-occluded `InstanceFactory<T>`.
+    replaced_template: Mutex<Option<Box<dyn Any + Send + Sync>>>,
+
+    // Whether `replaced_template` is set, so creating instances of families whose template was
+    // never replaced does not need to take the lock.
+    has_replaced_template: AtomicBool,
+
+    // Where the family was created, for diagnosing the mixing of instances of different
+    // families. This is unknown for static families, whose state is created in a const context.
+    #[cfg(any(debug_assertions, feature = "family_checks"))]
+    creation_site: Option<&'static Location<'static>>,
+}
+
+impl SharedFamilyState {
+    // This is `const` so that families can be defined in `static` variables, which is not
+    // possible with the loom primitives we use when model-checking.
+    #[cfg(not(loom))]
+    #[must_use]
+    pub(crate) const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            #[cfg(feature = "thread_tracking")]
+            threads: ThreadTracker::new(),
+            shared_fields: Mutex::new(Vec::new()),
+            on_last_instance_dropped: Mutex::new(Vec::new()),
+            version: AtomicUsize::new(0),
+            replaced_template: Mutex::new(None),
+            has_replaced_template: AtomicBool::new(false),
+            #[cfg(any(debug_assertions, feature = "family_checks"))]
+            creation_site: None,
+        }
+    }
+
+    #[cfg(loom)]
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            #[cfg(feature = "thread_tracking")]
+            threads: ThreadTracker::new(),
+            shared_fields: Mutex::new(Vec::new()),
+            on_last_instance_dropped: Mutex::new(Vec::new()),
+            version: AtomicUsize::new(0),
+            replaced_template: Mutex::new(None),
+            has_replaced_template: AtomicBool::new(false),
+            #[cfg(any(debug_assertions, feature = "family_checks"))]
+            creation_site: None,
+        }
+    }
+
+    #[cfg(any(debug_assertions, feature = "family_checks"))]
+    #[must_use]
+    fn with_creation_site(mut self, creation_site: &'static Location<'static>) -> Self {
+        self.creation_site = Some(creation_site);
+        self
+    }
+
+    /// Where the family was created, as far as we know, in a form suitable for error messages.
+    #[cfg(any(debug_assertions, feature = "family_checks"))]
+    fn describe_creation_site(&self) -> String {
+        self.creation_site.map_or_else(
+            || "an unknown location (static family)".to_string(),
+            ToString::to_string,
+        )
+    }
+
+    #[cfg(all(
+        not(any(debug_assertions, feature = "family_checks")),
+        feature = "thread_tracking"
+    ))]
+    #[expect(
+        clippy::unused_self,
+        reason = "same signature as when creation sites are tracked"
+    )]
+    fn describe_creation_site(&self) -> String {
+        "an unknown location (creation sites are only tracked in debug builds or with the `family_checks` feature)".to_string()
+    }
+
+    /// The identifier of the family, derived from the address of its shared state.
+    pub(crate) fn id(&self) -> FamilyId {
+        FamilyId(ptr::from_ref(self).addr())
+    }
+
+    /// The number of instances of the family that currently exist, across all threads.
+    pub(crate) fn instance_count(&self) -> usize {
+        self.current.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Keeps track of the instances of the family on each thread.
+    #[cfg(feature = "thread_tracking")]
+    pub(crate) fn threads(&self) -> &ThreadTracker {
+        &self.threads
+    }
+
+    /// The current version of the family, as incremented by `Family::bump_version()`.
+    #[inline]
+    pub(crate) fn version(&self) -> usize {
+        self.version.load(atomic::Ordering::Acquire)
+    }
+
+    /// Counts a new instance of the family.
+    ///
+    /// With the `thread_tracking` Cargo feature, the `Link` of the instance also counts it
+    /// against its thread via the `ThreadTracker` of the family.
+    pub(crate) fn increment(&self) {
+        let previous = self.current.fetch_add(1, atomic::Ordering::Relaxed);
+        let current = previous
+            .checked_add(1)
+            .expect("cannot have more instances than fit in memory");
+
+        // The peak rarely changes, so we avoid the read-modify-write operation if we can.
+        if current > self.peak.load(atomic::Ordering::Relaxed) {
+            self.peak.fetch_max(current, atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Replaces the template of the family with `template`, a type-occluded `InstanceFactory<T>`.
+    pub(crate) fn replace_template(&self, template: Box<dyn Any + Send + Sync>) {
+        let previous = self
+            .replaced_template
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .replace(template);
+
+        self.has_replaced_template
+            .store(true, atomic::Ordering::Release);
+
+        // Dropping the previous template drops the state it captured, which may execute arbitrary
+        // code, so we do it outside the lock.
+        drop(previous);
+    }
+
+    /// Returns the template that replaced the original template of the family, if any.
+    #[inline]
+    pub(crate) fn replaced_template<T>(&self) -> Option<InstanceFactory<T>>
+    where
+        T: 'static,
+    {
+        if !self.has_replaced_template.load(atomic::Ordering::Acquire) {
+            return None;
+        }
+
+        self.replaced_template
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .as_ref()
+            .map(|template| {
+                template
+                    .downcast_ref::<InstanceFactory<T>>()
+                    .expect("the template of a family of T is always an instance factory of T")
+                    .clone()
+            })
+    }
+
+    /// Returns the value of the shared field `name`, creating it via `create_value()` if this
+    /// is the first instance of the family to access the field.
+    pub(crate) fn shared_field<V>(
+        &self,
+        name: &'static str,
+        create_value: impl FnOnce() -> V,
+    ) -> Shared<V>
+    where
+        V: Send + Sync + 'static,
+    {
+        let Ok(value) = self.try_shared_field(name, || Ok::<_, Infallible>(create_value()));
+        value
+    }
+
+    /// Returns the value of the shared field `name`, creating it via `create_value()` if this
+    /// is the first instance of the family to access the field. If creating the value fails,
+    /// the field remains unset, so the next instance to access the field tries again.
+    pub(crate) fn try_shared_field<V, E>(
+        &self,
+        name: &'static str,
+        create_value: impl FnOnce() -> Result<V, E>,
+    ) -> Result<Shared<V>, E>
+    where
+        V: Send + Sync + 'static,
+    {
+        if let Some(value) = self.find_shared_field(name) {
+            return Ok(value);
+        }
+
+        // We create the value outside the lock because creating it may execute arbitrary code,
+        // including code that creates more instances of the same family.
+        let value = Shared::new(create_value()?);
+
+        let mut shared_fields = self.shared_fields.lock().expect(ERR_POISONED_LOCK);
+
+        // Someone else may have been faster - if so, we use theirs and drop ours.
+        if let Some(existing) = Self::find_shared_field_in(&shared_fields, name) {
+            return Ok(existing);
+        }
+
+        shared_fields.push((name, Box::new(value.clone())));
+        Ok(value)
+    }
+
+    fn find_shared_field<V>(&self, name: &'static str) -> Option<Shared<V>>
+    where
+        V: Send + Sync + 'static,
+    {
+        Self::find_shared_field_in(&self.shared_fields.lock().expect(ERR_POISONED_LOCK), name)
+    }
+
+    fn find_shared_field_in<V>(
+        shared_fields: &[(&'static str, Box<dyn Any + Send + Sync>)],
+        name: &'static str,
+    ) -> Option<Shared<V>>
+    where
+        V: Send + Sync + 'static,
+    {
+        shared_fields
+            .iter()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, value)| {
+                value
+                    .downcast_ref::<Shared<V>>()
+                    .expect("the type of a field is fixed by the struct definition")
+                    .clone()
+            })
+    }
+
+    /// Counts out an instance of the family that was counted via `increment()`.
+    pub(crate) fn decrement(&self) {
+        self.current.fetch_sub(1, atomic::Ordering::Relaxed);
+    }
+}
+
+/// A reference to the state shared by a family, which is either reference-counted (for families
+/// created at runtime) or lives in a `static` variable (for families defined at compile time).
+#[derive(Clone)]
+pub(crate) enum SharedFamilyStateRef {
+    Shared(Arc<SharedFamilyState>),
+
+    #[cfg(not(loom))]
+    Static(&'static SharedFamilyState),
+}
+
+impl SharedFamilyStateRef {
+    fn as_ptr(&self) -> *const SharedFamilyState {
+        &**self
+    }
+
+    pub(crate) fn id(&self) -> FamilyId {
+        (**self).id()
+    }
+}
+
+impl Deref for SharedFamilyStateRef {
+    type Target = SharedFamilyState;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Shared(state) => state,
+            #[cfg(not(loom))]
+            Self::Static(state) => state,
+        }
+    }
+}
+
+impl Drop for SharedFamilyState {
+    fn drop(&mut self) {
+        #[cfg(all(feature = "family_snapshot", not(loom)))]
+        crate::family_snapshot::unregister_family(self.id());
+
+        let callbacks = mem::take(
+            self.on_last_instance_dropped
+                .get_mut()
+                .expect(ERR_POISONED_LOCK),
+        );
+
+        for callback in callbacks {
+            callback();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    use super::*;
+    use crate::Object;
+
+    #[linked::object]
+    struct Thing {}
+
+    impl Thing {
+        fn new() -> Self {
+            linked::new!(Self {})
+        }
+    }
+
+    #[test]
+    fn equal_if_same_family() {
+        let thing = Thing::new();
+        let clone: Thing = thing.family().into();
+        let other_thing = Thing::new();
+
+        assert_eq!(thing.family(), clone.family());
+        assert_ne!(thing.family(), other_thing.family());
+
+        assert!(thing.is_same_family(&clone));
+        assert!(!thing.is_same_family(&other_thing));
+
+        #[expect(
+            clippy::mutable_key_type,
+            reason = "the hash is based on the identity of the family, not its mutable state"
+        )]
+        let families = [thing.family(), clone.family(), other_thing.family()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(families.len(), 2);
+    }
+
+    #[test]
+    fn counts_instances() {
+        let thing = Thing::new();
+        let family = thing.family();
+
+        assert_eq!(family.instance_count(), 1);
+        assert_eq!(family.peak_instance_count(), 1);
+
+        let clone = thing.clone();
+        assert_eq!(family.instance_count(), 2);
+        assert_eq!(family.peak_instance_count(), 2);
+
+        drop(clone);
+        assert_eq!(family.instance_count(), 1);
+        assert_eq!(family.peak_instance_count(), 2);
+
+        drop(thing);
+        assert_eq!(family.instance_count(), 0);
+        assert_eq!(family.peak_instance_count(), 2);
+    }
+
+    #[test]
+    fn on_last_instance_dropped_called_once_at_end_of_life() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let thing = Thing::new();
+        let family = thing.family();
+
+        family.on_last_instance_dropped({
+            let calls = Arc::clone(&calls);
+            move || {
+                calls.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+        });
+
+        let remote_thing = thread::spawn({
+            let family = family.clone();
+            move || -> Thing { family.into() }
+        });
+
+        drop(thing);
+        drop(remote_thing.join().unwrap());
+
+        // The family handle can still be used to create new instances.
+        assert_eq!(calls.load(atomic::Ordering::Relaxed), 0);
+
+        let thing: Thing = family.into();
+        assert_eq!(calls.load(atomic::Ordering::Relaxed), 0);
+
+        drop(thing);
+        assert_eq!(calls.load(atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[expect(
+        clippy::redundant_clone,
+        reason = "cloning creates an instance from the current template, which we are testing"
+    )]
+    fn replaced_template_used_by_new_instances() {
+        #[linked::object]
+        struct Client {
+            token: Arc<str>,
+        }
+
+        impl Client {
+            fn new(token: &str) -> Self {
+                let token: Arc<str> = Arc::from(token);
+
+                linked::new!(Self {
+                    token: Arc::clone(&token),
+                })
+            }
+        }
+
+        let client = Client::new("first");
+        let family = client.family();
+
+        family.replace_template(Client::new("second"));
+
+        // The discarded family of the new template is not counted in our family.
+        assert_eq!(family.instance_count(), 1);
+
+        let clone = client.clone();
+        assert_eq!(&*clone.token, "second");
+
+        // Existing instances are not affected.
+        assert_eq!(&*client.token, "first");
+
+        let from_family = thread::spawn({
+            let family = family.clone();
+            move || -> Client { family.into() }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(&*from_family.token, "second");
+        assert!(from_family.family() == family);
+
+        family.replace_template(Client::new("third"));
+
+        let clone_of_clone = clone.clone();
+        assert_eq!(&*clone_of_clone.token, "third");
+        assert_eq!(&*clone.token, "second");
+        assert_eq!(family.instance_count(), 4);
+    }
+
+    #[test]
+    fn bump_version_marks_existing_instances_stale() {
+        #[linked::object]
+        struct Cache {
+            refresh_count: usize,
+        }
+
+        impl Cache {
+            fn new() -> Self {
+                linked::new!(Self { refresh_count: 0 })
+            }
+
+            fn refresh(&mut self) {
+                self.refresh_count = self.refresh_count.wrapping_add(1);
+            }
+        }
+
+        let mut cache = Cache::new();
+        let family = cache.family();
+
+        assert_eq!(family.version(), 0);
+        assert!(!cache.is_stale());
+        assert!(!cache.refresh_if_stale(Cache::refresh));
+
+        family.bump_version();
+        assert_eq!(family.version(), 1);
+
+        let mut other = thread::spawn({
+            let family = family.clone();
+            move || -> Cache { family.into() }
+        })
+        .join()
+        .unwrap();
+
+        // Instances created after the bump are up to date.
+        assert!(cache.is_stale());
+        assert!(!other.is_stale());
+
+        assert!(cache.refresh_if_stale(Cache::refresh));
+        assert!(!cache.is_stale());
+        assert!(!cache.refresh_if_stale(Cache::refresh));
+        assert_eq!(cache.refresh_count, 1);
+
+        // Bumping while refreshing leaves the instance stale for the next refresh.
+        family.bump_version();
+        assert!(other.refresh_if_stale(|other| {
+            other.family().bump_version();
+            other.refresh();
+        }));
+        assert_eq!(family.version(), 3);
+        assert!(other.is_stale());
+        assert!(cache.is_stale());
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "family_checks"))]
+    fn mixed_families_report_creation_sites() {
+        let thing = Thing::new();
+        let other_thing = Thing::new();
+
+        linked::debug_assert_same_family(&thing, &thing.clone());
+
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            linked::debug_assert_same_family(&thing, &other_thing);
+        }))
+        .unwrap_err();
+
+        let message = panic.downcast_ref::<String>().unwrap();
+
+        // Both families were created by `Thing::new()` in this file.
+        assert_eq!(message.matches(file!()).count(), 2, "{message}");
     }
 }
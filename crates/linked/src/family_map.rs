@@ -0,0 +1,304 @@
+use std::{
+    collections::{HashMap, hash_map},
+    fmt::{self, Debug, Formatter},
+    hash::Hash,
+    sync::{Arc, RwLock},
+};
+
+use crate::{ERR_POISONED_LOCK, InstancePerThread, Ref};
+
+/// A map that lazily creates one family of [linked objects][crate] per runtime key (e.g. a tenant
+/// ID or a shard ID) and provides access to the current thread's instance from each family.
+///
+/// This is the keyed counterpart of [`InstancePerThread<T>`][1], for when the set of families
+/// is only known at runtime. The family for a key is created the first time the key is
+/// accessed, by calling the factory function provided when creating the map.
+///
+/// # Usage
+///
+/// Call [`.get(&key)`][Self::get] to obtain a [`Ref<T>`][2] to the current thread's instance
+/// of `T` in the family identified by `key`. The same resource management rules apply as
+/// for [`InstancePerThread<T>`][1] - the thread's instance is dropped when the last `Ref` to
+/// it on that thread is dropped, so keep the `Ref` around for reuse when possible.
+///
+/// Clones of the map share the same families, so you can give each thread its own clone.
+///
+/// # Example
+///
+/// ```
+/// # use std::sync::{Arc, Mutex};
+/// #
+/// # #[linked::object]
+/// # struct TenantQuota {
+/// #     tenant: String,
+/// #     used: Arc<Mutex<usize>>,
+/// # }
+/// #
+/// # impl TenantQuota {
+/// #     pub fn new(tenant: String) -> Self {
+/// #         let used = Arc::new(Mutex::new(0));
+/// #
+/// #         linked::new!(Self {
+/// #             tenant: tenant.clone(),
+/// #             used: Arc::clone(&used),
+/// #         })
+/// #     }
+/// #
+/// #     pub fn consume(&self, amount: usize) {
+/// #         *self.used.lock().unwrap() += amount;
+/// #     }
+/// #
+/// #     pub fn used(&self) -> usize {
+/// #         *self.used.lock().unwrap()
+/// #     }
+/// # }
+/// use linked::FamilyMap;
+///
+/// let quotas = FamilyMap::new(|tenant: &String| TenantQuota::new(tenant.clone()));
+///
+/// quotas.get(&"contoso".to_string()).consume(5);
+///
+/// std::thread::spawn({
+///     let quotas = quotas.clone();
+///
+///     move || {
+///         // Different thread, different instance - but the same family.
+///         quotas.get(&"contoso".to_string()).consume(10);
+///         quotas.get(&"fabrikam".to_string()).consume(1);
+///     }
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert_eq!(quotas.get(&"contoso".to_string()).used(), 15);
+/// assert_eq!(quotas.get(&"fabrikam".to_string()).used(), 1);
+/// ```
+///
+/// [1]: crate::InstancePerThread
+/// [2]: crate::Ref
+pub struct FamilyMap<K, T>
+where
+    T: linked::Object,
+{
+    first_instance_factory: Arc<dyn Fn(&K) -> T + Send + Sync>,
+
+    // The write lock is only held when a new key is first accessed.
+    families: Arc<RwLock<HashMap<K, InstancePerThread<T>>>>,
+}
+
+impl<K, T> FamilyMap<K, T>
+where
+    K: Eq + Hash + Clone,
+    T: linked::Object,
+{
+    /// Creates a new empty map.
+    ///
+    /// The first time a key is accessed, `first_instance_factory` is called to create the first
+    /// instance of the family for that key. All other instances of the family are created from
+    /// that first instance via the regular linked object mechanisms.
+    #[must_use]
+    pub fn new(first_instance_factory: impl Fn(&K) -> T + Send + Sync + 'static) -> Self {
+        Self {
+            first_instance_factory: Arc::new(first_instance_factory),
+            families: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Returns a `Ref<T>` that can be used to access the current thread's instance of `T`
+    /// in the family identified by `key`, creating the family if it does not yet exist.
+    #[must_use]
+    pub fn get(&self, key: &K) -> Ref<T> {
+        if let Some(family) = self.families.read().expect(ERR_POISONED_LOCK).get(key) {
+            return family.acquire();
+        }
+
+        // We create the first instance outside any locks, both to reduce the lock duration
+        // and because creating a linked object may execute arbitrary code, including code that
+        // tries to access the same map.
+        let family = InstancePerThread::new((self.first_instance_factory)(key));
+
+        let family = match self
+            .families
+            .write()
+            .expect(ERR_POISONED_LOCK)
+            .entry(key.clone())
+        {
+            // Someone else got there first - we use theirs and drop ours.
+            hash_map::Entry::Occupied(entry) => entry.get().clone(),
+            hash_map::Entry::Vacant(entry) => entry.insert(family).clone(),
+        };
+
+        family.acquire()
+    }
+
+    /// Removes the family identified by `key` from the map.
+    ///
+    /// Existing instances of the family are not affected. If the key is accessed again, a new
+    /// family is created for it.
+    ///
+    /// Returns whether the key was present in the map.
+    pub fn remove(&self, key: &K) -> bool {
+        self.families
+            .write()
+            .expect(ERR_POISONED_LOCK)
+            .remove(key)
+            .is_some()
+    }
+
+    /// Whether a family has been created for `key`.
+    #[must_use]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.families
+            .read()
+            .expect(ERR_POISONED_LOCK)
+            .contains_key(key)
+    }
+
+    /// The number of families in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.families.read().expect(ERR_POISONED_LOCK).len()
+    }
+
+    /// Whether there are no families in the map.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, T> Clone for FamilyMap<K, T>
+where
+    T: linked::Object,
+{
+    fn clone(&self) -> Self {
+        Self {
+            first_instance_factory: Arc::clone(&self.first_instance_factory),
+            families: Arc::clone(&self.families),
+        }
+    }
+}
+
+impl<K, T> Debug for FamilyMap<K, T>
+where
+    T: linked::Object,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FamilyMap")
+            .field(
+                "families",
+                &self.families.read().expect(ERR_POISONED_LOCK).len(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        sync::{
+            Arc,
+            atomic::{self, AtomicUsize},
+        },
+        thread,
+    };
+
+    use super::*;
+
+    #[linked::object]
+    struct Shard {
+        id: u32,
+        shared_value: Arc<AtomicUsize>,
+        local_value: Cell<usize>,
+    }
+
+    impl Shard {
+        fn new(id: u32) -> Self {
+            let shared_value = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                id,
+                shared_value: Arc::clone(&shared_value),
+                local_value: Cell::new(0),
+            })
+        }
+
+        fn increment(&self) {
+            self.shared_value.fetch_add(1, atomic::Ordering::Relaxed);
+            self.local_value.set(self.local_value.get().wrapping_add(1));
+        }
+
+        fn shared_value(&self) -> usize {
+            self.shared_value.load(atomic::Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn one_family_per_key() {
+        let map = FamilyMap::new(|id: &u32| Shard::new(*id));
+        assert!(map.is_empty());
+
+        let shard1 = map.get(&1);
+        let shard2 = map.get(&2);
+        assert_eq!(map.len(), 2);
+        assert!(map.contains_key(&1));
+        assert!(!map.contains_key(&3));
+
+        assert_eq!(shard1.id, 1);
+        assert_eq!(shard2.id, 2);
+
+        shard1.increment();
+        assert_eq!(shard1.shared_value(), 1);
+        assert_eq!(shard2.shared_value(), 0);
+
+        // Same thread, same key - same instance.
+        let shard1_again = map.get(&1);
+        assert_eq!(shard1_again.local_value.get(), 1);
+    }
+
+    #[test]
+    fn families_shared_across_threads() {
+        let map = FamilyMap::new(|id: &u32| Shard::new(*id));
+
+        let shard = map.get(&7);
+        shard.increment();
+
+        thread::spawn({
+            let map = map.clone();
+
+            move || {
+                let shard = map.get(&7);
+
+                // A different instance on this thread, in the same family.
+                assert_eq!(shard.local_value.get(), 0);
+                assert_eq!(shard.shared_value(), 1);
+
+                shard.increment();
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(shard.shared_value(), 2);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn remove_creates_new_family_on_next_access() {
+        let map = FamilyMap::new(|id: &u32| Shard::new(*id));
+
+        let old = map.get(&1);
+        old.increment();
+
+        assert!(map.remove(&1));
+        assert!(!map.remove(&1));
+
+        let new = map.get(&1);
+        assert_eq!(new.shared_value(), 0);
+
+        // Existing instances of the removed family keep working.
+        assert_eq!(old.shared_value(), 1);
+    }
+}
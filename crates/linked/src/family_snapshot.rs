@@ -0,0 +1,335 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::thread::ThreadId;
+
+use crate::{ERR_POISONED_LOCK, FamilyId, SharedFamilyState};
+
+/// Takes a snapshot of all families of [linked objects][crate] that currently exist in the
+/// process, for diagnosing which families are alive and what keeps them alive.
+///
+/// The snapshot describes each family with its type, its instances per thread, the number of
+/// other references (e.g. [`Family`][crate::Family] handles) that keep it alive and where it was
+/// created. The [`Display`] implementation of the snapshot renders a report for humans.
+///
+/// Families defined via [`linked::new_static!`][crate::new_static] live forever and are not
+/// included.
+///
+/// This is only available with the `family_snapshot` Cargo feature. Without the feature, there
+/// is no overhead from keeping track of families.
+///
+/// # Creation backtraces
+///
+/// The backtrace of the code that created each family is captured via
+/// [`Backtrace::capture()`], so it is only available if backtraces are enabled via the
+/// `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables. Capturing backtraces makes
+/// creating families considerably more expensive.
+///
+/// # Example
+///
+/// ```
+/// use linked::Object;
+///
+/// #[linked::object]
+/// struct Thing {}
+///
+/// impl Thing {
+///     pub fn new() -> Self {
+///         linked::new!(Self {})
+///     }
+/// }
+///
+/// let thing = Thing::new();
+/// let family = thing.family();
+///
+/// let snapshot = linked::snapshot_families();
+///
+/// let report = snapshot
+///     .families()
+///     .iter()
+///     .find(|report| report.id() == family.id())
+///     .unwrap();
+///
+/// assert_eq!(report.instance_count(), 1);
+/// assert_eq!(report.other_reference_count(), 1);
+///
+/// println!("{snapshot}");
+/// ```
+#[must_use]
+pub fn snapshot_families() -> FamiliesSnapshot {
+    // We must not drop the last reference to a family while holding the lock, as dropping the
+    // family unregisters it, so we collect the families first and only then inspect them.
+    let families = {
+        let registered = FAMILIES.lock().expect(ERR_POISONED_LOCK);
+
+        registered
+            .values()
+            .filter_map(|family| {
+                Some((
+                    family.state.upgrade()?,
+                    family.type_name,
+                    Arc::clone(&family.creation_backtrace),
+                ))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut families = families
+        .into_iter()
+        .map(|(state, type_name, creation_backtrace)| {
+            let instance_count = state.instance_count();
+
+            // One of the references is our own.
+            let other_reference_count = Arc::strong_count(&state)
+                .saturating_sub(1)
+                .saturating_sub(instance_count);
+
+            FamilySnapshot {
+                id: state.id(),
+                type_name,
+                instance_count,
+                instance_counts_per_thread: state.threads().instance_counts(),
+                other_reference_count,
+                creation_backtrace,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    families.sort_by_key(|family| (family.type_name, family.id));
+
+    FamiliesSnapshot { families }
+}
+
+/// A snapshot of all families of [linked objects][crate] in the process, taken via
+/// [`snapshot_families()`].
+#[derive(Debug)]
+pub struct FamiliesSnapshot {
+    families: Vec<FamilySnapshot>,
+}
+
+impl FamiliesSnapshot {
+    /// The families that existed when the snapshot was taken, ordered by type name.
+    #[must_use]
+    pub fn families(&self) -> &[FamilySnapshot] {
+        &self.families
+    }
+}
+
+impl Display for FamiliesSnapshot {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} linked object families", self.families.len())?;
+
+        for family in &self.families {
+            writeln!(f)?;
+            write!(f, "{family}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes one family of [linked objects][crate] in a [`FamiliesSnapshot`].
+///
+/// The values are read from the family one after another while other threads may be creating
+/// and dropping instances, so they may not be consistent with each other.
+#[derive(Debug)]
+pub struct FamilySnapshot {
+    id: FamilyId,
+    type_name: &'static str,
+    instance_count: usize,
+    instance_counts_per_thread: HashMap<ThreadId, usize>,
+    other_reference_count: usize,
+    creation_backtrace: Arc<Backtrace>,
+}
+
+impl FamilySnapshot {
+    /// The identifier of the family, as returned by [`Family::id()`][crate::Family::id].
+    #[must_use]
+    pub fn id(&self) -> FamilyId {
+        self.id
+    }
+
+    /// The name of the linked object type, as returned by [`std::any::type_name()`].
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The number of instances of the family, across all threads.
+    #[must_use]
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// The number of instances of the family, grouped by the thread that created them, as
+    /// returned by [`Family::instance_counts_per_thread()`][1].
+    ///
+    /// [1]: crate::Family::instance_counts_per_thread
+    #[must_use]
+    pub fn instance_counts_per_thread(&self) -> &HashMap<ThreadId, usize> {
+        &self.instance_counts_per_thread
+    }
+
+    /// The number of references other than instances that keep the family alive, such as
+    /// [`Family`][crate::Family] handles, including those held by static variables and by
+    /// wrappers like [`InstancePerThread<T>`][crate::InstancePerThread].
+    ///
+    /// A family with no instances is only alive because of these references.
+    #[must_use]
+    pub fn other_reference_count(&self) -> usize {
+        self.other_reference_count
+    }
+
+    /// The backtrace of the code that created the family.
+    ///
+    /// See [`snapshot_families()`] for when the backtrace is captured.
+    pub fn creation_backtrace(&self) -> &Backtrace {
+        &self.creation_backtrace
+    }
+}
+
+impl Display for FamilySnapshot {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} ({:?}): {} instances, {} other references",
+            self.type_name, self.id, self.instance_count, self.other_reference_count
+        )?;
+
+        let mut per_thread = self.instance_counts_per_thread.iter().collect::<Vec<_>>();
+        per_thread.sort_by_key(|(thread_id, _)| format!("{thread_id:?}"));
+
+        for (thread_id, instance_count) in per_thread {
+            writeln!(f, "    {thread_id:?}: {instance_count} instances")?;
+        }
+
+        if self.creation_backtrace.status() == BacktraceStatus::Captured {
+            writeln!(f, "    created at:")?;
+            writeln!(f, "{}", self.creation_backtrace)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Registers a newly created family, so it is included in snapshots until it is dropped.
+pub(crate) fn register_family(state: &Arc<SharedFamilyState>, type_name: &'static str) {
+    let family = RegisteredFamily {
+        state: Arc::downgrade(state),
+        type_name,
+        creation_backtrace: Arc::new(Backtrace::capture()),
+    };
+
+    FAMILIES
+        .lock()
+        .expect(ERR_POISONED_LOCK)
+        .insert(state.id(), family);
+}
+
+/// Removes a family that is being dropped from the snapshots.
+pub(crate) fn unregister_family(id: FamilyId) {
+    FAMILIES.lock().expect(ERR_POISONED_LOCK).remove(&id);
+}
+
+struct RegisteredFamily {
+    state: Weak<SharedFamilyState>,
+    type_name: &'static str,
+    creation_backtrace: Arc<Backtrace>,
+}
+
+// Families are registered when created at runtime and unregistered when dropped, so every entry
+// refers to a family that is alive or is being dropped (in which case it cannot be upgraded).
+static FAMILIES: LazyLock<Mutex<HashMap<FamilyId, RegisteredFamily>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{Family, Object};
+
+    #[linked::object]
+    struct Thing {}
+
+    impl Thing {
+        fn new() -> Self {
+            linked::new!(Self {})
+        }
+    }
+
+    // Other tests may create families concurrently, so we only look at our own family.
+    fn find(family: &Family<Thing>) -> Option<FamilySnapshot> {
+        snapshot_families()
+            .families
+            .into_iter()
+            .find(|snapshot| snapshot.id() == family.id())
+    }
+
+    #[test]
+    fn snapshot_describes_family() {
+        let thing = Thing::new();
+        let family = thing.family();
+
+        let (remote_thread_id, _remote) = thread::spawn({
+            let family = family.clone();
+            move || (thread::current().id(), Thing::from(family))
+        })
+        .join()
+        .unwrap();
+
+        let snapshot = find(&family).unwrap();
+
+        assert_eq!(snapshot.type_name(), std::any::type_name::<Thing>());
+        assert_eq!(snapshot.instance_count(), 2);
+        assert_eq!(snapshot.other_reference_count(), 1);
+        assert_eq!(
+            snapshot.instance_counts_per_thread(),
+            &HashMap::from([(thread::current().id(), 1), (remote_thread_id, 1)])
+        );
+
+        drop(thing);
+
+        let snapshot = find(&family).unwrap();
+        assert_eq!(snapshot.instance_count(), 1);
+    }
+
+    #[test]
+    fn dropped_family_not_in_snapshot() {
+        // The identifier of a dropped family may be reused by a family created by another test,
+        // so we look for families of a type that only this test uses.
+        #[linked::object]
+        struct Dropped {}
+
+        impl Dropped {
+            fn new() -> Self {
+                linked::new!(Self {})
+            }
+        }
+
+        let dropped = Dropped::new();
+        let family = dropped.family();
+
+        drop(dropped);
+
+        // The family handle keeps the family alive.
+        let snapshot = snapshot_families();
+        let mut families = snapshot
+            .families()
+            .iter()
+            .filter(|snapshot| snapshot.type_name() == std::any::type_name::<Dropped>());
+        assert_eq!(families.next().unwrap().instance_count(), 0);
+
+        drop(family);
+
+        assert!(
+            snapshot_families()
+                .families()
+                .iter()
+                .all(|snapshot| snapshot.type_name() != std::any::type_name::<Dropped>())
+        );
+    }
+}
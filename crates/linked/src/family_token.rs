@@ -0,0 +1,253 @@
+use std::any::{Any, type_name};
+use std::ffi::c_void;
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{Family, FamilyId, Object};
+
+/// An opaque handle to a family of [linked objects][crate] that does not expose the type of the
+/// linked object, redeemable back into a typed [`Family<T>`] with a runtime type check.
+///
+/// This allows families to be passed through code that cannot be generic over the linked object
+/// type, such as type-erased plugin registries or the boundary between a plugin host and
+/// dynamically loaded plugins. The token can be redeemed via [`redeem()`][Self::redeem] by any
+/// code that knows the type of the linked object, with redemption failing if the token is for a
+/// family of a different type.
+///
+/// Like a `Family<T>`, the token keeps the family alive and can be cloned freely.
+///
+/// # Dynamic library boundaries
+///
+/// A token can be converted into a raw pointer via [`into_raw()`][Self::into_raw] to pass it
+/// through a C-compatible interface and converted back via [`from_raw()`][Self::from_raw] on
+/// the other side. The runtime type check relies on [`TypeId`][std::any::TypeId], so the plugin
+/// host and the plugins must be built with the same compiler and the same version of `linked`
+/// and of the crate that defines the linked object type.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use linked::{FamilyToken, Object};
+///
+/// #[linked::object]
+/// struct AuditLog {}
+///
+/// #[linked::object]
+/// struct MetricsSink {}
+///
+/// impl AuditLog {
+///     pub fn new() -> Self {
+///         linked::new!(Self {})
+///     }
+/// }
+///
+/// // The plugin host keeps families of different types in one registry.
+/// let mut registry: HashMap<&str, FamilyToken> = HashMap::new();
+/// registry.insert("audit_log", AuditLog::new().family().to_token());
+///
+/// // A plugin that knows the type redeems the token to create its own instances.
+/// let token = &registry["audit_log"];
+/// let family = token.redeem::<AuditLog>().expect("the token is for an AuditLog family");
+/// let audit_log: AuditLog = family.into();
+///
+/// // Redeeming as a different type fails.
+/// assert!(token.redeem::<MetricsSink>().is_none());
+/// ```
+pub struct FamilyToken {
+    // Type-occluded `Family<T>`.
+    family: Box<dyn Any + Send + Sync>,
+
+    // Clones the type-occluded `Family<T>`, as `dyn Any` cannot be cloned on its own.
+    clone_family: fn(&(dyn Any + Send + Sync + 'static)) -> Box<dyn Any + Send + Sync>,
+
+    type_name: &'static str,
+    family_id: FamilyId,
+}
+
+impl FamilyToken {
+    /// Returns the family the token is for, if the family is a family of `T`.
+    ///
+    /// Returns `None` if the token is for a family of a different type.
+    #[must_use]
+    pub fn redeem<T>(&self) -> Option<Family<T>>
+    where
+        T: Object,
+    {
+        self.family.downcast_ref::<Family<T>>().cloned()
+    }
+
+    /// Whether the token is for a family of `T`.
+    #[must_use]
+    pub fn is_family_of<T>(&self) -> bool
+    where
+        T: Object,
+    {
+        self.family.is::<Family<T>>()
+    }
+
+    /// The name of the linked object type of the family, as returned by
+    /// [`std::any::type_name()`], for use in diagnostics.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The identifier of the family the token is for.
+    #[must_use]
+    pub fn family_id(&self) -> FamilyId {
+        self.family_id
+    }
+
+    /// Converts the token into a raw pointer, for passing it through a C-compatible interface.
+    ///
+    /// The token is leaked unless the pointer is converted back via
+    /// [`from_raw()`][Self::from_raw] exactly once.
+    #[must_use]
+    pub fn into_raw(self) -> *mut c_void {
+        Box::into_raw(Box::new(self)).cast()
+    }
+
+    /// Converts a raw pointer created by [`into_raw()`][Self::into_raw] back into a token.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must have been returned by `into_raw()` and must not have been converted back
+    /// into a token before. The code that called `into_raw()` must have been built with the same
+    /// compiler and the same version of `linked` as the code calling this function.
+    #[must_use]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Self {
+        // SAFETY: Forwarding guarantees from the caller - the pointer owns a boxed token.
+        *unsafe { Box::from_raw(ptr.cast::<Self>()) }
+    }
+}
+
+impl<T> From<Family<T>> for FamilyToken
+where
+    T: Object,
+{
+    fn from(family: Family<T>) -> Self {
+        Self {
+            family_id: family.id(),
+            family: Box::new(family),
+            clone_family: clone_family::<T>,
+            type_name: type_name::<T>(),
+        }
+    }
+}
+
+impl Clone for FamilyToken {
+    fn clone(&self) -> Self {
+        Self {
+            family: (self.clone_family)(&*self.family),
+            clone_family: self.clone_family,
+            type_name: self.type_name,
+            family_id: self.family_id,
+        }
+    }
+}
+
+impl Debug for FamilyToken {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FamilyToken")
+            .field("type_name", &self.type_name)
+            .field("family_id", &self.family_id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn clone_family<T>(family: &(dyn Any + Send + Sync + 'static)) -> Box<dyn Any + Send + Sync>
+where
+    T: Object,
+{
+    Box::new(
+        family
+            .downcast_ref::<Family<T>>()
+            .expect("the clone function is always paired with a family of the same T")
+            .clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{self, AtomicUsize};
+    use std::thread;
+
+    use super::*;
+
+    #[linked::object]
+    struct Counter {
+        value: Arc<AtomicUsize>,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            let value = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                value: Arc::clone(&value),
+            })
+        }
+
+        fn increment(&self) {
+            self.value.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[linked::object]
+    struct Other {}
+
+    #[test]
+    fn redeem_as_same_type() {
+        let counter = Counter::new();
+        let token = counter.family().to_token();
+
+        assert!(token.is_family_of::<Counter>());
+        assert_eq!(token.family_id(), counter.family().id());
+        assert_eq!(token.type_name(), type_name::<Counter>());
+
+        thread::spawn(move || {
+            let counter: Counter = token.redeem::<Counter>().unwrap().into();
+            counter.increment();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(counter.value.load(atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn redeem_as_other_type_fails() {
+        let token = FamilyToken::from(Counter::new().family());
+
+        assert!(!token.is_family_of::<Other>());
+        assert!(token.redeem::<Other>().is_none());
+    }
+
+    #[test]
+    fn token_keeps_family_alive() {
+        let counter = Counter::new();
+        let family = counter.family();
+        let token = family.to_token();
+        let clone = token.clone();
+
+        drop(counter);
+        drop(token);
+
+        let counter: Counter = clone.redeem::<Counter>().unwrap().into();
+        assert_eq!(counter.family(), family);
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        let counter = Counter::new();
+        let ptr = counter.family().to_token().into_raw();
+
+        // SAFETY: We just created the pointer via `into_raw()` and only convert it back once.
+        let token = unsafe { FamilyToken::from_raw(ptr) };
+
+        assert_eq!(token.redeem::<Counter>().unwrap(), counter.family());
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Debug, Formatter},
+    sync::{
+        Arc,
+        atomic::{self, AtomicU64},
+    },
+};
+
+/// Tracks the highest value recorded by any thread as a [linked object][crate], with the peak
+/// shared by the family and each instance keeping its own local peak.
+///
+/// Typical values to track are the deepest a queue has been or the most memory in use at once.
+/// Recording a value that does not exceed the local peak of the instance does not touch any
+/// state shared with other threads. Only a new local peak is published to the family, so the
+/// cost of tracking is proportional to how often the peak grows rather than to how often values
+/// are recorded.
+///
+/// # Usage
+///
+/// Create the tracker via [`HighWaterMark::new()`] and use it via any of the standard instance
+/// management mechanisms, such as [`linked::thread_local_rc!`][1] or
+/// [`linked::InstancePerThread<T>`][2].
+///
+/// The peak can be reset via [`reset()`][Self::reset], typically by a reporting task that
+/// publishes the peak of each reporting period. Values recorded concurrently with a reset may
+/// count toward either period.
+///
+/// # Example
+///
+/// ```
+/// use linked::HighWaterMark;
+///
+/// linked::thread_local_rc!(static QUEUE_DEPTH: HighWaterMark = HighWaterMark::new());
+///
+/// QUEUE_DEPTH.with(|depth| depth.record(3));
+/// QUEUE_DEPTH.with(|depth| depth.record(7));
+/// QUEUE_DEPTH.with(|depth| depth.record(5));
+///
+/// // Typically called by a reporting task at the end of every reporting period.
+/// let peak = QUEUE_DEPTH.with(|depth| depth.reset());
+/// assert_eq!(peak, 7);
+/// ```
+///
+/// [1]: crate::thread_local_rc
+/// [2]: crate::InstancePerThread
+#[linked::object]
+pub struct HighWaterMark {
+    local_peak: Cell<u64>,
+
+    // The reset generation the local peak belongs to. If the family has been reset since, the
+    // local peak is stale and does not suppress publishing.
+    local_generation: Cell<u64>,
+
+    shared: Arc<SharedPeak>,
+}
+
+struct SharedPeak {
+    peak: AtomicU64,
+    generation: AtomicU64,
+}
+
+impl HighWaterMark {
+    /// Creates a new tracker with a peak of zero, returning the first instance of a new family.
+    #[must_use]
+    pub fn new() -> Self {
+        let shared = Arc::new(SharedPeak {
+            peak: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
+        });
+
+        linked::new!(Self {
+            local_peak: Cell::new(0),
+            local_generation: Cell::new(0),
+            shared: Arc::clone(&shared),
+        })
+    }
+
+    /// Records an observed value, raising the peak if the value exceeds it.
+    pub fn record(&self, value: u64) {
+        let generation = self.shared.generation.load(atomic::Ordering::Acquire);
+
+        if generation != self.local_generation.get() {
+            self.local_generation.set(generation);
+            self.local_peak.set(0);
+        }
+
+        if value <= self.local_peak.get() {
+            return;
+        }
+
+        self.local_peak.set(value);
+        self.shared.peak.fetch_max(value, atomic::Ordering::Relaxed);
+    }
+
+    /// The highest value recorded by any instance in the family since the last reset.
+    #[must_use]
+    pub fn peak(&self) -> u64 {
+        self.shared.peak.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The highest value recorded by the current instance since the last reset.
+    #[must_use]
+    pub fn local_peak(&self) -> u64 {
+        if self.shared.generation.load(atomic::Ordering::Acquire) != self.local_generation.get() {
+            return 0;
+        }
+
+        self.local_peak.get()
+    }
+
+    /// Resets the peak of the family to zero, returning the peak before the reset.
+    pub fn reset(&self) -> u64 {
+        // We start a new generation first, so instances stop trusting their local peaks before
+        // the shared peak they were published to is gone.
+        self.shared
+            .generation
+            .fetch_add(1, atomic::Ordering::Release);
+
+        self.shared.peak.swap(0, atomic::Ordering::Relaxed)
+    }
+}
+
+impl Default for HighWaterMark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for HighWaterMark {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HighWaterMark")
+            .field("local_peak", &self.local_peak())
+            .field("peak", &self.peak())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn tracks_peak_across_instances() {
+        let tracker = HighWaterMark::new();
+        tracker.record(5);
+        tracker.record(2);
+
+        thread::spawn({
+            let tracker = tracker.clone();
+
+            move || {
+                tracker.record(8);
+                assert_eq!(tracker.local_peak(), 8);
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(tracker.local_peak(), 5);
+        assert_eq!(tracker.peak(), 8);
+    }
+
+    #[test]
+    fn reset_starts_new_period() {
+        let tracker = HighWaterMark::new();
+        tracker.record(10);
+
+        // Any instance in the family can reset the peak.
+        let previous_peak = thread::spawn({
+            let tracker = tracker.clone();
+
+            move || {
+                tracker.record(4);
+                tracker.reset()
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(previous_peak, 10);
+        assert_eq!(tracker.peak(), 0);
+        assert_eq!(tracker.local_peak(), 0);
+
+        // The local peak from before the reset does not prevent publishing a lower value.
+        tracker.record(3);
+        assert_eq!(tracker.peak(), 3);
+        assert_eq!(tracker.local_peak(), 3);
+    }
+}
@@ -0,0 +1,407 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    ops::Deref,
+    sync::{Arc, OnceLock},
+};
+
+use many_cpus::{HardwareInfo, HardwareTracker, ProcessorId, ProcessorSet};
+
+/// A wrapper that manages linked instances of `T`, ensuring that only one instance of `T` is
+/// created per processor, shared by all the threads that access it from that processor.
+///
+/// Requires `T: Send + Sync`.
+///
+/// The instance of a processor is created the first time it is accessed from that processor
+/// and lives for as long as the `InstancePerProcessor<T>` (or one of its clones) exists.
+///
+/// Access goes via [`acquire()`][Self::acquire], which resolves the instance of the processor
+/// the current thread is executing on. Unless the thread is pinned to a single processor, the
+/// operating system may move it to another processor at any time, including in the middle of an
+/// operation on the instance. What happens then is decided by the [`MigrationPolicy`] the
+/// wrapper was created with.
+///
+/// This is only available with the `processor_instances` Cargo feature enabled.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use linked::{InstancePerProcessor, MigrationPolicy};
+///
+/// #[linked::object]
+/// struct EventCounter {
+///     events: AtomicUsize,
+/// }
+///
+/// impl EventCounter {
+///     pub fn new() -> Self {
+///         linked::new!(Self {
+///             events: AtomicUsize::new(0),
+///         })
+///     }
+///
+///     pub fn record(&self) {
+///         self.events.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let counters = InstancePerProcessor::new(EventCounter::new(), MigrationPolicy::PinThread);
+///
+/// // The thread stays on the processor of the instance until the guard is dropped.
+/// let counter = counters.acquire();
+/// counter.record();
+/// counter.record();
+/// ```
+pub struct InstancePerProcessor<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    family: linked::Family<T>,
+    policy: MigrationPolicy,
+
+    // Indexed by processor ID, with the instance created on first access from that processor.
+    instances: Arc<[OnceLock<T>]>,
+}
+
+impl<T> InstancePerProcessor<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    /// Creates a new `InstancePerProcessor` with an existing instance of `T`, handling threads
+    /// that move to another processor while accessing an instance according to `policy`.
+    ///
+    /// Any further access to `T` via the returned value will be via an instance of `T` that
+    /// belongs to a processor.
+    #[must_use]
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "intentional needless consume to encourage all access to go via InstancePerProcessor<T>"
+    )]
+    pub fn new(inner: T, policy: MigrationPolicy) -> Self {
+        Self {
+            family: inner.family(),
+            policy,
+            instances: (0..=HardwareInfo::max_processor_id())
+                .map(|_| OnceLock::new())
+                .collect(),
+        }
+    }
+
+    /// The policy applied when a thread moves to another processor while accessing an instance.
+    #[must_use]
+    #[inline]
+    pub fn migration_policy(&self) -> MigrationPolicy {
+        self.policy
+    }
+
+    /// Returns a guard that provides access to the instance of `T` of the processor the current
+    /// thread is executing on, creating the instance if the processor does not have one.
+    ///
+    /// If the thread moves to another processor while the guard exists, the guard behaves
+    /// according to the [`MigrationPolicy`] of the wrapper.
+    #[must_use]
+    pub fn acquire(&self) -> ProcessorInstance<'_, T> {
+        let restore_affinity = match self.policy {
+            MigrationPolicy::PinThread if !HardwareTracker::is_thread_processor_pinned() => {
+                let original = ProcessorSet::builder()
+                    .where_available_for_current_thread()
+                    .take_all()
+                    .expect("the current thread must be allowed to use at least one processor");
+
+                // If we move before the pin takes effect, the pin moves us right back.
+                HardwareTracker::with_current_processor(|p| ProcessorSet::from(p.clone()))
+                    .pin_current_thread_to();
+
+                Some(original)
+            }
+            _ => None,
+        };
+
+        ProcessorInstance {
+            owner: self,
+            processor_id: HardwareTracker::current_processor_id(),
+            restore_affinity,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Returns the instance of `T` of the processor, creating it if it does not exist yet.
+    fn instance(&self, processor_id: ProcessorId) -> &T {
+        let index = usize::try_from(processor_id)
+            .expect("processor IDs are bounded by the platform and always fit in usize");
+
+        self.instances
+            .get(index)
+            .expect("processor IDs never exceed the maximum processor ID")
+            .get_or_init(|| T::from(self.family.clone()))
+    }
+}
+
+impl<T> Clone for InstancePerProcessor<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            family: self.family.clone(),
+            policy: self.policy,
+            instances: Arc::clone(&self.instances),
+        }
+    }
+}
+
+impl<T> Debug for InstancePerProcessor<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstancePerProcessor")
+            .field("family", &self.family)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How an [`InstancePerProcessor<T>`] handles a thread that moves to another processor while
+/// accessing the instance of its original processor via a [`ProcessorInstance`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MigrationPolicy {
+    /// The guard keeps referencing the instance of the processor it was acquired on, even after
+    /// the thread moves to another processor.
+    ///
+    /// Operations that span multiple accesses see one consistent instance but the instance may
+    /// be accessed concurrently from the thread's new processor.
+    StickToOriginal,
+
+    /// Every access via the guard resolves the instance of the processor the thread is
+    /// executing on at the time of the access.
+    ///
+    /// Each access is served by the instance of the current processor but consecutive accesses
+    /// may be served by different instances, so operations must not rely on state carried over
+    /// between accesses.
+    ResolvePerCall,
+
+    /// The thread is pinned to the processor it was executing on when the guard was acquired,
+    /// for as long as the guard exists, so it cannot move in the first place.
+    ///
+    /// The original processor affinity of the thread is restored when the guard is dropped.
+    /// If the thread is already pinned to a single processor, its affinity is left untouched.
+    PinThread,
+}
+
+/// Provides access to the instance of `T` that belongs to a processor, obtained via
+/// [`InstancePerProcessor::acquire()`].
+///
+/// The guard is bound to the thread it was acquired on. What the guard references when the
+/// thread moves to another processor depends on the [`MigrationPolicy`] of the wrapper.
+pub struct ProcessorInstance<'a, T>
+where
+    T: linked::Object + Send + Sync,
+{
+    owner: &'a InstancePerProcessor<T>,
+
+    // The processor the thread was executing on when the guard was acquired.
+    processor_id: ProcessorId,
+
+    // Only set if we pinned the thread, in which case we restore this affinity on drop.
+    restore_affinity: Option<ProcessorSet>,
+
+    // The guard pins or resolves based on the current thread, so it must stay on that thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T> ProcessorInstance<'_, T>
+where
+    T: linked::Object + Send + Sync,
+{
+    /// The processor the thread was executing on when the guard was acquired.
+    #[must_use]
+    #[inline]
+    pub fn processor_id(&self) -> ProcessorId {
+        self.processor_id
+    }
+}
+
+impl<T> Deref for ProcessorInstance<'_, T>
+where
+    T: linked::Object + Send + Sync,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match self.owner.policy {
+            MigrationPolicy::ResolvePerCall => {
+                self.owner.instance(HardwareTracker::current_processor_id())
+            }
+            MigrationPolicy::StickToOriginal | MigrationPolicy::PinThread => {
+                self.owner.instance(self.processor_id)
+            }
+        }
+    }
+}
+
+impl<T> Drop for ProcessorInstance<'_, T>
+where
+    T: linked::Object + Send + Sync,
+{
+    fn drop(&mut self) {
+        if let Some(original) = self.restore_affinity.take() {
+            original.pin_current_thread_to();
+        }
+    }
+}
+
+impl<T> Debug for ProcessorInstance<'_, T>
+where
+    T: linked::Object + Send + Sync,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessorInstance")
+            .field("processor_id", &self.processor_id)
+            .field("policy", &self.owner.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use many_cpus::Processor;
+
+    use super::*;
+
+    #[linked::object]
+    struct Counter {
+        value: AtomicUsize,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            linked::new!(Self {
+                value: AtomicUsize::new(0),
+            })
+        }
+
+        fn increment(&self) {
+            self.value.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn value(&self) -> usize {
+            self.value.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Two distinct single-processor sets, if the system has enough processors.
+    fn two_processors() -> Option<(ProcessorSet, ProcessorSet)> {
+        let processors = ProcessorSet::default();
+        let mut processors = processors.processors().iter().map(Processor::clone);
+
+        Some((
+            ProcessorSet::from(processors.next()?),
+            ProcessorSet::from(processors.next()?),
+        ))
+    }
+
+    #[test]
+    fn same_processor_shares_instance() {
+        let counters = InstancePerProcessor::new(Counter::new(), MigrationPolicy::StickToOriginal);
+        let processor = ProcessorSet::from(ProcessorSet::default().processors().first().clone());
+
+        thread::spawn(move || {
+            processor.pin_current_thread_to();
+
+            counters.acquire().increment();
+
+            let other_counters = counters.clone();
+            let counter = other_counters.acquire();
+            counter.increment();
+
+            assert_eq!(counter.value(), 2);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn stick_to_original_ignores_migration() {
+        let Some((first, second)) = two_processors() else {
+            eprintln!("Skipping test because there are not enough processors");
+            return;
+        };
+
+        let counters = InstancePerProcessor::new(Counter::new(), MigrationPolicy::StickToOriginal);
+
+        thread::spawn(move || {
+            first.pin_current_thread_to();
+            let counter = counters.acquire();
+            let original = ptr::from_ref(&*counter);
+
+            second.pin_current_thread_to();
+
+            assert_eq!(counter.processor_id(), first.processors().first().id());
+            assert!(ptr::eq(&*counter, original));
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn resolve_per_call_follows_migration() {
+        let Some((first, second)) = two_processors() else {
+            eprintln!("Skipping test because there are not enough processors");
+            return;
+        };
+
+        let counters = InstancePerProcessor::new(Counter::new(), MigrationPolicy::ResolvePerCall);
+
+        thread::spawn(move || {
+            first.pin_current_thread_to();
+            let counter = counters.acquire();
+            counter.increment();
+
+            second.pin_current_thread_to();
+
+            // The instance of the second processor has not been touched yet.
+            assert_eq!(counter.value(), 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn pin_thread_pins_until_dropped() {
+        if ProcessorSet::default().len() < 2 {
+            eprintln!("Skipping test because there are not enough processors");
+            return;
+        }
+
+        let counters = InstancePerProcessor::new(Counter::new(), MigrationPolicy::PinThread);
+
+        thread::spawn(move || {
+            assert!(!HardwareTracker::is_thread_processor_pinned());
+
+            let counter = counters.acquire();
+            assert!(HardwareTracker::is_thread_processor_pinned());
+            assert_eq!(
+                HardwareTracker::current_processor_id(),
+                counter.processor_id()
+            );
+
+            drop(counter);
+            assert!(!HardwareTracker::is_thread_processor_pinned());
+        })
+        .join()
+        .unwrap();
+    }
+}
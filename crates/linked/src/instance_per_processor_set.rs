@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+    sync::{Arc, Mutex, Weak},
+    thread::JoinHandle,
+};
+
+use many_cpus::{Processor, ProcessorId, ProcessorSet};
+
+use crate::ERR_POISONED_LOCK;
+
+/// A wrapper that manages linked instances of `T`, ensuring that only one instance of `T` is
+/// created per [`ProcessorSet`] partition, shared by all the threads pinned to that partition.
+///
+/// Requires `T: Send + Sync`.
+///
+/// This is the middle ground between one instance per process and one instance per thread
+/// ([`InstancePerThreadSync<T>`][1]): each pool of threads pinned to a set of processors (e.g.
+/// the processors of one memory region) shares one instance, without having to aggregate the
+/// per-thread instances of the pool manually.
+///
+/// The instance of a partition is resolved via a [`PartitionToken<T>`], which is obtained via
+/// [`partition()`][Self::partition] and is typically handed to the workers of the pool when they
+/// are spawned via [`spawn_threads()`][Self::spawn_threads]. Two processor sets with the same
+/// processors are the same partition.
+///
+/// This is only available with the `processor_set_instances` Cargo feature enabled.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use linked::InstancePerProcessorSet;
+/// use many_cpus::ProcessorSet;
+///
+/// #[linked::object]
+/// struct RequestCache {
+///     hits: AtomicUsize,
+/// }
+///
+/// impl RequestCache {
+///     pub fn new() -> Self {
+///         linked::new!(Self {
+///             hits: AtomicUsize::new(0),
+///         })
+///     }
+///
+///     pub fn record_hit(&self) {
+///         self.hits.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let caches = InstancePerProcessorSet::new(RequestCache::new());
+/// let processors = ProcessorSet::default();
+///
+/// // Keeps the instance of the partition alive after the workers are done.
+/// let cache = caches.partition(&processors);
+///
+/// // Every worker of the pool receives the instance of the pool's partition.
+/// let workers = caches.spawn_threads(&processors, |_processor, cache| cache.record_hit());
+///
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+///
+/// assert_eq!(cache.hits.load(Ordering::Relaxed), processors.len());
+/// ```
+///
+/// # Instance lifecycle
+///
+/// The instance of a partition is dropped when the last [`PartitionToken<T>`] of that partition
+/// is dropped. If a token for the same partition is later obtained, it references a new instance
+/// linked to the same family.
+///
+/// [1]: crate::InstancePerThreadSync
+pub struct InstancePerProcessorSet<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    family: linked::Family<T>,
+
+    // Keyed by the sorted IDs of the processors in the partition. Entries of partitions whose
+    // instance has been dropped are replaced when the partition is next requested.
+    partitions: Arc<Mutex<Partitions<T>>>,
+}
+
+type Partitions<T> = HashMap<Box<[ProcessorId]>, Weak<T>>;
+
+impl<T> InstancePerProcessorSet<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    /// Creates a new `InstancePerProcessorSet` with an existing instance of `T`.
+    ///
+    /// Any further access to `T` via the returned value will be via an instance of `T` that
+    /// belongs to a [`ProcessorSet`] partition.
+    #[must_use]
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "intentional needless consume to encourage all access to go via InstancePerProcessorSet<T>"
+    )]
+    pub fn new(inner: T) -> Self {
+        Self {
+            family: inner.family(),
+            partitions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns a token that references the instance of `T` of the partition made up of the
+    /// processors in `processors`, creating the instance if the partition does not have one.
+    ///
+    /// The token can be cloned and moved between threads. All tokens of the same partition
+    /// reference the same instance of `T`.
+    #[must_use]
+    pub fn partition(&self, processors: &ProcessorSet) -> PartitionToken<T> {
+        let mut key = processors
+            .processors()
+            .iter()
+            .map(Processor::id)
+            .collect::<Vec<_>>();
+        key.sort_unstable();
+
+        let mut partitions = self.partitions.lock().expect(ERR_POISONED_LOCK);
+
+        let instance = partitions
+            .get(key.as_slice())
+            .and_then(Weak::upgrade)
+            .unwrap_or_else(|| {
+                let instance = Arc::new(T::from(self.family.clone()));
+                partitions.insert(key.into_boxed_slice(), Arc::downgrade(&instance));
+                instance
+            });
+
+        PartitionToken {
+            instance,
+            processors: processors.clone(),
+        }
+    }
+
+    /// Spawns one thread for each processor in `processors`, pinned to that processor, providing
+    /// each thread with the target processor and a token for the instance of `T` of the partition
+    /// made up of `processors`.
+    ///
+    /// This is the same as [`ProcessorSet::spawn_threads()`] except for the token.
+    pub fn spawn_threads<E, R>(
+        &self,
+        processors: &ProcessorSet,
+        entrypoint: E,
+    ) -> Box<[JoinHandle<R>]>
+    where
+        E: Fn(Processor, PartitionToken<T>) -> R + Send + Clone + 'static,
+        R: Send + 'static,
+    {
+        let token = self.partition(processors);
+
+        processors.spawn_threads(move |processor| entrypoint(processor, token.clone()))
+    }
+}
+
+impl<T> Clone for InstancePerProcessorSet<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            family: self.family.clone(),
+            partitions: Arc::clone(&self.partitions),
+        }
+    }
+}
+
+impl<T> Debug for InstancePerProcessorSet<T>
+where
+    T: linked::Object + Send + Sync,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstancePerProcessorSet")
+            .field("family", &self.family)
+            .finish_non_exhaustive()
+    }
+}
+
+/// References the instance of a [linked object][crate] of type `T` that belongs to a
+/// [`ProcessorSet`] partition, obtained via [`InstancePerProcessorSet<T>`].
+///
+/// The token can be cloned and moved between threads. It keeps the instance of its partition
+/// alive for as long as the token (or one of its clones) exists.
+pub struct PartitionToken<T> {
+    instance: Arc<T>,
+    processors: ProcessorSet,
+}
+
+impl<T> PartitionToken<T> {
+    /// The processors that make up the partition the instance belongs to.
+    #[must_use]
+    pub fn processors(&self) -> &ProcessorSet {
+        &self.processors
+    }
+}
+
+impl<T> Deref for PartitionToken<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.instance
+    }
+}
+
+impl<T> Clone for PartitionToken<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            instance: Arc::clone(&self.instance),
+            processors: self.processors.clone(),
+        }
+    }
+}
+
+impl<T> Debug for PartitionToken<T> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionToken")
+            .field("processors", &self.processors)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use super::*;
+
+    #[linked::object]
+    struct Counter {
+        value: AtomicUsize,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            linked::new!(Self {
+                value: AtomicUsize::new(0),
+            })
+        }
+
+        fn increment(&self) {
+            self.value.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn value(&self) -> usize {
+            self.value.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn same_partition_shares_instance() {
+        let counters = InstancePerProcessorSet::new(Counter::new());
+        let processors = ProcessorSet::default();
+
+        let first = counters.partition(&processors);
+
+        // The same partition resolves to the same instance from other threads and clones.
+        let second = thread::spawn({
+            let counters = counters.clone();
+            move || counters.partition(&ProcessorSet::default())
+        })
+        .join()
+        .unwrap();
+
+        assert!(Arc::ptr_eq(&first.instance, &second.instance));
+
+        // A partition with different processors has its own instance.
+        let one = ProcessorSet::from(processors.processors().first().clone());
+        let other = counters.partition(&one);
+
+        if processors.len() > 1 {
+            assert!(!Arc::ptr_eq(&first.instance, &other.instance));
+        }
+    }
+
+    #[test]
+    fn workers_share_instance() {
+        let counters = InstancePerProcessorSet::new(Counter::new());
+        let processors = ProcessorSet::default();
+        let counter = counters.partition(&processors);
+
+        let workers = counters.spawn_threads(&processors, |_, counter| {
+            counter.increment();
+            counter.processors().len()
+        });
+
+        for worker in workers {
+            assert_eq!(worker.join().unwrap(), processors.len());
+        }
+
+        assert_eq!(counter.value(), processors.len());
+    }
+
+    #[test]
+    fn dropped_partition_gets_new_instance() {
+        let counters = InstancePerProcessorSet::new(Counter::new());
+        let processors = ProcessorSet::default();
+
+        let counter = counters.partition(&processors);
+        counter.increment();
+        assert_eq!(counter.value(), 1);
+
+        drop(counter);
+
+        let counter = counters.partition(&processors);
+        assert_eq!(counter.value(), 0);
+    }
+}
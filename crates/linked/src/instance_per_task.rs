@@ -0,0 +1,440 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{self, AtomicU64},
+    task::{Context, Poll},
+};
+
+use hash_hasher::HashedMap;
+
+use crate::Family;
+
+/// A wrapper that manages linked instances of `T`, ensuring that only one
+/// instance of `T` is created per async task.
+///
+/// This is the async counterpart of [`InstancePerThread<T>`][1]. When tasks are executed by a
+/// work-stealing executor, a task may be polled on a different thread every time, so
+/// thread-local instances do not follow the task around. Instead of being tied to a thread, the
+/// instance managed by this type is owned by the task itself and moves together with the task.
+///
+/// # Usage
+///
+/// Create an instance of `InstancePerTask` and provide it the initial instance of a linked
+/// object `T`. Any instance of `T` accessed through the same `InstancePerTask` or a clone of it
+/// will be linked to the same family.
+///
+/// Wrap the future of each task via [`.scope()`][Self::scope]. Code executed while the
+/// wrapped future is being polled can access the task's instance of `T` via
+/// [`.with()`][Self::with].
+///
+/// The task's instance is created when the wrapped future is first polled and dropped when
+/// the wrapped future is dropped. If the wrapped future is moved to another thread between polls,
+/// the instance moves with it, so the wrapped future is only `Send` if `T: Send`.
+///
+/// # Example
+///
+/// ```
+/// # use std::cell::Cell;
+/// #
+/// # #[linked::object]
+/// # struct RequestCounter {
+/// #     local_value: Cell<usize>,
+/// # }
+/// #
+/// # impl RequestCounter {
+/// #     pub fn new() -> Self {
+/// #         linked::new!(Self { local_value: Cell::new(0) })
+/// #     }
+/// #
+/// #     pub fn increment(&self) {
+/// #        self.local_value.set(self.local_value.get() + 1);
+/// #     }
+/// #
+/// #     pub fn local_value(&self) -> usize {
+/// #         self.local_value.get()
+/// #     }
+/// # }
+/// #
+/// # fn block_on<F: Future>(future: F) -> F::Output {
+/// #     let mut future = std::pin::pin!(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop {
+/// #         if let std::task::Poll::Ready(result) = future.as_mut().poll(&mut cx) {
+/// #             return result;
+/// #         }
+/// #     }
+/// # }
+/// use linked::InstancePerTask;
+///
+/// let counter = InstancePerTask::new(RequestCounter::new());
+///
+/// let task = counter.scope({
+///     let counter = counter.clone();
+///
+///     async move {
+///         counter.with(|c| c.increment());
+///         counter.with(|c| c.increment());
+///
+///         counter.with(|c| c.local_value())
+///     }
+/// });
+///
+/// assert_eq!(block_on(task), 2);
+/// ```
+///
+/// [1]: crate::InstancePerThread
+#[derive(Debug)]
+pub struct InstancePerTask<T>
+where
+    T: linked::Object,
+{
+    family: Family<T>,
+
+    // Identifies the family in the thread-local storage used to expose
+    // the current task's instance while the task is being polled.
+    key: u64,
+}
+
+impl<T> InstancePerTask<T>
+where
+    T: linked::Object,
+{
+    /// Creates a new `InstancePerTask` with an existing instance of `T`.
+    ///
+    /// Any further access of `T` instances via the `InstancePerTask` (or its clones) will return
+    /// instances of `T` from the same family.
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "intentional needless consume to encourage all access to go via InstancePerTask<T>"
+    )]
+    #[must_use]
+    pub fn new(inner: T) -> Self {
+        let key = NEXT_KEY.fetch_add(1, atomic::Ordering::Relaxed);
+
+        Self {
+            family: inner.family(),
+            key,
+        }
+    }
+
+    /// Wraps a future so that it gets its own instance of `T`, accessible via
+    /// [`.with()`][Self::with] whenever the future is being polled.
+    ///
+    /// Every wrapped future gets a separate instance of `T`, even if multiple
+    /// wrapped futures are polled on the same thread.
+    #[must_use]
+    pub fn scope<F>(&self, future: F) -> TaskScope<T, F>
+    where
+        F: Future,
+    {
+        TaskScope {
+            family: self.family.clone(),
+            key: self.key,
+            instance: None,
+            future: Box::pin(future),
+        }
+    }
+
+    /// Calls `f` with the current task's instance of `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from within a future wrapped via [`.scope()`][Self::scope] of this
+    /// `InstancePerTask` or one of its clones.
+    ///
+    /// Panics if `f` polls another future wrapped via [`.scope()`][Self::scope] of any
+    /// `InstancePerTask`.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        TASK_INSTANCES.with_borrow(|task_instances| {
+            let instance = task_instances
+                .get(&self.key)
+                .and_then(|instance| instance.downcast_ref::<T>())
+                .expect("InstancePerTask::with() can only be called from within a future wrapped via InstancePerTask::scope()");
+
+            f(instance)
+        })
+    }
+}
+
+impl<T> Clone for InstancePerTask<T>
+where
+    T: linked::Object,
+{
+    fn clone(&self) -> Self {
+        Self {
+            family: self.family.clone(),
+            key: self.key,
+        }
+    }
+}
+
+/// A future that owns an instance of `T` and exposes it via [`InstancePerTask::with()`]
+/// while the inner future is being polled.
+///
+/// Returned by [`InstancePerTask::scope()`].
+#[derive(Debug)]
+pub struct TaskScope<T, F>
+where
+    T: linked::Object,
+{
+    family: Family<T>,
+    key: u64,
+
+    // Created on first poll, so the instance is created by the thread that starts the task.
+    // Boxed so we can move it in and out of the thread-local storage without reallocating.
+    instance: Option<Box<T>>,
+
+    // Boxed so we do not need to project the pin to the inner future.
+    future: Pin<Box<F>>,
+}
+
+impl<T, F> Future for TaskScope<T, F>
+where
+    T: linked::Object,
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let instance = this
+            .instance
+            .take()
+            .unwrap_or_else(|| Box::new(this.family.clone().into()));
+
+        let _entered = EnteredScope::new(this.key, instance, &mut this.instance);
+
+        this.future.as_mut().poll(cx)
+    }
+}
+
+/// Makes the task's instance available in the thread-local storage for the lifetime of the
+/// value, moving it back to the task when dropped (even if polling the task panics).
+struct EnteredScope<'a, T>
+where
+    T: 'static,
+{
+    key: u64,
+
+    // If the same family is already entered on the current thread (because a scoped future is
+    // polled from within another scoped future), we restore the outer instance when we exit.
+    previous: Option<Box<dyn Any>>,
+
+    slot: &'a mut Option<Box<T>>,
+}
+
+impl<'a, T> EnteredScope<'a, T>
+where
+    T: 'static,
+{
+    fn new(key: u64, instance: Box<T>, slot: &'a mut Option<Box<T>>) -> Self {
+        let instance: Box<dyn Any> = instance;
+
+        let previous =
+            TASK_INSTANCES.with_borrow_mut(|task_instances| task_instances.insert(key, instance));
+
+        Self {
+            key,
+            previous,
+            slot,
+        }
+    }
+}
+
+impl<T> Drop for EnteredScope<'_, T>
+where
+    T: 'static,
+{
+    fn drop(&mut self) {
+        let instance = TASK_INSTANCES.with_borrow_mut(|task_instances| {
+            let instance = match self.previous.take() {
+                Some(previous) => task_instances.insert(self.key, previous),
+                None => task_instances.remove(&self.key),
+            };
+
+            instance.expect("we inserted the instance when entering the scope")
+        });
+
+        *self.slot = Some(
+            instance
+                .downcast::<T>()
+                .expect("we inserted an instance of this type when entering the scope"),
+        );
+    }
+}
+
+static NEXT_KEY: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static TASK_INSTANCES: RefCell<HashedMap<u64, Box<dyn Any>>> = RefCell::new(HashedMap::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, pin::pin, task::Waker, thread};
+
+    use super::*;
+
+    #[linked::object]
+    struct TokenCache {
+        local_value: Cell<usize>,
+    }
+
+    impl TokenCache {
+        fn new() -> Self {
+            linked::new!(Self {
+                local_value: Cell::new(0),
+            })
+        }
+
+        fn increment(&self) {
+            self.local_value.set(self.local_value.get().wrapping_add(1));
+        }
+
+        fn local_value(&self) -> usize {
+            self.local_value.get()
+        }
+    }
+
+    /// Returns `Pending` the first time it is polled and `Ready` the second time.
+    struct YieldOnce {
+        yielded: bool,
+    }
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.yielded {
+                Poll::Ready(())
+            } else {
+                self.yielded = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        Pin::new(future).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn separate_instance_per_task() {
+        let linked_cache = InstancePerTask::new(TokenCache::new());
+
+        let mut first = linked_cache.scope({
+            let linked_cache = linked_cache.clone();
+
+            async move {
+                linked_cache.with(TokenCache::increment);
+                YieldOnce { yielded: false }.await;
+                linked_cache.with(TokenCache::local_value)
+            }
+        });
+
+        let mut second = linked_cache.scope({
+            let linked_cache = linked_cache.clone();
+
+            async move {
+                linked_cache.with(TokenCache::increment);
+                linked_cache.with(TokenCache::increment);
+                linked_cache.with(TokenCache::local_value)
+            }
+        });
+
+        // Interleave the tasks on the same thread.
+        assert_eq!(poll_once(&mut first), Poll::Pending);
+        assert_eq!(poll_once(&mut second), Poll::Ready(2));
+        assert_eq!(poll_once(&mut first), Poll::Ready(1));
+
+        // Each task owns its instance until the task is dropped.
+        assert_eq!(linked_cache.family.instance_count(), 2);
+
+        drop(first);
+        drop(second);
+        assert_eq!(linked_cache.family.instance_count(), 0);
+    }
+
+    #[test]
+    fn instance_moves_with_task() {
+        #[linked::object]
+        struct Counter {
+            local_value: usize,
+        }
+
+        impl Counter {
+            fn new() -> Self {
+                linked::new!(Self { local_value: 0 })
+            }
+        }
+
+        let linked_counter = InstancePerTask::new(Counter::new());
+
+        let mut task = linked_counter.scope({
+            let linked_counter = linked_counter.clone();
+
+            async move {
+                let first_value = linked_counter.with(|c| c.local_value);
+                YieldOnce { yielded: false }.await;
+                let second_value = linked_counter.with(|c| c.local_value);
+                (first_value, second_value)
+            }
+        });
+
+        assert_eq!(poll_once(&mut task), Poll::Pending);
+
+        let result = thread::spawn(move || poll_once(&mut task)).join().unwrap();
+
+        assert_eq!(result, Poll::Ready((0, 0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_outside_scope_panics() {
+        let linked_cache = InstancePerTask::new(TokenCache::new());
+
+        linked_cache.with(TokenCache::increment);
+    }
+
+    #[test]
+    fn nested_scope_restores_outer_instance() {
+        let linked_cache = InstancePerTask::new(TokenCache::new());
+
+        let mut outer = linked_cache.scope({
+            let linked_cache = linked_cache.clone();
+
+            async move {
+                linked_cache.with(TokenCache::increment);
+
+                let inner_value = linked_cache
+                    .scope({
+                        let linked_cache = linked_cache.clone();
+                        async move { linked_cache.with(TokenCache::local_value) }
+                    })
+                    .await;
+
+                (inner_value, linked_cache.with(TokenCache::local_value))
+            }
+        });
+
+        assert_eq!(poll_once(&mut outer), Poll::Ready((0, 1)));
+    }
+
+    #[test]
+    fn scope_polled_with_pin() {
+        let linked_cache = InstancePerTask::new(TokenCache::new());
+
+        let task = pin!(linked_cache.scope({
+            let linked_cache = linked_cache.clone();
+            async move { linked_cache.with(TokenCache::local_value) }
+        }));
+
+        assert_eq!(
+            task.poll(&mut Context::from_waker(Waker::noop())),
+            Poll::Ready(0)
+        );
+    }
+}
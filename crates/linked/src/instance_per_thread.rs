@@ -195,6 +195,19 @@ where
     }
 }
 
+impl<T> From<linked::Family<T>> for InstancePerThread<T>
+where
+    T: linked::Object,
+{
+    /// Creates a new `InstancePerThread` that accesses instances of `family`, without first
+    /// creating an instance of the family on the current thread.
+    fn from(family: linked::Family<T>) -> Self {
+        Self {
+            family: FamilyStateReference::new(family),
+        }
+    }
+}
+
 impl<T> Clone for InstancePerThread<T>
 where
     T: linked::Object,
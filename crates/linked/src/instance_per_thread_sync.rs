@@ -40,6 +40,10 @@ use crate::{BuildThreadIdHasher, ERR_POISONED_LOCK};
 /// is dropped, similar to how `Arc<T>` would behave. If a new `RefSync` is later obtained,
 /// it is initialized with a new instance of the linked object.
 ///
+/// This holds even if the last `RefSync` is dropped on a different thread (e.g. after the
+/// thread it is aligned to has exited), so no state is left behind for threads that no longer
+/// hold any `RefSync`.
+///
 /// It is important to emphasize that this means if you only acquire temporary `RefSync`
 /// instances then you will get a new instance of `T` every time. The performance impact of
 /// this depends on how `T` works internally but you are recommended to keep `RefSync`
@@ -170,10 +174,12 @@ where
     /// the `RefSync<T>` instances as much as possible.
     #[must_use]
     pub fn acquire(&self) -> RefSync<T> {
-        let inner = self.family.current_thread_instance();
+        let thread_id = thread::current().id();
+        let inner = self.family.thread_instance(thread_id);
 
         RefSync {
             inner,
+            thread_id,
             family: self.family.clone(),
         }
     }
@@ -204,6 +210,10 @@ where
     // We really are just a wrapper around an Arc<T>. The only other duty we have
     // is to clean up the thread-local instance when the last `RefSync` is dropped.
     inner: Arc<T>,
+
+    // The thread this instance is aligned to, which is not necessarily the current thread.
+    thread_id: ThreadId,
+
     family: FamilyStateReference<T>,
 }
 
@@ -227,6 +237,7 @@ where
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            thread_id: self.thread_id,
             family: self.family.clone(),
         }
     }
@@ -237,16 +248,17 @@ where
     T: linked::Object + Send + Sync,
 {
     fn drop(&mut self) {
-        // If we were the last RefSync on this thread then we need to drop the thread-local
-        // state for this thread. Note that there are 2 references - ourselves and the family state.
+        // If we were the last RefSync aligned to our thread then we need to drop the thread-local
+        // state for that thread. Note that there are 2 references - ourselves and the family state.
+        // We may be running on a different thread than the one we are aligned to.
         if Arc::strong_count(&self.inner) != 2 {
             // No - there is another RefSync, so we do not need to clean up.
             return;
         }
 
-        self.family.clear_current_thread_instance();
+        self.family.clear_thread_instance(self.thread_id);
 
-        // `self.inner` is now the last reference to the current thread's instance of T
+        // `self.inner` is now the last reference to the aligned thread's instance of T
         // and this instance will be dropped once this function returns and drops the last `Arc<T>`.
     }
 }
@@ -280,11 +292,10 @@ where
         }
     }
 
-    /// Returns the `Arc<T>` for the current thread, creating it if necessary.
+    /// Returns the `Arc<T>` for the current thread (identified by `thread_id`),
+    /// creating it if necessary.
     #[must_use]
-    fn current_thread_instance(&self) -> Arc<T> {
-        let thread_id = thread::current().id();
-
+    fn thread_instance(&self, thread_id: ThreadId) -> Arc<T> {
         // First, an optimistic pass - let's assume it is already initialized for our thread.
         {
             let map = self.thread_specific.read().expect(ERR_POISONED_LOCK);
@@ -324,10 +335,7 @@ where
         }
     }
 
-    fn clear_current_thread_instance(&self) {
-        // We need to clear the thread-specific state for this thread.
-        let thread_id = thread::current().id();
-
+    fn clear_thread_instance(&self, thread_id: ThreadId) {
         let mut map = self.thread_specific.write().expect(ERR_POISONED_LOCK);
         map.remove(&thread_id);
     }
@@ -412,6 +420,7 @@ mod tests {
     };
 
     use super::*;
+    use crate::Object;
 
     #[linked::object]
     struct TokenCache {
@@ -558,4 +567,33 @@ mod tests {
         // Should be back to 2 here - the thread-local state was dropped when the thread exited.
         assert_eq!(Arc::strong_count(&cache.shared_value), 2);
     }
+
+    #[test]
+    fn thread_state_dropped_when_moved_to_other_thread() {
+        let linked_cache = InstancePerThreadSync::new(TokenCache::new());
+
+        let remote_cache = thread::spawn({
+            let linked_cache = linked_cache.clone();
+
+            move || linked_cache.acquire()
+        })
+        .join()
+        .unwrap();
+
+        let family = remote_cache.family();
+        assert_eq!(family.instance_count(), 1);
+
+        // The last `RefSync` aligned to the exited thread is dropped on the current thread.
+        drop(remote_cache);
+
+        assert_eq!(family.instance_count(), 0);
+        assert!(
+            linked_cache
+                .family
+                .thread_specific
+                .read()
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -0,0 +1,444 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+    sync::{Arc, Mutex},
+    thread::ThreadId,
+};
+
+use crate::__private::current_thread_id;
+use crate::ERR_POISONED_LOCK;
+
+/// An opt-in registry that allows visiting some state of every live instance in a family of
+/// [linked objects][crate], across all threads.
+///
+/// The instances of a linked object are typically not thread-safe and can be moved around
+/// freely, so the instances themselves cannot be visited from other threads. Instead, each
+/// instance registers a thread-safe piece of its own state (e.g. a set of atomic counters) with
+/// the registry when it is created. The registry can then visit the state of every instance that
+/// is still alive.
+///
+/// The registry is shared between instances of the family by capturing it in the
+/// [`linked::new!`][crate::new] macro body. A clone of the registry can also be kept outside
+/// the family, e.g. by a metrics reporter that aggregates the per-instance state.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use linked::{InstanceRegistry, RegisteredInstance};
+///
+/// #[linked::object]
+/// struct EventCounter {
+///     events: RegisteredInstance<AtomicUsize>,
+/// }
+///
+/// impl EventCounter {
+///     pub fn new(registry: InstanceRegistry<AtomicUsize>) -> Self {
+///         linked::new!(Self {
+///             // Every instance registers its own counter when it is created.
+///             events: registry.register(AtomicUsize::new(0)),
+///         })
+///     }
+///
+///     pub fn record_event(&self) {
+///         self.events.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let registry = InstanceRegistry::new();
+///
+/// let counter = EventCounter::new(registry.clone());
+/// counter.record_event();
+///
+/// std::thread::spawn({
+///     let counter = counter.clone();
+///
+///     move || {
+///         counter.record_event();
+///         counter.record_event();
+///
+///         // Once the instance is dropped, it is removed from the registry.
+///     }
+/// })
+/// .join()
+/// .unwrap();
+///
+/// let total = registry.aggregate(0, |total, events| total + events.load(Ordering::Relaxed));
+///
+/// // Only the instance on the main thread is still alive.
+/// assert_eq!(registry.len(), 1);
+/// assert_eq!(total, 1);
+/// ```
+pub struct InstanceRegistry<S> {
+    entries: Arc<Mutex<RegistryEntries<S>>>,
+}
+
+impl<S> InstanceRegistry<S> {
+    /// Creates a new empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(RegistryEntries {
+                next_id: 0,
+                entries: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Registers the state of a new instance with the registry.
+    ///
+    /// The state remains registered until the returned [`RegisteredInstance`] is dropped,
+    /// which typically happens when the instance that owns it is dropped.
+    ///
+    /// The state is associated with the current thread, which is typically the thread of the
+    /// instance being created (see [`for_each_instance_with_thread()`][1]).
+    ///
+    /// [1]: Self::for_each_instance_with_thread
+    #[must_use]
+    pub fn register(&self, state: S) -> RegisteredInstance<S> {
+        let state = Arc::new(state);
+
+        let mut entries = self.entries.lock().expect(ERR_POISONED_LOCK);
+
+        let id = entries.next_id;
+        entries.next_id = id
+            .checked_add(1)
+            .expect("u64 overflow is not realistic - we never register that many instances");
+
+        entries.entries.insert(
+            id,
+            RegistryEntry {
+                thread_id: current_thread_id(),
+                state: Arc::clone(&state),
+            },
+        );
+
+        RegisteredInstance {
+            id,
+            state,
+            entries: Arc::clone(&self.entries),
+        }
+    }
+
+    /// Calls `f` with the state of every instance that is currently registered.
+    ///
+    /// Instances may be created or dropped on other threads while the visit is in progress.
+    /// The visit covers the instances that were registered when the call started; state that
+    /// is unregistered during the visit may still be visited.
+    pub fn for_each_instance(&self, mut f: impl FnMut(&S)) {
+        self.for_each_instance_with_thread(|_, state| f(state));
+    }
+
+    /// Calls `f` with the state of every instance that is currently registered, together with
+    /// the thread that registered the state.
+    ///
+    /// This allows per-thread state to be reported per thread, e.g. as one metric series per
+    /// thread. The visiting rules are the same as for
+    /// [`for_each_instance()`][Self::for_each_instance].
+    pub fn for_each_instance_with_thread(&self, mut f: impl FnMut(ThreadId, &S)) {
+        // We take a snapshot so that we do not hold the lock while calling user code, which
+        // may itself create or drop instances of the family.
+        let snapshot = self
+            .entries
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .entries
+            .values()
+            .map(|entry| (entry.thread_id, Arc::clone(&entry.state)))
+            .collect::<Vec<_>>();
+
+        for (thread_id, state) in &snapshot {
+            f(*thread_id, state);
+        }
+    }
+
+    /// Combines the state of every instance that is currently registered into a single value,
+    /// e.g. to sum up per-thread counters.
+    ///
+    /// Starting from `init`, calls `fold` with the accumulated value and the state of each
+    /// instance in turn, returning the final accumulated value.
+    ///
+    /// Instances may be created or dropped on other threads while the aggregation is in progress,
+    /// with the same visiting rules as [`for_each_instance()`][Self::for_each_instance]. If you
+    /// need the aggregation to cover an exact set of instances, use
+    /// [`aggregate_consistent()`][Self::aggregate_consistent].
+    pub fn aggregate<A>(&self, init: A, mut fold: impl FnMut(A, &S) -> A) -> A {
+        let mut accumulated = Some(init);
+
+        self.for_each_instance(|state| {
+            let current = accumulated
+                .take()
+                .expect("we always put the value back after folding");
+            accumulated = Some(fold(current, state));
+        });
+
+        accumulated.expect("we always put the value back after folding")
+    }
+
+    /// Combines the state of every instance that is currently registered into a single value,
+    /// while preventing instances from being registered or unregistered until it completes.
+    ///
+    /// This guarantees that the result covers exactly the instances that were alive at one
+    /// specific point in time - no instance is missed or double-counted due to concurrent
+    /// creation or dropping of instances. The state of each instance may still change while
+    /// the aggregation is in progress, unless the registered state itself prevents that.
+    ///
+    /// Any threads that create or drop instances of the family are blocked until the aggregation
+    /// completes, so keep `fold` short.
+    ///
+    /// # Panics
+    ///
+    /// `fold` must not create or drop instances registered with this registry, as that would
+    /// deadlock or panic.
+    pub fn aggregate_consistent<A>(&self, init: A, mut fold: impl FnMut(A, &S) -> A) -> A {
+        let entries = self.entries.lock().expect(ERR_POISONED_LOCK);
+
+        entries
+            .entries
+            .values()
+            .fold(init, |accumulated, entry| fold(accumulated, &entry.state))
+    }
+
+    /// The number of instances that are currently registered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect(ERR_POISONED_LOCK).entries.len()
+    }
+
+    /// Whether there are no instances currently registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S> Default for InstanceRegistry<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Clone for InstanceRegistry<S> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+        }
+    }
+}
+
+impl<S> Debug for InstanceRegistry<S> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstanceRegistry")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+struct RegistryEntries<S> {
+    next_id: u64,
+    entries: BTreeMap<u64, RegistryEntry<S>>,
+}
+
+struct RegistryEntry<S> {
+    // The thread that registered the state.
+    thread_id: ThreadId,
+    state: Arc<S>,
+}
+
+/// The state of one instance registered with an [`InstanceRegistry`].
+///
+/// Dereferences to the registered state. The state is removed from the registry when this
+/// is dropped.
+pub struct RegisteredInstance<S> {
+    id: u64,
+    state: Arc<S>,
+    entries: Arc<Mutex<RegistryEntries<S>>>,
+}
+
+impl<S> Deref for RegisteredInstance<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<S> Drop for RegisteredInstance<S> {
+    fn drop(&mut self) {
+        self.entries
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .entries
+            .remove(&self.id);
+    }
+}
+
+impl<S> Debug for RegisteredInstance<S> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisteredInstance")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+
+    #[test]
+    fn visits_registered_instances() {
+        let registry = InstanceRegistry::new();
+        assert!(registry.is_empty());
+
+        let first = registry.register(AtomicUsize::new(1));
+        let second = registry.register(AtomicUsize::new(2));
+        assert_eq!(registry.len(), 2);
+
+        second.fetch_add(10, Ordering::Relaxed);
+
+        let mut total = 0;
+        registry.for_each_instance(|state| total += state.load(Ordering::Relaxed));
+        assert_eq!(total, 13);
+
+        drop(first);
+        assert_eq!(registry.len(), 1);
+
+        drop(second);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn visits_instances_on_other_threads() {
+        let registry = InstanceRegistry::new();
+
+        let local = registry.register(AtomicUsize::new(1));
+
+        let (registered_tx, registered_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        let remote = thread::spawn({
+            let registry = registry.clone();
+
+            move || {
+                let _remote = registry.register(AtomicUsize::new(5));
+                registered_tx.send(()).unwrap();
+
+                // Keep the instance alive until the main thread is done visiting.
+                _ = release_rx.recv();
+            }
+        });
+
+        registered_rx.recv().unwrap();
+
+        let mut total = 0;
+        registry.for_each_instance(|state| total += state.load(Ordering::Relaxed));
+        assert_eq!(total, 6);
+
+        drop(release_tx);
+        remote.join().unwrap();
+
+        assert_eq!(registry.len(), 1);
+        drop(local);
+    }
+
+    #[test]
+    fn visits_instances_with_registering_thread() {
+        let registry = InstanceRegistry::new();
+        let _local = registry.register(1_usize);
+
+        let (remote_thread_id, remote) = thread::spawn({
+            let registry = registry.clone();
+            move || (thread::current().id(), registry.register(2_usize))
+        })
+        .join()
+        .unwrap();
+
+        let mut visited = Vec::new();
+        registry
+            .for_each_instance_with_thread(|thread_id, state| visited.push((thread_id, *state)));
+        visited.sort_by_key(|(_, state)| *state);
+
+        assert_eq!(
+            visited,
+            [(thread::current().id(), 1), (remote_thread_id, 2)]
+        );
+
+        drop(remote);
+    }
+
+    #[test]
+    fn aggregates_registered_instances() {
+        let registry = InstanceRegistry::new();
+        assert_eq!(registry.aggregate(0, |total, _: &AtomicUsize| total + 1), 0);
+
+        let _first = registry.register(AtomicUsize::new(3));
+        let _second = registry.register(AtomicUsize::new(4));
+
+        let sum = |total: usize, state: &AtomicUsize| total + state.load(Ordering::Relaxed);
+
+        assert_eq!(registry.aggregate(0, sum), 7);
+        assert_eq!(registry.aggregate_consistent(0, sum), 7);
+    }
+
+    #[test]
+    fn consistent_aggregation_blocks_registration() {
+        let registry = InstanceRegistry::new();
+        let _local = registry.register(1_usize);
+
+        let (aggregating_tx, aggregating_rx) = mpsc::channel();
+        let (registered_tx, registered_rx) = mpsc::channel();
+
+        let remote = thread::spawn({
+            let registry = registry.clone();
+
+            move || {
+                aggregating_rx.recv().unwrap();
+                let _remote = registry.register(10);
+                registered_tx.send(()).unwrap();
+            }
+        });
+
+        let total = registry.aggregate_consistent(0, |total, state| {
+            aggregating_tx.send(()).unwrap();
+
+            // The remote thread cannot register while we are aggregating.
+            assert!(registered_rx.try_recv().is_err());
+            thread::sleep(Duration::from_millis(10));
+            assert!(registered_rx.try_recv().is_err());
+
+            total + state
+        });
+        assert_eq!(total, 1);
+
+        remote.join().unwrap();
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn visitor_can_register() {
+        let registry = InstanceRegistry::new();
+        let _first = registry.register(1_usize);
+
+        let mut registered_during_visit = Vec::new();
+
+        registry.for_each_instance(|_| {
+            registered_during_visit.push(registry.register(2));
+        });
+
+        assert_eq!(registered_during_visit.len(), 1);
+        assert_eq!(registry.len(), 2);
+    }
+}
@@ -181,6 +181,10 @@
 //! struct itself does not need to be thread-safe. See the next chapter to understand how to
 //! implement multithreaded logic.
 //!
+//! If creating the state of the family can fail (e.g. opening a connection pool), use
+//! [`linked::new_try!`][crate::new_try] instead, which allows the `?` operator in the
+//! struct-expression and returns a `Result`.
+//!
 //! # Linked objects on multiple threads
 //! [multiple-threads]: #multiple-threads
 //!
@@ -207,8 +211,14 @@
 //!   time how many object families you need to create;
 //! * [`linked::InstancePerThreadSync<T>`][9] is the same but equivalent to `thread_local_arc!`
 //!   and requires `T: Sync`;
+//! * [`linked::FamilyMap<K, T>`][17] is like `InstancePerThread<T>` but creates one family per
+//!   runtime key (e.g. tenant ID), for when you do not know in advance which families you need;
+//! * [`linked::InstancePerTask<T>`][16] maintains one instance per async task instead of one per
+//!   thread, which keeps working when a work-stealing executor moves tasks between threads;
 //! * [`linked::Family<T>`][11] is the lowest level primitive, being a handle to the object family
-//!   that can be used to create new instances on demand using custom logic.
+//!   that can be used to create new instances on demand using custom logic. It can be converted
+//!   into a [`linked::FamilyToken`][31] that does not expose the type `T`, for passing families
+//!   through type-erased code such as plugin registries.
 //!
 //! Example of using a static variable to link instances on different threads:
 //!
@@ -532,6 +542,33 @@
 //! The underlying assumption of the performance claims is that you do not actually share a single
 //! thread's instance with other threads, of course.
 //!
+//! # Checking requirements at compile time
+//!
+//! Whether a type satisfies these requirements is often only discovered far from its declaration,
+//! e.g. when a static variable is declared via [`linked::thread_local_arc!`][8] in another crate.
+//! To fail fast instead, assert the requirements next to the declaration of the type:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use std::sync::atomic::AtomicUsize;
+//!
+//! #[linked::object]
+//! pub struct Thing {
+//!     total: Arc<AtomicUsize>,
+//!     local: AtomicUsize,
+//! }
+//!
+//! // `Thing` is a linked object and can be used via `linked::thread_local_arc!`.
+//! linked::assert_object!(Thing);
+//! linked::assert_thread_safe!(Thing);
+//! ```
+//!
+//! [`linked::assert_object!`][33] checks that a type is a linked object and
+//! [`linked::assert_thread_safe!`][34] checks that a type can be shared between threads, which is
+//! required from all state shared between the instances of a family. The latter also explains
+//! how to share a nested linked object with a family when asserting on a type that captures an
+//! instance of one.
+//!
 //! # Using linked objects via abstractions
 //!
 //! You may find yourself in a situation where you need to use a linked object type `T` through
@@ -544,6 +581,13 @@
 //! * If the linked objects are **sometimes** to be accessed via trait objects, you can on-demand
 //!   wrap them into a [`std::boxed::Box<dyn Xyz>`][5].
 //!
+//! If the boxes need to be `Send` or `Sync`, name the auto traits in the trait object type,
+//! e.g. `linked::Box<dyn Xyz + Send>` (see [`linked::Box`] for details).
+//!
+//! For strictly single-threaded code that wants to share one `dyn Xyz` instance between multiple
+//! owners on the same thread, [`linked::Rc`] is a variant of [`linked::Box`] whose clones share
+//! the same instance via a non-atomic reference count, like [`std::rc::Rc`].
+//!
 //! The difference is that [`linked::Box`] preserves the linked object functionality even for the
 //! `dyn Xyz` form - you can clone the box, obtain a [`Family<linked::Box<dyn Xyz>>`][Family] to
 //! extend the object family to another thread and store such a box in a static variable in a
@@ -614,6 +658,173 @@
 //! }
 //! ```
 //!
+//! # Hiding linked objects behind a trait
+//!
+//! If call sites should use a thread-sharded service without knowing that it is a linked object,
+//! use [`linked::facade!`][28] to generate a zero-sized facade type that implements a trait by
+//! delegating every call to the current thread's instance in a static variable.
+//!
+//! # Linked objects without modifying the type
+//!
+//! The [`#[linked::object]`][object] attribute adds a hidden field to the struct, which changes
+//! its layout. If the layout of a type must not change (e.g. `#[repr(C)]` types used in FFI),
+//! leave the type untouched and wrap it in a [`linked::Wrapped<T>`][21] instead, created via
+//! [`linked::new_wrapped!`][22]. The wrapper is the linked object and dereferences to the type.
+//!
+//! # Tearing down per-thread instances on thread exit
+//!
+//! Per-thread instances managed by [`linked::thread_local_rc!`][2] and
+//! [`linked::thread_local_arc!`][8] (as well as the per-thread instances used by `.with()` on
+//! [`linked::instances!`][1] variables) are dropped when the thread exits, as part of the
+//! destruction of the thread's thread-local storage. The order of this destruction is unspecified,
+//! so logic in `Drop` that accesses other thread-local state (e.g. to flush buffered data) is
+//! hazardous. Some platforms also defer thread-local storage destruction, so the instances of a
+//! short-lived thread may outlive the thread for some time.
+//!
+//! Call [`linked::run_thread_exit_hooks()`][19] at the end of the thread's entry point to drop the
+//! thread's instances immediately, while all thread-local state is still intact. Any teardown
+//! logic registered via [`linked::on_thread_exit()`][18] after the thread first accessed an
+//! instance runs before that instance is dropped. Instances accessed again afterwards are
+//! re-created and dropped on thread-local storage destruction, as usual.
+//!
+//! Per-thread instances managed by [`InstancePerThreadSync<T>`][9] are dropped when the last
+//! [`RefSync<T>`][14] aligned to the thread is dropped, on whichever thread that happens.
+//!
+//! To detect instances leaked by exited threads, enable the `thread_tracking` Cargo feature and
+//! inspect `Family::instance_counts_per_thread()` - a thread that has exited should not have any
+//! instances counted against it, unless the instances were intentionally moved to other threads.
+//!
+//! # Visiting all instances of a family
+//!
+//! The instances of a linked object are independent of each other, so there is no built-in way to
+//! enumerate them. If you need to aggregate data across all live instances of a family (e.g.
+//! per-thread counters), use an [`InstanceRegistry`][15] shared by all instances of the family.
+//! Each instance registers a thread-safe part of its state with the registry when it is created
+//! and the registry can visit the registered state of all instances that are still alive or
+//! aggregate it into a single value (e.g. a sum of per-thread counters).
+//!
+//! # Interacting with instances on other threads
+//!
+//! Instances are typically not thread-safe, so a coordinator cannot directly access the instances
+//! owned by other threads. With the `thread_tracking` Cargo feature enabled, it can instead send a
+//! closure to a specific thread (or to all threads) via `Family::send_to_thread()` or
+//! `Family::send_to_all_threads()`, which is executed with one of that thread's own instances
+//! when the thread calls `Family::process_mailbox()`.
+//!
+//! To update the state of every thread's instance (e.g. when reloading configuration), the
+//! coordinator can likewise schedule a mutation via `Family::broadcast()`, which each thread
+//! applies to the instance cached by a [`linked::instances!`][1] static variable the next time it
+//! accesses the static variable.
+//!
+//! If the instances merely need to learn that state they derived from shared state is outdated,
+//! the coordinator can instead bump the version of the family via
+//! [`Family::bump_version()`][35], after which each instance reports itself as
+//! [stale][36] until it refreshes its state.
+//!
+//! # Object pools
+//!
+//! A common use of linked objects is an object pool where each thread reuses the items it has
+//! returned itself without synchronization, with excess items shared between threads.
+//! [`linked::Pool<T>`][20] is a ready-made linked object implementing this pattern.
+//!
+//! # Rate limiting and peak tracking
+//!
+//! The same split between state shared by the family and state local to each instance serves
+//! other primitives that are mostly accessed locally. [`linked::RateLimiter`][37] is a token
+//! bucket whose budget is shared by the family, with each instance leasing tokens in batches so
+//! most calls do not synchronize with other threads. [`linked::HighWaterMark`][38] tracks the
+//! highest value recorded by any thread, with each instance only publishing its new local peaks.
+//!
+//! # Read-mostly shared values
+//!
+//! A value that is read frequently but written rarely is best shared by a family via
+//! [`linked::RwLockShared<T>`][26], which protects the value with a lock shared by the family
+//! while letting each instance cache its own view of the value, so reads do not need the lock.
+//!
+//! # Placing per-thread state in the memory region of its thread
+//!
+//! With the `region_placement` Cargo feature enabled, `linked::set_region_allocator_hook()`
+//! sets a hook that wraps the creation of every instance obtained via a [`linked::instances!`][1]
+//! static variable, informing it of the memory region of the creating thread's processor (as
+//! reported by the `many_cpus` crate). This allows a region-aware allocator to place the heap
+//! state of per-thread instances in the memory region where it is used.
+//!
+//! # One instance per processor set
+//!
+//! With the `processor_set_instances` Cargo feature enabled, `linked::InstancePerProcessorSet<T>`
+//! shares one instance of `T` between all the threads pinned to the same `many_cpus` processor
+//! set (e.g. one instance per thread pool), resolved via a `linked::PartitionToken<T>` that is
+//! handed to the threads of the pool when they are spawned.
+//!
+//! # One instance per processor
+//!
+//! With the `processor_instances` Cargo feature enabled, `linked::InstancePerProcessor<T>`
+//! shares one instance of `T` between all the threads executing on the same processor. Because
+//! threads can move between processors at any time, the wrapper is created with a
+//! `linked::MigrationPolicy` that decides whether a thread that moves while accessing an instance
+//! keeps using the instance of its original processor, resolves the instance again on every
+//! access or is pinned to its processor for as long as it accesses the instance.
+//!
+//! # Testing multi-threaded behavior on one thread
+//!
+//! With the `virtual_threads` Cargo feature enabled, tests can use `linked::VirtualThread` to
+//! simulate multiple threads on one thread. Code running inside a virtual thread sees the
+//! per-thread instances of that virtual thread when accessing static variables, so tests can
+//! exercise the interaction of multiple instances without spawning threads.
+//!
+//! # Lifecycle events
+//!
+//! With the `lifecycle_events` Cargo feature enabled, diagnostics tooling can subscribe to the
+//! creation and dropping of every instance of every linked object via
+//! `linked::subscribe_lifecycle_events()`, with each event identifying the type, the family (see
+//! [`Family::id()`][27]) and the thread of the instance. Without the feature, there is no
+//! overhead from lifecycle event tracking.
+//!
+//! # Finding out what keeps families alive
+//!
+//! With the `family_snapshot` Cargo feature enabled, `linked::snapshot_families()` reports every
+//! family in the process with its type, its instances per thread, the number of other references
+//! that keep it alive and (if backtraces are enabled) the backtrace of the code that created it.
+//!
+//! # Detecting mixed families
+//!
+//! APIs that combine multiple instances of a linked object typically rely on them belonging to the
+//! same family. Mixing up instances of different families does not fail on its own but leads to
+//! the instances silently diverging. Such APIs can call [`linked::debug_assert_same_family()`][39]
+//! to panic with the creation sites of both families instead. The check is performed in debug
+//! builds and, with the `family_checks` Cargo feature enabled, also in release builds.
+//!
+//! # Publishing metrics
+//!
+//! With the `metrics` Cargo feature enabled, `linked::MetricsAdapter` publishes the state that
+//! the instances of a family register in a [`linked::InstanceRegistry`][15] (e.g. per-thread
+//! counters) as a [`metrics`](https://docs.rs/metrics) counter or gauge, either as one series per
+//! thread or aggregated over all threads.
+//!
+//! # Linking instances across processes
+//!
+//! Families of linked objects are limited to a single process. With the `process_shared` Cargo
+//! feature enabled on Unix platforms, state shared by the instances of multiple processes (e.g.
+//! the worker processes of a prefork-style service) can be placed in shared memory via
+//! `linked::ProcessShared<T>` and captured by the template of the family in each process. The
+//! process that creates the shared memory passes its handle to the other processes, which use it
+//! to map the same shared memory.
+//!
+//! # Dynamic libraries
+//!
+//! Each dynamic library that links its own copy of `linked` (e.g. a dynamically loaded plugin)
+//! has its own registry of [`linked::instances!`][1] static variables, so copies of the same static
+//! variable in different libraries have separate families and separate per-thread instances. To
+//! share them instead, the plugin host makes its registry the shared registry of the process via
+//! [`linked::SharedRegistry`][32] and the plugins attach to it before using any static variable.
+//!
+//! # Model checking with loom
+//!
+//! When building with `--cfg loom`, the state shared between the instances of a family uses
+//! [`loom`](https://docs.rs/loom) synchronization primitives and `linked::loom_model()` becomes
+//! available for running `loom` models. This allows concurrent types built on linked objects to
+//! be model-checked together with the family machinery.
+//!
 //! # Additional examples
 //!
 //! See `examples/linked_*.rs` for more examples of using linked objects in different scenarios.
@@ -632,33 +843,129 @@
 //! [12]: https://github.com/rust-lang/rfcs/issues/2190
 //! [13]: crate::Ref
 //! [14]: crate::RefSync
+//! [15]: crate::InstanceRegistry
+//! [16]: crate::InstancePerTask
+//! [17]: crate::FamilyMap
+//! [18]: crate::on_thread_exit
+//! [19]: crate::run_thread_exit_hooks
+//! [20]: crate::Pool
+//! [21]: crate::Wrapped
+//! [22]: crate::new_wrapped
+//! [26]: crate::RwLockShared
+//! [27]: crate::Family::id
+//! [28]: crate::facade
+//! [31]: crate::FamilyToken
+//! [32]: crate::SharedRegistry
+//! [33]: crate::assert_object
+//! [34]: crate::assert_thread_safe
+//! [35]: crate::Family::bump_version
+//! [36]: crate::Object::is_stale
+//! [37]: crate::RateLimiter
+//! [38]: crate::HighWaterMark
+//! [39]: crate::debug_assert_same_family
 
 use simple_mermaid::mermaid;
 
 #[doc(hidden)]
 pub mod __private;
 
+mod assertions;
 mod r#box;
 mod constants;
+mod facade;
 mod family;
+mod family_map;
+#[cfg(all(feature = "family_snapshot", not(loom)))]
+mod family_snapshot;
+mod family_token;
+mod high_water_mark;
+#[cfg(feature = "processor_instances")]
+mod instance_per_processor;
+#[cfg(feature = "processor_set_instances")]
+mod instance_per_processor_set;
+mod instance_per_task;
 mod instance_per_thread;
 mod instance_per_thread_sync;
+mod instance_registry;
+#[cfg(feature = "lifecycle_events")]
+mod lifecycle_events;
+#[cfg(loom)]
+mod loom;
+#[cfg(feature = "metrics")]
+mod metrics_adapter;
 mod object;
+mod pool;
+#[cfg(all(feature = "process_shared", unix))]
+mod process_shared;
+mod rate_limiter;
+mod rc;
+#[cfg(feature = "region_placement")]
+mod region_placement;
+mod rw_lock_shared;
+mod shared;
+mod shared_registry;
+#[cfg(not(loom))]
+mod static_family;
 mod static_instance_per_thread;
 mod static_instance_per_thread_sync;
 mod static_instances;
+mod sync;
+mod thread_exit;
 mod thread_id_hash;
+mod thread_local_backend;
+#[cfg(feature = "thread_tracking")]
+mod thread_tracking;
+#[cfg(feature = "virtual_threads")]
+mod virtual_thread;
+mod wrapped;
 
+pub use assertions::*;
 pub use r#box::*;
 pub(crate) use constants::*;
 pub use family::*;
+pub use family_map::*;
+#[cfg(all(feature = "family_snapshot", not(loom)))]
+pub use family_snapshot::*;
+pub use family_token::*;
+pub use high_water_mark::*;
+#[cfg(feature = "processor_instances")]
+pub use instance_per_processor::*;
+#[cfg(feature = "processor_set_instances")]
+pub use instance_per_processor_set::*;
+pub use instance_per_task::*;
 pub use instance_per_thread::*;
 pub use instance_per_thread_sync::*;
+pub use instance_registry::*;
+#[cfg(feature = "lifecycle_events")]
+pub use lifecycle_events::*;
+#[cfg(loom)]
+pub use loom::*;
+#[cfg(feature = "metrics")]
+pub use metrics_adapter::*;
 pub use object::*;
+pub use pool::*;
+#[cfg(all(feature = "process_shared", unix))]
+pub use process_shared::*;
+pub use rate_limiter::*;
+pub use rc::*;
+#[cfg(feature = "region_placement")]
+pub use region_placement::*;
+pub use rw_lock_shared::*;
+pub use shared::*;
+pub use shared_registry::*;
+#[cfg(not(loom))]
+pub use static_family::*;
 pub use static_instance_per_thread::*;
 pub use static_instance_per_thread_sync::*;
 pub use static_instances::*;
+pub use thread_exit::*;
 pub(crate) use thread_id_hash::*;
+pub use thread_local_backend::*;
+#[cfg(feature = "thread_tracking")]
+pub(crate) use thread_tracking::*;
+#[cfg(feature = "virtual_threads")]
+pub use virtual_thread::VirtualThread;
+pub use wrapped::*;
 
 mod macros;
 
@@ -696,15 +1003,198 @@ mod macros;
 ///    instances. This macro disables regular `Self {}` struct-expressions and requires the use
 ///    of `linked::new!`.
 /// 2. Implements `Clone` for the struct. All linked objects can be cloned to create new
-///    instances linked to the same family.
+///    instances linked to the same family. This can be disabled via the `custom_clone` option.
 /// 3. Implements the trait [`linked::Object`] for the struct, enabling standard linked object
 ///    pattern mechanisms such as calling `.family()` on instances to access the [`Family`].
 /// 4. Implements `From<linked::Family<T>>` for the struct. This allows converting a [`Family`]
 ///    into an instance of the linked object using `.into()`.
 ///
+/// # Options
+///
+/// * `custom_clone` - skips generating the `Clone` implementation, so you can provide your own.
+///   Your implementation must create the new instance via [`linked::clone_linked()`][clone_linked]
+///   and may then adjust individual fields (e.g. to carry over or deep-copy local state).
+///
+/// ```
+/// #[linked::object(custom_clone)]
+/// struct Counter {
+///     local_count: usize,
+/// }
+///
+/// impl Counter {
+///     pub fn new() -> Self {
+///         linked::new!(Self { local_count: 0 })
+///     }
+/// }
+///
+/// impl Clone for Counter {
+///     fn clone(&self) -> Self {
+///         let mut clone = linked::clone_linked(self);
+///         clone.local_count = self.local_count;
+///         clone
+///     }
+/// }
+/// ```
+///
+/// * `on_instance_created` - calls `Self::on_instance_created(&mut self)` on every new instance
+///   in the family, immediately after it has been created. This applies to all instances,
+///   including the first one created by `linked::new!`, clones and instances created from a
+///   [`Family`]. You must define this function on the type yourself.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[linked::object(on_instance_created)]
+/// struct Worker {
+///     instances_created: Arc<AtomicUsize>,
+/// }
+///
+/// impl Worker {
+///     pub fn new() -> Self {
+///         let instances_created = Arc::new(AtomicUsize::new(0));
+///
+///         linked::new!(Self {
+///             instances_created: Arc::clone(&instances_created),
+///         })
+///     }
+///
+///     fn on_instance_created(&mut self) {
+///         // For example, register the new instance with a metrics system.
+///         self.instances_created.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let worker = Worker::new();
+/// let _clone = worker.clone();
+///
+/// assert_eq!(worker.instances_created.load(Ordering::Relaxed), 2);
+/// ```
+///
+/// # Field options
+///
+/// * `#[linked(shared)]` - shares the value of the field between all instances in the family.
+///   The field type `T` becomes [`linked::Shared<T>`][Shared], which dereferences to `T`. In
+///   `linked::new!`, you initialize the field with a `T` as usual - the value is only created by
+///   the first instance in the family, with all other instances referencing the same value.
+///   The value is shared between threads, so `T` must be `Send` + `Sync`.
+///
+/// ```
+/// use std::sync::Mutex;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[linked::object]
+/// struct EventLog {
+///     #[linked(shared)]
+///     events: Mutex<Vec<String>>,
+///
+///     #[linked(shared)]
+///     event_count: AtomicUsize,
+///
+///     // Fields without the attribute are separate for every instance.
+///     local_event_count: usize,
+/// }
+///
+/// impl EventLog {
+///     pub fn new() -> Self {
+///         linked::new!(Self {
+///             events: Mutex::new(Vec::new()),
+///             event_count: AtomicUsize::new(0),
+///             local_event_count: 0,
+///         })
+///     }
+///
+///     pub fn record(&mut self, event: &str) {
+///         self.events.lock().unwrap().push(event.to_string());
+///         self.event_count.fetch_add(1, Ordering::Relaxed);
+///         self.local_event_count += 1;
+///     }
+/// }
+///
+/// let mut log = EventLog::new();
+/// let mut clone = log.clone();
+///
+/// log.record("first");
+/// clone.record("second");
+///
+/// assert_eq!(log.event_count.load(Ordering::Relaxed), 2);
+/// assert_eq!(log.local_event_count, 1);
+/// ```
+///
+/// * `#[linked(parent)]` - links every instance to the instance of a parent family on the same
+///   thread. The field type `P` (a linked object) becomes [`linked::Ref<P>`][Ref], which
+///   dereferences to `P`. In `linked::new!`, you initialize the field with the parent family,
+///   either as a [`linked::Family<P>`][Family] or as a [`linked::InstancePerThread<P>`][1] (to
+///   share the per-thread parent instances with other code using the same `InstancePerThread`).
+///   Every instance created on a thread then references the same parent instance on that thread,
+///   which lives as long as any instance references it.
+///
+/// ```
+/// use std::cell::Cell;
+///
+/// use linked::Object;
+///
+/// // The parent: one instance per thread, shared by all requests handled on that thread.
+/// #[linked::object]
+/// struct Service {
+///     handled_requests: Cell<usize>,
+/// }
+///
+/// impl Service {
+///     pub fn new() -> Self {
+///         linked::new!(Self {
+///             handled_requests: Cell::new(0),
+///         })
+///     }
+/// }
+///
+/// // The child: created per request, with access to the service instance of the current thread.
+/// #[linked::object]
+/// struct Request {
+///     #[linked(parent)]
+///     service: Service,
+/// }
+///
+/// impl Request {
+///     pub fn new(service: linked::Family<Service>) -> Self {
+///         linked::new!(Self {
+///             service: service.clone(),
+///         })
+///     }
+///
+///     pub fn handle(&self) {
+///         let service = &self.service;
+///         service.handled_requests.set(service.handled_requests.get() + 1);
+///     }
+/// }
+///
+/// let first = Request::new(Service::new().family());
+/// let second = first.clone();
+///
+/// first.handle();
+/// second.handle();
+///
+/// // Both requests are on the same thread, so they share one service instance.
+/// assert_eq!(first.service.handled_requests.get(), 2);
+/// ```
+///
+/// [1]: crate::InstancePerThread
+///
 /// # Constraints
 ///
 /// Only structs defined in the named fields form are supported (no tuple structs).
+///
+/// The attribute adds a hidden field to the struct, so derives that need to know all the fields
+/// (e.g. `Default` or `PartialEq`) cannot be used and `Clone` is implemented by the attribute
+/// itself. Place `#[linked::object]` before any `#[derive]` attributes - derives placed before it
+/// do not see the hidden field.
+///
+/// # Troubleshooting
+///
+/// If the compiler reports a missing `__private_linked_link` field, the struct is being
+/// constructed without [`linked::new!`][crate::new] (e.g. `Self { ... }` in a constructor or a
+/// derive placed before `#[linked::object]` that constructs the struct). Every new family must
+/// be created via `linked::new!`, with other instances created from existing instances.
 pub use linked_macros::__macro_linked_object as object;
 
 // This is so procedural macros can produce code which refers to
@@ -0,0 +1,259 @@
+use std::{
+    sync::{
+        Arc, RwLock,
+        atomic::{self, AtomicBool, AtomicU64},
+    },
+    thread::ThreadId,
+};
+
+use crate::{ERR_POISONED_LOCK, FamilyId};
+
+/// Subscribes to lifecycle events of all instances of all [linked objects][crate] in the process,
+/// for use by diagnostics tooling (e.g. to chart instance churn per family).
+///
+/// The callback is called on the thread where the event occurs, immediately after the instance
+/// is created or immediately before it is dropped. The callback may be called concurrently on
+/// multiple threads and must not take long, as it delays the code creating or dropping instances.
+///
+/// Events are delivered until the returned [`LifecycleSubscription`] is dropped.
+///
+/// This is only available with the `lifecycle_events` Cargo feature. Without the feature,
+/// the linked object machinery has no overhead from lifecycle event tracking.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use linked::{LifecycleEventKind, Object};
+///
+/// #[linked::object]
+/// struct Thing {}
+///
+/// impl Thing {
+///     pub fn new() -> Self {
+///         linked::new!(Self {})
+///     }
+/// }
+///
+/// let events = Arc::new(Mutex::new(Vec::new()));
+///
+/// let subscription = linked::subscribe_lifecycle_events({
+///     let events = Arc::clone(&events);
+///     move |event| events.lock().unwrap().push((event.kind(), event.family_id()))
+/// });
+///
+/// let thing = Thing::new();
+/// let family_id = thing.family().id();
+/// drop(thing);
+///
+/// drop(subscription);
+///
+/// let events = events.lock().unwrap();
+/// assert!(events.contains(&(LifecycleEventKind::Created, family_id)));
+/// assert!(events.contains(&(LifecycleEventKind::Dropped, family_id)));
+/// ```
+#[must_use = "events are only delivered while the subscription exists"]
+pub fn subscribe_lifecycle_events(
+    callback: impl Fn(&LifecycleEvent) + Send + Sync + 'static,
+) -> LifecycleSubscription {
+    let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, atomic::Ordering::Relaxed);
+
+    let mut subscribers = SUBSCRIBERS.write().expect(ERR_POISONED_LOCK);
+    subscribers.push((id, Arc::new(callback)));
+    HAS_SUBSCRIBERS.store(true, atomic::Ordering::Release);
+
+    LifecycleSubscription { id }
+}
+
+/// A subscription to lifecycle events created via [`subscribe_lifecycle_events()`].
+///
+/// Events are delivered to the callback of the subscription until this is dropped.
+#[derive(Debug)]
+pub struct LifecycleSubscription {
+    id: u64,
+}
+
+impl Drop for LifecycleSubscription {
+    fn drop(&mut self) {
+        let mut subscribers = SUBSCRIBERS.write().expect(ERR_POISONED_LOCK);
+        subscribers.retain(|(id, _)| *id != self.id);
+        HAS_SUBSCRIBERS.store(!subscribers.is_empty(), atomic::Ordering::Release);
+    }
+}
+
+/// Describes the creation or dropping of one instance of a linked object.
+#[derive(Clone, Debug)]
+pub struct LifecycleEvent {
+    kind: LifecycleEventKind,
+    type_name: &'static str,
+    family_id: FamilyId,
+    thread_id: ThreadId,
+}
+
+impl LifecycleEvent {
+    /// What happened to the instance.
+    #[must_use]
+    pub fn kind(&self) -> LifecycleEventKind {
+        self.kind
+    }
+
+    /// The name of the linked object type, as returned by [`std::any::type_name()`].
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The family that the instance belongs to.
+    #[must_use]
+    pub fn family_id(&self) -> FamilyId {
+        self.family_id
+    }
+
+    /// The thread that the instance is counted against, which is the thread that created it.
+    ///
+    /// For [`LifecycleEventKind::Dropped`] events, this is not necessarily the thread that drops
+    /// the instance, as instances of `Send` types can be moved to other threads.
+    #[must_use]
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+}
+
+/// The kind of a [`LifecycleEvent`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum LifecycleEventKind {
+    /// A new instance was created.
+    Created,
+
+    /// An instance is being dropped.
+    Dropped,
+}
+
+/// Publishes a lifecycle event to all subscribers, if there are any.
+#[inline]
+pub(crate) fn publish(
+    kind: LifecycleEventKind,
+    type_name: &'static str,
+    family_id: FamilyId,
+    thread_id: ThreadId,
+) {
+    if !HAS_SUBSCRIBERS.load(atomic::Ordering::Acquire) {
+        return;
+    }
+
+    // We do not hold the lock while calling the subscribers, as they may create or drop instances
+    // of linked objects themselves, which would publish more events.
+    let subscribers = SUBSCRIBERS
+        .read()
+        .expect(ERR_POISONED_LOCK)
+        .iter()
+        .map(|(_, callback)| Arc::clone(callback))
+        .collect::<Vec<_>>();
+
+    let event = LifecycleEvent {
+        kind,
+        type_name,
+        family_id,
+        thread_id,
+    };
+
+    for callback in subscribers {
+        callback(&event);
+    }
+}
+
+type Subscriber = Arc<dyn Fn(&LifecycleEvent) + Send + Sync>;
+
+// Allows skipping the lock when nobody is subscribed. Only modified under the write lock.
+static HAS_SUBSCRIBERS: AtomicBool = AtomicBool::new(false);
+
+static SUBSCRIBERS: RwLock<Vec<(u64, Subscriber)>> = RwLock::new(Vec::new());
+
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, thread};
+
+    use super::*;
+    use crate::Object;
+
+    #[linked::object]
+    struct Thing {}
+
+    impl Thing {
+        fn new() -> Self {
+            linked::new!(Self {})
+        }
+    }
+
+    #[test]
+    fn publishes_events_while_subscribed() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let subscription = subscribe_lifecycle_events({
+            let events = Arc::clone(&events);
+            move |event| events.lock().unwrap().push(event.clone())
+        });
+
+        let thing = Thing::new();
+        let family = thing.family();
+
+        let remote_thread_id = thread::spawn({
+            let family = family.clone();
+
+            move || {
+                let _remote: Thing = family.into();
+                thread::current().id()
+            }
+        })
+        .join()
+        .unwrap();
+
+        drop(subscription);
+        drop(thing);
+
+        // Other tests may create instances concurrently, so we only look at our own family.
+        let events = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.family_id() == family.id())
+            .map(|event| (event.kind(), event.thread_id()))
+            .collect::<Vec<_>>();
+
+        let local_thread_id = thread::current().id();
+
+        assert_eq!(
+            events,
+            vec![
+                (LifecycleEventKind::Created, local_thread_id),
+                (LifecycleEventKind::Created, remote_thread_id),
+                (LifecycleEventKind::Dropped, remote_thread_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn event_describes_type() {
+        let type_names = Arc::new(Mutex::new(Vec::new()));
+
+        let subscription = subscribe_lifecycle_events({
+            let type_names = Arc::clone(&type_names);
+            move |event| type_names.lock().unwrap().push(event.type_name())
+        });
+
+        let thing = Thing::new();
+        drop(subscription);
+        drop(thing);
+
+        assert!(
+            type_names
+                .lock()
+                .unwrap()
+                .contains(&std::any::type_name::<Thing>())
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use loom::model;
+
+/// Model-checks `f` with [`loom`][1], exploring the possible interleavings of the
+/// threads it spawns via `loom::thread::spawn()`.
+///
+/// This is only available when building with `--cfg loom`, in which case the state shared
+/// between the instances of a family (instance counts, end-of-life callbacks, ...) uses `loom`
+/// synchronization primitives. This allows linked objects whose shared state also uses `loom`
+/// primitives to be model-checked end to end, including the family machinery.
+///
+/// Prefer this over calling `loom::model()` directly, as it also clears the families registered
+/// by [`linked::instances!`][2] and related macros before every iteration of the model, so that
+/// no `loom` state leaks from one iteration to the next.
+///
+/// # Limitations
+///
+/// `loom` runs all its threads on a single operating system thread, so mechanisms that rely on
+/// thread-local storage (e.g. [`linked::thread_local_rc!`][3] and
+/// [`linked::InstancePerThread<T>`][4]) do not give each `loom` thread its own instance. Create
+/// the instances for each `loom` thread via [`Family<T>`][5] or by cloning an instance instead.
+///
+/// # Example
+///
+/// ```ignore
+/// use linked::Object; // This brings .family() into scope.
+/// use loom::sync::Arc;
+/// use loom::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[linked::object]
+/// struct Counter {
+///     value: Arc<AtomicUsize>,
+/// }
+/// #
+/// # impl Counter {
+/// #     fn new() -> Self {
+/// #         let value = Arc::new(AtomicUsize::new(0));
+/// #         linked::new!(Self { value: Arc::clone(&value) })
+/// #     }
+/// #
+/// #     fn increment(&self) {
+/// #         self.value.fetch_add(1, Ordering::Relaxed);
+/// #     }
+/// #
+/// #     fn value(&self) -> usize {
+/// #         self.value.load(Ordering::Relaxed)
+/// #     }
+/// # }
+///
+/// linked::loom_model(|| {
+///     let counter = Counter::new();
+///
+///     let other = loom::thread::spawn({
+///         let family = counter.family();
+///         move || {
+///             let counter: Counter = family.into();
+///             counter.increment();
+///         }
+///     });
+///
+///     counter.increment();
+///     other.join().unwrap();
+///
+///     assert_eq!(counter.value(), 2);
+/// });
+/// ```
+///
+/// [1]: https://docs.rs/loom
+/// [2]: crate::instances
+/// [3]: crate::thread_local_rc
+/// [4]: crate::InstancePerThread
+/// [5]: crate::Family
+pub fn loom_model<F>(f: F)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    model(move || {
+        crate::__private_clear_linked_variables();
+        f();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use loom::sync::Arc;
+    use loom::sync::atomic::{self, AtomicUsize};
+    use loom::thread;
+
+    use super::*;
+    use crate::Object;
+
+    #[linked::object]
+    struct Counter {
+        value: Arc<AtomicUsize>,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            let value = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                value: Arc::clone(&value),
+            })
+        }
+
+        fn increment(&self) {
+            self.value.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+
+        fn value(&self) -> usize {
+            self.value.load(atomic::Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn loom_concurrent_instances() {
+        loom_model(|| {
+            let counter = Counter::new();
+            let family = counter.family();
+
+            let end_of_life_calls = Arc::new(AtomicUsize::new(0));
+            family.on_last_instance_dropped({
+                let end_of_life_calls = Arc::clone(&end_of_life_calls);
+                move || {
+                    end_of_life_calls.fetch_add(1, atomic::Ordering::Relaxed);
+                }
+            });
+
+            let other = thread::spawn({
+                let family = family.clone();
+
+                move || {
+                    let counter: Counter = family.into();
+                    counter.increment();
+                }
+            });
+
+            counter.increment();
+            other.join().unwrap();
+
+            assert_eq!(counter.value(), 2);
+            assert_eq!(family.instance_count(), 1);
+
+            drop(counter);
+            drop(family);
+            assert_eq!(end_of_life_calls.load(atomic::Ordering::Relaxed), 1);
+        });
+    }
+}
@@ -79,7 +79,9 @@ macro_rules! new {
     // which essentially does not touch/change them.
     (Self { $($field:ident $( : $value:expr )?),* $(,)? }) => {
         $crate::__private::new(move |__private_linked_link| Self {
-            $($field: $crate::new!(@expand $field $( : $value )?)),*,
+            $($field: $crate::__private::paste!(Self::[< __private_linked_init_ $field >](
+                &__private_linked_link,
+                || $crate::new!(@expand $field $( : $value )?)))),*,
             __private_linked_link,
         })
     };
@@ -90,3 +92,123 @@ macro_rules! new {
         $field
     };
 }
+
+/// Defines the template used to create every instance in a linked object family, with the
+/// template being able to fail.
+///
+/// This is the same as [`linked::new!`][crate::new] except that the field initializers may use
+/// the `?` operator, with the macro returning a `Result<Self, E>` instead of `Self`. The error
+/// type `E` is determined by the return type of the function calling the macro and any errors
+/// returned by `?` are converted into it via `From`, just like with `?` in the function itself.
+///
+/// The template is first evaluated to create the first instance of the family. If this fails,
+/// the error is returned and no family is created.
+///
+/// # Panics
+///
+/// All instances other than the first are created from an existing family (e.g. by cloning an
+/// instance), which offers no way to return an error. If the template fails when creating any
+/// instance other than the first, this panics. Fallible field initializers are therefore best
+/// used for [`#[linked(shared)]`][crate::object] fields, whose values are only created once
+/// per family.
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use std::sync::Mutex;
+///
+/// # struct ConnectionPool { address: String }
+/// # impl ConnectionPool {
+/// #     fn open(address: &str) -> io::Result<Self> {
+/// #         if address.is_empty() {
+/// #             return Err(io::Error::new(io::ErrorKind::InvalidInput, "no address"));
+/// #         }
+/// #         Ok(Self { address: address.to_string() })
+/// #     }
+/// # }
+/// #[linked::object]
+/// struct Database {
+///     // Opened once by the first instance and shared by all instances in the family.
+///     #[linked(shared)]
+///     pool: Mutex<ConnectionPool>,
+///
+///     queries_executed: usize,
+/// }
+///
+/// impl Database {
+///     pub fn connect(address: String) -> io::Result<Self> {
+///         linked::new_try!(Self {
+///             pool: Mutex::new(ConnectionPool::open(&address)?),
+///             queries_executed: 0,
+///         })
+///     }
+/// }
+///
+/// let database = Database::connect("db.example.com".to_string()).unwrap();
+/// let clone = database.clone();
+/// assert_eq!(clone.pool.lock().unwrap().address, "db.example.com");
+///
+/// assert!(Database::connect(String::new()).is_err());
+/// ```
+///
+/// # Asynchronous setup
+///
+/// The template is evaluated synchronously whenever a new instance is created, so it cannot
+/// `.await` anything. If the state shared by the family requires asynchronous setup, perform the
+/// setup in the `async` constructor before calling the macro and capture the result in the
+/// template, which avoids blocking inside the template:
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+///
+/// # struct ConnectionPool;
+/// # impl ConnectionPool {
+/// #     async fn connect(_address: &str) -> io::Result<Self> { Ok(Self) }
+/// # }
+/// #[linked::object]
+/// struct Database {
+///     pool: Arc<ConnectionPool>,
+/// }
+///
+/// impl Database {
+///     pub async fn connect(address: &str) -> io::Result<Self> {
+///         let pool = Arc::new(ConnectionPool::connect(address).await?);
+///
+///         linked::new_try!(Self {
+///             pool: Arc::clone(&pool),
+///         })
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! new_try {
+    // `new_try!(Self)` is forwarded to `new_try!(Self {})`
+    (Self) => {
+        $crate::new_try!(Self {})
+    };
+    // Special case if there are no field initializers (for proper comma handling).
+    (Self {}) => {
+        $crate::__private::new_try(move |__private_linked_link| {
+            ::std::result::Result::Ok(Self {
+                __private_linked_link,
+            })
+        })
+    };
+    // Typical case - struct expression with zero or more field initializers. Each field
+    // initializer is evaluated in a closure returning `Result`, so `?` can be used in it.
+    (Self { $($field:ident $( : $value:expr )?),* $(,)? }) => {
+        $crate::__private::new_try(move |__private_linked_link| {
+            ::std::result::Result::Ok(Self {
+                $($field: match $crate::__private::paste!(Self::[< __private_linked_try_init_ $field >](
+                    &__private_linked_link,
+                    || ::std::result::Result::Ok($crate::new!(@expand $field $( : $value )?)))) {
+                    ::std::result::Result::Ok(value) => value,
+                    ::std::result::Result::Err(error) => return ::std::result::Result::Err(error),
+                }),*,
+                __private_linked_link,
+            })
+        })
+    };
+}
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::thread::ThreadId;
+
+use metrics::{Label, SharedString};
+
+use crate::{BuildThreadIdHasher, InstanceRegistry};
+
+/// Publishes the state registered by [linked object][crate] instances in an [`InstanceRegistry`]
+/// as a [`metrics`](https://docs.rs/metrics) counter or gauge.
+///
+/// The values of the instances are published either as one series per thread or aggregated over
+/// all threads.
+///
+/// Counters are the canonical use case for linked objects - each thread increments the counter of
+/// its own instance without contending with other threads, and the per-instance values only need
+/// to be combined when they are reported. The adapter does the combining: on every call to
+/// [`publish()`][Self::publish], it reads the value of every live instance via the registry and
+/// reports the result via the `metrics` recorder that is active at that time. Call it before
+/// every scrape of the metrics exporter or periodically, depending on the exporter.
+///
+/// With [`per_thread()`][Self::per_thread], the adapter reports one series per thread, labeled
+/// `thread` with the debug representation of the [`ThreadId`] of the thread that registered the
+/// state (e.g. `ThreadId(3)`). Instances registered by the same thread are summed up.
+///
+/// This is only available with the `metrics` Cargo feature enabled.
+///
+/// # Dropped instances
+///
+/// The state of an instance stops being reported once the instance is dropped, so a counter
+/// decreases if an instance that contributed to it is dropped. Keep the instances alive for as
+/// long as their thread (e.g. via `.with()` on a [`linked::instances!`][1] static variable) or
+/// publish a gauge instead.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// use linked::{InstanceRegistry, MetricsAdapter, RegisteredInstance};
+///
+/// #[linked::object]
+/// struct RequestCounter {
+///     requests: RegisteredInstance<AtomicU64>,
+/// }
+///
+/// impl RequestCounter {
+///     pub fn new(registry: InstanceRegistry<AtomicU64>) -> Self {
+///         linked::new!(Self {
+///             requests: registry.register(AtomicU64::new(0)),
+///         })
+///     }
+///
+///     pub fn record_request(&self) {
+///         self.requests.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let registry = InstanceRegistry::new();
+///
+/// let requests_metric =
+///     MetricsAdapter::counter("http_requests", registry.clone(), |requests: &AtomicU64| {
+///         requests.load(Ordering::Relaxed)
+///     })
+///     .per_thread();
+///
+/// let counter = RequestCounter::new(registry);
+/// counter.record_request();
+///
+/// // Typically called by the metrics exporter before every scrape.
+/// requests_metric.publish();
+/// ```
+///
+/// [1]: crate::instances
+pub struct MetricsAdapter<S> {
+    registry: InstanceRegistry<S>,
+    name: SharedString,
+    read_value: ReadValue<S>,
+    per_thread: bool,
+}
+
+enum ReadValue<S> {
+    Counter(fn(&S) -> u64),
+    Gauge(fn(&S) -> f64),
+}
+
+impl<S> MetricsAdapter<S> {
+    /// Creates an adapter that publishes the sum of the values read from the registered state
+    /// via `read_value` as the counter `name`.
+    #[must_use]
+    pub fn counter(
+        name: impl Into<SharedString>,
+        registry: InstanceRegistry<S>,
+        read_value: fn(&S) -> u64,
+    ) -> Self {
+        Self {
+            registry,
+            name: name.into(),
+            read_value: ReadValue::Counter(read_value),
+            per_thread: false,
+        }
+    }
+
+    /// Creates an adapter that publishes the sum of the values read from the registered state
+    /// via `read_value` as the gauge `name`.
+    #[must_use]
+    pub fn gauge(
+        name: impl Into<SharedString>,
+        registry: InstanceRegistry<S>,
+        read_value: fn(&S) -> f64,
+    ) -> Self {
+        Self {
+            registry,
+            name: name.into(),
+            read_value: ReadValue::Gauge(read_value),
+            per_thread: false,
+        }
+    }
+
+    /// Publishes one series per thread instead of one series aggregated over all threads.
+    #[must_use]
+    pub fn per_thread(mut self) -> Self {
+        self.per_thread = true;
+        self
+    }
+
+    /// Reads the value of every live instance and publishes the result via the active
+    /// `metrics` recorder.
+    pub fn publish(&self) {
+        match self.read_value {
+            ReadValue::Counter(read_value) => {
+                for (labels, total) in self.totals(read_value, u64::saturating_add) {
+                    metrics::counter!(self.name.clone(), labels).absolute(total);
+                }
+            }
+            ReadValue::Gauge(read_value) => {
+                for (labels, total) in self.totals(read_value, |a, b| a + b) {
+                    metrics::gauge!(self.name.clone(), labels).set(total);
+                }
+            }
+        }
+    }
+
+    // Sums up the values of the instances, per thread or over all threads, returning the labels
+    // of each series together with its value.
+    fn totals<V>(&self, read_value: fn(&S) -> V, add: fn(V, V) -> V) -> Vec<(Vec<Label>, V)>
+    where
+        V: Copy + Default,
+    {
+        if !self.per_thread {
+            let total = self
+                .registry
+                .aggregate(V::default(), |total, state| add(total, read_value(state)));
+
+            return vec![(Vec::new(), total)];
+        }
+
+        let mut per_thread: HashMap<ThreadId, V, BuildThreadIdHasher> =
+            HashMap::with_hasher(BuildThreadIdHasher);
+
+        self.registry
+            .for_each_instance_with_thread(|thread_id, state| {
+                let total = per_thread.entry(thread_id).or_default();
+                *total = add(*total, read_value(state));
+            });
+
+        per_thread
+            .into_iter()
+            .map(|(thread_id, total)| {
+                let labels = vec![Label::new(THREAD_LABEL, format!("{thread_id:?}"))];
+                (labels, total)
+            })
+            .collect()
+    }
+}
+
+impl<S> Debug for MetricsAdapter<S> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsAdapter")
+            .field("registry", &self.registry)
+            .field("name", &self.name)
+            .field(
+                "kind",
+                &match self.read_value {
+                    ReadValue::Counter(_) => "counter",
+                    ReadValue::Gauge(_) => "gauge",
+                },
+            )
+            .field("per_thread", &self.per_thread)
+            .finish()
+    }
+}
+
+const THREAD_LABEL: &str = "thread";
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use metrics::{
+        Counter, CounterFn, Gauge, GaugeFn, Histogram, Key, KeyName, Metadata, Recorder, Unit,
+    };
+
+    use super::*;
+
+    // Records the last value published for each series, keyed by the name and labels.
+    #[derive(Default)]
+    struct TestRecorder {
+        values: Arc<Mutex<HashMap<String, f64>>>,
+    }
+
+    impl TestRecorder {
+        fn value(&self, series: &str) -> Option<f64> {
+            self.values.lock().unwrap().get(series).copied()
+        }
+
+        fn series(&self, key: &Key) -> Arc<Series> {
+            let labels = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect::<Vec<_>>();
+
+            Arc::new(Series {
+                name: format!("{}{labels:?}", key.name()),
+                values: Arc::clone(&self.values),
+            })
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.series(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.series(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    struct Series {
+        name: String,
+        values: Arc<Mutex<HashMap<String, f64>>>,
+    }
+
+    impl Series {
+        fn record(&self, value: f64) {
+            self.values.lock().unwrap().insert(self.name.clone(), value);
+        }
+    }
+
+    impl CounterFn for Series {
+        fn increment(&self, _: u64) {
+            unreachable!("the adapter only sets absolute values");
+        }
+
+        #[expect(clippy::cast_precision_loss, reason = "test values are small")]
+        fn absolute(&self, value: u64) {
+            self.record(value as f64);
+        }
+    }
+
+    impl GaugeFn for Series {
+        fn increment(&self, _: f64) {
+            unreachable!("the adapter only sets absolute values");
+        }
+
+        fn decrement(&self, _: f64) {
+            unreachable!("the adapter only sets absolute values");
+        }
+
+        fn set(&self, value: f64) {
+            self.record(value);
+        }
+    }
+
+    fn load(value: &AtomicU64) -> u64 {
+        value.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn publishes_aggregated_counter() {
+        let registry = InstanceRegistry::new();
+        let _first = registry.register(AtomicU64::new(3));
+        let _second = registry.register(AtomicU64::new(4));
+
+        let recorder = TestRecorder::default();
+        let adapter = MetricsAdapter::counter("requests", registry, load);
+
+        metrics::with_local_recorder(&recorder, || adapter.publish());
+
+        assert_eq!(recorder.value("requests[]"), Some(7.0));
+    }
+
+    #[test]
+    fn publishes_gauge_per_thread() {
+        let registry = InstanceRegistry::new();
+        let _first = registry.register(1.5_f64);
+        let _second = registry.register(2.0_f64);
+
+        let (remote_thread_id, _remote) = thread::spawn({
+            let registry = registry.clone();
+            move || (thread::current().id(), registry.register(10.0_f64))
+        })
+        .join()
+        .unwrap();
+
+        let recorder = TestRecorder::default();
+        let adapter = MetricsAdapter::gauge("queue_depth", registry, |depth| *depth).per_thread();
+
+        metrics::with_local_recorder(&recorder, || adapter.publish());
+
+        let local_series = format!("queue_depth[\"thread={:?}\"]", thread::current().id());
+        let remote_series = format!("queue_depth[\"thread={remote_thread_id:?}\"]");
+
+        assert_eq!(recorder.value(&local_series), Some(3.5));
+        assert_eq!(recorder.value(&remote_series), Some(10.0));
+    }
+}
@@ -1,14 +1,173 @@
 // Copyright (c) Microsoft Corporation.
 // Copyright (c) Folo authors.
 
+use crate::__private::Link;
 use crate::Family;
 
 /// Operations available on every instance of a [linked object][crate].
 ///
 /// The only supported way to implement this is via [`#[linked::object]`][crate::object].
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a linked object",
+    label = "not a linked object",
+    note = "apply `#[linked::object]` to the type to make it a linked object"
+)]
 pub trait Object: From<Family<Self>> + Sized + Clone + 'static {
     /// The object family that the current instance is linked to.
     ///
     /// The returned object can be used to create additional instances linked to the same family.
     fn family(&self) -> Family<Self>;
+
+    /// Whether the current instance is linked to the same family as `other`.
+    #[must_use]
+    fn is_same_family(&self, other: &Self) -> bool {
+        self.family() == other.family()
+    }
+
+    /// Whether the version of the family has been bumped via [`Family::bump_version()`] since
+    /// the current instance was created or last refreshed via
+    /// [`refresh_if_stale()`][Self::refresh_if_stale].
+    #[must_use]
+    #[inline]
+    fn is_stale(&self) -> bool {
+        self.__private_link().stale_version().is_some()
+    }
+
+    /// Calls `refresh` with the current instance if it is [stale][Self::is_stale], after which
+    /// the instance is considered up to date with the current version of the family.
+    ///
+    /// If the version is bumped again while `refresh` is running, the instance remains stale.
+    ///
+    /// Returns whether `refresh` was called.
+    #[inline]
+    fn refresh_if_stale(&mut self, refresh: impl FnOnce(&mut Self)) -> bool {
+        let Some(version) = self.__private_link().stale_version() else {
+            return false;
+        };
+
+        refresh(self);
+        self.__private_link().observe_version(version);
+        true
+    }
+
+    /// Note: this function exists to serve the inner workings of the `#[linked::object]` macro
+    /// and should not be used directly. It is not part of the public API and may be removed or
+    /// changed at any time.
+    ///
+    /// The link that connects the current instance to the other instances in its family.
+    #[doc(hidden)]
+    fn __private_link(&self) -> &Link<Self>;
+
+    /// Note: this function exists to serve the inner workings of the `#[linked::object]` macro
+    /// and should not be used directly. It is not part of the public API and may be removed or
+    /// changed at any time.
+    ///
+    /// Called on every new instance of the family, immediately after it has been created.
+    #[doc(hidden)]
+    #[inline]
+    fn __private_on_instance_created(&mut self) {}
+}
+
+/// Creates a new instance linked to the same family as `value`.
+///
+/// This is what the `Clone` implementation generated by [`#[linked::object]`][crate::object]
+/// does. If you opt out of the generated implementation via
+/// `#[linked::object(custom_clone)]`, call this from your own `Clone` implementation to create
+/// the new instance and then adjust any fields that need special treatment.
+///
+/// # Example
+///
+/// ```
+/// # use std::sync::{Arc, Mutex};
+/// #[linked::object(custom_clone)]
+/// struct TokenCache {
+///     shared_tokens: Arc<Mutex<Vec<String>>>,
+///     local_hits: usize,
+/// }
+///
+/// impl TokenCache {
+///     pub fn new() -> Self {
+///         let shared_tokens = Arc::new(Mutex::new(Vec::new()));
+///
+///         linked::new!(Self {
+///             shared_tokens: Arc::clone(&shared_tokens),
+///             local_hits: 0,
+///         })
+///     }
+/// }
+///
+/// impl Clone for TokenCache {
+///     fn clone(&self) -> Self {
+///         let mut clone = linked::clone_linked(self);
+///
+///         // Carry over the local state instead of starting from scratch.
+///         clone.local_hits = self.local_hits;
+///         clone
+///     }
+/// }
+///
+/// let mut cache = TokenCache::new();
+/// cache.local_hits = 5;
+///
+/// let clone = cache.clone();
+/// assert_eq!(clone.local_hits, 5);
+/// ```
+#[must_use]
+pub fn clone_linked<T: Object>(value: &T) -> T {
+    value.family().into()
+}
+
+/// Panics if `a` and `b` are not instances of the same family, describing where each of the
+/// families was created.
+///
+/// Use this in APIs that combine multiple instances and rely on them sharing state, where mixing
+/// instances of different families would otherwise silently lead to divergent data.
+///
+/// The check is only performed in debug builds, or in any build if the `family_checks` Cargo
+/// feature is enabled. Otherwise, this does nothing. The creation site of a family is the code
+/// that invoked [`linked::new!`][crate::new] or [`linked::new_try!`][crate::new_try] for the first
+/// instance of the family and is unknown for families defined in static variables.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// #[linked::object]
+/// struct Ledger {
+///     entries: Arc<Mutex<Vec<i64>>>,
+/// }
+///
+/// impl Ledger {
+///     pub fn new() -> Self {
+///         let entries = Arc::new(Mutex::new(Vec::new()));
+///
+///         linked::new!(Self {
+///             entries: Arc::clone(&entries),
+///         })
+///     }
+///
+///     /// Moves an amount between two views of the same ledger.
+///     pub fn transfer(&self, to: &Self, amount: i64) {
+///         linked::debug_assert_same_family(self, to);
+///
+///         let mut entries = self.entries.lock().unwrap();
+///         entries.push(-amount);
+///         entries.push(amount);
+///     }
+/// }
+///
+/// let ledger = Ledger::new();
+/// ledger.transfer(&ledger.clone(), 100);
+/// ```
+#[track_caller]
+pub fn debug_assert_same_family<T: Object>(a: &T, b: &T) {
+    #[cfg(any(debug_assertions, feature = "family_checks"))]
+    a.family()
+        .assert_same_family(&b.family(), "the instances must belong to the same family");
+
+    #[cfg(not(any(debug_assertions, feature = "family_checks")))]
+    {
+        _ = (a, b);
+    }
 }
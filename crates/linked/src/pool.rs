@@ -0,0 +1,238 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use crate::ERR_POISONED_LOCK;
+
+/// An object pool implemented as a [linked object][crate], with each instance having its own
+/// local free list and all instances in the family sharing an overflow list.
+///
+/// Taking and returning items via the local free list does not require any synchronization.
+/// Only when the local free list is empty (on take) or full (on return) does the instance fall
+/// back to the overflow list shared by the family, which is protected by a mutex. This makes the
+/// pool efficient when each thread mostly reuses the items it has returned itself, while still
+/// allowing items to move between threads when one thread returns more than it takes.
+///
+/// # Usage
+///
+/// Create the pool via [`Pool::new()`], specifying the capacity of each instance's local free
+/// list and a function that creates new items when both the local and the overflow lists are
+/// empty. Use the pool via any of the standard instance management mechanisms, such as
+/// [`linked::thread_local_rc!`][1] or [`linked::InstancePerThread<T>`][2].
+///
+/// When an instance of the pool is dropped, any items in its local free list are moved to the
+/// overflow list, so they remain available to other instances in the family.
+///
+/// # Example
+///
+/// ```
+/// use linked::Pool;
+///
+/// linked::thread_local_rc!(static BUFFERS: Pool<Vec<u8>> = Pool::new(16, || Vec::with_capacity(4096)));
+///
+/// let mut buffer = BUFFERS.with(|pool| pool.take());
+/// buffer.extend_from_slice(b"hello");
+///
+/// // Items are returned as-is, so clear any state that must not be reused.
+/// buffer.clear();
+/// BUFFERS.with(|pool| pool.put(buffer));
+///
+/// assert_eq!(BUFFERS.with(|pool| pool.local_len()), 1);
+/// ```
+///
+/// [1]: crate::thread_local_rc
+/// [2]: crate::InstancePerThread
+#[linked::object]
+pub struct Pool<T>
+where
+    T: Send + 'static,
+{
+    local: RefCell<Vec<T>>,
+    local_capacity: usize,
+
+    overflow: Arc<Mutex<Vec<T>>>,
+    create_item: Arc<dyn Fn() -> T + Send + Sync>,
+}
+
+impl<T> Pool<T>
+where
+    T: Send + 'static,
+{
+    /// Creates a new pool, returning the first instance of a new family.
+    ///
+    /// Each instance keeps up to `local_capacity` items in its local free list, with the rest
+    /// going to the overflow list shared by the family. `create_item` is called to create a new
+    /// item when an item is requested and both lists are empty.
+    #[must_use]
+    pub fn new(local_capacity: usize, create_item: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        let overflow = Arc::new(Mutex::new(Vec::new()));
+        let create_item: Arc<dyn Fn() -> T + Send + Sync> = Arc::new(create_item);
+
+        linked::new!(Self {
+            local: RefCell::new(Vec::with_capacity(local_capacity)),
+            local_capacity,
+            overflow: Arc::clone(&overflow),
+            create_item: Arc::clone(&create_item),
+        })
+    }
+
+    /// Takes an item from the pool, creating a new one if the pool is empty.
+    ///
+    /// Items are taken from the local free list if possible, falling back to the shared overflow
+    /// list and only then creating a new item.
+    #[must_use]
+    pub fn take(&self) -> T {
+        if let Some(item) = self.local.borrow_mut().pop() {
+            return item;
+        }
+
+        // We release the lock before creating the item, as that may be arbitrarily expensive.
+        let item = self.overflow.lock().expect(ERR_POISONED_LOCK).pop();
+
+        item.unwrap_or_else(|| (self.create_item)())
+    }
+
+    /// Returns an item to the pool, making it available to be taken again.
+    ///
+    /// The item goes to the local free list if it has capacity remaining, otherwise to the shared
+    /// overflow list. Items are stored as-is - reset any state that must not be observed by
+    /// the next user of the item before returning it.
+    pub fn put(&self, item: T) {
+        let mut local = self.local.borrow_mut();
+
+        if local.len() < self.local_capacity {
+            local.push(item);
+        } else {
+            self.overflow.lock().expect(ERR_POISONED_LOCK).push(item);
+        }
+    }
+
+    /// The number of items in the local free list of the current instance.
+    #[must_use]
+    pub fn local_len(&self) -> usize {
+        self.local.borrow().len()
+    }
+
+    /// The number of items in the overflow list shared by all instances in the family.
+    #[must_use]
+    pub fn overflow_len(&self) -> usize {
+        self.overflow.lock().expect(ERR_POISONED_LOCK).len()
+    }
+}
+
+impl<T> Drop for Pool<T>
+where
+    T: Send + 'static,
+{
+    fn drop(&mut self) {
+        let local = mem::take(self.local.get_mut());
+
+        if !local.is_empty() {
+            self.overflow.lock().expect(ERR_POISONED_LOCK).extend(local);
+        }
+    }
+}
+
+impl<T> Debug for Pool<T>
+where
+    T: Send + 'static,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("local_len", &self.local_len())
+            .field("local_capacity", &self.local_capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{self, AtomicUsize},
+        thread,
+    };
+
+    use super::*;
+
+    fn counting_pool(local_capacity: usize) -> (Pool<usize>, Arc<AtomicUsize>) {
+        let created = Arc::new(AtomicUsize::new(0));
+
+        let pool = Pool::new(local_capacity, {
+            let created = Arc::clone(&created);
+            move || created.fetch_add(1, atomic::Ordering::Relaxed)
+        });
+
+        (pool, created)
+    }
+
+    #[test]
+    fn reuses_returned_items() {
+        let (pool, created) = counting_pool(2);
+
+        let first = pool.take();
+        let second = pool.take();
+        assert_eq!(created.load(atomic::Ordering::Relaxed), 2);
+
+        pool.put(first);
+        pool.put(second);
+        assert_eq!(pool.local_len(), 2);
+
+        // Most recently returned item is taken first.
+        assert_eq!(pool.take(), second);
+        assert_eq!(pool.take(), first);
+        assert_eq!(created.load(atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn excess_items_shared_via_overflow() {
+        let (pool, created) = counting_pool(1);
+
+        let first = pool.take();
+        let second = pool.take();
+
+        pool.put(first);
+        pool.put(second);
+        assert_eq!(pool.local_len(), 1);
+        assert_eq!(pool.overflow_len(), 1);
+
+        thread::spawn({
+            let pool = pool.clone();
+
+            move || {
+                // Nothing in the local list of this instance, so we get the overflow item.
+                assert_eq!(pool.local_len(), 0);
+                assert_eq!(pool.take(), second);
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(pool.overflow_len(), 0);
+        assert_eq!(created.load(atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn dropped_instance_moves_local_items_to_overflow() {
+        let (pool, created) = counting_pool(4);
+
+        thread::spawn({
+            let pool = pool.clone();
+
+            move || {
+                let item = pool.take();
+                pool.put(item);
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(pool.overflow_len(), 1);
+
+        _ = pool.take();
+        assert_eq!(created.load(atomic::Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,595 @@
+use std::{
+    ffi::CString,
+    fmt::{self, Debug, Display, Formatter},
+    io,
+    ops::Deref,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    process,
+    ptr::{self, NonNull},
+    str::FromStr,
+    sync::atomic::{
+        self, AtomicBool, AtomicI8, AtomicI16, AtomicI32, AtomicI64, AtomicIsize, AtomicU8,
+        AtomicU16, AtomicU32, AtomicU64, AtomicUsize,
+    },
+};
+
+/// A value shared by all processes that map the same shared memory segment, allowing
+/// [linked objects][crate] in multiple processes to collaborate.
+///
+/// Families of linked objects are limited to a single process - the family state lives on the
+/// heap of the process that created the family. In a multi-process architecture (e.g. a
+/// prefork-style service with one worker process per group of processors), the state shared by
+/// the workers can instead be placed into a `ProcessShared<T>`, which the linked object template
+/// captures just like any other shared state. The instances in each process are linked with each
+/// other via their family and with the instances in all other processes via the shared memory.
+///
+/// # Handle exchange
+///
+/// One process creates the shared memory segment via [`create()`][Self::create] and passes the
+/// [`handle()`][Self::handle] of the segment to the other processes, which map the same segment
+/// via [`open()`][Self::open]. The handle can be converted to and from a string (via `Display`
+/// and `FromStr`), so it can be passed in an environment variable, a command line argument or
+/// any other inter-process communication channel.
+///
+/// A mapping that exists when a process forks remains shared with the child process, so in a
+/// prefork-style service the parent process can simply create the segment before forking.
+///
+/// The segment is removed from the system when the `ProcessShared<T>` that created it is dropped.
+/// Processes that have already mapped the segment can keep using it but new processes can no
+/// longer open it after that.
+///
+/// # Shareable types
+///
+/// Only types that implement [`ProcessShareable`] can be placed in shared memory. These are types
+/// that do not contain any pointers (which would be meaningless in other processes), that use
+/// atomics for interior mutability and for which all-zero bytes is a valid value. The value is
+/// initialized to all-zero bytes when the segment is created.
+///
+/// All processes must use the same type `T` for the same segment. Opening a segment checks that
+/// the size of `T` matches the size used by the creator, which catches some mistakes but not all.
+///
+/// # Platform support
+///
+/// This is only available on Unix platforms with the `process_shared` Cargo feature enabled.
+///
+/// # Example
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// use linked::{ProcessShared, ProcessSharedHandle};
+///
+/// #[linked::object]
+/// struct RequestCounter {
+///     // Shared by all instances in all processes.
+///     total_requests: Arc<ProcessShared<AtomicU64>>,
+///
+///     // Separate for every instance.
+///     local_requests: Cell<u64>,
+/// }
+///
+/// impl RequestCounter {
+///     fn new(total_requests: ProcessShared<AtomicU64>) -> Self {
+///         let total_requests = Arc::new(total_requests);
+///
+///         linked::new!(Self {
+///             total_requests: Arc::clone(&total_requests),
+///             local_requests: Cell::new(0),
+///         })
+///     }
+///
+///     fn record_request(&self) {
+///         self.total_requests.fetch_add(1, Ordering::Relaxed);
+///         self.local_requests.set(self.local_requests.get() + 1);
+///     }
+/// }
+///
+/// // In the coordinating process.
+/// let total_requests = ProcessShared::<AtomicU64>::create().unwrap();
+/// let handle = total_requests.handle().to_string();
+/// let coordinator_counter = RequestCounter::new(total_requests);
+///
+/// // In a worker process, given the handle via e.g. an environment variable.
+/// let handle: ProcessSharedHandle = handle.parse().unwrap();
+/// let worker_counter = RequestCounter::new(ProcessShared::open(&handle).unwrap());
+/// worker_counter.record_request();
+///
+/// assert_eq!(coordinator_counter.total_requests.load(Ordering::Relaxed), 1);
+/// ```
+pub struct ProcessShared<T>
+where
+    T: ProcessShareable,
+{
+    segment: NonNull<Segment<T>>,
+    handle: ProcessSharedHandle,
+
+    // Whether this mapping created the segment and is therefore responsible for removing it.
+    is_creator: bool,
+}
+
+impl<T> ProcessShared<T>
+where
+    T: ProcessShareable,
+{
+    /// Creates a new shared memory segment holding an all-zero value of `T`.
+    ///
+    /// Other processes can map the same segment via [`open()`][Self::open], given the
+    /// [`handle()`][Self::handle] of the segment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operating system fails to create or map the segment.
+    pub fn create() -> io::Result<Self> {
+        loop {
+            let handle = ProcessSharedHandle::new_unique();
+            let name = handle.to_c_string()?;
+
+            // SAFETY: The name is a valid NUL-terminated string.
+            let fd = unsafe {
+                libc::shm_open(
+                    name.as_ptr(),
+                    libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+                    SEGMENT_PERMISSIONS,
+                )
+            };
+
+            if fd < 0 {
+                let error = io::Error::last_os_error();
+
+                // A leftover from an earlier process with the same process ID. We just move on to
+                // the next name instead of touching a segment that may still be in use.
+                if error.kind() == io::ErrorKind::AlreadyExists {
+                    continue;
+                }
+
+                return Err(error);
+            }
+
+            // SAFETY: We just opened the file descriptor and nobody else owns it.
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+            let result = Self::initialize(&fd, handle);
+
+            if result.is_err() {
+                unlink(&name);
+            }
+
+            return result;
+        }
+    }
+
+    /// Maps an existing shared memory segment created by [`create()`][Self::create],
+    /// typically in another process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the segment does not exist (e.g. because the `ProcessShared<T>` that
+    /// created it has been dropped), if it was created for a type of a different size or if the
+    /// operating system fails to map the segment.
+    pub fn open(handle: &ProcessSharedHandle) -> io::Result<Self> {
+        let name = handle.to_c_string()?;
+
+        // SAFETY: The name is a valid NUL-terminated string.
+        let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_RDWR, 0) };
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: We just opened the file descriptor and nobody else owns it.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // The creator may not have sized the segment yet or may have sized it for a smaller type,
+        // in which case we cannot map it.
+        let size = segment_size(&fd)?;
+
+        if size < size_of::<Segment<T>>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "shared memory segment {handle} is {size} bytes, which is too small for {}",
+                    std::any::type_name::<T>()
+                ),
+            ));
+        }
+
+        let result = Self {
+            segment: map::<T>(&fd)?,
+            handle: handle.clone(),
+            is_creator: false,
+        };
+
+        let segment = result.segment();
+
+        if segment.magic.load(atomic::Ordering::Acquire) != SEGMENT_MAGIC {
+            return Err(not_initialized(handle));
+        }
+
+        let value_size = segment.value_size.load(atomic::Ordering::Relaxed);
+
+        if value_size != size_of::<T>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "shared memory segment {handle} holds a value of {value_size} bytes but {} is {} bytes",
+                    std::any::type_name::<T>(),
+                    size_of::<T>()
+                ),
+            ));
+        }
+
+        Ok(result)
+    }
+
+    /// The handle that other processes can use to map the same shared memory segment
+    /// via [`open()`][Self::open].
+    #[must_use]
+    pub fn handle(&self) -> &ProcessSharedHandle {
+        &self.handle
+    }
+
+    fn initialize(fd: &OwnedFd, handle: ProcessSharedHandle) -> io::Result<Self> {
+        let len = libc::off_t::try_from(size_of::<Segment<T>>())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        // SAFETY: No safety requirements beyond passing valid arguments.
+        if unsafe { libc::ftruncate(fd.as_raw_fd(), len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // The operating system fills the segment with zero bytes, which is a valid value for
+        // both the header and the value, so all that remains is to fill in the header.
+        let result = Self {
+            segment: map::<T>(fd)?,
+            handle,
+            is_creator: true,
+        };
+
+        let segment = result.segment();
+        segment
+            .value_size
+            .store(size_of::<T>(), atomic::Ordering::Relaxed);
+        segment
+            .magic
+            .store(SEGMENT_MAGIC, atomic::Ordering::Release);
+
+        Ok(result)
+    }
+
+    fn segment(&self) -> &Segment<T> {
+        // SAFETY: The mapping remains valid until we are dropped and all-zero bytes are a valid
+        // value of the segment, so the segment is initialized no matter what other processes do.
+        unsafe { self.segment.as_ref() }
+    }
+}
+
+impl<T> Deref for ProcessShared<T>
+where
+    T: ProcessShareable,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.segment().value
+    }
+}
+
+impl<T> Drop for ProcessShared<T>
+where
+    T: ProcessShareable,
+{
+    fn drop(&mut self) {
+        // SAFETY: We mapped this range and nobody can be holding a reference into it, as all
+        // references we hand out are bound to our lifetime.
+        unsafe {
+            libc::munmap(self.segment.as_ptr().cast(), size_of::<Segment<T>>());
+        }
+
+        if self.is_creator {
+            if let Ok(name) = self.handle.to_c_string() {
+                unlink(&name);
+            }
+        }
+    }
+}
+
+impl<T> Debug for ProcessShared<T>
+where
+    T: ProcessShareable,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessShared")
+            .field("handle", &self.handle)
+            .field("is_creator", &self.is_creator)
+            .finish_non_exhaustive()
+    }
+}
+
+// SAFETY: The value is only accessed via shared references and `T: Sync` guarantees that this is
+// fine from any thread. The mapping itself can be unmapped from any thread.
+unsafe impl<T> Send for ProcessShared<T> where T: ProcessShareable {}
+
+// SAFETY: The value is only accessed via shared references and `T: Sync` guarantees that this is
+// fine from any thread.
+unsafe impl<T> Sync for ProcessShared<T> where T: ProcessShareable {}
+
+/// Identifies a shared memory segment created via [`ProcessShared::create()`], allowing other
+/// processes to map the same segment via [`ProcessShared::open()`].
+///
+/// Convert the handle to a string via `Display` (e.g. `handle.to_string()`) to pass it to another
+/// process and parse it back via `FromStr` (e.g. `text.parse::<ProcessSharedHandle>()`) there.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ProcessSharedHandle {
+    name: String,
+}
+
+impl ProcessSharedHandle {
+    fn new_unique() -> Self {
+        static NEXT_SEGMENT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let segment_id = NEXT_SEGMENT_ID.fetch_add(1, atomic::Ordering::Relaxed);
+
+        Self {
+            name: format!("{SEGMENT_NAME_PREFIX}{}-{segment_id}", process::id()),
+        }
+    }
+
+    fn to_c_string(&self) -> io::Result<CString> {
+        CString::new(self.name.as_str()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+}
+
+impl Display for ProcessSharedHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl FromStr for ProcessSharedHandle {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_valid = s.strip_prefix(SEGMENT_NAME_PREFIX).is_some_and(|rest| {
+            !rest.is_empty() && rest.bytes().all(|b| b.is_ascii_digit() || b == b'-')
+        });
+
+        if !is_valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{s}' is not a handle of a linked::ProcessShared segment"),
+            ));
+        }
+
+        Ok(Self {
+            name: s.to_string(),
+        })
+    }
+}
+
+/// Marks types that can be placed in shared memory via [`ProcessShared<T>`].
+///
+/// This is implemented for the atomic integer types and for arrays of shareable types. Implement
+/// it for your own `#[repr(C)]` structs composed of shareable types to share more complex state.
+///
+/// # Safety
+///
+/// Implementing types must:
+///
+/// * be valid when all their bytes are zero;
+/// * not contain any pointers or references, as these are meaningless in other processes;
+/// * not depend on any state outside the value itself, such as thread-local or global variables,
+///   as these are not shared with other processes;
+/// * be safe to mutate concurrently from multiple processes via shared references, which
+///   generally means using atomics for all interior mutability.
+pub unsafe trait ProcessShareable: Sync + 'static {}
+
+macro_rules! impl_process_shareable {
+    ($($t:ty),*) => {
+        $(
+            // SAFETY: Atomic integers are valid when zero, contain no pointers
+            // and are designed for concurrent mutation.
+            unsafe impl ProcessShareable for $t {}
+        )*
+    };
+}
+
+impl_process_shareable!(
+    AtomicBool,
+    AtomicI8,
+    AtomicI16,
+    AtomicI32,
+    AtomicI64,
+    AtomicIsize,
+    AtomicU8,
+    AtomicU16,
+    AtomicU32,
+    AtomicU64,
+    AtomicUsize
+);
+
+// SAFETY: An array satisfies the requirements if its elements do.
+unsafe impl<T, const N: usize> ProcessShareable for [T; N] where T: ProcessShareable {}
+
+// The contents of the shared memory segment. All-zero bytes are a valid value of the header.
+#[repr(C)]
+struct Segment<T> {
+    // Set to SEGMENT_MAGIC by the creator once the rest of the header has been filled in.
+    magic: AtomicU64,
+
+    // The size of the value, used to detect processes disagreeing on the type of the value.
+    value_size: AtomicUsize,
+
+    value: T,
+}
+
+// "linkedSH" - identifies initialized segments.
+const SEGMENT_MAGIC: u64 = 0x6C69_6E6B_6564_5348;
+
+const SEGMENT_NAME_PREFIX: &str = "/linked-";
+
+// Only processes of the same user can map the segment.
+const SEGMENT_PERMISSIONS: libc::mode_t = 0o600;
+
+fn map<T>(fd: &OwnedFd) -> io::Result<NonNull<Segment<T>>> {
+    // SAFETY: No safety requirements beyond passing valid arguments.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            size_of::<Segment<T>>(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd.as_raw_fd(),
+            0,
+        )
+    };
+
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Mappings are page-aligned, which satisfies the alignment of any reasonable type.
+    assert!(
+        ptr.cast::<Segment<T>>().is_aligned(),
+        "shared memory mapping is not sufficiently aligned for {}",
+        std::any::type_name::<T>()
+    );
+
+    Ok(NonNull::new(ptr.cast()).expect("a successful mapping is never at address zero"))
+}
+
+fn segment_size(fd: &OwnedFd) -> io::Result<usize> {
+    // SAFETY: All-zero bytes is a valid stat.
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+
+    // SAFETY: No safety requirements beyond passing valid arguments.
+    if unsafe { libc::fstat(fd.as_raw_fd(), &raw mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(usize::try_from(stat.st_size).unwrap_or(0))
+}
+
+fn unlink(name: &CString) {
+    // SAFETY: The name is a valid NUL-terminated string. If this fails, there is nothing we can
+    // do about it - the segment just lingers until the system is restarted.
+    unsafe {
+        libc::shm_unlink(name.as_ptr());
+    }
+}
+
+fn not_initialized(handle: &ProcessSharedHandle) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("shared memory segment {handle} has not been initialized by its creator yet"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+    use crate::Object;
+
+    #[test]
+    fn mappings_share_value() {
+        let created = ProcessShared::<AtomicU64>::create().unwrap();
+        let opened = ProcessShared::<AtomicU64>::open(created.handle()).unwrap();
+
+        created.fetch_add(1, atomic::Ordering::Relaxed);
+        opened.fetch_add(2, atomic::Ordering::Relaxed);
+
+        assert_eq!(created.load(atomic::Ordering::Relaxed), 3);
+        assert_eq!(opened.load(atomic::Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn handle_round_trips_through_string() {
+        let created = ProcessShared::<[AtomicU32; 4]>::create().unwrap();
+        let handle: ProcessSharedHandle = created.handle().to_string().parse().unwrap();
+
+        assert_eq!(&handle, created.handle());
+
+        let opened = ProcessShared::<[AtomicU32; 4]>::open(&handle).unwrap();
+        opened.last().unwrap().store(42, atomic::Ordering::Relaxed);
+
+        assert_eq!(created.last().unwrap().load(atomic::Ordering::Relaxed), 42);
+    }
+
+    #[test]
+    fn invalid_handle_rejected() {
+        "/tmp/something".parse::<ProcessSharedHandle>().unwrap_err();
+        "/linked-".parse::<ProcessSharedHandle>().unwrap_err();
+        "/linked-1-2/x".parse::<ProcessSharedHandle>().unwrap_err();
+    }
+
+    #[test]
+    fn open_after_creator_dropped_fails() {
+        let created = ProcessShared::<AtomicU64>::create().unwrap();
+        let handle = created.handle().clone();
+
+        let opened = ProcessShared::<AtomicU64>::open(&handle).unwrap();
+        created.store(5, atomic::Ordering::Relaxed);
+        drop(created);
+
+        // Existing mappings remain usable but nobody can open the segment anymore.
+        assert_eq!(opened.load(atomic::Ordering::Relaxed), 5);
+        ProcessShared::<AtomicU64>::open(&handle).unwrap_err();
+    }
+
+    #[test]
+    fn open_with_different_size_fails() {
+        let created = ProcessShared::<AtomicU64>::create().unwrap();
+
+        let error = ProcessShared::<[AtomicU64; 2]>::open(created.handle()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[linked::object]
+    struct Counter {
+        total: Arc<ProcessShared<AtomicU64>>,
+    }
+
+    impl Counter {
+        fn new(total: ProcessShared<AtomicU64>) -> Self {
+            let total = Arc::new(total);
+
+            linked::new!(Self {
+                total: Arc::clone(&total),
+            })
+        }
+
+        fn increment(&self) {
+            self.total.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn families_linked_via_shared_memory() {
+        // Each family stands in for the family of a different process.
+        let first = Counter::new(ProcessShared::create().unwrap());
+        let second = Counter::new(ProcessShared::open(first.total.handle()).unwrap());
+
+        assert!(!first.is_same_family(&second));
+
+        thread::spawn({
+            let family = second.family();
+
+            move || {
+                let counter: Counter = family.into();
+                counter.increment();
+            }
+        })
+        .join()
+        .unwrap();
+
+        first.increment();
+
+        assert_eq!(second.total.load(atomic::Ordering::Relaxed), 2);
+    }
+}
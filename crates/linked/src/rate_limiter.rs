@@ -0,0 +1,314 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::ERR_POISONED_LOCK;
+
+/// A token bucket rate limiter implemented as a [linked object][crate], with the token budget
+/// shared by the family and each instance taking tokens from the budget in local leases.
+///
+/// The bucket holds up to `capacity` tokens and is refilled at `refill_per_second` tokens per
+/// second. Each operation that is subject to the rate limit acquires one or more tokens via
+/// [`try_acquire()`][Self::try_acquire] and is rejected if not enough tokens are available.
+///
+/// Acquiring tokens from the local lease does not require any synchronization. Only when the
+/// lease is used up does the instance take a new lease of (up to) `lease_size` tokens from the
+/// bucket shared by the family, which is protected by a mutex. Larger leases mean less
+/// contention between threads but allow a thread to hold on to tokens that other threads could
+/// use, so the rate limit becomes less precise as the lease size approaches the capacity.
+///
+/// # Usage
+///
+/// Create the rate limiter via [`RateLimiter::new()`] and use it via any of the standard instance
+/// management mechanisms, such as [`linked::thread_local_rc!`][1] or
+/// [`linked::InstancePerThread<T>`][2].
+///
+/// When an instance of the rate limiter is dropped, any tokens left in its lease are returned to
+/// the bucket, so they remain available to other instances in the family.
+///
+/// # Example
+///
+/// ```
+/// use linked::RateLimiter;
+///
+/// // Up to 100 requests per second, with each thread leasing 10 tokens at a time.
+/// linked::thread_local_rc!(static REQUEST_LIMIT: RateLimiter = RateLimiter::new(100, 100, 10));
+///
+/// if REQUEST_LIMIT.with(|limit| limit.try_acquire(1)) {
+///     // Handle the request.
+/// } else {
+///     // Reject the request.
+/// }
+///
+/// // The first request leased 10 tokens, one of which was used by the request.
+/// assert_eq!(REQUEST_LIMIT.with(|limit| limit.local_tokens()), 9);
+/// ```
+///
+/// [1]: crate::thread_local_rc
+/// [2]: crate::InstancePerThread
+#[linked::object]
+pub struct RateLimiter {
+    // Tokens leased from the bucket by this instance and not yet acquired.
+    local_tokens: Cell<u64>,
+    lease_size: u64,
+
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter, returning the first instance of a new family.
+    ///
+    /// The bucket starts out full, holding `capacity` tokens, and is refilled at
+    /// `refill_per_second` tokens per second. Each instance leases up to `lease_size` tokens
+    /// at a time from the bucket (or more, if a single call requests more).
+    #[must_use]
+    pub fn new(capacity: u64, refill_per_second: u64, lease_size: u64) -> Self {
+        let bucket = Arc::new(Mutex::new(Bucket::new(
+            capacity,
+            refill_per_second,
+            Instant::now(),
+        )));
+
+        linked::new!(Self {
+            local_tokens: Cell::new(0),
+            lease_size,
+            bucket: Arc::clone(&bucket),
+        })
+    }
+
+    /// Attempts to acquire `tokens` tokens, returning whether they were acquired.
+    ///
+    /// Tokens are acquired from the local lease if possible, falling back to leasing more tokens
+    /// from the bucket shared by the family. If the bucket does not have enough tokens, no tokens
+    /// are acquired and the local lease is left as it was.
+    #[must_use]
+    pub fn try_acquire(&self, tokens: u64) -> bool {
+        let local_tokens = self.local_tokens.get();
+
+        if let Some(remaining) = local_tokens.checked_sub(tokens) {
+            self.local_tokens.set(remaining);
+            return true;
+        }
+
+        let missing = tokens.saturating_sub(local_tokens);
+
+        let Some(leased) = self.bucket.lock().expect(ERR_POISONED_LOCK).lease(
+            missing,
+            self.lease_size.max(missing),
+            Instant::now(),
+        ) else {
+            return false;
+        };
+
+        self.local_tokens
+            .set(local_tokens.saturating_add(leased).saturating_sub(tokens));
+        true
+    }
+
+    /// The number of tokens in the local lease of the current instance, which can be acquired
+    /// without synchronizing with other instances.
+    #[must_use]
+    pub fn local_tokens(&self) -> u64 {
+        self.local_tokens.get()
+    }
+
+    /// The number of tokens in the bucket shared by all instances in the family, not counting
+    /// the tokens leased by instances.
+    #[must_use]
+    pub fn available_tokens(&self) -> u64 {
+        let mut bucket = self.bucket.lock().expect(ERR_POISONED_LOCK);
+        bucket.refill(Instant::now());
+        bucket.tokens
+    }
+}
+
+impl Drop for RateLimiter {
+    fn drop(&mut self) {
+        let local_tokens = self.local_tokens.get();
+
+        if local_tokens > 0 {
+            self.bucket
+                .lock()
+                .expect(ERR_POISONED_LOCK)
+                .give_back(local_tokens);
+        }
+    }
+}
+
+impl Debug for RateLimiter {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("local_tokens", &self.local_tokens.get())
+            .field("lease_size", &self.lease_size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The token budget shared by a family of rate limiters.
+#[derive(Debug)]
+struct Bucket {
+    tokens: u64,
+    capacity: u64,
+    refill_per_second: u64,
+
+    // Time spent without refilling a whole token is not lost, as we only move this forward
+    // when we refill at least one token (or the bucket is full).
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(capacity: u64, refill_per_second: u64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            capacity,
+            refill_per_second,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+
+        let refilled = elapsed
+            .as_nanos()
+            .saturating_mul(u128::from(self.refill_per_second))
+            .checked_div(NANOS_PER_SECOND)
+            .expect("divisor is a non-zero constant");
+        let refilled = u64::try_from(refilled).unwrap_or(u64::MAX);
+
+        if refilled > 0 || self.tokens >= self.capacity {
+            self.tokens = self.tokens.saturating_add(refilled).min(self.capacity);
+            self.last_refill = now;
+        }
+    }
+
+    /// Takes up to `preferred` tokens from the bucket, returning the number of tokens taken or
+    /// `None` if the bucket has fewer than `required` tokens.
+    fn lease(&mut self, required: u64, preferred: u64, now: Instant) -> Option<u64> {
+        self.refill(now);
+
+        let leased = preferred.min(self.tokens);
+
+        if leased < required {
+            return None;
+        }
+
+        self.tokens = self.tokens.saturating_sub(leased);
+        Some(leased)
+    }
+
+    fn give_back(&mut self, tokens: u64) {
+        self.tokens = self.tokens.saturating_add(tokens).min(self.capacity);
+    }
+}
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn acquires_from_local_lease() {
+        // No refill, so the budget is deterministic.
+        let limiter = RateLimiter::new(100, 0, 10);
+
+        assert!(limiter.try_acquire(1));
+        assert_eq!(limiter.local_tokens(), 9);
+        assert_eq!(limiter.available_tokens(), 90);
+
+        assert!(limiter.try_acquire(9));
+        assert_eq!(limiter.local_tokens(), 0);
+        assert_eq!(limiter.available_tokens(), 90);
+
+        // A request larger than the lease size leases exactly what is missing.
+        assert!(limiter.try_acquire(25));
+        assert_eq!(limiter.local_tokens(), 0);
+        assert_eq!(limiter.available_tokens(), 65);
+    }
+
+    #[test]
+    fn rejects_when_budget_exhausted() {
+        let limiter = RateLimiter::new(15, 0, 10);
+
+        assert!(limiter.try_acquire(10));
+        assert_eq!(limiter.available_tokens(), 5);
+
+        // The remaining tokens are leased even though they are less than the lease size.
+        assert!(limiter.try_acquire(2));
+        assert_eq!(limiter.local_tokens(), 3);
+        assert_eq!(limiter.available_tokens(), 0);
+
+        assert!(!limiter.try_acquire(4));
+        assert_eq!(limiter.local_tokens(), 3);
+    }
+
+    #[test]
+    fn instances_share_budget() {
+        let limiter = RateLimiter::new(20, 0, 10);
+        assert!(limiter.try_acquire(1));
+
+        thread::spawn({
+            let limiter = limiter.clone();
+
+            move || {
+                assert!(limiter.try_acquire(10));
+
+                // The other instance holds the rest of the budget in its lease.
+                assert!(!limiter.try_acquire(1));
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert!(limiter.try_acquire(9));
+        assert!(!limiter.try_acquire(1));
+    }
+
+    #[test]
+    fn dropped_instance_returns_lease() {
+        let limiter = RateLimiter::new(100, 0, 10);
+
+        thread::spawn({
+            let limiter = limiter.clone();
+
+            move || {
+                assert!(limiter.try_acquire(3));
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(limiter.available_tokens(), 97);
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(10, 4, start);
+
+        assert_eq!(bucket.lease(10, 10, start), Some(10));
+        assert_eq!(bucket.lease(1, 1, start), None);
+
+        // Not enough time for a whole token yet.
+        bucket.refill(start + Duration::from_millis(200));
+        assert_eq!(bucket.tokens, 0);
+
+        // The time from the previous attempt still counts.
+        bucket.refill(start + Duration::from_millis(500));
+        assert_eq!(bucket.tokens, 2);
+
+        // The bucket never holds more than its capacity.
+        bucket.refill(start + Duration::from_secs(60));
+        assert_eq!(bucket.tokens, 10);
+
+        bucket.give_back(5);
+        assert_eq!(bucket.tokens, 10);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c) Microsoft Corporation.
+// Copyright (c) Folo authors.
+
+use std::ops::Deref;
+use std::rc::Rc as StdRc;
+
+use crate::{Family, Object};
+
+/// A single-threaded counterpart of [`linked::Box`][1] that acts like a
+/// `std::rc::Rc<dyn MyTrait>`.
+///
+/// Intended for strictly single-threaded code (e.g. a GUI layer) that exposes linked objects
+/// as trait objects and wants to share one instance between multiple owners on the same thread.
+///
+/// # Cloning
+///
+/// Unlike linked objects, which create a new linked instance when cloned, clones of a
+/// `linked::Rc` share the same instance, just like clones of `std::rc::Rc`. Cloning only
+/// increments a non-atomic reference count and does not involve the family machinery at all.
+///
+/// To create a new linked instance (e.g. for use on a different thread), obtain the family via
+/// [`.family()`][Self::family] and convert it into a new `linked::Rc` or `linked::Box`.
+///
+/// # Usage
+///
+/// Create instances via the [`linked::new_rc!` macro][2], which works the same as
+/// [`linked::new_box!`][3], or convert an existing `linked::Box` via `From`.
+///
+/// ```
+/// trait ConfigSource {
+///     fn config(&self) -> String;
+/// }
+///
+/// struct XmlConfig {
+///     config: String,
+/// }
+///
+/// impl ConfigSource for XmlConfig {
+///     fn config(&self) -> String {
+///         self.config.clone()
+///     }
+/// }
+///
+/// impl XmlConfig {
+///     pub fn new_as_config_source() -> linked::Rc<dyn ConfigSource> {
+///         linked::new_rc!(
+///             dyn ConfigSource,
+///             Self {
+///                 config: "xml".to_string(),
+///             }
+///         )
+///     }
+/// }
+///
+/// let config = XmlConfig::new_as_config_source();
+///
+/// // Both variables refer to the same instance.
+/// let shared_config = config.clone();
+/// assert_eq!(linked::Rc::strong_count(&config), 2);
+///
+/// assert_eq!(shared_config.config(), "xml");
+/// ```
+///
+/// [1]: crate::Box
+/// [2]: crate::new_rc
+/// [3]: crate::new_box
+#[derive(Debug)]
+pub struct Rc<T: ?Sized + 'static> {
+    instance: StdRc<crate::Box<T>>,
+}
+
+impl<T: ?Sized + 'static> Rc<T> {
+    /// The object family that the current instance is linked to.
+    ///
+    /// The returned object can be used to create additional instances linked to the same family.
+    #[must_use]
+    pub fn family(&self) -> Family<crate::Box<T>> {
+        self.instance.family()
+    }
+
+    /// The number of `linked::Rc` values that share the same instance as `this`.
+    #[must_use]
+    pub fn strong_count(this: &Self) -> usize {
+        StdRc::strong_count(&this.instance)
+    }
+
+    /// Whether both values share the same instance.
+    #[must_use]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        StdRc::ptr_eq(&this.instance, &other.instance)
+    }
+}
+
+impl<T: ?Sized + 'static> Clone for Rc<T> {
+    fn clone(&self) -> Self {
+        Self {
+            instance: StdRc::clone(&self.instance),
+        }
+    }
+}
+
+impl<T: ?Sized + 'static> Deref for Rc<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.instance
+    }
+}
+
+impl<T: ?Sized + 'static> From<crate::Box<T>> for Rc<T> {
+    fn from(value: crate::Box<T>) -> Self {
+        Self {
+            instance: StdRc::new(value),
+        }
+    }
+}
+
+impl<T: ?Sized + 'static> From<Family<crate::Box<T>>> for Rc<T> {
+    fn from(family: Family<crate::Box<T>>) -> Self {
+        Self::from(crate::Box::from(family))
+    }
+}
+
+/// Defines the template used to create every instance in a `linked::Rc<T>` object family.
+///
+/// This is the same as [`linked::new_box!`][crate::new_box] but returns a
+/// [`linked::Rc<T>`][crate::Rc] instead of a [`linked::Box<T>`][crate::Box].
+///
+/// # Arguments
+///
+/// * `$dyn_trait` - The trait object that the linked object is to be used as (e.g. `dyn SomeTrait`).
+/// * `$ctor` - The Self-expression that serves as the template for constructing new linked
+///   instances on demand. This will move-capture any referenced state. All captured
+///   values must be thread-safe (`Send` + `Sync` + `'static`).
+///
+/// # Example
+///
+/// ```rust
+/// # trait ConfigSource {}
+/// # impl ConfigSource for XmlConfig {}
+/// struct XmlConfig {
+///     config: String
+/// }
+///
+/// impl XmlConfig {
+///     pub fn new_as_config_source() -> linked::Rc<dyn ConfigSource> {
+///         linked::new_rc!(
+///             dyn ConfigSource,
+///             Self {
+///                 config: "xml".to_string(),
+///             }
+///         )
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! new_rc {
+    ($dyn_trait:ty, $ctor:expr) => {
+        ::linked::Rc::from(::linked::new_box!($dyn_trait, $ctor))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::thread;
+
+    trait Counter {
+        fn increment(&self);
+        fn local_count(&self) -> usize;
+    }
+
+    struct LocalCounter {
+        local_count: Cell<usize>,
+    }
+
+    impl Counter for LocalCounter {
+        fn increment(&self) {
+            self.local_count.set(self.local_count.get().wrapping_add(1));
+        }
+
+        fn local_count(&self) -> usize {
+            self.local_count.get()
+        }
+    }
+
+    impl LocalCounter {
+        fn new_as_counter() -> linked::Rc<dyn Counter> {
+            linked::new_rc!(
+                dyn Counter,
+                Self {
+                    local_count: Cell::new(0),
+                }
+            )
+        }
+    }
+
+    #[test]
+    fn clones_share_instance() {
+        let counter = LocalCounter::new_as_counter();
+        let clone = counter.clone();
+
+        assert!(linked::Rc::ptr_eq(&counter, &clone));
+        assert_eq!(linked::Rc::strong_count(&counter), 2);
+
+        counter.increment();
+        assert_eq!(clone.local_count(), 1);
+    }
+
+    #[test]
+    fn family_creates_new_instances() {
+        let counter = LocalCounter::new_as_counter();
+        counter.increment();
+
+        let family = counter.family();
+
+        let same_thread: linked::Rc<dyn Counter> = family.clone().into();
+        assert!(!linked::Rc::ptr_eq(&counter, &same_thread));
+        assert_eq!(same_thread.local_count(), 0);
+
+        thread::spawn(move || {
+            let other_thread: linked::Rc<dyn Counter> = family.into();
+            assert_eq!(other_thread.local_count(), 0);
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::OnceLock;
+
+use many_cpus::{HardwareTracker, MemoryRegionId};
+
+/// Sets the hook that places the heap state of per-thread instances of [linked objects][crate]
+/// in the memory region of the processor that creates them.
+///
+/// Per-thread instances are typically accessed only by the thread that created them, so their
+/// heap state is best placed in the memory region (NUMA node) of that thread's processor. The
+/// global allocator does not know this, so the heap state may end up in any memory region,
+/// making every access to it slower than necessary on systems with multiple memory regions.
+///
+/// Once the hook is set, every instance created via a [`linked::instances!`][1] static variable
+/// (via [`.get()`][2] or the instance cached by [`.with()`][3]) is created inside a call to the
+/// hook, which receives the [`MemoryRegionId`] of the current processor of the creating thread
+/// and a closure that creates the instance. The hook must call the closure exactly once, after
+/// directing the heap allocations of the current thread into the given memory region (e.g. by
+/// selecting a region-bound arena of a region-aware global allocator).
+///
+/// The hook can only be set once per process and applies to all static variables. Instances
+/// created before the hook is set are not affected.
+///
+/// This is only available with the `region_placement` Cargo feature enabled.
+///
+/// # Panics
+///
+/// Panics if the hook has already been set.
+///
+/// Creating an instance panics if the hook does not call the closure exactly once.
+///
+/// # Example
+///
+/// ```
+/// use std::cell::RefCell;
+///
+/// #[linked::object]
+/// struct RequestCache {
+///     entries: RefCell<Vec<String>>,
+/// }
+///
+/// impl RequestCache {
+///     pub fn new() -> Self {
+///         linked::new!(Self {
+///             entries: RefCell::new(Vec::with_capacity(1000)),
+///         })
+///     }
+/// }
+///
+/// linked::instances!(static REQUEST_CACHE: RequestCache = RequestCache::new());
+///
+/// linked::set_region_allocator_hook(|memory_region_id, create_instance| {
+///     // A real hook would select an allocator arena bound to `memory_region_id` here.
+///     println!("creating instance in memory region {memory_region_id}");
+///     create_instance();
+/// });
+///
+/// // The instance and its heap state are allocated via the hook.
+/// REQUEST_CACHE.with(|cache| cache.entries.borrow_mut().push("/index.html".to_string()));
+/// ```
+///
+/// [1]: crate::instances
+/// [2]: crate::StaticInstances::get
+/// [3]: crate::StaticInstances::with
+pub fn set_region_allocator_hook(
+    hook: impl Fn(MemoryRegionId, &mut dyn FnMut()) + Send + Sync + 'static,
+) {
+    assert!(
+        REGION_ALLOCATOR_HOOK.set(Box::new(hook)).is_ok(),
+        "the region allocator hook can only be set once per process"
+    );
+}
+
+/// Creates an instance via `create()`, inside a call to the region allocator hook if one is set.
+#[inline]
+pub(crate) fn place_instance<T>(create: impl FnOnce() -> T) -> T {
+    let Some(hook) = REGION_ALLOCATOR_HOOK.get() else {
+        return create();
+    };
+
+    let mut create = Some(create);
+    let mut instance = None;
+
+    hook(HardwareTracker::current_memory_region_id(), &mut || {
+        let create = create
+            .take()
+            .expect("the region allocator hook must call the closure only once");
+
+        instance = Some(create());
+    });
+
+    instance.expect("the region allocator hook must call the closure")
+}
+
+type RegionAllocatorHook = Box<dyn Fn(MemoryRegionId, &mut dyn FnMut()) + Send + Sync>;
+
+static REGION_ALLOCATOR_HOOK: OnceLock<RegionAllocatorHook> = OnceLock::new();
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{self, AtomicUsize};
+
+    use super::*;
+
+    #[test]
+    fn hook_wraps_instance_creation() {
+        static HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[linked::object]
+        struct Thing {
+            value: usize,
+        }
+
+        impl Thing {
+            fn new() -> Self {
+                linked::new!(Self { value: 42 })
+            }
+        }
+
+        linked::instances!(static THING: Thing = Thing::new());
+
+        // This is the only test that sets the hook, as it can only be set once per process.
+        set_region_allocator_hook(|_, create_instance| {
+            HOOK_CALLS.fetch_add(1, atomic::Ordering::Relaxed);
+            create_instance();
+        });
+
+        let calls_before = HOOK_CALLS.load(atomic::Ordering::Relaxed);
+
+        assert_eq!(THING.get().value, 42);
+        THING.with(|thing| assert_eq!(thing.value, 42));
+
+        // Other tests may create instances concurrently, so we may see more calls than ours.
+        assert!(HOOK_CALLS.load(atomic::Ordering::Relaxed) >= calls_before.wrapping_add(2));
+    }
+}
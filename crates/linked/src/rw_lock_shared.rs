@@ -0,0 +1,267 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+    sync::{
+        Arc, RwLock,
+        atomic::{self, AtomicU64},
+    },
+};
+
+use crate::ERR_POISONED_LOCK;
+
+/// A read-mostly value shared by a family of [linked objects][crate], protected by a lock
+/// shared by the family, with each instance caching its own view of the value.
+///
+/// Reading the value via [`read()`][Self::read] does not take the lock as long as the value has
+/// not changed since the current instance last looked at it - the instance keeps a cached view of
+/// the value and only checks an atomic version number to detect that the cached view is outdated.
+/// This makes reads cheap even when they happen very frequently on many threads.
+///
+/// Writing the value via [`write()`][Self::write] takes the lock exclusively and invalidates the
+/// cached views of all instances in the family, which will refresh their views on the next read.
+///
+/// # Consistency
+///
+/// A write is visible to reads on all instances once `write()` returns. A read that runs
+/// concurrently with a write may observe either the old or the new value.
+///
+/// Nested reads on the same instance (i.e. calling `read()` from inside the callback of another
+/// `read()`) observe the same view as the outer read, even if the value has changed meanwhile.
+///
+/// # Example
+///
+/// ```
+/// use linked::RwLockShared;
+///
+/// #[derive(Clone)]
+/// struct RoutingTable {
+///     routes: Vec<String>,
+/// }
+///
+/// linked::thread_local_rc!(static ROUTES: RwLockShared<RoutingTable> =
+///     RwLockShared::new(RoutingTable { routes: vec!["10.0.0.0/8".to_string()] }));
+///
+/// // The first read on each thread fetches the value, subsequent reads use the cached view.
+/// let route_count = ROUTES.with(|routes| routes.read(|table| table.routes.len()));
+/// assert_eq!(route_count, 1);
+///
+/// std::thread::spawn(|| {
+///     ROUTES.with(|routes| {
+///         routes.write(|table| table.routes.push("192.168.0.0/16".to_string()));
+///     });
+/// })
+/// .join()
+/// .unwrap();
+///
+/// // The write invalidated the cached view of every instance.
+/// let route_count = ROUTES.with(|routes| routes.read(|table| table.routes.len()));
+/// assert_eq!(route_count, 2);
+/// ```
+#[linked::object]
+pub struct RwLockShared<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    shared: Arc<SharedValue<T>>,
+
+    // The view of the value last seen by this instance, if any.
+    cached: RefCell<Option<CachedView<T>>>,
+}
+
+impl<T> RwLockShared<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Creates a new family sharing `value`, returning the first instance of the family.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let shared = Arc::new(SharedValue {
+            value: RwLock::new(Arc::new(value)),
+            version: AtomicU64::new(0),
+        });
+
+        linked::new!(Self {
+            shared: Arc::clone(&shared),
+            cached: RefCell::new(None),
+        })
+    }
+
+    /// Calls `f` with the current value, using the cached view of the current instance
+    /// if it is still up to date.
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        // If we cannot borrow mutably, this is a nested read - we just use the same view as the
+        // outer read, as it cannot be missing and is no older than the outer read considers fine.
+        if let Ok(mut cached) = self.cached.try_borrow_mut() {
+            let current_version = self.shared.version.load(atomic::Ordering::Acquire);
+
+            if cached
+                .as_ref()
+                .is_none_or(|view| view.version != current_version)
+            {
+                *cached = Some(self.shared.view());
+            }
+        }
+
+        let cached = self.cached.borrow();
+        let view = cached
+            .as_ref()
+            .expect("we populated the view above or we are in a nested read that populated it");
+
+        f(&view.value)
+    }
+
+    /// Calls `f` with exclusive access to the value, invalidating the cached views of all
+    /// instances in the family.
+    ///
+    /// If any instance is still holding on to the previous value (e.g. in its cached view),
+    /// the value is cloned before `f` is called, leaving the previous value untouched.
+    pub fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut value = self.shared.value.write().expect(ERR_POISONED_LOCK);
+
+        let result = f(Arc::make_mut(&mut value));
+
+        // We bump the version while still holding the lock, so any reader that takes the lock
+        // after us sees the new value together with the new version.
+        self.shared.version.fetch_add(1, atomic::Ordering::Release);
+
+        result
+    }
+
+    /// Discards the cached view of the current instance, forcing the next read on this instance
+    /// to fetch the value from the shared state.
+    ///
+    /// This also releases the reference the instance holds on the value, allowing the next write
+    /// to update the value in place instead of cloning it.
+    pub fn invalidate(&self) {
+        // If we are inside a read, the view is in use and the next read will refresh it anyway
+        // if the value has changed, so there is nothing for us to do.
+        if let Ok(mut cached) = self.cached.try_borrow_mut() {
+            *cached = None;
+        }
+    }
+
+    /// Returns a snapshot of the current value, which remains unchanged even if the value is
+    /// written afterwards.
+    #[must_use]
+    pub fn snapshot(&self) -> Arc<T> {
+        Arc::clone(&self.shared.view().value)
+    }
+}
+
+impl<T> Debug for RwLockShared<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwLockShared")
+            .field(
+                "version",
+                &self.shared.version.load(atomic::Ordering::Relaxed),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+struct SharedValue<T> {
+    value: RwLock<Arc<T>>,
+
+    // Incremented on every write, while holding the write lock.
+    version: AtomicU64,
+}
+
+impl<T> SharedValue<T> {
+    fn view(&self) -> CachedView<T> {
+        let value = self.value.read().expect(ERR_POISONED_LOCK);
+
+        CachedView {
+            value: Arc::clone(&value),
+            // The version only changes under the write lock, so it matches the value we just read.
+            version: self.version.load(atomic::Ordering::Relaxed),
+        }
+    }
+}
+
+struct CachedView<T> {
+    value: Arc<T>,
+    version: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ptr, thread};
+
+    use super::*;
+    use crate::Object;
+
+    #[test]
+    fn reads_use_cached_view_until_write() {
+        let shared = RwLockShared::new(vec![1, 2, 3]);
+        let clone: RwLockShared<_> = shared.family().into();
+
+        assert_eq!(shared.read(Vec::len), 3);
+        let first_view = shared.snapshot();
+
+        // While there is no write, the cached view is reused.
+        shared.read(|value| assert!(ptr_eq(value, &first_view)));
+
+        clone.write(|value| value.push(4));
+
+        assert_eq!(shared.read(Vec::len), 4);
+        assert_eq!(clone.read(Vec::len), 4);
+
+        // The snapshot taken before the write is not affected by the write.
+        assert_eq!(first_view.len(), 3);
+    }
+
+    #[test]
+    fn writes_visible_on_other_threads() {
+        let shared = RwLockShared::new(0_u32);
+        assert_eq!(shared.read(|value| *value), 0);
+
+        thread::spawn({
+            let shared = shared.clone();
+
+            move || shared.write(|value| *value = 42)
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(shared.read(|value| *value), 42);
+    }
+
+    #[test]
+    fn nested_read_sees_same_view() {
+        let shared = RwLockShared::new(1_u32);
+        let clone: RwLockShared<_> = shared.family().into();
+
+        shared.read(|outer| {
+            clone.write(|value| *value = 2);
+
+            shared.read(|inner| assert_eq!(inner, outer));
+        });
+
+        assert_eq!(shared.read(|value| *value), 2);
+    }
+
+    #[test]
+    fn invalidate_allows_write_in_place() {
+        let shared = RwLockShared::new(vec![1]);
+
+        shared.read(|_| {});
+        shared.invalidate();
+
+        let before = shared.snapshot();
+        let before_ptr = Arc::as_ptr(&before);
+        drop(before);
+
+        shared.write(|value| value.push(2));
+
+        // Nobody was holding the value, so it was updated in place instead of being cloned.
+        assert_eq!(Arc::as_ptr(&shared.snapshot()), before_ptr);
+    }
+
+    fn ptr_eq<T>(value: &T, view: &Arc<T>) -> bool {
+        ptr::eq(value, Arc::as_ptr(view))
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A value shared by all instances in a family of [linked objects][crate].
+///
+/// This is the type of fields marked with `#[linked(shared)]` in a struct marked with
+/// [`#[linked::object]`][crate::object]. The value of the field is created by the first instance
+/// in the family and all other instances in the family reference the same value.
+///
+/// Dereferences to the shared value. The value is shared between threads, so it must be
+/// thread-safe (`Send` + `Sync`) - use atomics or a `Mutex` if you need to mutate it.
+pub struct Shared<T>
+where
+    T: ?Sized,
+{
+    value: Arc<T>,
+}
+
+impl<T> Shared<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            value: Arc::new(value),
+        }
+    }
+}
+
+impl<T> Deref for Shared<T>
+where
+    T: ?Sized,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> Clone for Shared<T>
+where
+    T: ?Sized,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: Arc::clone(&self.value),
+        }
+    }
+}
+
+impl<T> Debug for Shared<T>
+where
+    T: Debug + ?Sized,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.value, f)
+    }
+}
@@ -0,0 +1,447 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::fmt::{self, Debug, Formatter};
+use std::iter;
+use std::ptr::NonNull;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{LazyLock, OnceLock, RwLock};
+
+use hash_hasher::HashedMap;
+
+use crate::{ERR_POISONED_LOCK, LocalSlots, register_populated_slot_on_thread, with_thread_slots};
+
+/// A handle to the registry of the [`linked::instances!`][1] static variables of one copy of
+/// `linked` in the process, allowing other copies of `linked` (e.g. in dynamically loaded
+/// plugins) to share it.
+///
+/// # Isolation by default
+///
+/// Every dynamic library that links its own copy of `linked` has its own registry of static
+/// variables and its own thread-local storage. A library that also links its own copy of a crate
+/// declaring a `linked::instances!` static variable therefore has its own copy of the static
+/// variable, with its own family and its own per-thread instances that are not linked to those
+/// of the other copies. This is safe but means that a thread accessing the static variable via
+/// both the plugin host and a plugin uses two unrelated instances.
+///
+/// # Shared-registry mode
+///
+/// To make all copies of a static variable in the process share one family and one cached
+/// per-thread instance, the plugin host calls [`SharedRegistry::host()`] and passes the handle
+/// (via [`into_raw()`][Self::into_raw]) to each plugin, which calls
+/// [`attach()`][Self::attach] before it accesses any static variable. From then on, the families
+/// and the per-thread state of the static variables of all copies of `linked` live in the
+/// registry and the thread-local storage of the copy of the plugin host.
+///
+/// This covers `linked::instances!` static variables (regardless of their thread-local storage
+/// backend) and [`linked::type_keyed_instance()`][2]. The per-thread state is released by the
+/// thread exit hooks of the plugin host's copy of `linked`, so threads must call the
+/// [`linked::run_thread_exit_hooks()`][3] of the plugin host if they call it at all. Plugins must
+/// not be unloaded while the process uses their static variables, as the registry refers to the
+/// code of the plugins.
+///
+/// # Compatibility
+///
+/// The copies of `linked` access the registry directly, so the plugin host and the plugins must
+/// be built with the same compiler and the same version and Cargo features of `linked` and of
+/// the crates declaring the static variables. Attaching to a registry of an incompatible build of
+/// `linked` is detected and rejected (see [`is_compatible()`][Self::is_compatible]).
+///
+/// # Example
+///
+/// ```
+/// use linked::SharedRegistry;
+///
+/// // In the plugin host, before accessing any `linked::instances!` static variable.
+/// let registry = SharedRegistry::host().into_raw();
+///
+/// // The pointer is passed to the plugin via its C-compatible entry point, where the plugin
+/// // attaches to the registry before accessing any static variable:
+/// //
+/// // #[unsafe(no_mangle)]
+/// // pub unsafe extern "C" fn plugin_init(registry: *const c_void) {
+/// //     let registry = unsafe { linked::SharedRegistry::from_raw(registry) };
+/// //     assert!(registry.is_compatible());
+/// //     registry.attach();
+/// // }
+/// # // SAFETY: We just created the pointer via `into_raw()`.
+/// # assert!(unsafe { SharedRegistry::from_raw(registry) }.is_compatible());
+/// ```
+///
+/// [1]: crate::instances
+/// [2]: crate::type_keyed_instance
+/// [3]: crate::run_thread_exit_hooks
+#[derive(Clone, Copy)]
+pub struct SharedRegistry {
+    // May point to a registry of an incompatible build of `linked`, in which case only the
+    // header at the start of the registry may be accessed.
+    registry: NonNull<Registry>,
+}
+
+impl SharedRegistry {
+    /// Makes the registry of this copy of `linked` the shared registry of the process and returns
+    /// a handle to it, for passing to the copies of `linked` that should attach to it.
+    ///
+    /// If this copy of `linked` has itself attached to the registry of another copy, returns a
+    /// handle to that registry instead. Calling this more than once returns the same registry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a static variable has already been accessed via this copy of `linked` before
+    /// the first call.
+    #[must_use]
+    pub fn host() -> Self {
+        let registry = registry();
+
+        if !registry.is_shared.load(atomic::Ordering::Relaxed) {
+            assert!(
+                !registry.is_in_use(),
+                "the registry must be shared before any static variable is accessed"
+            );
+
+            registry.is_shared.store(true, atomic::Ordering::Relaxed);
+        }
+
+        USES_SHARED_REGISTRY.store(true, atomic::Ordering::Relaxed);
+
+        Self {
+            registry: NonNull::from(registry),
+        }
+    }
+
+    /// Whether the registry was created by a build of `linked` that is compatible with this copy
+    /// of `linked`, which is required for [attaching][Self::attach] to it.
+    #[must_use]
+    pub fn is_compatible(self) -> bool {
+        // SAFETY: The registry is `repr(C)` and starts with the header in every build, with a
+        // layout that never changes, so reading it is valid even if the rest of the layout is
+        // different.
+        let header = unsafe { self.registry.cast::<RegistryHeader>().read() };
+
+        if header != RegistryHeader::current() {
+            return false;
+        }
+
+        // SAFETY: The header matches, so the registry was created by the same version of `linked`
+        // with the same Cargo features. The layout identifier directly follows the header, whose
+        // alignment ensures that the offset of the layout identifier is the same in every build.
+        // The layout identifier itself tells us whether the compiler was also the same.
+        let layout_id = unsafe { (*self.registry.as_ptr()).layout_id };
+
+        layout_id == TypeId::of::<Registry>()
+    }
+
+    /// Makes this copy of `linked` use the shared registry instead of its own registry, sharing
+    /// the families and the per-thread state of all static variables with the copy of `linked`
+    /// that [hosts][Self::host] the registry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registry is not [compatible][Self::is_compatible] with this copy of
+    /// `linked`, if this copy of `linked` has already attached to a registry or if a static
+    /// variable has already been accessed via this copy of `linked`.
+    pub fn attach(self) {
+        assert!(
+            self.is_compatible(),
+            "the shared registry was created by an incompatible build of linked"
+        );
+
+        let own_registry = LazyLock::force(&LOCAL_REGISTRY);
+
+        assert!(
+            !own_registry.is_in_use() && !own_registry.is_shared.load(atomic::Ordering::Relaxed),
+            "a shared registry must be attached before any static variable is accessed"
+        );
+
+        // SAFETY: The layout matches, as checked above, and registries are static variables.
+        let registry: &'static Registry = unsafe { self.registry.as_ref() };
+
+        assert!(
+            ATTACHED_REGISTRY.set(registry).is_ok(),
+            "a shared registry has already been attached"
+        );
+
+        USES_SHARED_REGISTRY.store(true, atomic::Ordering::Relaxed);
+    }
+
+    /// Converts the handle into a raw pointer, for passing it through a C-compatible interface.
+    ///
+    /// The registry lives as long as the copy of `linked` that hosts it, so the pointer does not
+    /// need to be released.
+    #[must_use]
+    pub fn into_raw(self) -> *const c_void {
+        self.registry.as_ptr().cast_const().cast()
+    }
+
+    /// Converts a raw pointer created by [`into_raw()`][Self::into_raw] back into a handle.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must have been returned by `into_raw()` in the current process, by a copy of
+    /// `linked` built with the same compiler as the copy calling this function.
+    #[must_use]
+    pub unsafe fn from_raw(ptr: *const c_void) -> Self {
+        Self {
+            registry: NonNull::new(ptr.cast_mut().cast())
+                .expect("the pointer must have been returned by SharedRegistry::into_raw()"),
+        }
+    }
+}
+
+// SAFETY: The handle only refers to a static registry, all of whose contents are thread-safe.
+unsafe impl Send for SharedRegistry {}
+// SAFETY: The handle only refers to a static registry, all of whose contents are thread-safe.
+unsafe impl Sync for SharedRegistry {}
+
+impl Debug for SharedRegistry {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedRegistry")
+            .field("registry", &self.registry)
+            .finish()
+    }
+}
+
+// We use HashedMap which takes the raw value from Hash::hash() and uses it directly as the key.
+// This is OK because TypeId already returns a hashed value as its raw value, no need to hash more.
+// We also do not care about any hash manipulation because none of this is untrusted user input.
+pub(crate) type FamilyRegistry = HashedMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+// The registry that is the ultimate authority where all static variable based linked object
+// families are registered, together with access to the per-thread state of the static variables.
+//
+// Other copies of `linked` in the process may access the registry directly (see
+// `SharedRegistry`), so the layout is fixed and starts with a header identifying the build of
+// `linked` that created the registry, followed by an identifier of the layout. Both are checked
+// before any other field is accessed.
+#[repr(C)]
+pub(crate) struct Registry {
+    header: RegistryHeader,
+    layout_id: TypeId,
+
+    // Values inside are type-occluded `Family<T>` where T may be different for each entry.
+    pub(crate) families: RwLock<FamilyRegistry>,
+
+    // Whether the registry has been made the shared registry of the process.
+    is_shared: AtomicBool,
+
+    // Access the thread-local storage of the copy of `linked` that owns the registry.
+    with_thread_slots: WithThreadSlots,
+    register_populated_slot: fn(Box<dyn FnOnce()>),
+}
+
+impl Registry {
+    fn new() -> Self {
+        Self {
+            header: RegistryHeader::current(),
+            layout_id: TypeId::of::<Self>(),
+            families: RwLock::new(FamilyRegistry::default()),
+            is_shared: AtomicBool::new(false),
+            with_thread_slots,
+            register_populated_slot: register_populated_slot_on_thread,
+        }
+    }
+
+    /// Calls `f` with the slots of the current thread, unless the thread-local storage is being
+    /// destroyed.
+    pub(crate) fn with_thread_slots(&self, f: &mut dyn FnMut(&RefCell<LocalSlots>)) {
+        (self.with_thread_slots)(f);
+    }
+
+    /// Records that the current thread has populated a slot, so its contents are released by
+    /// calling `clear` when the thread exits.
+    pub(crate) fn register_populated_slot(&self, clear: Box<dyn FnOnce()>) {
+        (self.register_populated_slot)(clear);
+    }
+
+    fn is_in_use(&self) -> bool {
+        !self.families.read().expect(ERR_POISONED_LOCK).is_empty()
+    }
+}
+
+type WithThreadSlots = fn(&mut dyn FnMut(&RefCell<LocalSlots>));
+
+// The start of every registry, identifying the build of `linked` that created it. Any build of
+// `linked` must be able to read the header of a registry created by any other build, so the layout
+// of the header must never change. The alignment makes the header big enough that the layout
+// identifier after it is at the same offset, no matter how the compiler aligns a `TypeId`.
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct RegistryHeader {
+    // Identifies the memory as a registry of `linked`.
+    magic: u64,
+
+    // Incremented whenever the layout of the registry after the header changes.
+    layout_version: u32,
+
+    // Hash of the version of `linked` and the names of its enabled Cargo features.
+    build_hash: u64,
+}
+
+impl RegistryHeader {
+    // "linkedRG" in ASCII.
+    const MAGIC: u64 = 0x6C69_6E6B_6564_5247;
+
+    const LAYOUT_VERSION: u32 = 1;
+
+    /// The header of registries created by this copy of `linked`.
+    fn current() -> Self {
+        Self {
+            magic: Self::MAGIC,
+            layout_version: Self::LAYOUT_VERSION,
+            build_hash: build_hash(),
+        }
+    }
+}
+
+/// Hashes the version of `linked` and the names of its enabled Cargo features with FNV-1a, which
+/// gives the same result in every build, unlike the hashers of the standard library.
+fn build_hash() -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+    let features = [
+        ("family_checks", cfg!(feature = "family_checks")),
+        ("family_snapshot", cfg!(feature = "family_snapshot")),
+        ("lifecycle_events", cfg!(feature = "lifecycle_events")),
+        ("metrics", cfg!(feature = "metrics")),
+        ("process_shared", cfg!(feature = "process_shared")),
+        ("processor_instances", cfg!(feature = "processor_instances")),
+        (
+            "processor_set_instances",
+            cfg!(feature = "processor_set_instances"),
+        ),
+        ("region_placement", cfg!(feature = "region_placement")),
+        ("thread_tracking", cfg!(feature = "thread_tracking")),
+        ("virtual_threads", cfg!(feature = "virtual_threads")),
+    ];
+
+    let enabled_features = features
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name);
+
+    // Every part is terminated by a separator, so adjacent parts cannot run together.
+    iter::once(env!("CARGO_PKG_VERSION"))
+        .chain(enabled_features)
+        .flat_map(|part| part.bytes().chain(iter::once(b',')))
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+static LOCAL_REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+// The registry of another copy of `linked`, once attached.
+static ATTACHED_REGISTRY: OnceLock<&'static Registry> = OnceLock::new();
+
+// Whether this copy of `linked` uses a shared registry, be it its own or that of another copy.
+static USES_SHARED_REGISTRY: AtomicBool = AtomicBool::new(false);
+
+/// The registry used by this copy of `linked`.
+pub(crate) fn registry() -> &'static Registry {
+    ATTACHED_REGISTRY
+        .get()
+        .copied()
+        .unwrap_or_else(|| LazyLock::force(&LOCAL_REGISTRY))
+}
+
+/// Whether the per-thread state of all static variables is kept in the slots of the registry
+/// instead of the thread-local storage backend of each static variable.
+#[inline]
+pub(crate) fn uses_shared_registry() -> bool {
+    USES_SHARED_REGISTRY.load(atomic::Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn own_registry_is_compatible() {
+        let registry = SharedRegistry {
+            registry: NonNull::from(LazyLock::force(&LOCAL_REGISTRY)),
+        };
+
+        // SAFETY: We just created the pointer via `into_raw()`.
+        let registry = unsafe { SharedRegistry::from_raw(registry.into_raw()) };
+
+        assert!(registry.is_compatible());
+    }
+
+    #[test]
+    fn different_build_is_incompatible() {
+        let other_version = Registry {
+            header: RegistryHeader {
+                build_hash: build_hash().wrapping_add(1),
+                ..RegistryHeader::current()
+            },
+            ..Registry::new()
+        };
+
+        let registry = SharedRegistry {
+            registry: NonNull::from(&other_version),
+        };
+
+        assert!(!registry.is_compatible());
+
+        let other_layout_version = Registry {
+            header: RegistryHeader {
+                layout_version: RegistryHeader::LAYOUT_VERSION.wrapping_add(1),
+                ..RegistryHeader::current()
+            },
+            ..Registry::new()
+        };
+
+        let registry = SharedRegistry {
+            registry: NonNull::from(&other_layout_version),
+        };
+
+        assert!(!registry.is_compatible());
+    }
+
+    #[test]
+    fn different_magic_is_incompatible() {
+        let not_a_registry = Registry {
+            header: RegistryHeader {
+                magic: 0,
+                ..RegistryHeader::current()
+            },
+            ..Registry::new()
+        };
+
+        let registry = SharedRegistry {
+            registry: NonNull::from(&not_a_registry),
+        };
+
+        assert!(!registry.is_compatible());
+    }
+
+    #[test]
+    fn different_layout_is_incompatible() {
+        let other_build = Registry {
+            layout_id: TypeId::of::<u64>(),
+            ..Registry::new()
+        };
+
+        let registry = SharedRegistry {
+            registry: NonNull::from(&other_build),
+        };
+
+        assert!(!registry.is_compatible());
+    }
+
+    #[test]
+    #[should_panic]
+    fn attach_to_incompatible_panics() {
+        static OTHER_BUILD: LazyLock<Registry> = LazyLock::new(|| Registry {
+            layout_id: TypeId::of::<u64>(),
+            ..Registry::new()
+        });
+
+        SharedRegistry {
+            registry: NonNull::from(LazyLock::force(&OTHER_BUILD)),
+        }
+        .attach();
+    }
+}
@@ -0,0 +1,222 @@
+use std::any::type_name;
+use std::fmt::{self, Debug, Formatter};
+
+use crate::__private::{InstanceFactory, Link};
+use crate::{Family, Object, SharedFamilyState, SharedFamilyStateRef};
+
+/// A family of [linked objects][crate] defined in a `static` variable, without any lazy
+/// initialization.
+///
+/// Families are normally created at runtime by [`linked::new!`][1], with the family state being
+/// allocated on the heap. Static variables declared via [`linked::instances!`][2] and related
+/// macros therefore have to lazily look up or create the family on first access, which costs a
+/// branch and some synchronization on every access of the static variable.
+///
+/// If all the state shared by the family can be created in a `const` context (e.g. atomics or
+/// `Mutex`es in `static` variables), the family can instead be defined at compile time via the
+/// [`linked::new_static!`][3] macro, with obtaining an instance not requiring any lookup.
+///
+/// # Limitations
+///
+/// The family lives for the lifetime of the process, so callbacks registered via
+/// [`Family::on_last_instance_dropped()`][4] are never called.
+///
+/// # Example
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[linked::object]
+/// struct HitCounter {
+///     // Shared state - lives in a static variable, shared by all instances in the family.
+///     total_hits: &'static AtomicUsize,
+///
+///     // Local state - separate for every instance.
+///     local_hits: Cell<usize>,
+/// }
+///
+/// impl HitCounter {
+///     fn record_hit(&self) {
+///         self.total_hits.fetch_add(1, Ordering::Relaxed);
+///         self.local_hits.set(self.local_hits.get() + 1);
+///     }
+/// }
+///
+/// static TOTAL_HITS: AtomicUsize = AtomicUsize::new(0);
+///
+/// static HIT_COUNTER: linked::StaticFamily<HitCounter> = linked::new_static!(HitCounter {
+///     total_hits: &TOTAL_HITS,
+///     local_hits: Cell::new(0),
+/// });
+///
+/// let counter = HIT_COUNTER.get();
+/// counter.record_hit();
+///
+/// std::thread::spawn(|| HIT_COUNTER.get().record_hit())
+///     .join()
+///     .unwrap();
+///
+/// assert_eq!(counter.local_hits.get(), 1);
+/// assert_eq!(TOTAL_HITS.load(Ordering::Relaxed), 2);
+/// ```
+///
+/// [1]: crate::new
+/// [2]: crate::instances
+/// [3]: crate::new_static
+/// [4]: crate::Family::on_last_instance_dropped
+pub struct StaticFamily<T> {
+    instance_factory: fn(Link<T>) -> T,
+    shared_state: SharedFamilyState,
+}
+
+impl<T> StaticFamily<T>
+where
+    T: Object,
+{
+    /// Note: this function exists to serve the inner workings of the
+    /// `linked::new_static!` macro and should not be used directly.
+    /// It is not part of the public API and may be removed or changed at any time.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(instance_factory: fn(Link<T>) -> T) -> Self {
+        Self {
+            instance_factory,
+            shared_state: SharedFamilyState::new(),
+        }
+    }
+
+    /// Creates a new linked instance of `T` from the family.
+    ///
+    /// # Performance
+    ///
+    /// This creates a new instance of `T` on every call so caching the return value is
+    /// performance-critical, just as with [`linked::instances!`][crate::instances].
+    #[must_use]
+    pub fn get(&'static self) -> T {
+        self.family().into()
+    }
+
+    /// The family defined by the static variable.
+    ///
+    /// The returned object can be used to create instances of the family.
+    #[must_use]
+    pub fn family(&'static self) -> Family<T> {
+        Family::from_parts(
+            InstanceFactory::Static(self.instance_factory),
+            SharedFamilyStateRef::Static(&self.shared_state),
+        )
+    }
+}
+
+impl<T> Debug for StaticFamily<T> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field(
+                "instance_factory",
+                &format_args!("fn(Link<{t}>) -> {t}", t = type_name::<T>()),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+/// Defines a family of [linked objects][crate] at compile time, for use in a `static` variable
+/// of type [`linked::StaticFamily<T>`][crate::StaticFamily].
+///
+/// This is the compile-time equivalent of [`linked::new!`][crate::new]. The struct expression
+/// serves as the template for constructing new linked instances on demand. Unlike with
+/// `linked::new!`, the template cannot capture any local variables - all state shared by the
+/// family must live in static variables, referenced from the template.
+///
+/// # Arguments
+///
+/// * `$t` - The linked object type (e.g. `TokenCache`), followed by the struct expression
+///   that serves as the template for new instances.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::sync::atomic::AtomicUsize;
+/// #[linked::object]
+/// struct TokenCache {
+///     issued_tokens: &'static AtomicUsize,
+/// }
+///
+/// static ISSUED_TOKENS: AtomicUsize = AtomicUsize::new(0);
+///
+/// static TOKEN_CACHE: linked::StaticFamily<TokenCache> = linked::new_static!(TokenCache {
+///     issued_tokens: &ISSUED_TOKENS,
+/// });
+/// ```
+#[macro_export]
+macro_rules! new_static {
+    ($t:ident { $($field:ident $( : $value:expr )?),* $(,)? }) => {
+        $crate::StaticFamily::new(|__private_linked_link| $t {
+            $($field: $crate::__private::paste!($t::[< __private_linked_init_ $field >](
+                &__private_linked_link,
+                || $crate::new!(@expand $field $( : $value )?))),)*
+            __private_linked_link,
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::atomic::{self, AtomicUsize};
+    use std::thread;
+
+    use crate::{Object, StaticFamily};
+
+    #[linked::object]
+    struct Counter {
+        shared_value: &'static AtomicUsize,
+        local_value: Cell<usize>,
+    }
+
+    impl Counter {
+        fn increment(&self) {
+            self.shared_value.fetch_add(1, atomic::Ordering::Relaxed);
+            self.local_value.set(self.local_value.get().wrapping_add(1));
+        }
+    }
+
+    static SHARED_VALUE: AtomicUsize = AtomicUsize::new(0);
+
+    static COUNTER: StaticFamily<Counter> = linked::new_static!(Counter {
+        shared_value: &SHARED_VALUE,
+        local_value: Cell::new(0),
+    });
+
+    #[test]
+    fn instances_share_static_state() {
+        let counter = COUNTER.get();
+        counter.increment();
+
+        let clone = counter.clone();
+        assert_eq!(clone.local_value.get(), 0);
+        assert!(clone.is_same_family(&counter));
+        assert_eq!(COUNTER.family(), counter.family());
+
+        thread::spawn(|| COUNTER.get().increment()).join().unwrap();
+
+        assert_eq!(SHARED_VALUE.load(atomic::Ordering::Relaxed), 2);
+        assert_eq!(COUNTER.family().instance_count(), 2);
+
+        drop(counter);
+        drop(clone);
+        assert_eq!(COUNTER.family().instance_count(), 0);
+    }
+
+    #[test]
+    fn empty_template() {
+        #[linked::object]
+        struct Empty {}
+
+        static EMPTY: StaticFamily<Empty> = linked::new_static!(Empty {});
+
+        let empty = EMPTY.get();
+        assert_eq!(empty.family(), EMPTY.family());
+    }
+}
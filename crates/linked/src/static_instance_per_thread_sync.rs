@@ -1,7 +1,13 @@
 // Copyright (c) Microsoft Corporation.
 // Copyright (c) Folo authors.
 
-use std::{sync::Arc, thread::LocalKey};
+use std::{cell::RefCell, sync::Arc, thread::LocalKey};
+
+#[cfg(feature = "virtual_threads")]
+use std::ptr;
+
+#[cfg(feature = "virtual_threads")]
+use crate::virtual_thread::{SlotKey, is_entered, virtual_slot};
 
 /// This is the real type of variables wrapped in the [`linked::thread_local_arc!` macro][1].
 /// See macro documentation for more details.
@@ -16,7 +22,11 @@ pub struct StaticInstancePerThreadSync<T>
 where
     T: linked::Object + Send + Sync,
 {
-    get_storage: fn() -> &'static LocalKey<Arc<T>>,
+    // Empty until the first access on a thread and again after the thread exit hooks have run.
+    get_storage: fn() -> &'static Storage<T>,
+
+    // Used to create the current thread's instance on first access and when it is reset.
+    new_instance: fn() -> T,
 }
 
 impl<T> StaticInstancePerThreadSync<T>
@@ -28,8 +38,11 @@ where
     /// It is not part of the public API and may be removed or changed at any time.
     #[doc(hidden)]
     #[must_use]
-    pub const fn new(get_storage: fn() -> &'static LocalKey<Arc<T>>) -> Self {
-        Self { get_storage }
+    pub const fn new(get_storage: fn() -> &'static Storage<T>, new_instance: fn() -> T) -> Self {
+        Self {
+            get_storage,
+            new_instance,
+        }
     }
 
     /// Executes a closure with the current thread's linked instance from
@@ -47,7 +60,13 @@ where
     where
         F: FnOnce(&Arc<T>) -> R,
     {
-        (self.get_storage)().with(f)
+        self.with_storage(|storage| {
+            if let Some(instance) = storage.borrow().as_ref() {
+                return f(instance);
+            }
+
+            f(&self.create_current_thread_instance())
+        })
     }
 
     /// Gets an `Arc<T>` to the current thread's linked instance from
@@ -70,10 +89,112 @@ where
     #[must_use]
     #[inline]
     pub fn to_arc(&self) -> Arc<T> {
-        (self.get_storage)().with(Arc::clone)
+        self.with(Arc::clone)
+    }
+
+    /// Creates the current thread's linked instance from the object family referenced by the
+    /// static variable, if it has not been created yet.
+    ///
+    /// Instances are normally created lazily on first access, so the first access on every thread
+    /// pays the cost of creating the instance. Call this when a thread starts (e.g. from a thread
+    /// pool's thread start callback) to keep that cost out of latency-sensitive code.
+    pub fn warm_up_current_thread(&self) {
+        self.with(|_| {});
+    }
+
+    /// Drops the current thread's linked instance from the object family referenced by the
+    /// static variable and replaces it with a new instance from the same family.
+    ///
+    /// The next access through the static variable on the current thread will see the new
+    /// instance, as if the thread had never accessed the static variable before. Instances
+    /// on other threads are not affected.
+    ///
+    /// Any `Arc<T>` previously obtained via [`.to_arc()`][Self::to_arc] keeps pointing to the old
+    /// instance, which is only dropped once the last such `Arc<T>` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a [`.with()`][Self::with] closure of the same static variable.
+    pub fn reset_current_thread(&self) {
+        // We create the new instance before touching the storage because creating a linked
+        // object may execute arbitrary code, including code that accesses this static variable.
+        let new_instance = Arc::new((self.new_instance)());
+
+        let old_instance = self.with_storage(|storage| storage.replace(Some(new_instance)));
+
+        if old_instance.is_none() {
+            self.release_on_thread_exit();
+        }
+
+        // Same here - dropping the old instance may access this static variable,
+        // so we only do it after we are done with the storage.
+        drop(old_instance);
+    }
+
+    fn create_current_thread_instance(&self) -> Arc<T> {
+        // We create the new instance outside any borrow of the storage because creating a linked
+        // object may execute arbitrary code, including code that accesses this static variable.
+        let new_instance = Arc::new((self.new_instance)());
+
+        let existing_instance = self.with_storage(|storage| {
+            let mut storage = storage.borrow_mut();
+
+            // If the arbitrary code above already created an instance, we use that one instead.
+            if let Some(existing_instance) = storage.as_ref() {
+                return Some(Arc::clone(existing_instance));
+            }
+
+            *storage = Some(Arc::clone(&new_instance));
+            None
+        });
+
+        // Same here - dropping the unused new instance may access this static variable,
+        // so we only do it after we are done with the storage.
+        if let Some(existing_instance) = existing_instance {
+            return existing_instance;
+        }
+
+        self.release_on_thread_exit();
+
+        new_instance
+    }
+
+    // Ensures the current thread's instance is dropped when the thread calls
+    // `linked::run_thread_exit_hooks()` instead of waiting for thread-local storage destruction.
+    fn release_on_thread_exit(&self) {
+        // The storage of virtual threads is released when the virtual thread is dropped.
+        #[cfg(feature = "virtual_threads")]
+        if is_entered() {
+            return;
+        }
+
+        let get_storage = self.get_storage;
+
+        crate::on_thread_exit(move || {
+            // If the thread-local storage is already destroyed, so is the instance.
+            let old_instance = get_storage().try_with(RefCell::take);
+            drop(old_instance);
+        });
+    }
+
+    // Calls `f` with the storage of the current thread - or of the virtual thread entered by the
+    // current thread, if any (only with the `virtual_threads` feature).
+    #[inline]
+    fn with_storage<R>(&self, f: impl FnOnce(&RefCell<Option<Arc<T>>>) -> R) -> R {
+        #[cfg(feature = "virtual_threads")]
+        if let Some(storage) = virtual_slot(SlotKey::PerThread(
+            ptr::from_ref((self.get_storage)()).addr(),
+        )) {
+            return f(&storage);
+        }
+
+        (self.get_storage)().with(f)
     }
 }
 
+// The current thread's instance, if it has been created.
+type Storage<T> = LocalKey<RefCell<Option<Arc<T>>>>;
+
 /// Declares that all static variables within the macro body
 /// contain thread-local [linked objects][crate].
 ///
@@ -145,10 +266,12 @@ macro_rules! thread_local_arc {
         $crate::__private::paste! {
             $crate::instances!(#[doc(hidden)] static [< $NAME _INITIALIZER >]: $t = $e;);
 
-            ::std::thread_local!(#[doc(hidden)] static [< $NAME _ARC >]: ::std::sync::Arc<$t> = ::std::sync::Arc::new([< $NAME _INITIALIZER >].get()));
+            ::std::thread_local!(#[doc(hidden)] static [< $NAME _ARC >]: ::std::cell::RefCell<::std::option::Option<::std::sync::Arc<$t>>> = const { ::std::cell::RefCell::new(::std::option::Option::None) });
 
             $(#[$attr])* $vis const $NAME: $crate::StaticInstancePerThreadSync<$t> =
-                $crate::StaticInstancePerThreadSync::new(move || &[< $NAME _ARC >]);
+                $crate::StaticInstancePerThreadSync::new(
+                    move || &[< $NAME _ARC >],
+                    move || [< $NAME _INITIALIZER >].get());
         }
     };
 }
@@ -160,6 +283,8 @@ mod tests {
         thread,
     };
 
+    use crate::Object;
+
     #[linked::object]
     struct TokenCache {
         local_value: AtomicUsize,
@@ -238,4 +363,64 @@ mod tests {
         .join()
         .unwrap();
     }
+
+    #[test]
+    fn warm_up_current_thread() {
+        linked::thread_local_arc!(static TOKEN_CACHE: TokenCache = TokenCache::new(1000));
+
+        TOKEN_CACHE.warm_up_current_thread();
+        TOKEN_CACHE.to_arc().increment();
+
+        // Warming up again does not replace the existing instance.
+        TOKEN_CACHE.warm_up_current_thread();
+        assert_eq!(TOKEN_CACHE.to_arc().value(), 1001);
+    }
+
+    #[test]
+    fn reset_current_thread() {
+        linked::thread_local_arc!(static TOKEN_CACHE: TokenCache = TokenCache::new(1000));
+
+        let old_cache = TOKEN_CACHE.to_arc();
+        old_cache.increment();
+
+        thread::spawn(move || {
+            TOKEN_CACHE.to_arc().increment();
+            TOKEN_CACHE.reset_current_thread();
+            assert_eq!(TOKEN_CACHE.to_arc().value(), 1000);
+        })
+        .join()
+        .unwrap();
+
+        // Resetting the instance on another thread does not affect the current thread.
+        assert_eq!(TOKEN_CACHE.to_arc().value(), 1001);
+
+        TOKEN_CACHE.reset_current_thread();
+        assert_eq!(TOKEN_CACHE.to_arc().value(), 1000);
+        TOKEN_CACHE.with(|cache| assert_eq!(cache.value(), 1000));
+
+        // Previously obtained references still point to the old instance.
+        assert_eq!(old_cache.value(), 1001);
+    }
+
+    #[test]
+    fn thread_exit_hooks_release_reset_instance() {
+        linked::thread_local_arc!(static TOKEN_CACHE: TokenCache = TokenCache::new(1000));
+
+        let family = TOKEN_CACHE.with(|cache| cache.family());
+
+        thread::spawn(move || {
+            TOKEN_CACHE.warm_up_current_thread();
+            linked::run_thread_exit_hooks();
+            assert_eq!(family.instance_count(), 1);
+
+            // Resetting after the instance was released also releases the new instance later.
+            TOKEN_CACHE.reset_current_thread();
+            assert_eq!(family.instance_count(), 2);
+
+            linked::run_thread_exit_hooks();
+            assert_eq!(family.instance_count(), 1);
+        })
+        .join()
+        .unwrap();
+    }
 }
@@ -1,14 +1,21 @@
 // Copyright (c) Microsoft Corporation.
 // Copyright (c) Folo authors.
 
-use std::any::{Any, TypeId};
+use std::any::TypeId;
+#[cfg(feature = "thread_tracking")]
+use std::cell::Cell;
 use std::cell::RefCell;
 use std::collections::hash_map;
-use std::sync::{LazyLock, RwLock};
+use std::rc::Rc;
 
-use hash_hasher::HashedMap;
-
-use crate::{ERR_POISONED_LOCK, Family};
+#[cfg(feature = "region_placement")]
+use crate::region_placement::place_instance;
+#[cfg(feature = "virtual_threads")]
+use crate::virtual_thread::{Virtualized, is_entered};
+use crate::{
+    ERR_POISONED_LOCK, Family, InstanceSlot, RegistryBackend, ThreadLocalBackend,
+    clear_populated_slots, register_populated_slot, registry, uses_shared_registry,
+};
 
 /// This is the real type of variables wrapped in the [`linked::instances!` macro][1].
 /// See macro documentation for more details.
@@ -16,11 +23,15 @@ use crate::{ERR_POISONED_LOCK, Family};
 /// Instances of this type are created by the [`linked::instances!` macro][1],
 /// never directly by user code, which can use `.get()` to obtain a linked instance of `T`.
 ///
+/// The per-thread state of the variable is kept in the thread-local storage provided by the
+/// backend `B` (see [`ThreadLocalBackend`]).
+///
 /// [1]: [crate::instances]
 #[derive(Debug)]
-pub struct StaticInstances<T>
+pub struct StaticInstances<T, B = RegistryBackend>
 where
     T: linked::Object,
+    B: ThreadLocalBackend<T>,
 {
     /// A function we can call to obtain the lookup key for the family of linked objects.
     ///
@@ -35,6 +46,9 @@ where
     /// this may be called multiple times, only one return value will ever be exposed to
     /// user code, with the others being dropped shortly after creation.
     first_instance_provider: fn() -> T,
+
+    /// Provides the slot in which the current thread keeps the per-thread state of the variable.
+    backend: B,
 }
 
 impl<T> StaticInstances<T>
@@ -49,10 +63,34 @@ where
     pub const fn new(
         family_key_provider: fn() -> TypeId,
         first_instance_provider: fn() -> T,
+    ) -> Self {
+        Self::with_backend(
+            family_key_provider,
+            first_instance_provider,
+            RegistryBackend::new(family_key_provider),
+        )
+    }
+}
+
+impl<T, B> StaticInstances<T, B>
+where
+    T: linked::Object,
+    B: ThreadLocalBackend<T>,
+{
+    /// This function exists to serve the inner workings of the
+    /// `linked::instances!` macro and should not be used directly.
+    /// It is not part of the public API and may be removed or changed at any time.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn with_backend(
+        family_key_provider: fn() -> TypeId,
+        first_instance_provider: fn() -> T,
+        backend: B,
     ) -> Self {
         Self {
             family_key_provider,
             first_instance_provider,
+            backend,
         }
     }
 
@@ -71,10 +109,267 @@ where
     /// [2]: [crate::thread_local_arc]
     #[must_use]
     pub fn get(&self) -> T {
-        if let Some(instance) = self.new_from_local_registry() {
+        let family = self.local_family().unwrap_or_else(|| self.register_local());
+
+        place_instance(|| family.into())
+    }
+
+    /// Creates a new linked instance of `T` like [`.get()`][Self::get] but only if the current
+    /// thread has already accessed the static variable, returning `None` otherwise.
+    ///
+    /// This never performs the per-thread setup of the static variable (nor creates the family
+    /// if no thread has accessed the static variable yet), making it suitable for code that must
+    /// not bring linked objects into existence on threads that have never used them, such as
+    /// telemetry flush logic that runs on every thread.
+    ///
+    /// Use [`.try_with()`][Self::try_with] to access the instance cached for the current thread
+    /// by `.with()` instead of creating a new instance.
+    #[must_use]
+    pub fn try_get(&self) -> Option<T> {
+        self.local_family()
+            .map(|family| place_instance(|| family.into()))
+    }
+
+    /// Executes a closure with a shared reference to the current thread's cached instance of `T`
+    /// from the family referenced by the static variable.
+    ///
+    /// The cached instance is created on the first call to `.with()` or
+    /// [`.with_mut()`][Self::with_mut] on each thread and is reused by all subsequent calls
+    /// on the same thread. It is separate from any instances returned by [`.get()`][Self::get].
+    /// The cached instance is dropped when the thread exits or calls
+    /// [`linked::run_thread_exit_hooks()`][crate::run_thread_exit_hooks], whichever comes first.
+    ///
+    /// # Performance
+    ///
+    /// This avoids creating a new instance on every access, making it suitable for hot paths that
+    /// only briefly need the instance, such as incrementing a counter.
+    ///
+    /// # Broadcasts
+    ///
+    /// With the `thread_tracking` Cargo feature, any mutations scheduled for the current thread
+    /// via `Family::broadcast()` are applied to the cached instance before `f` is called, unless
+    /// the instance is already being accessed by an outer `.with()` or `.with_mut()` call, in
+    /// which case they are left for later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a [`.with_mut()`][Self::with_mut] closure of the same
+    /// static variable.
+    #[inline]
+    pub fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let access = |cached: &CachedInstance<T>, f: F| {
+            cached.apply_broadcasts();
+
+            let instance = cached.instance.try_borrow().expect(
+                "cannot access instance via .with() while it is being accessed via .with_mut()",
+            );
+
+            f(&instance)
+        };
+
+        let mut f = Some(f);
+
+        self.with_existing_local_instance(|cached| access(cached, f.take().expect(ERR_F_TAKEN)))
+            .unwrap_or_else(|| access(&self.create_local_instance(), f.take().expect(ERR_F_TAKEN)))
+    }
+
+    /// Executes a closure with an exclusive reference to the current thread's cached instance
+    /// of `T` from the family referenced by the static variable.
+    ///
+    /// This accesses the same cached instance as [`.with()`][Self::with], with the same treatment
+    /// of broadcasts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a `.with()` or `.with_mut()` closure of the same
+    /// static variable.
+    #[inline]
+    pub fn with_mut<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let access = |cached: &CachedInstance<T>, f: F| {
+            cached.apply_broadcasts();
+
+            let mut instance = cached.instance.try_borrow_mut().expect(
+                "cannot access instance via .with_mut() while it is already being accessed",
+            );
+
+            f(&mut instance)
+        };
+
+        let mut f = Some(f);
+
+        self.with_existing_local_instance(|cached| access(cached, f.take().expect(ERR_F_TAKEN)))
+            .unwrap_or_else(|| access(&self.create_local_instance(), f.take().expect(ERR_F_TAKEN)))
+    }
+
+    /// Executes a closure with a shared reference to the current thread's cached instance of `T`
+    /// like [`.with()`][Self::with] but only if the instance has already been created by an
+    /// earlier `.with()` or [`.with_mut()`][Self::with_mut] call on the current thread.
+    ///
+    /// Returns `None` without calling `f` if there is no cached instance on the current thread.
+    /// This never creates the cached instance, making it suitable for flushing per-thread state
+    /// (e.g. telemetry) without creating empty instances on threads that never did any work.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a `.with_mut()` closure of the same static variable.
+    pub fn try_with<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.with_existing_local_instance(|cached| {
+            cached.apply_broadcasts();
+
+            let instance = cached.instance.try_borrow().expect(
+                "cannot access instance via .try_with() while it is being accessed via .with_mut()",
+            );
+
+            f(&instance)
+        })
+    }
+
+    // Calls `f` with the current thread's cached instance, if it has been created already.
+    //
+    // This is the hot path, so we call `f` while borrowing the slot instead of cloning the `Rc`
+    // out of it. Accessing the same static variable again from within `f` only borrows the slot
+    // again, which does not conflict with our borrow.
+    #[inline]
+    fn with_existing_local_instance<R>(
+        &self,
+        f: impl FnOnce(&CachedInstance<T>) -> R,
+    ) -> Option<R> {
+        self.slot_backend(self.backend)
+            .try_with_slot(|slot| slot.cached.borrow().as_deref().map(f))
+            .flatten()
+    }
+
+    // Creates the current thread's cached instance. This happens once per thread, so we keep it
+    // out of the inlined fast path of `.with()` and `.with_mut()`.
+    #[cold]
+    #[inline(never)]
+    fn create_local_instance(&self) -> Rc<CachedInstance<T>> {
+        let instance = if uses_shared_registry() {
+            self.shared_local_instance()
+        } else {
+            self.new_local_instance()
+        };
+
+        // The slot is already registered for clearing on thread exit by `.get()` when creating
+        // the instance. If the thread-local storage is being destroyed, the instance is simply
+        // not cached.
+        let previous = self
+            .slot_backend(self.backend)
+            .try_with_slot(|slot| slot.cached.replace(Some(Rc::clone(&instance))));
+        drop(previous);
+
+        instance
+    }
+
+    fn new_local_instance(&self) -> Rc<CachedInstance<T>> {
+        // We create the instance outside the borrow because creating a linked object may
+        // execute arbitrary code, including code that accesses the same static variable.
+        let instance = self.get();
+
+        Rc::new(CachedInstance {
+            family: instance.family(),
+            instance: RefCell::new(instance),
+            // Any broadcasts already queued for the current thread are applied on first access.
+            #[cfg(feature = "thread_tracking")]
+            broadcast_generation: Cell::new(0),
+        })
+    }
+
+    // When using a shared registry, all copies of the static variable in the process (in
+    // different dynamic libraries) share one cached instance per thread, kept in a slot of the
+    // registry. Each copy also keeps the instance in its own slot, so the shared slot is only
+    // consulted once per thread and copy.
+    fn shared_local_instance(&self) -> Rc<CachedInstance<T>> {
+        // Our own slot is cleared on thread exit like any other, even if we just take the shared
+        // instance without creating a new instance via `.get()`.
+        self.warm_up_current_thread();
+
+        let shared_backend = self.slot_backend(RegistryBackend::new(self.family_key_provider));
+
+        if let Some(instance) = shared_backend
+            .try_with_slot(|slot| slot.cached.borrow().clone())
+            .flatten()
+        {
             return instance;
         }
 
+        let instance = self.new_local_instance();
+
+        populate_slot(shared_backend, &instance.family);
+
+        let previous =
+            shared_backend.try_with_slot(|slot| slot.cached.replace(Some(Rc::clone(&instance))));
+        drop(previous);
+
+        instance
+    }
+
+    /// Prepares the current thread for obtaining instances of `T` via [`.get()`][Self::get],
+    /// performing the one-time per-thread setup (including creating the family if this is the
+    /// first access on any thread) ahead of time instead of on the first `.get()` call.
+    ///
+    /// Call this when a thread starts (e.g. from a thread pool's thread start callback) to keep
+    /// the one-time setup cost out of latency-sensitive code. Calling it more than once on the
+    /// same thread has no effect.
+    pub fn warm_up_current_thread(&self) {
+        if self.local_family().is_none() {
+            self.register_local();
+        }
+    }
+
+    /// The family of linked objects that all instances obtained via the static variable belong
+    /// to, creating the family if this is the first access on any thread.
+    ///
+    /// This allows handing the family to code that does not know about the static variable,
+    /// e.g. code that takes its dependencies as [`Family<T>`] parameters.
+    #[must_use]
+    pub fn family(&self) -> Family<T> {
+        self.local_family().unwrap_or_else(|| self.register_local())
+    }
+
+    /// Makes `family` the family of the static variable, instead of the family created from the
+    /// initializer expression of the static variable on first access.
+    ///
+    /// This applies to the entire process and is only possible before the static variable is
+    /// first accessed on any thread. This allows tests to point code that uses the static
+    /// variable at a family created by the test (e.g. with a test double as the template).
+    ///
+    /// # Errors
+    ///
+    /// Returns the family back if the static variable already has a family, either because it
+    /// has already been accessed or because a family has already been installed.
+    pub fn install_family(&self, family: Family<T>) -> Result<(), Family<T>> {
+        let mut global_registry = registry().families.write().expect(ERR_POISONED_LOCK);
+
+        match global_registry.entry((self.family_key_provider)()) {
+            hash_map::Entry::Occupied(_) => Err(family),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(Box::new(family));
+                Ok(())
+            }
+        }
+    }
+
+    // Returns the family from the current thread's slot, returning `None` if the static
+    // variable has not yet been seen by this thread.
+    fn local_family(&self) -> Option<Family<T>> {
+        self.slot_backend(self.backend)
+            .try_with_slot(|slot| slot.family.borrow().clone())
+            .flatten()
+    }
+
+    // Stores the family in the current thread's slot, initializing the global registry first
+    // if the family has not been seen by any thread yet.
+    fn register_local(&self) -> Family<T> {
         // TODO: This global registry step feels too smeared out.
         // Can we draw it together into one step under one lock?
         self.try_initialize_global_registry(self.first_instance_provider);
@@ -83,23 +378,33 @@ where
             .get_family_global()
             .expect("we just initialized it, the family must exist");
 
-        self.set_local(family);
+        populate_slot(self.slot_backend(self.backend), &family);
 
-        // We can now be certain the local registry has the value.
-        self.new_from_local_registry()
-            .expect("we just set the value, it must be there")
+        family
     }
 
-    fn set_local(&self, value: Family<T>) {
-        LOCAL_REGISTRY.with(|local_registry| {
-            local_registry
-                .borrow_mut()
-                .insert((self.family_key_provider)(), Box::new(value));
-        });
+    // Without the `virtual_threads` feature, the backend always provides the slot.
+    #[cfg(not(feature = "virtual_threads"))]
+    #[expect(
+        clippy::unused_self,
+        reason = "same signature as with the feature, which needs the family key"
+    )]
+    #[inline]
+    fn slot_backend<S>(&self, backend: S) -> S {
+        backend
+    }
+
+    // With the `virtual_threads` feature, the slot is provided by the virtual thread entered by
+    // the current thread, if any.
+    #[cfg(feature = "virtual_threads")]
+    #[inline]
+    fn slot_backend<S>(&self, backend: S) -> Virtualized<S> {
+        Virtualized::new(backend, self.family_key_provider)
     }
 
     fn get_family_global(&self) -> Option<Family<T>> {
-        GLOBAL_REGISTRY
+        registry()
+            .families
             .read()
             .expect(ERR_POISONED_LOCK)
             .get(&(self.family_key_provider)())
@@ -125,10 +430,8 @@ where
         // We do not today make use of our right to create a "first" instance of `T` even when
         // we do not need it. This is a potential future optimization if it proves valuable.
 
-        let mut global_registry = GLOBAL_REGISTRY.write().expect(ERR_POISONED_LOCK);
+        let mut global_registry = registry().families.write().expect(ERR_POISONED_LOCK);
 
-        // TODO: We are repeatedly acquiring the family key here and in sibling functions.
-        // Perhaps a trivial cost but explore the value of eliminating the duplicate access.
         let family_key = (self.family_key_provider)();
         let entry = global_registry.entry(family_key);
 
@@ -142,22 +445,164 @@ where
             }
         }
     }
+}
 
-    // Attempts to obtain a new instance of `T` using the current thread's family registry,
-    // returning `None` if the linked variable has not yet been seen by this thread and is
-    // therefore not present in the local registry.
-    fn new_from_local_registry(&self) -> Option<T> {
-        LOCAL_REGISTRY.with_borrow(|registry| {
-            let family_key = (self.family_key_provider)();
+// Stores the family in the current thread's slot provided by the backend, registering the slot
+// for clearing on thread exit if it was empty.
+fn populate_slot<T, B>(backend: B, family: &Family<T>)
+where
+    T: linked::Object,
+    B: ThreadLocalBackend<T>,
+{
+    // If the thread-local storage is being destroyed, we just do without the slot.
+    let is_populated = backend
+        .try_with_slot(|slot| slot.family.replace(Some(family.clone())).is_none())
+        .unwrap_or_default();
 
-            registry
-                .get(&family_key)
-                .and_then(|w| w.downcast_ref::<Family<T>>())
-                // TODO: We clone the family here, only to immediately transform it to a
-                // T instance. Can we skip the middle step and just create an instance directly?
-                .map(|family| family.clone().into())
-        })
+    // The slots of virtual threads are released when the virtual thread is dropped.
+    #[cfg(feature = "virtual_threads")]
+    if is_entered() {
+        return;
+    }
+
+    if is_populated {
+        register_populated_slot(move || {
+            let contents = backend.try_with_slot(InstanceSlot::take);
+            drop(contents);
+        });
+    }
+}
+
+// Without the `region_placement` feature, instances are placed wherever the allocator decides.
+#[cfg(not(feature = "region_placement"))]
+#[inline]
+fn place_instance<T>(create: impl FnOnce() -> T) -> T {
+    create()
+}
+
+// Only one of the two access paths of `.with()` and `.with_mut()` takes the closure.
+const ERR_F_TAKEN: &str = "the closure is taken by only one access path";
+
+/// An instance cached for the current thread by `.with()` and `.with_mut()`.
+pub(crate) struct CachedInstance<T>
+where
+    T: linked::Object,
+{
+    instance: RefCell<T>,
+    family: Family<T>,
+
+    // The broadcast generation of the family when we last applied broadcasts to the instance.
+    #[cfg(feature = "thread_tracking")]
+    broadcast_generation: Cell<usize>,
+}
+
+impl<T> CachedInstance<T>
+where
+    T: linked::Object,
+{
+    #[cfg(feature = "thread_tracking")]
+    #[inline]
+    fn apply_broadcasts(&self) {
+        let generation = self.family.broadcast_generation();
+
+        if generation == self.broadcast_generation.get() {
+            return;
+        }
+
+        // If the instance is being accessed by an outer call, we leave the broadcasts for later.
+        let Ok(mut instance) = self.instance.try_borrow_mut() else {
+            return;
+        };
+
+        self.broadcast_generation.set(generation);
+        self.family.apply_broadcasts(&mut instance);
     }
+
+    #[cfg(not(feature = "thread_tracking"))]
+    #[expect(
+        clippy::unused_self,
+        reason = "same signature as when broadcasts are tracked"
+    )]
+    #[inline]
+    fn apply_broadcasts(&self) {}
+}
+
+/// Returns a new linked instance of `T` from a family that is unique to the key type `K`,
+/// creating the family via `first_instance_provider` on first use.
+///
+/// This is the equivalent of a [`linked::instances!`][1] static variable for generic code, where
+/// static variables cannot be used because they cannot depend on generic parameters. Every
+/// distinct combination of `K` and `T` identifies one family, so using a generic type as the key
+/// creates one family per monomorphization.
+///
+/// All calls with the same `K` and `T` must use a functionally equivalent
+/// `first_instance_provider`, as only one of them will be used to create the family.
+///
+/// # Performance
+///
+/// This creates a new instance of `T` on every call so caching the return value is
+/// performance-critical, just as with [`linked::instances!`][1].
+///
+/// # Example
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// #[linked::object]
+/// struct Pool<T: Send + 'static> {
+///     items: Arc<Mutex<Vec<T>>>,
+/// }
+///
+/// impl<T: Send + 'static> Pool<T> {
+///     fn new() -> Self {
+///         let items = Arc::new(Mutex::new(Vec::new()));
+///
+///         linked::new!(Self {
+///             items: Arc::clone(&items),
+///         })
+///     }
+///
+///     /// Returns an instance from the family shared by all users of `Pool<T>` for this `T`.
+///     fn shared() -> Self {
+///         linked::type_keyed_instance::<Self, Self>(Self::new)
+///     }
+///
+///     fn push(&self, item: T) {
+///         self.items.lock().unwrap().push(item);
+///     }
+///
+///     fn len(&self) -> usize {
+///         self.items.lock().unwrap().len()
+///     }
+/// }
+///
+/// Pool::<String>::shared().push("hello".to_string());
+/// Pool::<u32>::shared().push(1);
+/// Pool::<u32>::shared().push(2);
+///
+/// // Each monomorphization has its own family.
+/// assert_eq!(Pool::<String>::shared().len(), 1);
+/// assert_eq!(Pool::<u32>::shared().len(), 2);
+/// ```
+///
+/// [1]: crate::instances
+#[must_use]
+pub fn type_keyed_instance<K, T>(first_instance_provider: fn() -> T) -> T
+where
+    K: 'static,
+    T: linked::Object,
+{
+    StaticInstances::new(type_key::<K, T>, first_instance_provider).get()
+}
+
+// We include `T` in the family key, so that the same key type can be used with different `T`
+// without the families colliding (which would fail as their type-occluded values differ).
+fn type_key<K, T>() -> TypeId
+where
+    K: 'static,
+    T: 'static,
+{
+    TypeId::of::<(K, T)>()
 }
 
 /// Declares that all static variables within the macro body define unique
@@ -166,6 +611,10 @@ where
 /// Each [`.get()`][1] on an included static variable returns a new linked object instance,
 /// with all instances obtained from the same static variable being part of the same family.
 ///
+/// Call [`.with()`][10] or [`.with_mut()`][11] to execute a closure with a reference to an
+/// instance cached for the current thread instead, which avoids creating a new instance on
+/// every access.
+///
 /// # Example
 ///
 /// ```
@@ -193,50 +642,136 @@ where
 /// * [`linked::InstancePerThreadSync<T>`][7] (if `T: Sync`)
 /// * [`linked::Family<T>`][3]
 ///
+/// # Compile-time families
+///
+/// If all the state shared by the family can be created in a `const` context, consider defining
+/// the family via [`linked::new_static!`][9] instead, which avoids the lazy initialization
+/// performed on first access of a `linked::instances!` static variable.
+///
+/// # Generic code
+///
+/// Static variables cannot depend on generic parameters. To get one family per monomorphization
+/// of a generic type, use [`linked::type_keyed_instance()`][8] instead.
+///
+/// # Injecting a family
+///
+/// Code that uses a static variable can be pointed at a different family (e.g. one created by a
+/// test) by installing the family via [`install_family()`][13] before the static variable is
+/// first accessed. Conversely, [`family()`][14] hands out the family of the static variable to
+/// code that takes its dependencies as [`Family<T>`][3] parameters.
+///
+/// ```
+/// # #[linked::object]
+/// # struct TokenCache { capacity: usize }
+/// # impl TokenCache { fn with_capacity(capacity: usize) -> Self { linked::new!(Self { capacity }) } }
+/// use linked::Object;
+///
+/// linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::with_capacity(1000));
+///
+/// // In a test, before any code accesses the static variable.
+/// TOKEN_CACHE
+///     .install_family(TokenCache::with_capacity(1).family())
+///     .unwrap();
+///
+/// assert_eq!(TOKEN_CACHE.with(|cache| cache.capacity), 1);
+/// ```
+///
+/// # Thread-local storage backend
+///
+/// Each static variable keeps its per-thread state in its own [`thread_local!`] slot. To keep
+/// the state in different thread-local storage (e.g. a `#[thread_local]` static variable on a
+/// nightly compiler), name a [`ThreadLocalBackend`][12] after the initializer expression:
+///
+/// ```
+/// # #[linked::object]
+/// # struct TokenCache { }
+/// # impl TokenCache { fn with_capacity(capacity: usize) -> Self { linked::new!(Self { } ) } }
+/// use linked::{InstanceSlot, ThreadLocalBackend};
+///
+/// thread_local!(static TOKEN_CACHE_SLOT: InstanceSlot<TokenCache> = const { InstanceSlot::new() });
+///
+/// #[derive(Clone, Copy, Debug)]
+/// struct TokenCacheBackend;
+///
+/// impl ThreadLocalBackend<TokenCache> for TokenCacheBackend {
+///     fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<TokenCache>) -> R) -> Option<R> {
+///         TOKEN_CACHE_SLOT.try_with(f).ok()
+///     }
+/// }
+///
+/// linked::instances!(
+///     static TOKEN_CACHE: TokenCache = TokenCache::with_capacity(1000) => TokenCacheBackend
+/// );
+/// ```
+///
 /// [1]: StaticInstances::get
 /// [3]: crate::Family
 /// [4]: crate::Object::handle
 /// [5]: crate::Object
 /// [6]: crate::InstancePerThread
 /// [7]: crate::InstancePerThreadSync
+/// [8]: crate::type_keyed_instance
+/// [9]: crate::new_static
+/// [10]: StaticInstances::with
+/// [11]: StaticInstances::with_mut
+/// [12]: crate::ThreadLocalBackend
+/// [13]: StaticInstances::install_family
+/// [14]: StaticInstances::family
 #[macro_export]
 macro_rules! instances {
     () => {};
 
+    ($(#[$attr:meta])* $vis:vis static $NAME:ident: $t:ty = $e:expr => $backend:path; $($rest:tt)*) => (
+        $crate::instances!($(#[$attr])* $vis static $NAME: $t = $e => $backend);
+        $crate::instances!($($rest)*);
+    );
+
     ($(#[$attr:meta])* $vis:vis static $NAME:ident: $t:ty = $e:expr; $($rest:tt)*) => (
         $crate::instances!($(#[$attr])* $vis static $NAME: $t = $e);
         $crate::instances!($($rest)*);
     );
 
-    ($(#[$attr:meta])* $vis:vis static $NAME:ident: $t:ty = $e:expr) => {
+    ($(#[$attr:meta])* $vis:vis static $NAME:ident: $t:ty = $e:expr => $backend:path) => {
         $crate::__private::paste! {
             #[doc(hidden)]
             #[expect(non_camel_case_types, reason = "intentionally uglified macro generated code")]
             struct [<__lookup_key_ $NAME>];
 
-            $(#[$attr])* $vis const $NAME: $crate::StaticInstances<$t> =
-            $crate::StaticInstances::new(
+            $(#[$attr])* $vis const $NAME: $crate::StaticInstances<$t, $backend> =
+            $crate::StaticInstances::with_backend(
                 ::std::any::TypeId::of::<[<__lookup_key_ $NAME>]>,
-                move || $e);
+                move || $e,
+                $backend);
         }
     };
-}
 
-// We use HashedMap which takes the raw value from Hash::hash() and uses it directly as the key.
-// This is OK because TypeId already returns a hashed value as its raw value, no need to hash more.
-// We also do not care about any hash manipulation because none of this is untrusted user input.
-type FamilyRegistry = HashedMap<TypeId, Box<dyn Any + Send + Sync>>;
-
-// Global registry that is the ultimate authority where all static variable based linked object
-// families are registered. Values inside are type-occluded `Family<T>` where T may be different
-// for each entry.
-static GLOBAL_REGISTRY: LazyLock<RwLock<FamilyRegistry>> =
-    LazyLock::new(|| RwLock::new(FamilyRegistry::default()));
-
-thread_local! {
-    // Thread-local registry where we cache any families that have been seen by the current
-    // thread. Values inside are type-occluded `Family<T>` where T may be different for each entry.
-    static LOCAL_REGISTRY: RefCell<FamilyRegistry> = RefCell::new(FamilyRegistry::default());
+    ($(#[$attr:meta])* $vis:vis static $NAME:ident: $t:ty = $e:expr) => {
+        $crate::__private::paste! {
+            // By default, each static variable has its own thread-local slot, which is much
+            // cheaper to access than looking up the slot by the family key.
+            #[doc(hidden)]
+            #[derive(Clone, Copy, Debug)]
+            #[expect(non_camel_case_types, reason = "intentionally uglified macro generated code")]
+            $vis struct [<__tls_ $NAME>];
+
+            impl $crate::ThreadLocalBackend<$t> for [<__tls_ $NAME>] {
+                #[inline]
+                fn try_with_slot<R>(
+                    &self,
+                    f: impl FnOnce(&$crate::InstanceSlot<$t>) -> R,
+                ) -> ::std::option::Option<R> {
+                    ::std::thread_local! {
+                        static SLOT: $crate::InstanceSlot<$t> =
+                            const { $crate::InstanceSlot::new() };
+                    }
+
+                    SLOT.try_with(f).ok()
+                }
+            }
+
+            $crate::instances!($(#[$attr])* $vis static $NAME: $t = $e => [<__tls_ $NAME>]);
+        }
+    };
 }
 
 /// Clears all data stored in the static variable based linked object family system
@@ -247,22 +782,24 @@ thread_local! {
 #[cfg_attr(test, mutants::skip)] // Test/bench logic, do not waste time mutating.
 #[doc(hidden)]
 pub fn __private_clear_linked_variables() {
-    let mut global_registry = GLOBAL_REGISTRY.write().expect(ERR_POISONED_LOCK);
-    global_registry.clear();
+    registry()
+        .families
+        .write()
+        .expect(ERR_POISONED_LOCK)
+        .clear();
 
-    LOCAL_REGISTRY.with(|local_registry| {
-        local_registry.borrow_mut().clear();
-    });
+    clear_populated_slots();
 }
 
 #[cfg(test)]
 mod tests {
     use std::any::TypeId;
+    use std::marker::PhantomData;
     use std::rc::Rc;
     use std::sync::{Arc, Mutex};
     use std::thread;
 
-    use crate::StaticInstances;
+    use crate::{Object, StaticInstances};
 
     #[linked::object]
     struct TokenCache {
@@ -369,6 +906,253 @@ mod tests {
         assert_eq!(YELLOW_TOKEN_CACHE.get().value(), 2002);
     }
 
+    #[test]
+    fn warm_up_current_thread() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        TOKEN_CACHE.warm_up_current_thread();
+        TOKEN_CACHE.warm_up_current_thread();
+
+        let token_cache = TOKEN_CACHE.get();
+        assert_eq!(token_cache.value(), 42);
+        token_cache.increment();
+
+        thread::spawn(move || {
+            TOKEN_CACHE.warm_up_current_thread();
+
+            // Same family as the instances on the main thread.
+            assert_eq!(TOKEN_CACHE.get().value(), 43);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn with_reuses_cached_instance() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        let family = TOKEN_CACHE.with(|cache| {
+            assert_eq!(cache.value(), 42);
+            cache.increment();
+            cache.family()
+        });
+
+        // The same instance is used on every access from the same thread.
+        assert_eq!(family.instance_count(), 1);
+        TOKEN_CACHE.with(TokenCache::increment);
+        assert_eq!(family.instance_count(), 1);
+
+        // Other static variables can be used from within the closure.
+        linked::instances!(static OTHER_CACHE: TokenCache = TokenCache::new(7));
+        TOKEN_CACHE.with(|_| OTHER_CACHE.with(|other| assert_eq!(other.value(), 7)));
+
+        thread::spawn(move || {
+            TOKEN_CACHE.with(|cache| assert_eq!(cache.value(), 44));
+            assert_eq!(family.instance_count(), 2);
+        })
+        .join()
+        .unwrap();
+
+        // The instance from the other thread was dropped when the thread exited.
+        assert_eq!(TOKEN_CACHE.get().family().instance_count(), 2);
+    }
+
+    #[test]
+    fn thread_exit_hooks_release_cached_instances() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        let family = TOKEN_CACHE.get().family();
+
+        thread::spawn(move || {
+            TOKEN_CACHE.with(TokenCache::increment);
+            assert_eq!(family.instance_count(), 1);
+
+            linked::run_thread_exit_hooks();
+            assert_eq!(family.instance_count(), 0);
+
+            // The next access caches a new instance.
+            TOKEN_CACHE.with(|cache| assert_eq!(cache.value(), 43));
+            assert_eq!(family.instance_count(), 1);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn family_is_shared_by_instances() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        let family = TOKEN_CACHE.family();
+        assert_eq!(family.instance_count(), 0);
+
+        assert!(TOKEN_CACHE.get().family() == family);
+        assert!(thread::spawn(|| TOKEN_CACHE.family()).join().unwrap() == family);
+        assert!(TOKEN_CACHE.with(Object::family) == family);
+    }
+
+    #[test]
+    fn installed_family_used_by_all_threads() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        let family = TokenCache::new(7).family();
+        TOKEN_CACHE.install_family(family.clone()).unwrap();
+
+        assert_eq!(TOKEN_CACHE.get().value(), 7);
+        assert!(TOKEN_CACHE.family() == family);
+
+        thread::spawn(|| TOKEN_CACHE.with(|cache| assert_eq!(cache.value(), 7)))
+            .join()
+            .unwrap();
+
+        // Once the static variable has a family, installing another one fails.
+        let other = TokenCache::new(8).family();
+        assert!(TOKEN_CACHE.install_family(other.clone()).unwrap_err() == other);
+    }
+
+    #[test]
+    fn install_family_after_first_access_fails() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        TOKEN_CACHE.with(|_| {});
+
+        let family = TokenCache::new(7).family();
+        assert!(TOKEN_CACHE.install_family(family).is_err());
+        assert_eq!(TOKEN_CACHE.get().value(), 42);
+    }
+
+    #[test]
+    fn with_mut_provides_exclusive_access() {
+        #[linked::object]
+        struct LocalCounter {
+            local_value: usize,
+        }
+
+        impl LocalCounter {
+            fn new() -> Self {
+                linked::new!(Self { local_value: 0 })
+            }
+        }
+
+        linked::instances!(static COUNTER: LocalCounter = LocalCounter::new());
+
+        COUNTER.with_mut(|counter| counter.local_value = counter.local_value.wrapping_add(1));
+        COUNTER.with_mut(|counter| counter.local_value = counter.local_value.wrapping_add(1));
+
+        assert_eq!(COUNTER.with(|counter| counter.local_value), 2);
+
+        // Instances from `.get()` are separate from the cached instance.
+        assert_eq!(COUNTER.get().local_value, 0);
+    }
+
+    #[test]
+    fn try_get_and_try_with_do_not_initialize() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        assert!(TOKEN_CACHE.try_get().is_none());
+        assert!(TOKEN_CACHE.try_with(TokenCache::value).is_none());
+
+        let token_cache = TOKEN_CACHE.get();
+        token_cache.increment();
+        let family = token_cache.family();
+
+        // The thread has accessed the static variable but has not used `.with()` yet.
+        assert_eq!(TOKEN_CACHE.try_get().unwrap().value(), 43);
+        assert!(TOKEN_CACHE.try_with(TokenCache::value).is_none());
+
+        TOKEN_CACHE.with(TokenCache::increment);
+        assert_eq!(TOKEN_CACHE.try_with(TokenCache::value), Some(44));
+
+        thread::spawn(move || {
+            // Nothing is created on a thread that has never accessed the static variable.
+            assert!(TOKEN_CACHE.try_get().is_none());
+            assert!(TOKEN_CACHE.try_with(TokenCache::value).is_none());
+
+            // Only the instances of the main thread exist.
+            assert_eq!(family.instance_count(), 2);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[cfg(feature = "thread_tracking")]
+    #[test]
+    fn broadcast_applied_on_next_access() {
+        use std::sync::mpsc;
+
+        #[linked::object]
+        struct Limit {
+            value: usize,
+        }
+
+        impl Limit {
+            fn new() -> Self {
+                linked::new!(Self { value: 10 })
+            }
+        }
+
+        linked::instances!(static LIMIT: Limit = Limit::new());
+
+        let family = LIMIT.with(Limit::family);
+
+        let (remote_ready_tx, remote_ready_rx) = mpsc::channel();
+        let (broadcast_sent_tx, broadcast_sent_rx) = mpsc::channel::<()>();
+
+        let remote = thread::spawn(move || {
+            assert_eq!(LIMIT.with(|limit| limit.value), 10);
+            remote_ready_tx.send(()).unwrap();
+
+            broadcast_sent_rx.recv().unwrap();
+            LIMIT.with(|limit| limit.value)
+        });
+
+        remote_ready_rx.recv().unwrap();
+
+        assert_eq!(family.broadcast(|limit: &mut Limit| limit.value = 20), 2);
+        broadcast_sent_tx.send(()).unwrap();
+
+        assert_eq!(remote.join().unwrap(), 20);
+        assert_eq!(LIMIT.with(|limit| limit.value), 20);
+
+        // Broadcasts sent while the instance is being accessed are applied on the next access.
+        LIMIT.with_mut(|limit| {
+            family.broadcast(|limit: &mut Limit| limit.value = 30);
+            assert_eq!(limit.value, 20);
+        });
+        assert_eq!(LIMIT.with(|limit| limit.value), 30);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_inside_with_mut_panics() {
+        linked::instances!(static TOKEN_CACHE: TokenCache = TokenCache::new(42));
+
+        TOKEN_CACHE.with_mut(|_| TOKEN_CACHE.with(|_| {}));
+    }
+
+    #[test]
+    fn type_keyed_per_key_type() {
+        struct Cache<K> {
+            _key: PhantomData<K>,
+        }
+
+        fn get_cache<K: 'static>() -> TokenCache {
+            crate::type_keyed_instance::<Cache<K>, TokenCache>(|| TokenCache::new(5))
+        }
+
+        get_cache::<u8>().increment();
+
+        thread::spawn(|| {
+            get_cache::<u8>().increment();
+            get_cache::<u16>().increment();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(get_cache::<u8>().value(), 7);
+        assert_eq!(get_cache::<u16>().value(), 6);
+        assert_eq!(get_cache::<u32>().value(), 5);
+    }
+
     #[test]
     fn stored_in_thread_local() {
         // We demonstrate that what we get from `instances!` can be stored in thread-local storage
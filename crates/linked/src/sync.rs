@@ -0,0 +1,10 @@
+//! Synchronization primitives used by the state shared between the instances of a family.
+//!
+//! When building with `--cfg loom`, these are the `loom` equivalents of the `std` primitives,
+//! which allows the family machinery to be model-checked together with the linked objects built
+//! on top of it.
+
+#[cfg(loom)]
+pub(crate) use loom::sync::{Arc, Mutex, atomic};
+#[cfg(not(loom))]
+pub(crate) use std::sync::{Arc, Mutex, atomic};
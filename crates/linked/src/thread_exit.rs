@@ -0,0 +1,217 @@
+use std::{cell::RefCell, mem};
+
+/// Registers a hook to run when the current thread exits.
+///
+/// Use this to tear down per-thread state such as the current thread's instances of linked
+/// objects (e.g. flushing buffered data or deregistering from a shared registry).
+///
+/// Hooks run in reverse order of registration, either:
+///
+/// 1. when the thread calls [`linked::run_thread_exit_hooks()`][1] (recommended), or
+/// 2. as a fallback, when the thread-local storage of the thread is destroyed on thread exit.
+///
+/// # Thread-local storage destruction order
+///
+/// The order in which thread-local variables are destroyed on thread exit is unspecified. If a
+/// hook runs as part of thread-local storage destruction, any thread-local variables it accesses
+/// (including those behind [`linked::thread_local_rc!`][2] and [`linked::thread_local_arc!`][3])
+/// may already have been destroyed, in which case accessing them panics.
+///
+/// To run the hooks while all thread-local state is still intact, call
+/// [`linked::run_thread_exit_hooks()`][1] at the end of the thread's entry point. Thread pools
+/// typically offer a callback that is called before a worker thread exits, which is a suitable
+/// place to do this.
+///
+/// If called while the hooks of the current thread are already being run as part of thread-local
+/// storage destruction, the hook is executed immediately.
+///
+/// # Example
+///
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::sync::{Arc, Mutex};
+/// #
+/// # #[linked::object]
+/// # struct EventLog {
+/// #     buffer: RefCell<Vec<String>>,
+/// #     flushed: Arc<Mutex<Vec<String>>>,
+/// # }
+/// #
+/// # impl EventLog {
+/// #     pub fn new() -> Self {
+/// #         let flushed = Arc::new(Mutex::new(Vec::new()));
+/// #
+/// #         linked::new!(Self {
+/// #             buffer: RefCell::new(Vec::new()),
+/// #             flushed: Arc::clone(&flushed),
+/// #         })
+/// #     }
+/// #
+/// #     pub fn record(&self, event: &str) {
+/// #         self.buffer.borrow_mut().push(event.to_string());
+/// #     }
+/// #
+/// #     pub fn flush(&self) {
+/// #         self.flushed.lock().unwrap().append(&mut self.buffer.borrow_mut());
+/// #     }
+/// #
+/// #     pub fn flushed_count(&self) -> usize {
+/// #         self.flushed.lock().unwrap().len()
+/// #     }
+/// # }
+/// use std::thread;
+///
+/// linked::thread_local_rc!(static EVENT_LOG: EventLog = EventLog::new());
+///
+/// thread::spawn(|| {
+///     EVENT_LOG.with(|log| log.record("started"));
+///
+///     // Make sure whatever is buffered on this thread is not lost when the thread exits.
+///     linked::on_thread_exit(|| EVENT_LOG.with(|log| log.flush()));
+///
+///     EVENT_LOG.with(|log| log.record("finished"));
+///
+///     // Runs the hooks while the thread-local storage is still intact.
+///     linked::run_thread_exit_hooks();
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert_eq!(EVENT_LOG.with(|log| log.flushed_count()), 2);
+/// ```
+///
+/// [1]: crate::run_thread_exit_hooks
+/// [2]: crate::thread_local_rc
+/// [3]: crate::thread_local_arc
+pub fn on_thread_exit(hook: impl FnOnce() + 'static) {
+    let mut hook = Some(hook);
+
+    // If the hooks are being destroyed, we are too late to register - just run the hook now.
+    _ = THREAD_EXIT_HOOKS.try_with(|hooks| {
+        let hook = hook
+            .take()
+            .expect("we only take the hook once and this closure runs at most once");
+
+        hooks.hooks.borrow_mut().push(Box::new(hook));
+    });
+
+    if let Some(hook) = hook {
+        hook();
+    }
+}
+
+/// Runs all hooks registered via [`linked::on_thread_exit()`][1] on the current thread,
+/// in reverse order of registration.
+///
+/// Call this at the end of a thread's entry point to run the hooks while all thread-local
+/// storage is still intact. Any hooks registered by the hooks themselves are also executed
+/// before this function returns.
+///
+/// Hooks that have been executed are removed, so calling this multiple times is harmless -
+/// each hook runs exactly once. Any hooks registered after this call will run either on the
+/// next call or when the thread-local storage of the thread is destroyed.
+///
+/// [1]: crate::on_thread_exit
+pub fn run_thread_exit_hooks() {
+    // If the hooks are being destroyed, they are already being executed - nothing for us to do.
+    _ = THREAD_EXIT_HOOKS.try_with(ThreadExitHooks::run);
+}
+
+struct ThreadExitHooks {
+    hooks: RefCell<Vec<Box<dyn FnOnce()>>>,
+}
+
+impl ThreadExitHooks {
+    fn run(&self) {
+        // Hooks may register more hooks, so we keep going until there are no more.
+        loop {
+            let hooks = mem::take(&mut *self.hooks.borrow_mut());
+
+            if hooks.is_empty() {
+                return;
+            }
+
+            for hook in hooks.into_iter().rev() {
+                hook();
+            }
+        }
+    }
+}
+
+impl Drop for ThreadExitHooks {
+    fn drop(&mut self) {
+        self.run();
+    }
+}
+
+thread_local! {
+    static THREAD_EXIT_HOOKS: ThreadExitHooks = const {
+        ThreadExitHooks {
+            hooks: RefCell::new(Vec::new()),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn explicit_run_in_reverse_order() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+
+        for i in 0..3 {
+            let calls = Rc::clone(&calls);
+            on_thread_exit(move || calls.borrow_mut().push(i));
+        }
+
+        run_thread_exit_hooks();
+        assert_eq!(*calls.borrow(), vec![2, 1, 0]);
+
+        // Each hook only runs once.
+        run_thread_exit_hooks();
+        assert_eq!(calls.borrow().len(), 3);
+    }
+
+    #[test]
+    fn hooks_registered_by_hooks_also_run() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+
+        on_thread_exit({
+            let calls = Rc::clone(&calls);
+
+            move || {
+                calls.borrow_mut().push("outer");
+
+                on_thread_exit(move || calls.borrow_mut().push("inner"));
+            }
+        });
+
+        run_thread_exit_hooks();
+        assert_eq!(*calls.borrow(), vec!["outer", "inner"]);
+    }
+
+    #[test]
+    fn runs_on_thread_exit() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        thread::spawn({
+            let calls = Arc::clone(&calls);
+
+            move || {
+                on_thread_exit(move || calls.lock().unwrap().push("exited"));
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["exited"]);
+    }
+}
@@ -0,0 +1,392 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
+use std::rc::Rc;
+
+use hash_hasher::HashedMap;
+
+use crate::{CachedInstance, Family, registry};
+
+/// Provides the thread-local storage in which a [`linked::instances!`][1] static variable keeps
+/// its per-thread state.
+///
+/// By default, every static variable declared via `linked::instances!` stores its per-thread
+/// state in its own [`thread_local!`] slot, which makes accessing the static variable little more
+/// expensive than accessing a `thread_local!` variable directly. Implement this trait to provide
+/// a different thread-local storage mechanism, such as a `#[thread_local]` static variable
+/// (which requires a nightly compiler) or a field in an existing thread-local context object.
+///
+/// The backend is selected by naming it after the initializer expression in the
+/// `linked::instances!` macro. The backend must be a unit struct, as the macro uses its name as
+/// both a type and a value:
+///
+/// ```ignore
+/// linked::instances!(static <NAME>: <Type> = <initializer> => <Backend>);
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use std::cell::RefCell;
+///
+/// use linked::{InstanceSlot, ThreadLocalBackend};
+///
+/// # #[linked::object]
+/// # struct EventLog {}
+/// # impl EventLog {
+/// #     pub fn new() -> Self {
+/// #         linked::new!(Self {})
+/// #     }
+/// #     pub fn record(&self, _event: &str) {}
+/// # }
+/// // An existing thread-local context of the application, which we extend with the slot.
+/// struct WorkerContext {
+///     name: RefCell<String>,
+///     event_log: InstanceSlot<EventLog>,
+/// }
+///
+/// thread_local! {
+///     static WORKER_CONTEXT: WorkerContext = const {
+///         WorkerContext {
+///             name: RefCell::new(String::new()),
+///             event_log: InstanceSlot::new(),
+///         }
+///     };
+/// }
+///
+/// #[derive(Clone, Copy, Debug)]
+/// struct WorkerContextBackend;
+///
+/// impl ThreadLocalBackend<EventLog> for WorkerContextBackend {
+///     #[inline]
+///     fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<EventLog>) -> R) -> Option<R> {
+///         WORKER_CONTEXT.try_with(|context| f(&context.event_log)).ok()
+///     }
+/// }
+///
+/// linked::instances!(static EVENT_LOG: EventLog = EventLog::new() => WorkerContextBackend);
+///
+/// EVENT_LOG.with(|log| log.record("started"));
+/// ```
+///
+/// # Native thread-local variables
+///
+/// On a nightly compiler with the `thread_local` feature enabled, a `#[thread_local]` static
+/// variable avoids the lazy registration of the destructor performed by [`thread_local!`]:
+///
+/// ```ignore
+/// #![feature(thread_local)]
+///
+/// #[thread_local]
+/// static EVENT_LOG_SLOT: InstanceSlot<EventLog> = InstanceSlot::new();
+///
+/// #[derive(Clone, Copy, Debug)]
+/// struct NativeBackend;
+///
+/// impl ThreadLocalBackend<EventLog> for NativeBackend {
+///     #[inline]
+///     fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<EventLog>) -> R) -> Option<R> {
+///         Some(f(&EVENT_LOG_SLOT))
+///     }
+/// }
+///
+/// linked::instances!(static EVENT_LOG: EventLog = EventLog::new() => NativeBackend);
+/// ```
+///
+/// The destructors of `#[thread_local]` static variables are never run, so the contents of such
+/// a slot are only released by the thread exit hooks of the thread (see
+/// [`linked::on_thread_exit()`][2]), which run either when the thread calls
+/// [`linked::run_thread_exit_hooks()`][3] or when the thread-local storage of the thread is
+/// destroyed.
+///
+/// [1]: crate::instances
+/// [2]: crate::on_thread_exit
+/// [3]: crate::run_thread_exit_hooks
+pub trait ThreadLocalBackend<T>: Copy + 'static
+where
+    T: linked::Object,
+{
+    /// Calls `f` with the current thread's slot, returning `None` without calling `f` if the
+    /// slot is no longer available because the thread-local storage is being destroyed.
+    ///
+    /// Every call on the same thread must provide the same slot and different threads must
+    /// never be provided the same slot.
+    fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<T>) -> R) -> Option<R>;
+}
+
+/// The per-thread state of one [`linked::instances!`][1] static variable, stored in the
+/// thread-local storage provided by a [`ThreadLocalBackend`].
+///
+/// The contents are managed by the static variable - a backend only needs to create an empty
+/// slot via [`InstanceSlot::new()`] for each thread.
+///
+/// [1]: crate::instances
+pub struct InstanceSlot<T>
+where
+    T: linked::Object,
+{
+    // The family of the static variable, once the static variable has been accessed on the
+    // current thread.
+    pub(crate) family: RefCell<Option<Family<T>>>,
+
+    // The instance used by `.with()` and `.with_mut()`, once created.
+    pub(crate) cached: RefCell<Option<Rc<CachedInstance<T>>>>,
+}
+
+impl<T> InstanceSlot<T>
+where
+    T: linked::Object,
+{
+    /// Creates an empty slot.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            family: RefCell::new(None),
+            cached: RefCell::new(None),
+        }
+    }
+
+    // Removes the contents of the slot, returning them so the caller can drop them after it has
+    // stopped accessing the slot (dropping them may access static variables).
+    //
+    // If we are called from within `.with()` or `.with_mut()`, the cached instance is in use and
+    // stays in the slot, to be dropped with the slot itself.
+    pub(crate) fn take(&self) -> SlotContents<T> {
+        let cached = self
+            .cached
+            .try_borrow_mut()
+            .ok()
+            .and_then(|mut cached| cached.take());
+
+        (self.family.take(), cached)
+    }
+}
+
+impl<T> Default for InstanceSlot<T>
+where
+    T: linked::Object,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for InstanceSlot<T>
+where
+    T: linked::Object,
+{
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstanceSlot")
+            .field(
+                "family",
+                &self.family.try_borrow().map(|family| family.is_some()),
+            )
+            .field(
+                "cached",
+                &self.cached.try_borrow().map(|cached| cached.is_some()),
+            )
+            .finish()
+    }
+}
+
+pub(crate) type SlotContents<T> = (Option<Family<T>>, Option<Rc<CachedInstance<T>>>);
+
+/// The [`ThreadLocalBackend`] used by static variables that are not declared via the
+/// [`linked::instances!`][1] macro, such as those behind [`linked::type_keyed_instance()`][2].
+///
+/// The slots are kept in a thread-local map keyed by the family key, which makes every access
+/// more expensive than with the backend generated by the macro for each static variable.
+///
+/// [1]: crate::instances
+/// [2]: crate::type_keyed_instance
+#[derive(Clone, Copy, Debug)]
+pub struct RegistryBackend {
+    family_key_provider: fn() -> TypeId,
+}
+
+impl RegistryBackend {
+    pub(crate) const fn new(family_key_provider: fn() -> TypeId) -> Self {
+        Self {
+            family_key_provider,
+        }
+    }
+}
+
+impl<T> ThreadLocalBackend<T> for RegistryBackend
+where
+    T: linked::Object,
+{
+    fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<T>) -> R) -> Option<R> {
+        let family_key = (self.family_key_provider)();
+
+        // We hand out a clone of the `Rc` instead of accessing the slot while borrowing the
+        // registry, so `f` may access other static variables (using the same registry) meanwhile.
+        let mut slot = None;
+
+        // The slots are in the thread-local storage of the copy of `linked` owning the registry,
+        // which is not our own if we are attached to a shared registry.
+        registry().with_thread_slots(&mut |slots| {
+            slot = Some(Rc::clone(
+                slots
+                    .borrow_mut()
+                    .entry(family_key)
+                    .or_insert_with(|| Box::new(Rc::new(InstanceSlot::<T>::new())))
+                    .downcast_ref::<Rc<InstanceSlot<T>>>()
+                    .expect("the family key determines the type of the slot"),
+            ));
+        });
+
+        let slot = slot?;
+
+        Some(f(&slot))
+    }
+}
+
+// Values inside are type-occluded `Rc<InstanceSlot<T>>` where T may be different for each entry.
+pub(crate) type LocalSlots = HashedMap<TypeId, Box<dyn Any>>;
+
+thread_local! {
+    // The slots of the static variables using `RegistryBackend` on the current thread, as well as
+    // the shared slots of all static variables in the process if we own a shared registry.
+    static LOCAL_SLOTS: RefCell<LocalSlots> = RefCell::new(HashedMap::default());
+
+    // Clears the slots populated on the current thread, regardless of their backend.
+    static POPULATED_SLOTS: RefCell<Vec<Box<dyn FnOnce()>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Calls `f` with the slots of the current thread, unless the thread-local storage is being
+/// destroyed.
+pub(crate) fn with_thread_slots(f: &mut dyn FnMut(&RefCell<LocalSlots>)) {
+    // If the thread-local storage is being destroyed, so are the slots.
+    _ = LOCAL_SLOTS.try_with(|slots| f(slots));
+}
+
+/// Records that the current thread has populated a slot, so its contents are released when
+/// the thread exits (or when the static variables are cleared) by calling `clear`.
+///
+/// If using a shared registry, this is recorded by the copy of `linked` owning the registry.
+pub(crate) fn register_populated_slot(clear: impl FnOnce() + 'static) {
+    registry().register_populated_slot(Box::new(clear));
+}
+
+/// Records a populated slot on the current thread of this copy of `linked`.
+pub(crate) fn register_populated_slot_on_thread(clear: Box<dyn FnOnce()>) {
+    let is_first = POPULATED_SLOTS
+        .try_with(|slots| {
+            let mut slots = slots.borrow_mut();
+            slots.push(clear);
+            slots.len() == 1
+        })
+        // If the thread-local storage is being destroyed, so are the slots.
+        .unwrap_or_default();
+
+    // The slots are cleared when the thread calls `linked::run_thread_exit_hooks()` instead of
+    // waiting for thread-local storage destruction, which does not happen for some backends.
+    if is_first {
+        crate::on_thread_exit(clear_populated_slots);
+    }
+}
+
+/// Clears all the slots populated on the current thread.
+pub(crate) fn clear_populated_slots() {
+    // Dropping the contents of the slots may access static variables, so we take the list first.
+    let clear_fns = POPULATED_SLOTS.try_with(RefCell::take).unwrap_or_default();
+
+    for clear in clear_fns {
+        clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::thread;
+
+    use super::*;
+    use crate::Object;
+
+    #[linked::object]
+    struct Counter {
+        value: Cell<usize>,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            linked::new!(Self {
+                value: Cell::new(0),
+            })
+        }
+
+        fn increment(&self) {
+            self.value.set(self.value.get().wrapping_add(1));
+        }
+    }
+
+    thread_local! {
+        static COUNTER_SLOT: InstanceSlot<Counter> = const { InstanceSlot::new() };
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    struct CounterBackend;
+
+    impl ThreadLocalBackend<Counter> for CounterBackend {
+        fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<Counter>) -> R) -> Option<R> {
+            COUNTER_SLOT.try_with(f).ok()
+        }
+    }
+
+    linked::instances!(static COUNTER: Counter = Counter::new() => CounterBackend);
+
+    fn slot_is_populated() -> bool {
+        COUNTER_SLOT.with(|slot| slot.family.borrow().is_some())
+    }
+
+    #[test]
+    fn custom_backend_holds_state() {
+        thread::spawn(|| {
+            assert!(!slot_is_populated());
+
+            COUNTER.with(Counter::increment);
+            COUNTER.with(Counter::increment);
+            assert_eq!(COUNTER.with(|counter| counter.value.get()), 2);
+
+            assert!(slot_is_populated());
+            assert!(COUNTER_SLOT.with(|slot| slot.cached.borrow().is_some()));
+
+            let family = COUNTER.get().family();
+            assert_eq!(family.instance_count(), 1);
+
+            linked::run_thread_exit_hooks();
+
+            // The slot was cleared, releasing the cached instance.
+            assert!(!slot_is_populated());
+            assert_eq!(family.instance_count(), 0);
+            assert!(COUNTER.try_get().is_none());
+        })
+        .join()
+        .unwrap();
+
+        // Each thread has its own slot.
+        thread::spawn(|| {
+            assert!(!slot_is_populated());
+            assert_eq!(COUNTER.with(|counter| counter.value.get()), 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn default_backend_is_per_static() {
+        linked::instances! {
+            static FIRST: Counter = Counter::new();
+            pub(crate) static SECOND: Counter = Counter::new();
+        }
+
+        FIRST.with(Counter::increment);
+
+        assert_eq!(FIRST.with(|counter| counter.value.get()), 1);
+        assert_eq!(SECOND.with(|counter| counter.value.get()), 0);
+        assert_ne!(FIRST.get().family(), SECOND.get().family());
+    }
+}
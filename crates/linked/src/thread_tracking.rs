@@ -0,0 +1,847 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::mem;
+use std::thread::{self, ThreadId};
+
+use crate::__private::current_thread_id;
+use crate::sync::atomic::{self, AtomicUsize};
+use crate::sync::{Arc, Mutex};
+use crate::{BuildThreadIdHasher, ERR_POISONED_LOCK, Family, Object};
+
+/// A message sent to the instances of a family on a specific thread.
+type Message<T> = Box<dyn FnOnce(&T) + Send>;
+
+/// A type-occluded `Message<T>`, as the state of the family does not know the type `T`.
+type OccludedMessage = Box<dyn Any + Send>;
+
+/// A mutation broadcast to an instance of a family on every thread.
+type Broadcast<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// Called when a broadcast is queued for a thread registered for immediate delivery.
+type BroadcastNotifier = Arc<Box<dyn Fn() + Send + Sync>>;
+
+impl<T> Family<T> {
+    /// The number of instances of the family that currently exist, grouped by the thread that
+    /// created them. Threads without any instances are not included.
+    ///
+    /// An instance is counted against the thread that created it, even if it was later moved to a
+    /// different thread (possible if `T: Send`).
+    ///
+    /// This can be used to detect instances leaked by threads that have exited: per-thread
+    /// instances managed by this crate are dropped when their thread exits, so any count still
+    /// reported for an exited thread belongs to instances that were either moved to another thread
+    /// or leaked (e.g. kept alive by a reference cycle or a thread pool that never runs
+    /// [`linked::run_thread_exit_hooks()`][crate::run_thread_exit_hooks]).
+    ///
+    /// This is intended for diagnostics. Instances may be created and dropped on other threads
+    /// concurrently, so the value may be outdated by the time you look at it.
+    ///
+    /// This is only available with the `thread_tracking` Cargo feature.
+    #[must_use]
+    pub fn instance_counts_per_thread(&self) -> HashMap<ThreadId, usize> {
+        self.threads().instance_counts()
+    }
+
+    /// Sends a message to the instances of the family on another thread, to be processed by an
+    /// instance on that thread the next time it calls [`process_mailbox()`][Self::process_mailbox].
+    ///
+    /// Instances of linked objects are typically not thread-safe, so you cannot directly access
+    /// the instances on another thread. Instead, you can send a closure to that thread, which is
+    /// executed by the target thread with a reference to one of its own instances. Use the
+    /// captured state of the closure (e.g. a channel) to return any results to the sender.
+    ///
+    /// The target thread is identified by its [`ThreadId`]. Use
+    /// [`instance_counts_per_thread()`][Self::instance_counts_per_thread] to discover the threads
+    /// that have instances of the family.
+    ///
+    /// Returns `false` and drops the message if there are no instances of the family on the
+    /// target thread. Messages that have not been processed by the time the last instance on
+    /// the target thread is dropped are also dropped.
+    ///
+    /// This is only available with the `thread_tracking` Cargo feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::sync::{Arc, Barrier, mpsc};
+    /// use std::thread;
+    ///
+    /// use linked::Object; // This brings .family() into scope.
+    ///
+    /// #[linked::object]
+    /// struct Worker {
+    ///     processed_items: Cell<usize>,
+    /// }
+    ///
+    /// impl Worker {
+    ///     pub fn new() -> Self {
+    ///         linked::new!(Self {
+    ///             processed_items: Cell::new(0),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let worker = Worker::new();
+    /// let family = worker.family();
+    ///
+    /// let (thread_id_tx, thread_id_rx) = mpsc::channel();
+    /// let (stats_tx, stats_rx) = mpsc::channel();
+    /// let barrier = Arc::new(Barrier::new(2));
+    ///
+    /// let remote = thread::spawn({
+    ///     let family = family.clone();
+    ///     let barrier = Arc::clone(&barrier);
+    ///
+    ///     move || {
+    ///         let worker: Worker = family.into();
+    ///         worker.processed_items.set(42);
+    ///
+    ///         thread_id_tx.send(thread::current().id()).unwrap();
+    ///
+    ///         // Wait for the coordinator to send us a message, then process it.
+    ///         barrier.wait();
+    ///         worker.family().process_mailbox(&worker);
+    ///     }
+    /// });
+    ///
+    /// let remote_thread_id = thread_id_rx.recv().unwrap();
+    ///
+    /// let sent = family.send_to_thread(remote_thread_id, move |worker: &Worker| {
+    ///     stats_tx.send(worker.processed_items.get()).unwrap();
+    /// });
+    /// assert!(sent);
+    ///
+    /// barrier.wait();
+    /// remote.join().unwrap();
+    ///
+    /// assert_eq!(stats_rx.recv().unwrap(), 42);
+    /// ```
+    pub fn send_to_thread(
+        &self,
+        thread_id: ThreadId,
+        message: impl FnOnce(&T) + Send + 'static,
+    ) -> bool
+    where
+        T: 'static,
+    {
+        let message: Message<T> = Box::new(message);
+
+        self.threads().send(thread_id, Box::new(message))
+    }
+
+    /// Sends a message to the instances of the family on every thread that currently has any
+    /// instances of the family, including the current thread.
+    ///
+    /// Each thread processes its copy of the message the next time an instance on that thread
+    /// calls [`process_mailbox()`][Self::process_mailbox]. See
+    /// [`send_to_thread()`][Self::send_to_thread] for details.
+    ///
+    /// Returns the number of threads the message was sent to.
+    pub fn send_to_all_threads(&self, message: impl Fn(&T) + Send + Sync + 'static) -> usize
+    where
+        T: 'static,
+    {
+        let message = Arc::new(message);
+
+        self.threads().send_to_all(|| {
+            let message = Arc::clone(&message);
+            let message: Message<T> = Box::new(move |instance: &T| message(instance));
+            Box::new(message)
+        })
+    }
+
+    /// Processes all messages sent to the current thread via
+    /// [`send_to_thread()`][Self::send_to_thread] or
+    /// [`send_to_all_threads()`][Self::send_to_all_threads], calling each message with
+    /// `instance`, which must be an instance of this family.
+    ///
+    /// Messages are processed in the order they were sent. Messages sent while processing is in
+    /// progress are left for the next call.
+    ///
+    /// Returns the number of messages processed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `instance` is not an instance of this family.
+    pub fn process_mailbox(&self, instance: &T) -> usize
+    where
+        T: Object,
+    {
+        self.assert_same_family(
+            &instance.family(),
+            "the instance must belong to the family whose mailbox is being processed",
+        );
+
+        let messages = self.threads().take_mailbox(thread::current().id());
+        let message_count = messages.len();
+
+        for message in messages {
+            let message = message
+                .downcast::<Message<T>>()
+                .expect("all messages in the mailbox of a family are for the same T");
+
+            message(instance);
+        }
+
+        message_count
+    }
+
+    /// Schedules `mutation` to be applied to an instance of the family on every thread that
+    /// currently has any instances of the family, including the current thread.
+    ///
+    /// This allows coherently updating state that each thread keeps in its own instance (e.g.
+    /// a per-thread cache of configuration that is being reloaded). As instances are typically
+    /// not thread-safe, the mutation is not applied immediately but queued for each thread, to be
+    /// applied by that thread:
+    ///
+    /// * to the instance cached for the thread by a [`linked::instances!`][1] static variable,
+    ///   the next time the thread accesses the static variable via `.with()` or `.with_mut()`;
+    /// * to any instance of the family on the thread, when the thread calls
+    ///   [`apply_broadcasts()`][Self::apply_broadcasts].
+    ///
+    /// Threads that cannot wait until they next touch the static variable (e.g. event loop
+    /// threads that may be idle) can register for immediate delivery via
+    /// [`on_broadcast_queued()`][Self::on_broadcast_queued].
+    ///
+    /// Instances created after the broadcast are not affected by it, so any state that the
+    /// template of the family uses to create new instances needs to be updated separately.
+    /// Broadcasts that have not been applied by the time the last instance on a thread is
+    /// dropped are also dropped.
+    ///
+    /// Returns the number of threads the mutation was scheduled for.
+    ///
+    /// This is only available with the `thread_tracking` Cargo feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use linked::Object; // This brings .family() into scope.
+    ///
+    /// #[linked::object]
+    /// struct RateLimiter {
+    ///     requests_per_second: usize,
+    /// }
+    ///
+    /// impl RateLimiter {
+    ///     pub fn new() -> Self {
+    ///         linked::new!(Self {
+    ///             requests_per_second: 100,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// linked::instances!(static RATE_LIMITER: RateLimiter = RateLimiter::new());
+    ///
+    /// assert_eq!(RATE_LIMITER.with(|limiter| limiter.requests_per_second), 100);
+    ///
+    /// // Configuration reload - every thread picks up the new limit on its next access.
+    /// let family = RATE_LIMITER.with(|limiter| limiter.family());
+    /// family.broadcast(|limiter: &mut RateLimiter| limiter.requests_per_second = 500);
+    ///
+    /// assert_eq!(RATE_LIMITER.with(|limiter| limiter.requests_per_second), 500);
+    /// ```
+    ///
+    /// [1]: crate::instances
+    pub fn broadcast(&self, mutation: impl Fn(&mut T) + Send + Sync + 'static) -> usize
+    where
+        T: 'static,
+    {
+        let mutation = Arc::new(mutation);
+
+        self.threads().broadcast(|| {
+            let mutation = Arc::clone(&mutation);
+            let broadcast: Broadcast<T> = Box::new(move |instance: &mut T| mutation(instance));
+            Box::new(broadcast)
+        })
+    }
+
+    /// Applies all mutations scheduled for the current thread via
+    /// [`broadcast()`][Self::broadcast] to `instance`, which must be an instance of this family.
+    ///
+    /// Mutations are applied in the order they were broadcast. Mutations broadcast while
+    /// applying is in progress are left for the next call.
+    ///
+    /// Returns the number of mutations applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `instance` is not an instance of this family.
+    pub fn apply_broadcasts(&self, instance: &mut T) -> usize
+    where
+        T: Object,
+    {
+        self.assert_same_family(
+            &instance.family(),
+            "the instance must belong to the family whose broadcasts are being applied",
+        );
+
+        let broadcasts = self.threads().take_broadcasts(thread::current().id());
+        let broadcast_count = broadcasts.len();
+
+        for broadcast in broadcasts {
+            let broadcast = broadcast
+                .downcast::<Broadcast<T>>()
+                .expect("all broadcasts of a family are for the same T");
+
+            broadcast(instance);
+        }
+
+        broadcast_count
+    }
+
+    /// Registers the current thread for immediate delivery of mutations scheduled via
+    /// [`broadcast()`][Self::broadcast].
+    ///
+    /// Whenever a mutation is scheduled for the current thread, `callback` is called on the
+    /// broadcasting thread, right after the mutation has been queued. The callback is expected to
+    /// wake up the current thread (e.g. by posting a task to its event loop), which then applies
+    /// the mutation via [`apply_broadcasts()`][Self::apply_broadcasts] or by accessing the
+    /// [`linked::instances!`][1] static variable that holds its instance.
+    ///
+    /// The registration replaces any earlier registration of the current thread and lasts until
+    /// the last instance of the family on the current thread is dropped.
+    ///
+    /// Returns `false` and drops the callback if there are no instances of the family on the
+    /// current thread.
+    ///
+    /// [1]: crate::instances
+    pub fn on_broadcast_queued(&self, callback: impl Fn() + Send + Sync + 'static) -> bool {
+        self.threads()
+            .set_broadcast_notifier(thread::current().id(), Arc::new(Box::new(callback)))
+    }
+
+    /// The number of broadcasts sent to the family so far, used to cheaply detect whether
+    /// there may be broadcasts to apply without inspecting the queue of the current thread.
+    pub(crate) fn broadcast_generation(&self) -> usize {
+        self.threads().broadcast_generation()
+    }
+}
+
+/// Keeps track of the instances of a family on each thread, which per-thread instance counts,
+/// messages and broadcasts rely on.
+///
+/// Every instance is counted against the thread that created it until it is dropped, which costs
+/// a lock whenever the first instance on a thread is created or the last one is dropped, so this
+/// is only part of the state of a family with the `thread_tracking` Cargo feature.
+pub(crate) struct ThreadTracker {
+    per_thread: Mutex<HashMap<ThreadId, ThreadState, BuildThreadIdHasher>>,
+
+    // Incremented after every broadcast, once the broadcast has been queued for every thread.
+    broadcast_generation: AtomicUsize,
+}
+
+impl ThreadTracker {
+    // This is `const` so that families can be defined in `static` variables, which is not
+    // possible with the loom primitives we use when model-checking.
+    #[cfg(not(loom))]
+    #[must_use]
+    pub(crate) const fn new() -> Self {
+        Self {
+            per_thread: Mutex::new(HashMap::with_hasher(BuildThreadIdHasher)),
+            broadcast_generation: AtomicUsize::new(0),
+        }
+    }
+
+    #[cfg(loom)]
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self {
+            per_thread: Mutex::new(HashMap::with_hasher(BuildThreadIdHasher)),
+            broadcast_generation: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of instances of the family that currently exist, grouped by thread.
+    pub(crate) fn instance_counts(&self) -> HashMap<ThreadId, usize> {
+        self.per_thread
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .iter()
+            .map(|(thread_id, thread_state)| {
+                (
+                    *thread_id,
+                    thread_state.instance_count.load(atomic::Ordering::Relaxed),
+                )
+            })
+            // A thread whose last instance is being dropped may still have a state for a moment.
+            .filter(|(_, instance_count)| *instance_count > 0)
+            .collect()
+    }
+
+    /// Counts a new instance on the current thread, returning the thread that the instance is
+    /// counted against, which the instance must pass to `decrement()` when it is dropped.
+    ///
+    /// If `existing` is an instance that is counted against the current thread, the new instance
+    /// is counted via the counter of `existing`, without locking the state of all threads. The
+    /// existing instance must remain alive until this returns, which guarantees that the state
+    /// of the thread is not removed meanwhile.
+    pub(crate) fn increment(&self, existing: Option<&CountedThread>) -> CountedThread {
+        let thread_id = current_thread_id();
+
+        if let Some(existing) = existing.filter(|existing| existing.thread_id == thread_id) {
+            existing
+                .instance_count
+                .fetch_add(1, atomic::Ordering::Relaxed);
+
+            return CountedThread {
+                thread_id,
+                instance_count: Arc::clone(&existing.instance_count),
+            };
+        }
+
+        let mut per_thread = self.per_thread.lock().expect(ERR_POISONED_LOCK);
+        let thread_state = per_thread.entry(thread_id).or_default();
+        thread_state
+            .instance_count
+            .fetch_add(1, atomic::Ordering::Relaxed);
+
+        CountedThread {
+            thread_id,
+            instance_count: Arc::clone(&thread_state.instance_count),
+        }
+    }
+
+    /// Counts out an instance of the thread it was counted against by `increment()`.
+    pub(crate) fn decrement(&self, counted: &CountedThread) {
+        let previous = counted
+            .instance_count
+            .fetch_sub(1, atomic::Ordering::Relaxed);
+        assert!(previous > 0, "instance must have been counted on creation");
+
+        if previous > 1 {
+            return;
+        }
+
+        let mut per_thread = self.per_thread.lock().expect(ERR_POISONED_LOCK);
+
+        // A new instance may have been counted on the thread before we acquired the lock, which
+        // may even have replaced the state of the thread after another instance removed it.
+        let is_unused = per_thread
+            .get(&counted.thread_id)
+            .is_some_and(|thread_state| {
+                Arc::ptr_eq(&thread_state.instance_count, &counted.instance_count)
+                    && thread_state.instance_count.load(atomic::Ordering::Relaxed) == 0
+            });
+
+        if is_unused {
+            let thread_state = per_thread
+                .remove(&counted.thread_id)
+                .expect("we just looked it up");
+
+            // Nobody is left on the thread to process any pending messages or broadcasts. We drop
+            // them outside the lock, as dropping a message may execute arbitrary code.
+            drop(per_thread);
+            drop(thread_state);
+        }
+    }
+
+    /// Adds a message to the mailbox of `thread_id`, unless there are no instances on that thread.
+    fn send(&self, thread_id: ThreadId, message: OccludedMessage) -> bool {
+        let mut per_thread = self.per_thread.lock().expect(ERR_POISONED_LOCK);
+
+        let Some(thread_state) = per_thread.get_mut(&thread_id) else {
+            // We drop the message outside the lock, as dropping it may execute arbitrary code.
+            drop(per_thread);
+            drop(message);
+            return false;
+        };
+
+        thread_state.mailbox.push(message);
+        true
+    }
+
+    /// Adds a message created by `create_message()` to the mailbox of every thread
+    /// that has instances of the family.
+    fn send_to_all(&self, mut create_message: impl FnMut() -> OccludedMessage) -> usize {
+        let mut per_thread = self.per_thread.lock().expect(ERR_POISONED_LOCK);
+
+        for thread_state in per_thread.values_mut() {
+            thread_state.mailbox.push(create_message());
+        }
+
+        per_thread.len()
+    }
+
+    fn take_mailbox(&self, thread_id: ThreadId) -> Vec<OccludedMessage> {
+        self.per_thread
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .get_mut(&thread_id)
+            .map(|thread_state| mem::take(&mut thread_state.mailbox))
+            .unwrap_or_default()
+    }
+
+    /// Adds a broadcast created by `create_broadcast()` to the queue of every thread that has
+    /// instances of the family, notifying the threads registered for immediate delivery.
+    fn broadcast(&self, mut create_broadcast: impl FnMut() -> OccludedMessage) -> usize {
+        let mut per_thread = self.per_thread.lock().expect(ERR_POISONED_LOCK);
+
+        let mut notifiers = Vec::new();
+
+        for thread_state in per_thread.values_mut() {
+            thread_state.broadcasts.push(create_broadcast());
+            notifiers.extend(thread_state.broadcast_notifier.clone());
+        }
+
+        let thread_count = per_thread.len();
+
+        self.broadcast_generation
+            .fetch_add(1, atomic::Ordering::Release);
+
+        // The notifiers may execute arbitrary code, so we call them outside the lock.
+        drop(per_thread);
+
+        for notifier in notifiers {
+            notifier();
+        }
+
+        thread_count
+    }
+
+    fn broadcast_generation(&self) -> usize {
+        self.broadcast_generation.load(atomic::Ordering::Acquire)
+    }
+
+    fn take_broadcasts(&self, thread_id: ThreadId) -> Vec<OccludedMessage> {
+        self.per_thread
+            .lock()
+            .expect(ERR_POISONED_LOCK)
+            .get_mut(&thread_id)
+            .map(|thread_state| mem::take(&mut thread_state.broadcasts))
+            .unwrap_or_default()
+    }
+
+    /// Sets the broadcast notifier of `thread_id`, unless there are no instances on that thread.
+    fn set_broadcast_notifier(&self, thread_id: ThreadId, notifier: BroadcastNotifier) -> bool {
+        let mut per_thread = self.per_thread.lock().expect(ERR_POISONED_LOCK);
+
+        let Some(thread_state) = per_thread.get_mut(&thread_id) else {
+            // We drop the notifier outside the lock, as dropping it may execute arbitrary code.
+            drop(per_thread);
+            drop(notifier);
+            return false;
+        };
+
+        let previous = thread_state.broadcast_notifier.replace(notifier);
+
+        drop(per_thread);
+        drop(previous);
+        true
+    }
+}
+
+/// The thread that an instance is counted against, kept by the `Link` of the instance until the
+/// instance is dropped.
+pub(crate) struct CountedThread {
+    thread_id: ThreadId,
+
+    // The instance counter of `thread_id`, which lets us count more instances on the same thread
+    // without locking the state of all threads.
+    instance_count: ThreadInstanceCount,
+}
+
+impl CountedThread {
+    /// The thread that created the instance.
+    pub(crate) fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+}
+
+/// The number of instances of a family on one thread, shared by the state of the thread and the
+/// `Link` of every instance counted on the thread. This allows an instance to count new instances
+/// on its own thread (e.g. when cloned) without locking the state of all threads.
+type ThreadInstanceCount = Arc<AtomicUsize>;
+
+/// The state of a family on one specific thread.
+#[derive(Default)]
+struct ThreadState {
+    instance_count: ThreadInstanceCount,
+
+    // Messages sent to this thread that have not yet been processed.
+    mailbox: Vec<OccludedMessage>,
+
+    // Type-occluded `Broadcast<T>`s queued for this thread that have not yet been applied.
+    broadcasts: Vec<OccludedMessage>,
+
+    // Notified when a broadcast is queued, if the thread is registered for immediate delivery.
+    broadcast_notifier: Option<BroadcastNotifier>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::{Arc, mpsc};
+    use std::thread;
+
+    use super::*;
+
+    #[linked::object]
+    struct Thing {}
+
+    impl Thing {
+        fn new() -> Self {
+            linked::new!(Self {})
+        }
+    }
+
+    #[test]
+    fn counts_instances_per_thread() {
+        let thing = Thing::new();
+        let family = thing.family();
+
+        let (remote_thread_id, remote_thing) = thread::spawn({
+            let family = family.clone();
+
+            move || {
+                let remote_thing: Thing = family.into();
+                (thread::current().id(), remote_thing)
+            }
+        })
+        .join()
+        .unwrap();
+
+        let per_thread = family.instance_counts_per_thread();
+        assert_eq!(per_thread.len(), 2);
+        assert_eq!(per_thread.get(&thread::current().id()), Some(&1));
+        assert_eq!(per_thread.get(&remote_thread_id), Some(&1));
+
+        // Still counted against the thread that created it.
+        drop(remote_thing);
+
+        let per_thread = family.instance_counts_per_thread();
+        assert_eq!(per_thread.len(), 1);
+        assert_eq!(per_thread.get(&thread::current().id()), Some(&1));
+
+        drop(thing);
+        assert!(family.instance_counts_per_thread().is_empty());
+    }
+
+    #[test]
+    fn clones_counted_on_cloning_thread() {
+        let thing = Thing::new();
+        let family = thing.family();
+
+        let local_clone = thing.clone();
+
+        let (remote_thread_id, remote_clone) = thread::scope(|s| {
+            s.spawn(|| (thread::current().id(), thing.clone()))
+                .join()
+                .unwrap()
+        });
+
+        let per_thread = family.instance_counts_per_thread();
+        assert_eq!(per_thread.get(&thread::current().id()), Some(&2));
+        assert_eq!(per_thread.get(&remote_thread_id), Some(&1));
+
+        // Dropping the last instance of a thread removes the thread, even on another thread.
+        thread::spawn(move || drop(remote_clone)).join().unwrap();
+        assert_eq!(family.instance_counts_per_thread().len(), 1);
+
+        drop(local_clone);
+        drop(thing);
+        assert!(family.instance_counts_per_thread().is_empty());
+
+        // A thread can come back after all its instances were dropped.
+        let thing: Thing = family.clone().into();
+        let clone = thing.clone();
+        assert_eq!(
+            family
+                .instance_counts_per_thread()
+                .get(&thread::current().id()),
+            Some(&2)
+        );
+
+        drop(clone);
+        drop(thing);
+        assert_eq!(family.instance_count(), 0);
+    }
+
+    #[linked::object]
+    struct Worker {
+        name: String,
+    }
+
+    impl Worker {
+        fn new() -> Self {
+            linked::new!(Self {
+                name: thread::current().name().unwrap_or_default().to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn send_to_thread_processed_by_target_thread() {
+        let worker = Worker::new();
+        let family = worker.family();
+
+        let (remote_ready_tx, remote_ready_rx) = mpsc::channel();
+        let (messages_sent_tx, messages_sent_rx) = mpsc::channel::<()>();
+        let (received_tx, received_rx) = mpsc::channel();
+
+        let remote = thread::Builder::new()
+            .name("remote".to_string())
+            .spawn({
+                let family = family.clone();
+
+                move || {
+                    let worker: Worker = family.clone().into();
+                    remote_ready_tx.send(thread::current().id()).unwrap();
+
+                    messages_sent_rx.recv().unwrap();
+                    family.process_mailbox(&worker)
+                }
+            })
+            .unwrap();
+
+        let remote_thread_id = remote_ready_rx.recv().unwrap();
+
+        for i in 0..2 {
+            let received_tx = received_tx.clone();
+
+            assert!(
+                family.send_to_thread(remote_thread_id, move |worker: &Worker| {
+                    received_tx.send((i, worker.name.clone())).unwrap();
+                })
+            );
+        }
+
+        // Nothing was sent to the current thread.
+        assert_eq!(family.process_mailbox(&worker), 0);
+
+        messages_sent_tx.send(()).unwrap();
+        assert_eq!(remote.join().unwrap(), 2);
+
+        drop(received_tx);
+        let received = received_rx.iter().collect::<Vec<_>>();
+        assert_eq!(
+            received,
+            vec![(0, "remote".to_string()), (1, "remote".to_string())]
+        );
+    }
+
+    #[test]
+    fn send_to_all_threads_reaches_every_thread() {
+        let worker = Worker::new();
+        let family = worker.family();
+
+        let processed = Arc::new(AtomicUsize::new(0));
+
+        let (remote_ready_tx, remote_ready_rx) = mpsc::channel();
+        let (message_sent_tx, message_sent_rx) = mpsc::channel::<()>();
+
+        let remote = thread::spawn({
+            let family = family.clone();
+
+            move || {
+                let worker: Worker = family.clone().into();
+                remote_ready_tx.send(()).unwrap();
+
+                message_sent_rx.recv().unwrap();
+                family.process_mailbox(&worker)
+            }
+        });
+
+        remote_ready_rx.recv().unwrap();
+
+        let thread_count = family.send_to_all_threads({
+            let processed = Arc::clone(&processed);
+
+            move |_: &Worker| {
+                processed.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+        });
+        assert_eq!(thread_count, 2);
+
+        message_sent_tx.send(()).unwrap();
+        assert_eq!(remote.join().unwrap(), 1);
+        assert_eq!(family.process_mailbox(&worker), 1);
+
+        assert_eq!(processed.load(atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn messages_dropped_if_no_instances_on_thread() {
+        let worker = Worker::new();
+        let family = worker.family();
+
+        let remote_thread_id = thread::spawn(|| thread::current().id()).join().unwrap();
+        assert!(!family.send_to_thread(remote_thread_id, |_: &Worker| {}));
+
+        // A pending message is dropped with the last instance on the thread.
+        let message_state = Arc::new(());
+        assert!(family.send_to_thread(thread::current().id(), {
+            let message_state = Arc::clone(&message_state);
+            move |_: &Worker| drop(message_state)
+        }));
+        assert_eq!(Arc::strong_count(&message_state), 2);
+
+        drop(worker);
+        assert_eq!(Arc::strong_count(&message_state), 1);
+    }
+
+    #[test]
+    fn broadcast_applied_by_every_thread() {
+        let mut worker = Worker::new();
+        let family = worker.family();
+
+        let notifications = Arc::new(AtomicUsize::new(0));
+
+        let (remote_ready_tx, remote_ready_rx) = mpsc::channel();
+        let (broadcast_sent_tx, broadcast_sent_rx) = mpsc::channel::<()>();
+
+        let remote = thread::spawn({
+            let family = family.clone();
+            let notifications = Arc::clone(&notifications);
+
+            move || {
+                let mut worker: Worker = family.clone().into();
+                assert!(family.on_broadcast_queued(move || {
+                    notifications.fetch_add(1, atomic::Ordering::Relaxed);
+                }));
+                remote_ready_tx.send(()).unwrap();
+
+                broadcast_sent_rx.recv().unwrap();
+                assert_eq!(family.apply_broadcasts(&mut worker), 2);
+                worker.name
+            }
+        });
+
+        remote_ready_rx.recv().unwrap();
+
+        assert_eq!(
+            family.broadcast(|worker: &mut Worker| worker.name.push('!')),
+            2
+        );
+        assert_eq!(
+            family.broadcast(|worker: &mut Worker| worker.name.push('?')),
+            2
+        );
+
+        // Only the remote thread registered for immediate delivery.
+        assert_eq!(notifications.load(atomic::Ordering::Relaxed), 2);
+
+        broadcast_sent_tx.send(()).unwrap();
+        assert!(remote.join().unwrap().ends_with("!?"));
+
+        assert_eq!(family.apply_broadcasts(&mut worker), 2);
+        assert!(worker.name.ends_with("!?"));
+        assert_eq!(family.apply_broadcasts(&mut worker), 0);
+    }
+
+    #[test]
+    fn on_broadcast_queued_requires_instances_on_thread() {
+        let family = Worker::new().family();
+
+        assert!(!family.on_broadcast_queued(|| {}));
+    }
+
+    #[test]
+    #[should_panic]
+    fn process_mailbox_with_foreign_instance_panics() {
+        let worker = Worker::new();
+        let other_worker = Worker::new();
+
+        worker.family().process_mailbox(&other_worker);
+    }
+}
@@ -0,0 +1,352 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::rc::Rc;
+
+use crate::{InstanceSlot, ThreadLocalBackend};
+
+/// A simulated thread that has its own per-thread instances of [linked objects][crate] but runs
+/// on the thread that enters it, allowing tests to exercise multi-instance behavior without
+/// spawning threads.
+///
+/// While code is running inside [`enter()`][Self::enter], every static variable declared via
+/// [`linked::instances!`][1], [`linked::thread_local_rc!`][2] or [`linked::thread_local_arc!`][3]
+/// keeps its per-thread state in the virtual thread instead of the real thread. Each virtual
+/// thread therefore sees its own instances, linked to the same families as the instances of
+/// real threads and other virtual threads. Entering the same virtual thread again gives access
+/// to the same instances as before, as if execution had returned to the same thread.
+///
+/// The per-thread instances of a virtual thread are dropped when the virtual thread is dropped,
+/// similar to a real thread exiting.
+///
+/// Anything that identifies threads by their [`ThreadId`][std::thread::ThreadId] still sees the
+/// real thread, including [`InstancePerThread<T>`][5] and the per-thread instance counts and
+/// mailboxes of a family.
+///
+/// This is only available with the `virtual_threads` Cargo feature enabled. The feature adds a
+/// check for an entered virtual thread to every access to a static variable, so it is meant to
+/// be enabled only for tests (e.g. via `dev-dependencies`).
+///
+/// # Example
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use linked::VirtualThread;
+///
+/// #[linked::object]
+/// struct Counter {
+///     local: Cell<usize>,
+///     total: Arc<AtomicUsize>,
+/// }
+///
+/// impl Counter {
+///     pub fn new() -> Self {
+///         let total = Arc::new(AtomicUsize::new(0));
+///
+///         linked::new!(Self {
+///             local: Cell::new(0),
+///             total: Arc::clone(&total),
+///         })
+///     }
+///
+///     pub fn increment(&self) {
+///         self.local.set(self.local.get() + 1);
+///         self.total.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// linked::thread_local_rc!(static COUNTER: Counter = Counter::new());
+///
+/// let first = VirtualThread::new();
+/// let second = VirtualThread::new();
+///
+/// first.enter(|| COUNTER.with(|counter| counter.increment()));
+/// second.enter(|| COUNTER.with(|counter| counter.increment()));
+/// first.enter(|| COUNTER.with(|counter| counter.increment()));
+///
+/// // Each virtual thread has its own instance, all in the same family.
+/// assert_eq!(first.enter(|| COUNTER.with(|counter| counter.local.get())), 2);
+/// assert_eq!(second.enter(|| COUNTER.with(|counter| counter.local.get())), 1);
+/// assert_eq!(COUNTER.with(|counter| counter.total.load(Ordering::Relaxed)), 3);
+/// ```
+///
+/// [1]: crate::instances
+/// [2]: crate::thread_local_rc
+/// [3]: crate::thread_local_arc
+/// [5]: crate::InstancePerThread
+pub struct VirtualThread {
+    state: Rc<VirtualThreadState>,
+}
+
+impl VirtualThread {
+    /// Creates a new virtual thread, which does not have any per-thread instances yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(VirtualThreadState {
+                slots: RefCell::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Runs `f` on the virtual thread, with static variables accessed by `f` using the
+    /// per-thread instances of the virtual thread.
+    ///
+    /// Virtual threads can be entered from within other virtual threads, in which case the
+    /// previously entered virtual thread is resumed when `f` returns.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.replace(Some(Rc::clone(&self.state)));
+
+        // Also resumes the previous virtual thread if `f` panics.
+        let _resume_previous = ResumeOnDrop { previous };
+
+        f()
+    }
+}
+
+impl Default for VirtualThread {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VirtualThread {
+    fn drop(&mut self) {
+        // Dropping the instances may access static variables. We drop them on the virtual thread
+        // so the real thread is not affected, until no more slots get populated.
+        self.enter(|| {
+            loop {
+                let slots = self.state.slots.take();
+
+                if slots.is_empty() {
+                    break;
+                }
+
+                drop(slots);
+            }
+        });
+    }
+}
+
+impl Debug for VirtualThread {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualThread")
+            .field(
+                "slot_count",
+                &self.state.slots.try_borrow().map(|slots| slots.len()),
+            )
+            .finish()
+    }
+}
+
+struct VirtualThreadState {
+    // Values inside are type-occluded `Rc<S>`, with the type of `S` determined by the key.
+    slots: RefCell<HashMap<SlotKey, Rc<dyn Any>>>,
+}
+
+/// Identifies the per-thread state of one static variable in a virtual thread.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum SlotKey {
+    /// The slot of a `linked::instances!` static variable, identified by its family key.
+    Instances(TypeId),
+
+    /// The storage of a `linked::thread_local_rc!` or `linked::thread_local_arc!` static
+    /// variable, identified by the address of its thread-local key.
+    PerThread(usize),
+}
+
+struct ResumeOnDrop {
+    previous: Option<Rc<VirtualThreadState>>,
+}
+
+impl Drop for ResumeOnDrop {
+    fn drop(&mut self) {
+        // If the thread-local storage is being destroyed, there is nothing left to resume.
+        let exited = CURRENT.try_with(|current| current.replace(self.previous.take()));
+        drop(exited);
+    }
+}
+
+thread_local! {
+    // The virtual thread entered by the current thread, if any.
+    static CURRENT: RefCell<Option<Rc<VirtualThreadState>>> = const { RefCell::new(None) };
+}
+
+/// Returns the slot identified by `key` in the virtual thread entered by the current thread,
+/// creating an empty slot if this is the first access, or `None` if no virtual thread is entered.
+///
+/// We hand out a clone of the `Rc` instead of accessing the slot while borrowing the slots, so
+/// the caller may access other static variables meanwhile.
+#[inline]
+pub(crate) fn virtual_slot<S>(key: SlotKey) -> Option<Rc<S>>
+where
+    S: Default + 'static,
+{
+    CURRENT
+        .try_with(|current| {
+            let current = current.borrow();
+            let mut slots = current.as_ref()?.slots.borrow_mut();
+
+            let slot = slots.entry(key).or_insert_with(|| Rc::new(S::default()));
+
+            Some(
+                Rc::clone(slot)
+                    .downcast::<S>()
+                    .expect("the key determines the type of the slot"),
+            )
+        })
+        .ok()
+        .flatten()
+}
+
+/// Whether the current thread has entered a virtual thread. The per-thread state of virtual
+/// threads is released when the virtual thread is dropped, not on thread exit.
+pub(crate) fn is_entered() -> bool {
+    CURRENT
+        .try_with(|current| current.borrow().is_some())
+        .unwrap_or_default()
+}
+
+/// A [`ThreadLocalBackend`] that provides the slot of the entered virtual thread if there is
+/// one, falling back to the slot provided by the wrapped backend.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Virtualized<B> {
+    backend: B,
+    family_key_provider: fn() -> TypeId,
+}
+
+impl<B> Virtualized<B> {
+    pub(crate) const fn new(backend: B, family_key_provider: fn() -> TypeId) -> Self {
+        Self {
+            backend,
+            family_key_provider,
+        }
+    }
+}
+
+impl<T, B> ThreadLocalBackend<T> for Virtualized<B>
+where
+    T: linked::Object,
+    B: ThreadLocalBackend<T>,
+{
+    #[inline]
+    fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<T>) -> R) -> Option<R> {
+        let key = SlotKey::Instances((self.family_key_provider)());
+
+        if let Some(slot) = virtual_slot::<InstanceSlot<T>>(key) {
+            return Some(f(&slot));
+        }
+
+        self.backend.try_with_slot(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[linked::object]
+    struct Counter {
+        local: Cell<usize>,
+        dropped: Arc<AtomicUsize>,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            let dropped = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                local: Cell::new(0),
+                dropped: Arc::clone(&dropped),
+            })
+        }
+
+        fn increment(&self) -> usize {
+            self.local.set(self.local.get().wrapping_add(1));
+            self.local.get()
+        }
+    }
+
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn instances_are_per_virtual_thread() {
+        linked::instances!(static COUNTER: Counter = Counter::new());
+
+        let first = VirtualThread::new();
+        let second = VirtualThread::new();
+
+        assert_eq!(first.enter(|| COUNTER.with(Counter::increment)), 1);
+        assert_eq!(second.enter(|| COUNTER.with(Counter::increment)), 1);
+        assert_eq!(first.enter(|| COUNTER.with(Counter::increment)), 2);
+
+        // The real thread is not affected.
+        assert_eq!(COUNTER.with(Counter::increment), 1);
+
+        // All instances are in the same family.
+        let family = COUNTER.family();
+        assert_eq!(first.enter(|| COUNTER.family()).id(), family.id());
+
+        // Only the instance of the real thread is left after the virtual threads are dropped.
+        drop(first);
+        drop(second);
+        assert_eq!(family.instance_count(), 1);
+    }
+
+    #[test]
+    fn thread_local_rc_is_per_virtual_thread() {
+        linked::thread_local_rc!(static COUNTER: Counter = Counter::new());
+
+        let dropped = COUNTER.with(|counter| Arc::clone(&counter.dropped));
+
+        let first = VirtualThread::new();
+        let second = VirtualThread::new();
+
+        let first_instance = first.enter(|| COUNTER.to_rc());
+        assert!(Rc::ptr_eq(
+            &first_instance,
+            &first.enter(|| COUNTER.to_rc())
+        ));
+        assert!(!Rc::ptr_eq(
+            &first_instance,
+            &second.enter(|| COUNTER.to_rc())
+        ));
+        assert!(!Rc::ptr_eq(&first_instance, &COUNTER.to_rc()));
+
+        drop(first_instance);
+        drop(first);
+        drop(second);
+
+        // The instance that created the family and those of the virtual threads are gone.
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn nested_virtual_threads_resume_outer() {
+        linked::instances!(static COUNTER: Counter = Counter::new());
+
+        let outer = VirtualThread::new();
+        let inner = VirtualThread::new();
+
+        outer.enter(|| {
+            assert_eq!(COUNTER.with(Counter::increment), 1);
+            assert_eq!(inner.enter(|| COUNTER.with(Counter::increment)), 1);
+            assert_eq!(COUNTER.with(Counter::increment), 2);
+        });
+
+        assert!(!is_entered());
+    }
+}
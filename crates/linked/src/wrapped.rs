@@ -0,0 +1,210 @@
+use std::ops::{Deref, DerefMut};
+
+/// A linked object that wraps a value of type `T`, for when `T` itself cannot be decorated with
+/// [`#[linked::object]`][1].
+///
+/// The [`#[linked::object]`][1] attribute adds a hidden field to the struct it is applied to,
+/// which changes the layout of the struct. This is a problem for types whose field layout matters,
+/// such as `#[repr(C)]` types used in FFI or types whose serialized form is derived from their
+/// fields. With `Wrapped<T>`, the type `T` remains untouched and the wrapper is the linked object.
+///
+/// # Usage
+///
+/// Create the wrapper via the [`linked::new_wrapped!` macro][2], which takes a struct-expression
+/// that serves as the template for new instances, just like [`linked::new!`][3]. Access the
+/// wrapped value via `Deref` and `DerefMut`.
+///
+/// The wrapper can be used via all the standard mechanisms such as [`linked::instances!`][4],
+/// [`linked::thread_local_rc!`][5] and [`linked::InstancePerThread<T>`][6].
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// // The layout of this type must not change, so we cannot apply `#[linked::object]` to it.
+/// #[repr(C)]
+/// pub struct RequestStats {
+///     local_requests: u64,
+///     total_requests: Arc<AtomicU64>,
+/// }
+///
+/// impl RequestStats {
+///     pub fn new() -> linked::Wrapped<Self> {
+///         let total_requests = Arc::new(AtomicU64::new(0));
+///
+///         linked::new_wrapped!(Self {
+///             local_requests: 0,
+///             total_requests: Arc::clone(&total_requests),
+///         })
+///     }
+///
+///     pub fn record_request(&mut self) {
+///         self.local_requests += 1;
+///         self.total_requests.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut stats = RequestStats::new();
+/// let mut clone = stats.clone();
+///
+/// stats.record_request();
+/// clone.record_request();
+///
+/// assert_eq!(stats.local_requests, 1);
+/// assert_eq!(stats.total_requests.load(Ordering::Relaxed), 2);
+/// ```
+///
+/// [1]: crate::object
+/// [2]: crate::new_wrapped
+/// [3]: crate::new
+/// [4]: crate::instances
+/// [5]: crate::thread_local_rc
+/// [6]: crate::InstancePerThread
+#[linked::object]
+#[derive(Debug)]
+pub struct Wrapped<T: 'static> {
+    value: T,
+}
+
+impl<T> Wrapped<T> {
+    /// This is an implementation detail of the `linked::new_wrapped!` macro and is not part of
+    /// the public API. It is not meant to be used directly and may change or be removed at any time.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn new(instance_factory: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        linked::new!(Self {
+            value: (instance_factory)(),
+        })
+    }
+
+    /// Unwraps the value, detaching it from the family.
+    ///
+    /// The returned value is no longer a linked object, so it does not count as an instance
+    /// of the family and cannot be used to create new instances.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Wrapped<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Wrapped<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+/// Defines the template used to create every instance in a `linked::Wrapped<T>` object family.
+///
+/// This is the same as [`linked::new!`][crate::new] but for types that are not decorated with
+/// [`#[linked::object]`][crate::object], returning a [`linked::Wrapped<T>`][crate::Wrapped]
+/// instead of the type itself.
+///
+/// # Arguments
+///
+/// * `$ctor` - The struct-expression that serves as the template for constructing new linked
+///   instances on demand. This will move-capture any referenced state. All captured values
+///   must be thread-safe (`Send` + `Sync` + `'static`).
+///
+/// # Example
+///
+/// ```rust
+/// #[repr(C)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// impl Point {
+///     pub fn new_linked_origin() -> linked::Wrapped<Self> {
+///         linked::new_wrapped!(Self { x: 0.0, y: 0.0 })
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! new_wrapped {
+    ($ctor:expr) => {
+        ::linked::Wrapped::new(move || $ctor)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{self, AtomicUsize};
+    use std::thread;
+
+    use crate::{Object, Wrapped};
+
+    #[repr(C)]
+    struct Counter {
+        local_value: usize,
+        shared_value: Arc<AtomicUsize>,
+    }
+
+    impl Counter {
+        fn new() -> Wrapped<Self> {
+            let shared_value = Arc::new(AtomicUsize::new(0));
+
+            linked::new_wrapped!(Self {
+                local_value: 0,
+                shared_value: Arc::clone(&shared_value),
+            })
+        }
+
+        fn increment(&mut self) {
+            self.local_value = self.local_value.wrapping_add(1);
+            self.shared_value.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn wrapped_instances_are_linked() {
+        let mut counter = Counter::new();
+        counter.increment();
+
+        let mut clone = counter.clone();
+        clone.increment();
+        assert_eq!(clone.local_value, 1);
+
+        thread::spawn({
+            let family = counter.family();
+
+            move || {
+                let mut counter: Wrapped<Counter> = family.into();
+                counter.increment();
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(counter.shared_value.load(atomic::Ordering::Relaxed), 3);
+        assert_eq!(counter.family().instance_count(), 2);
+
+        let inner = clone.into_inner();
+        assert_eq!(inner.local_value, 1);
+        assert_eq!(counter.family().instance_count(), 1);
+    }
+
+    #[test]
+    fn wrapped_type_layout_unchanged() {
+        let counter = Counter::new();
+        let inner: &Counter = &counter;
+
+        assert_eq!(
+            size_of_val(inner),
+            size_of::<usize>() + size_of::<Arc<AtomicUsize>>()
+        );
+    }
+}
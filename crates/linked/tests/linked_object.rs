@@ -27,3 +27,363 @@ fn very_empty_struct() {
 
     drop(Empty::new());
 }
+
+#[test]
+fn custom_clone() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[linked::object(custom_clone)]
+    struct Tracked {
+        shared: Arc<AtomicUsize>,
+        local: Vec<usize>,
+    }
+
+    impl Tracked {
+        fn new() -> Self {
+            let shared = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                shared: Arc::clone(&shared),
+                local: Vec::new(),
+            })
+        }
+    }
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            let mut clone = linked::clone_linked(self);
+            clone.local.clone_from(&self.local);
+            clone
+        }
+    }
+
+    let mut original = Tracked::new();
+    original.local.push(42);
+    original.shared.store(1, Ordering::Relaxed);
+
+    let clone = original.clone();
+
+    // Local state was copied by our custom logic.
+    assert_eq!(clone.local, vec![42]);
+
+    // Shared state is still shared via the family.
+    assert_eq!(clone.shared.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn on_instance_created() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use linked::Object;
+
+    #[linked::object(on_instance_created)]
+    struct Registered {
+        created: Arc<AtomicUsize>,
+        is_initialized: bool,
+    }
+
+    impl Registered {
+        fn new() -> Self {
+            let created = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                created: Arc::clone(&created),
+                is_initialized: false,
+            })
+        }
+
+        fn on_instance_created(&mut self) {
+            self.created.fetch_add(1, Ordering::Relaxed);
+            self.is_initialized = true;
+        }
+    }
+
+    let first = Registered::new();
+    assert!(first.is_initialized);
+    assert_eq!(first.created.load(Ordering::Relaxed), 1);
+
+    let clone = first.clone();
+    drop(first);
+
+    assert!(clone.is_initialized);
+    assert_eq!(clone.created.load(Ordering::Relaxed), 2);
+
+    thread::spawn({
+        let family = clone.family();
+
+        move || {
+            let from_family: Registered = family.into();
+            assert!(from_family.is_initialized);
+        }
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(clone.created.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn shared_fields() {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use linked::Object;
+
+    #[linked::object]
+    struct Counter {
+        #[linked(shared)]
+        total: AtomicUsize,
+
+        #[linked(shared)]
+        names: Mutex<Vec<String>>,
+
+        local: usize,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            linked::new!(Self {
+                total: AtomicUsize::new(0),
+                names: Mutex::new(Vec::new()),
+                local: 0,
+            })
+        }
+
+        fn record(&mut self, name: &str) {
+            self.total.fetch_add(1, Ordering::Relaxed);
+            self.names.lock().unwrap().push(name.to_string());
+            self.local = self.local.wrapping_add(1);
+        }
+    }
+
+    let mut counter = Counter::new();
+    counter.record("first");
+
+    let mut clone = counter.clone();
+    clone.record("second");
+
+    thread::spawn({
+        let family = counter.family();
+
+        move || {
+            let mut remote: Counter = family.into();
+            remote.record("third");
+        }
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(counter.total.load(Ordering::Relaxed), 3);
+    assert_eq!(counter.names.lock().unwrap().len(), 3);
+
+    // Fields without the attribute are not shared.
+    assert_eq!(counter.local, 1);
+    assert_eq!(clone.local, 1);
+
+    // A new family gets new shared values.
+    let other = Counter::new();
+    assert_eq!(other.total.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn field_initializers_coerce_to_field_type() {
+    #[linked::object]
+    struct Formatter {
+        format: Box<dyn Fn(usize) -> String + Send + Sync>,
+        history: Vec<String>,
+    }
+
+    impl Formatter {
+        fn new() -> Self {
+            linked::new!(Self {
+                format: Box::new(|value| format!("#{value}")),
+                history: ["a", "b"].iter().map(ToString::to_string).collect(),
+            })
+        }
+    }
+
+    let formatter = Formatter::new();
+    assert_eq!((formatter.format)(5), "#5");
+    assert_eq!(formatter.history.len(), 2);
+}
+
+#[test]
+fn fallible_template() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use linked::Object;
+
+    #[linked::object(on_instance_created)]
+    struct Connection {
+        #[linked(shared)]
+        address: String,
+
+        format: Box<dyn Fn(usize) -> String + Send + Sync>,
+        instances_created: usize,
+    }
+
+    impl Connection {
+        fn open(address: &str) -> Result<Self, String> {
+            let address = address.to_string();
+
+            linked::new_try!(Self {
+                address: validate(&address)?,
+                format: Box::new(|value| format!("#{value}")),
+                instances_created: 0,
+            })
+        }
+
+        fn on_instance_created(&mut self) {
+            self.instances_created = self.instances_created.wrapping_add(1);
+        }
+    }
+
+    static VALIDATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    fn validate(address: &str) -> Result<String, String> {
+        VALIDATIONS.fetch_add(1, Ordering::Relaxed);
+
+        if address.is_empty() {
+            Err("empty address".to_string())
+        } else {
+            Ok(address.to_string())
+        }
+    }
+
+    let connection = Connection::open("example.com").unwrap();
+    assert_eq!(*connection.address, "example.com");
+    assert_eq!((connection.format)(5), "#5");
+    assert_eq!(connection.instances_created, 1);
+
+    // The shared field is only created by the first instance, so it is not validated again.
+    let clone: Connection = connection.family().into();
+    assert_eq!(*clone.address, "example.com");
+    assert_eq!(clone.instances_created, 1);
+    assert_eq!(VALIDATIONS.load(Ordering::Relaxed), 1);
+
+    assert_eq!(
+        Connection::open("").err(),
+        Some("empty address".to_string())
+    );
+}
+
+#[test]
+fn empty_fallible_template() {
+    use linked::Object;
+
+    #[linked::object]
+    struct Empty {}
+
+    impl Empty {
+        fn new() -> Result<Self, String> {
+            linked::new_try!(Self)
+        }
+    }
+
+    let empty = Empty::new().unwrap();
+    assert_eq!(empty.family().instance_count(), 1);
+}
+
+#[test]
+#[should_panic]
+fn fallible_template_panics_after_first_instance() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use linked::Object;
+
+    #[linked::object]
+    struct Flaky {
+        value: usize,
+    }
+
+    impl Flaky {
+        fn new(fail_after_first: &'static AtomicBool) -> Result<Self, String> {
+            linked::new_try!(Self {
+                value: if fail_after_first.swap(true, Ordering::Relaxed) {
+                    return Err("only the first instance may be created".to_string());
+                } else {
+                    42
+                },
+            })
+        }
+    }
+
+    static CREATED: AtomicBool = AtomicBool::new(false);
+
+    let flaky = Flaky::new(&CREATED).unwrap();
+    assert_eq!(flaky.value, 42);
+
+    // This evaluates the template again, which fails.
+    let _clone: Flaky = flaky.family().into();
+}
+
+#[test]
+fn parent_instance_per_thread() {
+    use std::cell::Cell;
+    use std::thread;
+
+    use linked::{InstancePerThread, Object};
+
+    #[linked::object]
+    struct Service {
+        local_requests: Cell<usize>,
+    }
+
+    impl Service {
+        fn new() -> Self {
+            linked::new!(Self {
+                local_requests: Cell::new(0),
+            })
+        }
+    }
+
+    #[linked::object]
+    struct Request {
+        #[linked(parent)]
+        service: Service,
+    }
+
+    impl Request {
+        fn new(services: InstancePerThread<Service>) -> Self {
+            linked::new!(Self {
+                service: services.clone(),
+            })
+        }
+
+        fn handle(&self) {
+            self.service
+                .local_requests
+                .set(self.service.local_requests.get().wrapping_add(1));
+        }
+    }
+
+    let services = InstancePerThread::new(Service::new());
+    let service = services.acquire();
+
+    let request = Request::new(services);
+    request.handle();
+
+    let other_request: Request = request.family().into();
+    other_request.handle();
+
+    // The requests share the parent instance of the current thread, also used by other code.
+    assert_eq!(service.local_requests.get(), 2);
+
+    let family = request.family();
+
+    thread::spawn(move || {
+        let request: Request = family.into();
+        request.handle();
+
+        // Each thread has its own parent instance.
+        assert_eq!(request.service.local_requests.get(), 1);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(service.local_requests.get(), 2);
+}
@@ -0,0 +1,89 @@
+//! Sharing the registry of static variables between copies of `linked` in one process.
+//!
+//! Hosting a shared registry affects the entire process, so this is kept apart from other tests.
+
+use std::any::TypeId;
+use std::cell::Cell;
+use std::thread;
+
+use linked::{InstanceSlot, Object, SharedRegistry, StaticInstances, ThreadLocalBackend};
+
+#[linked::object]
+struct Counter {
+    value: Cell<usize>,
+}
+
+impl Counter {
+    fn new() -> Self {
+        linked::new!(Self {
+            value: Cell::new(0),
+        })
+    }
+
+    fn increment(&self) {
+        self.value.set(self.value.get().wrapping_add(1));
+    }
+
+    fn value(&self) -> usize {
+        self.value.get()
+    }
+}
+
+thread_local! {
+    static COUNTER_SLOT: InstanceSlot<Counter> = const { InstanceSlot::new() };
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CounterBackend;
+
+impl ThreadLocalBackend<Counter> for CounterBackend {
+    fn try_with_slot<R>(&self, f: impl FnOnce(&InstanceSlot<Counter>) -> R) -> Option<R> {
+        COUNTER_SLOT.try_with(f).ok()
+    }
+}
+
+struct CounterKey;
+
+// Two copies of the same static variable with different thread-local storage backends, standing
+// in for the copies of the static variable in a plugin host and in a plugin.
+const HOST_COUNTER: StaticInstances<Counter> =
+    StaticInstances::new(TypeId::of::<CounterKey>, Counter::new);
+const PLUGIN_COUNTER: StaticInstances<Counter, CounterBackend> =
+    StaticInstances::with_backend(TypeId::of::<CounterKey>, Counter::new, CounterBackend);
+
+#[test]
+fn shared_registry_shares_per_thread_instances() {
+    let registry = SharedRegistry::host();
+
+    // SAFETY: We just created the pointer via `into_raw()`.
+    let registry_from_raw = unsafe { SharedRegistry::from_raw(registry.into_raw()) };
+    assert!(registry_from_raw.is_compatible());
+    assert_eq!(SharedRegistry::host().into_raw(), registry.into_raw());
+
+    HOST_COUNTER.with(Counter::increment);
+    PLUGIN_COUNTER.with(Counter::increment);
+
+    // Both copies use the same cached instance, which lives in the registry's slots.
+    assert_eq!(HOST_COUNTER.with(Counter::value), 2);
+    assert_eq!(PLUGIN_COUNTER.with(Counter::value), 2);
+
+    assert_eq!(HOST_COUNTER.get().family(), PLUGIN_COUNTER.get().family());
+
+    thread::spawn(|| {
+        PLUGIN_COUNTER.with(Counter::increment);
+        assert_eq!(HOST_COUNTER.with(Counter::value), 1);
+
+        #[cfg(feature = "thread_tracking")]
+        {
+            let family = HOST_COUNTER.get().family();
+            assert_eq!(family.instance_counts_per_thread().len(), 2);
+        }
+
+        linked::run_thread_exit_hooks();
+
+        // The cached instance of the thread was released.
+        assert!(HOST_COUNTER.try_get().is_none());
+    })
+    .join()
+    .unwrap();
+}
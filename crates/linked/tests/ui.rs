@@ -0,0 +1,7 @@
+//! Compile-time diagnostics produced by the linked object macros when they are misused.
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,7 @@
+struct Thing {
+    value: usize,
+}
+
+linked::assert_object!(Thing);
+
+fn main() {}
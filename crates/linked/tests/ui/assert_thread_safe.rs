@@ -0,0 +1,15 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[linked::object]
+struct Thing {
+    value: Cell<usize>,
+}
+
+// Shared state must be thread-safe.
+linked::assert_thread_safe!(Rc<RefCell<Vec<String>>>);
+
+// Linked object instances are not thread-safe, so they cannot be shared with a family.
+linked::assert_thread_safe!(Thing);
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[linked::object]
+#[derive(Debug, Clone)]
+struct Thing {
+    value: usize,
+}
+
+fn main() {}
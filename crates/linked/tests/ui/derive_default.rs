@@ -0,0 +1,7 @@
+#[linked::object]
+#[derive(Default, PartialEq)]
+struct Thing {
+    value: usize,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[linked::object]
+enum Direction {
+    Up,
+    Down,
+}
+
+fn main() {}
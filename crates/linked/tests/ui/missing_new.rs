@@ -0,0 +1,14 @@
+#[linked::object]
+struct Thing {
+    value: usize,
+}
+
+impl Thing {
+    fn new() -> Self {
+        Self { value: 42 }
+    }
+}
+
+fn main() {
+    drop(Thing::new());
+}
@@ -0,0 +1,6 @@
+use std::sync::{Arc, Mutex};
+
+#[linked::object]
+struct Counter(usize, Arc<Mutex<Vec<u8>>>);
+
+fn main() {}
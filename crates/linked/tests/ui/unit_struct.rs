@@ -0,0 +1,4 @@
+#[linked::object]
+struct Marker;
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[linked::object]
+struct Thing {
+    #[linked(share)]
+    value: usize,
+}
+
+fn main() {}
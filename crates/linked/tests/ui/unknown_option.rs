@@ -0,0 +1,6 @@
+#[linked::object(custom_clones)]
+struct Thing {
+    value: usize,
+}
+
+fn main() {}
@@ -2,18 +2,46 @@
 // Copyright (c) Folo authors.
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{ToTokens, format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Fields, FieldsNamed, Item, ItemStruct, parse_quote};
+use syn::{
+    Attribute, Field, Fields, FieldsNamed, Ident, Item, ItemStruct, Path, Token, parse_quote,
+};
 
-use crate::syn_helpers::token_stream_and_error;
+use crate::syn_helpers::{did_you_mean, to_source_string, token_stream_and_error};
 
 #[must_use]
-pub fn entrypoint(_attr: &TokenStream, input: &TokenStream) -> TokenStream {
+pub fn entrypoint(attr: &TokenStream, input: &TokenStream) -> TokenStream {
+    let options = match parse_options(attr) {
+        Ok(options) => options,
+        Err(e) => return token_stream_and_error(&without_field_options(input), &e),
+    };
+
     let item_ast = syn::parse2::<Item>(input.clone());
 
     let result = match item_ast {
-        Ok(Item::Struct(item)) => core(item),
+        Ok(Item::Struct(item)) => core(item, &options),
+        Ok(Item::Enum(item)) => {
+            let (enum_token, ident) = (&item.enum_token, &item.ident);
+
+            Err(syn::Error::new_spanned(
+                quote! { #enum_token #ident },
+                "the `linked::object` attribute cannot be applied to an enum - wrap the enum in a \
+                struct with named fields or use `linked::Wrapped<T>` via `linked::new_wrapped!`",
+            ))
+        }
+        Ok(Item::Union(item)) => {
+            let (union_token, ident) = (&item.union_token, &item.ident);
+
+            Err(syn::Error::new_spanned(
+                quote! { #union_token #ident },
+                "the `linked::object` attribute cannot be applied to a union - wrap the union in a \
+                struct with named fields or use `linked::Wrapped<T>` via `linked::new_wrapped!`",
+            ))
+        }
         Ok(x) => Err(syn::Error::new(
             x.span(),
             "the `linked::object` attribute must be applied to a struct",
@@ -23,25 +51,358 @@ pub fn entrypoint(_attr: &TokenStream, input: &TokenStream) -> TokenStream {
 
     match result {
         Ok(r) => r,
-        Err(e) => token_stream_and_error(input, &e),
+        Err(e) => token_stream_and_error(&without_field_options(input), &e),
+    }
+}
+
+/// Removes the `#[linked(...)]` field attributes from the input, so emitting the input together
+/// with an error does not cause additional errors about an unknown `linked` attribute.
+fn without_field_options(input: &TokenStream) -> TokenStream {
+    let Ok(mut item) = syn::parse2::<ItemStruct>(input.clone()) else {
+        return input.clone();
+    };
+
+    for field in &mut item.fields {
+        field.attrs.retain(|attr| !attr.path().is_ident("linked"));
+    }
+
+    item.into_token_stream()
+}
+
+/// Options that can be specified in the attribute, e.g. `#[linked::object(custom_clone)]`.
+#[derive(Debug, Default)]
+struct Options {
+    /// If set, we do not generate a `Clone` implementation and leave it up to the user to
+    /// implement it via `linked::clone_linked()`.
+    custom_clone: bool,
+
+    /// If set, we call `Self::on_instance_created(&mut self)` on every new instance in the family.
+    on_instance_created: bool,
+}
+
+const OPTION_NAMES: &[&str] = &["custom_clone", "on_instance_created"];
+
+const FIELD_OPTION_NAMES: &[&str] = &["shared", "parent"];
+
+fn parse_options(attr: &TokenStream) -> Result<Options, syn::Error> {
+    let mut options = Options::default();
+
+    let option_names = Punctuated::<Ident, Token![,]>::parse_terminated.parse2(attr.clone())?;
+
+    for option_name in option_names {
+        if option_name == "custom_clone" {
+            options.custom_clone = true;
+        } else if option_name == "on_instance_created" {
+            options.on_instance_created = true;
+        } else if let Some(suggestion) = did_you_mean(&option_name.to_string(), OPTION_NAMES) {
+            return Err(syn::Error::new(
+                option_name.span(),
+                format!("unknown `linked::object` option - did you mean `{suggestion}`?"),
+            ));
+        } else {
+            return Err(syn::Error::new(
+                option_name.span(),
+                "unknown `linked::object` option - supported options are `custom_clone` and `on_instance_created`",
+            ));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Processes the `#[linked(...)]` attributes on a field (removing them from the field) and
+/// generates the function that `linked::new!` uses to initialize the field.
+///
+/// A field marked with `#[linked(shared)]` is changed from type `T` to `linked::Shared<T>`, with
+/// its value being created once per family instead of once per instance. A field marked with
+/// `#[linked(parent)]` is changed from type `P` to `linked::Ref<P>`, referencing the current
+/// thread's instance of the parent family. All other fields are initialized with the value from
+/// the `linked::new!` struct-expression as-is.
+fn field_initializer(field: &mut Field) -> Result<TokenStream, syn::Error> {
+    let mut shared = false;
+    let mut parent = false;
+
+    let mut error = None;
+
+    field.attrs.retain(|attr| {
+        if !attr.path().is_ident("linked") {
+            return true;
+        }
+
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("shared") {
+                shared = true;
+                Ok(())
+            } else if meta.path.is_ident("parent") {
+                parent = true;
+                Ok(())
+            } else if let Some(suggestion) = meta
+                .path
+                .get_ident()
+                .and_then(|name| did_you_mean(&name.to_string(), FIELD_OPTION_NAMES))
+            {
+                Err(meta.error(format!(
+                    "unknown `linked` field option - did you mean `{suggestion}`?"
+                )))
+            } else {
+                Err(meta.error(
+                    "unknown `linked` field option - supported options are `shared` and `parent`",
+                ))
+            }
+        });
+
+        if let Err(e) = result {
+            error.get_or_insert(e);
+        }
+
+        false
+    });
+
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    if shared && parent {
+        return Err(syn::Error::new_spanned(
+            &field.ident,
+            "a field cannot be both `shared` and `parent` - the parent is already shared by the family",
+        ));
+    }
+
+    let field_name = field
+        .ident
+        .as_ref()
+        .expect("we only process structs with named fields")
+        .unraw();
+    let init_fn = format_ident!("__private_linked_init_{}", field_name);
+    let try_init_fn = format_ident!("__private_linked_try_init_{}", field_name);
+    let vis = &field.vis;
+    let ty = field.ty.clone();
+
+    // If the field only exists under some conditions, so does its initializer.
+    let cfg_attrs = field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect::<Vec<_>>();
+
+    let initializer = if shared {
+        let field_name = field_name.to_string();
+        field.ty = parse_quote!(::linked::Shared<#ty>);
+
+        quote! {
+            #(#cfg_attrs)*
+            #[doc(hidden)]
+            #[inline]
+            #vis fn #init_fn(
+                link: &::linked::__private::Link<Self>,
+                create_value: impl FnOnce() -> #ty,
+            ) -> ::linked::Shared<#ty> {
+                ::linked::__private::shared_field(link, #field_name, create_value)
+            }
+
+            #(#cfg_attrs)*
+            #[doc(hidden)]
+            #[inline]
+            #vis fn #try_init_fn<__LinkedError>(
+                link: &::linked::__private::Link<Self>,
+                create_value: impl FnOnce() -> ::std::result::Result<#ty, __LinkedError>,
+            ) -> ::std::result::Result<::linked::Shared<#ty>, __LinkedError> {
+                ::linked::__private::try_shared_field(link, #field_name, create_value)
+            }
+        }
+    } else if parent {
+        let field_name = field_name.to_string();
+        field.ty = parse_quote!(::linked::Ref<#ty>);
+
+        quote! {
+            #(#cfg_attrs)*
+            #[doc(hidden)]
+            #[inline]
+            #vis fn #init_fn<__LinkedParent: ::std::convert::Into<::linked::InstancePerThread<#ty>>>(
+                link: &::linked::__private::Link<Self>,
+                create_value: impl FnOnce() -> __LinkedParent,
+            ) -> ::linked::Ref<#ty> {
+                ::linked::__private::parent_field(link, #field_name, create_value)
+            }
+
+            #(#cfg_attrs)*
+            #[doc(hidden)]
+            #[inline]
+            #vis fn #try_init_fn<__LinkedParent: ::std::convert::Into<::linked::InstancePerThread<#ty>>, __LinkedError>(
+                link: &::linked::__private::Link<Self>,
+                create_value: impl FnOnce() -> ::std::result::Result<__LinkedParent, __LinkedError>,
+            ) -> ::std::result::Result<::linked::Ref<#ty>, __LinkedError> {
+                ::linked::__private::try_parent_field(link, #field_name, create_value)
+            }
+        }
+    } else {
+        quote! {
+            #(#cfg_attrs)*
+            #[doc(hidden)]
+            #[inline]
+            #vis fn #init_fn(
+                _link: &::linked::__private::Link<Self>,
+                create_value: impl FnOnce() -> #ty,
+            ) -> #ty {
+                create_value()
+            }
+
+            #(#cfg_attrs)*
+            #[doc(hidden)]
+            #[inline]
+            #vis fn #try_init_fn<__LinkedError>(
+                _link: &::linked::__private::Link<Self>,
+                create_value: impl FnOnce() -> ::std::result::Result<#ty, __LinkedError>,
+            ) -> ::std::result::Result<#ty, __LinkedError> {
+                create_value()
+            }
+        }
+    };
+
+    Ok(initializer)
+}
+
+/// Creates the error for a struct without named fields, suggesting the named fields equivalent.
+fn named_fields_required(item: &ItemStruct) -> syn::Error {
+    let name = &item.ident;
+
+    let (span, suggestion) = match &item.fields {
+        Fields::Unnamed(fields) => {
+            let fields = fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(index, field)| format!("field_{index}: {}", to_source_string(&field.ty)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            (item.fields.span(), format!("struct {name} {{ {fields} }}"))
+        }
+        _ => (name.span(), format!("struct {name} {{}}")),
+    };
+
+    syn::Error::new(
+        span,
+        format!(
+            "the `linked::object` attribute must be applied to a struct with named fields - \
+            did you mean `{suggestion}`?"
+        ),
+    )
+}
+
+/// Rejects derives that cannot work on a linked object because of the field we add to the struct.
+///
+/// We only see the derives placed after our attribute - any placed before it are expanded before
+/// we add the field, so they fail later with an error about the missing field.
+fn check_derives(attrs: &[Attribute], options: &Options) -> Result<(), syn::Error> {
+    let mut error: Option<syn::Error> = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let paths = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+
+        for path in paths {
+            let Some(trait_name) = path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+            else {
+                continue;
+            };
+
+            let message = match trait_name.as_str() {
+                "Clone" if options.custom_clone => {
+                    "linked objects cannot derive `Clone` - with `custom_clone`, implement \
+                    `Clone` manually via `linked::clone_linked()`"
+                        .to_string()
+                }
+                "Clone" => "linked objects cannot derive `Clone` - the `linked::object` \
+                    attribute already implements it, so remove `Clone` from the derive list"
+                    .to_string(),
+                "Copy" => "linked objects cannot derive `Copy` - every instance is registered \
+                    with its family, so instances can only be cloned"
+                    .to_string(),
+                "Default" => "linked objects cannot derive `Default` - every instance must be \
+                    created via `linked::new!`, so add a constructor like \
+                    `fn new() -> Self { linked::new!(Self { ... }) }` instead"
+                    .to_string(),
+                "PartialEq" | "Eq" | "PartialOrd" | "Ord" | "Hash" => format!(
+                    "linked objects cannot derive `{trait_name}` - implement it manually, \
+                    considering only the fields that matter"
+                ),
+                _ => continue,
+            };
+
+            let new_error = syn::Error::new_spanned(&path, message);
+
+            match &mut error {
+                Some(error) => error.combine(new_error),
+                None => error = Some(new_error),
+            }
+        }
     }
+
+    error.map_or(Ok(()), Err)
 }
 
-fn core(mut item: ItemStruct) -> Result<TokenStream, syn::Error> {
+fn core(mut item: ItemStruct, options: &Options) -> Result<TokenStream, syn::Error> {
     let (impl_generics, type_generics, where_clause) = &item.generics.split_for_impl();
     let name = &item.ident;
 
+    check_derives(&item.attrs, options)?;
+
     let Fields::Named(FieldsNamed { named: fields, .. }) = &mut item.fields else {
-        return Err(syn::Error::new(
-            item.span(),
-            "the `linked::object` attribute must be applied to a struct with named fields",
-        ));
+        return Err(named_fields_required(&item));
+    };
+
+    // `linked::new!` initializes every field via a generated function, so fields can be
+    // initialized differently depending on the attributes applied to them.
+    let field_initializers = fields
+        .iter_mut()
+        .map(field_initializer)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let field_initializers_impl = if field_initializers.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #[allow(dead_code, reason = "only used if the type is constructed via `linked::new!`")]
+            impl #impl_generics #name #type_generics #where_clause {
+                #(#field_initializers)*
+            }
+        }
     };
 
     // We add a field to store the Link<Self>, which is later referenced by other macros.
     fields
         .push(parse_quote!(#[doc(hidden)] __private_linked_link: ::linked::__private::Link<Self>));
 
+    // With `custom_clone`, the user provides their own `Clone` via `linked::clone_linked()`.
+    let clone_impl = if options.custom_clone {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics Clone for #name #type_generics #where_clause {
+                fn clone(&self) -> Self {
+                    ::linked::__private::clone(&self.__private_linked_link)
+                }
+            }
+        }
+    };
+
+    // Every instance (including the first one, created by `linked::new!` or `linked::new_try!`)
+    // is passed to this hook right after being created, so this is where we invoke the
+    // user-provided creation hook, if requested.
+    let on_instance_created_impl = if options.on_instance_created {
+        quote! {
+            fn __private_on_instance_created(&mut self) {
+                Self::on_instance_created(self);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     let extended = quote! {
         #item
 
@@ -49,14 +410,18 @@ fn core(mut item: ItemStruct) -> Result<TokenStream, syn::Error> {
             fn family(&self) -> ::linked::Family<Self> {
                 self.__private_linked_link.family()
             }
-        }
 
-        impl #impl_generics Clone for #name #type_generics #where_clause {
-            fn clone(&self) -> Self {
-                ::linked::__private::clone(self)
+            fn __private_link(&self) -> &::linked::__private::Link<Self> {
+                &self.__private_linked_link
             }
+
+            #on_instance_created_impl
         }
 
+        #field_initializers_impl
+
+        #clone_impl
+
         impl #impl_generics ::std::convert::From<::linked::Family<#name #type_generics>> for #name #type_generics #where_clause {
             fn from(family: ::linked::Family<#name #type_generics>) -> Self {
                 family.__private_into()
@@ -93,11 +458,15 @@ mod tests {
                 fn family(&self) -> ::linked::Family<Self> {
                     self.__private_linked_link.family()
                 }
+
+                fn __private_link(&self) -> &::linked::__private::Link<Self> {
+                    &self.__private_linked_link
+                }
             }
 
             impl Clone for Foo {
                 fn clone(&self) -> Self {
-                    ::linked::__private::clone(self)
+                    ::linked::__private::clone(&self.__private_linked_link)
                 }
             }
 
@@ -143,6 +512,52 @@ mod tests {
                 fn family(&self) -> ::linked::Family<Self> {
                     self.__private_linked_link.family()
                 }
+
+                fn __private_link(&self) -> &::linked::__private::Link<Self> {
+                    &self.__private_linked_link
+                }
+            }
+
+            #[allow(dead_code, reason = "only used if the type is constructed via `linked::new!`")]
+            impl<'y, T: Clone, X> Foo<'y, T, X>
+            where
+                X: Debug
+            {
+                #[doc(hidden)]
+                #[inline]
+                fn __private_linked_init_something(
+                    _link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> X,
+                ) -> X {
+                    create_value()
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                fn __private_linked_try_init_something<__LinkedError>(
+                    _link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> ::std::result::Result<X, __LinkedError>,
+                ) -> ::std::result::Result<X, __LinkedError> {
+                    create_value()
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                fn __private_linked_init_something_else(
+                    _link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> &'y Y,
+                ) -> &'y Y {
+                    create_value()
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                fn __private_linked_try_init_something_else<__LinkedError>(
+                    _link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> ::std::result::Result<&'y Y, __LinkedError>,
+                ) -> ::std::result::Result<&'y Y, __LinkedError> {
+                    create_value()
+                }
             }
 
             impl<'y, T: Clone, X> Clone for Foo<'y, T, X>
@@ -150,7 +565,7 @@ mod tests {
             X: Debug
             {
                 fn clone(&self) -> Self {
-                    ::linked::__private::clone(self)
+                    ::linked::__private::clone(&self.__private_linked_link)
                 }
             }
 
@@ -167,6 +582,227 @@ mod tests {
         assert_eq!(result.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn custom_clone_skips_clone() {
+        let input = quote! {
+            struct Foo {
+            }
+        };
+
+        let result = entrypoint(&quote! { custom_clone }, &input);
+
+        let expected = quote! {
+            struct Foo {
+                #[doc(hidden)]
+                __private_linked_link: ::linked::__private::Link<Self>
+            }
+
+            impl ::linked::Object for Foo {
+                fn family(&self) -> ::linked::Family<Self> {
+                    self.__private_linked_link.family()
+                }
+
+                fn __private_link(&self) -> &::linked::__private::Link<Self> {
+                    &self.__private_linked_link
+                }
+            }
+
+            impl ::std::convert::From<::linked::Family<Foo>> for Foo {
+                fn from(family: ::linked::Family<Foo>) -> Self {
+                    family.__private_into()
+                }
+            }
+        };
+
+        assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn on_instance_created_calls_hook() {
+        let input = quote! {
+            struct Foo {
+            }
+        };
+
+        let result = entrypoint(&quote! { custom_clone, on_instance_created }, &input);
+
+        let expected = quote! {
+            struct Foo {
+                #[doc(hidden)]
+                __private_linked_link: ::linked::__private::Link<Self>
+            }
+
+            impl ::linked::Object for Foo {
+                fn family(&self) -> ::linked::Family<Self> {
+                    self.__private_linked_link.family()
+                }
+
+                fn __private_link(&self) -> &::linked::__private::Link<Self> {
+                    &self.__private_linked_link
+                }
+
+                fn __private_on_instance_created(&mut self) {
+                    Self::on_instance_created(self);
+                }
+            }
+
+            impl ::std::convert::From<::linked::Family<Foo>> for Foo {
+                fn from(family: ::linked::Family<Foo>) -> Self {
+                    family.__private_into()
+                }
+            }
+        };
+
+        assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn shared_field_wrapped() {
+        let input = quote! {
+            struct Foo {
+                #[linked(shared)]
+                counter: AtomicUsize,
+                pub local: usize,
+            }
+        };
+
+        let result = entrypoint(&quote! { custom_clone }, &input);
+
+        let expected = quote! {
+            struct Foo {
+                counter: ::linked::Shared<AtomicUsize>,
+                pub local: usize,
+                #[doc(hidden)]
+                __private_linked_link: ::linked::__private::Link<Self>
+            }
+
+            impl ::linked::Object for Foo {
+                fn family(&self) -> ::linked::Family<Self> {
+                    self.__private_linked_link.family()
+                }
+
+                fn __private_link(&self) -> &::linked::__private::Link<Self> {
+                    &self.__private_linked_link
+                }
+            }
+
+            #[allow(dead_code, reason = "only used if the type is constructed via `linked::new!`")]
+            impl Foo {
+                #[doc(hidden)]
+                #[inline]
+                fn __private_linked_init_counter(
+                    link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> AtomicUsize,
+                ) -> ::linked::Shared<AtomicUsize> {
+                    ::linked::__private::shared_field(link, "counter", create_value)
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                fn __private_linked_try_init_counter<__LinkedError>(
+                    link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> ::std::result::Result<AtomicUsize, __LinkedError>,
+                ) -> ::std::result::Result<::linked::Shared<AtomicUsize>, __LinkedError> {
+                    ::linked::__private::try_shared_field(link, "counter", create_value)
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                pub fn __private_linked_init_local(
+                    _link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> usize,
+                ) -> usize {
+                    create_value()
+                }
+
+                #[doc(hidden)]
+                #[inline]
+                pub fn __private_linked_try_init_local<__LinkedError>(
+                    _link: &::linked::__private::Link<Self>,
+                    create_value: impl FnOnce() -> ::std::result::Result<usize, __LinkedError>,
+                ) -> ::std::result::Result<usize, __LinkedError> {
+                    create_value()
+                }
+            }
+
+            impl ::std::convert::From<::linked::Family<Foo>> for Foo {
+                fn from(family: ::linked::Family<Foo>) -> Self {
+                    family.__private_into()
+                }
+            }
+        };
+
+        assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn parent_field_wrapped() {
+        let input = quote! {
+            struct Foo {
+                #[linked(parent)]
+                service: Service,
+            }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(!contains_compile_error(&result));
+
+        let result = result.to_string();
+        assert!(result.contains(&quote! { service: ::linked::Ref<Service> }.to_string()));
+        assert!(
+            result.contains(
+                &quote! { ::linked::__private::parent_field(link, "service", create_value) }
+                    .to_string()
+            )
+        );
+        assert!(
+            result.contains(
+                &quote! { ::linked::__private::try_parent_field(link, "service", create_value) }
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn with_shared_parent_field_fails() {
+        let input = quote! {
+            struct Foo {
+                #[linked(shared, parent)]
+                service: Service,
+            }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(contains_compile_error(&result));
+    }
+
+    #[test]
+    fn with_unknown_field_option_fails() {
+        let input = quote! {
+            struct Foo {
+                #[linked(bananas)]
+                counter: usize,
+            }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(contains_compile_error(&result));
+
+        // The field option is removed, so it does not cause an unknown attribute error.
+        assert!(!result.to_string().contains("bananas"));
+    }
+
+    #[test]
+    fn with_unknown_option_fails() {
+        let input = quote! {
+            struct Foo {
+            }
+        };
+
+        let result = entrypoint(&quote! { custom_clone, bananas }, &input);
+        assert!(contains_compile_error(&result));
+    }
+
     #[test]
     fn with_unnamed_fields_fails() {
         let input = quote! {
@@ -186,4 +822,64 @@ mod tests {
         let result = entrypoint(&TokenStream::new(), &input);
         assert!(contains_compile_error(&result));
     }
+
+    #[test]
+    fn with_unit_struct_fails() {
+        let input = quote! {
+            struct Foo;
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(contains_compile_error(&result));
+    }
+
+    #[test]
+    fn with_union_fails() {
+        let input = quote! {
+            union Foo { a: u32, b: f32 }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(contains_compile_error(&result));
+    }
+
+    #[test]
+    fn with_derived_clone_fails() {
+        let input = quote! {
+            #[derive(Debug, Clone)]
+            struct Foo {
+            }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(contains_compile_error(&result));
+
+        let result = entrypoint(&quote! { custom_clone }, &input);
+        assert!(contains_compile_error(&result));
+    }
+
+    #[test]
+    fn with_derived_default_fails() {
+        let input = quote! {
+            #[derive(Debug)]
+            #[derive(std::default::Default)]
+            struct Foo {
+            }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(contains_compile_error(&result));
+    }
+
+    #[test]
+    fn with_harmless_derive_succeeds() {
+        let input = quote! {
+            #[derive(Debug)]
+            struct Foo {
+            }
+        };
+
+        let result = entrypoint(&TokenStream::new(), &input);
+        assert!(!contains_compile_error(&result));
+    }
 }
@@ -4,7 +4,7 @@
 //! This module contains helper functions for consuming and producing Rust syntax elements.
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{ToTokens, quote};
 
 /// Combines a token stream with a syn-originating contextual error message that contains
 /// all the necessary metadata to emit rich errors (with red underlines and all that).
@@ -22,6 +22,69 @@ pub(crate) fn token_stream_and_error(s: &TokenStream, e: &syn::Error) -> TokenSt
     }
 }
 
+/// Finds the candidate that the user most likely meant to type instead of `unknown`, for use in
+/// "did you mean" suggestions in error messages. Returns `None` if no candidate is similar enough.
+pub(crate) fn did_you_mean<'a>(unknown: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    // Anything further away than this is more likely a different word than a typo.
+    const MAX_DISTANCE: usize = 2;
+
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(unknown, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between two strings - the number of single-character insertions,
+/// deletions and substitutions needed to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+
+    // The distances between the prefix of `a` processed so far and every prefix of `b`.
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (a_index, a_char) in a.chars().enumerate() {
+        let mut row = Vec::with_capacity(previous_row.len());
+        row.push(a_index.wrapping_add(1));
+
+        for (b_char, window) in b.iter().zip(previous_row.windows(2)) {
+            let &[diagonal, above] = window else {
+                unreachable!("windows(2) always yields slices of length 2");
+            };
+
+            let left = *row.last().expect("we pushed the first element above");
+
+            let substitution = diagonal.wrapping_add(usize::from(a_char != *b_char));
+            let deletion = above.wrapping_add(1);
+            let insertion = left.wrapping_add(1);
+
+            row.push(substitution.min(deletion).min(insertion));
+        }
+
+        previous_row = row;
+    }
+
+    *previous_row
+        .last()
+        .expect("there is always at least one element in a row")
+}
+
+/// Renders a syntax element as source code in the compact form a human would write it in,
+/// for use in suggestions in error messages (e.g. `Vec<u8>` instead of `Vec < u8 >`).
+pub(crate) fn to_source_string(tokens: &impl ToTokens) -> String {
+    tokens
+        .to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+}
+
 /// Attempts to identify any compile-time error in the token stream. This is useful for unit
 /// testing macros - if the macro is expected to produce a compile-time error, we can check
 /// whether one exists.
@@ -87,6 +150,40 @@ mod tests {
         assert!(contains_compile_error(&tokens));
     }
 
+    #[test]
+    fn did_you_mean_finds_typo() {
+        let candidates = ["custom_clone", "on_instance_created"];
+
+        assert_eq!(
+            did_you_mean("custom_clones", &candidates),
+            Some("custom_clone")
+        );
+        assert_eq!(
+            did_you_mean("on_instance_create", &candidates),
+            Some("on_instance_created")
+        );
+        assert_eq!(did_you_mean("bananas", &candidates), None);
+    }
+
+    #[test]
+    fn edit_distance_counts_changes() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("shared", "shared"), 0);
+        assert_eq!(edit_distance("share", "shared"), 1);
+        assert_eq!(edit_distance("shraed", "shared"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn to_source_string_is_compact() {
+        let ty: syn::Type = syn::parse_quote!(::std::sync::Arc<Mutex<Vec<u8>>>);
+        assert_eq!(to_source_string(&ty), "::std::sync::Arc<Mutex<Vec<u8>>>");
+
+        let ty: syn::Type = syn::parse_quote!(&'a HashMap<String, usize>);
+        assert_eq!(to_source_string(&ty), "&'a HashMap<String, usize>");
+    }
+
     #[test]
     fn contains_compile_error_no() {
         let tokens = quote! {
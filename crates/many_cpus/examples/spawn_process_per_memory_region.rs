@@ -0,0 +1,50 @@
+//! Spawns one child process for each memory region, with each process pinned to the processors
+//! of its memory region. This demonstrates the building block of a shared-nothing architecture
+//! where each memory region is served by its own process.
+//!
+//! The child processes are instances of this same example, told which memory region they own
+//! via an environment variable.
+
+use std::{env, process::Command};
+
+use many_cpus::{HardwareTracker, ProcessorSet};
+
+const MEMORY_REGION_ENV_VAR: &str = "MANY_CPUS_EXAMPLE_MEMORY_REGION";
+
+fn main() {
+    if let Ok(memory_region_id) = env::var(MEMORY_REGION_ENV_VAR) {
+        // We are one of the child processes.
+        println!(
+            "Child process for memory region {memory_region_id} is running on processor {} in memory region {}",
+            HardwareTracker::current_processor_id(),
+            HardwareTracker::current_memory_region_id()
+        );
+
+        return;
+    }
+
+    let current_exe = env::current_exe().expect("could not determine the current executable");
+
+    let processes = ProcessorSet::default()
+        .spawn_processes_per_memory_region(|memory_region_id, processors| {
+            println!(
+                "Spawning child process for memory region {memory_region_id} with {} processors",
+                processors.len()
+            );
+
+            let mut command = Command::new(&current_exe);
+            command.env(MEMORY_REGION_ENV_VAR, memory_region_id.to_string());
+            command
+        })
+        .expect("could not spawn child processes - this example is not supported on Windows");
+
+    for process in processes {
+        let memory_region_id = process.memory_region_id();
+        let status = process
+            .into_child()
+            .wait()
+            .expect("could not wait for child process");
+
+        println!("Child process for memory region {memory_region_id} exited with {status}");
+    }
+}
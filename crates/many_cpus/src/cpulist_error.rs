@@ -0,0 +1,56 @@
+use std::{error::Error, fmt};
+
+use crate::ProcessorId;
+
+/// Explains why [`ProcessorSet::from_cpulist()`][1] could not create a processor set from a
+/// cpulist string.
+///
+/// [1]: crate::ProcessorSet::from_cpulist
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CpulistError {
+    /// The string is not a valid cpulist.
+    InvalidSyntax {
+        /// A human-readable description of the problem.
+        problem: String,
+    },
+
+    /// The cpulist does not list any processors.
+    Empty,
+
+    /// The cpulist lists processors with IDs greater than the highest processor ID that can
+    /// exist on the system.
+    UnknownProcessors {
+        /// The IDs of the unknown processors, in the order they were listed.
+        processor_ids: Vec<ProcessorId>,
+    },
+
+    /// The cpulist lists processors that exist but are not available to the current process,
+    /// e.g. because they are offline or excluded from the processor affinity of the process.
+    UnavailableProcessors {
+        /// The IDs of the unavailable processors, in the order they were listed.
+        processor_ids: Vec<ProcessorId>,
+    },
+}
+
+impl fmt::Display for CpulistError {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for the exact message.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSyntax { problem } => write!(f, "invalid cpulist: {problem}"),
+            Self::Empty => write!(f, "cpulist does not list any processors"),
+            Self::UnknownProcessors { processor_ids } => write!(
+                f,
+                "cpulist lists processors that do not exist on the system: {}",
+                cpulist::emit(processor_ids.iter().copied())
+            ),
+            Self::UnavailableProcessors { processor_ids } => write!(
+                f,
+                "cpulist lists processors that are offline or not available to the current process: {}",
+                cpulist::emit(processor_ids.iter().copied())
+            ),
+        }
+    }
+}
+
+impl Error for CpulistError {}
@@ -81,6 +81,64 @@ impl HardwareInfo {
             .checked_add(1)
             .expect("overflow when counting memory regions - this can only result from a critical error in the PAL")
     }
+
+    /// Gets the relative distance between two memory regions, as reported by the platform.
+    ///
+    /// The values follow the conventions of the ACPI System Locality Information Table: the
+    /// distance from a memory region to itself is 10 and greater values mean that accessing memory
+    /// in the `to` memory region from processors in the `from` memory region is slower. The values
+    /// are only meaningful relative to each other.
+    ///
+    /// If the platform does not report the distances (e.g. on Windows, or for memory regions that
+    /// are not currently active), the distance between different memory regions is 20.
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    #[inline]
+    #[must_use]
+    pub fn memory_region_distance(from: MemoryRegionId, to: MemoryRegionId) -> u32 {
+        BUILD_TARGET_PLATFORM.memory_region_distance(from, to)
+    }
+
+    /// Gets the relative distances between all pairs of memory regions, as reported by the
+    /// platform, indexed as `[from][to]` by memory region ID.
+    ///
+    /// The matrix covers every memory region that could possibly be present on the system (see
+    /// [`max_memory_region_count()`][Self::max_memory_region_count]), with each value following
+    /// the same conventions as [`memory_region_distance()`][Self::memory_region_distance].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use many_cpus::HardwareInfo;
+    ///
+    /// for (from, distances) in HardwareInfo::memory_region_distances().iter().enumerate() {
+    ///     println!("{from}: {distances:?}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn memory_region_distances() -> Vec<Vec<u32>> {
+        (0..=Self::max_memory_region_id())
+            .map(|from| {
+                (0..=Self::max_memory_region_id())
+                    .map(|to| Self::memory_region_distance(from, to))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Gets the memory region that the PCI device with the given address (e.g. `0000:3b:00.0`
+    /// for a network adapter) is attached to.
+    ///
+    /// Accessing the device from processors in this memory region is typically cheaper than
+    /// accessing it from processors in other memory regions.
+    ///
+    /// Returns `None` if there is no such device or the platform does not report the memory
+    /// region of the device. This is currently only reported on Linux.
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    #[inline]
+    #[must_use]
+    pub fn pci_device_memory_region_id(pci_address: &str) -> Option<MemoryRegionId> {
+        BUILD_TARGET_PLATFORM.pci_device_memory_region_id(pci_address)
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +157,32 @@ mod tests {
             HardwareInfo::max_memory_region_id() as usize + 1
         );
     }
+
+    #[cfg(not(miri))] // Real platform is not supported under Miri.
+    #[test]
+    fn distance_matrix_matches_distances_real() {
+        let distances = HardwareInfo::memory_region_distances();
+
+        assert_eq!(distances.len(), HardwareInfo::max_memory_region_count());
+
+        for (from, row) in (0..).zip(&distances) {
+            assert_eq!(row.len(), HardwareInfo::max_memory_region_count());
+
+            for (to, distance) in (0..).zip(row) {
+                assert_eq!(*distance, HardwareInfo::memory_region_distance(from, to));
+            }
+        }
+    }
+
+    #[cfg(not(miri))] // Real platform is not supported under Miri.
+    #[test]
+    fn memory_region_is_nearest_to_itself_real() {
+        for from in 0..=HardwareInfo::max_memory_region_id() {
+            let local = HardwareInfo::memory_region_distance(from, from);
+
+            for to in 0..=HardwareInfo::max_memory_region_id() {
+                assert!(HardwareInfo::memory_region_distance(from, to) >= local);
+            }
+        }
+    }
 }
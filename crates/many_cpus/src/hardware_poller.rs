@@ -0,0 +1,481 @@
+use std::{
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{self, AtomicBool, AtomicU64},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    MemoryRegionId, ProcessorId,
+    pal::{AbstractProcessor, Platform, PlatformFacade},
+};
+
+/// Decides when the hardware poller started via [`HardwareTracker::start_polling_with()`][1]
+/// refreshes the hardware information it tracks.
+///
+/// The poller calls [`wait()`][Self::wait] on its own thread in a loop, refreshing the hardware
+/// information after every call that reports a poll as due. Custom implementations allow tests
+/// to control exactly when the poller refreshes, instead of depending on timing.
+///
+/// The same applies to the checks of a [`ProcessorWatchdog`][2].
+///
+/// [1]: crate::HardwareTracker::start_polling_with
+/// [2]: crate::ProcessorWatchdog
+pub trait PollScheduler: Send + 'static {
+    /// Blocks the poller thread until the next poll is due, returning `true` if it is.
+    ///
+    /// Stopping the poller unparks the poller thread, so implementations should block via
+    /// [`thread::park()`] or [`thread::park_timeout()`] and return `false` if unparked before
+    /// the next poll is due, which allows the poller to stop promptly.
+    fn wait(&mut self) -> bool;
+}
+
+/// A [`PollScheduler`] that polls at a fixed interval. This is the scheduler used by
+/// [`HardwareTracker::start_polling()`][1].
+///
+/// [1]: crate::HardwareTracker::start_polling
+#[derive(Debug)]
+pub struct IntervalPollScheduler {
+    interval: Duration,
+}
+
+impl IntervalPollScheduler {
+    /// Creates a scheduler that polls every `interval`.
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+}
+
+impl PollScheduler for IntervalPollScheduler {
+    #[cfg_attr(test, mutants::skip)] // Timing-dependent, we only test the poller logic.
+    fn wait(&mut self) -> bool {
+        let started = Instant::now();
+
+        thread::park_timeout(self.interval);
+
+        // If we were unparked early, either the poller is stopping or this was a spurious
+        // wakeup. Either way, we are not due yet and the poller will call us again if needed.
+        started.elapsed() >= self.interval
+    }
+}
+
+/// A change in the hardware information observed by the hardware poller, delivered to the
+/// callbacks registered via [`HardwareTracker::subscribe()`][1].
+///
+/// [1]: crate::HardwareTracker::subscribe
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum HardwareChange {
+    /// The resource quota of the current process changed.
+    ResourceQuota {
+        /// The new value of [`ResourceQuota::max_processor_time()`][1].
+        ///
+        /// [1]: crate::ResourceQuota::max_processor_time
+        max_processor_time: f64,
+    },
+
+    /// The number of active processors on the system changed.
+    ActiveProcessorCount {
+        /// The new value of [`HardwareTracker::active_processor_count()`][1].
+        ///
+        /// [1]: crate::HardwareTracker::active_processor_count
+        active_processor_count: usize,
+    },
+
+    /// The set of processors available to the current process changed.
+    AvailableProcessors {
+        /// The number of processors now available to the current process.
+        available_processor_count: usize,
+    },
+
+    /// Processors were brought online on the system (e.g. CPU hot-plug).
+    ProcessorsOnline {
+        /// The IDs of the processors that came online, sorted ascending.
+        processor_ids: Vec<ProcessorId>,
+    },
+
+    /// Processors were taken offline on the system.
+    ProcessorsOffline {
+        /// The IDs of the processors that went offline, sorted ascending.
+        processor_ids: Vec<ProcessorId>,
+    },
+
+    /// Memory regions appeared on the system (e.g. memory hot-add in a virtual machine).
+    MemoryRegionsAppeared {
+        /// The IDs of the memory regions that appeared, sorted ascending.
+        memory_region_ids: Vec<MemoryRegionId>,
+    },
+
+    /// Memory regions disappeared from the system.
+    MemoryRegionsDisappeared {
+        /// The IDs of the memory regions that disappeared, sorted ascending.
+        memory_region_ids: Vec<MemoryRegionId>,
+    },
+}
+
+/// A subscription to hardware changes created via [`HardwareTracker::subscribe()`][1].
+///
+/// Changes are delivered to the callback of the subscription until this is dropped.
+///
+/// [1]: crate::HardwareTracker::subscribe
+#[derive(Debug)]
+pub struct HardwareSubscription {
+    id: u64,
+}
+
+impl Drop for HardwareSubscription {
+    fn drop(&mut self) {
+        SUBSCRIBERS
+            .write()
+            .expect(ERR_POISONED_LOCK)
+            .retain(|(id, _)| *id != self.id);
+    }
+}
+
+type Subscriber = Arc<dyn Fn(&HardwareChange) + Send + Sync>;
+
+static SUBSCRIBERS: RwLock<Vec<(u64, Subscriber)>> = RwLock::new(Vec::new());
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(0);
+
+// The poller of the process, if one is running.
+static POLLER: Mutex<Option<HardwarePoller>> = Mutex::new(None);
+
+const ERR_POISONED_LOCK: &str =
+    "poisoned lock - safe execution no longer possible because a panic occurred on another thread";
+
+pub(crate) fn subscribe(
+    callback: impl Fn(&HardwareChange) + Send + Sync + 'static,
+) -> HardwareSubscription {
+    let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, atomic::Ordering::Relaxed);
+
+    SUBSCRIBERS
+        .write()
+        .expect(ERR_POISONED_LOCK)
+        .push((id, Arc::new(callback)));
+
+    HardwareSubscription { id }
+}
+
+pub(crate) fn start_polling(pal: PlatformFacade, scheduler: impl PollScheduler) {
+    // We replace any existing poller, so a new cadence takes effect immediately.
+    stop_polling();
+
+    let poller = HardwarePoller::start(pal, scheduler);
+    let previous = POLLER.lock().expect(ERR_POISONED_LOCK).replace(poller);
+
+    // Someone else started a poller concurrently - last one wins.
+    if let Some(previous) = previous {
+        previous.stop();
+    }
+}
+
+pub(crate) fn stop_polling() {
+    // We release the lock before waiting for the poller thread, as subscriber callbacks
+    // executing on the poller thread may themselves try to access the poller.
+    let poller = POLLER.lock().expect(ERR_POISONED_LOCK).take();
+
+    if let Some(poller) = poller {
+        poller.stop();
+    }
+}
+
+pub(crate) fn is_polling() -> bool {
+    POLLER.lock().expect(ERR_POISONED_LOCK).is_some()
+}
+
+/// Publishes a change to all subscribers.
+fn publish(change: &HardwareChange) {
+    // We call the subscribers without holding the lock, so they may (un)subscribe meanwhile.
+    let subscribers = SUBSCRIBERS
+        .read()
+        .expect(ERR_POISONED_LOCK)
+        .iter()
+        .map(|(_, subscriber)| Arc::clone(subscriber))
+        .collect::<Vec<_>>();
+
+    for subscriber in subscribers {
+        subscriber(change);
+    }
+}
+
+/// A background thread that polls the hardware information and publishes any changes.
+#[derive(Debug)]
+struct HardwarePoller {
+    stop_requested: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl HardwarePoller {
+    fn start(pal: PlatformFacade, scheduler: impl PollScheduler) -> Self {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::Builder::new()
+            .name("many_cpus_hardware_poller".to_string())
+            .spawn({
+                let stop_requested = Arc::clone(&stop_requested);
+                move || poll_until_stopped(&pal, scheduler, &stop_requested)
+            })
+            .expect("failed to spawn hardware poller thread");
+
+        Self {
+            stop_requested,
+            thread,
+        }
+    }
+
+    /// Stops the poller, waiting for the poller thread to exit.
+    fn stop(self) {
+        self.stop_requested.store(true, atomic::Ordering::Release);
+        self.thread.thread().unpark();
+
+        self.thread
+            .join()
+            .expect("hardware poller thread panicked - a subscriber callback must have panicked");
+    }
+}
+
+fn poll_until_stopped(
+    pal: &PlatformFacade,
+    mut scheduler: impl PollScheduler,
+    stop_requested: &AtomicBool,
+) {
+    let mut observed = ObservedHardware::new(pal);
+
+    loop {
+        let is_due = scheduler.wait();
+
+        if stop_requested.load(atomic::Ordering::Acquire) {
+            return;
+        }
+
+        if is_due {
+            for change in observed.refresh(pal) {
+                publish(&change);
+            }
+        }
+    }
+}
+
+/// The hardware information as of the last poll.
+#[derive(Debug)]
+struct ObservedHardware {
+    max_processor_time: f64,
+    active_processor_count: usize,
+
+    // We compare the IDs, not only the count, as the processors may also be swapped for others.
+    available_processor_ids: Vec<ProcessorId>,
+
+    // Sorted ascending, as reported by the platform.
+    online_processor_ids: Vec<ProcessorId>,
+    online_memory_region_ids: Vec<MemoryRegionId>,
+}
+
+impl ObservedHardware {
+    fn new(pal: &PlatformFacade) -> Self {
+        // The processors available to the process may change at runtime (e.g. the cgroup cpuset
+        // of a resized container), so we re-evaluate them on every poll. This also keeps the
+        // processor sets built meanwhile up to date.
+        pal.refresh_available_processors();
+
+        Self {
+            max_processor_time: pal.max_processor_time(),
+            active_processor_count: pal.active_processor_count(),
+            available_processor_ids: pal
+                .get_all_processors()
+                .iter()
+                .map(AbstractProcessor::id)
+                .collect(),
+            online_processor_ids: pal.online_processor_ids(),
+            online_memory_region_ids: pal.online_memory_region_ids(),
+        }
+    }
+
+    /// Refreshes the hardware information, returning what changed since the last poll.
+    fn refresh(&mut self, pal: &PlatformFacade) -> Vec<HardwareChange> {
+        let current = Self::new(pal);
+        let mut changes = Vec::new();
+
+        // We compare the bits because any change is a change, even a tiny one.
+        if current.max_processor_time.to_bits() != self.max_processor_time.to_bits() {
+            changes.push(HardwareChange::ResourceQuota {
+                max_processor_time: current.max_processor_time,
+            });
+        }
+
+        if current.active_processor_count != self.active_processor_count {
+            changes.push(HardwareChange::ActiveProcessorCount {
+                active_processor_count: current.active_processor_count,
+            });
+        }
+
+        if current.available_processor_ids != self.available_processor_ids {
+            changes.push(HardwareChange::AvailableProcessors {
+                available_processor_count: current.available_processor_ids.len(),
+            });
+        }
+
+        let (online, offline) =
+            appeared_and_disappeared(&self.online_processor_ids, &current.online_processor_ids);
+
+        if !online.is_empty() {
+            changes.push(HardwareChange::ProcessorsOnline {
+                processor_ids: online,
+            });
+        }
+
+        if !offline.is_empty() {
+            changes.push(HardwareChange::ProcessorsOffline {
+                processor_ids: offline,
+            });
+        }
+
+        let (appeared, disappeared) = appeared_and_disappeared(
+            &self.online_memory_region_ids,
+            &current.online_memory_region_ids,
+        );
+
+        if !appeared.is_empty() {
+            changes.push(HardwareChange::MemoryRegionsAppeared {
+                memory_region_ids: appeared,
+            });
+        }
+
+        if !disappeared.is_empty() {
+            changes.push(HardwareChange::MemoryRegionsDisappeared {
+                memory_region_ids: disappeared,
+            });
+        }
+
+        *self = current;
+        changes
+    }
+}
+
+/// Returns the items that are in `current` but not in `previous` and the items that are in
+/// `previous` but not in `current`, preserving the order of the items.
+fn appeared_and_disappeared<T: Copy + PartialEq>(
+    previous: &[T],
+    current: &[T],
+) -> (Vec<T>, Vec<T>) {
+    let appeared = current
+        .iter()
+        .filter(|item| !previous.contains(item))
+        .copied()
+        .collect();
+
+    let disappeared = previous
+        .iter()
+        .filter(|item| !current.contains(item))
+        .copied()
+        .collect();
+
+    (appeared, disappeared)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use nonempty::nonempty;
+
+    use crate::pal::{FakeProcessor, MockPlatform, ProcessorFacade};
+
+    use super::*;
+
+    /// Reports a poll as due a fixed number of times, then notifies the test and waits to
+    /// be stopped.
+    struct CountedScheduler {
+        remaining_polls: usize,
+        polls_completed: mpsc::Sender<()>,
+    }
+
+    impl PollScheduler for CountedScheduler {
+        fn wait(&mut self) -> bool {
+            let Some(remaining_polls) = self.remaining_polls.checked_sub(1) else {
+                // The previous poll was the last one. The test may be gone already.
+                drop(self.polls_completed.send(()));
+                thread::park();
+                return false;
+            };
+
+            self.remaining_polls = remaining_polls;
+            true
+        }
+    }
+
+    #[test]
+    fn publishes_changes_until_stopped() {
+        let mut platform = MockPlatform::new();
+
+        // Initial observation, then two polls - the quota only changes in the first.
+        platform.expect_max_processor_time().times(3).returning({
+            let mut values = [2.0, 1.0, 1.0].into_iter();
+            move || values.next().unwrap()
+        });
+        platform
+            .expect_active_processor_count()
+            .times(3)
+            .return_const(2_usize);
+        platform
+            .expect_refresh_available_processors()
+            .times(3)
+            .return_const(());
+
+        // Processor 1 goes offline in the second poll, memory regions stay the same.
+        platform.expect_online_processor_ids().times(3).returning({
+            let mut values = [vec![0, 1], vec![0, 1], vec![0]].into_iter();
+            move || values.next().unwrap()
+        });
+        platform
+            .expect_online_memory_region_ids()
+            .times(3)
+            .return_const(vec![0]);
+        platform
+            .expect_get_all_processors_core()
+            .times(3)
+            .return_const(nonempty![
+                ProcessorFacade::Fake(FakeProcessor::with_index(0)),
+                ProcessorFacade::Fake(FakeProcessor::with_index(1))
+            ]);
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+
+        let subscription = subscribe({
+            let changes = Arc::clone(&changes);
+            move |change| changes.lock().unwrap().push(change.clone())
+        });
+
+        let (polls_completed_tx, polls_completed_rx) = mpsc::channel();
+
+        start_polling(
+            platform.into(),
+            CountedScheduler {
+                remaining_polls: 2,
+                polls_completed: polls_completed_tx,
+            },
+        );
+        assert!(is_polling());
+
+        polls_completed_rx.recv().unwrap();
+
+        stop_polling();
+        assert!(!is_polling());
+
+        drop(subscription);
+
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [
+                HardwareChange::ResourceQuota {
+                    max_processor_time: 1.0
+                },
+                HardwareChange::ProcessorsOffline {
+                    processor_ids: vec![1]
+                }
+            ]
+        );
+    }
+}
@@ -1,9 +1,10 @@
-use std::{cell::RefCell, marker::PhantomData};
+use std::{cell::RefCell, marker::PhantomData, time::Duration};
 
 use negative_impl::negative_impl;
 
 use crate::{
-    MemoryRegionId, Processor, ProcessorId, ResourceQuota,
+    HardwareChange, HardwareSubscription, IntervalPollScheduler, MemoryRegionId, PollScheduler,
+    Processor, ProcessorId, ResourceQuota, hardware_poller,
     pal::{AbstractProcessor, Platform, PlatformFacade},
 };
 
@@ -248,6 +249,99 @@ impl HardwareTracker {
         // and ignore changes that occur at runtime.
         CURRENT_TRACKER.with_borrow(HardwareTrackerCore::active_processor_count)
     }
+
+    /// Starts a background thread that polls the hardware information every `interval`,
+    /// delivering any changes to the callbacks registered via [`subscribe()`][1].
+    ///
+    /// This allows any number of consumers to react to changes (e.g. a reduced resource quota)
+    /// without each of them running their own polling loop. If polling is already active, the
+    /// existing poller is replaced, so this can also be used to change the cadence.
+    ///
+    /// The poller runs until stopped via [`stop_polling()`][2].
+    ///
+    /// Note that the platform currently loads the processor information once and ignores any
+    /// changes that occur at runtime, so in practice only resource quota changes are observed.
+    ///
+    /// [1]: HardwareTracker::subscribe
+    /// [2]: HardwareTracker::stop_polling
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    pub fn start_polling(interval: Duration) {
+        Self::start_polling_with(IntervalPollScheduler::new(interval));
+    }
+
+    /// Starts a background thread that polls the hardware information whenever the scheduler
+    /// reports a poll as due, delivering any changes to the callbacks registered via
+    /// [`subscribe()`][1].
+    ///
+    /// This is the same as [`start_polling()`][2] except that the scheduler decides when to
+    /// poll, which allows tests to make polling deterministic.
+    ///
+    /// [1]: HardwareTracker::subscribe
+    /// [2]: HardwareTracker::start_polling
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    pub fn start_polling_with(scheduler: impl PollScheduler) {
+        hardware_poller::start_polling(PlatformFacade::real(), scheduler);
+    }
+
+    /// Stops the background thread started via [`start_polling()`][1], waiting for it to exit.
+    ///
+    /// Does nothing if polling is not active.
+    ///
+    /// [1]: HardwareTracker::start_polling
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    pub fn stop_polling() {
+        hardware_poller::stop_polling();
+    }
+
+    /// Whether the hardware information is being polled in the background, as started via
+    /// [`start_polling()`][1].
+    ///
+    /// [1]: HardwareTracker::start_polling
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    #[must_use]
+    pub fn is_polling() -> bool {
+        hardware_poller::is_polling()
+    }
+
+    /// Subscribes to changes in the hardware information observed by the background poller
+    /// started via [`start_polling()`][1].
+    ///
+    /// The callback is called on the poller thread, once for every change observed by a poll.
+    /// Changes are delivered until the returned [`HardwareSubscription`] is dropped.
+    ///
+    /// Besides changes in the constraints applied to the current process, this reports changes
+    /// in the topology of the system, such as processors going online or offline and memory
+    /// regions appearing or disappearing (see [`HardwareChange`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use many_cpus::{HardwareChange, HardwareTracker};
+    ///
+    /// let subscription = HardwareTracker::subscribe(|change| {
+    ///     if let HardwareChange::ResourceQuota { max_processor_time } = change {
+    ///         println!("Resource quota changed to {max_processor_time} processors");
+    ///     }
+    /// });
+    ///
+    /// HardwareTracker::start_polling(Duration::from_secs(10));
+    ///
+    /// // ...
+    ///
+    /// HardwareTracker::stop_polling();
+    /// drop(subscription);
+    /// ```
+    ///
+    /// [1]: HardwareTracker::start_polling
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    #[must_use = "changes are only delivered while the subscription exists"]
+    pub fn subscribe(
+        callback: impl Fn(&HardwareChange) + Send + Sync + 'static,
+    ) -> HardwareSubscription {
+        hardware_poller::subscribe(callback)
+    }
 }
 
 /// The real implementation of `HardwareTracker`, accepting the PAL facade as a parameter
@@ -351,7 +445,8 @@ impl HardwareTrackerCore {
     #[must_use]
     pub(crate) fn resource_quota(&self) -> ResourceQuota {
         let max_processor_time = self.pal.max_processor_time();
-        ResourceQuota::new(max_processor_time)
+        let processor_weight = self.pal.processor_weight();
+        ResourceQuota::new(max_processor_time, processor_weight)
     }
 
     #[must_use]
@@ -542,12 +637,14 @@ mod tests {
             FakeProcessor {
                 index: 0,
                 memory_region: 0,
-                efficiency_class: EfficiencyClass::Performance
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
-                efficiency_class: EfficiencyClass::Performance
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             } // Ghost at index 2.
         ];
 
@@ -651,12 +748,14 @@ mod tests {
             FakeProcessor {
                 index: 0,
                 memory_region: 0,
-                efficiency_class: EfficiencyClass::Performance
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
-                efficiency_class: EfficiencyClass::Performance
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -676,17 +775,28 @@ mod tests {
             .times(1)
             .return_const(2.5);
 
+        platform
+            .expect_processor_weight()
+            .times(1)
+            .return_const(Some(0.5));
+
         let tracker = HardwareTrackerCore::new(PlatformFacade::from_mock(platform));
+        let resource_quota = tracker.resource_quota();
 
         #[expect(
             clippy::float_cmp,
             reason = "we use absolute error, which is the right thing to do"
         )]
         {
+            assert_eq!(
+                f64_diff_abs(resource_quota.max_processor_time(), 2.5, CLOSE_ENOUGH),
+                0.0
+            );
+
             assert_eq!(
                 f64_diff_abs(
-                    tracker.resource_quota().max_processor_time(),
-                    2.5,
+                    resource_quota.processor_weight().unwrap(),
+                    0.5,
                     CLOSE_ENOUGH
                 ),
                 0.0
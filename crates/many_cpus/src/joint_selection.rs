@@ -0,0 +1,364 @@
+use std::cmp::Reverse;
+
+use foldhash::HashSet;
+use itertools::Itertools;
+use rand::prelude::*;
+
+use crate::{Processor, ProcessorId};
+
+/// What two named selections of a [`ProcessorSetBuilder`][crate::ProcessorSetBuilder] must not
+/// share, as declared via
+/// [`ProcessorSetBuilder::anti_affinity()`][crate::ProcessorSetBuilder::anti_affinity].
+///
+/// Named selections never share processors, regardless of any anti-affinity.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum AntiAffinity {
+    /// The selections do not share any physical processor cores, so their threads never
+    /// compete for the execution resources of a core via simultaneous multithreading.
+    ///
+    /// If the platform does not report which processors share a physical core, every processor
+    /// is considered to be a separate core.
+    DistinctCores,
+
+    /// The selections do not share any last level cache (L3) domains, so they do not evict each
+    /// other's data from the cache.
+    ///
+    /// See [`ProcessorSetBuilder::same_l3_domain()`][crate::ProcessorSetBuilder::same_l3_domain]
+    /// for details on L3 domains.
+    DistinctL3Domains,
+
+    /// The selections do not share any memory regions.
+    DifferentMemoryRegions,
+}
+
+impl AntiAffinity {
+    /// From the coarsest to the finest, which is also the order in which we pack selections.
+    const ALL_COARSEST_FIRST: [Self; 3] = [
+        Self::DifferentMemoryRegions,
+        Self::DistinctL3Domains,
+        Self::DistinctCores,
+    ];
+}
+
+/// Resolves the processors of all the named selections at once, so that each selection gets
+/// `count` processors from `candidates`, no two selections share a processor and no two
+/// selections connected by an anti-affinity share any domain of the kind that the anti-affinity
+/// forbids.
+///
+/// `selections` are the requested processor counts, `anti_affinities` refer to the selections
+/// by their index and `domain_of` identifies the domain (e.g. the physical core) of a processor.
+///
+/// Returns the processors of each selection, in the order of `selections`, or `None` if there
+/// is no assignment of processors that satisfies all the constraints.
+pub(crate) fn resolve_jointly(
+    candidates: &[Processor],
+    selections: &[usize],
+    anti_affinities: &[(usize, usize, AntiAffinity)],
+    rng: &mut impl Rng,
+    domain_of: impl Fn(&Processor, AntiAffinity) -> u32,
+) -> Option<Vec<Vec<Processor>>> {
+    // Only the domain kinds that take part in some anti-affinity matter for packing.
+    let relevant_kinds = AntiAffinity::ALL_COARSEST_FIRST
+        .into_iter()
+        .filter(|kind| anti_affinities.iter().any(|(_, _, k)| k == kind))
+        .collect_vec();
+
+    // We look up the domains once, as the lookups may be expensive.
+    let candidates = candidates
+        .iter()
+        .map(|p| Candidate {
+            domains: relevant_kinds
+                .iter()
+                .map(|kind| (*kind, domain_of(p, *kind)))
+                .collect(),
+            processor: p.clone(),
+        })
+        .collect_vec();
+
+    // The largest selections are the hardest to satisfy, so we resolve them first.
+    let order = (0..selections.len())
+        .sorted_by_key(|index| Reverse(selections.get(*index)))
+        .collect_vec();
+
+    let mut assignments = vec![None; selections.len()];
+
+    if !assign(
+        &candidates,
+        selections,
+        anti_affinities,
+        &order,
+        &mut assignments,
+        rng,
+    ) {
+        return None;
+    }
+
+    Some(
+        assignments
+            .into_iter()
+            .map(|assignment: Option<Vec<&Candidate>>| {
+                assignment
+                    .expect("every selection is assigned if the search succeeded")
+                    .into_iter()
+                    .map(|c| c.processor.clone())
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+#[derive(Debug)]
+struct Candidate {
+    processor: Processor,
+
+    // The domain of the processor for each relevant kind, coarsest first.
+    domains: Vec<(AntiAffinity, u32)>,
+}
+
+impl Candidate {
+    fn domain(&self, kind: AntiAffinity) -> u32 {
+        self.domains
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, domain)| *domain)
+            .expect("we look up the domains of every kind that takes part in an anti-affinity")
+    }
+}
+
+/// Assigns processors to the selections in `order`, backtracking whenever a selection cannot be
+/// satisfied with what the previously assigned selections left over.
+fn assign<'a>(
+    candidates: &'a [Candidate],
+    selections: &[usize],
+    anti_affinities: &[(usize, usize, AntiAffinity)],
+    order: &[usize],
+    assignments: &mut [Option<Vec<&'a Candidate>>],
+    rng: &mut impl Rng,
+) -> bool {
+    let Some((&selection, remaining_order)) = order.split_first() else {
+        // Everything is assigned - we are done.
+        return true;
+    };
+
+    let count = *selections
+        .get(selection)
+        .expect("the order only refers to existing selections");
+
+    let used: HashSet<ProcessorId> = assignments
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|c| c.processor.id())
+        .collect();
+
+    // The domains the selection must stay away from, due to the already assigned selections.
+    let forbidden: HashSet<(AntiAffinity, u32)> = anti_affinities
+        .iter()
+        .filter_map(|(a, b, kind)| {
+            if *a == selection {
+                Some((*b, *kind))
+            } else if *b == selection {
+                Some((*a, *kind))
+            } else {
+                None
+            }
+        })
+        .filter_map(|(other, kind)| {
+            let assigned = assignments.get(other)?.as_ref()?;
+            Some(assigned.iter().map(move |c| (kind, c.domain(kind))))
+        })
+        .flatten()
+        .collect();
+
+    let pool = candidates
+        .iter()
+        .filter(|c| !used.contains(&c.processor.id()))
+        .filter(|c| {
+            !c.domains
+                .iter()
+                .any(|(kind, domain)| forbidden.contains(&(*kind, *domain)))
+        })
+        .collect_vec();
+
+    if pool.len() < count {
+        return false;
+    }
+
+    // Each option packs the selection as tightly as possible, starting from a different memory
+    // region, so it leaves as many whole domains as possible to the other selections. We try the
+    // starting regions in random order, to spread the selections over the system.
+    let mut anchor_regions = pool
+        .iter()
+        .map(|c| c.processor.memory_region_id())
+        .unique()
+        .collect_vec();
+    anchor_regions.shuffle(rng);
+
+    let mut tried_options = HashSet::default();
+
+    for anchor_region in anchor_regions {
+        let option = pool
+            .iter()
+            .copied()
+            .sorted_by_key(|c| {
+                (
+                    c.processor.memory_region_id() != anchor_region,
+                    c.domains.iter().map(|(_, domain)| *domain).collect_vec(),
+                    c.processor.id(),
+                )
+            })
+            .take(count)
+            .collect_vec();
+
+        let option_key = option
+            .iter()
+            .map(|c| c.processor.id())
+            .sorted_unstable()
+            .collect_vec();
+
+        if !tried_options.insert(option_key) {
+            continue;
+        }
+
+        *assignments
+            .get_mut(selection)
+            .expect("the order only refers to existing selections") = Some(option);
+
+        if assign(
+            candidates,
+            selections,
+            anti_affinities,
+            remaining_order,
+            assignments,
+            rng,
+        ) {
+            return true;
+        }
+    }
+
+    *assignments
+        .get_mut(selection)
+        .expect("the order only refers to existing selections") = None;
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EfficiencyClass,
+        pal::{FakeProcessor, ProcessorFacade},
+    };
+
+    use rand::rng;
+
+    use super::*;
+
+    fn processor(index: ProcessorId, memory_region: u32) -> Processor {
+        Processor::new(ProcessorFacade::Fake(FakeProcessor {
+            index,
+            memory_region,
+            efficiency_class: EfficiencyClass::Performance,
+            package: 0,
+        }))
+    }
+
+    fn ids(processors: &[Processor]) -> Vec<ProcessorId> {
+        processors.iter().map(Processor::id).sorted().collect()
+    }
+
+    /// Processors 0-3 in region 0 and processors 4-5 in region 1, with two processors per core.
+    fn candidates() -> Vec<Processor> {
+        vec![
+            processor(0, 0),
+            processor(1, 0),
+            processor(2, 0),
+            processor(3, 0),
+            processor(4, 1),
+            processor(5, 1),
+        ]
+    }
+
+    fn domain_of(p: &Processor, kind: AntiAffinity) -> u32 {
+        match kind {
+            AntiAffinity::DifferentMemoryRegions => p.memory_region_id(),
+            AntiAffinity::DistinctL3Domains => 0,
+            AntiAffinity::DistinctCores => p.id() / 2,
+        }
+    }
+
+    #[test]
+    fn different_memory_regions_resolved_jointly() {
+        // Resolving the small selection first could put it in region 0, leaving
+        // not enough processors in region 1 for the large selection.
+        let (small, large) = resolve_jointly(
+            &candidates(),
+            &[2, 4],
+            &[(0, 1, AntiAffinity::DifferentMemoryRegions)],
+            &mut rng(),
+            domain_of,
+        )
+        .unwrap()
+        .into_iter()
+        .collect_tuple()
+        .unwrap();
+
+        assert_eq!(ids(&small), [4, 5]);
+        assert_eq!(ids(&large), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn distinct_cores_packs_selections() {
+        // Each selection must take both processors of a core, as otherwise the leftover
+        // sibling cannot be used by the other selections.
+        let assignments = resolve_jointly(
+            &candidates(),
+            &[2, 2, 2],
+            &[
+                (0, 1, AntiAffinity::DistinctCores),
+                (1, 2, AntiAffinity::DistinctCores),
+                (0, 2, AntiAffinity::DistinctCores),
+            ],
+            &mut rng(),
+            domain_of,
+        )
+        .unwrap();
+
+        for assignment in &assignments {
+            let cores = assignment.iter().map(|p| p.id() / 2).unique().count();
+            assert_eq!(cores, 1);
+        }
+
+        assert_eq!(
+            assignments
+                .iter()
+                .flat_map(|a| ids(a))
+                .sorted()
+                .collect_vec(),
+            [0, 1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_returns_none() {
+        // There are only two memory regions for three mutually separated selections.
+        assert!(
+            resolve_jointly(
+                &candidates(),
+                &[1, 1, 1],
+                &[
+                    (0, 1, AntiAffinity::DifferentMemoryRegions),
+                    (1, 2, AntiAffinity::DifferentMemoryRegions),
+                    (0, 2, AntiAffinity::DifferentMemoryRegions),
+                ],
+                &mut rng(),
+                domain_of,
+            )
+            .is_none()
+        );
+
+        // Not enough processors for everyone.
+        assert!(resolve_jointly(&candidates(), &[4, 3], &[], &mut rng(), domain_of).is_none());
+    }
+}
@@ -193,24 +193,83 @@
 //! #    thread.join().unwrap();
 //! # }
 //! ```
+//!
+//! # Detecting stray threads
+//!
+//! Keeping some processors free of other work (e.g. for latency-sensitive threads) is only as
+//! reliable as every thread in the process. On Linux, a [`ProcessorWatchdog`] periodically checks
+//! that no thread of the process is executing outside an allowed processor set and reports any
+//! thread it observes there, such as threads spawned by third-party libraries.
+//!
+//! # Sharing processors between subsystems
+//!
+//! When multiple independent subsystems of the same process (e.g. different libraries) each
+//! select processors for their own pinned threads, they may end up on the same processors. A
+//! subsystem can [reserve][ProcessorSet::reserve] the processors it uses in a process-wide
+//! registry and the others can [exclude the reserved processors][ProcessorSetBuilder::except_reserved]
+//! when selecting their own.
+//!
+//! # Spin-waiting
+//!
+//! How a thread should wait in a spin loop depends on the hardware it executes on - whether its
+//! physical core is shared with other processors, what kind of processor it is and whether the
+//! process is constrained by a resource quota. [`spin_hint_for()`] chooses a [`SpinHint`] for
+//! the threads executing on a processor set.
+//!
+//! # Persisting processor sets
+//!
+//! With the `serde` Cargo feature enabled, `Processor` and `ProcessorSet` can be serialized
+//! (e.g. into a configuration file) together with the memory region and efficiency class of each
+//! processor. When deserialized, the stored processors are reconciled against the live hardware
+//! topology and loading fails if any of them is no longer available to the current process or
+//! its memory region or efficiency class has changed.
 
 mod clients;
+mod cpulist_error;
 mod hardware_info;
+mod hardware_poller;
 mod hardware_tracker;
+mod joint_selection;
+mod memory_region_process;
+mod pinned_thread_builder;
 mod primitive_types;
 mod processor;
+mod processor_feature;
+mod processor_reservation;
+mod processor_selection_error;
 mod processor_set;
 mod processor_set_builder;
+mod processor_watchdog;
 mod resource_quota;
+#[cfg(feature = "serde")]
+mod serialization;
+mod spin_hint;
+mod thread_pin_guard;
+mod thread_priority;
+mod watched_processor_set;
 
 pub(crate) use clients::*;
+pub use cpulist_error::*;
 pub use hardware_info::*;
+pub use hardware_poller::*;
 pub use hardware_tracker::*;
+pub use joint_selection::AntiAffinity;
+pub use memory_region_process::*;
+pub use pinned_thread_builder::PinnedThreadBuilder;
 pub use primitive_types::*;
 pub use processor::*;
+pub use processor_feature::ProcessorFeature;
+pub(crate) use processor_feature::ProcessorFeatures;
+pub use processor_reservation::ProcessorReservation;
+pub use processor_selection_error::*;
 pub use processor_set::*;
 pub use processor_set_builder::*;
+pub use processor_watchdog::*;
 pub use resource_quota::*;
+pub use spin_hint::{SpinHint, spin_hint_for};
+pub use thread_pin_guard::ThreadPinGuard;
+pub use thread_priority::ThreadPriority;
+pub use watched_processor_set::{ProcessorSetChange, WatchedProcessorSet};
 
 // No documented public API but we have benchmarks that reach in via undocumented private API.
 #[doc(hidden)]
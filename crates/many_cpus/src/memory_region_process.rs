@@ -0,0 +1,63 @@
+use std::process::Child;
+
+use crate::{MemoryRegionId, ProcessorSet};
+
+/// A child process spawned via [`ProcessorSet::spawn_processes_per_memory_region()`], pinned to
+/// the processors of one memory region.
+#[derive(Debug)]
+pub struct MemoryRegionProcess {
+    memory_region_id: MemoryRegionId,
+    processors: ProcessorSet,
+    child: Child,
+}
+
+impl MemoryRegionProcess {
+    #[must_use]
+    pub(crate) fn new(
+        memory_region_id: MemoryRegionId,
+        processors: ProcessorSet,
+        child: Child,
+    ) -> Self {
+        Self {
+            memory_region_id,
+            processors,
+            child,
+        }
+    }
+
+    /// The memory region owned by the process.
+    #[must_use]
+    #[inline]
+    pub fn memory_region_id(&self) -> MemoryRegionId {
+        self.memory_region_id
+    }
+
+    /// The processors the process is pinned to, which are all in the memory region owned by
+    /// the process.
+    #[must_use]
+    #[inline]
+    pub fn processors(&self) -> &ProcessorSet {
+        &self.processors
+    }
+
+    /// The handle of the child process.
+    #[must_use]
+    #[inline]
+    pub fn child(&self) -> &Child {
+        &self.child
+    }
+
+    /// The handle of the child process, e.g. to wait for it to exit or to kill it.
+    #[must_use]
+    #[inline]
+    pub fn child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Consumes the value, returning the handle of the child process.
+    #[must_use]
+    #[inline]
+    pub fn into_child(self) -> Child {
+        self.child
+    }
+}
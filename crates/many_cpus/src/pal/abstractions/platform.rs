@@ -1,8 +1,8 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, io, process::Command};
 
 use nonempty::NonEmpty;
 
-use crate::{MemoryRegionId, ProcessorId, pal::ProcessorFacade};
+use crate::{MemoryRegionId, ProcessorId, ThreadPlacement, ThreadPriority, pal::ProcessorFacade};
 
 pub(crate) trait Platform: Debug + Send + Sync + 'static {
     /// Returns all processors available to the current process.
@@ -18,6 +18,16 @@ pub(crate) trait Platform: Debug + Send + Sync + 'static {
     where
         P: AsRef<ProcessorFacade>;
 
+    /// Configures the command so that the process it spawns is only allowed to execute on the
+    /// given processors and, if they are all in the same memory region, prefers to allocate
+    /// memory in that memory region.
+    ///
+    /// Returns an error if the platform does not support configuring this before the process
+    /// is spawned.
+    fn pin_command_to<P>(&self, command: &mut Command, processors: &NonEmpty<P>) -> io::Result<()>
+    where
+        P: AsRef<ProcessorFacade>;
+
     /// Gets the ID of the processor currently executing this thread.
     #[must_use]
     fn current_processor_id(&self) -> ProcessorId;
@@ -57,6 +67,14 @@ pub(crate) trait Platform: Debug + Send + Sync + 'static {
     #[must_use]
     fn max_processor_time(&self) -> f64;
 
+    /// Gets the scheduler weight of the process relative to the default weight of the platform
+    /// (so 1.0 is the default weight), or `None` if the platform does not assign a weight.
+    ///
+    /// The weight is a soft share that only applies when processors are contended, unlike the
+    /// hard cap of `max_processor_time()`.
+    #[must_use]
+    fn processor_weight(&self) -> Option<f64>;
+
     /// Gets the total number of active processors on the system, including ones that are not
     /// necessarily available to the current process (if any such are known).
     ///
@@ -64,4 +82,108 @@ pub(crate) trait Platform: Debug + Send + Sync + 'static {
     /// speak in terms of system-scoped data, we occasionally need to access such values.
     #[must_use]
     fn active_processor_count(&self) -> usize;
+
+    /// Gets the relative distance between two memory regions, using the conventions of the ACPI
+    /// System Locality Information Table: the distance from a memory region to itself is
+    /// `LOCAL_MEMORY_REGION_DISTANCE` and greater values mean slower access.
+    ///
+    /// If the platform does not report distances, the distance between different memory regions
+    /// is `REMOTE_MEMORY_REGION_DISTANCE`.
+    ///
+    /// This value is a constant and will not change over time.
+    #[must_use]
+    fn memory_region_distance(&self, from: MemoryRegionId, to: MemoryRegionId) -> u32;
+
+    /// Identifies the physical processor core that a processor belongs to, by the lowest ID of
+    /// any processor in the same core. Processors that share a core via simultaneous
+    /// multithreading (SMT, e.g. hyper-threading) therefore have the same core ID.
+    ///
+    /// If the platform does not report the physical cores, every processor is its own core.
+    ///
+    /// This value is a constant and will not change over time.
+    #[must_use]
+    fn physical_core_id(&self, processor_id: ProcessorId) -> ProcessorId;
+
+    /// Identifies the last level cache (L3) domain that a processor belongs to, by the lowest ID
+    /// of any processor sharing the same L3 cache. Processors in the same domain (e.g. an AMD
+    /// CCX) therefore have the same domain ID.
+    ///
+    /// If the platform does not report the L3 caches, every processor is its own domain.
+    ///
+    /// This value is a constant and will not change over time.
+    #[must_use]
+    fn l3_cache_domain_id(&self, processor_id: ProcessorId) -> ProcessorId;
+
+    /// Returns the processor that each thread of the current process last executed on.
+    ///
+    /// Threads may start and exit while the threads are enumerated, so the result is only
+    /// a snapshot. If the platform does not report this information, returns an empty list.
+    #[must_use]
+    fn current_process_threads(&self) -> Vec<ThreadPlacement>;
+
+    /// Sets the scheduling priority of the current thread.
+    ///
+    /// Returns an error if the operating system refuses, e.g. due to insufficient privileges.
+    fn set_current_thread_priority(&self, priority: ThreadPriority) -> io::Result<()>;
+
+    /// Returns the number of device interrupts (e.g. from network adapters or storage
+    /// controllers) that each processor has handled since the system started.
+    ///
+    /// The counts change over time, so every call reads them again. Processors that the platform
+    /// does not report on are not listed. If the platform does not report interrupt counts at
+    /// all, returns an empty list.
+    #[must_use]
+    fn interrupt_counts(&self) -> Vec<(ProcessorId, u64)>;
+
+    /// Re-evaluates the constraints on the processors available to the current process that may
+    /// change while the process is running (e.g. the cgroup cpuset on Linux), so future calls to
+    /// `get_all_processors()` reflect any changes.
+    ///
+    /// Until this is first called, the constraints as of when the processor information was
+    /// loaded apply.
+    fn refresh_available_processors(&self);
+
+    /// Gets the IDs of the processors that are currently online on the system, including ones
+    /// that are not available to the current process, sorted ascending.
+    ///
+    /// Processors may be brought online or taken offline at runtime, so every call reads this
+    /// again. If the platform does not report this, returns an empty list.
+    #[must_use]
+    fn online_processor_ids(&self) -> Vec<ProcessorId>;
+
+    /// Gets the IDs of the memory regions that are currently present on the system, sorted
+    /// ascending.
+    ///
+    /// Memory regions may appear or disappear at runtime (e.g. memory hot-add in virtual
+    /// machines), so every call reads this again. If the platform does not report this, returns
+    /// an empty list.
+    #[must_use]
+    fn online_memory_region_ids(&self) -> Vec<MemoryRegionId>;
+
+    /// Gets the memory region that the PCI device with the given address (e.g. `0000:3b:00.0`)
+    /// is attached to.
+    ///
+    /// Returns `None` if there is no such device or the platform does not report the memory
+    /// region of the device.
+    #[must_use]
+    fn pci_device_memory_region_id(&self, pci_address: &str) -> Option<MemoryRegionId>;
+}
+
+/// The distance from a memory region to itself, as defined by the ACPI specification.
+pub(crate) const LOCAL_MEMORY_REGION_DISTANCE: u32 = 10;
+
+/// The distance between different memory regions when the platform does not tell us the real
+/// distance, as defined by the ACPI specification for systems without distance information.
+pub(crate) const REMOTE_MEMORY_REGION_DISTANCE: u32 = 20;
+
+/// The distance between two memory regions if the platform does not know any better.
+pub(crate) const fn default_memory_region_distance(
+    from: MemoryRegionId,
+    to: MemoryRegionId,
+) -> u32 {
+    if from == to {
+        LOCAL_MEMORY_REGION_DISTANCE
+    } else {
+        REMOTE_MEMORY_REGION_DISTANCE
+    }
 }
@@ -3,7 +3,7 @@ use std::{
     hash::Hash,
 };
 
-use crate::{EfficiencyClass, MemoryRegionId, ProcessorId};
+use crate::{EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeatures, ProcessorId};
 
 pub(crate) trait AbstractProcessor:
     Clone + Copy + Debug + Display + Eq + Hash + PartialEq + Send
@@ -11,4 +11,23 @@ pub(crate) trait AbstractProcessor:
     fn id(&self) -> ProcessorId;
     fn memory_region_id(&self) -> MemoryRegionId;
     fn efficiency_class(&self) -> EfficiencyClass;
+
+    /// The physical package (socket) the processor is in. Platforms that do not expose packages
+    /// report all processors as being in package 0.
+    fn package_id(&self) -> PackageId;
+
+    /// The optional instruction set features the processor supports.
+    fn features(&self) -> ProcessorFeatures;
+
+    /// The physical core the processor is in, by the lowest ID of any processor in the core.
+    fn physical_core_id(&self) -> ProcessorId;
+
+    /// The L3 cache domain the processor is in, by the lowest ID of any processor in the domain.
+    fn l3_cache_domain_id(&self) -> ProcessorId;
+
+    /// The base (nominal) frequency of the processor in megahertz, if known.
+    fn base_frequency_mhz(&self) -> Option<u32>;
+
+    /// The maximum frequency of the processor in megahertz, including boost, if known.
+    fn max_frequency_mhz(&self) -> Option<u32>;
 }
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, io, process::Command};
 
 #[cfg(test)]
 use std::sync::Arc;
@@ -47,6 +47,21 @@ impl Platform for PlatformFacade {
         }
     }
 
+    fn pin_command_to<P>(
+        &self,
+        command: &mut Command,
+        processors: &nonempty::NonEmpty<P>,
+    ) -> io::Result<()>
+    where
+        P: AsRef<ProcessorFacade>,
+    {
+        match self {
+            Self::Real(p) => p.pin_command_to(command, processors),
+            #[cfg(test)]
+            Self::Mock(p) => p.pin_command_to(command, processors),
+        }
+    }
+
     fn current_processor_id(&self) -> crate::ProcessorId {
         match self {
             Self::Real(p) => p.current_processor_id(),
@@ -87,6 +102,14 @@ impl Platform for PlatformFacade {
         }
     }
 
+    fn processor_weight(&self) -> Option<f64> {
+        match self {
+            Self::Real(p) => p.processor_weight(),
+            #[cfg(test)]
+            Self::Mock(p) => p.processor_weight(),
+        }
+    }
+
     fn active_processor_count(&self) -> usize {
         match self {
             Self::Real(p) => p.active_processor_count(),
@@ -94,6 +117,90 @@ impl Platform for PlatformFacade {
             Self::Mock(p) => p.active_processor_count(),
         }
     }
+
+    fn memory_region_distance(
+        &self,
+        from: crate::MemoryRegionId,
+        to: crate::MemoryRegionId,
+    ) -> u32 {
+        match self {
+            Self::Real(p) => p.memory_region_distance(from, to),
+            #[cfg(test)]
+            Self::Mock(p) => p.memory_region_distance(from, to),
+        }
+    }
+
+    fn physical_core_id(&self, processor_id: crate::ProcessorId) -> crate::ProcessorId {
+        match self {
+            Self::Real(p) => p.physical_core_id(processor_id),
+            #[cfg(test)]
+            Self::Mock(p) => p.physical_core_id(processor_id),
+        }
+    }
+
+    fn l3_cache_domain_id(&self, processor_id: crate::ProcessorId) -> crate::ProcessorId {
+        match self {
+            Self::Real(p) => p.l3_cache_domain_id(processor_id),
+            #[cfg(test)]
+            Self::Mock(p) => p.l3_cache_domain_id(processor_id),
+        }
+    }
+
+    fn current_process_threads(&self) -> Vec<crate::ThreadPlacement> {
+        match self {
+            Self::Real(p) => p.current_process_threads(),
+            #[cfg(test)]
+            Self::Mock(p) => p.current_process_threads(),
+        }
+    }
+
+    fn set_current_thread_priority(&self, priority: crate::ThreadPriority) -> io::Result<()> {
+        match self {
+            Self::Real(p) => p.set_current_thread_priority(priority),
+            #[cfg(test)]
+            Self::Mock(p) => p.set_current_thread_priority(priority),
+        }
+    }
+
+    fn interrupt_counts(&self) -> Vec<(crate::ProcessorId, u64)> {
+        match self {
+            Self::Real(p) => p.interrupt_counts(),
+            #[cfg(test)]
+            Self::Mock(p) => p.interrupt_counts(),
+        }
+    }
+
+    fn refresh_available_processors(&self) {
+        match self {
+            Self::Real(p) => p.refresh_available_processors(),
+            #[cfg(test)]
+            Self::Mock(p) => p.refresh_available_processors(),
+        }
+    }
+
+    fn online_processor_ids(&self) -> Vec<crate::ProcessorId> {
+        match self {
+            Self::Real(p) => p.online_processor_ids(),
+            #[cfg(test)]
+            Self::Mock(p) => p.online_processor_ids(),
+        }
+    }
+
+    fn online_memory_region_ids(&self) -> Vec<crate::MemoryRegionId> {
+        match self {
+            Self::Real(p) => p.online_memory_region_ids(),
+            #[cfg(test)]
+            Self::Mock(p) => p.online_memory_region_ids(),
+        }
+    }
+
+    fn pci_device_memory_region_id(&self, pci_address: &str) -> Option<crate::MemoryRegionId> {
+        match self {
+            Self::Real(p) => p.pci_device_memory_region_id(pci_address),
+            #[cfg(test)]
+            Self::Mock(p) => p.pci_device_memory_region_id(pci_address),
+        }
+    }
 }
 
 impl From<&'static BuildTargetPlatform> for PlatformFacade {
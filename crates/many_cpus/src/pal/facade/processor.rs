@@ -55,6 +55,54 @@ impl AbstractProcessor for ProcessorFacade {
             Self::Fake(p) => p.efficiency_class(),
         }
     }
+
+    fn package_id(&self) -> crate::PackageId {
+        match self {
+            Self::Real(p) => p.package_id(),
+            #[cfg(test)]
+            Self::Fake(p) => p.package_id(),
+        }
+    }
+
+    fn features(&self) -> crate::ProcessorFeatures {
+        match self {
+            Self::Real(p) => p.features(),
+            #[cfg(test)]
+            Self::Fake(p) => p.features(),
+        }
+    }
+
+    fn physical_core_id(&self) -> crate::ProcessorId {
+        match self {
+            Self::Real(p) => p.physical_core_id(),
+            #[cfg(test)]
+            Self::Fake(p) => p.physical_core_id(),
+        }
+    }
+
+    fn l3_cache_domain_id(&self) -> crate::ProcessorId {
+        match self {
+            Self::Real(p) => p.l3_cache_domain_id(),
+            #[cfg(test)]
+            Self::Fake(p) => p.l3_cache_domain_id(),
+        }
+    }
+
+    fn base_frequency_mhz(&self) -> Option<u32> {
+        match self {
+            Self::Real(p) => p.base_frequency_mhz(),
+            #[cfg(test)]
+            Self::Fake(p) => p.base_frequency_mhz(),
+        }
+    }
+
+    fn max_frequency_mhz(&self) -> Option<u32> {
+        match self {
+            Self::Real(p) => p.max_frequency_mhz(),
+            #[cfg(test)]
+            Self::Fake(p) => p.max_frequency_mhz(),
+        }
+    }
 }
 
 impl From<ProcessorImpl> for ProcessorFacade {
@@ -1,6 +1,6 @@
 use std::{fmt::Debug, io};
 
-use libc::cpu_set_t;
+use libc::{c_ulong, cpu_set_t};
 
 /// Bindings for FFI calls into external libraries (either provided by operating system or not).
 ///
@@ -14,4 +14,18 @@ pub(crate) trait Bindings: Debug + Send + Sync + 'static {
     fn sched_getaffinity_current(&self) -> Result<cpu_set_t, io::Error>;
 
     fn sched_getcpu(&self) -> i32;
+
+    // setpriority() for the current thread
+    fn setpriority_current(&self, nice: i32) -> Result<(), io::Error>;
+
+    // sched_setscheduler() for the current thread
+    fn sched_setscheduler_current(&self, policy: i32, priority: i32) -> Result<(), io::Error>;
+
+    // set_mempolicy() for the current thread, with a node mask of a single c_ulong
+    fn set_mempolicy_current(
+        &self,
+        mode: i32,
+        node_mask: c_ulong,
+        max_node: c_ulong,
+    ) -> Result<(), io::Error>;
 }
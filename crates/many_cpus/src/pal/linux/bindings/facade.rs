@@ -3,7 +3,7 @@ use std::{fmt::Debug, io};
 #[cfg(test)]
 use std::sync::Arc;
 
-use libc::cpu_set_t;
+use libc::{c_ulong, cpu_set_t};
 
 use crate::pal::linux::{Bindings, BuildTargetBindings};
 
@@ -54,6 +54,35 @@ impl Bindings for BindingsFacade {
             Self::Mock(mock) => mock.sched_getaffinity_current(),
         }
     }
+
+    fn setpriority_current(&self, nice: i32) -> Result<(), io::Error> {
+        match self {
+            Self::Real(bindings) => bindings.setpriority_current(nice),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.setpriority_current(nice),
+        }
+    }
+
+    fn sched_setscheduler_current(&self, policy: i32, priority: i32) -> Result<(), io::Error> {
+        match self {
+            Self::Real(bindings) => bindings.sched_setscheduler_current(policy, priority),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.sched_setscheduler_current(policy, priority),
+        }
+    }
+
+    fn set_mempolicy_current(
+        &self,
+        mode: i32,
+        node_mask: c_ulong,
+        max_node: c_ulong,
+    ) -> Result<(), io::Error> {
+        match self {
+            Self::Real(bindings) => bindings.set_mempolicy_current(mode, node_mask, max_node),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.set_mempolicy_current(mode, node_mask, max_node),
+        }
+    }
 }
 
 impl Debug for BindingsFacade {
@@ -1,6 +1,6 @@
-use std::{fmt::Debug, io, mem};
+use std::{fmt::Debug, io, mem, ptr};
 
-use libc::cpu_set_t;
+use libc::{c_ulong, cpu_set_t};
 
 use crate::pal::linux::Bindings;
 
@@ -43,4 +43,58 @@ impl Bindings for BuildTargetBindings {
             Err(io::Error::last_os_error())
         }
     }
+
+    fn setpriority_current(&self, nice: i32) -> Result<(), io::Error> {
+        // On Linux, the nice value is a property of the thread and 0 means the current thread.
+        // SAFETY: No safety requirements beyond passing valid arguments.
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    fn sched_setscheduler_current(&self, policy: i32, priority: i32) -> Result<(), io::Error> {
+        // The structure has additional fields on some platforms, all of which may be zero.
+        // SAFETY: All zeroes is a valid sched_param.
+        let mut param: libc::sched_param = unsafe { mem::zeroed() };
+        param.sched_priority = priority;
+
+        // 0 means current thread.
+        // SAFETY: No safety requirements beyond passing valid arguments.
+        let result = unsafe { libc::sched_setscheduler(0, policy, &raw const param) };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    fn set_mempolicy_current(
+        &self,
+        mode: i32,
+        node_mask: c_ulong,
+        max_node: c_ulong,
+    ) -> Result<(), io::Error> {
+        // The memory policy is a property of the thread, so this only affects the current thread.
+        // SAFETY: The node mask is a valid c_ulong that outlives the call and the caller passes
+        // a max node value that tells the operating system not to read past its end.
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_set_mempolicy,
+                mode,
+                ptr::from_ref(&node_mask),
+                max_node,
+            )
+        };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
 }
@@ -25,17 +25,113 @@ pub(crate) trait Filesystem: Debug + Send + Sync + 'static {
     /// This is a cpulist format file ("0,1,2-4,5-10:2" style list).
     fn get_numa_node_possible_contents(&self) -> Option<String>;
 
+    /// Get the contents of the /sys/devices/system/node/online file or `None` if it does not
+    /// exist.
+    ///
+    /// This lists the NUMA nodes that are currently online, which may change at runtime.
+    ///
+    /// This is a cpulist format file ("0,1,2-4,5-10:2" style list).
+    fn get_numa_node_online_contents(&self) -> Option<String>;
+
     /// Get the contents of the /sys/devices/system/node/node{}/cpulist file.
     ///
     /// This is a cpulist format file ("0,1,2-4,5-10:2" style list).
     fn get_numa_node_cpulist_contents(&self, node_index: u32) -> String;
 
+    /// Get the contents of the /sys/devices/system/node/node{}/distance file or `None` if it does
+    /// not exist.
+    ///
+    /// This is a single line of space-separated distances from the node to every online NUMA
+    /// node, in ascending order of the NUMA node index (+ newline).
+    fn get_numa_node_distance_contents(&self, node_index: u32) -> Option<String>;
+
     /// Gets the contents of the /sys/devices/system/cpu/cpu{}/online file.
     ///
     /// This is a single line file with either 0 or 1 as content (+ newline).
     /// This file may be absent on some Linux flavors, in which case we assume every CPU is online.
     fn get_cpu_online_contents(&self, cpu_index: u32) -> Option<String>;
 
+    /// Gets the contents of the /sys/devices/system/cpu/online file or `None` if it does not
+    /// exist.
+    ///
+    /// This lists the processors that are currently online, which may change at runtime.
+    ///
+    /// This is a cpulist format file ("0,1,2-4,5-10:2" style list).
+    fn get_cpu_online_list_contents(&self) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/system/cpu/cpu{}/cpufreq/base_frequency file or
+    /// `None` if it does not exist.
+    ///
+    /// This is a single line file with the base frequency in kHz (+ newline). Only some
+    /// frequency scaling drivers (e.g. `intel_pstate`) provide this file.
+    fn get_cpu_base_frequency_contents(&self, cpu_index: u32) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/system/cpu/cpu{}/cpufreq/cpuinfo_max_freq file or
+    /// `None` if it does not exist.
+    ///
+    /// This is a single line file with the maximum frequency in kHz (+ newline). The file is
+    /// absent if there is no frequency scaling driver, which is common in virtual machines.
+    fn get_cpu_max_frequency_contents(&self, cpu_index: u32) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/system/cpu/cpu{}/cpu_capacity file or `None` if it
+    /// does not exist.
+    ///
+    /// This is a single line file with the capacity of the processor relative to the most capable
+    /// processor on the system, which has a capacity of 1024 (+ newline). The file is only present
+    /// on systems with heterogeneous processors (e.g. ARM big.LITTLE).
+    fn get_cpu_capacity_contents(&self, cpu_index: u32) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/cpu_atom/cpus file or `None` if it does not exist.
+    ///
+    /// This is a cpulist of the efficiency cores on Intel hybrid processors, which have their own
+    /// performance monitoring unit. The file is absent on other processors.
+    fn get_hybrid_efficiency_cpus_contents(&self) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/system/cpu/cpu{}/topology/thread_siblings_list file
+    /// or `None` if it does not exist.
+    ///
+    /// This lists the processors in the same physical core as the processor, including itself.
+    ///
+    /// This is a cpulist format file ("0,1,2-4,5-10:2" style list).
+    fn get_cpu_thread_siblings_list_contents(&self, cpu_index: u32) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/system/cpu/cpu{}/cache/index{}/level file or `None`
+    /// if it does not exist.
+    ///
+    /// The caches of a processor are numbered from zero without gaps, in no guaranteed order of
+    /// level. This is a single line file with the cache level (e.g. 3) as content (+ newline).
+    fn get_cpu_cache_level_contents(&self, cpu_index: u32, cache_index: u32) -> Option<String>;
+
+    /// Gets the contents of the /sys/devices/system/cpu/cpu{}/cache/index{}/shared_cpu_list file
+    /// or `None` if it does not exist.
+    ///
+    /// This lists the processors that share the cache with the processor, including itself.
+    ///
+    /// This is a cpulist format file ("0,1,2-4,5-10:2" style list).
+    fn get_cpu_cache_shared_cpu_list_contents(
+        &self,
+        cpu_index: u32,
+        cache_index: u32,
+    ) -> Option<String>;
+
+    /// Gets the IDs of all the threads of the current process, from the entries of the
+    /// /proc/self/task directory.
+    fn get_proc_self_task_ids(&self) -> Vec<u32>;
+
+    /// Gets the contents of the /proc/self/task/{}/stat file or `None` if it does not exist
+    /// (e.g. because the thread has exited).
+    ///
+    /// This is a single line of space-separated fields, with the second field being the name of
+    /// the thread in parentheses (which may itself contain spaces and parentheses).
+    fn get_proc_self_task_stat_contents(&self, task_id: u32) -> Option<String>;
+
+    /// Gets the contents of the /proc/interrupts file or `None` if it does not exist.
+    ///
+    /// The first line lists the online processors as "CPU{}" columns. Every other line starts
+    /// with an interrupt label (numeric for device interrupts) followed by a colon, the number of
+    /// interrupts each processor has handled and a free-form description.
+    fn get_proc_interrupts_contents(&self) -> Option<String>;
+
     /// Gets the contents of the /prod/{pid}/status file for the current process.
     ///
     /// This is a plaintext file with "key:     value" pairs.
@@ -52,4 +148,23 @@ pub(crate) trait Filesystem: Debug + Send + Sync + 'static {
 
     /// Contents of `/sys/fs/cgroup/{name}/cpu.max`
     fn get_v2_cgroup_cpu_quota_and_period(&self, cgroup_name: &str) -> Option<String>;
+
+    /// Contents of `/sys/fs/cgroup/cpu/{name}/cpu.shares`
+    fn get_v1_cgroup_cpu_shares(&self, cgroup_name: &str) -> Option<String>;
+
+    /// Contents of `/sys/fs/cgroup/{name}/cpu.weight`
+    fn get_v2_cgroup_cpu_weight(&self, cgroup_name: &str) -> Option<String>;
+
+    /// Contents of `/sys/fs/cgroup/{name}/cpuset.cpus.effective`
+    ///
+    /// This is a cpulist of the processors the cgroup is allowed to use, which may change at
+    /// runtime (e.g. when a container orchestrator resizes the container).
+    fn get_v2_cgroup_cpuset_cpus_effective(&self, cgroup_name: &str) -> Option<String>;
+
+    /// Gets the contents of the /sys/bus/pci/devices/{address}/numa_node file or `None` if it
+    /// does not exist.
+    ///
+    /// This is a single line file with the NUMA node the PCI device is attached to, or -1 if
+    /// the device is not associated with a NUMA node (+ newline).
+    fn get_pci_device_numa_node_contents(&self, pci_address: &str) -> Option<String>;
 }
@@ -45,6 +45,14 @@ impl Filesystem for FilesystemFacade {
         }
     }
 
+    fn get_numa_node_distance_contents(&self, node_index: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_numa_node_distance_contents(node_index),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_numa_node_distance_contents(node_index),
+        }
+    }
+
     fn get_cpu_online_contents(&self, cpu_index: u32) -> Option<String> {
         match self {
             Self::Real(filesystem) => filesystem.get_cpu_online_contents(cpu_index),
@@ -53,6 +61,78 @@ impl Filesystem for FilesystemFacade {
         }
     }
 
+    fn get_cpu_online_list_contents(&self) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_cpu_online_list_contents(),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_online_list_contents(),
+        }
+    }
+
+    fn get_cpu_base_frequency_contents(&self, cpu_index: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_cpu_base_frequency_contents(cpu_index),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_base_frequency_contents(cpu_index),
+        }
+    }
+
+    fn get_cpu_max_frequency_contents(&self, cpu_index: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_cpu_max_frequency_contents(cpu_index),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_max_frequency_contents(cpu_index),
+        }
+    }
+
+    fn get_cpu_capacity_contents(&self, cpu_index: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_cpu_capacity_contents(cpu_index),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_capacity_contents(cpu_index),
+        }
+    }
+
+    fn get_hybrid_efficiency_cpus_contents(&self) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_hybrid_efficiency_cpus_contents(),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_hybrid_efficiency_cpus_contents(),
+        }
+    }
+
+    fn get_cpu_thread_siblings_list_contents(&self, cpu_index: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_cpu_thread_siblings_list_contents(cpu_index),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_thread_siblings_list_contents(cpu_index),
+        }
+    }
+
+    fn get_cpu_cache_level_contents(&self, cpu_index: u32, cache_index: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => {
+                filesystem.get_cpu_cache_level_contents(cpu_index, cache_index)
+            }
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_cache_level_contents(cpu_index, cache_index),
+        }
+    }
+
+    fn get_cpu_cache_shared_cpu_list_contents(
+        &self,
+        cpu_index: u32,
+        cache_index: u32,
+    ) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => {
+                filesystem.get_cpu_cache_shared_cpu_list_contents(cpu_index, cache_index)
+            }
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_cpu_cache_shared_cpu_list_contents(cpu_index, cache_index),
+        }
+    }
+
     fn get_numa_node_possible_contents(&self) -> Option<String> {
         match self {
             Self::Real(filesystem) => filesystem.get_numa_node_possible_contents(),
@@ -61,6 +141,38 @@ impl Filesystem for FilesystemFacade {
         }
     }
 
+    fn get_numa_node_online_contents(&self) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_numa_node_online_contents(),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_numa_node_online_contents(),
+        }
+    }
+
+    fn get_proc_self_task_ids(&self) -> Vec<u32> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_proc_self_task_ids(),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_proc_self_task_ids(),
+        }
+    }
+
+    fn get_proc_self_task_stat_contents(&self, task_id: u32) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_proc_self_task_stat_contents(task_id),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_proc_self_task_stat_contents(task_id),
+        }
+    }
+
+    fn get_proc_interrupts_contents(&self) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_proc_interrupts_contents(),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_proc_interrupts_contents(),
+        }
+    }
+
     fn get_proc_self_status_contents(&self) -> String {
         match self {
             Self::Real(filesystem) => filesystem.get_proc_self_status_contents(),
@@ -100,6 +212,38 @@ impl Filesystem for FilesystemFacade {
             Self::Mock(mock) => mock.get_v2_cgroup_cpu_quota_and_period(cgroup_name),
         }
     }
+
+    fn get_v1_cgroup_cpu_shares(&self, cgroup_name: &str) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_v1_cgroup_cpu_shares(cgroup_name),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_v1_cgroup_cpu_shares(cgroup_name),
+        }
+    }
+
+    fn get_v2_cgroup_cpu_weight(&self, cgroup_name: &str) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_v2_cgroup_cpu_weight(cgroup_name),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_v2_cgroup_cpu_weight(cgroup_name),
+        }
+    }
+
+    fn get_v2_cgroup_cpuset_cpus_effective(&self, cgroup_name: &str) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_v2_cgroup_cpuset_cpus_effective(cgroup_name),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_v2_cgroup_cpuset_cpus_effective(cgroup_name),
+        }
+    }
+
+    fn get_pci_device_numa_node_contents(&self, pci_address: &str) -> Option<String> {
+        match self {
+            Self::Real(filesystem) => filesystem.get_pci_device_numa_node_contents(pci_address),
+            #[cfg(test)]
+            Self::Mock(mock) => mock.get_pci_device_numa_node_contents(pci_address),
+        }
+    }
 }
 
 impl Debug for FilesystemFacade {
@@ -19,15 +19,95 @@ impl Filesystem for BuildTargetFilesystem {
         fs::read_to_string("/sys/devices/system/node/possible").ok()
     }
 
+    fn get_numa_node_online_contents(&self) -> Option<String> {
+        fs::read_to_string("/sys/devices/system/node/online").ok()
+    }
+
     fn get_numa_node_cpulist_contents(&self, node_index: u32) -> String {
         fs::read_to_string(format!("/sys/devices/system/node/node{node_index}/cpulist",))
             .expect("failed to read NUMA node cpulist - cannot continue execution")
     }
 
+    fn get_numa_node_distance_contents(&self, node_index: u32) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/node/node{node_index}/distance"
+        ))
+        .ok()
+    }
+
     fn get_cpu_online_contents(&self, cpu_index: u32) -> Option<String> {
         fs::read_to_string(format!("/sys/devices/system/cpu/cpu{cpu_index}/online")).ok()
     }
 
+    fn get_cpu_online_list_contents(&self) -> Option<String> {
+        fs::read_to_string("/sys/devices/system/cpu/online").ok()
+    }
+
+    fn get_cpu_base_frequency_contents(&self, cpu_index: u32) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{cpu_index}/cpufreq/base_frequency"
+        ))
+        .ok()
+    }
+
+    fn get_cpu_max_frequency_contents(&self, cpu_index: u32) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{cpu_index}/cpufreq/cpuinfo_max_freq"
+        ))
+        .ok()
+    }
+
+    fn get_cpu_capacity_contents(&self, cpu_index: u32) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{cpu_index}/cpu_capacity"
+        ))
+        .ok()
+    }
+
+    fn get_hybrid_efficiency_cpus_contents(&self) -> Option<String> {
+        fs::read_to_string("/sys/devices/cpu_atom/cpus").ok()
+    }
+
+    fn get_cpu_thread_siblings_list_contents(&self, cpu_index: u32) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{cpu_index}/topology/thread_siblings_list"
+        ))
+        .ok()
+    }
+
+    fn get_cpu_cache_level_contents(&self, cpu_index: u32, cache_index: u32) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{cpu_index}/cache/index{cache_index}/level"
+        ))
+        .ok()
+    }
+
+    fn get_cpu_cache_shared_cpu_list_contents(
+        &self,
+        cpu_index: u32,
+        cache_index: u32,
+    ) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{cpu_index}/cache/index{cache_index}/shared_cpu_list"
+        ))
+        .ok()
+    }
+
+    fn get_proc_self_task_ids(&self) -> Vec<u32> {
+        fs::read_dir("/proc/self/task")
+            .expect("failed to read /proc/self/task - cannot continue execution")
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect()
+    }
+
+    fn get_proc_self_task_stat_contents(&self, task_id: u32) -> Option<String> {
+        fs::read_to_string(format!("/proc/self/task/{task_id}/stat")).ok()
+    }
+
+    fn get_proc_interrupts_contents(&self) -> Option<String> {
+        fs::read_to_string("/proc/interrupts").ok()
+    }
+
     fn get_proc_self_status_contents(&self) -> String {
         fs::read_to_string("/proc/self/status")
             .expect("failed to read /proc/self/status - cannot continue execution")
@@ -51,4 +131,23 @@ impl Filesystem for BuildTargetFilesystem {
     fn get_v2_cgroup_cpu_quota_and_period(&self, cgroup_name: &str) -> Option<String> {
         fs::read_to_string(format!("/sys/fs/cgroup/{cgroup_name}/cpu.max")).ok()
     }
+
+    fn get_v1_cgroup_cpu_shares(&self, cgroup_name: &str) -> Option<String> {
+        fs::read_to_string(format!("/sys/fs/cgroup/cpu/{cgroup_name}/cpu.shares")).ok()
+    }
+
+    fn get_v2_cgroup_cpu_weight(&self, cgroup_name: &str) -> Option<String> {
+        fs::read_to_string(format!("/sys/fs/cgroup/{cgroup_name}/cpu.weight")).ok()
+    }
+
+    fn get_v2_cgroup_cpuset_cpus_effective(&self, cgroup_name: &str) -> Option<String> {
+        fs::read_to_string(format!(
+            "/sys/fs/cgroup/{cgroup_name}/cpuset.cpus.effective"
+        ))
+        .ok()
+    }
+
+    fn get_pci_device_numa_node_contents(&self, pci_address: &str) -> Option<String> {
+        fs::read_to_string(format!("/sys/bus/pci/devices/{pci_address}/numa_node")).ok()
+    }
 }
@@ -1,13 +1,21 @@
-use std::{iter::once, mem, sync::OnceLock};
+use std::{
+    io,
+    iter::once,
+    mem,
+    os::unix::process::CommandExt,
+    process::Command,
+    sync::{OnceLock, RwLock},
+};
 
 use foldhash::HashMap;
 use itertools::Itertools;
 use nonempty::NonEmpty;
 
 use crate::{
-    EfficiencyClass, MemoryRegionId, ProcessorId,
+    EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeature, ProcessorFeatures, ProcessorId,
+    ThreadPlacement, ThreadPriority,
     pal::{
-        Platform, ProcessorFacade, ProcessorImpl,
+        Platform, ProcessorFacade, ProcessorImpl, default_memory_region_distance,
         linux::{Bindings, BindingsFacade, Filesystem, filesystem::FilesystemFacade},
     },
 };
@@ -36,34 +44,78 @@ pub(crate) struct BuildTargetPlatform {
 
     // Only active.
     all_active_processors: OnceLock<NonEmpty<ProcessorFacade>>,
+
+    // Keyed by (from, to). None if the platform does not report distances.
+    memory_region_distances: OnceLock<Option<HashMap<(MemoryRegionId, MemoryRegionId), u32>>>,
+
+    // Keyed by processor ID, including inactive.
+    physical_core_ids: OnceLock<HashMap<ProcessorId, ProcessorId>>,
+
+    // Keyed by processor ID, including inactive.
+    l3_cache_domain_ids: OnceLock<HashMap<ProcessorId, ProcessorId>>,
+
+    // The processors in the cgroup v2 cpuset of the current process as of the last refresh.
+    // None if never refreshed or if the process is not in a cgroup with a cpuset. Unlike the rest
+    // of the processor information, this may change at runtime (e.g. Kubernetes resizing a pod).
+    effective_cpuset: RwLock<Option<Vec<ProcessorId>>>,
 }
 
 impl Platform for BuildTargetPlatform {
     fn get_all_processors(&self) -> NonEmpty<ProcessorFacade> {
-        self.get_active_processors().clone()
+        let active_processors = self.get_active_processors();
+
+        let effective_cpuset = self.effective_cpuset.read().expect(ERR_POISONED_LOCK);
+
+        let Some(effective_cpuset) = effective_cpuset.as_ref() else {
+            return active_processors.clone();
+        };
+
+        // Processors added to the cpuset after the processor information was loaded are unknown
+        // to us, so we can only shrink the set. If nothing we know of remains, we keep using
+        // everything we know of - the operating system will keep our threads where it wants them.
+        NonEmpty::from_vec(
+            active_processors
+                .iter()
+                .filter(|p| effective_cpuset.contains(&p.as_real().id))
+                .cloned()
+                .collect_vec(),
+        )
+        .unwrap_or_else(|| active_processors.clone())
     }
 
     fn pin_current_thread_to<P>(&self, processors: &NonEmpty<P>)
     where
         P: AsRef<ProcessorFacade>,
     {
-        // SAFETY: Zero-initialized cpu_set_t is a valid value.
-        let mut cpu_set: libc::cpu_set_t = unsafe { mem::zeroed() };
-
-        for processor in processors.iter() {
-            // SAFETY: No safety requirements.
-            unsafe {
-                // TODO: This can go out of bounds with giant CPU set (1000+), we would need to use
-                // dynamically allocated CPU sets instead of relying on the fixed-size one in libc.
-                libc::CPU_SET(processor.as_ref().as_real().id as usize, &mut cpu_set);
-            }
-        }
+        let cpu_set = cpu_set_of(processors);
 
         self.bindings
             .sched_setaffinity_current(&cpu_set)
             .expect("failed to configure thread affinity");
     }
 
+    fn pin_command_to<P>(&self, command: &mut Command, processors: &NonEmpty<P>) -> io::Result<()>
+    where
+        P: AsRef<ProcessorFacade>,
+    {
+        let cpu_set = cpu_set_of(processors);
+        let preferred_node_mask = preferred_node_mask_of(processors);
+
+        // Everything is prepared in advance, so the child process only makes system calls.
+        let bindings = self.bindings.clone();
+
+        let pre_exec = move || apply_command_placement(&bindings, &cpu_set, preferred_node_mask);
+
+        // SAFETY: The closure runs in the child process between fork and exec, where only
+        // async-signal-safe operations are allowed. It only makes system calls using data
+        // prepared in advance, without allocating memory or taking locks.
+        unsafe {
+            command.pre_exec(pre_exec);
+        }
+
+        Ok(())
+    }
+
     #[expect(
         clippy::cast_sign_loss,
         reason = "negative processor IDs are not valid regardless, we do not expect to receive them"
@@ -114,9 +166,250 @@ impl Platform for BuildTargetPlatform {
         max_processor_time
     }
 
+    fn processor_weight(&self) -> Option<f64> {
+        let name = self.fs.get_proc_self_cgroup().and_then(parse_cgroup_name)?;
+
+        // The default weight is 100 in cgroups v2 and 1024 in cgroups v1. We prefer v2, like
+        // we do with the processor time quota.
+        let (weight, default_weight) = self
+            .fs
+            .get_v2_cgroup_cpu_weight(&name)
+            .map(|contents| (contents, 100))
+            .or_else(|| {
+                self.fs
+                    .get_v1_cgroup_cpu_shares(&name)
+                    .map(|contents| (contents, 1024))
+            })?;
+
+        let weight = weight.trim().parse::<u32>().ok()?;
+
+        Some(f64::from(weight) / f64::from(default_weight))
+    }
+
     fn active_processor_count(&self) -> usize {
         self.get_active_processors().len()
     }
+
+    fn memory_region_distance(&self, from: MemoryRegionId, to: MemoryRegionId) -> u32 {
+        self.memory_region_distances
+            .get_or_init(|| self.load_memory_region_distances())
+            .as_ref()
+            .and_then(|distances| distances.get(&(from, to)).copied())
+            .unwrap_or_else(|| default_memory_region_distance(from, to))
+    }
+
+    fn physical_core_id(&self, processor_id: ProcessorId) -> ProcessorId {
+        self.physical_core_ids
+            .get_or_init(|| self.load_physical_core_ids())
+            .get(&processor_id)
+            .copied()
+            .unwrap_or(processor_id)
+    }
+
+    fn l3_cache_domain_id(&self, processor_id: ProcessorId) -> ProcessorId {
+        self.l3_cache_domain_ids
+            .get_or_init(|| self.load_l3_cache_domain_ids())
+            .get(&processor_id)
+            .copied()
+            .unwrap_or(processor_id)
+    }
+
+    fn current_process_threads(&self) -> Vec<ThreadPlacement> {
+        self.fs
+            .get_proc_self_task_ids()
+            .into_iter()
+            .filter_map(|task_id| {
+                // The thread may have exited after we listed it, in which case we skip it.
+                let stat = self.fs.get_proc_self_task_stat_contents(task_id)?;
+
+                Some(parse_task_stat(task_id, &stat))
+            })
+            .collect()
+    }
+
+    fn set_current_thread_priority(&self, priority: ThreadPriority) -> io::Result<()> {
+        let nice = match priority {
+            ThreadPriority::Low => 10,
+            ThreadPriority::Normal => 0,
+            ThreadPriority::High => -10,
+            ThreadPriority::Realtime => {
+                return self
+                    .bindings
+                    .sched_setscheduler_current(libc::SCHED_FIFO, 1);
+            }
+        };
+
+        // A new thread inherits the scheduling policy of the thread that spawned it, so we
+        // explicitly return to the regular policy in case the spawning thread was realtime.
+        self.bindings
+            .sched_setscheduler_current(libc::SCHED_OTHER, 0)?;
+        self.bindings.setpriority_current(nice)
+    }
+
+    fn interrupt_counts(&self) -> Vec<(ProcessorId, u64)> {
+        self.fs
+            .get_proc_interrupts_contents()
+            .map(|contents| parse_interrupts(&contents))
+            .unwrap_or_default()
+    }
+
+    fn refresh_available_processors(&self) {
+        let effective_cpuset = self
+            .fs
+            .get_proc_self_cgroup()
+            .and_then(parse_cgroup_name)
+            .and_then(|name| self.fs.get_v2_cgroup_cpuset_cpus_effective(&name))
+            // The file is empty if the cpuset controller is not enabled for the cgroup.
+            .filter(|contents| !contents.trim().is_empty())
+            .map(|contents| {
+                cpulist::parse(contents.trim())
+                    .expect("platform provided invalid cpulist in cpuset.cpus.effective")
+            });
+
+        *self.effective_cpuset.write().expect(ERR_POISONED_LOCK) = effective_cpuset;
+    }
+
+    fn online_processor_ids(&self) -> Vec<ProcessorId> {
+        self.fs
+            .get_cpu_online_list_contents()
+            .map(|contents| {
+                cpulist::parse(contents.trim())
+                    .expect("platform provided invalid cpulist for online processors")
+            })
+            .unwrap_or_default()
+    }
+
+    fn online_memory_region_ids(&self) -> Vec<MemoryRegionId> {
+        self.fs
+            .get_numa_node_online_contents()
+            .map(|contents| {
+                cpulist::parse(contents.trim())
+                    .expect("platform provided invalid cpulist for online NUMA nodes")
+            })
+            .unwrap_or_default()
+    }
+
+    fn pci_device_memory_region_id(&self, pci_address: &str) -> Option<MemoryRegionId> {
+        // The address becomes part of a path, so we reject anything that could point elsewhere.
+        if pci_address.is_empty() || pci_address.contains('/') || pci_address.starts_with('.') {
+            return None;
+        }
+
+        // The kernel reports -1 if the device is not associated with a memory region, which
+        // we treat the same as an unknown device.
+        self.fs
+            .get_pci_device_numa_node_contents(pci_address)?
+            .trim()
+            .parse()
+            .ok()
+    }
+}
+
+const ERR_POISONED_LOCK: &str =
+    "poisoned lock - safe execution no longer possible because a panic occurred on another thread";
+
+/// Parses the contents of a cpufreq frequency file, which is in kHz, into MHz.
+fn parse_frequency_khz_as_mhz(contents: &str) -> u32 {
+    contents
+        .trim()
+        .parse::<u32>()
+        .expect("platform provided invalid processor frequency")
+        .checked_div(1000)
+        .expect("division by non-zero constant cannot fail")
+}
+
+/// Parses the space-separated instruction set flags of a processor in /proc/cpuinfo.
+fn parse_cpu_flags(flags: &str) -> ProcessorFeatures {
+    flags
+        .split_whitespace()
+        .filter_map(|flag| match flag {
+            "avx2" => Some(ProcessorFeature::Avx2),
+            "avx512f" => Some(ProcessorFeature::Avx512),
+            "amx_tile" => Some(ProcessorFeature::Amx),
+            "sve" => Some(ProcessorFeature::Sve),
+            _ => None,
+        })
+        .fold(ProcessorFeatures::default(), ProcessorFeatures::with)
+}
+
+/// Parses the contents of the /proc/interrupts file into the number of device interrupts that
+/// each processor has handled.
+fn parse_interrupts(contents: &str) -> Vec<(ProcessorId, u64)> {
+    let mut lines = contents.lines();
+
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+
+    let processor_ids = header
+        .split_whitespace()
+        .map(|column| {
+            column
+                .strip_prefix("CPU")
+                .and_then(|id| id.parse::<ProcessorId>().ok())
+                .expect("platform provided /proc/interrupts with invalid processor column")
+        })
+        .collect_vec();
+
+    let mut counts = vec![0_u64; processor_ids.len()];
+
+    for line in lines {
+        let Some((label, values)) = line.split_once(':') else {
+            continue;
+        };
+
+        // Device interrupts are numbered, whereas the named ones (e.g. LOC for the local timer)
+        // are raised by the processors themselves and do not tell us anything about devices.
+        if label.trim().parse::<u32>().is_err() {
+            continue;
+        }
+
+        // Some lines have fewer values than there are processors, followed by the description.
+        for (count, value) in counts.iter_mut().zip(values.split_whitespace()) {
+            let Ok(value) = value.parse::<u64>() else {
+                break;
+            };
+
+            *count = count.saturating_add(value);
+        }
+    }
+
+    processor_ids.into_iter().zip(counts).collect()
+}
+
+/// Parses the contents of a /proc/self/task/{}/stat file into the placement of the thread.
+fn parse_task_stat(task_id: u32, stat: &str) -> ThreadPlacement {
+    // The name is in parentheses and may itself contain parentheses, so we look for the outer
+    // ones. The fields after the name are plain numbers and single-letter states.
+    let (name_start, name_end) = stat
+        .find('(')
+        .zip(stat.rfind(')'))
+        .expect("platform provided task stat without thread name");
+
+    let name = stat
+        .get(
+            name_start
+                .checked_add(1)
+                .expect("index of found character cannot be usize::MAX")..name_end,
+        )
+        .expect("platform provided task stat with invalid thread name");
+
+    // The first field after the name is field 3 (state), so field 39 (processor) is at index 36.
+    let processor_id = stat
+        .get(name_end..)
+        .expect("we just found the end of the name")
+        .trim_start_matches(')')
+        .split_whitespace()
+        .nth(36)
+        .expect("platform provided task stat without processor field")
+        .parse()
+        .expect("platform provided task stat with invalid processor field");
+
+    ThreadPlacement::new(
+        u64::from(task_id),
+        (!name.is_empty()).then(|| name.to_string()),
+        processor_id,
+    )
 }
 
 impl BuildTargetPlatform {
@@ -128,6 +421,10 @@ impl BuildTargetPlatform {
             all_active_processors: OnceLock::new(),
             max_processor_id: OnceLock::new(),
             max_memory_region_id: OnceLock::new(),
+            memory_region_distances: OnceLock::new(),
+            physical_core_ids: OnceLock::new(),
+            l3_cache_domain_ids: OnceLock::new(),
+            effective_cpuset: RwLock::new(None),
         }
     }
 
@@ -200,14 +497,28 @@ impl BuildTargetPlatform {
         let numa_nodes = numa_nodes
             .unwrap_or_else(|| once((0, cpu_infos.clone().map(|info| info.index))).collect());
 
-        // We identify efficiency cores by comparing the frequency of each processor to the maximum
-        // frequency of all processors. If the frequency is less than the maximum, we consider it an
-        // efficiency core.
-        let max_frequency = cpu_infos
+        let hybrid_efficiency_processors =
+            self.fs
+                .get_hybrid_efficiency_cpus_contents()
+                .map(|contents| {
+                    cpulist::parse(contents.trim()).expect(
+                        "platform provided invalid cpulist for hybrid efficiency processors",
+                    )
+                });
+
+        let capacities = cpu_infos
             .iter()
-            .map(|info| info.frequency_mhz)
-            .max()
-            .expect("must have at least one processor in NonEmpty");
+            .filter_map(|info| {
+                let capacity = self.fs.get_cpu_capacity_contents(info.index)?;
+                Some((info.index, capacity.trim().parse::<u32>().ok()?))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let efficiency_classes = efficiency_classes(
+            &cpu_infos,
+            hybrid_efficiency_processors.as_deref(),
+            &capacities,
+        );
 
         let mut processors = cpu_infos.map(|info| {
             let memory_region = numa_nodes
@@ -221,11 +532,9 @@ impl BuildTargetPlatform {
                 })
                 .expect("processor not found in any NUMA node");
 
-            let efficiency_class = if info.frequency_mhz < max_frequency {
-                EfficiencyClass::Efficiency
-            } else {
-                EfficiencyClass::Performance
-            };
+            let efficiency_class = *efficiency_classes
+                .get(&info.index)
+                .expect("every processor is classified");
 
             // Some Linux flavors do not report this, so just assume online by default.
             // Sometimes this is also omitted for a specific processor because... it just is.
@@ -238,6 +547,16 @@ impl BuildTargetPlatform {
                 id: info.index,
                 memory_region_id: memory_region,
                 efficiency_class,
+                package_id: info.package_id,
+                features: info.features,
+                base_frequency_mhz: self
+                    .fs
+                    .get_cpu_base_frequency_contents(info.index)
+                    .map(|s| parse_frequency_khz_as_mhz(&s)),
+                max_frequency_mhz: self
+                    .fs
+                    .get_cpu_max_frequency_contents(info.index)
+                    .map(|s| parse_frequency_khz_as_mhz(&s)),
                 is_active: is_online,
             }
         });
@@ -270,10 +589,19 @@ impl BuildTargetPlatform {
                     // This line gives us the processor frequency:
                     // cpu MHz         : 3400.036
                     //
+                    // This line gives us the physical package (socket) of the processor:
+                    // physical id     : 1
+                    //
+                    // This line gives us the instruction set features of the processor (on
+                    // x86-64 - on Arm, the key is "Features" instead):
+                    // flags           : fpu vme de pse tsc msr ... avx2 ... avx512f ...
+                    //
                     // All other lines we ignore.
 
                     let mut index = None;
                     let mut frequency_mhz = None;
+                    let mut package_id = None;
+                    let mut features = ProcessorFeatures::default();
 
                     for line in lines {
                         let (key, value) = line
@@ -287,6 +615,8 @@ impl BuildTargetPlatform {
                             "cpu MHz" => {
                                 frequency_mhz = value.parse::<f32>().map(|f| f.round() as u32).ok();
                             }
+                            "physical id" => package_id = value.parse::<PackageId>().ok(),
+                            "flags" | "Features" => features = parse_cpu_flags(value),
                             _ => {}
                         }
                     }
@@ -295,6 +625,10 @@ impl BuildTargetPlatform {
                         index: index.expect("processor index not found for processor"),
                         frequency_mhz: frequency_mhz
                             .expect("processor frequency not found for processor"),
+                        // Not every architecture reports packages (e.g. many ARM systems do not),
+                        // in which case we consider all processors to be in the same package.
+                        package_id: package_id.unwrap_or_default(),
+                        features,
                     })
                 })
                 .collect_vec(),
@@ -380,6 +714,111 @@ impl BuildTargetPlatform {
         )
     }
 
+    // May return None if everything is in a single NUMA node or the distances are not reported
+    // in a form we understand.
+    //
+    // Otherwise, returns the distance between every pair of online NUMA nodes.
+    fn load_physical_core_ids(&self) -> HashMap<ProcessorId, ProcessorId> {
+        // Processors in the same physical core list each other as thread siblings. If the
+        // platform does not tell us the siblings of a processor, it is its own core.
+        self.get_all_processors_impl()
+            .iter()
+            .map(|processor| {
+                let core_id = self
+                    .fs
+                    .get_cpu_thread_siblings_list_contents(processor.id)
+                    .and_then(|contents| {
+                        cpulist::parse(contents.trim())
+                            .expect("platform provided invalid cpulist for thread siblings")
+                            .into_iter()
+                            .min()
+                    })
+                    .unwrap_or(processor.id);
+
+                (processor.id, core_id)
+            })
+            .collect()
+    }
+
+    fn load_l3_cache_domain_ids(&self) -> HashMap<ProcessorId, ProcessorId> {
+        // Processors that share an L3 cache all list each other as sharing that cache. If the
+        // platform does not tell us about the L3 cache of a processor, it is its own domain.
+        self.get_all_processors_impl()
+            .iter()
+            .map(|processor| {
+                let domain_id = self
+                    .find_l3_cache_index(processor.id)
+                    .and_then(|cache_index| {
+                        self.fs
+                            .get_cpu_cache_shared_cpu_list_contents(processor.id, cache_index)
+                    })
+                    .and_then(|contents| {
+                        cpulist::parse(contents.trim())
+                            .expect("platform provided invalid cpulist for cache sharing")
+                            .into_iter()
+                            .min()
+                    })
+                    .unwrap_or(processor.id);
+
+                (processor.id, domain_id)
+            })
+            .collect()
+    }
+
+    /// Finds the index of the L3 cache among the caches of a processor, if it has one.
+    fn find_l3_cache_index(&self, processor_id: ProcessorId) -> Option<u32> {
+        // The caches are numbered without gaps, so the first missing index ends the list.
+        (0..)
+            .map_while(|cache_index| {
+                let level = self
+                    .fs
+                    .get_cpu_cache_level_contents(processor_id, cache_index)?;
+
+                Some((cache_index, level))
+            })
+            .find(|(_, level)| level.trim() == "3")
+            .map(|(cache_index, _)| cache_index)
+    }
+
+    fn load_memory_region_distances(
+        &self,
+    ) -> Option<HashMap<(MemoryRegionId, MemoryRegionId), u32>> {
+        let node_indexes = cpulist::parse(self.fs.get_numa_node_possible_contents()?.trim())
+            .expect("platform provided invalid cpulist for list of NUMA nodes");
+
+        // Only online nodes have a distance file and each distance file only lists the distances
+        // to online nodes, so the online nodes are exactly the ones we find a distance file for.
+        let online_nodes = node_indexes
+            .into_iter()
+            .filter_map(|node| Some((node, self.fs.get_numa_node_distance_contents(node)?)))
+            .collect_vec();
+
+        let mut distances = HashMap::default();
+
+        for (from, contents) in &online_nodes {
+            let row = contents
+                .split_whitespace()
+                .map(|distance| {
+                    distance
+                        .parse::<u32>()
+                        .expect("platform provided invalid NUMA node distance")
+                })
+                .collect_vec();
+
+            if row.len() != online_nodes.len() {
+                // Nodes came online or went offline while we were reading - we cannot trust
+                // the positions in the list to identify the nodes, so we give up.
+                return None;
+            }
+
+            for ((to, _), distance) in online_nodes.iter().zip(row) {
+                distances.insert((*from, *to), distance);
+            }
+        }
+
+        Some(distances)
+    }
+
     /// Processor time limit in processor-seconds per second.
     fn cgroups_max_processor_time(&self) -> Option<f64> {
         let name = self.fs.get_proc_self_cgroup().and_then(parse_cgroup_name)?;
@@ -426,15 +865,164 @@ impl BuildTargetPlatform {
     }
 }
 
+// From linux/mempolicy.h, which is not exposed via libc.
+const MPOL_PREFERRED: libc::c_int = 1;
+
+/// The node mask that makes the memory region of the processors the preferred memory region, if
+/// all the processors are in the same memory region.
+///
+/// The node mask only fits the first `c_ulong::BITS` memory regions - beyond that, we return
+/// `None` and rely on the default policy of allocating memory near the processor.
+fn preferred_node_mask_of<P>(processors: &NonEmpty<P>) -> Option<libc::c_ulong>
+where
+    P: AsRef<ProcessorFacade>,
+{
+    processors
+        .iter()
+        .map(|p| p.as_ref().as_real().memory_region_id)
+        .all_equal_value()
+        .ok()
+        .and_then(|memory_region_id| {
+            let node_mask: libc::c_ulong = 1;
+            node_mask.checked_shl(memory_region_id)
+        })
+}
+
+/// The `max_node` argument of `set_mempolicy()` for a node mask of a single `c_ulong`.
+fn node_mask_max_node() -> libc::c_ulong {
+    // The kernel reads `max_node - 1` bits from the node mask, so we pass one more than
+    // the size of the node mask.
+    libc::c_ulong::from(libc::c_ulong::BITS)
+        .checked_add(1)
+        .expect("the size of a c_ulong in bits is a small number")
+}
+
+/// Pins the current thread to the processors in `cpu_set` and, if given, makes the memory
+/// regions in `preferred_node_mask` the preferred memory regions of the current thread, so memory
+/// is allocated there even if it is first touched from a different thread.
+///
+/// This is called in a child process between fork and exec, so it must not allocate memory or
+/// take locks.
+fn apply_command_placement(
+    bindings: &impl Bindings,
+    cpu_set: &libc::cpu_set_t,
+    preferred_node_mask: Option<libc::c_ulong>,
+) -> io::Result<()> {
+    bindings.sched_setaffinity_current(cpu_set)?;
+
+    if let Some(node_mask) = preferred_node_mask {
+        bindings.set_mempolicy_current(MPOL_PREFERRED, node_mask, node_mask_max_node())?;
+    }
+
+    Ok(())
+}
+
+fn cpu_set_of<P>(processors: &NonEmpty<P>) -> libc::cpu_set_t
+where
+    P: AsRef<ProcessorFacade>,
+{
+    // SAFETY: Zero-initialized cpu_set_t is a valid value.
+    let mut cpu_set: libc::cpu_set_t = unsafe { mem::zeroed() };
+
+    for processor in processors.iter() {
+        // SAFETY: No safety requirements.
+        unsafe {
+            // TODO: This can go out of bounds with giant CPU set (1000+), we would need to use
+            // dynamically allocated CPU sets instead of relying on the fixed-size one in libc.
+            libc::CPU_SET(processor.as_ref().as_real().id as usize, &mut cpu_set);
+        }
+    }
+
+    cpu_set
+}
+
 // One result from /proc/cpuinfo.
 #[derive(Clone, Debug)]
 struct CpuInfo {
     index: ProcessorId,
 
-    /// CPU frequency, rounded to nearest integer. We use this to identify efficiency versus
-    /// performance cores, where the processors with max frequency are considered performance
-    /// cores and any with lower frequency are considered efficiency cores.
+    /// CPU frequency, rounded to nearest integer. If the platform offers no better source of
+    /// information, we use this to identify efficiency versus performance cores, where the
+    /// processors with max frequency are considered performance cores and any with lower frequency
+    /// are considered efficiency cores.
     frequency_mhz: u32,
+
+    /// The physical package (socket) of the processor, from the "physical id" line.
+    package_id: PackageId,
+
+    /// The instruction set features of the processor, from the "flags" or "Features" line.
+    features: ProcessorFeatures,
+}
+
+/// Classifies the processors as performance or efficiency processors, using the best source of
+/// information the platform offers:
+///
+/// 1. On Intel hybrid processors, the efficiency cores are listed in /sys/devices/cpu_atom/cpus.
+/// 2. On other heterogeneous systems (e.g. ARM big.LITTLE), the scheduler reports the relative
+///    capacity of each processor.
+/// 3. Otherwise, we compare the frequencies reported in /proc/cpuinfo.
+///
+/// The classification is relative to the given processors - if they are all of the same kind,
+/// they are all performance processors.
+fn efficiency_classes(
+    cpu_infos: &NonEmpty<CpuInfo>,
+    hybrid_efficiency_processors: Option<&[ProcessorId]>,
+    capacities: &HashMap<ProcessorId, u32>,
+) -> HashMap<ProcessorId, EfficiencyClass> {
+    let hybrid_efficiency_processors = hybrid_efficiency_processors.filter(|efficiency| {
+        cpu_infos
+            .iter()
+            .any(|info| !efficiency.contains(&info.index))
+    });
+
+    if let Some(efficiency) = hybrid_efficiency_processors {
+        return cpu_infos
+            .iter()
+            .map(|info| {
+                let class = if efficiency.contains(&info.index) {
+                    EfficiencyClass::Efficiency
+                } else {
+                    EfficiencyClass::Performance
+                };
+
+                (info.index, class)
+            })
+            .collect();
+    }
+
+    // Capacities are only comparable if the platform reports them for every processor.
+    let use_capacities = cpu_infos
+        .iter()
+        .all(|info| capacities.contains_key(&info.index));
+
+    let score = |info: &CpuInfo| {
+        if use_capacities {
+            *capacities
+                .get(&info.index)
+                .expect("we verified that every processor has a capacity")
+        } else {
+            info.frequency_mhz
+        }
+    };
+
+    let max_score = cpu_infos
+        .iter()
+        .map(score)
+        .max()
+        .expect("NonEmpty always has at least one item");
+
+    cpu_infos
+        .iter()
+        .map(|info| {
+            let class = if score(info) < max_score {
+                EfficiencyClass::Efficiency
+            } else {
+                EfficiencyClass::Performance
+            };
+
+            (info.index, class)
+        })
+        .collect()
 }
 
 /// This is the relative path of the cgroup the current process belongs to (e.g. `/foo/bar`)
@@ -501,9 +1089,13 @@ fn parse_v1_cgroup_cpu_quota_and_period_us(
 mod tests {
     use std::fmt::Write;
 
+    use nonempty::nonempty;
     use testing::f64_diff_abs;
 
-    use crate::pal::linux::{MockBindings, MockFilesystem};
+    use crate::pal::{
+        AbstractProcessor,
+        linux::{MockBindings, MockFilesystem},
+    };
 
     use super::*;
 
@@ -561,6 +1153,142 @@ mod tests {
         assert_eq!(p3.as_real().memory_region_id, 0);
     }
 
+    #[test]
+    fn processor_features_from_cpuinfo_flags() {
+        let mut fs = MockFilesystem::new();
+
+        simulate_processor_layout(
+            &mut fs,
+            [0, 1, 2, 3],
+            None,
+            None,
+            [0, 0, 0, 0],
+            [99.9, 99.9, 99.9, 99.9],
+        );
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        let processors = platform.get_all_processors();
+
+        for processor in processors.iter() {
+            let features = processor.features();
+
+            assert!(features.contains(ProcessorFeature::Avx2));
+            assert_eq!(
+                features.contains(ProcessorFeature::Avx512),
+                processor.id() % 2 == 0
+            );
+            assert!(!features.contains(ProcessorFeature::Amx));
+            assert!(!features.contains(ProcessorFeature::Sve));
+        }
+    }
+
+    #[test]
+    fn processor_frequencies_from_cpufreq() {
+        let mut fs = MockFilesystem::new();
+
+        simulate_processor_layout(&mut fs, [0, 1], None, None, [0, 0], [3500.0, 2400.0]);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        let processors = platform.get_all_processors();
+
+        let p0 = &processors[0];
+        assert_eq!(p0.max_frequency_mhz(), Some(3500));
+        assert_eq!(p0.base_frequency_mhz(), None);
+
+        let p1 = &processors[1];
+        assert_eq!(p1.max_frequency_mhz(), Some(2400));
+        assert_eq!(p1.base_frequency_mhz(), None);
+    }
+
+    #[test]
+    fn parse_cpu_flags_recognizes_arm_features() {
+        let features = parse_cpu_flags("fp asimd evtstrm aes pmull sha1 sha2 crc32 atomics sve");
+
+        assert!(features.contains(ProcessorFeature::Sve));
+        assert!(!features.contains(ProcessorFeature::Avx2));
+    }
+
+    fn cpu_infos_with_frequencies(frequencies_mhz: [u32; 4]) -> NonEmpty<CpuInfo> {
+        NonEmpty::from_vec(
+            (0..4)
+                .zip(frequencies_mhz)
+                .map(|(index, frequency_mhz)| CpuInfo {
+                    index,
+                    frequency_mhz,
+                    package_id: 0,
+                    features: ProcessorFeatures::default(),
+                })
+                .collect_vec(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn efficiency_classes_prefer_hybrid_efficiency_processors() {
+        // The frequencies say otherwise but the hybrid processor list wins.
+        let classes = efficiency_classes(
+            &cpu_infos_with_frequencies([3000, 3000, 3000, 3000]),
+            Some(&[2, 3]),
+            &[(0, 1024), (1, 1024), (2, 1024), (3, 1024)]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(classes[&0], EfficiencyClass::Performance);
+        assert_eq!(classes[&1], EfficiencyClass::Performance);
+        assert_eq!(classes[&2], EfficiencyClass::Efficiency);
+        assert_eq!(classes[&3], EfficiencyClass::Efficiency);
+
+        // If all the processors are efficiency cores, they are all the fastest we have.
+        let classes = efficiency_classes(
+            &cpu_infos_with_frequencies([3000, 3000, 3000, 3000]),
+            Some(&[0, 1, 2, 3]),
+            &HashMap::default(),
+        );
+
+        assert!(
+            classes
+                .values()
+                .all(|class| *class == EfficiencyClass::Performance)
+        );
+    }
+
+    #[test]
+    fn efficiency_classes_use_capacities_if_complete() {
+        let classes = efficiency_classes(
+            &cpu_infos_with_frequencies([2000, 2000, 3000, 3000]),
+            None,
+            &[(0, 1024), (1, 1024), (2, 446), (3, 446)]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(classes[&0], EfficiencyClass::Performance);
+        assert_eq!(classes[&1], EfficiencyClass::Performance);
+        assert_eq!(classes[&2], EfficiencyClass::Efficiency);
+        assert_eq!(classes[&3], EfficiencyClass::Efficiency);
+
+        // Without a capacity for every processor, we fall back to the frequencies.
+        let classes = efficiency_classes(
+            &cpu_infos_with_frequencies([2000, 2000, 3000, 3000]),
+            None,
+            &[(0, 1024), (1, 1024)].into_iter().collect(),
+        );
+
+        assert_eq!(classes[&0], EfficiencyClass::Efficiency);
+        assert_eq!(classes[&1], EfficiencyClass::Efficiency);
+        assert_eq!(classes[&2], EfficiencyClass::Performance);
+        assert_eq!(classes[&3], EfficiencyClass::Performance);
+    }
+
     #[test]
     fn forbidden_processors_are_ignored() {
         let mut fs = MockFilesystem::new();
@@ -651,20 +1379,68 @@ mod tests {
     }
 
     #[test]
-    fn two_numa_nodes_efficiency_performance() {
+    fn effective_cpuset_is_applied_on_refresh() {
         let mut fs = MockFilesystem::new();
-        // Two nodes, each with 2 processors:
-        // Node 0 -> [Performance, Efficiency], Node 1 -> [Efficiency, Performance].
+
         simulate_processor_layout(
             &mut fs,
             [0, 1, 2, 3],
             None,
             None,
-            [0, 0, 1, 1],
-            [3400.0, 2000.0, 2000.0, 3400.0],
+            [0, 0, 0, 0],
+            [99.9, 99.9, 99.9, 99.9],
         );
 
-        let platform = BuildTargetPlatform::new(
+        fs.expect_get_proc_self_cgroup()
+            .times(3)
+            .return_const("0::/foo/bar\n".to_string());
+
+        // The cpuset shrinks, then moves to processors we do not know of, then goes away.
+        fs.expect_get_v2_cgroup_cpuset_cpus_effective()
+            .withf(|name| name == "/foo/bar")
+            .times(3)
+            .returning({
+                let mut values = [Some("1-2\n"), Some("8-9\n"), Some("")].into_iter();
+                move |_| values.next().unwrap().map(str::to_string)
+            });
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        let ids = |processors: NonEmpty<ProcessorFacade>| {
+            processors.iter().map(|p| p.as_real().id).collect_vec()
+        };
+
+        // Until the first refresh, the cpuset is not applied.
+        assert_eq!(ids(platform.get_all_processors()), [0, 1, 2, 3]);
+
+        platform.refresh_available_processors();
+        assert_eq!(ids(platform.get_all_processors()), [1, 2]);
+
+        platform.refresh_available_processors();
+        assert_eq!(ids(platform.get_all_processors()), [0, 1, 2, 3]);
+
+        platform.refresh_available_processors();
+        assert_eq!(ids(platform.get_all_processors()), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn two_numa_nodes_efficiency_performance() {
+        let mut fs = MockFilesystem::new();
+        // Two nodes, each with 2 processors:
+        // Node 0 -> [Performance, Efficiency], Node 1 -> [Efficiency, Performance].
+        simulate_processor_layout(
+            &mut fs,
+            [0, 1, 2, 3],
+            None,
+            None,
+            [0, 0, 1, 1],
+            [3400.0, 2000.0, 2000.0, 3400.0],
+        );
+
+        let platform = BuildTargetPlatform::new(
             BindingsFacade::from_mock(MockBindings::new()),
             FilesystemFacade::from_mock(fs),
         );
@@ -681,12 +1457,14 @@ mod tests {
         assert_eq!(p1.as_real().id, 1);
         assert_eq!(p1.as_real().memory_region_id, 0);
         assert_eq!(p1.as_real().efficiency_class, EfficiencyClass::Efficiency);
+        assert_eq!(p1.as_real().package_id, 0);
 
         // Node 1
         let p2 = &processors[2];
         assert_eq!(p2.as_real().id, 2);
         assert_eq!(p2.as_real().memory_region_id, 1);
         assert_eq!(p2.as_real().efficiency_class, EfficiencyClass::Efficiency);
+        assert_eq!(p2.as_real().package_id, 1);
 
         let p3 = &processors[3];
         assert_eq!(p3.as_real().id, 3);
@@ -821,6 +1599,365 @@ mod tests {
         assert_eq!(p3.as_real().efficiency_class, EfficiencyClass::Performance);
     }
 
+    #[test]
+    fn memory_region_distances_are_reported() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_numa_node_possible_contents()
+            .times(1)
+            .return_const(Some("0-2\n".to_string()));
+
+        // Node 1 is offline, so it has no distance file and is not in the lists of others.
+        fs.expect_get_numa_node_distance_contents()
+            .withf(|n| *n == 0)
+            .times(1)
+            .return_const(Some("10 32\n".to_string()));
+        fs.expect_get_numa_node_distance_contents()
+            .withf(|n| *n == 1)
+            .times(1)
+            .return_const(None);
+        fs.expect_get_numa_node_distance_contents()
+            .withf(|n| *n == 2)
+            .times(1)
+            .return_const(Some("32 10\n".to_string()));
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.memory_region_distance(0, 0), 10);
+        assert_eq!(platform.memory_region_distance(0, 2), 32);
+        assert_eq!(platform.memory_region_distance(2, 0), 32);
+        assert_eq!(platform.memory_region_distance(2, 2), 10);
+
+        // Unknown memory regions get the default distance.
+        assert_eq!(platform.memory_region_distance(0, 1), 20);
+    }
+
+    #[test]
+    fn memory_region_distances_default_without_numa_info() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_numa_node_possible_contents()
+            .times(1)
+            .return_const(None);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.memory_region_distance(0, 0), 10);
+        assert_eq!(platform.memory_region_distance(0, 1), 20);
+    }
+
+    #[test]
+    fn physical_core_ids_from_thread_siblings() {
+        let mut fs = MockFilesystem::new();
+
+        simulate_processor_layout(
+            &mut fs,
+            [0, 1, 2, 3],
+            None,
+            None,
+            [0, 0, 0, 0],
+            [99.9, 99.9, 99.9, 99.9],
+        );
+
+        // Processors 0 and 2 share a core, as do 1 and 3. Processor 3 does not say so, though.
+        fs.expect_get_cpu_thread_siblings_list_contents()
+            .withf(|p| *p == 0 || *p == 2)
+            .times(2)
+            .return_const(Some("0,2\n".to_string()));
+        fs.expect_get_cpu_thread_siblings_list_contents()
+            .withf(|p| *p == 1)
+            .times(1)
+            .return_const(Some("1,3\n".to_string()));
+        fs.expect_get_cpu_thread_siblings_list_contents()
+            .withf(|p| *p == 3)
+            .times(1)
+            .return_const(None);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.physical_core_id(0), 0);
+        assert_eq!(platform.physical_core_id(1), 1);
+        assert_eq!(platform.physical_core_id(2), 0);
+
+        // Without sibling information, the processor is its own core.
+        assert_eq!(platform.physical_core_id(3), 3);
+    }
+
+    #[test]
+    fn current_process_threads_from_task_stats() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_proc_self_task_ids()
+            .times(1)
+            .return_const(vec![100, 101, 102]);
+
+        // The name of the second thread contains parentheses and spaces, to try confuse us.
+        fs.expect_get_proc_self_task_stat_contents()
+            .withf(|t| *t == 100)
+            .times(1)
+            .return_const(Some(task_stat(100, "main", 3)));
+        fs.expect_get_proc_self_task_stat_contents()
+            .withf(|t| *t == 101)
+            .times(1)
+            .return_const(Some(task_stat(101, "worker (1) ", 7)));
+
+        // The third thread exited after we listed it.
+        fs.expect_get_proc_self_task_stat_contents()
+            .withf(|t| *t == 102)
+            .times(1)
+            .return_const(None);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(
+            platform.current_process_threads(),
+            [
+                ThreadPlacement::new(100, Some("main".to_string()), 3),
+                ThreadPlacement::new(101, Some("worker (1) ".to_string()), 7)
+            ]
+        );
+    }
+
+    #[test]
+    fn interrupt_counts_only_include_device_interrupts() {
+        let mut fs = MockFilesystem::new();
+
+        // Processor 1 is offline, so it has no column. The named interrupts are not from devices.
+        fs.expect_get_proc_interrupts_contents()
+            .times(1)
+            .return_const(Some(
+                [
+                    "           CPU0       CPU2       CPU3       ",
+                    "  0:         36          0          0   IO-APIC   2-edge      timer",
+                    " 24:      50000         10          0   PCI-MSI 524288-edge      nvme0q0",
+                    " 25:        100       7000          3   PCI-MSI 1048576-edge      eth0-rx-0",
+                    "NMI:          5          5          5   Non-maskable interrupts",
+                    "LOC:    1234567    1234567    1234567   Local timer interrupts",
+                    "ERR:          0",
+                    "",
+                ]
+                .join("\n"),
+            ));
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.interrupt_counts(), [(0, 50136), (2, 7010), (3, 3)]);
+    }
+
+    #[test]
+    fn processor_weight_from_cgroup() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_proc_self_cgroup()
+            .times(2)
+            .return_const("0::/foo/bar\n".to_string());
+
+        // The first time we find a v2 weight, the second time only v1 shares.
+        fs.expect_get_v2_cgroup_cpu_weight()
+            .withf(|name| name == "/foo/bar")
+            .times(2)
+            .returning({
+                let mut values = [Some("200\n"), None].into_iter();
+                move |_| values.next().unwrap().map(str::to_string)
+            });
+
+        fs.expect_get_v1_cgroup_cpu_shares()
+            .withf(|name| name == "/foo/bar")
+            .times(1)
+            .return_const(Some("512\n".to_string()));
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.processor_weight(), Some(2.0));
+        assert_eq!(platform.processor_weight(), Some(0.5));
+    }
+
+    #[test]
+    fn processor_weight_without_cgroup() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_proc_self_cgroup().times(1).return_const(None);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.processor_weight(), None);
+    }
+
+    #[test]
+    fn online_ids_are_read_on_every_call() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_cpu_online_list_contents()
+            .times(2)
+            .returning({
+                let mut values = ["0-3\n", "0-1,3\n"].into_iter();
+                move || values.next().map(str::to_string)
+            });
+
+        fs.expect_get_numa_node_online_contents()
+            .times(2)
+            .returning({
+                let mut values = [Some("0\n"), None].into_iter();
+                move || values.next().unwrap().map(str::to_string)
+            });
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.online_processor_ids(), [0, 1, 2, 3]);
+        assert_eq!(platform.online_processor_ids(), [0, 1, 3]);
+
+        assert_eq!(platform.online_memory_region_ids(), [0]);
+        assert!(platform.online_memory_region_ids().is_empty());
+    }
+
+    #[test]
+    fn pci_device_memory_region_id_read_from_numa_node() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_pci_device_numa_node_contents()
+            .withf(|address| address == "0000:3b:00.0")
+            .times(1)
+            .returning(|_| Some("1\n".to_string()));
+
+        fs.expect_get_pci_device_numa_node_contents()
+            .withf(|address| address == "0000:00:1f.6")
+            .times(1)
+            .returning(|_| Some("-1\n".to_string()));
+
+        fs.expect_get_pci_device_numa_node_contents()
+            .withf(|address| address == "0000:ff:00.0")
+            .times(1)
+            .returning(|_| None);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(
+            platform.pci_device_memory_region_id("0000:3b:00.0"),
+            Some(1)
+        );
+
+        // Not associated with any memory region.
+        assert_eq!(platform.pci_device_memory_region_id("0000:00:1f.6"), None);
+
+        // No such device.
+        assert_eq!(platform.pci_device_memory_region_id("0000:ff:00.0"), None);
+    }
+
+    #[test]
+    fn pci_device_memory_region_id_rejects_paths() {
+        // The filesystem is never consulted, as the mock has no expectations.
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(MockFilesystem::new()),
+        );
+
+        assert_eq!(platform.pci_device_memory_region_id(""), None);
+        assert_eq!(platform.pci_device_memory_region_id(".."), None);
+        assert_eq!(platform.pci_device_memory_region_id("../../node0"), None);
+        assert_eq!(
+            platform.pci_device_memory_region_id("0000:3b:00.0/.."),
+            None
+        );
+    }
+
+    #[test]
+    fn interrupt_counts_empty_if_not_reported() {
+        let mut fs = MockFilesystem::new();
+
+        fs.expect_get_proc_interrupts_contents()
+            .times(1)
+            .return_const(None);
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert!(platform.interrupt_counts().is_empty());
+    }
+
+    /// A /proc/self/task/{}/stat line with the given thread name and last processor.
+    fn task_stat(task_id: u32, name: &str, processor_id: ProcessorId) -> String {
+        // Fields 3-38 do not matter to us, so we just fill them with zeroes.
+        let ignored_fields = ["0"; 36].join(" ");
+
+        format!("{task_id} ({name}) {ignored_fields} {processor_id} 0 0 0 0\n")
+    }
+
+    #[test]
+    fn l3_cache_domain_ids_from_shared_caches() {
+        let mut fs = MockFilesystem::new();
+
+        simulate_processor_layout(
+            &mut fs,
+            [0, 1, 2, 3],
+            None,
+            None,
+            [0, 0, 0, 0],
+            [99.9, 99.9, 99.9, 99.9],
+        );
+
+        // Processors 0-2 have L1, L2 and L3 caches. Processor 3 only reports an L1 cache.
+        fs.expect_get_cpu_cache_level_contents()
+            .returning(
+                |processor_id, cache_index| match (processor_id, cache_index) {
+                    (0..=3, 0) => Some("1\n".to_string()),
+                    (0..=2, 1) => Some("2\n".to_string()),
+                    (0..=2, 2) => Some("3\n".to_string()),
+                    _ => None,
+                },
+            );
+
+        // Processors 0 and 1 share an L3 cache, processor 2 has one of its own.
+        fs.expect_get_cpu_cache_shared_cpu_list_contents()
+            .withf(|p, c| (*p == 0 || *p == 1) && *c == 2)
+            .times(2)
+            .return_const(Some("0-1\n".to_string()));
+        fs.expect_get_cpu_cache_shared_cpu_list_contents()
+            .withf(|p, c| *p == 2 && *c == 2)
+            .times(1)
+            .return_const(Some("2\n".to_string()));
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(MockBindings::new()),
+            FilesystemFacade::from_mock(fs),
+        );
+
+        assert_eq!(platform.l3_cache_domain_id(0), 0);
+        assert_eq!(platform.l3_cache_domain_id(1), 0);
+        assert_eq!(platform.l3_cache_domain_id(2), 2);
+
+        // Without L3 cache information, the processor is its own domain.
+        assert_eq!(platform.l3_cache_domain_id(3), 3);
+    }
+
     /// Configures mock bindings and filesystem to simulate a particular type of processor layout.
     ///
     /// The simulation is valid for one call to `get_all_processors_impl()`.
@@ -841,11 +1978,24 @@ mod tests {
 
         let mut cpuinfo = String::new();
 
-        for (processor_index, frequency) in
-            processor_index.iter().zip(frequencies_per_processor.iter())
+        // Each simulated memory region is its own physical package.
+        for ((processor_index, frequency), package_id) in processor_index
+            .iter()
+            .zip(frequencies_per_processor.iter())
+            .zip(memory_region_index.iter())
         {
             writeln!(cpuinfo, "processor       : {processor_index}").unwrap();
             writeln!(cpuinfo, "cpu MHz         : {frequency}").unwrap();
+            writeln!(cpuinfo, "physical id     : {package_id}").unwrap();
+
+            // Every processor supports AVX2 but only the even ones support AVX-512, like on a
+            // system that mixes processors of different generations.
+            if processor_index % 2 == 0 {
+                writeln!(cpuinfo, "flags           : fpu sse2 avx2 avx512f").unwrap();
+            } else {
+                writeln!(cpuinfo, "flags           : fpu sse2 avx2").unwrap();
+            }
+
             writeln!(cpuinfo, "whatever        : 123").unwrap();
             writeln!(cpuinfo, "other           : ignored").unwrap();
             writeln!(cpuinfo).unwrap();
@@ -872,6 +2022,10 @@ mod tests {
             .times(1)
             .return_const(Some(node_indexes_cpulist));
 
+        fs.expect_get_hybrid_efficiency_cpus_contents()
+            .times(1)
+            .return_const(None);
+
         for (index, processor_id) in processor_index.iter().copied().enumerate() {
             if !processor_is_allowed[index] {
                 // Forbidden processors are not probed.
@@ -888,6 +2042,25 @@ mod tests {
                 } else {
                     Some("0".to_string())
                 });
+
+            // We simulate a frequency scaling driver that does not report the base frequency.
+            fs.expect_get_cpu_base_frequency_contents()
+                .withf(move |p| *p == processor_id)
+                .times(1)
+                .return_const(None);
+
+            // We simulate a homogeneous system, which does not report capacities.
+            fs.expect_get_cpu_capacity_contents()
+                .withf(move |p| *p == processor_id)
+                .times(1)
+                .return_const(None);
+
+            // The maximum frequency is in kHz.
+            let max_frequency_khz = format!("{:.0}\n", frequencies_per_processor[index] * 1000.0);
+            fs.expect_get_cpu_max_frequency_contents()
+                .withf(move |p| *p == processor_id)
+                .times(1)
+                .return_const(Some(max_frequency_khz));
         }
 
         for (node, processors) in processors_per_node {
@@ -990,6 +2163,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_current_thread_priority_maps_to_nice_or_realtime() {
+        let mut bindings = MockBindings::new();
+
+        bindings
+            .expect_sched_setscheduler_current()
+            .withf(|policy, priority| *policy == libc::SCHED_OTHER && *priority == 0)
+            .times(2)
+            .returning(|_, _| Ok(()));
+
+        bindings
+            .expect_setpriority_current()
+            .withf(|nice| *nice == 10)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        bindings
+            .expect_setpriority_current()
+            .withf(|nice| *nice == -10)
+            .times(1)
+            .returning(|_| Err(io::Error::from_raw_os_error(libc::EACCES)));
+
+        bindings
+            .expect_sched_setscheduler_current()
+            .withf(|policy, priority| *policy == libc::SCHED_FIFO && *priority == 1)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let platform = BuildTargetPlatform::new(
+            BindingsFacade::from_mock(bindings),
+            FilesystemFacade::from_mock(MockFilesystem::new()),
+        );
+
+        platform
+            .set_current_thread_priority(ThreadPriority::Low)
+            .unwrap();
+        platform
+            .set_current_thread_priority(ThreadPriority::High)
+            .unwrap_err();
+        platform
+            .set_current_thread_priority(ThreadPriority::Realtime)
+            .unwrap();
+    }
+
+    fn processor_in_memory_region(
+        id: ProcessorId,
+        memory_region_id: MemoryRegionId,
+    ) -> ProcessorFacade {
+        ProcessorFacade::Real(ProcessorImpl {
+            id,
+            memory_region_id,
+            efficiency_class: EfficiencyClass::Performance,
+            package_id: 0,
+            features: ProcessorFeatures::default(),
+            base_frequency_mhz: None,
+            max_frequency_mhz: None,
+            is_active: true,
+        })
+    }
+
+    #[test]
+    fn preferred_node_mask_of_single_memory_region() {
+        let region_0 = nonempty![
+            processor_in_memory_region(0, 0),
+            processor_in_memory_region(1, 0)
+        ];
+        let region_3 = nonempty![
+            processor_in_memory_region(2, 3),
+            processor_in_memory_region(3, 3)
+        ];
+        let mixed = nonempty![
+            processor_in_memory_region(0, 0),
+            processor_in_memory_region(2, 3)
+        ];
+
+        assert_eq!(preferred_node_mask_of(&region_0), Some(0b1));
+        assert_eq!(preferred_node_mask_of(&region_3), Some(0b1000));
+
+        // Processors in different memory regions have no single preferred memory region.
+        assert_eq!(preferred_node_mask_of(&mixed), None);
+    }
+
+    #[test]
+    fn preferred_node_mask_of_memory_region_beyond_mask() {
+        let last_in_mask = libc::c_ulong::BITS - 1;
+        let first_beyond_mask = libc::c_ulong::BITS;
+
+        assert_eq!(
+            preferred_node_mask_of(&nonempty![processor_in_memory_region(0, last_in_mask)]),
+            Some(1 << last_in_mask)
+        );
+        assert_eq!(
+            preferred_node_mask_of(&nonempty![processor_in_memory_region(0, first_beyond_mask)]),
+            None
+        );
+    }
+
+    #[test]
+    fn node_mask_max_node_covers_whole_mask() {
+        // One more than the number of bits in the node mask.
+        assert_eq!(
+            node_mask_max_node(),
+            libc::c_ulong::from(libc::c_ulong::BITS) + 1
+        );
+
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(node_mask_max_node(), 65);
+    }
+
+    #[test]
+    fn apply_command_placement_sets_affinity_and_memory_policy() {
+        let mut bindings = MockBindings::new();
+
+        let expected_set = cpuset_from([2, 3]);
+
+        bindings
+            .expect_sched_setaffinity_current()
+            .withf(move |cpu_set| {
+                // SAFETY: No safety requirements.
+                unsafe { libc::CPU_EQUAL(cpu_set, &expected_set) }
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        bindings
+            .expect_set_mempolicy_current()
+            .withf(|mode, node_mask, max_node| {
+                *mode == MPOL_PREFERRED && *node_mask == 0b1000 && *max_node == node_mask_max_node()
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        apply_command_placement(&bindings, &cpuset_from([2, 3]), Some(0b1000)).unwrap();
+    }
+
+    #[test]
+    fn apply_command_placement_without_memory_policy() {
+        let mut bindings = MockBindings::new();
+
+        bindings
+            .expect_sched_setaffinity_current()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        bindings.expect_set_mempolicy_current().times(0);
+
+        apply_command_placement(&bindings, &cpuset_from([0, 1]), None).unwrap();
+    }
+
+    #[test]
+    fn apply_command_placement_stops_on_affinity_error() {
+        let mut bindings = MockBindings::new();
+
+        bindings
+            .expect_sched_setaffinity_current()
+            .times(1)
+            .returning(|_| Err(io::Error::from_raw_os_error(libc::EINVAL)));
+
+        bindings.expect_set_mempolicy_current().times(0);
+
+        apply_command_placement(&bindings, &cpuset_from([0]), Some(0b1)).unwrap_err();
+    }
+
     #[test]
     fn pin_current_thread_to_single_processor() {
         let mut bindings = MockBindings::new();
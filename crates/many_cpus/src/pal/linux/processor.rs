@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use crate::{EfficiencyClass, MemoryRegionId, ProcessorId, pal::AbstractProcessor};
+use crate::{
+    EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeatures, ProcessorId,
+    pal::{AbstractProcessor, BUILD_TARGET_PLATFORM, Platform},
+};
 
 /// A processor present on the system and available to the current process.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -8,6 +11,10 @@ pub(crate) struct ProcessorImpl {
     pub(super) id: ProcessorId,
     pub(super) memory_region_id: MemoryRegionId,
     pub(super) efficiency_class: EfficiencyClass,
+    pub(super) package_id: PackageId,
+    pub(super) features: ProcessorFeatures,
+    pub(super) base_frequency_mhz: Option<u32>,
+    pub(super) max_frequency_mhz: Option<u32>,
 
     pub(super) is_active: bool,
 }
@@ -31,6 +38,32 @@ impl AbstractProcessor for ProcessorImpl {
     fn efficiency_class(&self) -> EfficiencyClass {
         self.efficiency_class
     }
+
+    fn package_id(&self) -> PackageId {
+        self.package_id
+    }
+
+    fn features(&self) -> ProcessorFeatures {
+        self.features
+    }
+
+    fn physical_core_id(&self) -> ProcessorId {
+        // Processors only ever come from the real platform, which resolves cores on demand.
+        BUILD_TARGET_PLATFORM.physical_core_id(self.id)
+    }
+
+    fn l3_cache_domain_id(&self) -> ProcessorId {
+        // Processors only ever come from the real platform, which resolves caches on demand.
+        BUILD_TARGET_PLATFORM.l3_cache_domain_id(self.id)
+    }
+
+    fn base_frequency_mhz(&self) -> Option<u32> {
+        self.base_frequency_mhz
+    }
+
+    fn max_frequency_mhz(&self) -> Option<u32> {
+        self.max_frequency_mhz
+    }
 }
 
 impl PartialOrd for ProcessorImpl {
@@ -61,17 +94,29 @@ mod tests {
             id: 2,
             memory_region_id: 3,
             efficiency_class: EfficiencyClass::Performance,
+            package_id: 1,
+            features: ProcessorFeatures::default(),
+            base_frequency_mhz: Some(2000),
+            max_frequency_mhz: None,
             is_active: true,
         };
 
         assert_eq!(processor.id(), 2);
         assert_eq!(processor.memory_region_id(), 3);
         assert_eq!(processor.efficiency_class(), EfficiencyClass::Performance);
+        assert_eq!(processor.package_id(), 1);
+        assert_eq!(processor.features(), ProcessorFeatures::default());
+        assert_eq!(processor.base_frequency_mhz(), Some(2000));
+        assert_eq!(processor.max_frequency_mhz(), None);
 
         let processor2 = ProcessorImpl {
             id: 2,
             memory_region_id: 3,
             efficiency_class: EfficiencyClass::Performance,
+            package_id: 1,
+            features: ProcessorFeatures::default(),
+            base_frequency_mhz: Some(2000),
+            max_frequency_mhz: None,
             is_active: true,
         };
 
@@ -81,6 +126,10 @@ mod tests {
             id: 4,
             memory_region_id: 3,
             efficiency_class: EfficiencyClass::Performance,
+            package_id: 1,
+            features: ProcessorFeatures::default(),
+            base_frequency_mhz: Some(2000),
+            max_frequency_mhz: None,
             is_active: true,
         };
 
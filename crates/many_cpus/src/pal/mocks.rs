@@ -1,11 +1,14 @@
 #![expect(clippy::same_name_method, reason = "mock magic")]
 
+use std::{io, process::Command};
+
 use derive_more::derive::Display;
 use mockall::mock;
 use nonempty::NonEmpty;
 
 use crate::{
-    EfficiencyClass, MemoryRegionId, ProcessorId,
+    EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeatures, ProcessorId, ThreadPlacement,
+    ThreadPriority,
     pal::{AbstractProcessor, Platform, ProcessorFacade},
 };
 
@@ -15,14 +18,19 @@ pub(crate) struct FakeProcessor {
     pub(crate) index: ProcessorId,
     pub(crate) memory_region: MemoryRegionId,
     pub(crate) efficiency_class: EfficiencyClass,
+    pub(crate) package: PackageId,
 }
 
+pub(crate) const FAKE_PERFORMANCE_FREQUENCY_MHZ: u32 = 3000;
+pub(crate) const FAKE_EFFICIENCY_FREQUENCY_MHZ: u32 = 2000;
+
 impl FakeProcessor {
     pub(crate) fn with_index(index: ProcessorId) -> Self {
         Self {
             index,
             memory_region: 0,
             efficiency_class: EfficiencyClass::Performance,
+            package: 0,
         }
     }
 }
@@ -39,6 +47,37 @@ impl AbstractProcessor for FakeProcessor {
     fn efficiency_class(&self) -> EfficiencyClass {
         self.efficiency_class
     }
+
+    fn package_id(&self) -> PackageId {
+        self.package
+    }
+
+    fn features(&self) -> ProcessorFeatures {
+        // Fake processors do not support any optional instruction set features.
+        ProcessorFeatures::default()
+    }
+
+    fn physical_core_id(&self) -> ProcessorId {
+        // Every fake processor is its own core.
+        self.index
+    }
+
+    fn l3_cache_domain_id(&self) -> ProcessorId {
+        // Every fake processor is its own L3 cache domain.
+        self.index
+    }
+
+    fn base_frequency_mhz(&self) -> Option<u32> {
+        None
+    }
+
+    fn max_frequency_mhz(&self) -> Option<u32> {
+        // Like on real systems, the efficiency processors are the slower ones.
+        Some(match self.efficiency_class {
+            EfficiencyClass::Performance => FAKE_PERFORMANCE_FREQUENCY_MHZ,
+            EfficiencyClass::Efficiency => FAKE_EFFICIENCY_FREQUENCY_MHZ,
+        })
+    }
 }
 
 // Mockall is not able to express all methods on the trait (due to generics deficiency), so we mock
@@ -49,12 +88,24 @@ mock! {
     pub Platform {
         pub fn get_all_processors_core(&self) -> NonEmpty<ProcessorFacade>;
         pub fn pin_current_thread_to_core(&self, processors: Vec<ProcessorFacade>);
+        pub fn pin_command_to_core(&self, processors: Vec<ProcessorFacade>) -> io::Result<()>;
         pub fn current_processor_id(&self) -> ProcessorId;
         pub fn max_processor_id(&self) -> ProcessorId;
         pub fn max_memory_region_id(&self) -> MemoryRegionId;
         pub fn current_thread_processors(&self) -> NonEmpty<ProcessorId>;
         pub fn max_processor_time(&self) -> f64;
+        pub fn processor_weight(&self) -> Option<f64>;
         pub fn active_processor_count(&self) -> usize;
+        pub fn memory_region_distance(&self, from: MemoryRegionId, to: MemoryRegionId) -> u32;
+        pub fn physical_core_id(&self, processor_id: ProcessorId) -> ProcessorId;
+        pub fn l3_cache_domain_id(&self, processor_id: ProcessorId) -> ProcessorId;
+        pub fn current_process_threads(&self) -> Vec<ThreadPlacement>;
+        pub fn set_current_thread_priority(&self, priority: ThreadPriority) -> io::Result<()>;
+        pub fn interrupt_counts(&self) -> Vec<(ProcessorId, u64)>;
+        pub fn refresh_available_processors(&self);
+        pub fn online_processor_ids(&self) -> Vec<ProcessorId>;
+        pub fn online_memory_region_ids(&self) -> Vec<MemoryRegionId>;
+        pub fn pci_device_memory_region_id(&self, pci_address: &str) -> Option<MemoryRegionId>;
     }
 }
 
@@ -71,6 +122,14 @@ impl Platform for MockPlatform {
         self.pin_current_thread_to_core(processors);
     }
 
+    fn pin_command_to<P>(&self, _command: &mut Command, processors: &NonEmpty<P>) -> io::Result<()>
+    where
+        P: AsRef<ProcessorFacade>,
+    {
+        let processors = processors.iter().map(|p| *p.as_ref()).collect();
+        self.pin_command_to_core(processors)
+    }
+
     fn current_processor_id(&self) -> ProcessorId {
         self.current_processor_id()
     }
@@ -91,7 +150,51 @@ impl Platform for MockPlatform {
         self.max_processor_time()
     }
 
+    fn processor_weight(&self) -> Option<f64> {
+        self.processor_weight()
+    }
+
     fn active_processor_count(&self) -> usize {
         self.active_processor_count()
     }
+
+    fn memory_region_distance(&self, from: MemoryRegionId, to: MemoryRegionId) -> u32 {
+        self.memory_region_distance(from, to)
+    }
+
+    fn physical_core_id(&self, processor_id: ProcessorId) -> ProcessorId {
+        self.physical_core_id(processor_id)
+    }
+
+    fn l3_cache_domain_id(&self, processor_id: ProcessorId) -> ProcessorId {
+        self.l3_cache_domain_id(processor_id)
+    }
+
+    fn current_process_threads(&self) -> Vec<ThreadPlacement> {
+        self.current_process_threads()
+    }
+
+    fn set_current_thread_priority(&self, priority: ThreadPriority) -> io::Result<()> {
+        self.set_current_thread_priority(priority)
+    }
+
+    fn interrupt_counts(&self) -> Vec<(ProcessorId, u64)> {
+        self.interrupt_counts()
+    }
+
+    fn refresh_available_processors(&self) {
+        self.refresh_available_processors();
+    }
+
+    fn online_processor_ids(&self) -> Vec<ProcessorId> {
+        self.online_processor_ids()
+    }
+
+    fn online_memory_region_ids(&self) -> Vec<MemoryRegionId> {
+        self.online_memory_region_ids()
+    }
+
+    fn pci_device_memory_region_id(&self, pci_address: &str) -> Option<MemoryRegionId> {
+        self.pci_device_memory_region_id(pci_address)
+    }
 }
@@ -4,9 +4,12 @@ use windows::{
     Win32::System::{
         JobObjects::JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
         Kernel::PROCESSOR_NUMBER,
+        Power::PROCESSOR_POWER_INFORMATION,
         SystemInformation::{
             GROUP_AFFINITY, LOGICAL_PROCESSOR_RELATIONSHIP, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
         },
+        Threading::{PROCESSOR_FEATURE_ID, THREAD_PRIORITY},
+        WindowsProgramming::SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION,
     },
     core::Result,
 };
@@ -42,4 +45,20 @@ pub(crate) trait Bindings: Debug + Send + Sync + 'static {
     fn get_current_job_cpu_rate_control(&self) -> Option<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>;
 
     fn get_current_thread_legacy_group_affinity(&self) -> GROUP_AFFINITY;
+
+    fn set_current_thread_priority(&self, priority: THREAD_PRIORITY) -> Result<()>;
+
+    fn is_processor_feature_present(&self, feature: PROCESSOR_FEATURE_ID) -> bool;
+
+    // ProcessorInformation; one item for each of the first `processor_count` processors.
+    fn get_processor_power_information(
+        &self,
+        processor_count: usize,
+    ) -> Vec<PROCESSOR_POWER_INFORMATION>;
+
+    // SystemProcessorPerformanceInformation; only covers the processor group of the current
+    // thread, indexed by the processor number within the group.
+    fn get_current_group_processor_performance_information(
+        &self,
+    ) -> Vec<SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION>;
 }
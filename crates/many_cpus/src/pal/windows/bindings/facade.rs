@@ -7,9 +7,12 @@ use windows::{
     Win32::System::{
         JobObjects::JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
         Kernel::PROCESSOR_NUMBER,
+        Power::PROCESSOR_POWER_INFORMATION,
         SystemInformation::{
             GROUP_AFFINITY, LOGICAL_PROCESSOR_RELATIONSHIP, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
         },
+        Threading::{PROCESSOR_FEATURE_ID, THREAD_PRIORITY},
+        WindowsProgramming::SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION,
     },
     core::Result,
 };
@@ -154,6 +157,43 @@ impl Bindings for BindingsFacade {
             Self::Mock(bindings) => bindings.get_current_job_cpu_rate_control(),
         }
     }
+
+    fn set_current_thread_priority(&self, priority: THREAD_PRIORITY) -> Result<()> {
+        match self {
+            Self::Real(bindings) => bindings.set_current_thread_priority(priority),
+            #[cfg(test)]
+            Self::Mock(bindings) => bindings.set_current_thread_priority(priority),
+        }
+    }
+
+    fn is_processor_feature_present(&self, feature: PROCESSOR_FEATURE_ID) -> bool {
+        match self {
+            Self::Real(bindings) => bindings.is_processor_feature_present(feature),
+            #[cfg(test)]
+            Self::Mock(bindings) => bindings.is_processor_feature_present(feature),
+        }
+    }
+
+    fn get_processor_power_information(
+        &self,
+        processor_count: usize,
+    ) -> Vec<PROCESSOR_POWER_INFORMATION> {
+        match self {
+            Self::Real(bindings) => bindings.get_processor_power_information(processor_count),
+            #[cfg(test)]
+            Self::Mock(bindings) => bindings.get_processor_power_information(processor_count),
+        }
+    }
+
+    fn get_current_group_processor_performance_information(
+        &self,
+    ) -> Vec<SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION> {
+        match self {
+            Self::Real(bindings) => bindings.get_current_group_processor_performance_information(),
+            #[cfg(test)]
+            Self::Mock(bindings) => bindings.get_current_group_processor_performance_information(),
+        }
+    }
 }
 
 impl Debug for BindingsFacade {
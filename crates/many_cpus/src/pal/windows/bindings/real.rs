@@ -1,6 +1,9 @@
 use std::fmt::Debug;
 
 use windows::{
+    Wdk::System::SystemInformation::{
+        NtQuerySystemInformation, SystemProcessorPerformanceInformation,
+    },
     Win32::System::{
         JobObjects::{
             IsProcessInJob, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
@@ -8,6 +11,7 @@ use windows::{
             QueryInformationJobObject,
         },
         Kernel::PROCESSOR_NUMBER,
+        Power::{CallNtPowerInformation, PROCESSOR_POWER_INFORMATION, ProcessorInformation},
         SystemInformation::{
             GROUP_AFFINITY, GetLogicalProcessorInformationEx, LOGICAL_PROCESSOR_RELATIONSHIP,
             SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
@@ -16,8 +20,10 @@ use windows::{
             GetActiveProcessorCount, GetCurrentProcess, GetCurrentProcessorNumberEx,
             GetCurrentThread, GetMaximumProcessorCount, GetMaximumProcessorGroupCount,
             GetNumaHighestNodeNumber, GetProcessDefaultCpuSetMasks, GetThreadGroupAffinity,
-            GetThreadSelectedCpuSetMasks, SetThreadSelectedCpuSetMasks,
+            GetThreadSelectedCpuSetMasks, IsProcessorFeaturePresent, PROCESSOR_FEATURE_ID,
+            SetThreadPriority, SetThreadSelectedCpuSetMasks, THREAD_PRIORITY,
         },
+        WindowsProgramming::SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION,
     },
     core::{BOOL, Result},
 };
@@ -248,4 +254,83 @@ impl Bindings for BuildTargetBindings {
 
         Some(result)
     }
+
+    fn set_current_thread_priority(&self, priority: THREAD_PRIORITY) -> Result<()> {
+        // SAFETY: No safety requirements. Does not require closing the handle.
+        let current_thread = unsafe { GetCurrentThread() };
+
+        // SAFETY: No safety requirements beyond passing valid inputs.
+        unsafe { SetThreadPriority(current_thread, priority) }
+    }
+
+    fn is_processor_feature_present(&self, feature: PROCESSOR_FEATURE_ID) -> bool {
+        // SAFETY: No safety requirements.
+        unsafe { IsProcessorFeaturePresent(feature) }.as_bool()
+    }
+
+    fn get_processor_power_information(
+        &self,
+        processor_count: usize,
+    ) -> Vec<PROCESSOR_POWER_INFORMATION> {
+        let mut buffer = vec![PROCESSOR_POWER_INFORMATION::default(); processor_count];
+
+        let buffer_len_bytes = size_of_val(buffer.as_slice())
+            .try_into()
+            .expect("buffer for every processor guaranteed to fit in u32");
+
+        // SAFETY: No safety requirements beyond passing valid inputs.
+        unsafe {
+            CallNtPowerInformation(
+                ProcessorInformation,
+                None,
+                0,
+                Some(buffer.as_mut_ptr().cast()),
+                buffer_len_bytes,
+            )
+        }
+        .ok()
+        .expect("platform refused to provide the processor power information");
+
+        buffer
+    }
+
+    fn get_current_group_processor_performance_information(
+        &self,
+    ) -> Vec<SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION> {
+        // A processor group never has more processors than this.
+        const MAX_PROCESSORS_PER_GROUP: usize = 64;
+
+        let mut buffer =
+            vec![SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION::default(); MAX_PROCESSORS_PER_GROUP];
+
+        let mut bytes_written: u32 = 0;
+
+        let buffer_len_bytes = size_of_val(buffer.as_slice())
+            .try_into()
+            .expect("buffer of known size guaranteed to fit in u32");
+
+        // SAFETY: No safety requirements beyond passing valid inputs.
+        unsafe {
+            NtQuerySystemInformation(
+                SystemProcessorPerformanceInformation,
+                buffer.as_mut_ptr().cast(),
+                buffer_len_bytes,
+                &raw mut bytes_written,
+            )
+        }
+        .ok()
+        .expect("platform refused to provide the processor performance information");
+
+        buffer.truncate(
+            bytes_written
+                .checked_div(
+                    size_of::<SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION>()
+                        .try_into()
+                        .expect("struct of known size guaranteed to fit in u32"),
+                )
+                .expect("SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION is not a ZST, so there can be no division by zero")
+                as usize,
+        );
+        buffer
+    }
 }
@@ -1,7 +1,9 @@
 use std::{
     hint::black_box,
+    io,
     mem::offset_of,
     num::{NonZero, NonZeroUsize},
+    process::Command,
     ptr::NonNull,
     sync::OnceLock,
 };
@@ -16,11 +18,18 @@ use windows::{
         System::{
             JobObjects::{
                 JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
-                JOB_OBJECT_CPU_RATE_CONTROL_MIN_MAX_RATE, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+                JOB_OBJECT_CPU_RATE_CONTROL_MIN_MAX_RATE, JOB_OBJECT_CPU_RATE_CONTROL_WEIGHT_BASED,
+                JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
             },
             SystemInformation::{
-                GROUP_AFFINITY, LOGICAL_PROCESSOR_RELATIONSHIP, RelationNumaNode,
-                RelationNumaNodeEx, RelationProcessorCore, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+                GROUP_AFFINITY, LOGICAL_PROCESSOR_RELATIONSHIP, RelationCache, RelationNumaNode,
+                RelationNumaNodeEx, RelationProcessorCore, RelationProcessorPackage,
+                SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+            },
+            Threading::{
+                PF_AVX2_INSTRUCTIONS_AVAILABLE, PF_AVX512F_INSTRUCTIONS_AVAILABLE,
+                THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_HIGHEST, THREAD_PRIORITY_NORMAL,
+                THREAD_PRIORITY_TIME_CRITICAL,
             },
         },
     },
@@ -28,9 +37,10 @@ use windows::{
 };
 
 use crate::{
-    EfficiencyClass, MemoryRegionId, ProcessorId,
+    EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeature, ProcessorFeatures, ProcessorId,
+    ThreadPlacement, ThreadPriority,
     pal::{
-        GroupMask, Platform, ProcessorFacade, ProcessorImpl,
+        GroupMask, Platform, ProcessorFacade, ProcessorImpl, default_memory_region_distance,
         windows::{Bindings, BindingsFacade, ProcessorGroupIndex, ProcessorIndexInGroup},
     },
 };
@@ -68,6 +78,21 @@ pub struct BuildTargetPlatform {
 
     // Combines some of the above information to make it easier to work with.
     group_metas: OnceLock<Box<[ProcessorGroupMeta]>>,
+
+    // Indexed by processor ID.
+    physical_core_ids: OnceLock<Box<[ProcessorId]>>,
+
+    // Indexed by processor ID.
+    package_ids: OnceLock<Box<[PackageId]>>,
+
+    // Indexed by processor ID.
+    l3_cache_domain_ids: OnceLock<Box<[ProcessorId]>>,
+
+    // The same for every processor, as the platform only reports features for the system.
+    processor_features: OnceLock<ProcessorFeatures>,
+
+    // Indexed by processor ID. Zero if the platform did not report the processor.
+    base_frequencies_mhz: OnceLock<Box<[u32]>>,
 }
 
 #[derive(Debug)]
@@ -203,6 +228,19 @@ impl Platform for BuildTargetPlatform {
         self.bindings.get_numa_highest_node_number()
     }
 
+    fn pin_command_to<P>(&self, _command: &mut Command, _processors: &NonEmpty<P>) -> io::Result<()>
+    where
+        P: AsRef<ProcessorFacade>,
+    {
+        // The standard library does not allow us to specify the processor group affinity or CPU
+        // sets of a process at creation time and configuring them after the process has started
+        // would let the process run unconstrained for a while.
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pinning a process before it starts is not supported on Windows",
+        ))
+    }
+
     fn current_thread_processors(&self) -> NonEmpty<ProcessorId> {
         let mut current_thread_affinities = self.bindings.get_current_thread_cpu_set_masks();
 
@@ -340,6 +378,19 @@ impl Platform for BuildTargetPlatform {
         }
     }
 
+    fn processor_weight(&self) -> Option<f64> {
+        let rate_control = self.bindings.get_current_job_cpu_rate_control()?;
+
+        if !is_weight_based(rate_control) {
+            return None;
+        }
+
+        // SAFETY: Guarded by the flags we validated.
+        let weight = unsafe { rate_control.Anonymous.Weight };
+
+        Some(f64::from(weight) / f64::from(DEFAULT_JOB_CPU_RATE_WEIGHT))
+    }
+
     fn active_processor_count(&self) -> usize {
         self.active_processor_count
             .get_or_init(|| {
@@ -355,6 +406,111 @@ impl Platform for BuildTargetPlatform {
             })
             .get()
     }
+
+    fn memory_region_distance(&self, from: MemoryRegionId, to: MemoryRegionId) -> u32 {
+        // Windows does not expose the distances between NUMA nodes via public APIs.
+        default_memory_region_distance(from, to)
+    }
+
+    fn physical_core_id(&self, processor_id: ProcessorId) -> ProcessorId {
+        self.physical_core_ids
+            .get_or_init(|| self.get_physical_core_ids())
+            .get(processor_id as usize)
+            .copied()
+            .unwrap_or(processor_id)
+    }
+
+    fn l3_cache_domain_id(&self, processor_id: ProcessorId) -> ProcessorId {
+        self.l3_cache_domain_ids
+            .get_or_init(|| self.get_l3_cache_domain_ids())
+            .get(processor_id as usize)
+            .copied()
+            .unwrap_or(processor_id)
+    }
+
+    #[cfg_attr(test, mutants::skip)] // Nothing to test - there is no logic here.
+    fn current_process_threads(&self) -> Vec<ThreadPlacement> {
+        // Windows does not expose which processor another thread is executing on.
+        Vec::new()
+    }
+
+    fn set_current_thread_priority(&self, priority: ThreadPriority) -> io::Result<()> {
+        let priority = match priority {
+            ThreadPriority::Low => THREAD_PRIORITY_BELOW_NORMAL,
+            ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::High => THREAD_PRIORITY_HIGHEST,
+            ThreadPriority::Realtime => THREAD_PRIORITY_TIME_CRITICAL,
+        };
+
+        self.bindings
+            .set_current_thread_priority(priority)
+            .map_err(io::Error::other)
+    }
+
+    fn interrupt_counts(&self) -> Vec<(ProcessorId, u64)> {
+        // The platform only reports the processor group of the current thread, so we do not know
+        // anything about the processors in other groups.
+        let current_processor = self.bindings.get_current_processor_number_ex();
+
+        let group_start_offset = *self
+            .get_processor_group_start_offsets()
+            .get(current_processor.Group as usize)
+            .expect("the platform told us how many groups exist, so if it now tells us an out of bounds group, nothing we can do");
+
+        self.bindings
+            .get_current_group_processor_performance_information()
+            .into_iter()
+            .enumerate()
+            .map(|(index, information)| {
+                let processor_id = group_start_offset
+                    .checked_add(
+                        ProcessorId::try_from(index)
+                            .expect("a processor group has at most 64 processors"),
+                    )
+                    .expect("processor ID calculation overflowed - only possible if platform gives us bad IDs as inputs");
+
+                // The interrupt count is not in the public definition of the structure but it is
+                // documented as the field that follows the processor times. Windows does not
+                // separate device interrupts from the others (e.g. the clock), so this includes
+                // everything.
+                (processor_id, u64::from(information.Reserved2))
+            })
+            .collect()
+    }
+
+    fn refresh_available_processors(&self) {
+        // The job object constraints are only evaluated when the processor information is
+        // loaded, so there is nothing to refresh.
+    }
+
+    fn online_processor_ids(&self) -> Vec<ProcessorId> {
+        // The active processors of a group are always the first ones in the group. We ask for
+        // the active counts again instead of using the cached ones, as they may have changed.
+        self.get_processor_group_start_offsets()
+            .iter()
+            .zip(0..)
+            .flat_map(|(&group_start_offset, group_index)| {
+                let active_count = self.bindings.get_active_processor_count(group_index);
+
+                (0..active_count).map(move |index_in_group| {
+                    group_start_offset
+                        .checked_add(index_in_group)
+                        .expect("processor ID calculation overflowed - only possible if platform gives us bad IDs as inputs")
+                })
+            })
+            .collect()
+    }
+
+    fn online_memory_region_ids(&self) -> Vec<MemoryRegionId> {
+        (0..=self.bindings.get_numa_highest_node_number()).collect()
+    }
+
+    #[cfg_attr(test, mutants::skip)] // Trivial, nothing to test.
+    fn pci_device_memory_region_id(&self, _pci_address: &str) -> Option<MemoryRegionId> {
+        // Windows only exposes this through the device installation (SetupAPI) property store,
+        // which identifies devices by instance ID rather than by PCI address. Not supported yet.
+        None
+    }
 }
 
 impl BuildTargetPlatform {
@@ -368,9 +524,74 @@ impl BuildTargetPlatform {
             max_processor_id: OnceLock::new(),
             group_metas: OnceLock::new(),
             active_processor_count: OnceLock::new(),
+            physical_core_ids: OnceLock::new(),
+            package_ids: OnceLock::new(),
+            l3_cache_domain_ids: OnceLock::new(),
+            processor_features: OnceLock::new(),
+            base_frequencies_mhz: OnceLock::new(),
         }
     }
 
+    /// The physical package (socket) of the processor.
+    ///
+    /// This is resolved on first use instead of when enumerating the processors, as few callers
+    /// care about packages and it requires another walk over the processor topology.
+    pub(crate) fn package_id(&self, processor_id: ProcessorId) -> PackageId {
+        self.package_ids
+            .get_or_init(|| self.get_package_ids())
+            .get(processor_id as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The rated frequency of the processor in megahertz, if the platform reports it.
+    ///
+    /// This is resolved on first use instead of when enumerating the processors, as few callers
+    /// care about frequencies and it requires another query of the platform.
+    pub(crate) fn base_frequency_mhz(&self, processor_id: ProcessorId) -> Option<u32> {
+        self.base_frequencies_mhz
+            .get_or_init(|| self.get_base_frequencies_mhz())
+            .get(processor_id as usize)
+            .copied()
+            .filter(|mhz| *mhz != 0)
+    }
+
+    fn get_base_frequencies_mhz(&self) -> Box<[u32]> {
+        let mut frequencies = vec![0; self.max_processor_count()];
+
+        for information in self
+            .bindings
+            .get_processor_power_information(self.active_processor_count())
+        {
+            // The platform identifies the processor by its number, which is our processor ID.
+            if let Some(frequency) = frequencies.get_mut(information.Number as usize) {
+                *frequency = information.MaxMhz;
+            }
+        }
+
+        frequencies.into_boxed_slice()
+    }
+
+    /// The optional instruction set features of the processors.
+    ///
+    /// The platform does not report AMX or SVE, so these are never included.
+    pub(crate) fn processor_features(&self) -> ProcessorFeatures {
+        *self.processor_features.get_or_init(|| {
+            [
+                (PF_AVX2_INSTRUCTIONS_AVAILABLE, ProcessorFeature::Avx2),
+                (PF_AVX512F_INSTRUCTIONS_AVAILABLE, ProcessorFeature::Avx512),
+            ]
+            .into_iter()
+            .filter(|(platform_feature, _)| {
+                self.bindings
+                    .is_processor_feature_present(*platform_feature)
+            })
+            .fold(ProcessorFeatures::default(), |features, (_, feature)| {
+                features.with(feature)
+            })
+        })
+    }
+
     #[must_use]
     fn get_processor_group_max_count(&self) -> ProcessorGroupIndex {
         *self
@@ -555,6 +776,191 @@ impl BuildTargetPlatform {
         }
     }
 
+    /// Gets the physical core IDs (the lowest processor ID in the core) of all processors on the
+    /// system, ordered by processor ID. Processors not in any reported core are their own core.
+    fn get_physical_core_ids(&self) -> Box<[ProcessorId]> {
+        let mut core_ids = (0..=self.max_processor_id()).collect_vec();
+
+        let core_relationships_raw =
+            self.get_logical_processor_information_raw(RelationProcessorCore);
+
+        let raw_range = core_relationships_raw.as_data_ptr_range();
+        let mut next: NonNull<SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX> = raw_range.start.cast();
+        let end = raw_range.end.cast();
+
+        while next < end {
+            // SAFETY: We just process the data in the form the OS promises to give it to us.
+            let info = unsafe { next.as_ref() };
+
+            // SAFETY: We just process the data in the form the OS promises to give it to us.
+            next = unsafe { next.byte_add(info.Size as usize) };
+
+            assert_eq!(info.Relationship, RelationProcessorCore);
+
+            // SAFETY: Guarded via info.Relationship, asserted above.
+            let details = unsafe { &info.Anonymous.Processor };
+
+            // API docs: If the PROCESSOR_RELATIONSHIP structure represents a processor core,
+            // the GroupCount member is always 1.
+            assert_eq!(details.GroupCount, 1);
+
+            // With SMT (hyper-threading), there are multiple bits set in the mask of the core.
+            let processor_ids = self.affinity_mask_to_processor_ids(&details.GroupMask[0]);
+
+            let Some(core_id) = processor_ids.iter().min().copied() else {
+                continue;
+            };
+
+            for processor_id in processor_ids {
+                *core_ids.get_mut(processor_id as usize)
+                    .expect("the platform gave us a processor ID that was out of the range of valid processor IDs - it lied about the max ID!") = core_id;
+            }
+        }
+
+        core_ids.into_boxed_slice()
+    }
+
+    /// Gets the package IDs of all processors on the system, ordered by processor ID. Packages are
+    /// numbered in the order the operating system reports them, starting from zero.
+    fn get_package_ids(&self) -> Box<[PackageId]> {
+        let package_relationships_raw =
+            self.get_logical_processor_information_raw(RelationProcessorPackage);
+
+        let mut result = vec![0; self.max_processor_count()];
+
+        let raw_range = package_relationships_raw.as_data_ptr_range();
+        let mut next: NonNull<SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX> = raw_range.start.cast();
+        let end = raw_range.end.cast();
+
+        let mut package_id: PackageId = 0;
+
+        while next < end {
+            let current = next;
+
+            // SAFETY: We just process the data in the form the OS promises to give it to us.
+            let info = unsafe { current.as_ref() };
+
+            // SAFETY: We just process the data in the form the OS promises to give it to us.
+            next = unsafe { next.byte_add(info.Size as usize) };
+
+            assert_eq!(info.Relationship, RelationProcessorPackage);
+
+            // SAFETY: Guarded via info.Relationship, asserted above.
+            let details = unsafe { &info.Anonymous.Processor };
+
+            // Unlike for cores, a package may span multiple processor groups, so we use pointer
+            // arithmetic to access all the elements of the 1-element array in the definition.
+            //
+            // NOTE: that we need to start from scratch with the original pointer here!
+            // Pointer -> shared ref -> pointer conversions are not guaranteed to return the
+            // original pointer, so if we get rid of a pointer once, we cannot get it back!
+            //
+            // SAFETY: RelationProcessorPackage guarantees that this union member is present.
+            let mut group_mask_array = unsafe {
+                current
+                    .byte_add(offset_of!(
+                        SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+                        Anonymous.Processor.GroupMask
+                    ))
+                    .cast::<GROUP_AFFINITY>()
+            };
+
+            for _ in 0..details.GroupCount {
+                // SAFETY: The OS promises us that this array contains `GroupCount` elements.
+                let affinity = unsafe { *group_mask_array.as_ref() };
+
+                for processor_id in self.affinity_mask_to_processor_ids(&affinity) {
+                    *result.get_mut(processor_id as usize)
+                        .expect("the platform gave us a processor ID that was out of the range of valid processor IDs - it lied about the max ID!") = package_id;
+                }
+
+                // SAFETY: The OS promises us that this array contains `GroupCount` elements.
+                // It is fine to move past the end if we never access it (because the loop ends).
+                group_mask_array = unsafe { group_mask_array.add(1) };
+            }
+
+            package_id = package_id
+                .checked_add(1)
+                .expect("there cannot be more packages than fit in PackageId");
+        }
+
+        result.into_boxed_slice()
+    }
+
+    /// Gets the L3 cache domain IDs (the lowest processor ID sharing the L3 cache) of all
+    /// processors on the system, ordered by processor ID. Processors not sharing any reported
+    /// L3 cache are their own domain.
+    fn get_l3_cache_domain_ids(&self) -> Box<[ProcessorId]> {
+        let mut domain_ids = (0..=self.max_processor_id()).collect_vec();
+
+        let cache_relationships_raw = self.get_logical_processor_information_raw(RelationCache);
+
+        let raw_range = cache_relationships_raw.as_data_ptr_range();
+        let mut next: NonNull<SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX> = raw_range.start.cast();
+        let end = raw_range.end.cast();
+
+        while next < end {
+            let current = next;
+
+            // SAFETY: We just process the data in the form the OS promises to give it to us.
+            let info = unsafe { current.as_ref() };
+
+            // SAFETY: We just process the data in the form the OS promises to give it to us.
+            next = unsafe { next.byte_add(info.Size as usize) };
+
+            assert_eq!(info.Relationship, RelationCache);
+
+            // SAFETY: Guarded via info.Relationship, asserted above.
+            let details = unsafe { &info.Anonymous.Cache };
+
+            // Every cache of every level is listed separately - we only care about L3.
+            if details.Level != 3 {
+                continue;
+            }
+
+            // API docs: Older versions of Windows do not fill GroupCount, with zero meaning that
+            // there is exactly one group mask.
+            let group_count = details.GroupCount.max(1);
+
+            // We use pointer arithmetic to access all the elements of the 1-element array in the
+            // definition, starting from scratch with the original pointer, as with NUMA nodes.
+            //
+            // SAFETY: RelationCache guarantees that this union member is present.
+            let mut group_mask_array = unsafe {
+                current
+                    .byte_add(offset_of!(
+                        SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+                        Anonymous.Cache.Anonymous.GroupMasks
+                    ))
+                    .cast::<GROUP_AFFINITY>()
+            };
+
+            let mut processor_ids = Vec::new();
+
+            for _ in 0..group_count {
+                // SAFETY: The OS promises us that this array contains `GroupCount` elements.
+                let affinity = unsafe { *group_mask_array.as_ref() };
+
+                processor_ids.extend(self.affinity_mask_to_processor_ids(&affinity));
+
+                // SAFETY: The OS promises us that this array contains `GroupCount` elements.
+                // It is fine to move past the end if we never access it (because the loop ends).
+                group_mask_array = unsafe { group_mask_array.add(1) };
+            }
+
+            let Some(domain_id) = processor_ids.iter().min().copied() else {
+                continue;
+            };
+
+            for processor_id in processor_ids {
+                *domain_ids.get_mut(processor_id as usize)
+                    .expect("the platform gave us a processor ID that was out of the range of valid processor IDs - it lied about the max ID!") = domain_id;
+            }
+        }
+
+        domain_ids.into_boxed_slice()
+    }
+
     /// Gets the efficiency classes of all processors on the system, ordered by processor ID.
     /// This also returns data for offline processors but the value for those is unspecified.
     fn get_processor_efficiency_classes(&self) -> Box<[EfficiencyClass]> {
@@ -823,6 +1229,17 @@ fn is_soft_capped(rate_control: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION) -> bool
     rate_control.ControlFlags.0 & SOFT_CAP_FLAGS == SOFT_CAP_FLAGS
 }
 
+#[cfg_attr(test, mutants::skip)] // No-op mutates the constant, not helpful.
+fn is_weight_based(rate_control: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION) -> bool {
+    const WEIGHT_BASED_FLAGS: u32 =
+        JOB_OBJECT_CPU_RATE_CONTROL_ENABLE.0 | JOB_OBJECT_CPU_RATE_CONTROL_WEIGHT_BASED.0;
+
+    rate_control.ControlFlags.0 & WEIGHT_BASED_FLAGS == WEIGHT_BASED_FLAGS
+}
+
+/// Job CPU rate control weights range from 1 to 9, with this being the default.
+const DEFAULT_JOB_CPU_RATE_WEIGHT: u32 = 5;
+
 #[allow(
     clippy::arithmetic_side_effects,
     clippy::cast_possible_truncation,
@@ -844,6 +1261,8 @@ mod tests {
             JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0_0,
         },
         Kernel::PROCESSOR_NUMBER,
+        Power::PROCESSOR_POWER_INFORMATION,
+        WindowsProgramming::SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION,
     };
 
     use crate::{
@@ -2089,6 +2508,131 @@ mod tests {
         drop(platform.get_all_processors());
     }
 
+    #[test]
+    fn processor_features_from_system() {
+        let mut bindings = MockBindings::new();
+
+        bindings
+            .expect_is_processor_feature_present()
+            .times(2)
+            .returning(|feature| feature == PF_AVX2_INSTRUCTIONS_AVAILABLE);
+
+        let platform = BuildTargetPlatform::new(BindingsFacade::from_mock(bindings));
+
+        let features = platform.processor_features();
+        assert!(features.contains(ProcessorFeature::Avx2));
+        assert!(!features.contains(ProcessorFeature::Avx512));
+
+        // The features are only queried once.
+        assert_eq!(platform.processor_features(), features);
+    }
+
+    #[test]
+    fn base_frequencies_from_power_information() {
+        let mut bindings = MockBindings::new();
+        // 1 group, 2 active processors and 1 offline processor.
+        simulate_processor_layout(&mut bindings, [2], [3], [vec![0, 0]], [vec![0, 0]], None);
+
+        bindings
+            .expect_get_processor_power_information()
+            .withf(|count| *count == 2)
+            .times(1)
+            .return_once(|_| {
+                [(0, 3000), (1, 2200)]
+                    .into_iter()
+                    .map(|(number, max_mhz)| PROCESSOR_POWER_INFORMATION {
+                        Number: number,
+                        MaxMhz: max_mhz,
+                        ..Default::default()
+                    })
+                    .collect()
+            });
+
+        let platform = BuildTargetPlatform::new(BindingsFacade::from_mock(bindings));
+
+        assert_eq!(platform.base_frequency_mhz(0), Some(3000));
+        assert_eq!(platform.base_frequency_mhz(1), Some(2200));
+
+        // The offline processor is not reported.
+        assert_eq!(platform.base_frequency_mhz(2), None);
+
+        // We do this just to ensure we meet all the expectations declared by the simulation.
+        drop(platform.get_all_processors());
+    }
+
+    #[test]
+    fn interrupt_counts_cover_current_group() {
+        let mut bindings = MockBindings::new();
+        // 2 groups, 2 processors each group.
+        simulate_processor_layout(
+            &mut bindings,
+            [2, 2],
+            [2, 2],
+            [vec![0, 0], vec![0, 0]],
+            [vec![0, 0], vec![0, 0]],
+            None, // All processors are allowed by job constraints.
+        );
+
+        bindings
+            .expect_get_current_processor_number_ex()
+            .times(1)
+            .return_once(|| PROCESSOR_NUMBER {
+                Group: 1,
+                Number: 0,
+                ..Default::default()
+            });
+
+        bindings
+            .expect_get_current_group_processor_performance_information()
+            .times(1)
+            .return_once(|| {
+                [5000, 30]
+                    .into_iter()
+                    .map(|interrupt_count| SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION {
+                        Reserved2: interrupt_count,
+                        ..Default::default()
+                    })
+                    .collect()
+            });
+
+        let platform = BuildTargetPlatform::new(BindingsFacade::from_mock(bindings));
+
+        assert_eq!(platform.interrupt_counts(), [(2, 5000), (3, 30)]);
+
+        // We do this just to ensure we meet all the expectations declared by the simulation.
+        drop(platform.get_all_processors());
+    }
+
+    #[test]
+    fn online_ids_are_read_on_every_call() {
+        let mut bindings = MockBindings::new();
+
+        // 2 groups of 2 processors each, with the last one going offline before the second call.
+        bindings
+            .expect_get_maximum_processor_group_count()
+            .return_const(2_u16);
+        bindings
+            .expect_get_maximum_processor_count()
+            .return_const(2_u32);
+        bindings
+            .expect_get_active_processor_count()
+            .times(4)
+            .returning({
+                let mut values = [2, 2, 2, 1].into_iter();
+                move |_| values.next().unwrap()
+            });
+        bindings
+            .expect_get_numa_highest_node_number()
+            .times(1)
+            .return_const(1_u32);
+
+        let platform = BuildTargetPlatform::new(BindingsFacade::from_mock(bindings));
+
+        assert_eq!(platform.online_processor_ids(), [0, 1, 2, 3]);
+        assert_eq!(platform.online_processor_ids(), [0, 1, 2]);
+        assert_eq!(platform.online_memory_region_ids(), [0, 1]);
+    }
+
     #[test]
     fn max_processor_time_without_job() {
         // If there is no job, max_processor_time is the same as the number of available processors.
@@ -2191,6 +2735,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn processor_weight_from_weight_based_job() {
+        let mut bindings = MockBindings::new();
+
+        bindings
+            .expect_get_current_job_cpu_rate_control()
+            .times(3)
+            .returning({
+                let mut values = [
+                    Some(JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+                        ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL(
+                            JOB_OBJECT_CPU_RATE_CONTROL_ENABLE.0
+                                | JOB_OBJECT_CPU_RATE_CONTROL_WEIGHT_BASED.0,
+                        ),
+                        Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 {
+                            Weight: 8, // Above the default of 5.
+                        },
+                    }),
+                    // A hard cap is not a weight.
+                    Some(JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+                        ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL(
+                            JOB_OBJECT_CPU_RATE_CONTROL_ENABLE.0
+                                | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP.0,
+                        ),
+                        Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 { CpuRate: 5000 },
+                    }),
+                    // Not in a job.
+                    None,
+                ]
+                .into_iter();
+                move || values.next().unwrap()
+            });
+
+        let platform = BuildTargetPlatform::new(BindingsFacade::from_mock(bindings));
+
+        #[expect(
+            clippy::float_cmp,
+            reason = "we use absolute error, which is the right way to compare"
+        )]
+        {
+            assert_eq!(
+                f64_diff_abs(
+                    platform.processor_weight().unwrap(),
+                    1.6,
+                    PROCESSOR_TIME_CLOSE_ENOUGH
+                ),
+                0.0
+            );
+        }
+
+        assert!(platform.processor_weight().is_none());
+        assert!(platform.processor_weight().is_none());
+    }
+
     #[test]
     fn max_processor_time_below_available_soft_cap() {
         // If the job limit is less than the number of available processors,
@@ -1,9 +1,9 @@
 use std::fmt::Display;
 
 use crate::{
-    EfficiencyClass, MemoryRegionId, ProcessorId,
+    EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeatures, ProcessorId,
     pal::{
-        AbstractProcessor,
+        AbstractProcessor, BUILD_TARGET_PLATFORM, Platform,
         windows::{ProcessorGroupIndex, ProcessorIndexInGroup},
     },
 };
@@ -62,6 +62,36 @@ impl AbstractProcessor for ProcessorImpl {
     fn efficiency_class(&self) -> EfficiencyClass {
         self.efficiency_class
     }
+
+    fn package_id(&self) -> PackageId {
+        // Processors only ever come from the real platform, which resolves packages on demand.
+        BUILD_TARGET_PLATFORM.package_id(self.id)
+    }
+
+    fn features(&self) -> ProcessorFeatures {
+        // The platform only reports features for the system as a whole.
+        BUILD_TARGET_PLATFORM.processor_features()
+    }
+
+    fn physical_core_id(&self) -> ProcessorId {
+        // Processors only ever come from the real platform, which resolves cores on demand.
+        BUILD_TARGET_PLATFORM.physical_core_id(self.id)
+    }
+
+    fn l3_cache_domain_id(&self) -> ProcessorId {
+        // Processors only ever come from the real platform, which resolves caches on demand.
+        BUILD_TARGET_PLATFORM.l3_cache_domain_id(self.id)
+    }
+
+    fn base_frequency_mhz(&self) -> Option<u32> {
+        // Processors only ever come from the real platform, which resolves frequencies on demand.
+        BUILD_TARGET_PLATFORM.base_frequency_mhz(self.id)
+    }
+
+    fn max_frequency_mhz(&self) -> Option<u32> {
+        // The platform does not report boost frequencies.
+        None
+    }
 }
 
 impl AsRef<Self> for ProcessorImpl {
@@ -0,0 +1,394 @@
+use std::{hint::black_box, io, thread};
+
+use nonempty::NonEmpty;
+
+use crate::{Processor, ProcessorSet, ThreadPriority};
+
+/// The stack size we assume if the stack is to be faulted in but no stack size is specified.
+///
+/// This matches the default stack size of spawned threads documented by the Rust standard
+/// library. We set it explicitly when spawning, so the actual default does not matter.
+const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+/// How much of the stack we leave untouched when faulting it in, to account for what the
+/// operating system and the runtime place in the stack area (e.g. thread-local storage) and
+/// for the bookkeeping overhead of the faulting logic itself.
+const STACK_FAULT_RESERVE: usize = 128 * 1024;
+
+/// How much of the stack each step of the faulting logic touches. This is a multiple of the
+/// common page sizes, so every page is touched.
+const STACK_FAULT_STEP: usize = 16 * 1024;
+
+/// Spawns threads pinned to the processors of a [`ProcessorSet`], with custom thread settings.
+///
+/// Obtain a builder via [`ProcessorSet::thread_builder()`]. Without any customization, this
+/// behaves the same as [`ProcessorSet::spawn_thread()`] and [`ProcessorSet::spawn_threads()`],
+/// except that errors in spawning threads are returned instead of causing a panic.
+///
+/// # Memory region local stacks
+///
+/// The operating system typically allocates the physical memory of a page when the page is first
+/// touched, in the memory region of the processor that touches it. The stack of a thread is no
+/// exception, so a thread whose stack grows deep only after migrating to a different memory
+/// region, or whose stack pages happened to be touched elsewhere, pays the cost of accessing
+/// remote memory on every stack access.
+///
+/// With [`prefault_stack()`][Self::prefault_stack], each spawned thread touches the pages of its
+/// stack after being pinned and before the entrypoint is called, so the pages are allocated in
+/// the memory region of the processors the thread is pinned to. This is only meaningful if all
+/// the processors the thread is pinned to are in the same memory region, which is always the
+/// case with [`spawn_per_processor()`][Self::spawn_per_processor].
+///
+/// # Thread priority
+///
+/// With [`priority()`][Self::priority], each spawned thread sets its own scheduling priority
+/// after being pinned and before the entrypoint is called. See [`ThreadPriority`] for how each
+/// priority maps to the operating system.
+///
+/// # Example
+///
+/// ```
+/// use many_cpus::ProcessorSet;
+///
+/// let processors = ProcessorSet::default();
+///
+/// let threads = processors
+///     .thread_builder()
+///     .stack_size(16 * 1024 * 1024)
+///     .prefault_stack()
+///     .spawn_per_processor(|processor| processor.id())
+///     .unwrap();
+///
+/// for thread in threads {
+///     thread.join().unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct PinnedThreadBuilder {
+    processor_set: ProcessorSet,
+
+    stack_size: Option<usize>,
+    prefault_stack: bool,
+    priority: Option<ThreadPriority>,
+}
+
+impl PinnedThreadBuilder {
+    pub(crate) fn new(processor_set: ProcessorSet) -> Self {
+        Self {
+            processor_set,
+            stack_size: None,
+            prefault_stack: false,
+            priority: None,
+        }
+    }
+
+    /// Sets the size of the stack of each spawned thread, in bytes.
+    ///
+    /// The operating system may round this up (e.g. to a multiple of the page size). If not set,
+    /// the default of the Rust standard library is used.
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = Some(bytes);
+        self
+    }
+
+    /// Touches the pages of the stack of each spawned thread after the thread is pinned and
+    /// before the entrypoint is called, so the stack is allocated in the memory region of the
+    /// processors the thread is pinned to. See the [type-level documentation][Self] for details.
+    ///
+    /// If the stack size is not set, the stack size of each spawned thread is set to 2 MiB, so
+    /// we know how much of the stack to touch.
+    pub fn prefault_stack(mut self) -> Self {
+        self.prefault_stack = true;
+        self
+    }
+
+    /// Sets the scheduling priority of each spawned thread.
+    ///
+    /// If not set, the spawned threads keep whatever priority the operating system assigns to
+    /// new threads (typically inherited from the spawning thread).
+    ///
+    /// The priority is set by each spawned thread itself, so a failure to set it (e.g. due to
+    /// insufficient privileges) cannot be returned by the spawn methods. Instead, the spawned
+    /// thread panics before calling the entrypoint, which surfaces when the thread is joined.
+    pub fn priority(mut self, priority: ThreadPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Spawns a single thread pinned to the set, the same as [`ProcessorSet::spawn_thread()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operating system fails to create the thread.
+    ///
+    /// # Panics
+    ///
+    /// The spawned thread panics before calling the entrypoint if a [priority][Self::priority]
+    /// is set and the operating system refuses to apply it.
+    pub fn spawn<E, R>(&self, entrypoint: E) -> io::Result<thread::JoinHandle<R>>
+    where
+        E: FnOnce(ProcessorSet) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let set = self.processor_set.clone();
+        let prefault_stack_size = self.prefault_stack_size();
+        let priority = self.priority;
+
+        self.std_builder().spawn(move || {
+            set.pin_current_thread_to();
+            apply_priority(&set, priority);
+
+            if let Some(stack_size) = prefault_stack_size {
+                prefault_stack(stack_size);
+            }
+
+            entrypoint(set)
+        })
+    }
+
+    /// Spawns one thread for each processor in the set, pinned to that processor, the same as
+    /// [`ProcessorSet::spawn_threads()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operating system fails to create any of the threads. The threads
+    /// that were already spawned keep running.
+    ///
+    /// # Panics
+    ///
+    /// The spawned threads panic before calling the entrypoint if a [priority][Self::priority]
+    /// is set and the operating system refuses to apply it.
+    pub fn spawn_per_processor<E, R>(
+        &self,
+        entrypoint: E,
+    ) -> io::Result<Box<[thread::JoinHandle<R>]>>
+    where
+        E: Fn(Processor) -> R + Send + Clone + 'static,
+        R: Send + 'static,
+    {
+        let prefault_stack_size = self.prefault_stack_size();
+        let priority = self.priority;
+
+        self.processor_set
+            .processors()
+            .iter()
+            .map(|processor| {
+                let set = self
+                    .processor_set
+                    .with_processors(NonEmpty::new(processor.clone()));
+                let processor = processor.clone();
+                let entrypoint = entrypoint.clone();
+
+                self.std_builder().spawn(move || {
+                    set.pin_current_thread_to();
+                    apply_priority(&set, priority);
+
+                    if let Some(stack_size) = prefault_stack_size {
+                        prefault_stack(stack_size);
+                    }
+
+                    entrypoint(processor)
+                })
+            })
+            .collect()
+    }
+
+    fn std_builder(&self) -> thread::Builder {
+        let builder = thread::Builder::new();
+
+        match self.stack_size.or(self.prefault_stack_size()) {
+            Some(stack_size) => builder.stack_size(stack_size),
+            None => builder,
+        }
+    }
+
+    /// The stack size to fault in, if the stack is to be faulted in at all.
+    fn prefault_stack_size(&self) -> Option<usize> {
+        self.prefault_stack
+            .then(|| self.stack_size.unwrap_or(DEFAULT_STACK_SIZE))
+    }
+}
+
+/// Sets the priority of the current thread, if one is requested.
+///
+/// # Panics
+///
+/// Panics if the operating system refuses to apply the priority.
+fn apply_priority(set: &ProcessorSet, priority: Option<ThreadPriority>) {
+    if let Some(priority) = priority {
+        set.set_current_thread_priority(priority)
+            .unwrap_or_else(|e| {
+                panic!("failed to set the priority of the thread to {priority:?}: {e}")
+            });
+    }
+}
+
+/// Touches the pages of the stack of the current thread, assuming that the stack is `stack_size`
+/// bytes and that the current thread has only used a small part of it so far.
+fn prefault_stack(stack_size: usize) {
+    let steps = stack_size
+        .saturating_sub(STACK_FAULT_RESERVE)
+        .checked_div(STACK_FAULT_STEP)
+        .expect("the step is a non-zero constant");
+
+    touch_stack(steps);
+}
+
+/// Touches one step worth of the stack and recurses until `remaining_steps` is exhausted.
+#[inline(never)]
+fn touch_stack(remaining_steps: usize) {
+    let Some(remaining_steps) = remaining_steps.checked_sub(1) else {
+        return;
+    };
+
+    // The buffer must stay alive across the recursive call, so every step occupies its own
+    // part of the stack. Writing to it is what makes the operating system allocate the pages.
+    let mut buffer = [0_u8; STACK_FAULT_STEP];
+    _ = black_box(&mut buffer);
+
+    touch_stack(remaining_steps);
+
+    _ = black_box(&buffer);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use nonempty::nonempty;
+
+    use crate::{
+        HardwareTrackerClientFacade, MockHardwareTrackerClient,
+        pal::{FakeProcessor, MockPlatform, PlatformFacade},
+    };
+
+    use super::*;
+
+    #[test]
+    fn spawns_pinned_threads_with_prefaulted_stack() {
+        let mut platform = MockPlatform::new();
+
+        // Once for the single thread pinned to the entire set.
+        platform
+            .expect_pin_current_thread_to_core()
+            .times(1)
+            .withf(|p| p.len() == 2)
+            .return_const(());
+
+        // Once for each thread pinned to its own processor.
+        platform
+            .expect_pin_current_thread_to_core()
+            .times(2)
+            .withf(|p| p.len() == 1)
+            .return_const(());
+
+        let mut tracker_client = MockHardwareTrackerClient::new();
+        tracker_client.expect_update_pin_status().return_const(());
+
+        let processor_set = ProcessorSet::new(
+            nonempty![0, 1].map(|index| Processor::new(FakeProcessor::with_index(index).into())),
+            HardwareTrackerClientFacade::from_mock(tracker_client),
+            PlatformFacade::from_mock(platform),
+        );
+
+        let builder = processor_set
+            .thread_builder()
+            .stack_size(1024 * 1024)
+            .prefault_stack();
+
+        let len = builder.spawn(|set| set.len()).unwrap().join().unwrap();
+        assert_eq!(len, 2);
+
+        let threads_spawned = Arc::new(AtomicUsize::new(0));
+
+        let ids = builder
+            .spawn_per_processor({
+                let threads_spawned = Arc::clone(&threads_spawned);
+
+                move |processor| {
+                    threads_spawned.fetch_add(1, Ordering::Relaxed);
+                    processor.id()
+                }
+            })
+            .unwrap()
+            .into_vec()
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(threads_spawned.load(Ordering::Relaxed), 2);
+        assert!(ids.contains(&0) && ids.contains(&1));
+    }
+
+    #[test]
+    fn spawned_threads_apply_priority() {
+        let mut platform = MockPlatform::new();
+
+        platform
+            .expect_pin_current_thread_to_core()
+            .return_const(());
+
+        platform
+            .expect_set_current_thread_priority()
+            .withf(|priority| *priority == ThreadPriority::High)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        platform
+            .expect_set_current_thread_priority()
+            .withf(|priority| *priority == ThreadPriority::Realtime)
+            .times(1)
+            .returning(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+
+        let mut tracker_client = MockHardwareTrackerClient::new();
+        tracker_client.expect_update_pin_status().return_const(());
+
+        let processor_set = ProcessorSet::new(
+            nonempty![Processor::new(FakeProcessor::with_index(0).into())],
+            HardwareTrackerClientFacade::from_mock(tracker_client),
+            PlatformFacade::from_mock(platform),
+        );
+
+        let entrypoint_called = Arc::new(AtomicUsize::new(0));
+
+        let entrypoint = {
+            let entrypoint_called = Arc::clone(&entrypoint_called);
+
+            move |_| {
+                entrypoint_called.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+
+        processor_set
+            .thread_builder()
+            .priority(ThreadPriority::High)
+            .spawn(entrypoint.clone())
+            .unwrap()
+            .join()
+            .unwrap();
+
+        // The thread panics before calling the entrypoint if the priority cannot be set.
+        processor_set
+            .thread_builder()
+            .priority(ThreadPriority::Realtime)
+            .spawn(entrypoint)
+            .unwrap()
+            .join()
+            .unwrap_err();
+
+        assert_eq!(entrypoint_called.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn prefault_without_stack_size_uses_default() {
+        let builder = ProcessorSet::default().thread_builder().prefault_stack();
+
+        assert_eq!(builder.prefault_stack_size(), Some(DEFAULT_STACK_SIZE));
+
+        builder.spawn(|_| ()).unwrap().join().unwrap();
+    }
+}
@@ -12,12 +12,21 @@ pub type ProcessorId = u32;
 /// or to start from zero (aspects that are also not guaranteed by operating system tooling).
 pub type MemoryRegionId = u32;
 
+/// A physical package (socket) identifier, used to differentiate the processor packages in the
+/// system. This will match the numeric identifier used by standard tooling of the operating system
+/// where the operating system exposes one.
+///
+/// It is important to highlight that the values used are not guaranteed to be sequential/contiguous
+/// or to start from zero (aspects that are also not guaranteed by operating system tooling).
+pub type PackageId = u32;
+
 /// Differentiates processors by their efficiency class, allowing work requiring high
 /// performance to be placed on the most performant processors at the expense of energy usage.
 ///
 /// This is a relative measurement - the most performant processors in a system are always
 /// considered performance processors, with less performant ones considered efficiency processors.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[expect(
     clippy::exhaustive_enums,
     reason = "mirroring two-tier structure of platform APIs"
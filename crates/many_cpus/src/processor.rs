@@ -6,7 +6,7 @@ use std::{
 use derive_more::derive::AsRef;
 
 use crate::{
-    EfficiencyClass, MemoryRegionId, ProcessorId,
+    EfficiencyClass, MemoryRegionId, PackageId, ProcessorFeature, ProcessorId, ProcessorSet,
     pal::{AbstractProcessor, ProcessorFacade},
 };
 
@@ -52,12 +52,127 @@ impl Processor {
     /// This is a relative measure - the fastest processors on any given system are always
     /// considered performance processors, while any that are slower are considered efficiency
     /// processors.
+    ///
+    /// On hybrid processors, the efficiency class matches the core type reported by the platform
+    /// (e.g. P-cores and E-cores on Intel, big and LITTLE cores on ARM), so results measured on
+    /// processors of the same efficiency class are comparable.
     #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
     #[inline]
     #[must_use]
     pub fn efficiency_class(&self) -> EfficiencyClass {
         self.inner.efficiency_class()
     }
+
+    /// The numeric ID of the physical package (socket) the processor is in, matching the ID used
+    /// by operating system tools.
+    ///
+    /// On systems with a single package, or where the operating system does not expose package
+    /// information, all processors are in package 0.
+    #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
+    #[inline]
+    #[must_use]
+    pub fn package_id(&self) -> PackageId {
+        self.inner.package_id()
+    }
+
+    /// Whether the processor supports an optional instruction set feature.
+    ///
+    /// See [`ProcessorFeature`] for the limitations of the platforms.
+    #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
+    #[inline]
+    #[must_use]
+    pub fn has_feature(&self, feature: ProcessorFeature) -> bool {
+        self.inner.features().contains(feature)
+    }
+
+    /// Identifies the physical processor core the processor belongs to, by the lowest ID of any
+    /// processor in the same core.
+    ///
+    /// Processors that share a core via simultaneous multithreading (SMT, e.g. hyper-threading)
+    /// have the same core ID, so this identifies the SMT siblings of a processor. If the platform
+    /// does not report the physical cores, every processor is its own core.
+    ///
+    /// The lowest ID in the core may belong to a processor that is not available to the current
+    /// process (e.g. due to processor affinity), in which case no available processor has an ID
+    /// equal to its core ID.
+    #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
+    #[inline]
+    #[must_use]
+    pub fn physical_core_id(&self) -> ProcessorId {
+        self.inner.physical_core_id()
+    }
+
+    /// The other processors in the same physical core as this processor (its SMT siblings) that
+    /// are available to the current process, sorted by the processor ID.
+    ///
+    /// This is empty if the core has no other processors, if the platform does not report the
+    /// physical cores or if the siblings are not available to the current process. The resource
+    /// quota of the process does not limit the siblings returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// let worker_processor = ProcessorSet::default().processors().first().clone();
+    ///
+    /// // A helper thread on a sibling shares the caches of the worker but not its processor.
+    /// let helper_processors = worker_processor.smt_siblings();
+    ///
+    /// if let Some(sibling) = helper_processors.first() {
+    ///     println!("Helper thread can use processor {sibling}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn smt_siblings(&self) -> Vec<Self> {
+        let core_id = self.physical_core_id();
+
+        let mut siblings = ProcessorSet::builder()
+            .ignoring_resource_quota()
+            .filter(|p| p != self && p.physical_core_id() == core_id)
+            .take_all()
+            .map(|set| set.processors().iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        siblings.sort_by_key(Self::id);
+        siblings
+    }
+
+    /// Identifies the last level cache (L3) domain the processor belongs to, by the lowest ID
+    /// of any processor sharing the same L3 cache.
+    ///
+    /// See [`ProcessorSetBuilder::same_l3_domain()`][crate::ProcessorSetBuilder::same_l3_domain]
+    /// for details on L3 domains. If the platform does not report the L3 caches, every processor
+    /// is its own domain.
+    #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
+    #[inline]
+    #[must_use]
+    pub fn l3_cache_domain_id(&self) -> ProcessorId {
+        self.inner.l3_cache_domain_id()
+    }
+
+    /// The base (nominal) frequency of the processor in megahertz, if the platform reports it.
+    ///
+    /// On Linux, this is only reported by some frequency scaling drivers (e.g. `intel_pstate`).
+    /// On Windows, this is the rated frequency of the processor.
+    #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
+    #[inline]
+    #[must_use]
+    pub fn base_frequency_mhz(&self) -> Option<u32> {
+        self.inner.base_frequency_mhz()
+    }
+
+    /// The maximum frequency of the processor in megahertz (including any boost or turbo
+    /// frequencies), if the platform reports it.
+    ///
+    /// On Linux, this is reported by the frequency scaling driver, which virtual machines often
+    /// lack. Windows does not report the maximum frequency.
+    #[cfg_attr(test, mutants::skip)] // Trivial delegation, do not waste time on mutation.
+    #[inline]
+    #[must_use]
+    pub fn max_frequency_mhz(&self) -> Option<u32> {
+        self.inner.max_frequency_mhz()
+    }
 }
 
 impl PartialEq for Processor {
@@ -108,6 +223,7 @@ mod tests {
             index: 42,
             memory_region: 13,
             efficiency_class: EfficiencyClass::Efficiency,
+            package: 2,
         };
 
         let processor = Processor::new(pal_processor.into());
@@ -115,7 +231,10 @@ mod tests {
         // Getters appear to get the expected values.
         assert_eq!(processor.id(), 42);
         assert_eq!(processor.memory_region_id(), 13);
+        assert_eq!(processor.package_id(), 2);
         assert_eq!(processor.efficiency_class(), EfficiencyClass::Efficiency);
+        assert_eq!(processor.physical_core_id(), 42);
+        assert_eq!(processor.l3_cache_domain_id(), 42);
 
         // A clone is a legit clone.
         #[expect(clippy::redundant_clone, reason = "testing")]
@@ -141,4 +260,24 @@ mod tests {
         let debugged = format!("{processor:?}");
         assert!(!debugged.is_empty());
     }
+
+    #[test]
+    #[cfg(not(miri))] // Miri does not support talking to the real platform.
+    fn real_smt_siblings_share_core() {
+        let processors = ProcessorSet::builder()
+            .ignoring_resource_quota()
+            .take_all()
+            .unwrap();
+
+        for processor in processors.processors() {
+            let siblings = processor.smt_siblings();
+
+            for sibling in &siblings {
+                assert_ne!(sibling, processor);
+                assert_eq!(sibling.physical_core_id(), processor.physical_core_id());
+            }
+
+            assert!(siblings.is_sorted_by_key(Processor::id));
+        }
+    }
 }
@@ -0,0 +1,53 @@
+/// An optional instruction set feature that a [`Processor`][crate::Processor] may support.
+///
+/// On heterogeneous systems, some processors may lack features that others have, so code that
+/// dispatches to specialized SIMD code paths needs to know which processors it may execute on.
+/// Use [`Processor::has_feature()`][crate::Processor::has_feature] to inspect a processor or
+/// [`ProcessorSetBuilder::requiring_feature()`][crate::ProcessorSetBuilder::requiring_feature]
+/// to only select processors that support a feature.
+///
+/// On Linux, the features are read from the flags of each processor in `/proc/cpuinfo`. Windows
+/// only reports features for the system as a whole and does not report AMX or SVE, so on Windows
+/// either every processor has a feature or none does.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ProcessorFeature {
+    /// The AVX2 extensions of the x86-64 instruction set.
+    Avx2,
+
+    /// The AVX-512 foundation extensions of the x86-64 instruction set (AVX-512F).
+    Avx512,
+
+    /// The Advanced Matrix Extensions of the x86-64 instruction set (AMX-TILE).
+    Amx,
+
+    /// The Scalable Vector Extension of the Arm instruction set.
+    Sve,
+}
+
+impl ProcessorFeature {
+    const fn bit(self) -> u8 {
+        match self {
+            Self::Avx2 => 0b0001,
+            Self::Avx512 => 0b0010,
+            Self::Amx => 0b0100,
+            Self::Sve => 0b1000,
+        }
+    }
+}
+
+/// The set of [`ProcessorFeature`]s that a processor supports.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub(crate) struct ProcessorFeatures(u8);
+
+impl ProcessorFeatures {
+    #[must_use]
+    pub(crate) const fn with(self, feature: ProcessorFeature) -> Self {
+        Self(self.0 | feature.bit())
+    }
+
+    #[must_use]
+    pub(crate) const fn contains(self, feature: ProcessorFeature) -> bool {
+        self.0 & feature.bit() != 0
+    }
+}
@@ -0,0 +1,144 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use foldhash::HashSet;
+
+use crate::{ProcessorId, ProcessorSet};
+
+/// How many reservations exist for each reserved processor, across the entire process.
+///
+/// Processors without any reservations are not present.
+static RESERVATIONS: Mutex<BTreeMap<ProcessorId, usize>> = Mutex::new(BTreeMap::new());
+
+/// A reservation of the processors in a [`ProcessorSet`], created via
+/// [`ProcessorSet::reserve()`]. The processors are released when this is dropped.
+///
+/// Reservations are a process-wide registry that allows independent subsystems of the same
+/// process (e.g. different libraries that each spawn their own pinned threads) to coordinate
+/// which processors they use. Any subsystem can exclude the processors reserved by others via
+/// [`ProcessorSetBuilder::except_reserved()`][crate::ProcessorSetBuilder::except_reserved].
+///
+/// Reservations are advisory - they do not prevent anyone from using the reserved processors,
+/// they only inform the builders that choose to respect them. The same processor may be reserved
+/// multiple times, in which case it stays reserved until all the reservations are dropped.
+///
+/// # Example
+///
+/// ```
+/// use many_cpus::ProcessorSet;
+///
+/// let mine = ProcessorSet::builder().take_all().unwrap();
+/// let _reservation = mine.reserve();
+///
+/// // Anyone who respects reservations now stays away from our processors.
+/// if let Some(others) = ProcessorSet::builder().except_reserved().take_all() {
+///     assert!(others.intersection(&mine).is_none());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProcessorReservation {
+    processors: ProcessorSet,
+}
+
+impl ProcessorReservation {
+    #[must_use]
+    pub(crate) fn new(processors: ProcessorSet) -> Self {
+        let mut reservations = RESERVATIONS.lock().expect(RESERVATIONS_POISONED);
+
+        for processor in processors.processors() {
+            let count = reservations.entry(processor.id()).or_default();
+            *count = count
+                .checked_add(1)
+                .expect("we will never have so many reservations that we overflow usize");
+        }
+
+        Self { processors }
+    }
+
+    /// The processors that are reserved.
+    #[must_use]
+    #[inline]
+    pub fn processors(&self) -> &ProcessorSet {
+        &self.processors
+    }
+}
+
+impl Drop for ProcessorReservation {
+    fn drop(&mut self) {
+        let Ok(mut reservations) = RESERVATIONS.lock() else {
+            // Someone panicked while holding the lock, so the registry is already
+            // inconsistent. We do not want to turn that into a double panic.
+            return;
+        };
+
+        for processor in self.processors.processors() {
+            let count = reservations
+                .get_mut(&processor.id())
+                .expect("every processor of a reservation is in the registry until it is dropped");
+
+            *count = count
+                .checked_sub(1)
+                .expect("every reservation is counted exactly once");
+
+            if *count == 0 {
+                reservations.remove(&processor.id());
+            }
+        }
+    }
+}
+
+/// The IDs of all the processors reserved in the current process.
+#[must_use]
+pub(crate) fn reserved_processor_ids() -> HashSet<ProcessorId> {
+    RESERVATIONS
+        .lock()
+        .expect(RESERVATIONS_POISONED)
+        .keys()
+        .copied()
+        .collect()
+}
+
+const RESERVATIONS_POISONED: &str = "processor reservation registry poisoned - a reservation must have panicked while being created";
+
+#[cfg(test)]
+mod tests {
+    use nonempty::{NonEmpty, nonempty};
+
+    use crate::{
+        HardwareTrackerClientFacade, Processor,
+        pal::{FakeProcessor, MockPlatform, PlatformFacade},
+    };
+
+    use super::*;
+
+    fn new_processor_set(indexes: NonEmpty<ProcessorId>) -> ProcessorSet {
+        ProcessorSet::new(
+            indexes.map(|index| Processor::new(FakeProcessor::with_index(index).into())),
+            HardwareTrackerClientFacade::default_mock(),
+            PlatformFacade::from_mock(MockPlatform::new()),
+        )
+    }
+
+    #[test]
+    fn reserved_until_all_reservations_dropped() {
+        // The registry is process-wide, so we use processor IDs no other test uses.
+        let first = new_processor_set(nonempty![9000, 9001]);
+        let second = new_processor_set(nonempty![9001, 9002]);
+
+        let first_reservation = first.reserve();
+        let second_reservation = second.reserve();
+
+        let reserved = |id| reserved_processor_ids().contains(&id);
+
+        assert!(reserved(9000) && reserved(9001) && reserved(9002));
+
+        drop(first_reservation);
+
+        assert!(!reserved(9000));
+        assert!(reserved(9001) && reserved(9002));
+
+        assert_eq!(second_reservation.processors().len(), 2);
+        drop(second_reservation);
+
+        assert!(!reserved(9000) && !reserved(9001) && !reserved(9002));
+    }
+}
@@ -0,0 +1,273 @@
+use std::{error::Error, fmt};
+
+use derive_more::derive::Display;
+
+/// Explains why a [`ProcessorSetBuilder`][1] could not create a processor set, returned by
+/// [`try_take()`][2] and [`try_take_all()`][3].
+///
+/// The explanation lists how many processors each constraint of the builder removed from the
+/// candidates, in the order the constraints were applied, followed by the reason the remaining
+/// candidates could not satisfy the request.
+///
+/// # Example
+///
+/// ```
+/// use many_cpus::ProcessorSet;
+///
+/// let result = ProcessorSet::builder()
+///     .filter(|_| false)
+///     .try_take_all();
+///
+/// let error = result.unwrap_err();
+///
+/// // E.g. "no candidate processors remained (filter() removed 16, 0 remained)"
+/// println!("{error}");
+/// ```
+///
+/// [1]: crate::ProcessorSetBuilder
+/// [2]: crate::ProcessorSetBuilder::try_take
+/// [3]: crate::ProcessorSetBuilder::try_take_all
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcessorSelectionError {
+    eliminations: Vec<ConstraintElimination>,
+    failure: SelectionFailure,
+}
+
+impl ProcessorSelectionError {
+    #[must_use]
+    pub(crate) fn new(eliminations: Vec<ConstraintElimination>, failure: SelectionFailure) -> Self {
+        Self {
+            eliminations,
+            failure,
+        }
+    }
+
+    /// How many processors each constraint removed from the candidates, in the order the
+    /// constraints were applied.
+    ///
+    /// This is empty if the request failed before any processors were considered, e.g. because
+    /// the requested processor count exceeds the resource quota.
+    #[must_use]
+    #[inline]
+    pub fn eliminations(&self) -> &[ConstraintElimination] {
+        &self.eliminations
+    }
+
+    /// The reason the remaining candidates could not satisfy the request.
+    #[must_use]
+    #[inline]
+    pub fn failure(&self) -> SelectionFailure {
+        self.failure
+    }
+}
+
+impl fmt::Display for ProcessorSelectionError {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for the exact message.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.failure)?;
+
+        let Some(last) = self.eliminations.last() else {
+            return Ok(());
+        };
+
+        write!(f, " (")?;
+
+        for elimination in &self.eliminations {
+            write!(
+                f,
+                "{} removed {}, ",
+                elimination.constraint, elimination.removed_count
+            )?;
+        }
+
+        write!(f, "{} remained)", last.remaining_count)
+    }
+}
+
+impl Error for ProcessorSelectionError {}
+
+/// How many processors one constraint of a [`ProcessorSetBuilder`][1] removed from the
+/// candidates, as part of a [`ProcessorSelectionError`].
+///
+/// [1]: crate::ProcessorSetBuilder
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConstraintElimination {
+    constraint: SelectionConstraint,
+    removed_count: usize,
+    remaining_count: usize,
+}
+
+impl ConstraintElimination {
+    #[must_use]
+    pub(crate) fn new(
+        constraint: SelectionConstraint,
+        removed_count: usize,
+        remaining_count: usize,
+    ) -> Self {
+        Self {
+            constraint,
+            removed_count,
+            remaining_count,
+        }
+    }
+
+    /// The constraint that removed the processors.
+    #[must_use]
+    #[inline]
+    pub fn constraint(&self) -> SelectionConstraint {
+        self.constraint
+    }
+
+    /// How many of the candidates remaining before the constraint was applied were removed by it.
+    ///
+    /// Processors already removed by an earlier constraint are not counted again.
+    #[must_use]
+    #[inline]
+    pub fn removed_count(&self) -> usize {
+        self.removed_count
+    }
+
+    /// How many candidates remained after the constraint was applied.
+    #[must_use]
+    #[inline]
+    pub fn remaining_count(&self) -> usize {
+        self.remaining_count
+    }
+}
+
+/// A constraint of a [`ProcessorSetBuilder`][1] that removes processors from the candidates.
+///
+/// [1]: crate::ProcessorSetBuilder
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SelectionConstraint {
+    /// [`ProcessorSetBuilder::filter()`][crate::ProcessorSetBuilder::filter].
+    #[display("filter()")]
+    Filter,
+
+    /// [`ProcessorSetBuilder::except()`][crate::ProcessorSetBuilder::except].
+    #[display("except()")]
+    Except,
+
+    /// [`ProcessorSetBuilder::where_available_for_current_thread()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::where_available_for_current_thread
+    #[display("where_available_for_current_thread()")]
+    AvailableForCurrentThread,
+
+    /// [`ProcessorSetBuilder::performance_processors_only()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::performance_processors_only
+    #[display("performance_processors_only()")]
+    PerformanceProcessorsOnly,
+
+    /// [`ProcessorSetBuilder::efficiency_processors_only()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::efficiency_processors_only
+    #[display("efficiency_processors_only()")]
+    EfficiencyProcessorsOnly,
+
+    /// [`ProcessorSetBuilder::distinct_cores()`][crate::ProcessorSetBuilder::distinct_cores].
+    #[display("distinct_cores()")]
+    DistinctCores,
+
+    /// [`ProcessorSetBuilder::except_reserved()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::except_reserved
+    #[display("except_reserved()")]
+    ExceptReserved,
+
+    /// [`ProcessorSetBuilder::excluding_irq_heavy()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::excluding_irq_heavy
+    #[display("excluding_irq_heavy()")]
+    ExcludingIrqHeavy,
+
+    /// [`ProcessorSetBuilder::requiring_feature()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::requiring_feature
+    #[display("requiring_feature()")]
+    RequiringFeature,
+
+    /// [`ProcessorSetBuilder::frequency_at_least()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::frequency_at_least
+    #[display("frequency_at_least()")]
+    FrequencyAtLeast,
+
+    /// [`ProcessorSetBuilder::max_distance_from()`][1].
+    ///
+    /// [1]: crate::ProcessorSetBuilder::max_distance_from
+    #[display("max_distance_from()")]
+    MaxDistanceFrom,
+
+    /// [`ProcessorSetBuilder::package()`][crate::ProcessorSetBuilder::package].
+    #[display("package()")]
+    Package,
+
+    /// [`ProcessorSetBuilder::same_package()`][crate::ProcessorSetBuilder::same_package].
+    #[display("same_package()")]
+    SamePackage,
+
+    /// [`ProcessorSetBuilder::same_l3_domain()`][crate::ProcessorSetBuilder::same_l3_domain].
+    #[display("same_l3_domain()")]
+    SameL3Domain,
+}
+
+/// The reason the candidates remaining after applying the constraints of a
+/// [`ProcessorSetBuilder`][1] could not satisfy the request.
+///
+/// [1]: crate::ProcessorSetBuilder
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SelectionFailure {
+    /// The constraints removed every processor from the candidates.
+    #[display("no candidate processors remained")]
+    NoCandidates,
+
+    /// More processors were requested than the process resource quota allows.
+    #[display("{requested} processors requested but the resource quota allows at most {limit}")]
+    ResourceQuotaExceeded {
+        /// The number of processors requested.
+        requested: usize,
+
+        /// The maximum number of processors the resource quota allows.
+        limit: usize,
+    },
+
+    /// Fewer candidates remained than the number of processors requested.
+    #[display("{requested} processors requested but only {candidates} candidates remained")]
+    NotEnoughCandidates {
+        /// The number of processors requested.
+        requested: usize,
+
+        /// The number of candidates that remained.
+        candidates: usize,
+    },
+
+    /// No memory region had as many candidates as the number of processors requested, as
+    /// required by [`same_memory_region()`][crate::ProcessorSetBuilder::same_memory_region].
+    #[display(
+        "{requested} processors requested from the same memory region but the largest memory region only has {largest_memory_region} candidates"
+    )]
+    NotEnoughInAnyMemoryRegion {
+        /// The number of processors requested.
+        requested: usize,
+
+        /// The number of candidates in the memory region with the most candidates.
+        largest_memory_region: usize,
+    },
+
+    /// Fewer memory regions had candidates than the number of processors requested, as required
+    /// by [`different_memory_regions()`][crate::ProcessorSetBuilder::different_memory_regions].
+    #[display(
+        "{requested} processors requested from different memory regions but only {memory_regions} memory regions have candidates"
+    )]
+    NotEnoughMemoryRegions {
+        /// The number of processors requested.
+        requested: usize,
+
+        /// The number of memory regions with at least one candidate.
+        memory_regions: usize,
+    },
+}
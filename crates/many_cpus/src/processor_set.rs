@@ -1,11 +1,23 @@
-use std::{fmt::Display, sync::LazyLock, thread};
+use std::{
+    fmt::Display,
+    io,
+    process::{Child, Command},
+    sync::LazyLock,
+    thread,
+    time::{Duration, Instant},
+};
 
+use foldhash::HashSet;
 use itertools::Itertools;
 use nonempty::NonEmpty;
 
 use crate::{
-    HardwareTrackerClient, HardwareTrackerClientFacade, Processor, ProcessorSetBuilder,
+    CpulistError, HardwareTrackerClient, HardwareTrackerClientFacade, MemoryRegionId,
+    MemoryRegionProcess, PinnedThreadBuilder, Processor, ProcessorId, ProcessorReservation,
+    ProcessorSetBuilder, ProcessorSetChange, ThreadPinGuard, ThreadPlacement, ThreadPriority,
+    WatchedProcessorSet,
     pal::{Platform, PlatformFacade},
+    resource_quota::processor_count_limit,
 };
 
 // https://github.com/cloudhead/nonempty/issues/68
@@ -37,6 +49,11 @@ static DEFAULT_PROCESSORS: LazyLock<ProcessorSet> = LazyLock::new(|| {
 ///    will only be scheduled to run on the processors in the set.
 /// 3. You can use [`ProcessorSet::spawn_threads()`] to spawn a set of threads, with one thread
 ///    for each of the processors in the set. Each thread will be pinned to its own processor.
+/// 4. You can use [`ProcessorSet::thread_builder()`] to do either of the above with custom
+///    thread settings, such as the stack size.
+///
+/// The processor set can also be applied to child processes, via
+/// [`ProcessorSet::pin_command_to()`] or [`ProcessorSet::spawn_processes_per_memory_region()`].
 ///
 #[doc = include_str!("../docs/snippets/changes_at_runtime.md")]
 #[derive(Clone, Debug)]
@@ -59,6 +76,23 @@ impl ProcessorSet {
         ProcessorSetBuilder::default()
     }
 
+    /// Creates a processor set that starts out as `ProcessorSet::builder().take_all()` and
+    /// follows any changes in the constraints applied to the process at runtime (e.g. its
+    /// resource quota), calling `on_change` whenever processors join or leave the set.
+    ///
+    /// Changes are only observed while the hardware information is being polled in the
+    /// background - see [`WatchedProcessorSet`] for details.
+    #[must_use]
+    pub fn watching_default(
+        on_change: impl Fn(&ProcessorSetChange) + Send + Sync + 'static,
+    ) -> WatchedProcessorSet {
+        let initial = Self::builder()
+            .take_all()
+            .expect("there must be at least one processor - how could this code run if not");
+
+        WatchedProcessorSet::new(initial, on_change)
+    }
+
     /// Returns a [`ProcessorSetBuilder`] that is narrowed down to all processors in this
     /// processor set.
     ///
@@ -112,6 +146,103 @@ impl ProcessorSet {
         )
     }
 
+    /// Returns a [`ProcessorSet`] containing the processors listed in a [cpulist][1] string
+    /// (e.g. `0-3,8-11`), the format used by `taskset`, the systemd `AllowedCPUs` setting and
+    /// other tools that exchange sets of processors.
+    ///
+    /// Every listed processor must exist on the system and be available to the current process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid cpulist, does not list any processors or
+    /// lists processors that do not exist or are not available to the current process (e.g.
+    /// because they are offline).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// let processors = ProcessorSet::default();
+    ///
+    /// // E.g. "0-3,8-11"
+    /// let cpulist = processors.to_cpulist();
+    ///
+    /// let processors_again = ProcessorSet::from_cpulist(&cpulist).unwrap();
+    /// assert_eq!(processors_again.len(), processors.len());
+    /// ```
+    ///
+    /// [1]: https://docs.rs/cpulist
+    pub fn from_cpulist(cpulist: &str) -> Result<Self, CpulistError> {
+        Self::from_cpulist_core(
+            cpulist,
+            HardwareTrackerClientFacade::real(),
+            PlatformFacade::real(),
+        )
+    }
+
+    fn from_cpulist_core(
+        cpulist: &str,
+        tracker_client: HardwareTrackerClientFacade,
+        pal: PlatformFacade,
+    ) -> Result<Self, CpulistError> {
+        let processor_ids =
+            cpulist::parse(cpulist.trim()).map_err(|e| CpulistError::InvalidSyntax {
+                problem: e.to_string(),
+            })?;
+
+        let max_processor_id = pal.max_processor_id();
+
+        let unknown = processor_ids
+            .iter()
+            .copied()
+            .filter(|id| *id > max_processor_id)
+            .collect_vec();
+
+        if !unknown.is_empty() {
+            return Err(CpulistError::UnknownProcessors {
+                processor_ids: unknown,
+            });
+        }
+
+        // This only contains the processors that are online and available to the process.
+        let all_processors = pal.get_all_processors().map(Processor::new);
+
+        let unavailable = processor_ids
+            .iter()
+            .copied()
+            .filter(|id| !all_processors.iter().any(|p| p.id() == *id))
+            .collect_vec();
+
+        if !unavailable.is_empty() {
+            return Err(CpulistError::UnavailableProcessors {
+                processor_ids: unavailable,
+            });
+        }
+
+        let processors = NonEmpty::collect(
+            all_processors
+                .into_iter()
+                .filter(|p| processor_ids.contains(&p.id())),
+        )
+        .ok_or(CpulistError::Empty)?;
+
+        Ok(Self::new(processors, tracker_client, pal))
+    }
+
+    /// Returns the IDs of the processors in the set as a [cpulist][1] string (e.g. `0-3,8-11`),
+    /// which can be passed to `taskset`, the systemd `AllowedCPUs` setting and other tools that
+    /// exchange sets of processors.
+    ///
+    /// The result can be parsed back into a processor set via
+    /// [`from_cpulist()`][Self::from_cpulist].
+    ///
+    /// [1]: https://docs.rs/cpulist
+    #[must_use]
+    pub fn to_cpulist(&self) -> String {
+        cpulist::emit(self.processors.iter().map(Processor::id))
+    }
+
     /// Returns the number of processors in the set. A processor set is never empty.
     #[must_use]
     #[inline]
@@ -127,6 +258,196 @@ impl ProcessorSet {
         &self.processors
     }
 
+    /// Returns a processor set with as many of the processors in this set as the process can use
+    /// without exceeding its current resource quota.
+    ///
+    /// This is the resource quota step of [`ProcessorSetBuilder::take_all()`] as an explicit
+    /// operation - see [`ProcessorSetBuilder::all_matching_criteria()`] for combining the two.
+    /// If the resource quota allows using every processor in the set, the result contains all
+    /// of them.
+    #[must_use]
+    pub fn available_within_quota(&self) -> Self {
+        let max_count = processor_count_limit(self.pal.max_processor_time());
+
+        if self.processors.len() <= max_count {
+            return self.clone();
+        }
+
+        let processors = NonEmpty::collect(self.processors.iter().take(max_count).cloned())
+            .expect("the resource quota always allows at least one processor");
+
+        self.with_processors(processors)
+    }
+
+    /// Returns a processor set containing the processors that are in this set, in `other` or
+    /// in both.
+    ///
+    /// The processors keep their metadata (e.g. memory region), so the result can be narrowed
+    /// down further via [`to_builder()`][Self::to_builder] like any other processor set.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut processors = self.processors.clone();
+
+        processors.extend(
+            other
+                .processors
+                .iter()
+                .filter(|p| !self.processors.contains(p))
+                .cloned(),
+        );
+
+        self.with_processors(processors)
+    }
+
+    /// Returns a processor set containing the processors that are in both this set and `other`.
+    ///
+    /// Returns `None` if the sets have no processors in common, as a processor set is never empty.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        NonEmpty::collect(
+            self.processors
+                .iter()
+                .filter(|p| other.processors.contains(p))
+                .cloned(),
+        )
+        .map(|processors| self.with_processors(processors))
+    }
+
+    /// Returns a processor set containing the processors that are in this set but not in `other`.
+    ///
+    /// Returns `None` if every processor in this set is also in `other`, as a processor set is
+    /// never empty.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Option<Self> {
+        NonEmpty::collect(
+            self.processors
+                .iter()
+                .filter(|p| !other.processors.contains(p))
+                .cloned(),
+        )
+        .map(|processors| self.with_processors(processors))
+    }
+
+    /// Whether every processor in this set is also in `other`.
+    #[must_use]
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.processors.iter().all(|p| other.processors.contains(p))
+    }
+
+    /// Returns the threads of the current process that were last executing on processors that
+    /// are not in this set, as of the time of the call.
+    ///
+    /// Use [`ProcessorWatchdog`][crate::ProcessorWatchdog] to check this periodically.
+    ///
+    /// This is only supported on Linux. On other operating systems, this always returns an
+    /// empty list.
+    #[must_use]
+    pub fn threads_outside(&self) -> Vec<ThreadPlacement> {
+        self.pal
+            .current_process_threads()
+            .into_iter()
+            .filter(|thread| {
+                !self
+                    .processors
+                    .iter()
+                    .any(|p| p.id() == thread.processor_id())
+            })
+            .collect()
+    }
+
+    /// Splits the processor set by last level cache (L3) domain, returning one processor set for
+    /// each domain that has processors in this set, in ascending order of domain.
+    ///
+    /// See [`ProcessorSetBuilder::same_l3_domain()`] for more details on L3 domains.
+    #[must_use]
+    pub fn l3_domains(&self) -> Vec<Self> {
+        self.processors
+            .iter()
+            .cloned()
+            .into_group_map_by(|p| self.pal.l3_cache_domain_id(p.id()))
+            .into_iter()
+            .sorted_unstable_by_key(|(l3_domain, _)| *l3_domain)
+            .map(|(_, processors)| {
+                self.with_processors(
+                    NonEmpty::from_vec(processors)
+                        .expect("every group has at least the processor that created it"),
+                )
+            })
+            .collect()
+    }
+
+    /// A processor set with the same internals as this one but different processors.
+    pub(crate) fn with_processors(&self, processors: NonEmpty<Processor>) -> Self {
+        Self::new(processors, self.tracker_client.clone(), self.pal.clone())
+    }
+
+    /// Whether any processor in the set shares its physical core with another processor of the
+    /// system, so threads executing on the set may compete with simultaneous multithreading
+    /// siblings for the execution resources of the core.
+    pub(crate) fn has_smt_siblings(&self) -> bool {
+        let cores = self
+            .processors
+            .iter()
+            .map(|p| self.pal.physical_core_id(p.id()))
+            .collect::<HashSet<_>>();
+
+        // If there are more processors on these cores than there are cores, some are siblings.
+        self.pal
+            .get_all_processors()
+            .map(Processor::new)
+            .iter()
+            .filter(|p| cores.contains(&self.pal.physical_core_id(p.id())))
+            .count()
+            > cores.len()
+    }
+
+    /// Whether one thread per processor in the set would use more processor time than the
+    /// resource quota of the process allows.
+    pub(crate) fn exceeds_resource_quota(&self) -> bool {
+        self.len() > processor_count_limit(self.pal.max_processor_time())
+    }
+
+    /// Selects the default processor set again under the current constraints, keeping as many
+    /// of the processors in this set as the constraints allow, so the selection does not change
+    /// unless the constraints do.
+    pub(crate) fn refreshed_default(&self) -> Self {
+        self.pal.refresh_available_processors();
+
+        let candidates =
+            ProcessorSetBuilder::with_internals(self.tracker_client.clone(), self.pal.clone())
+                .ignoring_resource_quota()
+                .take_all()
+                .expect("there must be at least one processor - how could this code run if not");
+
+        let max_count = processor_count_limit(self.pal.max_processor_time());
+
+        let (kept, others): (Vec<_>, Vec<_>) = candidates
+            .processors
+            .into_iter()
+            .partition(|p| self.processors.contains(p));
+
+        let processors = NonEmpty::collect(kept.into_iter().chain(others).take(max_count))
+            .expect("the resource quota always allows at least one processor");
+
+        self.with_processors(processors)
+    }
+
+    /// Sets the scheduling priority of the current thread via the platform of this set.
+    pub(crate) fn set_current_thread_priority(&self, priority: ThreadPriority) -> io::Result<()> {
+        self.pal.set_current_thread_priority(priority)
+    }
+
+    /// Reserves the processors in this set in the process-wide reservation registry, until the
+    /// returned reservation is dropped.
+    ///
+    /// Builders that call [`ProcessorSetBuilder::except_reserved()`] do not select reserved
+    /// processors, allowing independent subsystems of the same process to avoid pinning their
+    /// threads to the same processors. See [`ProcessorReservation`] for details.
+    #[must_use]
+    pub fn reserve(&self) -> ProcessorReservation {
+        ProcessorReservation::new(self.clone())
+    }
+
     /// Modifies the affinity of the current thread to execute
     /// only on the processors in this processor set.
     ///
@@ -163,6 +484,86 @@ impl ProcessorSet {
         }
     }
 
+    /// Modifies the affinity of the current thread to execute only on the processors in this
+    /// processor set and waits until the operating system has actually moved the thread to one
+    /// of them, returning the processor the thread is then executing on.
+    ///
+    /// Changing the affinity of a thread does not necessarily move it immediately - the thread
+    /// may keep executing on its previous processor until the operating system next schedules
+    /// it. When rebalancing workers between processors, this allows the worker to signal that
+    /// the move has taken effect once this returns, instead of racing with the scheduler.
+    ///
+    /// The current thread yields to the operating system while waiting. Returns `None` if the
+    /// thread was not moved within `timeout`, in which case the affinity is still modified and
+    /// the thread will be moved whenever the operating system gets around to it.
+    #[must_use]
+    pub fn migrate_current_thread_to(&self, timeout: Duration) -> Option<ProcessorId> {
+        self.pin_current_thread_to();
+
+        let started = Instant::now();
+
+        loop {
+            // We ask the platform directly because the hardware tracker may already be
+            // reporting the pinned processor as current, even if we have not moved yet.
+            let processor_id = self.pal.current_processor_id();
+
+            if self.processors.iter().any(|p| p.id() == processor_id) {
+                return Some(processor_id);
+            }
+
+            if started.elapsed() >= timeout {
+                return None;
+            }
+
+            // Giving up our time slice gives the operating system an opportunity to move us.
+            thread::yield_now();
+        }
+    }
+
+    /// Modifies the affinity of the current thread to execute only on the processors in this
+    /// processor set until the returned guard is dropped, at which point the previous affinity of
+    /// the thread is restored.
+    ///
+    /// This allows a library function to temporarily pin the thread it is called on without
+    /// permanently altering the affinity chosen by its caller.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// fn do_local_work(processor_set: &ProcessorSet) {
+    ///     let _pinned = processor_set.pin_current_thread_scoped();
+    ///
+    ///     // Work done here is pinned to the processor set.
+    /// }
+    ///
+    /// let first_processor = ProcessorSet::from(ProcessorSet::default().processors().first().clone());
+    /// do_local_work(&first_processor);
+    ///
+    /// // The affinity of the current thread is the same as before the call.
+    /// ```
+    pub fn pin_current_thread_scoped(&self) -> ThreadPinGuard {
+        let previous_processor_ids = self.pal.current_thread_processors();
+
+        let previous_processors = self
+            .pal
+            .get_all_processors()
+            .map(Processor::new)
+            .into_iter()
+            .filter(|p| previous_processor_ids.contains(&p.id()))
+            .collect_vec();
+
+        let previous = self.with_processors(
+            NonEmpty::from_vec(previous_processors)
+                .expect("the current thread is allowed to execute on at least one processor"),
+        );
+
+        self.pin_current_thread_to();
+
+        ThreadPinGuard::new(previous)
+    }
+
     /// Spawns one thread for each processor in the set, pinned to that processor,
     /// providing the target processor information to the thread entry point.
     ///
@@ -218,6 +619,148 @@ impl ProcessorSet {
             entrypoint(set)
         })
     }
+
+    /// Creates a builder for spawning threads pinned to the set with custom thread settings,
+    /// such as the stack size. See [`PinnedThreadBuilder`] for details.
+    #[must_use]
+    pub fn thread_builder(&self) -> PinnedThreadBuilder {
+        PinnedThreadBuilder::new(self.clone())
+    }
+
+    /// Spawns a single thread pinned to the set within a [`thread::scope()`], allowing the thread
+    /// to borrow non-`'static` data from the caller. This is otherwise the same as
+    /// [`spawn_thread()`][Self::spawn_thread].
+    ///
+    /// All threads spawned in the scope are joined automatically before `thread::scope()` returns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::thread;
+    ///
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// let processors = ProcessorSet::default();
+    /// let numbers = vec![1, 2, 3, 4];
+    ///
+    /// let sum = thread::scope(|s| {
+    ///     let (left, right) = numbers.split_at(2);
+    ///
+    ///     let left = processors.spawn_scoped(s, move |_| left.iter().sum::<i32>());
+    ///     let right = processors.spawn_scoped(s, move |_| right.iter().sum::<i32>());
+    ///
+    ///     left.join().unwrap() + right.join().unwrap()
+    /// });
+    ///
+    /// assert_eq!(sum, 10);
+    /// ```
+    pub fn spawn_scoped<'scope, 'env, E, R>(
+        &self,
+        scope: &'scope thread::Scope<'scope, 'env>,
+        entrypoint: E,
+    ) -> thread::ScopedJoinHandle<'scope, R>
+    where
+        E: FnOnce(Self) -> R + Send + 'scope,
+        R: Send + 'scope,
+    {
+        let set = self.clone();
+
+        scope.spawn(move || {
+            set.pin_current_thread_to();
+            entrypoint(set)
+        })
+    }
+
+    /// Configures a command so that the process it spawns will only execute on the processors
+    /// in this processor set.
+    ///
+    /// If all the processors in the set are in the same memory region, the process is also
+    /// configured to prefer allocating memory in that memory region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`Unsupported`][io::ErrorKind::Unsupported] if the operating
+    /// system does not allow the process to be configured before it starts, which is currently
+    /// the case on Windows.
+    pub fn pin_command_to(&self, command: &mut Command) -> io::Result<()> {
+        self.pal.pin_command_to(command, &self.processors)
+    }
+
+    /// Spawns one child process for each memory region with processors in the set, with each
+    /// process pinned to the processors of its memory region (see [`pin_command_to()`][1]).
+    ///
+    /// The command of each process is created by `build_command`, which receives the memory region
+    /// and the processors the process will be pinned to, so each process can be told which
+    /// memory region it owns (e.g. via arguments or environment variables).
+    ///
+    /// Returns the spawned processes in ascending order of memory region ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the processes cannot be pinned (see [`pin_command_to()`][1]) or if any
+    /// of the processes fails to spawn, in which case the processes already spawned are killed.
+    ///
+    /// [1]: Self::pin_command_to
+    pub fn spawn_processes_per_memory_region<F>(
+        &self,
+        mut build_command: F,
+    ) -> io::Result<Box<[MemoryRegionProcess]>>
+    where
+        F: FnMut(MemoryRegionId, &Self) -> Command,
+    {
+        let processors_per_region = self
+            .processors
+            .iter()
+            .cloned()
+            .into_group_map_by(Processor::memory_region_id)
+            .into_iter()
+            .sorted_unstable_by_key(|(memory_region_id, _)| *memory_region_id)
+            .map(|(memory_region_id, processors)| {
+                let processors = Self::new(
+                    NonEmpty::from_vec(processors)
+                        .expect("every group has at least the processor that created it"),
+                    self.tracker_client.clone(),
+                    self.pal.clone(),
+                );
+
+                (memory_region_id, processors)
+            })
+            .collect_vec();
+
+        let mut spawned = Vec::with_capacity(processors_per_region.len());
+
+        for (memory_region_id, processors) in processors_per_region {
+            let mut command = build_command(memory_region_id, &processors);
+
+            let child = processors
+                .pin_command_to(&mut command)
+                .and_then(|()| command.spawn());
+
+            match child {
+                Ok(child) => spawned.push(MemoryRegionProcess::new(
+                    memory_region_id,
+                    processors,
+                    child,
+                )),
+                Err(e) => {
+                    for process in spawned {
+                        kill_and_reap(process.into_child());
+                    }
+
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(spawned.into_boxed_slice())
+    }
+}
+
+/// Stops a child process that is no longer wanted, ignoring errors (e.g. if it already exited).
+fn kill_and_reap(mut child: Child) {
+    // Killing fails if the process already exited, in which case we only need to reap it.
+    _ = child.kill();
+    _ = child.wait();
 }
 
 impl Default for ProcessorSet {
@@ -227,7 +770,8 @@ impl Default for ProcessorSet {
     /// This is equivalent to calling `ProcessorSet::builder().take_all()`, except that the default
     /// processor set is initialized on first use and never updated. If you expect the constraints
     /// applied to the process to change at runtime, you should manually build a new processor set
-    /// via `ProcessorSet::builder().take_all()` whenever you wish to apply any updated constraints.
+    /// via `ProcessorSet::builder().take_all()` whenever you wish to apply any updated constraints,
+    /// or use [`ProcessorSet::watching_default()`] to have this done automatically.
     ///
     /// # Resource quota
     ///
@@ -265,8 +809,7 @@ impl Display for ProcessorSet {
     #[cfg_attr(test, mutants::skip)] // We have no API contract to test here.
     #[inline]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let list = cpulist::emit(self.processors.iter().map(Processor::id));
-        write!(f, " {list} ({} processors)", self.len())
+        write!(f, " {} ({} processors)", self.to_cpulist(), self.len())
     }
 }
 
@@ -278,11 +821,12 @@ mod tests {
     };
 
     use folo_utils::nz;
+    use mockall::Sequence;
     use nonempty::nonempty;
 
     use crate::{
         EfficiencyClass, MockHardwareTrackerClient,
-        pal::{FakeProcessor, MockPlatform},
+        pal::{AbstractProcessor, FakeProcessor, MockPlatform, ProcessorFacade},
     };
 
     use super::*;
@@ -321,11 +865,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -434,6 +980,261 @@ mod tests {
         assert_eq!(cloned_processor_set.len(), 2);
     }
 
+    #[test]
+    fn pin_current_thread_scoped_restores_previous_affinity() {
+        let mut platform = MockPlatform::new();
+        let mut seq = Sequence::new();
+
+        platform
+            .expect_current_thread_processors()
+            .return_const(nonempty![0, 2]);
+
+        platform
+            .expect_get_all_processors_core()
+            .return_const(nonempty![
+                ProcessorFacade::Fake(FakeProcessor::with_index(0)),
+                ProcessorFacade::Fake(FakeProcessor::with_index(1)),
+                ProcessorFacade::Fake(FakeProcessor::with_index(2)),
+            ]);
+
+        // First we pin to the set, then the guard restores the previous affinity.
+        platform
+            .expect_pin_current_thread_to_core()
+            .times(1)
+            .in_sequence(&mut seq)
+            .withf(|p| p.len() == 1 && p.first().map(AbstractProcessor::id) == Some(1))
+            .return_const(());
+
+        platform
+            .expect_pin_current_thread_to_core()
+            .times(1)
+            .in_sequence(&mut seq)
+            .withf(|p| p.iter().map(AbstractProcessor::id).collect_vec() == [0, 2])
+            .return_const(());
+
+        let mut tracker_client = MockHardwareTrackerClient::new();
+        tracker_client.expect_update_pin_status().return_const(());
+
+        let processor_set = ProcessorSet::new(
+            nonempty![Processor::new(FakeProcessor::with_index(1).into())],
+            HardwareTrackerClientFacade::from_mock(tracker_client),
+            PlatformFacade::from_mock(platform),
+        );
+
+        let guard = processor_set.pin_current_thread_scoped();
+        drop(guard);
+    }
+
+    #[test]
+    fn spawn_scoped_borrows_from_caller() {
+        let mut platform = MockPlatform::new();
+
+        // Each spawned thread is pinned to the entire set.
+        platform
+            .expect_pin_current_thread_to_core()
+            .times(2)
+            .withf(|p| p.len() == 2)
+            .return_const(());
+
+        let mut tracker_client = MockHardwareTrackerClient::new();
+        tracker_client.expect_update_pin_status().return_const(());
+
+        let processor_set = ProcessorSet::new(
+            nonempty![0, 1].map(|index| Processor::new(FakeProcessor::with_index(index).into())),
+            HardwareTrackerClientFacade::from_mock(tracker_client),
+            PlatformFacade::from_mock(platform),
+        );
+
+        // Neither of these is 'static or shared via Arc.
+        let numbers = vec![1, 2, 3, 4];
+        let threads_spawned = AtomicUsize::new(0);
+
+        let sum = thread::scope(|s| {
+            let handles = numbers
+                .chunks(2)
+                .map(|half| {
+                    let threads_spawned = &threads_spawned;
+
+                    processor_set.spawn_scoped(s, move |processor_set| {
+                        assert_eq!(processor_set.len(), 2);
+                        threads_spawned.fetch_add(1, Ordering::Relaxed);
+                        half.iter().sum::<i32>()
+                    })
+                })
+                .collect_vec();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .sum::<i32>()
+        });
+
+        assert_eq!(sum, 10);
+        assert_eq!(threads_spawned.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn set_algebra() {
+        let new_set = |indexes: NonEmpty<u32>| {
+            ProcessorSet::new(
+                indexes.map(|index| {
+                    Processor::new(
+                        FakeProcessor {
+                            index,
+                            memory_region: index / 2,
+                            efficiency_class: EfficiencyClass::Performance,
+                            package: 0,
+                        }
+                        .into(),
+                    )
+                }),
+                HardwareTrackerClientFacade::from_mock(MockHardwareTrackerClient::new()),
+                PlatformFacade::from_mock(MockPlatform::new()),
+            )
+        };
+
+        let ids = |set: &ProcessorSet| {
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec()
+        };
+
+        let a = new_set(nonempty![0, 1, 2]);
+        let b = new_set(nonempty![2, 3]);
+        let c = new_set(nonempty![4]);
+
+        let union = a.union(&b);
+        assert_eq!(ids(&union), [0, 1, 2, 3]);
+
+        // Memory region metadata is preserved.
+        assert!(
+            union
+                .processors()
+                .iter()
+                .all(|p| p.memory_region_id() == p.id() / 2)
+        );
+
+        assert_eq!(ids(&a.intersection(&b).unwrap()), [2]);
+        assert!(a.intersection(&c).is_none());
+
+        assert_eq!(ids(&a.difference(&b).unwrap()), [0, 1]);
+        assert!(a.difference(&union).is_none());
+
+        assert!(a.is_subset_of(&union));
+        assert!(b.is_subset_of(&union));
+        assert!(!a.is_subset_of(&b));
+        assert!(!c.is_subset_of(&union));
+    }
+
+    #[test]
+    fn l3_domains_groups_by_shared_cache() {
+        let mut platform = MockPlatform::new();
+
+        // Every group of 4 processors shares an L3 cache.
+        platform
+            .expect_l3_cache_domain_id()
+            .returning(|processor_id| processor_id / 4);
+
+        let processors = nonempty![9, 0, 5, 1, 4]
+            .map(|index| Processor::new(FakeProcessor::with_index(index).into()));
+
+        let processor_set = ProcessorSet::new(
+            processors,
+            HardwareTrackerClientFacade::default_mock(),
+            PlatformFacade::from_mock(platform),
+        );
+
+        let l3_domains = processor_set
+            .l3_domains()
+            .iter()
+            .map(|set| {
+                set.processors()
+                    .iter()
+                    .map(Processor::id)
+                    .sorted()
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        assert_eq!(l3_domains, [vec![0, 1], vec![4, 5], vec![9]]);
+    }
+
+    #[test]
+    fn available_within_quota_rounds_down() {
+        let mut platform = MockPlatform::new();
+        platform.expect_max_processor_time().times(3).returning({
+            let mut values = [2.9, 4.0, 0.5].into_iter();
+            move || values.next().unwrap()
+        });
+
+        let processors =
+            nonempty![0, 1, 2].map(|index| Processor::new(FakeProcessor::with_index(index).into()));
+
+        let processor_set = ProcessorSet::new(
+            processors,
+            HardwareTrackerClientFacade::default_mock(),
+            PlatformFacade::from_mock(platform),
+        );
+
+        assert_eq!(processor_set.available_within_quota().len(), 2);
+        assert_eq!(processor_set.available_within_quota().len(), 3);
+
+        // There is always at least one processor available.
+        assert_eq!(processor_set.available_within_quota().len(), 1);
+    }
+
+    #[test]
+    fn from_cpulist_validates_against_topology() {
+        let new_platform = || {
+            let mut platform = MockPlatform::new();
+
+            // Processor 2 is offline, processor 4 is the highest that could exist.
+            platform.expect_max_processor_id().return_const(4_u32);
+            platform
+                .expect_get_all_processors_core()
+                .return_const(nonempty![
+                    ProcessorFacade::Fake(FakeProcessor::with_index(0)),
+                    ProcessorFacade::Fake(FakeProcessor::with_index(1)),
+                    ProcessorFacade::Fake(FakeProcessor::with_index(3)),
+                    ProcessorFacade::Fake(FakeProcessor::with_index(4))
+                ]);
+
+            PlatformFacade::from_mock(platform)
+        };
+
+        let from_cpulist = |cpulist| {
+            ProcessorSet::from_cpulist_core(
+                cpulist,
+                HardwareTrackerClientFacade::default_mock(),
+                new_platform(),
+            )
+        };
+
+        let set = from_cpulist(" 0-1,4\n").unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.to_cpulist(), "0-1,4");
+
+        assert_eq!(
+            from_cpulist("0-1,5,7").unwrap_err(),
+            CpulistError::UnknownProcessors {
+                processor_ids: vec![5, 7]
+            }
+        );
+        assert_eq!(
+            from_cpulist("1-3").unwrap_err(),
+            CpulistError::UnavailableProcessors {
+                processor_ids: vec![2]
+            }
+        );
+        assert_eq!(from_cpulist("").unwrap_err(), CpulistError::Empty);
+        assert!(matches!(
+            from_cpulist("foo").unwrap_err(),
+            CpulistError::InvalidSyntax { .. }
+        ));
+    }
+
     #[cfg(not(miri))] // Miri does not support talking to the real platform.
     #[test]
     fn from_processor_preserves_processor() {
@@ -533,4 +1334,115 @@ mod tests {
         .join()
         .unwrap();
     }
+
+    #[test]
+    fn migrate_current_thread_waits_until_moved() {
+        let mut platform = MockPlatform::new();
+
+        platform
+            .expect_pin_current_thread_to_core()
+            .times(2)
+            .withf(|p| p.len() == 2)
+            .return_const(());
+
+        // The first migration observes the move on the third check, the second never does.
+        platform.expect_current_processor_id().returning({
+            let mut observed = [5, 5, 1].into_iter();
+            move || observed.next().unwrap_or(5)
+        });
+
+        let platform = PlatformFacade::from_mock(platform);
+
+        let mut tracker_client = MockHardwareTrackerClient::new();
+        tracker_client
+            .expect_update_pin_status()
+            .times(2)
+            .return_const(());
+
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            }
+        ];
+
+        let processors = pal_processors.map(move |p| Processor::new(p.into()));
+
+        let processor_set = ProcessorSet::new(
+            processors,
+            HardwareTrackerClientFacade::from_mock(tracker_client),
+            platform,
+        );
+
+        assert_eq!(
+            processor_set.migrate_current_thread_to(Duration::from_secs(60)),
+            Some(1)
+        );
+        assert_eq!(
+            processor_set.migrate_current_thread_to(Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn spawn_processes_per_memory_region_stops_on_pin_failure() {
+        let mut platform = MockPlatform::new();
+
+        platform
+            .expect_pin_command_to_core()
+            .times(1)
+            .withf(|p| p.len() == 2)
+            .returning(|_| Err(io::Error::from(io::ErrorKind::Unsupported)));
+
+        let platform = PlatformFacade::from_mock(platform);
+
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 1,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 2,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            }
+        ];
+
+        let processors = pal_processors.map(move |p| Processor::new(p.into()));
+
+        let processor_set = ProcessorSet::new(
+            processors,
+            HardwareTrackerClientFacade::from_mock(MockHardwareTrackerClient::new()),
+            platform,
+        );
+
+        let mut requested_memory_regions = Vec::new();
+
+        let result = processor_set.spawn_processes_per_memory_region(|memory_region_id, set| {
+            requested_memory_regions.push((memory_region_id, set.len()));
+            Command::new("this-command-is-never-executed")
+        });
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Unsupported);
+
+        // Memory regions are processed in ascending order and nothing after the failure.
+        assert_eq!(requested_memory_regions, [(0, 2)]);
+    }
 }
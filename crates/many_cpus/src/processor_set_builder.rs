@@ -1,16 +1,25 @@
-use std::{collections::VecDeque, fmt::Debug, num::NonZeroUsize};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+    num::NonZeroUsize,
+};
 
-use foldhash::HashMapExt;
-use foldhash::{HashMap, HashSet, HashSetExt};
+use foldhash::{HashMap, HashSet};
 use itertools::Itertools;
 use nonempty::NonEmpty;
 use rand::prelude::*;
-use rand::rng;
+use rand::rngs::StdRng;
 
 use crate::HardwareTrackerClientFacade;
 use crate::{
-    EfficiencyClass, MemoryRegionId, Processor, ProcessorId, ProcessorSet,
+    AntiAffinity, ConstraintElimination, EfficiencyClass, MemoryRegionId, PackageId, Processor,
+    ProcessorFeature, ProcessorId, ProcessorSelectionError, ProcessorSet, SelectionConstraint,
+    SelectionFailure,
+    joint_selection::resolve_jointly,
     pal::{Platform, PlatformFacade},
+    processor_reservation::reserved_processor_ids,
+    resource_quota::processor_count_limit,
 };
 
 /// Builds a [`ProcessorSet`] based on specified criteria. The default criteria include all
@@ -38,10 +47,29 @@ pub struct ProcessorSetBuilder {
     processor_type_selector: ProcessorTypeSelector,
     memory_region_selector: MemoryRegionSelector,
 
-    except_indexes: HashSet<ProcessorId>,
+    // The processors removed from the candidates by each `filter()`, `except()`,
+    // `except_reserved()`, `excluding_irq_heavy()`, `requiring_feature()`,
+    // `frequency_at_least()`, `package()`, `max_distance_from()` and
+    // `where_available_for_current_thread()` call, in call order. We keep them separate
+    // so we can explain which constraint removed which processors if the build fails.
+    exclusions: Vec<(SelectionConstraint, HashSet<ProcessorId>)>,
+
+    distinct_cores: bool,
+
+    same_package: bool,
+
+    same_l3_domain: bool,
 
     obey_resource_quota: bool,
 
+    // The selections declared via `named_selection()` and the anti-affinities between them,
+    // only used by `take_named()`.
+    named_selections: Vec<(String, NonZeroUsize)>,
+    anti_affinities: Vec<(String, String, AntiAffinity)>,
+
+    // If set, the random choices between equally valid candidates are derived from this seed.
+    seed: Option<u64>,
+
     // ProcessorSet needs this because it needs to inform the tracker
     // about any changes to the pinning status of the current thread.
     // We just carry it around and pass to any processor set we create.
@@ -69,8 +97,14 @@ impl ProcessorSetBuilder {
         Self {
             processor_type_selector: ProcessorTypeSelector::Any,
             memory_region_selector: MemoryRegionSelector::Any,
-            except_indexes: HashSet::new(),
+            exclusions: Vec::new(),
+            distinct_cores: false,
+            same_package: false,
+            same_l3_domain: false,
             obey_resource_quota: true,
+            named_selections: Vec::new(),
+            anti_affinities: Vec::new(),
+            seed: None,
             tracker_client,
             pal,
         }
@@ -94,6 +128,155 @@ impl ProcessorSetBuilder {
         self
     }
 
+    /// Requires that all processors in the set have the specified [efficiency class][1].
+    ///
+    /// This is equivalent to [`performance_processors_only()`][2] or
+    /// [`efficiency_processors_only()`][3], for when the desired efficiency class is only known
+    /// at runtime (e.g. from configuration).
+    ///
+    /// [1]: EfficiencyClass
+    /// [2]: ProcessorSetBuilder::performance_processors_only
+    /// [3]: ProcessorSetBuilder::efficiency_processors_only
+    #[must_use]
+    pub fn efficiency_class(self, efficiency_class: EfficiencyClass) -> Self {
+        match efficiency_class {
+            EfficiencyClass::Performance => self.performance_processors_only(),
+            EfficiencyClass::Efficiency => self.efficiency_processors_only(),
+        }
+    }
+
+    /// Requires that all processors in the set support an optional instruction set feature,
+    /// as reported by [`Processor::has_feature()`].
+    ///
+    /// This can be called multiple times to require multiple features. See [`ProcessorFeature`]
+    /// for the limitations of the platforms.
+    #[must_use]
+    pub fn requiring_feature(mut self, feature: ProcessorFeature) -> Self {
+        let excluded = self
+            .all_processors()
+            .into_iter()
+            .filter(|processor| !processor.has_feature(feature))
+            .map(|processor| processor.id())
+            .collect();
+
+        self.exclusions
+            .push((SelectionConstraint::RequiringFeature, excluded));
+        self
+    }
+
+    /// Requires that all processors in the set have a frequency of at least `min_mhz`
+    /// megahertz.
+    ///
+    /// Processors are compared by their [maximum frequency][1] if the platform reports it, as
+    /// that is the frequency they run at when busy, otherwise by their [base frequency][2].
+    /// Processors whose frequency the platform does not report are removed.
+    ///
+    /// [1]: Processor::max_frequency_mhz
+    /// [2]: Processor::base_frequency_mhz
+    #[must_use]
+    pub fn frequency_at_least(mut self, min_mhz: u32) -> Self {
+        let excluded = self
+            .all_processors()
+            .into_iter()
+            .filter(|processor| comparable_frequency_mhz(processor) < min_mhz)
+            .map(|processor| processor.id())
+            .collect();
+
+        self.exclusions
+            .push((SelectionConstraint::FrequencyAtLeast, excluded));
+        self
+    }
+
+    /// Requires that all processors in the set be from different physical processor cores,
+    /// selecting a maximum of 1 logical processor from each physical core.
+    ///
+    /// With simultaneous multithreading (SMT, e.g. hyper-threading), multiple logical processors
+    /// share the execution resources of one physical core. Compute-heavy workers pinned to such
+    /// sibling processors compete with each other for those resources, which this avoids.
+    ///
+    /// If the platform does not report which processors share a physical core, every processor
+    /// is considered to be a separate core.
+    #[must_use]
+    pub fn distinct_cores(mut self) -> Self {
+        self.distinct_cores = true;
+        self
+    }
+
+    /// Requires that all processors in the set be from the physical processor package (socket)
+    /// with the specified [package ID][1].
+    ///
+    /// [1]: Processor::package_id
+    #[must_use]
+    pub fn package(mut self, package_id: PackageId) -> Self {
+        let excluded = self
+            .all_processors()
+            .into_iter()
+            .filter(|processor| processor.package_id() != package_id)
+            .map(|processor| processor.id())
+            .collect();
+
+        self.exclusions
+            .push((SelectionConstraint::Package, excluded));
+        self
+    }
+
+    /// Requires that all processors in the set be from memory regions at most `max_distance`
+    /// away from the memory region `from`, as reported by
+    /// [`HardwareInfo::memory_region_distance()`][1].
+    ///
+    /// The distance from a memory region to itself is 10, so a `max_distance` of 10 only allows
+    /// processors in `from` itself. On systems with many memory regions, this can be used to
+    /// allow "near" remote memory regions while excluding the distant ones.
+    ///
+    /// [1]: crate::HardwareInfo::memory_region_distance
+    #[must_use]
+    pub fn max_distance_from(mut self, from: MemoryRegionId, max_distance: u32) -> Self {
+        let excluded = self
+            .all_processors()
+            .into_iter()
+            .filter(|processor| {
+                self.pal
+                    .memory_region_distance(from, processor.memory_region_id())
+                    > max_distance
+            })
+            .map(|processor| processor.id())
+            .collect();
+
+        self.exclusions
+            .push((SelectionConstraint::MaxDistanceFrom, excluded));
+        self
+    }
+
+    /// Requires that all processors in the set be from the same physical processor package
+    /// (socket), without caring which one.
+    ///
+    /// The package is chosen after all other per-processor constraints have been applied,
+    /// preferring packages whose remaining candidates can satisfy the request (including any
+    /// memory region constraints). If no package can, the request fails.
+    #[must_use]
+    pub fn same_package(mut self) -> Self {
+        self.same_package = true;
+        self
+    }
+
+    /// Requires that all processors in the set share the same last level cache (L3), without
+    /// caring which one.
+    ///
+    /// On processors with multiple L3 cache domains per package (e.g. the CCXs of AMD EPYC),
+    /// communication between processors in different domains is much slower than within one,
+    /// even in the same memory region.
+    ///
+    /// The domain is chosen in the same way as the package for [`same_package()`][1]. If the
+    /// platform does not report which processors share an L3 cache, every processor is
+    /// considered to be a separate domain.
+    ///
+    /// [1]: ProcessorSetBuilder::same_package
+    #[must_use]
+    pub fn same_l3_domain(mut self) -> Self {
+        self.same_l3_domain = true;
+        self
+    }
+
     /// Requires that all processors in the set be from different memory regions, selecting a
     /// maximum of 1 processor from each memory region.
     #[must_use]
@@ -135,17 +318,41 @@ impl ProcessorSetBuilder {
     /// conditions - even if this predicate returns `true`, the processor may end up being filtered
     /// out by other conditions. Conversely, some candidates may already be filtered out before
     /// being passed to this predicate.
+    ///
+    /// The predicate can express custom selection policies in terms of any of the metadata
+    /// exposed by [`Processor`], such as the memory region, package, efficiency class,
+    /// [physical core][Processor::physical_core_id] (shared by SMT siblings) and
+    /// [L3 cache domain][Processor::l3_cache_domain_id] of each processor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::collections::HashSet;
+    ///
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// // At most one processor per physical core, so no two threads compete via SMT.
+    /// let seen_cores = RefCell::new(HashSet::new());
+    ///
+    /// let one_per_core = ProcessorSet::builder()
+    ///     .filter(|p| seen_cores.borrow_mut().insert(p.physical_core_id()))
+    ///     .take_all();
+    /// ```
     #[must_use]
     pub fn filter(mut self, predicate: impl Fn(&Processor) -> bool) -> Self {
         // We invoke the filters immediately because the API gets really annoying if the
         // predicate has to borrow some stuff because it would need to be 'static and that
         // is cumbersome (since we do not return a generic-lifetimed thing back to the caller).
-        for processor in self.all_processors() {
-            if !predicate(&processor) {
-                self.except_indexes.insert(processor.id());
-            }
-        }
+        let excluded = self
+            .all_processors()
+            .into_iter()
+            .filter(|processor| !predicate(processor))
+            .map(|processor| processor.id())
+            .collect();
 
+        self.exclusions
+            .push((SelectionConstraint::Filter, excluded));
         self
     }
 
@@ -155,10 +362,74 @@ impl ProcessorSetBuilder {
     where
         I: IntoIterator<Item = &'a Processor>,
     {
-        for processor in processors {
-            self.except_indexes.insert(processor.id());
-        }
+        let excluded = processors.into_iter().map(Processor::id).collect();
+
+        self.exclusions
+            .push((SelectionConstraint::Except, excluded));
+        self
+    }
+
+    /// Removes processors from the set of candidates if they are reserved by any
+    /// [`ProcessorReservation`][crate::ProcessorReservation] in the current process, as of the
+    /// time of this call.
+    ///
+    /// This allows independent subsystems of the same process to stay away from each other's
+    /// processors. See [`ProcessorSet::reserve()`].
+    #[must_use]
+    pub fn except_reserved(mut self) -> Self {
+        self.exclusions.push((
+            SelectionConstraint::ExceptReserved,
+            reserved_processor_ids(),
+        ));
+        self
+    }
+
+    /// Removes processors from the set of candidates if they handle a disproportionate share of
+    /// the device interrupts of the system (e.g. from network adapters or storage controllers),
+    /// as of the time of this call.
+    ///
+    /// A processor is considered interrupt-heavy if it has handled more than `threshold` times
+    /// the average number of device interrupts per processor since the system started. Threads
+    /// on such processors are regularly interrupted to service the devices, which hurts the tail
+    /// latency of latency-sensitive work.
+    ///
+    /// On Linux, the interrupt counts are read from `/proc/interrupts`. On Windows, only the
+    /// processors in the processor group of the current thread are considered and the counts
+    /// include all interrupts, not only those from devices. If the platform does not report
+    /// interrupt counts, no processors are removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is not a positive finite number.
+    #[must_use]
+    pub fn excluding_irq_heavy(mut self, threshold: f64) -> Self {
+        assert!(
+            threshold.is_finite() && threshold > 0.0,
+            "threshold must be a positive finite number, got {threshold}"
+        );
+
+        let interrupt_counts = self.pal.interrupt_counts();
+
+        let total: u64 = interrupt_counts.iter().map(|(_, count)| count).sum();
 
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "interrupt counts are compared approximately, precision loss is acceptable"
+        )]
+        let limit = threshold * (total as f64 / interrupt_counts.len().max(1) as f64);
+
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "interrupt counts are compared approximately, precision loss is acceptable"
+        )]
+        let excluded = interrupt_counts
+            .into_iter()
+            .filter(|(_, count)| *count as f64 > limit)
+            .map(|(processor_id, _)| processor_id)
+            .collect();
+
+        self.exclusions
+            .push((SelectionConstraint::ExcludingIrqHeavy, excluded));
         self
     }
 
@@ -171,12 +442,15 @@ impl ProcessorSetBuilder {
     pub fn where_available_for_current_thread(mut self) -> Self {
         let current_thread_processors = self.pal.current_thread_processors();
 
-        for processor in self.all_processors() {
-            if !current_thread_processors.contains(&processor.id()) {
-                self.except_indexes.insert(processor.id());
-            }
-        }
+        let excluded = self
+            .all_processors()
+            .into_iter()
+            .map(|processor| processor.id())
+            .filter(|id| !current_thread_processors.contains(id))
+            .collect();
 
+        self.exclusions
+            .push((SelectionConstraint::AvailableForCurrentThread, excluded));
         self
     }
 
@@ -192,13 +466,78 @@ impl ProcessorSetBuilder {
         self
     }
 
+    /// Makes the selection deterministic, deriving every choice between equally valid candidate
+    /// processors from `seed` instead of choosing randomly.
+    ///
+    /// Given the same hardware topology, the same criteria and the same seed, the builder
+    /// selects the same processors every time, including across executions of the process. This
+    /// allows CI runs and benchmarks to reproduce the exact same processor selection.
+    ///
+    /// The selection may differ between versions of this crate, as well as on systems with
+    /// different processors available to the process.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZero;
+    ///
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// let take = || {
+    ///     ProcessorSet::builder()
+    ///         .with_seed(1234)
+    ///         .take(NonZero::new(1).unwrap())
+    ///         .unwrap()
+    /// };
+    ///
+    /// assert_eq!(take().processors(), take().processors());
+    /// ```
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Declares a named selection of `count` processors, to be resolved together with the other
+    /// named selections via [`take_named()`][1].
+    ///
+    /// Named selections never share processors with each other. Use [`anti_affinity()`][2] to
+    /// also keep them from sharing physical cores, L3 domains or memory regions.
+    ///
+    /// [1]: ProcessorSetBuilder::take_named
+    /// [2]: ProcessorSetBuilder::anti_affinity
+    #[must_use]
+    pub fn named_selection(mut self, name: impl Into<String>, count: NonZeroUsize) -> Self {
+        self.named_selections.push((name.into(), count));
+        self
+    }
+
+    /// Requires that the named selections `a` and `b` do not share the hardware resources
+    /// described by `anti_affinity`.
+    ///
+    /// The selections are declared via [`named_selection()`][1], either before or after this call.
+    ///
+    /// [1]: ProcessorSetBuilder::named_selection
+    #[must_use]
+    pub fn anti_affinity(
+        mut self,
+        a: impl Into<String>,
+        b: impl Into<String>,
+        anti_affinity: AntiAffinity,
+    ) -> Self {
+        self.anti_affinities
+            .push((a.into(), b.into(), anti_affinity));
+        self
+    }
+
     /// Creates a processor set with a specific number of processors that match the
     /// configured criteria.
     ///
     /// If multiple candidate sets are a match, returns an arbitrary one of them. For example, if
     /// there are six valid candidate processors then `take(4)` may return any four of them.
     ///
-    /// Returns `None` if there were not enough candidate processors to satisfy the request.
+    /// Returns `None` if there were not enough candidate processors to satisfy the request. Use
+    /// [`try_take()`][2] to find out which constraints removed the missing processors.
     ///
     /// # Resource quota
     ///
@@ -207,22 +546,53 @@ impl ProcessorSetBuilder {
     /// documentation for more details on resource quota handling best practices.
     ///
     /// [1]: ProcessorSetBuilder::ignoring_resource_quota
+    /// [2]: ProcessorSetBuilder::try_take
     #[must_use]
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
     pub fn take(self, count: NonZeroUsize) -> Option<ProcessorSet> {
+        self.try_take(count).ok()
+    }
+
+    /// Creates a processor set with a specific number of processors that match the
+    /// configured criteria, explaining the failure if the request cannot be satisfied.
+    ///
+    /// This is the same as [`take()`][1], except that on failure it returns an error that
+    /// describes how many processors each constraint removed from the candidates and why the
+    /// remaining candidates were not enough.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there were not enough candidate processors to satisfy the request,
+    /// including if the number of requested processors is above the process resource quota.
+    ///
+    /// [1]: ProcessorSetBuilder::take
+    pub fn try_take(self, count: NonZeroUsize) -> Result<ProcessorSet, ProcessorSelectionError> {
         if let Some(max_count) = self.resource_quota_processor_count_limit() {
             if count.get() > max_count {
-                // We cannot satisfy the request.
-                return None;
+                // We cannot satisfy the request. We fail before looking at any processors,
+                // so there are no eliminations to report.
+                return Err(ProcessorSelectionError::new(
+                    Vec::new(),
+                    SelectionFailure::ResourceQuotaExceeded {
+                        requested: count.get(),
+                        limit: max_count,
+                    },
+                ));
             }
         }
 
-        let candidates = self.candidates_by_memory_region();
+        let rng = &mut self.new_rng();
+
+        let (candidates, eliminations) = self.candidates_by_memory_region(count.get(), rng);
+        let fail = |failure| ProcessorSelectionError::new(eliminations.clone(), failure);
 
         if candidates.is_empty() {
             // No candidates to choose from - everything was filtered out.
-            return None;
+            return Err(fail(SelectionFailure::NoCandidates));
         }
 
+        let candidate_count = candidates.values().map(Vec::len).sum::<usize>();
+
         let processors = match self.memory_region_selector {
             MemoryRegionSelector::Any => {
                 // We do not care about memory regions, so merge into one big happy family and
@@ -234,11 +604,14 @@ impl ProcessorSetBuilder {
 
                 if all_processors.len() < count.get() {
                     // Not enough processors to satisfy request.
-                    return None;
+                    return Err(fail(SelectionFailure::NotEnoughCandidates {
+                        requested: count.get(),
+                        candidates: all_processors.len(),
+                    }));
                 }
 
                 all_processors
-                    .choose_multiple(&mut rng(), count.get())
+                    .choose_multiple(rng, count.get())
                     .cloned()
                     .collect_vec()
             }
@@ -251,7 +624,7 @@ impl ProcessorSetBuilder {
                 // although we will consider all memory regions with at least 'count' candidates
                 // as equal in sort order to avoid needlessly preferring giant memory regions.
                 let mut remaining_memory_regions = candidates.keys().copied().collect_vec();
-                remaining_memory_regions.shuffle(&mut rng());
+                remaining_memory_regions.shuffle(rng);
                 remaining_memory_regions.sort_unstable_by_key(|x| {
                     candidates
                         .get(x)
@@ -268,7 +641,12 @@ impl ProcessorSetBuilder {
                 let mut processors: Vec<Processor> = Vec::with_capacity(count);
 
                 while processors.len() < count {
-                    let memory_region = remaining_memory_regions.pop_front()?;
+                    let memory_region = remaining_memory_regions.pop_front().ok_or_else(|| {
+                        fail(SelectionFailure::NotEnoughCandidates {
+                            requested: count,
+                            candidates: candidate_count,
+                        })
+                    })?;
 
                     let processors_in_region = candidates.get(&memory_region).expect(
                         "we picked an existing key from an existing HashSet - the values must exist",
@@ -278,7 +656,7 @@ impl ProcessorSetBuilder {
                     let choose_count = count.min(processors_in_region.len());
 
                     let region_processors = processors_in_region
-                        .choose_multiple(&mut rng(), choose_count)
+                        .choose_multiple(rng, choose_count)
                         .cloned();
 
                     processors.extend(region_processors);
@@ -300,14 +678,23 @@ impl ProcessorSetBuilder {
                     })
                     .collect_vec();
 
-                let memory_region = qualifying_memory_regions.choose(&mut rng())?;
+                let memory_region = qualifying_memory_regions.choose(rng).ok_or_else(|| {
+                    fail(SelectionFailure::NotEnoughInAnyMemoryRegion {
+                        requested: count.get(),
+                        largest_memory_region: candidates
+                            .values()
+                            .map(Vec::len)
+                            .max()
+                            .unwrap_or_default(),
+                    })
+                })?;
 
                 let processors = candidates.get(memory_region).expect(
                     "we picked an existing key for an existing HashSet - the values must exist",
                 );
 
                 processors
-                    .choose_multiple(&mut rng(), count.get())
+                    .choose_multiple(rng, count.get())
                     .cloned()
                     .collect_vec()
             }
@@ -323,12 +710,17 @@ impl ProcessorSetBuilder {
                 while processors.len() < count.get() {
                     if candidates.is_empty() {
                         // Not enough candidates remaining to satisfy request.
-                        return None;
+                        return Err(fail(SelectionFailure::NotEnoughCandidates {
+                            requested: count.get(),
+                            candidates: candidate_count,
+                        }));
                     }
 
                     for remaining_processors in candidates.values_mut() {
                         let (index, processor) =
-                            remaining_processors.iter().enumerate().choose(&mut rng())?;
+                            remaining_processors.iter().enumerate().choose(rng).expect(
+                                "depleted memory regions are removed - processors must exist",
+                            );
 
                         let processor = processor.clone();
 
@@ -352,15 +744,18 @@ impl ProcessorSetBuilder {
 
                 if candidates.len() < count.get() {
                     // Not enough memory regions to satisfy request.
-                    return None;
+                    return Err(fail(SelectionFailure::NotEnoughMemoryRegions {
+                        requested: count.get(),
+                        memory_regions: candidates.len(),
+                    }));
                 }
 
                 candidates
                     .iter()
-                    .choose_multiple(&mut rng(), count.get())
+                    .choose_multiple(rng, count.get())
                     .into_iter()
                     .map(|(_, processors)| {
-                        processors.iter().choose(&mut rng()).cloned().expect(
+                        processors.iter().choose(rng).cloned().expect(
                             "we are picking one item from a non-empty list - item must exist",
                         )
                     })
@@ -368,11 +763,54 @@ impl ProcessorSetBuilder {
             }
         };
 
-        Some(ProcessorSet::new(
-            NonEmpty::from_vec(processors)?,
-            self.tracker_client,
-            self.pal,
-        ))
+        let processors =
+            NonEmpty::from_vec(processors).ok_or_else(|| fail(SelectionFailure::NoCandidates))?;
+
+        Ok(ProcessorSet::new(processors, self.tracker_client, self.pal))
+    }
+
+    /// Creates a processor set with the `count` fastest processors that match the configured
+    /// criteria.
+    ///
+    /// Processors are compared by frequency in the same way as for
+    /// [`frequency_at_least()`][1], with processors whose frequency the platform does not report
+    /// considered the slowest. Among processors of equal frequency, the choice is random.
+    ///
+    /// The processors are chosen from the ones that [`all_matching_criteria()`][2] would return,
+    /// so any memory region or package constraints are resolved before comparing frequencies.
+    ///
+    /// Returns `None` if there were not enough matching processors to satisfy the request or if
+    /// the number of requested processors is above the process resource quota.
+    ///
+    /// [1]: ProcessorSetBuilder::frequency_at_least
+    /// [2]: ProcessorSetBuilder::all_matching_criteria
+    #[must_use]
+    pub fn fastest(self, count: NonZeroUsize) -> Option<ProcessorSet> {
+        if self
+            .resource_quota_processor_count_limit()
+            .is_some_and(|max_count| count.get() > max_count)
+        {
+            return None;
+        }
+
+        let rng = &mut self.new_rng();
+
+        let candidates = self.all_matching_criteria()?;
+
+        if candidates.len() < count.get() {
+            return None;
+        }
+
+        let mut processors = candidates.processors().iter().cloned().collect_vec();
+
+        // The sort is stable, so shuffling first makes the choice among equals random.
+        processors.shuffle(rng);
+        processors.sort_by_key(|processor| Reverse(comparable_frequency_mhz(processor)));
+
+        let processors = NonEmpty::collect(processors.into_iter().take(count.get()))
+            .expect("we verified there are at least `count` candidates");
+
+        Some(candidates.with_processors(processors))
     }
 
     /// Returns a processor set with all processors that match the configured criteria.
@@ -381,7 +819,8 @@ impl ProcessorSetBuilder {
     /// For example, if specifying only a "same memory region" constraint, it will return all
     /// the processors in an arbitrary memory region with at least one qualifying processor.
     ///
-    /// Returns `None` if there were no matching processors to satisfy the request.
+    /// Returns `None` if there were no matching processors to satisfy the request. Use
+    /// [`try_take_all()`][2] to find out which constraints removed the processors.
     ///
     /// # Resource quota
     ///
@@ -390,14 +829,74 @@ impl ProcessorSetBuilder {
     /// for more details on resource quota handling best practices.
     ///
     /// [1]: ProcessorSetBuilder::ignoring_resource_quota
+    /// [2]: ProcessorSetBuilder::try_take_all
     #[must_use]
     #[cfg_attr(test, mutants::skip)] // Hangs due to recursive access of OnceLock.
     pub fn take_all(self) -> Option<ProcessorSet> {
-        let candidates = self.candidates_by_memory_region();
+        self.try_take_all().ok()
+    }
+
+    /// Returns a processor set with all processors that match the configured criteria, without
+    /// limiting the number of processors by the process resource quota.
+    ///
+    /// This is the same as `.ignoring_resource_quota().take_all()`. Together with
+    /// [`ProcessorSet::available_within_quota()`][1], it splits [`take_all()`][2] into two
+    /// explicit steps, so callers can see both how many processors match their criteria and how
+    /// many of them they can use without exceeding the resource quota, e.g. for capacity planning.
+    ///
+    /// Returns `None` if there were no matching processors to satisfy the request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use many_cpus::ProcessorSet;
+    ///
+    /// let matching = ProcessorSet::builder()
+    ///     .performance_processors_only()
+    ///     .all_matching_criteria()
+    ///     .unwrap();
+    ///
+    /// let usable = matching.available_within_quota();
+    ///
+    /// println!(
+    ///     "{} processors match but the resource quota only allows using {}",
+    ///     matching.len(),
+    ///     usable.len()
+    /// );
+    /// ```
+    ///
+    /// [1]: ProcessorSet::available_within_quota
+    /// [2]: ProcessorSetBuilder::take_all
+    #[must_use]
+    #[cfg_attr(test, mutants::skip)] // Trivial layer, we only test the underlying logic.
+    pub fn all_matching_criteria(self) -> Option<ProcessorSet> {
+        self.ignoring_resource_quota().take_all()
+    }
+
+    /// Returns a processor set with all processors that match the configured criteria,
+    /// explaining the failure if there are no matching processors.
+    ///
+    /// This is the same as [`take_all()`][1], except that on failure it returns an error that
+    /// describes how many processors each constraint removed from the candidates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there were no matching processors to satisfy the request.
+    ///
+    /// [1]: ProcessorSetBuilder::take_all
+    #[cfg_attr(test, mutants::skip)] // Hangs due to recursive access of OnceLock.
+    pub fn try_take_all(self) -> Result<ProcessorSet, ProcessorSelectionError> {
+        // Any non-empty package satisfies the "all" criterion.
+        let rng = &mut self.new_rng();
+
+        let (candidates, eliminations) = self.candidates_by_memory_region(1, rng);
 
         if candidates.is_empty() {
             // No candidates to choose from - everything was filtered out.
-            return None;
+            return Err(ProcessorSelectionError::new(
+                eliminations,
+                SelectionFailure::NoCandidates,
+            ));
         }
 
         let processors = match self.memory_region_selector {
@@ -418,7 +917,7 @@ impl ProcessorSetBuilder {
                 // count, so even 1 processor is enough to satisfy the "all" criterion.
                 let memory_region = candidates
                     .keys()
-                    .choose(&mut rng())
+                    .choose(rng)
                     .expect("we picked a random existing index - element must exist");
 
                 let processors = candidates.get(memory_region).expect(
@@ -433,7 +932,7 @@ impl ProcessorSetBuilder {
                 // we know that all candidate memory regions have enough to satisfy our needs.
                 let processors = candidates.values().map(|processors| {
                     processors
-                        .choose(&mut rng())
+                        .choose(rng)
                         .cloned()
                         .expect("we picked a random item from a non-empty list - item must exist")
                 });
@@ -444,11 +943,148 @@ impl ProcessorSetBuilder {
 
         let processors = self.reduce_processors_until_under_quota(processors);
 
-        Some(ProcessorSet::new(
-            NonEmpty::from_vec(processors)?,
-            self.tracker_client,
-            self.pal,
-        ))
+        let processors = NonEmpty::from_vec(processors).ok_or_else(|| {
+            ProcessorSelectionError::new(eliminations, SelectionFailure::NoCandidates)
+        })?;
+
+        Ok(ProcessorSet::new(processors, self.tracker_client, self.pal))
+    }
+
+    /// Resolves all the selections declared via [`named_selection()`][1] together, returning a
+    /// processor set for each of them by name.
+    ///
+    /// The selections are resolved jointly, considering all the selections and
+    /// [anti-affinities][2] at once. This avoids the conflicts that arise when resolving the
+    /// selections one at a time, where an early selection can take processors that a later
+    /// selection cannot do without (e.g. taking a processor from the only memory region large
+    /// enough for a later selection).
+    ///
+    /// The candidates for the selections are determined by the other criteria of the builder,
+    /// such as [`performance_processors_only()`][3] or [`except_reserved()`][4]. Memory region
+    /// criteria such as [`same_memory_region()`][5] are not applied to named selections - use
+    /// [`AntiAffinity::DifferentMemoryRegions`] to keep selections apart instead.
+    ///
+    /// Returns `None` if the selections cannot be satisfied together.
+    ///
+    /// # Resource quota
+    ///
+    /// Unless overridden by [`ignoring_resource_quota()`][6], the call will fail if the total
+    /// number of requested processors is above the process resource quota.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no named selections have been declared, if two selections have the same name
+    /// or if an anti-affinity refers to a selection that has not been declared.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZero;
+    ///
+    /// use many_cpus::{AntiAffinity, ProcessorSet};
+    ///
+    /// let selections = ProcessorSet::builder()
+    ///     .performance_processors_only()
+    ///     .named_selection("network", NonZero::new(1).unwrap())
+    ///     .named_selection("compute", NonZero::new(1).unwrap())
+    ///     .anti_affinity("network", "compute", AntiAffinity::DistinctCores)
+    ///     .take_named();
+    ///
+    /// if let Some(selections) = selections {
+    ///     let network = &selections["network"];
+    ///     let compute = &selections["compute"];
+    ///
+    ///     assert!(network.intersection(compute).is_none());
+    /// }
+    /// ```
+    ///
+    /// [1]: ProcessorSetBuilder::named_selection
+    /// [2]: ProcessorSetBuilder::anti_affinity
+    /// [3]: ProcessorSetBuilder::performance_processors_only
+    /// [4]: ProcessorSetBuilder::except_reserved
+    /// [5]: ProcessorSetBuilder::same_memory_region
+    /// [6]: ProcessorSetBuilder::ignoring_resource_quota
+    #[must_use]
+    pub fn take_named(self) -> Option<BTreeMap<String, ProcessorSet>> {
+        assert!(
+            !self.named_selections.is_empty(),
+            "take_named() requires at least one named_selection()"
+        );
+
+        let index_of = |name: &str| {
+            self.named_selections
+                .iter()
+                .position(|(n, _)| n == name)
+                .unwrap_or_else(|| {
+                    panic!("anti_affinity() refers to undeclared named selection '{name}'")
+                })
+        };
+
+        for (index, (name, _)) in self.named_selections.iter().enumerate() {
+            assert!(
+                index_of(name) == index,
+                "named selection '{name}' is declared more than once"
+            );
+        }
+
+        let anti_affinities = self
+            .anti_affinities
+            .iter()
+            .map(|(a, b, anti_affinity)| (index_of(a), index_of(b), *anti_affinity))
+            .collect_vec();
+
+        let counts = self
+            .named_selections
+            .iter()
+            .map(|(_, count)| count.get())
+            .collect_vec();
+
+        let total_count = counts
+            .iter()
+            .try_fold(0_usize, |total, count| total.checked_add(*count))?;
+
+        if let Some(max_count) = self.resource_quota_processor_count_limit() {
+            if total_count > max_count {
+                return None;
+            }
+        }
+
+        let rng = &mut self.new_rng();
+
+        let (candidates, _) = self.candidates_by_memory_region(total_count, rng);
+        let candidates = candidates.into_values().flatten().collect_vec();
+
+        let assignments = resolve_jointly(
+            &candidates,
+            &counts,
+            &anti_affinities,
+            rng,
+            |p, anti_affinity| match anti_affinity {
+                AntiAffinity::DistinctCores => self.pal.physical_core_id(p.id()),
+                AntiAffinity::DistinctL3Domains => self.pal.l3_cache_domain_id(p.id()),
+                AntiAffinity::DifferentMemoryRegions => p.memory_region_id(),
+            },
+        )?;
+
+        Some(
+            self.named_selections
+                .iter()
+                .zip(assignments)
+                .map(|((name, _), processors)| {
+                    let processors = NonEmpty::from_vec(processors)
+                        .expect("every named selection requests at least one processor");
+
+                    (
+                        name.clone(),
+                        ProcessorSet::new(
+                            processors,
+                            self.tracker_client.clone(),
+                            self.pal.clone(),
+                        ),
+                    )
+                })
+                .collect(),
+        )
     }
 
     fn reduce_processors_until_under_quota(&self, processors: Vec<Processor>) -> Vec<Processor> {
@@ -473,39 +1109,188 @@ impl ProcessorSetBuilder {
     /// as the next stage of filtering (the memory region logic) permits it.
     ///
     /// Returns candidates grouped by memory region, with each returned memory region having at
-    /// least one candidate processor.
-    fn candidates_by_memory_region(&self) -> HashMap<MemoryRegionId, Vec<Processor>> {
-        let candidates_iter = self.all_processors().into_iter().filter_map(move |p| {
-            if self.except_indexes.contains(&p.id()) {
-                return None;
-            }
-
-            let is_acceptable_type = match self.processor_type_selector {
-                ProcessorTypeSelector::Any => true,
-                ProcessorTypeSelector::Performance => {
-                    p.efficiency_class() == EfficiencyClass::Performance
-                }
-                ProcessorTypeSelector::Efficiency => {
-                    p.efficiency_class() == EfficiencyClass::Efficiency
-                }
+    /// least one candidate processor, together with how many processors each constraint removed.
+    ///
+    /// `count` is the number of processors requested, used to pick a package that can satisfy
+    /// the request if the candidates must all be from the same package.
+    fn candidates_by_memory_region(
+        &self,
+        count: usize,
+        rng: &mut StdRng,
+    ) -> (
+        BTreeMap<MemoryRegionId, Vec<Processor>>,
+        Vec<ConstraintElimination>,
+    ) {
+        let mut remaining = self.all_processors().into_iter().collect_vec();
+        let mut eliminations = Vec::with_capacity(self.exclusions.len().saturating_add(1));
+
+        // We pass in the candidates instead of capturing them, so they can be inspected between
+        // constraints (e.g. to decide which package to keep).
+        let mut apply =
+            |remaining: &mut Vec<Processor>,
+             constraint,
+             is_acceptable: &mut dyn FnMut(&Processor) -> bool| {
+                let before_count = remaining.len();
+                remaining.retain(is_acceptable);
+
+                let removed_count = before_count
+                    .checked_sub(remaining.len())
+                    .expect("retaining processors can never add processors");
+
+                eliminations.push(ConstraintElimination::new(
+                    constraint,
+                    removed_count,
+                    remaining.len(),
+                ));
             };
 
-            if !is_acceptable_type {
-                return None;
+        for (constraint, excluded) in &self.exclusions {
+            apply(&mut remaining, *constraint, &mut |p| {
+                !excluded.contains(&p.id())
+            });
+        }
+
+        match self.processor_type_selector {
+            ProcessorTypeSelector::Any => {}
+            ProcessorTypeSelector::Performance => {
+                apply(
+                    &mut remaining,
+                    SelectionConstraint::PerformanceProcessorsOnly,
+                    &mut |p| p.efficiency_class() == EfficiencyClass::Performance,
+                );
+            }
+            ProcessorTypeSelector::Efficiency => {
+                apply(
+                    &mut remaining,
+                    SelectionConstraint::EfficiencyProcessorsOnly,
+                    &mut |p| p.efficiency_class() == EfficiencyClass::Efficiency,
+                );
+            }
+        }
+
+        if self.distinct_cores {
+            // We apply this last, so a core is only skipped if none of its processors qualify.
+            // The platform does not promise any order of the processors, so we explicitly keep
+            // the remaining processor with the lowest ID in each core.
+            let mut lowest_per_core = HashMap::<ProcessorId, ProcessorId>::default();
+
+            for processor in &remaining {
+                lowest_per_core
+                    .entry(self.pal.physical_core_id(processor.id()))
+                    .and_modify(|lowest| *lowest = (*lowest).min(processor.id()))
+                    .or_insert(processor.id());
             }
 
-            Some((p.memory_region_id(), p))
-        });
+            apply(
+                &mut remaining,
+                SelectionConstraint::DistinctCores,
+                &mut |p| lowest_per_core.get(&self.pal.physical_core_id(p.id())) == Some(&p.id()),
+            );
+        }
+
+        if self.same_package {
+            // We apply this after the other constraints, so we only consider packages based on
+            // the candidates they have left after everything else has had its say.
+            let package = self.choose_group(&remaining, count, Processor::package_id, rng);
+
+            apply(&mut remaining, SelectionConstraint::SamePackage, &mut |p| {
+                Some(p.package_id()) == package
+            });
+        }
+
+        if self.same_l3_domain {
+            let l3_domain_of = |p: &Processor| self.pal.l3_cache_domain_id(p.id());
+            let l3_domain = self.choose_group(&remaining, count, l3_domain_of, rng);
 
-        let mut candidates = HashMap::new();
-        for (region, processor) in candidates_iter {
+            apply(
+                &mut remaining,
+                SelectionConstraint::SameL3Domain,
+                &mut |p| Some(l3_domain_of(p)) == l3_domain,
+            );
+        }
+
+        // Ordered, so that iterating over the memory regions is deterministic.
+        let mut candidates = BTreeMap::new();
+        for processor in remaining {
+            let region = processor.memory_region_id();
             candidates
                 .entry(region)
                 .or_insert_with(Vec::new)
                 .push(processor);
         }
 
-        candidates
+        (candidates, eliminations)
+    }
+
+    /// Picks a random group of processors (e.g. a package), as identified by `group_of`, whose
+    /// candidates can satisfy a request for `count` processors, considering the memory region
+    /// constraints. If there is no such group, picks the one that comes closest, so the request
+    /// fails with an explanation of what was missing.
+    ///
+    /// Returns `None` if there are no candidates at all.
+    fn choose_group(
+        &self,
+        candidates: &[Processor],
+        count: usize,
+        group_of: impl Fn(&Processor) -> u32,
+        rng: &mut StdRng,
+    ) -> Option<u32> {
+        let mut by_group: BTreeMap<u32, Vec<&Processor>> = BTreeMap::new();
+
+        for processor in candidates {
+            by_group
+                .entry(group_of(processor))
+                .or_default()
+                .push(processor);
+        }
+
+        // How many processors each group could contribute to the request.
+        let capacities = by_group
+            .into_iter()
+            .map(|(group, processors)| {
+                let capacity = match self.memory_region_selector {
+                    MemoryRegionSelector::Any
+                    | MemoryRegionSelector::PreferSame
+                    | MemoryRegionSelector::PreferDifferent => processors.len(),
+                    MemoryRegionSelector::RequireSame => processors
+                        .iter()
+                        .counts_by(|p| p.memory_region_id())
+                        .into_values()
+                        .max()
+                        .unwrap_or_default(),
+                    MemoryRegionSelector::RequireDifferent => processors
+                        .iter()
+                        .map(|p| p.memory_region_id())
+                        .unique()
+                        .count(),
+                };
+
+                (group, capacity)
+            })
+            .collect_vec();
+
+        let qualifying_groups = capacities
+            .iter()
+            .filter(|(_, capacity)| *capacity >= count)
+            .map(|(group, _)| *group)
+            .collect_vec();
+
+        qualifying_groups.choose(rng).copied().or_else(|| {
+            capacities
+                .iter()
+                .max_by_key(|(_, capacity)| *capacity)
+                .map(|(group, _)| *group)
+        })
+    }
+
+    /// The source of randomness for one selection, seeded via [`with_seed()`][1] if requested.
+    ///
+    /// [1]: ProcessorSetBuilder::with_seed
+    fn new_rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
+        }
     }
 
     fn all_processors(&self) -> NonEmpty<Processor> {
@@ -516,22 +1301,7 @@ impl ProcessorSetBuilder {
 
     fn resource_quota_processor_count_limit(&self) -> Option<usize> {
         if self.obey_resource_quota {
-            let max_processor_time = self.pal.max_processor_time();
-
-            // We round down the quota to get a whole number of processors.
-            // We specifically round down because our goal with the resource quota is to never
-            // exceed it, even by a fraction, as that would cause quality of service degradation.
-            #[expect(clippy::cast_sign_loss, reason = "quota cannot be negative")]
-            #[expect(
-                clippy::cast_possible_truncation,
-                reason = "we are correctly rounding to avoid the problem"
-            )]
-            let max_processor_count = max_processor_time.floor() as usize;
-
-            // We can never restrict to less than 1 processor by quota because that would be
-            // nonsense - there is always some available processor time, so at least one
-            // processor must be usable. Therefore, we round below 1, and round down above 1.
-            Some(max_processor_count.max(1))
+            Some(processor_count_limit(self.pal.max_processor_time()))
         } else {
             None
         }
@@ -545,6 +1315,15 @@ impl Default for ProcessorSetBuilder {
     }
 }
 
+/// The frequency we compare processors by. This is the maximum frequency if the platform reports
+/// it, otherwise the base frequency, or zero if the platform reports neither.
+fn comparable_frequency_mhz(processor: &Processor) -> u32 {
+    processor
+        .max_frequency_mhz()
+        .or_else(|| processor.base_frequency_mhz())
+        .unwrap_or_default()
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 enum MemoryRegionSelector {
     /// The default - memory regions are not considered in processor selection.
@@ -711,7 +1490,9 @@ mod tests_real {
 
 #[cfg(test)]
 mod tests {
-    use crate::pal::{FakeProcessor, MockPlatform, ProcessorFacade};
+    use crate::pal::{
+        FAKE_PERFORMANCE_FREQUENCY_MHZ, FakeProcessor, MockPlatform, ProcessorFacade,
+    };
     use folo_utils::nz;
     use nonempty::nonempty;
 
@@ -727,11 +1508,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -754,11 +1537,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -774,29 +1559,70 @@ mod tests {
     }
 
     #[test]
-    fn efficiency_class_filter_take_all() {
+    fn efficiency_class_parameter_take_all() {
         let pal_processors = nonempty![
             FakeProcessor {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
-        let platform = new_mock_platform(pal_processors);
+        let platform = new_mock_platform_with_get_count(pal_processors, 2, 2);
+        let pal: PlatformFacade = platform.into();
 
-        let builder = ProcessorSetBuilder::with_internals(
+        let set = ProcessorSetBuilder::with_internals(
             HardwareTrackerClientFacade::default_mock(),
-            platform.into(),
-        );
-        let set = builder.efficiency_processors_only().take_all().unwrap();
+            pal.clone(),
+        )
+        .efficiency_class(EfficiencyClass::Performance)
+        .take_all()
+        .unwrap();
         assert_eq!(set.len(), 1);
-        assert_eq!(set.processors().first().id(), 0);
+        assert_eq!(set.processors().first().id(), 1);
+
+        let set =
+            ProcessorSetBuilder::with_internals(HardwareTrackerClientFacade::default_mock(), pal)
+                .efficiency_class(EfficiencyClass::Efficiency)
+                .take_all()
+                .unwrap();
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.processors().first().id(), 0);
+    }
+
+    #[test]
+    fn efficiency_class_filter_take_all() {
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            }
+        ];
+
+        let platform = new_mock_platform(pal_processors);
+
+        let builder = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        );
+        let set = builder.efficiency_processors_only().take_all().unwrap();
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.processors().first().id(), 0);
     }
 
     #[test]
@@ -806,16 +1632,19 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             }
         ];
 
@@ -836,11 +1665,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -881,11 +1712,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -911,6 +1744,152 @@ mod tests {
         assert_eq!(set.unwrap().len(), 2);
     }
 
+    #[test]
+    fn try_take_explains_eliminations() {
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 2,
+                memory_region: 1,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 3,
+                memory_region: 1,
+                efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
+            }
+        ];
+
+        // .filter() eagerly evaluates processors, so we need to allow 2 calls.
+        let platform = new_mock_platform_with_get_count(pal_processors, 2, 1);
+
+        let builder = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        );
+        let error = builder
+            .filter(|p| p.id() != 0)
+            .performance_processors_only()
+            .try_take(nz!(2))
+            .unwrap_err();
+
+        assert_eq!(
+            error.eliminations(),
+            &[
+                ConstraintElimination::new(SelectionConstraint::Filter, 1, 3),
+                ConstraintElimination::new(SelectionConstraint::PerformanceProcessorsOnly, 2, 1),
+            ]
+        );
+        assert_eq!(
+            error.failure(),
+            SelectionFailure::NotEnoughCandidates {
+                requested: 2,
+                candidates: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "2 processors requested but only 1 candidates remained (filter() removed 1, performance_processors_only() removed 2, 1 remained)"
+        );
+    }
+
+    #[test]
+    fn try_take_explains_quota() {
+        let mut platform = MockPlatform::new();
+
+        // There is only 1.0 processors worth of quota available.
+        platform
+            .expect_max_processor_time()
+            .times(1)
+            .return_const(1.0);
+
+        let builder = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        );
+        let error = builder.try_take(nz!(2)).unwrap_err();
+
+        // We fail before considering any processors, so nothing was eliminated.
+        assert!(error.eliminations().is_empty());
+        assert_eq!(
+            error.failure(),
+            SelectionFailure::ResourceQuotaExceeded {
+                requested: 2,
+                limit: 1
+            }
+        );
+    }
+
+    #[test]
+    fn try_take_explains_memory_regions() {
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 2,
+                memory_region: 1,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            }
+        ];
+
+        let platform = new_mock_platform_with_get_count(pal_processors, 2, 2);
+        let pal: PlatformFacade = platform.into();
+
+        let error = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            pal.clone(),
+        )
+        .same_memory_region()
+        .try_take(nz!(3))
+        .unwrap_err();
+
+        assert_eq!(
+            error.failure(),
+            SelectionFailure::NotEnoughInAnyMemoryRegion {
+                requested: 3,
+                largest_memory_region: 2
+            }
+        );
+
+        let error =
+            ProcessorSetBuilder::with_internals(HardwareTrackerClientFacade::default_mock(), pal)
+                .different_memory_regions()
+                .try_take(nz!(3))
+                .unwrap_err();
+
+        assert_eq!(
+            error.failure(),
+            SelectionFailure::NotEnoughMemoryRegions {
+                requested: 3,
+                memory_regions: 2
+            }
+        );
+    }
+
     #[test]
     fn take_n_quota_limit_min_1() {
         let pal_processors = nonempty![
@@ -918,11 +1897,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -956,11 +1937,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -993,11 +1976,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1032,6 +2017,7 @@ mod tests {
             index: 0,
             memory_region: 0,
             efficiency_class: EfficiencyClass::Efficiency,
+            package: 0,
         }];
 
         // We expect 0 calls to max_processor_time() because we failed early in the build.
@@ -1052,11 +2038,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1084,11 +2072,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1115,11 +2105,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1142,11 +2134,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1169,11 +2163,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1194,11 +2190,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1219,21 +2217,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1259,21 +2261,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1292,6 +2298,589 @@ mod tests {
         );
     }
 
+    #[test]
+    fn distinct_cores_take_all() {
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 2,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 3,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            }
+        ];
+
+        // .except() does not evaluate processors, so we only need one call per build.
+        let mut platform = new_mock_platform_with_get_count(pal_processors, 2, 2);
+
+        // Processors 0 and 2 share a core, as do 1 and 3.
+        platform
+            .expect_physical_core_id()
+            .returning(|processor_id| processor_id % 2);
+
+        let pal: PlatformFacade = platform.into();
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            pal.clone(),
+        )
+        .distinct_cores()
+        .take_all()
+        .unwrap();
+        assert_eq!(
+            set.processors().iter().map(Processor::id).collect_vec(),
+            [0, 1]
+        );
+
+        // If a processor is excluded, a sibling in the same core is used instead.
+        let except = set.processors().first().clone();
+
+        let set =
+            ProcessorSetBuilder::with_internals(HardwareTrackerClientFacade::default_mock(), pal)
+                .except([&except])
+                .distinct_cores()
+                .take_all()
+                .unwrap();
+        assert_eq!(
+            set.processors().iter().map(Processor::id).collect_vec(),
+            [1, 2]
+        );
+    }
+
+    #[test]
+    fn distinct_cores_keeps_lowest_id_regardless_of_order() {
+        // The platform does not promise to return processors in ascending ID order.
+        let pal_processors = NonEmpty::collect((0..4).rev().map(|index| FakeProcessor {
+            index,
+            memory_region: 0,
+            efficiency_class: EfficiencyClass::Performance,
+            package: 0,
+        }))
+        .unwrap();
+
+        let mut platform = new_mock_platform_with_get_count(pal_processors, 1, 1);
+
+        // Processors 0 and 2 share a core, as do 1 and 3.
+        platform
+            .expect_physical_core_id()
+            .returning(|processor_id| processor_id % 2);
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .distinct_cores()
+        .take_all()
+        .unwrap();
+
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [0, 1]
+        );
+    }
+
+    #[test]
+    fn same_l3_domain_take() {
+        let pal_processors = NonEmpty::collect((0..4).map(|index| FakeProcessor {
+            index,
+            memory_region: 0,
+            efficiency_class: EfficiencyClass::Performance,
+            package: 0,
+        }))
+        .unwrap();
+
+        let mut platform = new_mock_platform_with_get_count(pal_processors, 3, 3);
+
+        // Processors 0 and 1 share an L3 cache, as do 2 and 3.
+        platform
+            .expect_l3_cache_domain_id()
+            .returning(|processor_id| processor_id / 2);
+
+        let pal: PlatformFacade = platform.into();
+        let builder = || {
+            ProcessorSetBuilder::with_internals(
+                HardwareTrackerClientFacade::default_mock(),
+                pal.clone(),
+            )
+        };
+
+        let set = builder().same_l3_domain().take(nz!(2)).unwrap();
+        let l3_domains = set
+            .processors()
+            .iter()
+            .map(|p| p.id() / 2)
+            .unique()
+            .collect_vec();
+        assert_eq!(l3_domains.len(), 1);
+
+        // Only the domain of processors 2 and 3 still has enough candidates.
+        let except = Processor::new(FakeProcessor::with_index(0).into());
+        let set = builder()
+            .except([&except])
+            .same_l3_domain()
+            .take(nz!(2))
+            .unwrap();
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [2, 3]
+        );
+
+        let error = builder().same_l3_domain().try_take(nz!(3)).unwrap_err();
+        assert_eq!(
+            error.eliminations(),
+            [ConstraintElimination::new(
+                SelectionConstraint::SameL3Domain,
+                2,
+                2
+            )]
+        );
+    }
+
+    #[test]
+    fn seeded_selection_is_deterministic() {
+        let pal_processors = NonEmpty::from_vec(
+            (0..16)
+                .map(|index| FakeProcessor {
+                    index,
+                    memory_region: index % 4,
+                    efficiency_class: EfficiencyClass::Performance,
+                    package: 0,
+                })
+                .collect_vec(),
+        )
+        .unwrap();
+
+        let platform = new_mock_platform_with_get_count(pal_processors, 2, 2);
+        let pal: PlatformFacade = platform.into();
+
+        let take = || {
+            ProcessorSetBuilder::with_internals(
+                HardwareTrackerClientFacade::default_mock(),
+                pal.clone(),
+            )
+            .with_seed(42)
+            .prefer_different_memory_regions()
+            .take(nz!(6))
+            .unwrap()
+            .processors()
+            .iter()
+            .map(Processor::id)
+            .collect_vec()
+        };
+
+        assert_eq!(take(), take());
+    }
+
+    #[test]
+    fn take_named_resolves_jointly() {
+        // Region 0 has four processors, region 1 has two.
+        let pal_processors = nonempty![0, 1, 2, 3, 4, 5].map(|index| FakeProcessor {
+            index,
+            memory_region: u32::from(index >= 4),
+            efficiency_class: EfficiencyClass::Performance,
+            package: 0,
+        });
+
+        let platform = new_mock_platform(pal_processors);
+
+        let selections = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .named_selection("small", nz!(2))
+        .named_selection("large", nz!(4))
+        .anti_affinity("small", "large", AntiAffinity::DifferentMemoryRegions)
+        .take_named()
+        .unwrap();
+
+        let ids = |name: &str| {
+            selections
+                .get(name)
+                .unwrap()
+                .processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec()
+        };
+
+        assert_eq!(ids("small"), [4, 5]);
+        assert_eq!(ids("large"), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn take_named_unsatisfiable() {
+        let pal_processors = nonempty![0, 1].map(FakeProcessor::with_index);
+
+        let platform = new_mock_platform(pal_processors);
+
+        let selections = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .named_selection("a", nz!(1))
+        .named_selection("b", nz!(1))
+        .anti_affinity("a", "b", AntiAffinity::DifferentMemoryRegions)
+        .take_named();
+
+        assert!(selections.is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn take_named_rejects_unknown_name() {
+        let platform = MockPlatform::new();
+
+        _ = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .named_selection("a", nz!(1))
+        .anti_affinity("a", "b", AntiAffinity::DistinctCores)
+        .take_named();
+    }
+
+    #[test]
+    fn except_reserved_skips_reserved() {
+        // The reservation registry is process-wide, so we use processor IDs no other test uses.
+        let pal_processors = nonempty![8000, 8001, 8002].map(|index| FakeProcessor {
+            index,
+            memory_region: 0,
+            efficiency_class: EfficiencyClass::Performance,
+            package: 0,
+        });
+
+        let platform = new_mock_platform(pal_processors);
+
+        let reserved = ProcessorSet::new(
+            nonempty![Processor::new(FakeProcessor::with_index(8001).into())],
+            HardwareTrackerClientFacade::default_mock(),
+            MockPlatform::new().into(),
+        );
+        let reservation = reserved.reserve();
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .except_reserved()
+        .take_all()
+        .unwrap();
+
+        drop(reservation);
+
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [8000, 8002]
+        );
+    }
+
+    #[test]
+    fn max_distance_from_filters() {
+        let pal_processors = nonempty![0, 1, 2, 3].map(|index| FakeProcessor {
+            index,
+            memory_region: index,
+            efficiency_class: EfficiencyClass::Performance,
+            package: 0,
+        });
+
+        // .max_distance_from() evaluates processors immediately, so it needs one more call.
+        let mut platform = new_mock_platform_with_get_count(pal_processors, 2, 1);
+
+        // Regions 0 and 1 are near each other, 2 is further away and 3 is distant.
+        platform
+            .expect_memory_region_distance()
+            .returning(|from, to| match (from, to) {
+                (0, 0) => 10,
+                (0, 1) => 12,
+                (0, 2) => 20,
+                _ => 30,
+            });
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .max_distance_from(0, 20)
+        .take_all()
+        .unwrap();
+
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::memory_region_id)
+                .sorted()
+                .collect_vec(),
+            [0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn excluding_irq_heavy_filters() {
+        let pal_processors = nonempty![0, 1, 2, 3].map(FakeProcessor::with_index);
+
+        let mut platform = new_mock_platform_with_get_count(pal_processors, 1, 1);
+
+        // The average is 4000, so with a limit of 4800 processors 0 and 1 are interrupt-heavy.
+        // Processor 3 is not reported, so it is not excluded.
+        platform
+            .expect_interrupt_counts()
+            .times(1)
+            .return_const(vec![(0, 6000), (1, 5000), (2, 1000)]);
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .excluding_irq_heavy(1.2)
+        .take_all()
+        .unwrap();
+
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [2, 3]
+        );
+    }
+
+    #[test]
+    fn requiring_feature_filters() {
+        let pal_processors = nonempty![0, 1].map(FakeProcessor::with_index);
+
+        // .requiring_feature() evaluates processors immediately, so it needs one more call.
+        let platform = new_mock_platform_with_get_count(pal_processors, 2, 1);
+
+        // Fake processors do not support any features, so nothing is left.
+        let result = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .requiring_feature(ProcessorFeature::Avx512)
+        .try_take_all();
+
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.eliminations(),
+            &[ConstraintElimination::new(
+                SelectionConstraint::RequiringFeature,
+                2,
+                0
+            )]
+        );
+        assert_eq!(error.failure(), SelectionFailure::NoCandidates);
+    }
+
+    #[test]
+    fn frequency_at_least_filters() {
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            }
+        ];
+
+        // .frequency_at_least() evaluates processors immediately, so it needs one more call.
+        let platform = new_mock_platform_with_get_count(pal_processors, 2, 1);
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .frequency_at_least(FAKE_PERFORMANCE_FREQUENCY_MHZ)
+        .take_all()
+        .unwrap();
+
+        assert_eq!(
+            set.processors().iter().map(Processor::id).collect_vec(),
+            [1]
+        );
+    }
+
+    #[test]
+    fn fastest_prefers_higher_frequency() {
+        let pal_processors = nonempty![0, 1, 2, 3].map(|index| FakeProcessor {
+            index,
+            memory_region: 0,
+            efficiency_class: if index % 2 == 0 {
+                EfficiencyClass::Efficiency
+            } else {
+                EfficiencyClass::Performance
+            },
+            package: 0,
+        });
+
+        // The candidates are selected ignoring the quota, so the quota is only checked once.
+        let platform = new_mock_platform_with_get_count(pal_processors, 1, 1);
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .fastest(nz!(2))
+        .unwrap();
+
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [1, 3]
+        );
+    }
+
+    #[test]
+    fn fastest_not_enough_processors() {
+        let pal_processors = nonempty![0, 1].map(FakeProcessor::with_index);
+
+        let platform = new_mock_platform_with_get_count(pal_processors, 1, 0);
+
+        assert!(
+            ProcessorSetBuilder::with_internals(
+                HardwareTrackerClientFacade::default_mock(),
+                platform.into(),
+            )
+            .ignoring_resource_quota()
+            .fastest(nz!(3))
+            .is_none()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn excluding_irq_heavy_rejects_invalid_threshold() {
+        let platform = MockPlatform::new();
+
+        _ = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            platform.into(),
+        )
+        .excluding_irq_heavy(f64::NAN);
+    }
+
+    #[test]
+    fn package_filters() {
+        let pal_processors = nonempty![
+            FakeProcessor {
+                index: 0,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 0,
+            },
+            FakeProcessor {
+                index: 1,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 1,
+            },
+            FakeProcessor {
+                index: 2,
+                memory_region: 0,
+                efficiency_class: EfficiencyClass::Performance,
+                package: 1,
+            }
+        ];
+
+        // .package() evaluates processors immediately, so it needs one more call than the rest.
+        let platform = new_mock_platform_with_get_count(pal_processors, 4, 3);
+        let pal: PlatformFacade = platform.into();
+
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            pal.clone(),
+        )
+        .package(1)
+        .take_all()
+        .unwrap();
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [1, 2]
+        );
+
+        // Only package 1 has enough candidates to satisfy the request.
+        let set = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            pal.clone(),
+        )
+        .same_package()
+        .take(nz!(2))
+        .unwrap();
+        assert_eq!(
+            set.processors()
+                .iter()
+                .map(Processor::id)
+                .sorted()
+                .collect_vec(),
+            [1, 2]
+        );
+
+        // No package has enough, so the largest one is picked and the request fails.
+        let error =
+            ProcessorSetBuilder::with_internals(HardwareTrackerClientFacade::default_mock(), pal)
+                .same_package()
+                .try_take(nz!(3))
+                .unwrap_err();
+        assert_eq!(
+            error.eliminations(),
+            [ConstraintElimination::new(
+                SelectionConstraint::SamePackage,
+                1,
+                2
+            )]
+        );
+        assert_eq!(
+            error.failure(),
+            SelectionFailure::NotEnoughCandidates {
+                requested: 3,
+                candidates: 2
+            }
+        );
+    }
+
     #[test]
     fn filter_combinations() {
         let pal_processors = nonempty![
@@ -1299,16 +2888,19 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             }
         ];
 
@@ -1338,16 +2930,19 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             }
         ];
 
@@ -1370,21 +2965,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 2,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 3,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1410,6 +3009,7 @@ mod tests {
             index: 0,
             memory_region: 0,
             efficiency_class: EfficiencyClass::Efficiency,
+            package: 0,
         }];
 
         // We expect 0 calls to max_processor_time() because we failed early in the build.
@@ -1431,11 +3031,13 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             }
         ];
 
@@ -1459,16 +3061,19 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             }
         ];
 
@@ -1492,21 +3097,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 2,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1536,21 +3145,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 2,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1577,21 +3190,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 2,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1615,21 +3232,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 2,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
@@ -1660,21 +3281,25 @@ mod tests {
                 index: 0,
                 memory_region: 0,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 1,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             },
             FakeProcessor {
                 index: 2,
                 memory_region: 1,
                 efficiency_class: EfficiencyClass::Efficiency,
+                package: 0,
             },
             FakeProcessor {
                 index: 3,
                 memory_region: 2,
                 efficiency_class: EfficiencyClass::Performance,
+                package: 0,
             }
         ];
 
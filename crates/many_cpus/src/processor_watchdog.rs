@@ -0,0 +1,260 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{self, AtomicBool},
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{PollScheduler, ProcessorId, ProcessorSet};
+
+/// The processor a thread of the current process was observed executing on, as reported by
+/// [`ProcessorSet::threads_outside()`] and [`ProcessorWatchdog`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ThreadPlacement {
+    thread_id: u64,
+    thread_name: Option<String>,
+    processor_id: ProcessorId,
+}
+
+impl ThreadPlacement {
+    #[must_use]
+    pub(crate) fn new(
+        thread_id: u64,
+        thread_name: Option<String>,
+        processor_id: ProcessorId,
+    ) -> Self {
+        Self {
+            thread_id,
+            thread_name,
+            processor_id,
+        }
+    }
+
+    /// The operating system ID of the thread.
+    ///
+    /// This is not the same as the ID of a [`std::thread::Thread`], which is only meaningful
+    /// within the Rust standard library.
+    #[must_use]
+    #[inline]
+    pub fn thread_id(&self) -> u64 {
+        self.thread_id
+    }
+
+    /// The name of the thread, if it has one.
+    ///
+    /// The operating system may truncate the name (e.g. to 15 bytes on Linux).
+    #[must_use]
+    #[inline]
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread_name.as_deref()
+    }
+
+    /// The processor the thread was last executing on when it was observed.
+    ///
+    /// For a thread that is not currently running, this is where it last ran,
+    /// which may have been some time ago.
+    #[must_use]
+    #[inline]
+    pub fn processor_id(&self) -> ProcessorId {
+        self.processor_id
+    }
+}
+
+/// Periodically verifies that no thread of the current process is executing on processors
+/// outside an allowed processor set, reporting every thread observed outside the set.
+///
+/// This can be used to detect threads that do not stay away from isolated processors, e.g.
+/// threads spawned by third-party libraries that ignore the processor affinity the rest of the
+/// application is carefully maintaining.
+///
+/// The watchdog checks the threads on its own thread whenever the [`PollScheduler`] reports that
+/// a check is due, calling the violation callback for every thread observed outside the allowed
+/// set. A thread that stays outside the allowed set is reported again on every check. The
+/// watchdog thread itself is pinned to the allowed processors, so it never reports itself.
+///
+/// The watchdog stops when this is dropped.
+///
+/// # Operating system compatibility
+///
+/// This is only supported on Linux. On other operating systems, the watchdog never observes
+/// any threads and therefore never reports any violations.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use many_cpus::{IntervalPollScheduler, ProcessorSet, ProcessorWatchdog};
+///
+/// let allowed = ProcessorSet::default();
+///
+/// let watchdog = ProcessorWatchdog::start(
+///     allowed,
+///     IntervalPollScheduler::new(Duration::from_secs(1)),
+///     |violation| {
+///         eprintln!(
+///             "thread {} ({:?}) is executing on forbidden processor {}",
+///             violation.thread_id(),
+///             violation.thread_name(),
+///             violation.processor_id()
+///         );
+///     },
+/// );
+///
+/// // ... run the application ...
+///
+/// drop(watchdog);
+/// ```
+#[derive(Debug)]
+pub struct ProcessorWatchdog {
+    stop_requested: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ProcessorWatchdog {
+    /// Starts a watchdog that checks whether all threads of the current process are executing
+    /// on processors in `allowed` whenever `scheduler` reports that a check is due, calling
+    /// `on_violation` for every thread observed outside `allowed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the watchdog thread cannot be spawned.
+    #[must_use]
+    pub fn start(
+        allowed: ProcessorSet,
+        mut scheduler: impl PollScheduler,
+        on_violation: impl Fn(&ThreadPlacement) + Send + 'static,
+    ) -> Self {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::Builder::new()
+            .name("many_cpus_processor_watchdog".to_string())
+            .spawn({
+                let stop_requested = Arc::clone(&stop_requested);
+
+                move || {
+                    allowed.pin_current_thread_to();
+
+                    loop {
+                        let is_due = scheduler.wait();
+
+                        if stop_requested.load(atomic::Ordering::Acquire) {
+                            return;
+                        }
+
+                        if is_due {
+                            for violation in allowed.threads_outside() {
+                                on_violation(&violation);
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn processor watchdog thread");
+
+        Self {
+            stop_requested,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for ProcessorWatchdog {
+    fn drop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+
+        self.stop_requested.store(true, atomic::Ordering::Release);
+        thread.thread().unpark();
+
+        // If we are already panicking, we do not want to turn it into an abort.
+        assert!(
+            thread.join().is_ok() || thread::panicking(),
+            "processor watchdog thread panicked - the violation callback must have panicked"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, mpsc};
+
+    use nonempty::nonempty;
+
+    use crate::{
+        HardwareTrackerClientFacade, MockHardwareTrackerClient, Processor,
+        pal::{FakeProcessor, MockPlatform, PlatformFacade},
+    };
+
+    use super::*;
+
+    /// Reports a check as due a fixed number of times, then notifies the test and waits to
+    /// be stopped.
+    struct CountedScheduler {
+        remaining_checks: usize,
+        checks_completed: mpsc::Sender<()>,
+    }
+
+    impl PollScheduler for CountedScheduler {
+        fn wait(&mut self) -> bool {
+            let Some(remaining_checks) = self.remaining_checks.checked_sub(1) else {
+                // The previous check was the last one. The test may be gone already.
+                drop(self.checks_completed.send(()));
+                thread::park();
+                return false;
+            };
+
+            self.remaining_checks = remaining_checks;
+            true
+        }
+    }
+
+    #[test]
+    fn reports_threads_outside_allowed_set() {
+        let mut platform = MockPlatform::new();
+
+        platform
+            .expect_pin_current_thread_to_core()
+            .return_const(());
+        platform
+            .expect_current_process_threads()
+            .times(2)
+            .return_const(vec![
+                ThreadPlacement::new(1, Some("main".to_string()), 0),
+                ThreadPlacement::new(2, Some("stray".to_string()), 5),
+                ThreadPlacement::new(3, None, 1),
+            ]);
+
+        let mut tracker_client = MockHardwareTrackerClient::new();
+        tracker_client.expect_update_pin_status().return_const(());
+
+        let allowed = ProcessorSet::new(
+            nonempty![0, 1].map(|index| Processor::new(FakeProcessor::with_index(index).into())),
+            HardwareTrackerClientFacade::from_mock(tracker_client),
+            PlatformFacade::from_mock(platform),
+        );
+
+        let violations = Arc::new(Mutex::new(Vec::new()));
+        let (checks_completed_tx, checks_completed_rx) = mpsc::channel();
+
+        let watchdog = ProcessorWatchdog::start(
+            allowed,
+            CountedScheduler {
+                remaining_checks: 2,
+                checks_completed: checks_completed_tx,
+            },
+            {
+                let violations = Arc::clone(&violations);
+                move |violation| violations.lock().unwrap().push(violation.clone())
+            },
+        );
+
+        checks_completed_rx.recv().unwrap();
+        drop(watchdog);
+
+        let stray = ThreadPlacement::new(2, Some("stray".to_string()), 5);
+        assert_eq!(*violations.lock().unwrap(), [stray.clone(), stray]);
+    }
+}
@@ -7,11 +7,15 @@
 #[derive(Debug)]
 pub struct ResourceQuota {
     max_processor_time: f64,
+    processor_weight: Option<f64>,
 }
 
 impl ResourceQuota {
-    pub(crate) fn new(max_processor_time: f64) -> Self {
-        Self { max_processor_time }
+    pub(crate) fn new(max_processor_time: f64, processor_weight: Option<f64>) -> Self {
+        Self {
+            max_processor_time,
+            processor_weight,
+        }
     }
 
     /// How many seconds of processor time the process is allowed to use per second of real time.
@@ -22,4 +26,39 @@ impl ResourceQuota {
     pub fn max_processor_time(&self) -> f64 {
         self.max_processor_time
     }
+
+    /// The scheduler weight of the process, relative to the default weight of the operating
+    /// system, or `None` if the operating system does not assign a weight to the process.
+    ///
+    /// Unlike [`max_processor_time()`][Self::max_processor_time], which is a hard cap, the weight
+    /// is a soft share: it only matters when processors are contended, in which case a process
+    /// with weight 2.0 gets twice the processor time of a process with the default weight of 1.0.
+    /// When processors are idle, a process may use more than its share.
+    ///
+    /// The weight comes from `cpu.weight` (cgroups v2) or `cpu.shares` (cgroups v1) on Linux and
+    /// from weight-based job CPU rate control on Windows.
+    #[must_use]
+    #[inline]
+    pub fn processor_weight(&self) -> Option<f64> {
+        self.processor_weight
+    }
+}
+
+/// The maximum number of processors a process may use without exceeding a resource quota that
+/// allows `max_processor_time` seconds of processor time per second of real time.
+pub(crate) fn processor_count_limit(max_processor_time: f64) -> usize {
+    // We round down the quota to get a whole number of processors.
+    // We specifically round down because our goal with the resource quota is to never
+    // exceed it, even by a fraction, as that would cause quality of service degradation.
+    #[expect(clippy::cast_sign_loss, reason = "quota cannot be negative")]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "we are correctly rounding to avoid the problem"
+    )]
+    let max_processor_count = max_processor_time.floor() as usize;
+
+    // We can never restrict to less than 1 processor by quota because that would be
+    // nonsense - there is always some available processor time, so at least one
+    // processor must be usable. Therefore, we round below 1, and round down above 1.
+    max_processor_count.max(1)
 }
@@ -0,0 +1,253 @@
+//! Optional `serde` support for persisting processors and processor sets, e.g. in configuration
+//! files.
+//!
+//! Processors are stored as plain records of their ID and metadata. When loaded, the records are
+//! reconciled against the live hardware topology: every stored processor must still be available
+//! to the current process and still have the same metadata, as a stored set that no longer
+//! matches the hardware would not mean what it meant when it was stored.
+
+use derive_more::derive::Display;
+use nonempty::NonEmpty;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::{
+    EfficiencyClass, HardwareTrackerClientFacade, MemoryRegionId, Processor, ProcessorId,
+    ProcessorSet,
+    pal::{Platform, PlatformFacade},
+};
+
+/// The stored form of a [`Processor`].
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct ProcessorRecord {
+    id: ProcessorId,
+    memory_region_id: MemoryRegionId,
+    efficiency_class: EfficiencyClass,
+}
+
+impl From<&Processor> for ProcessorRecord {
+    fn from(processor: &Processor) -> Self {
+        Self {
+            id: processor.id(),
+            memory_region_id: processor.memory_region_id(),
+            efficiency_class: processor.efficiency_class(),
+        }
+    }
+}
+
+/// Why stored processors could not be reconciled against the live hardware topology.
+#[derive(Debug, Display, Eq, PartialEq)]
+enum ReconcileError {
+    #[display("stored processor set is empty")]
+    Empty,
+
+    #[display(
+        "stored processor {processor_id} does not exist or is not available to the current process"
+    )]
+    Unavailable { processor_id: ProcessorId },
+
+    #[display(
+        "stored processor {processor_id} was in memory region {stored} but is now in memory region {actual}"
+    )]
+    MemoryRegionMismatch {
+        processor_id: ProcessorId,
+        stored: MemoryRegionId,
+        actual: MemoryRegionId,
+    },
+
+    #[display(
+        "stored processor {processor_id} was a {stored:?} processor but is now a {actual:?} processor"
+    )]
+    EfficiencyClassMismatch {
+        processor_id: ProcessorId,
+        stored: EfficiencyClass,
+        actual: EfficiencyClass,
+    },
+}
+
+/// Resolves the stored processors to the live processors with the same IDs, verifying that the
+/// metadata of each processor has not changed since it was stored.
+fn reconcile(
+    records: Vec<ProcessorRecord>,
+    pal: &PlatformFacade,
+) -> Result<NonEmpty<Processor>, ReconcileError> {
+    let all_processors = pal.get_all_processors().map(Processor::new);
+
+    let processors = records
+        .into_iter()
+        .map(|record| {
+            let processor = all_processors.iter().find(|p| p.id() == record.id).ok_or(
+                ReconcileError::Unavailable {
+                    processor_id: record.id,
+                },
+            )?;
+
+            if processor.memory_region_id() != record.memory_region_id {
+                return Err(ReconcileError::MemoryRegionMismatch {
+                    processor_id: record.id,
+                    stored: record.memory_region_id,
+                    actual: processor.memory_region_id(),
+                });
+            }
+
+            if processor.efficiency_class() != record.efficiency_class {
+                return Err(ReconcileError::EfficiencyClassMismatch {
+                    processor_id: record.id,
+                    stored: record.efficiency_class,
+                    actual: processor.efficiency_class(),
+                });
+            }
+
+            Ok(processor.clone())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    NonEmpty::from_vec(processors).ok_or(ReconcileError::Empty)
+}
+
+impl Serialize for Processor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ProcessorRecord::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Processor {
+    /// Loads a stored processor, failing if the processor is no longer available to the current
+    /// process or its metadata has changed since it was stored.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = ProcessorRecord::deserialize(deserializer)?;
+
+        reconcile(vec![record], &PlatformFacade::real())
+            .map(|processors| processors.head)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for ProcessorSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.processors().iter().map(ProcessorRecord::from))
+    }
+}
+
+impl<'de> Deserialize<'de> for ProcessorSet {
+    /// Loads a stored processor set, failing if any of the processors is no longer available to
+    /// the current process or its metadata has changed since it was stored.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let records = Vec::<ProcessorRecord>::deserialize(deserializer)?;
+        let pal = PlatformFacade::real();
+
+        let processors = reconcile(records, &pal).map_err(de::Error::custom)?;
+
+        Ok(Self::new(
+            processors,
+            HardwareTrackerClientFacade::real(),
+            pal,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nonempty::nonempty;
+
+    use crate::pal::{FakeProcessor, MockPlatform, ProcessorFacade};
+
+    use super::*;
+
+    fn new_platform() -> PlatformFacade {
+        let mut platform = MockPlatform::new();
+
+        platform
+            .expect_get_all_processors_core()
+            .return_const(nonempty![
+                ProcessorFacade::Fake(FakeProcessor {
+                    index: 0,
+                    memory_region: 0,
+                    efficiency_class: EfficiencyClass::Efficiency,
+                    package: 0,
+                }),
+                ProcessorFacade::Fake(FakeProcessor {
+                    index: 1,
+                    memory_region: 1,
+                    efficiency_class: EfficiencyClass::Performance,
+                    package: 0,
+                })
+            ]);
+
+        PlatformFacade::from_mock(platform)
+    }
+
+    fn record(
+        id: ProcessorId,
+        memory_region_id: MemoryRegionId,
+        efficiency_class: EfficiencyClass,
+    ) -> ProcessorRecord {
+        ProcessorRecord {
+            id,
+            memory_region_id,
+            efficiency_class,
+        }
+    }
+
+    #[test]
+    fn serialize_stores_metadata() {
+        let pal = new_platform();
+        let processors = pal.get_all_processors().map(Processor::new);
+        let set = ProcessorSet::new(processors, HardwareTrackerClientFacade::default_mock(), pal);
+
+        assert_eq!(
+            serde_json::to_string(&set).unwrap(),
+            r#"[{"id":0,"memory_region_id":0,"efficiency_class":"Efficiency"},{"id":1,"memory_region_id":1,"efficiency_class":"Performance"}]"#
+        );
+    }
+
+    #[test]
+    fn reconcile_accepts_matching_topology() {
+        let processors = reconcile(
+            vec![record(1, 1, EfficiencyClass::Performance)],
+            &new_platform(),
+        )
+        .unwrap();
+
+        assert_eq!(processors.len(), 1);
+        assert_eq!(processors.head.id(), 1);
+        assert_eq!(processors.head.memory_region_id(), 1);
+    }
+
+    #[test]
+    fn reconcile_rejects_changed_topology() {
+        let reconcile = |records| reconcile(records, &new_platform()).unwrap_err();
+
+        assert_eq!(reconcile(Vec::new()), ReconcileError::Empty);
+        assert_eq!(
+            reconcile(vec![record(2, 0, EfficiencyClass::Performance)]),
+            ReconcileError::Unavailable { processor_id: 2 }
+        );
+        assert_eq!(
+            reconcile(vec![record(1, 0, EfficiencyClass::Performance)]),
+            ReconcileError::MemoryRegionMismatch {
+                processor_id: 1,
+                stored: 0,
+                actual: 1
+            }
+        );
+        assert_eq!(
+            reconcile(vec![record(0, 0, EfficiencyClass::Performance)]),
+            ReconcileError::EfficiencyClassMismatch {
+                processor_id: 0,
+                stored: EfficiencyClass::Performance,
+                actual: EfficiencyClass::Efficiency
+            }
+        );
+    }
+
+    #[cfg(not(miri))] // Miri does not support talking to the real platform.
+    #[test]
+    fn round_trip_on_real_platform() {
+        let set = ProcessorSet::default();
+
+        let json = serde_json::to_string(&set).unwrap();
+        let restored: ProcessorSet = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.processors(), set.processors());
+    }
+}
@@ -0,0 +1,191 @@
+use std::{hint, num::NonZero, thread};
+
+use folo_utils::nz;
+
+use crate::{EfficiencyClass, ProcessorSet};
+
+/// How many spin loop hints to issue per wait if nothing else is known about the processors.
+const PAUSE_ITERATIONS: NonZero<u32> = nz!(16);
+
+/// How many spin loop hints to issue per wait if the processors have simultaneous
+/// multithreading siblings. Longer pauses leave more of the execution resources of the physical
+/// core to the sibling, which may be the very thread we are waiting for.
+const SMT_PAUSE_ITERATIONS: NonZero<u32> = nz!(64);
+
+/// The equivalents of the above for efficiency processors, on which each iteration of a spin
+/// loop takes longer, so fewer iterations cover the same time.
+const EFFICIENCY_PAUSE_ITERATIONS: NonZero<u32> = nz!(8);
+const EFFICIENCY_SMT_PAUSE_ITERATIONS: NonZero<u32> = nz!(32);
+
+/// How to wait in one iteration of a spin loop, as chosen by [`spin_hint_for()`] based on the
+/// hardware that the spinning thread executes on.
+///
+/// The cost and effect of the processor's spin loop hint differs between architectures (e.g.
+/// `PAUSE` on x86 or `ISB` on ARM) and the right amount of spinning differs between systems,
+/// so hardcoding a single strategy tends to be wrong on some of them.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// use many_cpus::{ProcessorSet, spin_hint_for};
+///
+/// let ready = AtomicBool::new(true);
+/// let spin_hint = spin_hint_for(&ProcessorSet::default());
+///
+/// while !ready.load(Ordering::Acquire) {
+///     spin_hint.wait();
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SpinHint {
+    /// Issue the spin loop hint of the processor the given number of times.
+    Pause(NonZero<u32>),
+
+    /// Give up the rest of the time slice to the operating system, so other threads can execute
+    /// on the processor instead of the spinning thread.
+    Yield,
+}
+
+impl SpinHint {
+    /// Waits for one iteration of a spin loop, as described by the hint.
+    #[inline]
+    pub fn wait(self) {
+        match self {
+            Self::Pause(iterations) => {
+                for _ in 0..iterations.get() {
+                    hint::spin_loop();
+                }
+            }
+            Self::Yield => thread::yield_now(),
+        }
+    }
+}
+
+/// Chooses how threads executing on `processor_set` should wait in spin loops.
+///
+/// * If one thread per processor in the set would exceed the resource quota of the process, the
+///   threads are oversubscribed and spinning only burns processor time that the thread being
+///   waited for could have used, so the hint is to yield.
+/// * If the processors have simultaneous multithreading siblings, the hint is a long pause that
+///   leaves the execution resources of the physical core to the sibling.
+/// * Otherwise, the hint is a short pause, shortened further if all the processors are
+///   efficiency processors, as each iteration of the loop takes longer on them.
+///
+/// The hint is a snapshot of the current state of the system. The resource quota of the process
+/// may change over time, so long-lived threads may want to refresh the hint occasionally.
+#[must_use]
+pub fn spin_hint_for(processor_set: &ProcessorSet) -> SpinHint {
+    if processor_set.exceeds_resource_quota() {
+        return SpinHint::Yield;
+    }
+
+    let efficiency_only = processor_set
+        .processors()
+        .iter()
+        .all(|p| p.efficiency_class() == EfficiencyClass::Efficiency);
+
+    let iterations = match (processor_set.has_smt_siblings(), efficiency_only) {
+        (false, false) => PAUSE_ITERATIONS,
+        (true, false) => SMT_PAUSE_ITERATIONS,
+        (false, true) => EFFICIENCY_PAUSE_ITERATIONS,
+        (true, true) => EFFICIENCY_SMT_PAUSE_ITERATIONS,
+    };
+
+    SpinHint::Pause(iterations)
+}
+
+#[cfg(test)]
+mod tests {
+    use nonempty::{NonEmpty, nonempty};
+
+    use crate::{
+        HardwareTrackerClientFacade, Processor,
+        pal::{FakeProcessor, MockPlatform, PlatformFacade, ProcessorFacade},
+    };
+
+    use super::*;
+
+    fn processor_set(
+        processors: NonEmpty<FakeProcessor>,
+        physical_core_of: fn(u32) -> u32,
+        max_processor_time: f64,
+    ) -> ProcessorSet {
+        let processors = processors.map(ProcessorFacade::Fake);
+
+        let mut platform = MockPlatform::new();
+
+        platform
+            .expect_get_all_processors_core()
+            .return_const(processors.clone());
+        platform
+            .expect_physical_core_id()
+            .returning(physical_core_of);
+        platform
+            .expect_max_processor_time()
+            .return_const(max_processor_time);
+
+        ProcessorSet::new(
+            processors.map(Processor::new),
+            HardwareTrackerClientFacade::default_mock(),
+            PlatformFacade::from_mock(platform),
+        )
+    }
+
+    #[test]
+    fn oversubscribed_yields() {
+        let set = processor_set(
+            nonempty![FakeProcessor::with_index(0), FakeProcessor::with_index(1)],
+            |id| id,
+            1.0,
+        );
+
+        assert_eq!(spin_hint_for(&set), SpinHint::Yield);
+    }
+
+    #[test]
+    fn smt_siblings_pause_longer() {
+        let set = processor_set(
+            nonempty![FakeProcessor::with_index(0), FakeProcessor::with_index(1)],
+            |id| id / 2,
+            2.0,
+        );
+
+        assert_eq!(spin_hint_for(&set), SpinHint::Pause(SMT_PAUSE_ITERATIONS));
+
+        let set = processor_set(
+            nonempty![FakeProcessor::with_index(0), FakeProcessor::with_index(1)],
+            |id| id,
+            2.0,
+        );
+
+        assert_eq!(spin_hint_for(&set), SpinHint::Pause(PAUSE_ITERATIONS));
+    }
+
+    #[test]
+    fn efficiency_processors_pause_shorter() {
+        let set = processor_set(
+            nonempty![FakeProcessor {
+                efficiency_class: EfficiencyClass::Efficiency,
+                ..FakeProcessor::with_index(0)
+            }],
+            |id| id,
+            1.0,
+        );
+
+        assert_eq!(
+            spin_hint_for(&set),
+            SpinHint::Pause(EFFICIENCY_PAUSE_ITERATIONS)
+        );
+    }
+
+    #[test]
+    fn wait_smoke_test() {
+        SpinHint::Pause(PAUSE_ITERATIONS).wait();
+        SpinHint::Yield.wait();
+
+        spin_hint_for(&ProcessorSet::default()).wait();
+    }
+}
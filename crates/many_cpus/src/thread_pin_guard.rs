@@ -0,0 +1,32 @@
+use negative_impl::negative_impl;
+
+use crate::ProcessorSet;
+
+/// Restores the previous processor affinity of the current thread when dropped, created via
+/// [`ProcessorSet::pin_current_thread_scoped()`].
+///
+/// The guard restores the affinity of the thread that created it, so it cannot be sent to or
+/// shared with other threads. When multiple guards are nested, they must be dropped in the
+/// reverse order of their creation for the original affinity to be restored at the end.
+#[derive(Debug)]
+#[must_use = "the previous affinity is restored as soon as the guard is dropped"]
+pub struct ThreadPinGuard {
+    previous: ProcessorSet,
+}
+
+impl ThreadPinGuard {
+    pub(crate) fn new(previous: ProcessorSet) -> Self {
+        Self { previous }
+    }
+}
+
+impl Drop for ThreadPinGuard {
+    fn drop(&mut self) {
+        self.previous.pin_current_thread_to();
+    }
+}
+
+#[negative_impl]
+impl !Send for ThreadPinGuard {}
+#[negative_impl]
+impl !Sync for ThreadPinGuard {}
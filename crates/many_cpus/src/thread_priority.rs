@@ -0,0 +1,33 @@
+/// The scheduling priority of a thread spawned via [`PinnedThreadBuilder`][crate::PinnedThreadBuilder],
+/// relative to the other threads on the system.
+///
+/// Each priority is mapped to the closest equivalent of the operating system:
+///
+/// | Priority     | Linux                          | Windows                         |
+/// |--------------|--------------------------------|---------------------------------|
+/// | `Low`        | nice value 10                  | `THREAD_PRIORITY_BELOW_NORMAL`  |
+/// | `Normal`     | nice value 0                   | `THREAD_PRIORITY_NORMAL`        |
+/// | `High`       | nice value -10                 | `THREAD_PRIORITY_HIGHEST`       |
+/// | `Realtime`   | `SCHED_FIFO` with priority 1   | `THREAD_PRIORITY_TIME_CRITICAL` |
+///
+/// Raising the priority above `Normal` typically requires additional privileges (e.g. the
+/// `CAP_SYS_NICE` capability or a suitable `RLIMIT_NICE`/`RLIMIT_RTPRIO` resource limit on Linux).
+///
+/// Use `Realtime` with care - a realtime thread that never blocks can starve everything else
+/// executing on the same processors, including the operating system.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ThreadPriority {
+    /// Below the default priority, for background work that should yield to everything else.
+    Low,
+
+    /// The default priority of threads.
+    Normal,
+
+    /// Above the default priority, for latency-sensitive work.
+    High,
+
+    /// A realtime scheduling class that preempts all threads with non-realtime priorities,
+    /// for latency-critical work.
+    Realtime,
+}
@@ -0,0 +1,284 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
+
+use itertools::Itertools;
+
+use crate::{HardwareSubscription, HardwareTracker, Processor, ProcessorSet};
+
+/// A processor set that follows changes in the constraints applied to the current process (e.g.
+/// its resource quota), created via [`ProcessorSet::watching_default()`].
+///
+/// The membership is refreshed whenever the hardware poller of [`HardwareTracker`] observes a
+/// change, so changes are only observed while polling is active (see
+/// [`HardwareTracker::start_polling()`]). Use [`refresh()`][Self::refresh] to refresh the
+/// membership on demand.
+///
+/// Whenever the membership changes, the callback given to `watching_default()` is called with a
+/// [`ProcessorSetChange`] describing which processors appeared and which disappeared. A refresh
+/// keeps as many of the existing members as the new constraints allow, so the membership does not
+/// change unless the constraints do. Threads already pinned to processors that disappeared are not
+/// moved - it is up to the callback to react to the change.
+///
+/// The observed constraints are the resource quota and, on Linux, the cgroup v2 cpuset of the
+/// process (`cpuset.cpus.effective`), which container orchestrators may change underneath a
+/// running process (e.g. Kubernetes vertical scaling). A cpuset can only shrink the membership to
+/// processors the process was allowed to use when the processor information was first loaded -
+/// processors added to the cpuset later are not used. Other processor information is loaded once
+/// and any changes to it at runtime are ignored.
+///
+/// Use [`last_refreshed()`][Self::last_refreshed] to find out how current the membership is.
+///
+/// # Example
+///
+/// ```
+/// use std::{sync::mpsc, time::Duration};
+///
+/// use many_cpus::{HardwareTracker, ProcessorSet};
+///
+/// let (changes_tx, changes_rx) = mpsc::channel();
+///
+/// let processors = ProcessorSet::watching_default(move |change| {
+///     // The receiver may be gone already.
+///     _ = changes_tx.send(change.clone());
+/// });
+///
+/// HardwareTracker::start_polling(Duration::from_secs(10));
+///
+/// // The processors that are members right now.
+/// let current = processors.current();
+/// println!("Using {} processors", current.len());
+///
+/// // ...
+///
+/// for change in changes_rx.try_iter() {
+///     println!("Processors changed to {}", change.current().to_cpulist());
+/// }
+///
+/// HardwareTracker::stop_polling();
+/// ```
+pub struct WatchedProcessorSet {
+    state: Arc<WatchedState>,
+
+    // Refreshes the membership whenever the hardware poller observes a change, until dropped.
+    _subscription: HardwareSubscription,
+}
+
+impl WatchedProcessorSet {
+    pub(crate) fn new(
+        initial: ProcessorSet,
+        on_change: impl Fn(&ProcessorSetChange) + Send + Sync + 'static,
+    ) -> Self {
+        let state = Arc::new(WatchedState {
+            current: RwLock::new(initial),
+            refresh_lock: Mutex::new(()),
+            last_refreshed: RwLock::new(Instant::now()),
+            on_change: Box::new(on_change),
+        });
+
+        let subscription = HardwareTracker::subscribe({
+            let state = Arc::clone(&state);
+            move |_| state.refresh()
+        });
+
+        Self {
+            state,
+            _subscription: subscription,
+        }
+    }
+
+    /// The processors that are members of the set right now.
+    ///
+    /// The returned processor set is a snapshot - it does not change when the membership changes.
+    #[must_use]
+    pub fn current(&self) -> ProcessorSet {
+        self.state.current.read().expect(ERR_POISONED_LOCK).clone()
+    }
+
+    /// Refreshes the membership immediately, without waiting for the hardware poller to observe
+    /// a change, calling the callback if the membership changes.
+    pub fn refresh(&self) {
+        self.state.refresh();
+    }
+
+    /// When the membership was last re-evaluated under the current constraints, whether or not
+    /// the membership changed as a result.
+    ///
+    /// This is when the set was created if it has never been refreshed.
+    #[must_use]
+    pub fn last_refreshed(&self) -> Instant {
+        *self.state.last_refreshed.read().expect(ERR_POISONED_LOCK)
+    }
+}
+
+impl fmt::Debug for WatchedProcessorSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchedProcessorSet")
+            .field("current", &self.current())
+            .field("last_refreshed", &self.last_refreshed())
+            .finish_non_exhaustive()
+    }
+}
+
+struct WatchedState {
+    current: RwLock<ProcessorSet>,
+
+    // Held for the entire refresh (including the callback), so concurrent refreshes on the
+    // poller thread and the owner's thread report the changes in the order they happen.
+    refresh_lock: Mutex<()>,
+
+    last_refreshed: RwLock<Instant>,
+
+    on_change: Box<dyn Fn(&ProcessorSetChange) + Send + Sync>,
+}
+
+impl WatchedState {
+    fn refresh(&self) {
+        let _refreshing = self.refresh_lock.lock().expect(ERR_POISONED_LOCK);
+
+        let previous = self.current.read().expect(ERR_POISONED_LOCK).clone();
+        let current = previous.refreshed_default();
+
+        *self.last_refreshed.write().expect(ERR_POISONED_LOCK) = Instant::now();
+
+        let added = current
+            .processors()
+            .iter()
+            .filter(|p| !previous.processors().contains(p))
+            .cloned()
+            .collect_vec();
+
+        let removed = previous
+            .processors()
+            .iter()
+            .filter(|p| !current.processors().contains(p))
+            .cloned()
+            .collect_vec();
+
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        *self.current.write().expect(ERR_POISONED_LOCK) = current.clone();
+
+        // The callback may inspect the set, so we call it without holding the lock.
+        (self.on_change)(&ProcessorSetChange {
+            added,
+            removed,
+            current,
+        });
+    }
+}
+
+/// A change in the membership of a [`WatchedProcessorSet`], delivered to the callback given to
+/// [`ProcessorSet::watching_default()`].
+#[derive(Clone, Debug)]
+pub struct ProcessorSetChange {
+    added: Vec<Processor>,
+    removed: Vec<Processor>,
+    current: ProcessorSet,
+}
+
+impl ProcessorSetChange {
+    /// The processors that became members of the set.
+    #[must_use]
+    pub fn added(&self) -> &[Processor] {
+        &self.added
+    }
+
+    /// The processors that are no longer members of the set.
+    #[must_use]
+    pub fn removed(&self) -> &[Processor] {
+        &self.removed
+    }
+
+    /// The processors that are members of the set after the change.
+    #[must_use]
+    pub fn current(&self) -> &ProcessorSet {
+        &self.current
+    }
+}
+
+const ERR_POISONED_LOCK: &str =
+    "poisoned lock - safe execution no longer possible because a panic occurred on another thread";
+
+#[cfg(test)]
+mod tests {
+    use nonempty::nonempty;
+
+    use crate::{
+        HardwareTrackerClientFacade, ProcessorSetBuilder,
+        pal::{FakeProcessor, MockPlatform, PlatformFacade, ProcessorFacade},
+    };
+
+    use super::*;
+
+    #[test]
+    fn membership_follows_resource_quota() {
+        let mut platform = MockPlatform::new();
+
+        // Initial selection, then one refresh for each quota change and one without a change.
+        platform.expect_max_processor_time().times(4).returning({
+            let mut values = [3.0, 1.0, 1.0, 2.0].into_iter();
+            move || values.next().unwrap()
+        });
+        platform
+            .expect_refresh_available_processors()
+            .times(3)
+            .return_const(());
+        platform
+            .expect_get_all_processors_core()
+            .return_const(nonempty![
+                ProcessorFacade::Fake(FakeProcessor::with_index(0)),
+                ProcessorFacade::Fake(FakeProcessor::with_index(1)),
+                ProcessorFacade::Fake(FakeProcessor::with_index(2)),
+            ]);
+
+        let initial = ProcessorSetBuilder::with_internals(
+            HardwareTrackerClientFacade::default_mock(),
+            PlatformFacade::from_mock(platform),
+        )
+        .take_all()
+        .unwrap();
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+
+        let watched = WatchedProcessorSet::new(initial, {
+            let changes = Arc::clone(&changes);
+            move |change| changes.lock().unwrap().push(change.clone())
+        });
+
+        assert_eq!(watched.current().len(), 3);
+
+        let created = watched.last_refreshed();
+
+        watched.refresh();
+        assert!(watched.last_refreshed() >= created);
+
+        let remaining = watched.current().processors().first().clone();
+        assert_eq!(watched.current().len(), 1);
+
+        watched.refresh();
+        assert_eq!(watched.current().len(), 1);
+
+        watched.refresh();
+        let current = watched.current();
+        assert_eq!(current.len(), 2);
+
+        // The remaining processor is kept when the quota grows again.
+        assert!(current.processors().contains(&remaining));
+
+        let changes = changes.lock().unwrap();
+        assert_eq!(changes.len(), 2);
+
+        let (shrunk, grown) = (changes.first().unwrap(), changes.get(1).unwrap());
+        assert!(shrunk.added().is_empty());
+        assert_eq!(shrunk.removed().len(), 2);
+        assert_eq!(shrunk.current().len(), 1);
+        assert_eq!(grown.added().len(), 1);
+        assert!(grown.removed().is_empty());
+        assert_eq!(grown.current().len(), 2);
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    fmt, mem,
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
+
+use many_cpus::ProcessorSet;
+
+/// Given by the benchmark harness to [`Payload::process_chunked()`][crate::Payload::process_chunked],
+/// to be notified whenever the payload completes a chunk of its work.
+///
+/// The harness times each chunk (from the start of processing or the previous checkpoint to the
+/// current one) and reports the percentiles of the chunk durations once the benchmark has
+/// completed, giving insight into the variance within a single long-running iteration.
+///
+/// If the payload has a [timeout][crate::Payload::timeout] and the iteration has been running
+/// for longer than that, the checkpoint tells the payload to stop processing.
+///
+/// In [migration runs][crate::RunConfig::migration], the worker is moved to a different
+/// processor at the first checkpoint.
+#[derive(Debug)]
+pub struct Checkpoint {
+    // Whether the chunks are timed. If not, the checkpoint never tells the payload to stop.
+    is_timed: bool,
+
+    timeout: Option<Duration>,
+
+    started: Instant,
+    chunk_started: Instant,
+
+    chunk_durations: Vec<Duration>,
+    is_aborted: bool,
+
+    // If set, the current thread is pinned to this at the first checkpoint of each payload.
+    migrate_to: Option<ProcessorSet>,
+    is_migrated: bool,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint that does not time the chunks and never tells the payload to stop.
+    ///
+    /// This is intended for implementing [`Payload::process()`][crate::Payload::process] in terms
+    /// of [`Payload::process_chunked()`][crate::Payload::process_chunked].
+    #[must_use]
+    pub fn untimed() -> Self {
+        let now = Instant::now();
+
+        Self {
+            is_timed: false,
+            timeout: None,
+            started: now,
+            chunk_started: now,
+            chunk_durations: Vec::new(),
+            is_aborted: false,
+            migrate_to: None,
+            is_migrated: false,
+        }
+    }
+
+    pub(crate) fn timed(timeout: Option<Duration>) -> Self {
+        Self {
+            is_timed: true,
+            timeout,
+            ..Self::untimed()
+        }
+    }
+
+    /// Moves the current thread to `target` at the first checkpoint of each payload.
+    pub(crate) fn migrating_to(mut self, target: Option<ProcessorSet>) -> Self {
+        self.migrate_to = target;
+        self
+    }
+
+    /// Marks the start of the processing of one payload.
+    pub(crate) fn start(&mut self) {
+        let now = Instant::now();
+
+        self.started = now;
+        self.chunk_started = now;
+        self.is_aborted = false;
+        self.is_migrated = false;
+    }
+
+    /// Whether the current thread was moved to the migration target while processing the
+    /// current payload.
+    pub(crate) fn is_migrated(&self) -> bool {
+        self.is_migrated
+    }
+
+    /// Whether the processing of the current payload was aborted due to the timeout.
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.is_aborted
+    }
+
+    /// Takes the durations of all the chunks completed since the checkpoint was created or
+    /// this was last called.
+    pub(crate) fn take_chunk_durations(&mut self) -> Vec<Duration> {
+        mem::take(&mut self.chunk_durations)
+    }
+
+    /// Records the completion of a chunk of work.
+    ///
+    /// Returns [`ControlFlow::Break`] if the payload must stop processing because the iteration
+    /// has exceeded the timeout of the payload. The payload is expected to return from
+    /// `process_chunked()` without processing any further chunks, as the remaining work is not
+    /// going to be measured anyway.
+    #[must_use]
+    pub fn chunk_completed(&mut self) -> ControlFlow<()> {
+        if !self.is_timed {
+            return ControlFlow::Continue(());
+        }
+
+        let now = Instant::now();
+
+        self.chunk_durations
+            .push(now.saturating_duration_since(self.chunk_started));
+        self.chunk_started = now;
+
+        if self
+            .timeout
+            .is_some_and(|timeout| now.saturating_duration_since(self.started) > timeout)
+        {
+            self.is_aborted = true;
+        }
+
+        if self.is_aborted {
+            return ControlFlow::Break(());
+        }
+
+        if let Some(target) = &self.migrate_to {
+            if !self.is_migrated {
+                // This is counted as part of the next chunk, as it is the cost of migration.
+                target.pin_current_thread_to();
+                self.is_migrated = true;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// The chunk durations of all the iterations of a benchmark and how many of the iterations were
+/// aborted due to the timeout of the payload.
+#[derive(Debug, Default)]
+pub(crate) struct ChunkStats {
+    chunk_durations: Vec<Duration>,
+    aborted_iterations: u64,
+}
+
+impl ChunkStats {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether any payload reported completing a chunk, i.e. whether there is anything to report.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.chunk_durations.is_empty() && self.aborted_iterations == 0
+    }
+
+    pub(crate) fn add_iteration(&mut self, checkpoint: &mut Checkpoint) {
+        self.chunk_durations
+            .extend(checkpoint.take_chunk_durations());
+
+        if checkpoint.is_aborted() {
+            self.aborted_iterations = self
+                .aborted_iterations
+                .checked_add(1)
+                .expect("iteration count overflow is unfathomable within our spacetime boundaries");
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.chunk_durations.extend(other.chunk_durations);
+        self.aborted_iterations = self
+            .aborted_iterations
+            .checked_add(other.aborted_iterations)
+            .expect("iteration count overflow is unfathomable within our spacetime boundaries");
+    }
+}
+
+impl fmt::Display for ChunkStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sorted = self.chunk_durations.clone();
+        sorted.sort_unstable();
+
+        write!(f, "{} chunks", sorted.len())?;
+
+        if let Some(max) = sorted.last() {
+            write!(
+                f,
+                ", p50={:?}, p90={:?}, p99={:?}, max={max:?}",
+                percentile(&sorted, 50),
+                percentile(&sorted, 90),
+                percentile(&sorted, 99),
+            )?;
+        }
+
+        write!(f, ", {} iterations aborted", self.aborted_iterations)
+    }
+}
+
+/// The nearest-rank percentile of a non-empty sorted list of durations.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    assert!(!sorted.is_empty());
+    assert!(percent <= 100);
+
+    // The rank is ceil(percent * len / 100), at least 1, converted to a 0-based index.
+    let rank = sorted
+        .len()
+        .checked_mul(percent)
+        .expect("we will never have so many chunks that we overflow usize")
+        .div_ceil(100)
+        .max(1);
+
+    *sorted
+        .get(rank.checked_sub(1).expect("rank is at least 1"))
+        .expect("rank cannot exceed the length because percent is at most 100")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let sorted = (1..=10).map(Duration::from_millis).collect::<Vec<_>>();
+
+        assert_eq!(percentile(&sorted, 0), Duration::from_millis(1));
+        assert_eq!(percentile(&sorted, 50), Duration::from_millis(5));
+        assert_eq!(percentile(&sorted, 90), Duration::from_millis(9));
+        assert_eq!(percentile(&sorted, 99), Duration::from_millis(10));
+        assert_eq!(percentile(&sorted, 100), Duration::from_millis(10));
+
+        assert_eq!(
+            percentile(&[Duration::from_millis(7)], 50),
+            Duration::from_millis(7)
+        );
+    }
+
+    #[test]
+    fn timeout_aborts_iteration() {
+        let mut checkpoint = Checkpoint::timed(Some(Duration::ZERO));
+        let mut stats = ChunkStats::new();
+
+        checkpoint.start();
+        thread::sleep(Duration::from_millis(1));
+
+        assert!(checkpoint.chunk_completed().is_break());
+        assert!(checkpoint.chunk_completed().is_break());
+
+        stats.add_iteration(&mut checkpoint);
+
+        // The next iteration starts from scratch.
+        checkpoint.start();
+        assert!(!checkpoint.is_aborted());
+
+        let mut checkpoint = Checkpoint::timed(None);
+        checkpoint.start();
+
+        assert!(checkpoint.chunk_completed().is_continue());
+
+        stats.add_iteration(&mut checkpoint);
+
+        assert_eq!(stats.chunk_durations.len(), 3);
+        assert_eq!(stats.aborted_iterations, 1);
+    }
+
+    #[test]
+    fn untimed_records_nothing() {
+        let mut checkpoint = Checkpoint::untimed();
+        let mut stats = ChunkStats::new();
+
+        checkpoint.start();
+        assert!(checkpoint.chunk_completed().is_continue());
+
+        stats.add_iteration(&mut checkpoint);
+
+        assert!(stats.is_empty());
+    }
+}
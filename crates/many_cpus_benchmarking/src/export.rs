@@ -0,0 +1,903 @@
+//! Exporting benchmark results for comparing them across machines.
+//!
+//! Every machine executes the same benchmark suite, writing the samples of its benchmarks into
+//! a [`MachineResults`] file. A [`Manifest`] lists the machines and the environment to execute
+//! the suite with and merges the results files of all the machines into one [`Comparison`] per
+//! [`TopologyFingerprint`], as results are only comparable between machines with the same
+//! hardware topology.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use many_cpus::{EfficiencyClass, MemoryRegionId, ProcessorSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{RunSamples, Sample, run::is_fake_run};
+
+/// Name of the environment variable that enables exporting the results of all benchmark runs.
+///
+/// The value is the path of the [`MachineResults`] file to write. Benchmarks that are skipped,
+/// only listed or executed as tests are not exported. The file is rewritten after every
+/// benchmark, so it contains the results of all benchmarks completed so far, including
+/// those completed by earlier executions of the benchmark binaries that exported into the same
+/// file. Results of a benchmark that is executed again replace the earlier results.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the file cannot be written, if an existing file is not
+/// a valid results file or if it contains the results of a different machine.
+pub const RESULTS_PATH_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_RESULTS_PATH";
+
+/// Name of the environment variable that names the machine the benchmarks are executed on, to
+/// tell apart the [`MachineResults`] of different machines.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if [`RESULTS_PATH_ENV_VAR`] is set but this is not.
+pub const MACHINE_NAME_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_MACHINE";
+
+/// The version of the format of [`MachineResults`] files, incremented on incompatible changes.
+const RESULTS_FORMAT_VERSION: u32 = 1;
+
+/// Exports the samples of each benchmark into the results file of the current machine, as
+/// configured via [`RESULTS_PATH_ENV_VAR`] and [`MACHINE_NAME_ENV_VAR`].
+#[derive(Debug)]
+pub(crate) struct Exporter {
+    path: PathBuf,
+    machine: String,
+    topology: TopologyFingerprint,
+}
+
+impl Exporter {
+    /// Returns `None` if exporting is not enabled or if the benchmarks are only listed or
+    /// executed as tests, which do not produce meaningful results.
+    pub(crate) fn from_env() -> Option<Self> {
+        if is_fake_run() {
+            return None;
+        }
+
+        let path = env::var_os(RESULTS_PATH_ENV_VAR)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)?;
+
+        let machine = env::var(MACHINE_NAME_ENV_VAR).unwrap_or_else(|_| {
+            panic!(
+                "{MACHINE_NAME_ENV_VAR} must be set when exporting results via {RESULTS_PATH_ENV_VAR}"
+            )
+        });
+
+        Some(Self {
+            path,
+            machine,
+            topology: TopologyFingerprint::current(),
+        })
+    }
+
+    /// Exports the samples of one completed benchmark, unless there are none to export.
+    pub(crate) fn export(&self, samples: &RunSamples) {
+        if samples.samples().is_empty() {
+            return;
+        }
+
+        export_samples(&self.path, &self.machine, &self.topology, samples).unwrap_or_else(|e| {
+            panic!(
+                "cannot export the results of benchmark {} to {}: {e}",
+                samples.benchmark_name(),
+                self.path.display()
+            )
+        });
+    }
+}
+
+/// Adds the samples of one benchmark to the results file at `path`, creating the file if it
+/// does not exist yet.
+fn export_samples(
+    path: &Path,
+    machine: &str,
+    topology: &TopologyFingerprint,
+    samples: &RunSamples,
+) -> io::Result<()> {
+    let mut results = if path.exists() {
+        let results = MachineResults::read_from(path)?;
+
+        if results.machine != machine || results.topology != *topology {
+            return Err(io::Error::other(format!(
+                "the file contains the results of machine {} with topology {}, not of machine {machine} with topology {topology}",
+                results.machine, results.topology
+            )));
+        }
+
+        results
+    } else {
+        MachineResults::new(machine.to_string(), topology.clone())
+    };
+
+    results.add(BenchmarkResults::from(samples));
+    results.write_to(path)
+}
+
+/// Summarizes the hardware topology of a machine, so the results of machines with the same
+/// topology can be compared with each other.
+///
+/// The fingerprint consists of the number of performance and efficiency processors in each
+/// memory region, considering all processors available to the current process.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TopologyFingerprint {
+    memory_regions: Vec<MemoryRegionFingerprint>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+struct MemoryRegionFingerprint {
+    performance_processors: usize,
+    efficiency_processors: usize,
+}
+
+impl TopologyFingerprint {
+    /// The fingerprint of the hardware topology of the current machine.
+    #[must_use]
+    pub fn current() -> Self {
+        let processors = ProcessorSet::builder()
+            .ignoring_resource_quota()
+            .take_all()
+            .expect("there is always at least one processor available to the current process");
+
+        let mut memory_regions = BTreeMap::<MemoryRegionId, MemoryRegionFingerprint>::new();
+
+        for processor in processors.processors() {
+            let memory_region = memory_regions
+                .entry(processor.memory_region_id())
+                .or_insert(MemoryRegionFingerprint {
+                    performance_processors: 0,
+                    efficiency_processors: 0,
+                });
+
+            let count = match processor.efficiency_class() {
+                EfficiencyClass::Performance => &mut memory_region.performance_processors,
+                EfficiencyClass::Efficiency => &mut memory_region.efficiency_processors,
+            };
+
+            *count = count.saturating_add(1);
+        }
+
+        Self {
+            memory_regions: memory_regions.into_values().collect(),
+        }
+    }
+}
+
+impl fmt::Display for TopologyFingerprint {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for the exact format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, memory_region) in self.memory_regions.iter().enumerate() {
+            if index > 0 {
+                write!(f, "/")?;
+            }
+
+            write!(
+                f,
+                "{}P+{}E",
+                memory_region.performance_processors, memory_region.efficiency_processors
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The results of the benchmarks executed on one machine, as exported via
+/// [`RESULTS_PATH_ENV_VAR`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MachineResults {
+    format_version: u32,
+    machine: String,
+    topology: TopologyFingerprint,
+    benchmarks: Vec<BenchmarkResults>,
+}
+
+impl MachineResults {
+    fn new(machine: String, topology: TopologyFingerprint) -> Self {
+        Self {
+            format_version: RESULTS_FORMAT_VERSION,
+            machine,
+            topology,
+            benchmarks: Vec::new(),
+        }
+    }
+
+    /// Reads a results file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a results file in the format used
+    /// by this version of the benchmark harness.
+    pub fn read_from(path: impl AsRef<Path>) -> io::Result<Self> {
+        let results: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+        if results.format_version != RESULTS_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "results file has format version {} but version {RESULTS_FORMAT_VERSION} is required",
+                    results.format_version
+                ),
+            ));
+        }
+
+        Ok(results)
+    }
+
+    /// Writes the results into a file, replacing the file if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+
+    /// The name of the machine, as given by [`MACHINE_NAME_ENV_VAR`].
+    #[must_use]
+    pub fn machine(&self) -> &str {
+        &self.machine
+    }
+
+    /// The fingerprint of the hardware topology of the machine.
+    #[must_use]
+    pub fn topology(&self) -> &TopologyFingerprint {
+        &self.topology
+    }
+
+    /// The results of the individual benchmarks, in the order they were first executed.
+    #[must_use]
+    pub fn benchmarks(&self) -> &[BenchmarkResults] {
+        &self.benchmarks
+    }
+
+    /// Adds the results of a benchmark, replacing any earlier results of the same benchmark.
+    fn add(&mut self, benchmark: BenchmarkResults) {
+        match self
+            .benchmarks
+            .iter_mut()
+            .find(|existing| existing.key() == benchmark.key())
+        {
+            Some(existing) => *existing = benchmark,
+            None => self.benchmarks.push(benchmark),
+        }
+    }
+}
+
+/// The samples of one benchmark in [`MachineResults`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BenchmarkResults {
+    payload_type: String,
+    benchmark_name: String,
+    work_distribution: String,
+    samples: Vec<Sample>,
+
+    // Results files exported before custom metrics were included have no metrics.
+    #[serde(default)]
+    metrics: BTreeMap<String, f64>,
+}
+
+impl BenchmarkResults {
+    /// The name of the payload type, which is also the name of the Criterion benchmark group.
+    #[must_use]
+    pub fn payload_type(&self) -> &str {
+        &self.payload_type
+    }
+
+    /// The name of the benchmark within the Criterion benchmark group.
+    #[must_use]
+    pub fn benchmark_name(&self) -> &str {
+        &self.benchmark_name
+    }
+
+    /// The name of the work distribution the benchmark was executed with.
+    #[must_use]
+    pub fn work_distribution(&self) -> &str {
+        &self.work_distribution
+    }
+
+    /// The samples of the benchmark, in the order they were measured.
+    #[must_use]
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// The mean per iteration of each custom metric of the benchmark, by metric name.
+    ///
+    /// This is empty if no custom metrics were collected.
+    #[must_use]
+    pub fn metrics(&self) -> &BTreeMap<String, f64> {
+        &self.metrics
+    }
+
+    /// The mean duration of one iteration over all the samples of the benchmark, or `None` if
+    /// there are no samples.
+    #[must_use]
+    pub fn mean_iteration_duration(&self) -> Option<Duration> {
+        let total_iterations = self
+            .samples
+            .iter()
+            .map(|sample| u128::from(sample.iterations()))
+            .sum::<u128>();
+
+        let total_nanos = self
+            .samples
+            .iter()
+            .map(|sample| sample.duration().as_nanos())
+            .sum::<u128>();
+
+        total_nanos
+            .checked_div(total_iterations)
+            .and_then(|nanos| u64::try_from(nanos).ok())
+            .map(Duration::from_nanos)
+    }
+
+    fn key(&self) -> (&str, &str) {
+        (&self.payload_type, &self.benchmark_name)
+    }
+}
+
+impl From<&RunSamples> for BenchmarkResults {
+    fn from(samples: &RunSamples) -> Self {
+        Self {
+            payload_type: samples.payload_type().to_string(),
+            benchmark_name: samples.benchmark_name().to_string(),
+            work_distribution: samples.work_distribution().to_string(),
+            samples: samples.samples().to_vec(),
+            metrics: samples
+                .metrics()
+                .iter()
+                .map(|(&name, &mean)| (name.to_string(), mean))
+                .collect(),
+        }
+    }
+}
+
+/// Describes the execution of a benchmark suite across several machines.
+///
+/// The manifest is typically kept as a JSON file next to the benchmarks:
+///
+/// ```json
+/// {
+///   "machines": ["epyc-2s", "xeon-1s"],
+///   "env": {
+///     "MANY_CPUS_BENCHMARKING_DISTRIBUTIONS": "PinnedMemoryRegionPairs,PinnedSameMemoryRegion",
+///     "MANY_CPUS_BENCHMARKING_SEED": "42"
+///   }
+/// }
+/// ```
+///
+/// On each machine, the runner [configures the command][Self::configure_command] that executes
+/// the benchmark binaries (e.g. `cargo bench`), so every benchmark they execute is exported.
+/// Once the results files of all machines have been collected, they are
+/// [merged][Self::merge] into a comparison of the machines.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Manifest {
+    machines: Vec<String>,
+
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Reads a manifest file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid manifest.
+    pub fn read_from(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    /// The names of the machines to execute the benchmark suite on.
+    #[must_use]
+    pub fn machines(&self) -> &[String] {
+        &self.machines
+    }
+
+    /// The path of the results file of `machine` in `results_dir`.
+    #[must_use]
+    pub fn results_path(results_dir: impl AsRef<Path>, machine: &str) -> PathBuf {
+        results_dir.as_ref().join(format!("{machine}.json"))
+    }
+
+    /// Configures `command` to execute the benchmark suite on `machine`, with the environment
+    /// variables of the manifest and exporting the results into the
+    /// [results file of the machine][Self::results_path] in `results_dir`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `machine` is not listed in the manifest.
+    pub fn configure_command<'a>(
+        &self,
+        command: &'a mut Command,
+        machine: &str,
+        results_dir: impl AsRef<Path>,
+    ) -> &'a mut Command {
+        assert!(
+            self.machines.iter().any(|m| m == machine),
+            "machine {machine} is not listed in the manifest"
+        );
+
+        command
+            .envs(&self.env)
+            .env(MACHINE_NAME_ENV_VAR, machine)
+            .env(
+                RESULTS_PATH_ENV_VAR,
+                Self::results_path(results_dir, machine),
+            )
+    }
+
+    /// Merges the results of the machines listed in the manifest into one comparison for each
+    /// hardware topology, ordered by topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there are results for exactly the machines listed in the manifest,
+    /// one for each machine.
+    pub fn merge(
+        &self,
+        results: impl IntoIterator<Item = MachineResults>,
+    ) -> Result<Vec<Comparison>, MergeError> {
+        let mut seen_machines = BTreeSet::new();
+        let mut by_topology = BTreeMap::<TopologyFingerprint, Vec<MachineResults>>::new();
+
+        for machine_results in results {
+            if !self.machines.contains(&machine_results.machine) {
+                return Err(MergeError::UnknownMachine {
+                    machine: machine_results.machine,
+                });
+            }
+
+            if !seen_machines.insert(machine_results.machine.clone()) {
+                return Err(MergeError::DuplicateMachine {
+                    machine: machine_results.machine,
+                });
+            }
+
+            by_topology
+                .entry(machine_results.topology.clone())
+                .or_default()
+                .push(machine_results);
+        }
+
+        let missing_machines = self
+            .machines
+            .iter()
+            .filter(|machine| !seen_machines.contains(*machine))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !missing_machines.is_empty() {
+            return Err(MergeError::MissingMachines {
+                machines: missing_machines,
+            });
+        }
+
+        Ok(by_topology
+            .into_iter()
+            .map(|(topology, machine_results)| Comparison::new(topology, &machine_results))
+            .collect())
+    }
+}
+
+/// Explains why [`Manifest::merge()`] could not merge the results of the machines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// Results were given for a machine that is not listed in the manifest.
+    UnknownMachine {
+        /// The name of the machine.
+        machine: String,
+    },
+
+    /// Results were given more than once for the same machine.
+    DuplicateMachine {
+        /// The name of the machine.
+        machine: String,
+    },
+
+    /// No results were given for some of the machines listed in the manifest.
+    MissingMachines {
+        /// The names of the machines, in the order they are listed in the manifest.
+        machines: Vec<String>,
+    },
+}
+
+impl fmt::Display for MergeError {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for the exact message.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownMachine { machine } => {
+                write!(f, "machine {machine} is not listed in the manifest")
+            }
+            Self::DuplicateMachine { machine } => {
+                write!(f, "machine {machine} has more than one set of results")
+            }
+            Self::MissingMachines { machines } => {
+                write!(f, "no results for machines: {}", machines.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// Compares the results of the machines that share one hardware topology.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Comparison {
+    topology: TopologyFingerprint,
+    machines: Vec<String>,
+    benchmarks: Vec<ComparedBenchmark>,
+}
+
+impl Comparison {
+    fn new(topology: TopologyFingerprint, machine_results: &[MachineResults]) -> Self {
+        let mut benchmarks = Vec::<ComparedBenchmark>::new();
+
+        for results in machine_results {
+            for benchmark in &results.benchmarks {
+                let Some(mean_iteration_duration) = benchmark.mean_iteration_duration() else {
+                    continue;
+                };
+
+                let machine = results.machine.clone();
+
+                if let Some(compared) = benchmarks
+                    .iter_mut()
+                    .find(|compared| compared.key() == benchmark.key())
+                {
+                    compared
+                        .mean_iteration_durations
+                        .insert(machine, mean_iteration_duration);
+                } else {
+                    benchmarks.push(ComparedBenchmark {
+                        payload_type: benchmark.payload_type.clone(),
+                        benchmark_name: benchmark.benchmark_name.clone(),
+                        mean_iteration_durations: BTreeMap::from([(
+                            machine,
+                            mean_iteration_duration,
+                        )]),
+                    });
+                }
+            }
+        }
+
+        Self {
+            topology,
+            machines: machine_results
+                .iter()
+                .map(|results| results.machine.clone())
+                .collect(),
+            benchmarks,
+        }
+    }
+
+    /// Writes the comparison into a file, replacing the file if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+
+    /// The hardware topology shared by the compared machines.
+    #[must_use]
+    pub fn topology(&self) -> &TopologyFingerprint {
+        &self.topology
+    }
+
+    /// The names of the compared machines.
+    #[must_use]
+    pub fn machines(&self) -> &[String] {
+        &self.machines
+    }
+
+    /// The benchmarks executed on any of the compared machines, in the order they were first
+    /// executed.
+    #[must_use]
+    pub fn benchmarks(&self) -> &[ComparedBenchmark] {
+        &self.benchmarks
+    }
+}
+
+/// The results of one benchmark on each of the machines in a [`Comparison`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ComparedBenchmark {
+    payload_type: String,
+    benchmark_name: String,
+    mean_iteration_durations: BTreeMap<String, Duration>,
+}
+
+impl ComparedBenchmark {
+    /// The name of the payload type, which is also the name of the Criterion benchmark group.
+    #[must_use]
+    pub fn payload_type(&self) -> &str {
+        &self.payload_type
+    }
+
+    /// The name of the benchmark within the Criterion benchmark group.
+    #[must_use]
+    pub fn benchmark_name(&self) -> &str {
+        &self.benchmark_name
+    }
+
+    /// The mean duration of one iteration of the benchmark on each machine that executed it,
+    /// keyed by the name of the machine.
+    #[must_use]
+    pub fn mean_iteration_durations(&self) -> &BTreeMap<String, Duration> {
+        &self.mean_iteration_durations
+    }
+
+    fn key(&self) -> (&str, &str) {
+        (&self.payload_type, &self.benchmark_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsStr, fs, process};
+
+    use super::*;
+    use crate::WorkDistribution;
+
+    fn topology(memory_regions: &[(usize, usize)]) -> TopologyFingerprint {
+        TopologyFingerprint {
+            memory_regions: memory_regions
+                .iter()
+                .map(
+                    |&(performance_processors, efficiency_processors)| MemoryRegionFingerprint {
+                        performance_processors,
+                        efficiency_processors,
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    fn results(machine: &str, topology: TopologyFingerprint, nanos: u64) -> MachineResults {
+        let mut results = MachineResults::new(machine.to_string(), topology);
+
+        results.add(BenchmarkResults::from(&RunSamples::new(
+            "CopyBytes",
+            "PinnedSelf".to_string(),
+            WorkDistribution::PinnedSelf,
+            vec![Sample::new(
+                2,
+                Duration::from_nanos(nanos.saturating_mul(2)),
+            )],
+            BTreeMap::new(),
+        )));
+
+        results
+    }
+
+    fn manifest(machines: &[&str]) -> Manifest {
+        Manifest {
+            machines: machines.iter().map(ToString::to_string).collect(),
+            env: BTreeMap::from([("MANY_CPUS_BENCHMARKING_SEED".to_string(), "42".to_string())]),
+        }
+    }
+
+    #[test]
+    fn current_topology_counts_all_processors() {
+        let topology = TopologyFingerprint::current();
+
+        let processor_count = topology
+            .memory_regions
+            .iter()
+            .map(|r| {
+                r.performance_processors
+                    .saturating_add(r.efficiency_processors)
+            })
+            .sum::<usize>();
+
+        let expected = ProcessorSet::builder()
+            .ignoring_resource_quota()
+            .take_all()
+            .unwrap()
+            .len();
+
+        assert_eq!(processor_count, expected);
+    }
+
+    #[test]
+    fn topology_display() {
+        assert_eq!(topology(&[(8, 0), (4, 4)]).to_string(), "8P+0E/4P+4E");
+    }
+
+    #[test]
+    fn mean_iteration_duration() {
+        let benchmark = BenchmarkResults {
+            payload_type: "CopyBytes".to_string(),
+            benchmark_name: "PinnedSelf".to_string(),
+            work_distribution: "PinnedSelf".to_string(),
+            samples: vec![
+                Sample::new(1, Duration::from_nanos(100)),
+                Sample::new(3, Duration::from_nanos(500)),
+            ],
+            metrics: BTreeMap::new(),
+        };
+
+        assert_eq!(
+            benchmark.mean_iteration_duration(),
+            Some(Duration::from_nanos(150))
+        );
+
+        let empty = BenchmarkResults {
+            samples: Vec::new(),
+            ..benchmark
+        };
+
+        assert_eq!(empty.mean_iteration_duration(), None);
+    }
+
+    #[test]
+    fn export_replaces_results_of_same_benchmark() {
+        let path = env::temp_dir().join(format!(
+            "many_cpus_benchmarking_export_{}.json",
+            process::id()
+        ));
+        _ = fs::remove_file(&path);
+
+        let topology = topology(&[(2, 0)]);
+
+        let samples = |benchmark_name: &str, nanos| {
+            RunSamples::new(
+                "CopyBytes",
+                benchmark_name.to_string(),
+                WorkDistribution::PinnedSelf,
+                vec![Sample::new(1, Duration::from_nanos(nanos))],
+                BTreeMap::from([("misses", 0.5)]),
+            )
+        };
+
+        export_samples(&path, "a", &topology, &samples("PinnedSelf", 10)).unwrap();
+        export_samples(&path, "a", &topology, &samples("UnpinnedSelf", 20)).unwrap();
+        export_samples(&path, "a", &topology, &samples("PinnedSelf", 30)).unwrap();
+
+        let results = MachineResults::read_from(&path).unwrap();
+
+        assert_eq!(results.machine(), "a");
+        assert_eq!(results.topology(), &topology);
+        let [pinned, unpinned] = results.benchmarks() else {
+            panic!("expected two benchmarks, got {:?}", results.benchmarks());
+        };
+
+        assert_eq!(pinned.benchmark_name(), "PinnedSelf");
+        assert_eq!(
+            pinned.samples(),
+            &[Sample::new(1, Duration::from_nanos(30))]
+        );
+        assert_eq!(
+            pinned.metrics(),
+            &BTreeMap::from([("misses".to_string(), 0.5)])
+        );
+        assert_eq!(unpinned.benchmark_name(), "UnpinnedSelf");
+
+        // The results of a different machine cannot be mixed into the same file.
+        export_samples(&path, "b", &topology, &samples("PinnedSelf", 10)).unwrap_err();
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn results_round_trip() {
+        let results = results("a", topology(&[(2, 0)]), 10);
+
+        let json = serde_json::to_string(&results).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<MachineResults>(&json).unwrap(),
+            results
+        );
+    }
+
+    #[test]
+    fn configure_command_sets_env() {
+        let manifest = manifest(&["a", "b"]);
+
+        let mut command = Command::new("cargo");
+        manifest.configure_command(&mut command, "b", "results");
+
+        let envs = command
+            .get_envs()
+            .map(|(key, value)| (key.to_owned(), value.unwrap().to_owned()))
+            .collect::<BTreeMap<_, _>>();
+
+        assert_eq!(envs.get(OsStr::new(MACHINE_NAME_ENV_VAR)).unwrap(), "b");
+        assert_eq!(
+            envs.get(OsStr::new(RESULTS_PATH_ENV_VAR)).unwrap(),
+            Manifest::results_path("results", "b").as_os_str()
+        );
+        assert_eq!(
+            envs.get(OsStr::new("MANY_CPUS_BENCHMARKING_SEED")).unwrap(),
+            "42"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn configure_command_for_unknown_machine_panics() {
+        manifest(&["a"]).configure_command(&mut Command::new("cargo"), "b", "results");
+    }
+
+    #[test]
+    fn merge_groups_by_topology() {
+        let small = topology(&[(2, 0)]);
+        let large = topology(&[(8, 0), (8, 0)]);
+
+        let comparisons = manifest(&["a", "b", "c"])
+            .merge([
+                results("a", large.clone(), 10),
+                results("b", small.clone(), 20),
+                results("c", large.clone(), 30),
+            ])
+            .unwrap();
+
+        let [first, second] = comparisons.as_slice() else {
+            panic!("expected two comparisons, got {comparisons:?}");
+        };
+
+        assert_eq!(first.topology(), &small);
+        assert_eq!(first.machines(), &["b".to_string()]);
+
+        assert_eq!(second.topology(), &large);
+        assert_eq!(second.machines(), &["a".to_string(), "c".to_string()]);
+        let [benchmark] = second.benchmarks() else {
+            panic!("expected one benchmark, got {:?}", second.benchmarks());
+        };
+
+        assert_eq!(
+            benchmark.mean_iteration_durations(),
+            &BTreeMap::from([
+                ("a".to_string(), Duration::from_nanos(10)),
+                ("c".to_string(), Duration::from_nanos(30)),
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_requires_exactly_the_listed_machines() {
+        let topology = topology(&[(2, 0)]);
+        let manifest = manifest(&["a", "b"]);
+
+        assert_eq!(
+            manifest.merge([results("a", topology.clone(), 10)]),
+            Err(MergeError::MissingMachines {
+                machines: vec!["b".to_string()]
+            })
+        );
+
+        assert_eq!(
+            manifest.merge([
+                results("a", topology.clone(), 10),
+                results("a", topology.clone(), 10),
+            ]),
+            Err(MergeError::DuplicateMachine {
+                machine: "a".to_string()
+            })
+        );
+
+        assert_eq!(
+            manifest.merge([results("x", topology, 10)]),
+            Err(MergeError::UnknownMachine {
+                machine: "x".to_string()
+            })
+        );
+    }
+}
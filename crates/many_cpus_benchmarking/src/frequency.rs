@@ -0,0 +1,164 @@
+use many_cpus::{ProcessorId, ProcessorSet};
+
+/// Fixes the frequency of a set of processors for as long as the value exists, restoring the
+/// original frequency settings of the processors when dropped.
+///
+/// The processors are switched to the `performance` frequency governor, with both the minimum
+/// and the maximum frequency set to the requested frequency. This removes the variance caused by
+/// processors boosting to different frequencies in different iterations.
+///
+/// This requires permission to write to `/sys/devices/system/cpu/cpu*/cpufreq/`, which typically
+/// means running as root, and is only supported on Linux.
+#[derive(Debug)]
+pub(crate) struct FrequencyPin {
+    original_settings: Vec<FrequencySettings>,
+}
+
+/// The frequency settings of one processor, as found in its `cpufreq` directory.
+#[derive(Debug)]
+#[cfg_attr(
+    not(target_os = "linux"),
+    expect(dead_code, reason = "only the Linux implementation reads the settings")
+)]
+struct FrequencySettings {
+    processor_id: ProcessorId,
+    governor: String,
+    min_khz: u64,
+    max_khz: u64,
+}
+
+impl FrequencyPin {
+    /// Fixes the frequency of every processor in `processors` at `frequency_khz`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frequency settings cannot be changed, e.g. due to missing permissions,
+    /// a frequency not supported by the processors or an unsupported operating system. Any
+    /// settings already changed are restored before panicking.
+    pub(crate) fn new(processors: &ProcessorSet, frequency_khz: u64) -> Self {
+        let mut pin = Self {
+            original_settings: Vec::with_capacity(processors.len()),
+        };
+
+        for processor in processors.processors() {
+            let processor_id = processor.id();
+
+            // We record the original settings before changing anything, so even partially
+            // applied changes are restored (by dropping `pin`) if we panic below.
+            pin.original_settings
+                .push(platform::read_settings(processor_id));
+
+            platform::write_settings(&FrequencySettings {
+                processor_id,
+                governor: PINNED_GOVERNOR.to_string(),
+                min_khz: frequency_khz,
+                max_khz: frequency_khz,
+            })
+            .unwrap_or_else(|e| {
+                panic!(
+                    "failed to fix the frequency of processor {processor_id} at {frequency_khz} kHz - this requires write access to the cpufreq settings in /sys (typically root) and a frequency supported by the processor: {e}"
+                )
+            });
+        }
+
+        pin
+    }
+}
+
+impl Drop for FrequencyPin {
+    fn drop(&mut self) {
+        for settings in self.original_settings.drain(..) {
+            // We may be dropped during a panic, so we do our best and only report failures.
+            if let Err(e) = platform::write_settings(&settings) {
+                eprintln!(
+                    "Failed to restore the frequency settings of processor {}: {e}",
+                    settings.processor_id
+                );
+            }
+        }
+    }
+}
+
+const PINNED_GOVERNOR: &str = "performance";
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, io};
+
+    use many_cpus::ProcessorId;
+
+    use super::FrequencySettings;
+
+    pub(super) fn read_settings(processor_id: ProcessorId) -> FrequencySettings {
+        let read = |file| {
+            fs::read_to_string(cpufreq_path(processor_id, file))
+                .map(|contents| contents.trim().to_string())
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "failed to read cpufreq {file} of processor {processor_id} - frequency pinning requires a Linux system with cpufreq support: {e}"
+                    )
+                })
+        };
+
+        let read_khz = |file| {
+            read(file).parse::<u64>().unwrap_or_else(|e| {
+                panic!("cpufreq {file} of processor {processor_id} is not a valid frequency: {e}")
+            })
+        };
+
+        FrequencySettings {
+            processor_id,
+            governor: read("scaling_governor"),
+            min_khz: read_khz("scaling_min_freq"),
+            max_khz: read_khz("scaling_max_freq"),
+        }
+    }
+
+    pub(super) fn write_settings(settings: &FrequencySettings) -> io::Result<()> {
+        let write = |file, value: &str| fs::write(cpufreq_path(settings.processor_id, file), value);
+
+        write("scaling_governor", &settings.governor)?;
+
+        // The operating system rejects a minimum above the current maximum (and vice versa),
+        // so the order of the writes depends on which way the frequency range is moving.
+        let current_max_khz =
+            fs::read_to_string(cpufreq_path(settings.processor_id, "scaling_max_freq"))?
+                .trim()
+                .parse::<u64>()
+                .map_err(io::Error::other)?;
+
+        if settings.min_khz > current_max_khz {
+            write("scaling_max_freq", &settings.max_khz.to_string())?;
+            write("scaling_min_freq", &settings.min_khz.to_string())?;
+        } else {
+            write("scaling_min_freq", &settings.min_khz.to_string())?;
+            write("scaling_max_freq", &settings.max_khz.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    fn cpufreq_path(processor_id: ProcessorId, file: &str) -> String {
+        format!("/sys/devices/system/cpu/cpu{processor_id}/cpufreq/{file}")
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use std::io;
+
+    use many_cpus::ProcessorId;
+
+    use super::FrequencySettings;
+
+    pub(super) fn read_settings(_processor_id: ProcessorId) -> FrequencySettings {
+        panic!("frequency pinning is only supported on Linux");
+    }
+
+    pub(super) fn write_settings(_settings: &FrequencySettings) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "frequency pinning is only supported on Linux",
+        ))
+    }
+}
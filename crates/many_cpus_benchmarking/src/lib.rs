@@ -100,6 +100,20 @@
 //!
 //! <img src="https://media.githubusercontent.com/media/folo-rs/folo/refs/heads/main/crates/many_cpus_benchmarking/images/work_distribution_comparison.png">
 //!
+//! # Run configuration
+//!
+//! `execute_runs()` is a shorthand for the default [`RunConfig`][7], which measures the
+//! steady-state processing of the payloads. The customizations described in the following
+//! sections are enabled via the methods of `RunConfig` and can be freely combined:
+//!
+//! ```rust ignore (benchmark)
+//! fn entrypoint(c: &mut Criterion) {
+//!     RunConfig::new()
+//!         .memory_pressure(MemoryPressure::new(1024 * 1024 * 1024))
+//!         .execute::<CopyBytes, 1>(c, WorkDistribution::all());
+//! }
+//! ```
+//!
 //! # Payload multiplier
 //!
 //! It may sometimes be desirable to multiply the size of a benchmark scenario, e.g. if a scenario is
@@ -111,17 +125,192 @@
 //! benchmark harness overheads to be reduced, so the majority of the time is spent on payload
 //! processing.
 //!
+//! # Cold-start runs
+//!
+//! The regular runs only measure the processing of payloads by workers that are already running.
+//! If the startup costs matter for your scenario (e.g. the workers only live for a single request),
+//! use [`RunConfig::cold_start()`][24] instead, which measures everything from spawning the workers
+//! to the end of processing, tearing down the workers after every iteration.
+//!
+//! # Interference
+//!
+//! To measure how a workload is affected by other work happening on the same system (e.g. a memory
+//! bandwidth hog in a different memory region), use [`RunConfig::interference()`][8]. This
+//! runs a background payload on processors selected by a second work distribution for as long as
+//! the measured payloads are being processed.
+//!
+//! # Migration
+//!
+//! To quantify the cost of moving work between processors while its data is hot in the caches
+//! (e.g. due to work stealing), use [`RunConfig::migration()`][20]. This moves every worker
+//! to a different processor in the same or in a different memory region at the first
+//! [checkpoint][18] of each payload.
+//!
+//! # Custom metrics
+//!
+//! If the duration is not the only thing of interest, use [`RunConfig::metrics()`][9] to
+//! wrap the timed section of each worker in a closure that collects custom metrics (e.g. counters
+//! maintained by the payload). The metrics are reported together with the other run details and
+//! are included in the [raw samples][22] of each benchmark.
+//!
+//! # Memory pressure
+//!
+//! To see how a workload behaves on a system that is not idle, use
+//! [`RunConfig::memory_pressure()`][11] to allocate and touch a configurable amount of
+//! memory in selected memory regions while the benchmarks are running, simulating the memory
+//! footprint of a busy co-tenant.
+//!
+//! # Profiling
+//!
+//! To profile only the processing of the payloads and not the orchestration performed by the
+//! harness, use [`RunConfig::profiler()`][15] with an implementation of [`Profiler`][16]
+//! that controls an external profiler such as `perf` or `pprof`. The harness starts a profiling
+//! session for every benchmark, with a consistent name that can be used for the output files, and
+//! enables sampling only while workers are in the timed sections.
+//!
+//! # Chunked processing
+//!
+//! A single long-running iteration only yields one duration, with no insight into the variance
+//! within it. Payloads can instead [process their work as a series of chunks][17], notifying the
+//! harness via a [`Checkpoint`][18] after each chunk. The harness reports the percentiles of the
+//! chunk durations together with the other run details and aborts iterations that run longer than
+//! the [timeout][19] of the payload.
+//!
+//! # Raw samples
+//!
+//! To calculate custom statistics from the measurements, use [`RunConfig::on_samples()`][21]
+//! to receive the [raw samples][22] of each benchmark, as reported to Criterion, together with the
+//! details of the benchmark they belong to.
+//!
+//! # Shared read-only data
+//!
+//! Scenarios in which all workers access the same large read-only data set (e.g. a shared
+//! in-memory index) can be defined by implementing [`SharedDataScenario`][13] and executed via
+//! [`SharedDataPayload`][14]. The data set is prepared only once and either replicated in every
+//! memory region or kept in one memory region, depending on the placement of the scenario.
+//!
+//! # Linked objects
+//!
+//! With the `linked` Cargo feature enabled, scenarios whose state is a [linked object][12] can be
+//! defined by implementing `LinkedScenario` and executed via `LinkedPayload`, which creates one
+//! family for each worker pair and one instance of the family on the thread of each worker.
+//!
+//! # Comparing machines
+//!
+//! With the `export` Cargo feature enabled, the same benchmark suite can be executed on several
+//! machines and the results merged into one comparison for each hardware topology. Benchmarks
+//! executed while `MANY_CPUS_BENCHMARKING_RESULTS_PATH` is set write their samples into a results
+//! file of the machine, while a `Manifest` lists the machines, configures the command that
+//! executes the suite on each of them and merges the collected results files.
+//!
+//! # Tags
+//!
+//! Scenarios can be categorized by [overriding `Payload::tags()`][10]. If the
+//! `MANY_CPUS_BENCHMARKING_TAGS` environment variable is set to a comma-separated list of tags,
+//! only the scenarios with at least one of the listed tags are executed. This can be used to
+//! partition a benchmark suite by category, in addition to the name filter of Criterion.
+//!
+//! # Avoiding noisy processors
+//!
+//! Some processors are busier than others with work outside the benchmark, such as handling the
+//! interrupts of a network adapter, which distorts the samples of any iteration that lands on them.
+//! If the `MANY_CPUS_BENCHMARKING_AVOID_PROCESSORS` environment variable is set to a list of
+//! processor IDs in the cpulist format (e.g. `0,16-17`), the harness avoids these processors
+//! whenever the work distribution can be satisfied without them. Whether the processors were
+//! avoided is reported together with the reference selection of each benchmark.
+//!
+//! Instead of maintaining such a list by hand, the harness can identify the processors that
+//! handle a disproportionate share of the device interrupts of the system. If the
+//! `MANY_CPUS_BENCHMARKING_AVOID_IRQ_HEAVY_PROCESSORS` environment variable is set to a threshold
+//! (e.g. `2.0`), every processor that has handled more than that many times the average number
+//! of device interrupts per processor is avoided in the same way, as determined by
+//! [`ProcessorSetBuilder::excluding_irq_heavy()`][23].
+//!
+//! # Pinning the coordinating thread
+//!
+//! The thread that executes the benchmark runs coordinates the workers and competes with them for
+//! processor time, which can distort the samples on systems with few processors, especially with
+//! the unpinned work distributions. If the `MANY_CPUS_BENCHMARKING_COORDINATOR_PROCESSORS`
+//! environment variable is set to a list of processor IDs in the cpulist format (e.g. `0-1`) or
+//! to a memory region (e.g. `region:1`), the coordinating thread is pinned to these processors
+//! while the benchmarks are running and the workers avoid them whenever the work distribution can
+//! be satisfied without them.
+//!
+//! # Fixed processor frequency
+//!
+//! Processors boost to different frequencies depending on temperature and the load on other
+//! processors, which can hide small differences between work distributions. On Linux, setting
+//! the `MANY_CPUS_BENCHMARKING_FIXED_FREQUENCY_KHZ` environment variable to a frequency in kHz
+//! fixes the frequency of all processors at that value while the benchmarks are running, restoring
+//! the original settings afterwards. This requires write access to the processor frequency
+//! settings in `/sys`, which typically means running the benchmarks as root.
+//!
+//! # Steering benchmarks without recompiling
+//!
+//! The same compiled benchmark binary can be steered (e.g. by different CI jobs) via environment
+//! variables:
+//!
+//! * `MANY_CPUS_BENCHMARKING_DISTRIBUTIONS` - a comma-separated list of work distributions (e.g.
+//!   `PinnedMemoryRegionPairs,PinnedSelf`). Only the requested work distributions in the list
+//!   are executed.
+//! * `MANY_CPUS_BENCHMARKING_PAYLOAD_MULTIPLIER` - a positive integer that replaces the
+//!   [payload multiplier](#payload-multiplier) given in code.
+//! * `MANY_CPUS_BENCHMARKING_SEED` - an integer seed from which the processor selections of all
+//!   iterations are derived, making them reproducible between executions on the same system.
+//! * `MANY_CPUS_BENCHMARKING_PROCESSORS` - a list of processor IDs in the cpulist format (e.g.
+//!   `0-15`). The workers only use these processors and the work distributions that cannot be
+//!   satisfied with them are skipped.
+//!
 //! [1]: https://bheisler.github.io/criterion.rs/book/index.html
 //! [3]: crate::Payload::new_pair
 //! [4]: crate::Payload::prepare
 //! [5]: crate::Payload::process
 //! [6]: crate::execute_runs
+//! [7]: crate::RunConfig
+//! [8]: crate::RunConfig::interference
+//! [9]: crate::RunConfig::metrics
+//! [10]: crate::Payload::tags
+//! [11]: crate::RunConfig::memory_pressure
+//! [12]: https://docs.rs/linked
+//! [13]: crate::SharedDataScenario
+//! [14]: crate::SharedDataPayload
+//! [15]: crate::RunConfig::profiler
+//! [16]: crate::Profiler
+//! [17]: crate::Payload::process_chunked
+//! [18]: crate::Checkpoint
+//! [19]: crate::Payload::timeout
+//! [20]: crate::RunConfig::migration
+//! [21]: crate::RunConfig::on_samples
+//! [22]: crate::RunSamples
+//! [23]: many_cpus::ProcessorSetBuilder::excluding_irq_heavy
+//! [24]: crate::RunConfig::cold_start
 
 pub(crate) mod cache;
+mod checkpoint;
+#[cfg(feature = "export")]
+mod export;
+mod frequency;
+#[cfg(feature = "linked")]
+mod linked_payload;
+mod memory_pressure;
+mod migration;
 mod payload;
+mod profiling;
 mod run;
+mod samples;
+mod shared_data;
 mod work_distribution;
 
+pub use checkpoint::*;
+#[cfg(feature = "export")]
+pub use export::*;
+#[cfg(feature = "linked")]
+pub use linked_payload::*;
+pub use memory_pressure::*;
+pub use migration::*;
 pub use payload::*;
+pub use profiling::*;
 pub use run::*;
+pub use samples::*;
+pub use shared_data::*;
 pub use work_distribution::*;
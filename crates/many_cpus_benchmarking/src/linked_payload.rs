@@ -0,0 +1,230 @@
+use std::{fmt, marker::PhantomData};
+
+use linked::{Family, Object};
+
+use crate::Payload;
+
+/// A benchmark scenario whose state is a [linked object][linked], executed via
+/// [`LinkedPayload`].
+///
+/// Both workers in a worker pair use their own instance of the same family, just as two threads
+/// of a real application would. This is only available with the `linked` Cargo feature.
+pub trait LinkedScenario: 'static {
+    /// The linked object whose instances the workers operate on.
+    type Object: Object + Send;
+
+    /// Creates the family shared by the workers of one worker pair in one benchmark iteration.
+    /// This will be called on the main thread.
+    ///
+    /// To create the family from the constructor of the linked object, create an instance and
+    /// return its family (e.g. `Counter::new().family()`).
+    fn new_family() -> Family<Self::Object>;
+
+    /// Performs any initialization of the instance of a worker required. This will be called
+    /// right after the instance has been created on the thread of the worker and is not counted
+    /// as part of the benchmark time span.
+    fn prepare(_instance: &mut Self::Object) {}
+
+    /// Processes the instance of a worker. The iteration is complete when this returns for all
+    /// instances.
+    fn process(instance: &mut Self::Object);
+
+    /// Tags that categorize the benchmark scenario, see [`Payload::tags()`].
+    #[must_use]
+    fn tags() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// A [`Payload`] that executes a [`LinkedScenario`], creating one family of the linked object
+/// for each worker pair and one instance of the family for each worker.
+///
+/// The instance of each worker is created on the thread of the worker when the worker is ready
+/// to process the payload (see [`Payload::prepare_local()`]), so that the instance is counted
+/// against the thread it is used on and any per-thread state of the family (e.g. memory
+/// allocated by the instance) is created by the processor the worker is pinned to. Before that,
+/// the payload only carries the family, which can be moved freely between threads.
+///
+/// # Example
+///
+/// ```rust ignore (benchmark)
+/// #[linked::object]
+/// struct Counter {
+///     local: usize,
+///     total: Arc<AtomicUsize>,
+/// }
+///
+/// impl Counter {
+///     fn new() -> Self {
+///         let total = Arc::new(AtomicUsize::new(0));
+///
+///         linked::new!(Self {
+///             local: 0,
+///             total: Arc::clone(&total),
+///         })
+///     }
+///
+///     fn increment(&mut self) {
+///         self.local += 1;
+///         self.total.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// struct IncrementCounter;
+///
+/// impl LinkedScenario for IncrementCounter {
+///     type Object = Counter;
+///
+///     fn new_family() -> Family<Counter> {
+///         Counter::new().family()
+///     }
+///
+///     fn process(instance: &mut Counter) {
+///         for _ in 0..10_000 {
+///             instance.increment();
+///         }
+///     }
+/// }
+///
+/// fn entrypoint(c: &mut Criterion) {
+///     execute_runs::<LinkedPayload<IncrementCounter>, 1>(c, WorkDistribution::all());
+/// }
+/// ```
+pub struct LinkedPayload<S: LinkedScenario> {
+    family: Family<S::Object>,
+
+    // Created on the thread of the worker in `prepare_local()`.
+    instance: Option<S::Object>,
+
+    _scenario: PhantomData<fn() -> S>,
+}
+
+impl<S: LinkedScenario> LinkedPayload<S> {
+    fn new(family: Family<S::Object>) -> Self {
+        Self {
+            family,
+            instance: None,
+            _scenario: PhantomData,
+        }
+    }
+}
+
+impl<S: LinkedScenario> Payload for LinkedPayload<S> {
+    fn new_pair() -> (Self, Self) {
+        let family = S::new_family();
+
+        (Self::new(family.clone()), Self::new(family))
+    }
+
+    fn prepare_local(&mut self) {
+        let mut instance: S::Object = self.family.clone().into();
+        S::prepare(&mut instance);
+
+        self.instance = Some(instance);
+    }
+
+    fn process(&mut self) {
+        S::process(
+            self.instance
+                .as_mut()
+                .expect("prepare_local() is always called before process()"),
+        );
+    }
+
+    fn tags() -> &'static [&'static str] {
+        S::tags()
+    }
+}
+
+impl<S: LinkedScenario> fmt::Debug for LinkedPayload<S> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkedPayload")
+            .field("family", &self.family)
+            .field("has_instance", &self.instance.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    use super::*;
+
+    #[linked::object]
+    struct Counter {
+        prepared: bool,
+        total: Arc<AtomicUsize>,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            let total = Arc::new(AtomicUsize::new(0));
+
+            linked::new!(Self {
+                prepared: false,
+                total: Arc::clone(&total),
+            })
+        }
+    }
+
+    struct CountProcessed;
+
+    impl LinkedScenario for CountProcessed {
+        type Object = Counter;
+
+        fn new_family() -> Family<Counter> {
+            Counter::new().family()
+        }
+
+        fn prepare(instance: &mut Counter) {
+            instance.prepared = true;
+        }
+
+        fn process(instance: &mut Counter) {
+            assert!(instance.prepared);
+            instance.total.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn tags() -> &'static [&'static str] {
+            &["linked"]
+        }
+    }
+
+    #[test]
+    fn pair_shares_family_with_instance_per_worker() {
+        let (mut first, second) = LinkedPayload::<CountProcessed>::new_pair();
+        let family = first.family.clone();
+
+        // The instance used to create the family is gone, so there are no instances yet.
+        assert_eq!(family.instance_count(), 0);
+
+        first.prepare_local();
+        first.process();
+
+        thread::spawn(move || {
+            let mut second = second;
+            second.prepare_local();
+            second.process();
+
+            // One instance for each worker.
+            assert_eq!(second.family.instance_count(), 2);
+        })
+        .join()
+        .unwrap();
+
+        // The instance of the worker was dropped together with the payload on its thread.
+        assert_eq!(family.instance_count(), 1);
+
+        let instance = first.instance.as_ref().unwrap();
+        assert_eq!(instance.total.load(Ordering::Relaxed), 2);
+        assert_eq!(LinkedPayload::<CountProcessed>::tags(), &["linked"]);
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fmt,
+    hint::black_box,
+    mem,
+    sync::{
+        Arc, Barrier, Mutex,
+        mpsc::{self, TryRecvError},
+    },
+    thread::JoinHandle,
+};
+
+use itertools::Itertools;
+use many_cpus::{MemoryRegionId, Processor, ProcessorSet};
+
+/// Granularity at which we touch the allocated memory. This is the smallest page size in common
+/// use - if the real page size is larger, we just touch some pages more than once, which is fine.
+const PAGE_SIZE: usize = 4096;
+
+/// Describes memory pressure that is applied to the system during benchmark runs, simulating
+/// the memory footprint of a busy co-tenant.
+///
+/// The memory is allocated and touched by a background thread in each selected memory region,
+/// which ensures that the physical memory is committed in that memory region (assuming the
+/// operating system allocates physical memory in the region of the processor that first touches
+/// it, which is the typical default).
+///
+/// See [`RunConfig::memory_pressure()`][crate::RunConfig::memory_pressure].
+///
+/// # Example
+///
+/// ```
+/// use many_cpus_benchmarking::MemoryPressure;
+///
+/// // 1 GB in memory region 0, touched continuously while the benchmark is running.
+/// let pressure = MemoryPressure::new(1024 * 1024 * 1024)
+///     .in_memory_regions([0])
+///     .touch_continuously();
+/// ```
+#[derive(Clone, Debug)]
+pub struct MemoryPressure {
+    bytes_per_memory_region: usize,
+
+    /// If `None`, all memory regions with processors are used.
+    memory_regions: Option<Vec<MemoryRegionId>>,
+
+    touch_continuously: bool,
+}
+
+impl MemoryPressure {
+    /// Creates a description of memory pressure that allocates `bytes_per_memory_region` bytes
+    /// in every memory region that has processors available to the current process.
+    ///
+    /// By default, the memory is touched once after allocation and then held
+    /// without being accessed until the benchmark run completes.
+    #[must_use]
+    pub fn new(bytes_per_memory_region: usize) -> Self {
+        Self {
+            bytes_per_memory_region,
+            memory_regions: None,
+            touch_continuously: false,
+        }
+    }
+
+    /// Limits the memory pressure to the specified memory regions.
+    ///
+    /// If any of the memory regions has no processors available to the current process, the
+    /// memory cannot be allocated in it and any benchmark runs using this memory pressure
+    /// will be skipped.
+    #[must_use]
+    pub fn in_memory_regions(
+        mut self,
+        memory_regions: impl IntoIterator<Item = MemoryRegionId>,
+    ) -> Self {
+        self.memory_regions = Some(memory_regions.into_iter().collect());
+        self
+    }
+
+    /// Keeps touching the allocated memory throughout the benchmark run, in addition to touching
+    /// it once after allocation. This also consumes memory bandwidth and processor time in the
+    /// selected memory regions, in addition to consuming memory capacity.
+    #[must_use]
+    pub fn touch_continuously(mut self) -> Self {
+        self.touch_continuously = true;
+        self
+    }
+
+    /// Allocates and touches the memory, returning once all the memory has been touched once.
+    /// The memory is released when the returned value is dropped.
+    ///
+    /// Returns `None` if any of the selected memory regions has no processors that
+    /// can be used to allocate memory in it.
+    pub(crate) fn apply(&self) -> Option<AppliedMemoryPressure> {
+        let all_processors = ProcessorSet::builder().take_all()?;
+
+        let memory_regions = match &self.memory_regions {
+            Some(memory_regions) => memory_regions.clone(),
+            None => all_processors
+                .processors()
+                .iter()
+                .map(Processor::memory_region_id)
+                .unique()
+                .collect_vec(),
+        };
+
+        let processor_sets = memory_regions
+            .iter()
+            .map(|&memory_region_id| {
+                all_processors
+                    .to_builder()
+                    .filter(|p| p.memory_region_id() == memory_region_id)
+                    .take_all()
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let workers_plus_coordinator = processor_sets.len().checked_add(1).expect(
+            "we will never have so many memory regions that we overflow usize, even if we add one",
+        );
+
+        let ready_signal = Arc::new(Barrier::new(workers_plus_coordinator));
+
+        // Each worker keeps running until the sender is dropped.
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let stop_rx = Arc::new(Mutex::new(stop_rx));
+
+        let join_handles = processor_sets
+            .iter()
+            .map(|processor_set| {
+                let ready_signal = Arc::clone(&ready_signal);
+                let stop_rx = Arc::clone(&stop_rx);
+                let bytes = self.bytes_per_memory_region;
+                let touch_continuously = self.touch_continuously;
+
+                processor_set.spawn_thread(move |_| {
+                    // This does not yet commit any physical memory - that only happens on touch.
+                    let mut buffer = vec![0_u8; bytes];
+
+                    touch(&mut buffer);
+
+                    ready_signal.wait();
+
+                    if touch_continuously {
+                        while matches!(stop_rx.lock().unwrap().try_recv(), Err(TryRecvError::Empty))
+                        {
+                            touch(&mut buffer);
+                        }
+                    } else {
+                        // We never send anything, so this returns when the sender is dropped.
+                        _ = stop_rx.lock().unwrap().recv();
+                    }
+
+                    drop(buffer);
+                })
+            })
+            .collect_vec();
+
+        ready_signal.wait();
+
+        Some(AppliedMemoryPressure {
+            stop_tx: Some(stop_tx),
+            join_handles: join_handles.into_boxed_slice(),
+        })
+    }
+}
+
+impl fmt::Display for MemoryPressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MemoryPressure({} bytes", self.bytes_per_memory_region)?;
+
+        if let Some(memory_regions) = &self.memory_regions {
+            write!(f, " in {}", cpulist::emit(memory_regions.iter().copied()))?;
+        }
+
+        if self.touch_continuously {
+            write!(f, ", continuous")?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+/// Writes to every page of the buffer, forcing the operating system to commit physical memory.
+fn touch(buffer: &mut [u8]) {
+    for byte in buffer.iter_mut().step_by(PAGE_SIZE) {
+        *byte = black_box(byte.wrapping_add(1));
+    }
+}
+
+/// Memory pressure that is currently being applied. Dropping this releases the memory.
+#[derive(Debug)]
+pub(crate) struct AppliedMemoryPressure {
+    stop_tx: Option<mpsc::Sender<()>>,
+    join_handles: Box<[JoinHandle<()>]>,
+}
+
+impl Drop for AppliedMemoryPressure {
+    fn drop(&mut self) {
+        // Disconnecting the channel signals the workers to stop.
+        drop(self.stop_tx.take());
+
+        for handle in mem::take(&mut self.join_handles) {
+            // If a worker panicked, we bring the panic to the main thread.
+            handle.join().unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touch_modifies_every_page() {
+        let mut buffer = vec![0_u8; PAGE_SIZE * 3 + 1];
+
+        touch(&mut buffer);
+
+        assert_eq!(buffer.iter().filter(|b| **b == 1).count(), 4);
+    }
+
+    #[test]
+    fn display_includes_details() {
+        let pressure = MemoryPressure::new(1024)
+            .in_memory_regions([0, 1])
+            .touch_continuously();
+
+        assert_eq!(
+            pressure.to_string(),
+            "MemoryPressure(1024 bytes in 0-1, continuous)"
+        );
+    }
+
+    #[cfg(not(miri))] // Talks to the real platform.
+    #[test]
+    fn apply_smoke_test() {
+        let pressure = MemoryPressure::new(PAGE_SIZE * 4).touch_continuously();
+
+        let applied = pressure.apply().unwrap();
+        drop(applied);
+    }
+}
@@ -0,0 +1,113 @@
+use derive_more::Display;
+use folo_utils::nz;
+use many_cpus::{Processor, ProcessorSet, ProcessorSetBuilder};
+
+/// Where the workers are moved to midway through processing a payload, to simulate the
+/// migration of work between processors (e.g. due to work stealing) while its data is hot
+/// in the caches of the original processor.
+///
+/// See [`RunConfig::migration()`][crate::RunConfig::migration].
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Migration {
+    /// Each worker is moved to a different processor in the same memory region, so only the
+    /// caches of the original processor are left behind.
+    SameMemoryRegion,
+
+    /// Each worker is moved to a processor in a different memory region, so the worker continues
+    /// with data that is not only in the wrong caches but also in remote memory.
+    ///
+    /// Benchmark runs with this migration will be skipped if the system only has a single
+    /// memory region.
+    DifferentMemoryRegion,
+}
+
+impl Migration {
+    /// Selects the processor that a worker executing on `processor_set` is moved to from the
+    /// candidates matched by `candidates`, avoiding the processors in `occupied` (e.g. the
+    /// processors of the other workers).
+    ///
+    /// Returns `None` if there is no suitable processor.
+    pub(crate) fn target(
+        self,
+        candidates: ProcessorSetBuilder,
+        processor_set: &ProcessorSet,
+        occupied: &[Processor],
+    ) -> Option<ProcessorSet> {
+        let memory_region_id = processor_set.processors().first().memory_region_id();
+
+        candidates
+            .performance_processors_only()
+            .except(occupied)
+            .filter(|p| match self {
+                Self::SameMemoryRegion => p.memory_region_id() == memory_region_id,
+                Self::DifferentMemoryRegion => p.memory_region_id() != memory_region_id,
+            })
+            .take(nz!(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_avoids_occupied_processors() {
+        let processor_set = ProcessorSet::builder()
+            .ignoring_resource_quota()
+            .take_all()
+            .unwrap();
+        let occupied = processor_set
+            .processors()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        assert!(
+            Migration::SameMemoryRegion
+                .target(ProcessorSet::builder(), &processor_set, &occupied)
+                .is_none()
+        );
+        assert!(
+            Migration::DifferentMemoryRegion
+                .target(ProcessorSet::builder(), &processor_set, &occupied)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn target_matches_memory_region() {
+        let processor_set =
+            ProcessorSet::from(ProcessorSet::default().processors().first().clone());
+        let memory_region_id = processor_set.processors().first().memory_region_id();
+        let occupied = processor_set
+            .processors()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if let Some(target) =
+            Migration::SameMemoryRegion.target(ProcessorSet::builder(), &processor_set, &occupied)
+        {
+            assert_eq!(
+                target.processors().first().memory_region_id(),
+                memory_region_id
+            );
+            assert_ne!(
+                target.processors().first(),
+                processor_set.processors().first()
+            );
+        }
+
+        if let Some(target) = Migration::DifferentMemoryRegion.target(
+            ProcessorSet::builder(),
+            &processor_set,
+            &occupied,
+        ) {
+            assert_ne!(
+                target.processors().first().memory_region_id(),
+                memory_region_id
+            );
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use crate::Checkpoint;
+
 /// One benchmark payload, to be processed by each worker involved in each benchmark.
 ///
 /// Payloads are created in pairs because the workers are created in pairs. Depending on the
@@ -10,7 +14,8 @@
 /// 1. Each payload in the pair is transferred to a specific thread hosting a specific worker.
 /// 1. The `prepare()` method is called to generate any input data.
 /// 1. The payload pair is exchanged between the two paired workers.
-/// 1. The `process()` method is called to process the data received from the other pair member.
+/// 1. The `process_chunked()` method is called to process the data received from the other pair
+///    member. Unless overridden, this calls `process()`.
 /// 1. The payload pair is dropped.
 ///
 /// Note that some [work distribution modes][crate::WorkDistribution] (named `*Self`) may skip
@@ -34,4 +39,79 @@ pub trait Payload: Sized + Send + 'static {
     /// for all payloads. The payloads are dropped later, to ensure that the benchmark time is not
     /// affected by the time it takes to drop the payload and release the memory.
     fn process(&mut self);
+
+    /// Processes the payload as a series of chunks, calling [`Checkpoint::chunk_completed()`]
+    /// after completing each chunk. This is what the benchmark harness calls to process the
+    /// payload and, by default, it processes the entire payload as a single chunk by calling
+    /// [`process()`][Self::process] without reaching any checkpoints.
+    ///
+    /// Override this if a single call to `process()` takes long enough for the variance within
+    /// it to matter. The harness times each chunk and reports the percentiles of the chunk
+    /// durations once the benchmark has completed. If the checkpoint returns
+    /// [`ControlFlow::Break`][std::ops::ControlFlow::Break], the iteration has exceeded the
+    /// [timeout][Self::timeout] and the payload must stop processing.
+    ///
+    /// When overriding this, implement `process()` by calling this with
+    /// [`Checkpoint::untimed()`], so both process the payload in the same way.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use many_cpus_benchmarking::{Checkpoint, Payload};
+    ///
+    /// #[derive(Debug, Default)]
+    /// struct SumBlocks {
+    ///     blocks: Vec<Vec<u64>>,
+    ///     sum: u64,
+    /// }
+    ///
+    /// impl Payload for SumBlocks {
+    ///     fn new_pair() -> (Self, Self) {
+    ///         (Self::default(), Self::default())
+    ///     }
+    ///
+    ///     fn prepare(&mut self) {
+    ///         self.blocks = vec![vec![1; 1024]; 16];
+    ///     }
+    ///
+    ///     fn process(&mut self) {
+    ///         self.process_chunked(&mut Checkpoint::untimed());
+    ///     }
+    ///
+    ///     fn process_chunked(&mut self, checkpoint: &mut Checkpoint) {
+    ///         for block in &self.blocks {
+    ///             self.sum = self.sum.wrapping_add(block.iter().sum::<u64>());
+    ///
+    ///             if checkpoint.chunk_completed().is_break() {
+    ///                 return;
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn process_chunked(&mut self, checkpoint: &mut Checkpoint) {
+        _ = checkpoint;
+        self.process();
+    }
+
+    /// The longest time one iteration of the payload may take before its processing is aborted.
+    ///
+    /// The timeout is only enforced at the checkpoints of
+    /// [`process_chunked()`][Self::process_chunked], so it has no effect on payloads that do
+    /// not report their chunks. An aborted iteration is measured up to the checkpoint at which
+    /// it was aborted and the number of aborted iterations is reported once the benchmark has
+    /// completed. By default, there is no timeout.
+    #[must_use]
+    fn timeout() -> Option<Duration> {
+        None
+    }
+
+    /// Tags that categorize the benchmark scenario (e.g. "bandwidth", "latency", "collaborative").
+    ///
+    /// The tags can be used to select which scenarios are executed, via the environment variable
+    /// named by [`TAG_FILTER_ENV_VAR`][crate::TAG_FILTER_ENV_VAR]. By default, a scenario has no tags.
+    #[must_use]
+    fn tags() -> &'static [&'static str] {
+        &[]
+    }
 }
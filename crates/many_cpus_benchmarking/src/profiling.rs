@@ -0,0 +1,242 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+/// Integration point for an external profiler (e.g. `perf` or `pprof`), allowing samples to be
+/// collected only while workers are executing the timed sections of a benchmark, instead of
+/// mostly capturing the orchestration performed by the harness.
+///
+/// See [`RunConfig::profiler()`][crate::RunConfig::profiler].
+///
+/// Each benchmark (one work distribution of one payload type) is a separate profiling session,
+/// delimited by [`start()`][Self::start] and [`stop()`][Self::stop]. Within a session, sampling
+/// is enabled via [`resume()`][Self::resume] when the first worker enters a timed section and
+/// disabled via [`pause()`][Self::pause] when the last worker leaves one. The session starts in
+/// the paused state.
+///
+/// The methods may be called from any thread but calls are never concurrent.
+///
+/// # Output file names
+///
+/// The `name` given to `start()` and `stop()` identifies the benchmark as
+/// `<payload type>-<benchmark name>`, with every character that is not an ASCII letter, digit,
+/// `-` or `_` replaced by `_` (e.g. `my_benches__CopyBytes-PinnedMemoryRegionPairs`). It is
+/// intended to be used as the stem of the output file names of the session, so the output of
+/// every benchmark ends up in a consistently named file.
+pub trait Profiler: Send + Sync + 'static {
+    /// Starts a profiling session for the benchmark with the given name, in the paused state.
+    ///
+    /// This is called before the first iteration of the benchmark, including warm-up iterations.
+    fn start(&self, name: &str);
+
+    /// Enables sampling, as a worker is entering a timed section.
+    fn resume(&self);
+
+    /// Disables sampling, as no worker is in a timed section anymore.
+    fn pause(&self);
+
+    /// Ends the profiling session for the benchmark with the given name, e.g. writing the
+    /// collected samples to an output file.
+    ///
+    /// This is called after the last iteration of the benchmark.
+    fn stop(&self, name: &str);
+}
+
+/// Drives the profiler of one benchmark, resuming sampling when the first worker enters a timed
+/// section and pausing it when the last worker leaves one.
+pub(crate) struct ProfilerGate {
+    profiler: Arc<dyn Profiler>,
+    name: String,
+
+    // How many workers are in a timed section. Only the workers that move this from zero or
+    // to zero need to touch the profiler, so the others never wait for anything.
+    active_sections: AtomicUsize,
+
+    // Whether the profiler is sampling. The lock serializes the calls to the profiler, which
+    // must never be concurrent, and is not held by anyone while in a timed section.
+    sampling: Mutex<bool>,
+}
+
+impl ProfilerGate {
+    pub(crate) fn new(profiler: Arc<dyn Profiler>, payload_type: &str, benchmark: &str) -> Self {
+        Self {
+            profiler,
+            name: profile_name(payload_type, benchmark),
+            active_sections: AtomicUsize::new(0),
+            sampling: Mutex::new(false),
+        }
+    }
+
+    pub(crate) fn start(&self) {
+        self.profiler.start(&self.name);
+    }
+
+    pub(crate) fn stop(&self) {
+        self.profiler.stop(&self.name);
+    }
+
+    /// Marks the start of a timed section, which lasts until the returned value is dropped.
+    pub(crate) fn enter(&self) -> TimedSection<'_> {
+        if self.active_sections.fetch_add(1, Ordering::AcqRel) == 0 {
+            self.sync_sampling();
+        }
+
+        TimedSection { gate: self }
+    }
+
+    /// Resumes or pauses the profiler to match whether any worker is in a timed section.
+    ///
+    /// A worker leaving the last timed section and another entering the first one may call this
+    /// in either order, so instead of blindly toggling the profiler, we look at the latest count.
+    /// Whoever calls this last sees the final count, so the profiler always ends up in the right
+    /// state once the workers are done changing the count.
+    fn sync_sampling(&self) {
+        let mut sampling = self.sampling.lock().unwrap();
+
+        let should_sample = self.active_sections.load(Ordering::Acquire) != 0;
+
+        if should_sample == *sampling {
+            return;
+        }
+
+        if should_sample {
+            self.profiler.resume();
+        } else {
+            self.profiler.pause();
+        }
+
+        *sampling = should_sample;
+    }
+}
+
+/// A timed section of a worker, during which the profiler is sampling.
+pub(crate) struct TimedSection<'a> {
+    gate: &'a ProfilerGate,
+}
+
+impl Drop for TimedSection<'_> {
+    fn drop(&mut self) {
+        if self.gate.active_sections.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.gate.sync_sampling();
+        }
+    }
+}
+
+/// The name of the profiling session of a benchmark, usable as a file name stem.
+fn profile_name(payload_type: &str, benchmark: &str) -> String {
+    format!("{payload_type}-{benchmark}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingProfiler {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Profiler for RecordingProfiler {
+        fn start(&self, name: &str) {
+            self.calls.lock().unwrap().push(format!("start {name}"));
+        }
+
+        fn resume(&self) {
+            self.calls.lock().unwrap().push("resume".to_string());
+        }
+
+        fn pause(&self) {
+            self.calls.lock().unwrap().push("pause".to_string());
+        }
+
+        fn stop(&self, name: &str) {
+            self.calls.lock().unwrap().push(format!("stop {name}"));
+        }
+    }
+
+    #[test]
+    fn profile_name_is_file_name_safe() {
+        assert_eq!(
+            profile_name("my_benches::CopyBytes", "PinnedNearMemoryRegion(1)"),
+            "my_benches__CopyBytes-PinnedNearMemoryRegion_1_"
+        );
+    }
+
+    #[test]
+    fn samples_only_while_any_section_is_active() {
+        let profiler = Arc::new(RecordingProfiler::default());
+        let gate = ProfilerGate::new(
+            Arc::<RecordingProfiler>::clone(&profiler),
+            "Payload",
+            "Bench",
+        );
+
+        gate.start();
+
+        let first = gate.enter();
+        let second = gate.enter();
+        drop(first);
+        drop(second);
+
+        drop(gate.enter());
+
+        gate.stop();
+
+        assert_eq!(
+            *profiler.calls.lock().unwrap(),
+            [
+                "start Payload-Bench",
+                "resume",
+                "pause",
+                "resume",
+                "pause",
+                "stop Payload-Bench"
+            ]
+        );
+    }
+
+    #[cfg(not(miri))] // Too slow under Miri.
+    #[test]
+    fn concurrent_sections_end_paused() {
+        let profiler = Arc::new(RecordingProfiler::default());
+        let gate = ProfilerGate::new(
+            Arc::<RecordingProfiler>::clone(&profiler),
+            "Payload",
+            "Bench",
+        );
+
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        drop(gate.enter());
+                    }
+                });
+            }
+        });
+
+        let calls = profiler.calls.lock().unwrap();
+
+        // The profiler is only ever toggled, never resumed or paused twice in a row.
+        assert!(!calls.is_empty());
+        assert!(
+            calls
+                .iter()
+                .zip(["resume", "pause"].iter().cycle())
+                .all(|(call, expected)| call == expected)
+        );
+        assert_eq!(calls.last().unwrap(), "pause");
+    }
+}
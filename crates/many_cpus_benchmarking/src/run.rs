@@ -1,11 +1,17 @@
 use std::{
     any::type_name,
-    collections::VecDeque,
-    env,
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+    env, fmt,
     iter::{once, repeat_with},
     mem,
     num::NonZero,
-    sync::{Arc, Barrier, Mutex, mpsc},
+    sync::{
+        Arc, Barrier, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
     thread::JoinHandle,
     time::{Duration, Instant},
 };
@@ -13,11 +19,19 @@ use std::{
 use criterion::{BenchmarkGroup, Criterion, SamplingMode, measurement::WallTime};
 use folo_utils::nz;
 use itertools::Itertools;
-use many_cpus::{Processor, ProcessorSet};
+use many_cpus::{
+    EfficiencyClass, HardwareInfo, MemoryRegionId, Processor, ProcessorId, ProcessorSet,
+    ProcessorSetBuilder,
+};
 use nonempty::{NonEmpty, nonempty};
-use rand::{rng, seq::SliceRandom};
+use rand::{RngCore, SeedableRng, rng, rngs::StdRng, seq::SliceRandom};
 
-use crate::{Payload, WorkDistribution};
+#[cfg(feature = "export")]
+use crate::export::Exporter;
+use crate::{
+    Checkpoint, MemoryPressure, Migration, Payload, Profiler, RunSamples, Sample, WorkDistribution,
+    checkpoint::ChunkStats, frequency::FrequencyPin, profiling::ProfilerGate,
+};
 
 // https://github.com/cloudhead/nonempty/issues/68
 extern crate alloc;
@@ -31,10 +45,713 @@ extern crate alloc;
 /// Assign the highest value that is low enough for this many payloads to fit in memory at the
 /// same time on every worker thread. The benchmark infrastructure may also limit this value, so
 /// this is merely an upper bound.
+///
+/// This is a shorthand for executing the runs with the default [`RunConfig`]. Use a customized
+/// `RunConfig` to measure cold starts, apply interference or memory pressure, collect custom
+/// metrics and so on.
 pub fn execute_runs<P: Payload, const BATCH_SIZE: u64>(
     c: &mut Criterion,
     work_distributions: &[WorkDistribution],
 ) {
+    RunConfig::new().execute::<P, BATCH_SIZE>(c, work_distributions);
+}
+
+/// User-defined metrics reported by a measurement closure, as `(name, value)` pairs.
+///
+/// See [`RunConfig::metrics()`].
+pub type CustomMetrics = Vec<(&'static str, f64)>;
+
+/// Customizes how benchmark runs are executed, beyond the work distribution modes.
+///
+/// The default configuration measures the steady-state processing of payloads, equivalent to
+/// [`execute_runs()`]. The customizations can be freely combined, with each one that affects
+/// the measurement adding a suffix to the benchmark names, so the runs can be part of the same
+/// benchmark group as the regular runs without a name conflict.
+///
+/// # Example
+///
+/// ```rust ignore (benchmark)
+/// fn entrypoint(c: &mut Criterion) {
+///     RunConfig::new()
+///         .memory_pressure(MemoryPressure::new(1024 * 1024 * 1024))
+///         .migration(Migration::SameMemoryRegion)
+///         .execute::<CopyBytes, 1>(c, WorkDistribution::all());
+/// }
+/// ```
+#[derive(Default)]
+pub struct RunConfig {
+    mode: MeasurementMode,
+    interference: Option<InterferenceSpec>,
+    measure: Option<MeasureFn>,
+    memory_pressure: Option<MemoryPressure>,
+    profiler: Option<Arc<dyn Profiler>>,
+    migration: Option<Migration>,
+    on_samples: Option<SamplesFn>,
+}
+
+impl RunConfig {
+    /// Creates a configuration that measures the steady-state processing of payloads,
+    /// without any customizations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measures cold starts instead of the steady-state processing of payloads.
+    ///
+    /// Unlike the regular runs, which only measure the time spent processing payloads on workers
+    /// that are already up and running, each iteration of a cold-start run measures the full cost
+    /// of spawning and pinning the workers, preparing the payloads and processing them. The
+    /// workers are torn down after every iteration, so every iteration starts from scratch and
+    /// there is nothing to batch - the batch size given to [`execute()`][Self::execute] and the
+    /// payload multiplier are ignored.
+    ///
+    /// Processor caches are not cleaned before the iterations of a cold-start run, as the startup
+    /// effects themselves are the subject of the measurement.
+    ///
+    /// The benchmarks are named after the work distribution mode with a `ColdStart` suffix.
+    #[must_use]
+    pub fn cold_start(mut self) -> Self {
+        self.mode = MeasurementMode::ColdStart;
+        self
+    }
+
+    /// Generates interference while the runs are executed, by having background workers process
+    /// payloads of type `N` on processors selected by `distribution`.
+    ///
+    /// This allows quantifying how much a "noisy neighbor" hurts the measured work. For example, to
+    /// see how a memory bandwidth hog in memory region 0 affects work in memory region 1, use
+    /// [`WorkDistribution::PinnedNearMemoryRegion`] with region 1 for the measured work and with
+    /// region 0 for the interference.
+    ///
+    /// The interference workers are started before the measured workers are spawned and keep
+    /// running until the measured workers have finished processing. Each interference worker
+    /// prepares one payload and then calls [`Payload::process()`] on it repeatedly, so the
+    /// interference payload must support being processed any number of times and must not rely
+    /// on its partner to complete a single call to `process()`. The interference workers are
+    /// placed independently of the measured workers, so the two may share processors, depending
+    /// on the distributions used.
+    ///
+    /// The benchmarks are named after the work distribution mode with an `Under` suffix naming the
+    /// interference distribution.
+    ///
+    /// If the system hardware topology is not compatible with the interference distribution,
+    /// all runs are skipped.
+    #[must_use]
+    pub fn interference<N: Payload>(mut self, distribution: WorkDistribution) -> Self {
+        self.interference = Some(InterferenceSpec {
+            distribution,
+            start: Interference::start::<N>,
+        });
+        self
+    }
+
+    /// Collects custom metrics on each worker via the `measure` closure.
+    ///
+    /// The closure is called on each worker for every payload it processes and is given the work
+    /// distribution being executed and the timed section as a callback. The closure must call the
+    /// callback exactly once and may run any code before and after it (e.g. to read counters
+    /// exposed by the payload or by the operating system), returning the metrics it collected.
+    /// Only the time spent in the callback counts toward the measured duration.
+    ///
+    /// The metrics are averaged across all the timed sections of a benchmark and are reported
+    /// together with the other details of the run once the benchmark has completed. The averages
+    /// are also included in the [`RunSamples`] of the benchmark. Note that the averages also
+    /// include the iterations executed by Criterion during warm-up.
+    ///
+    /// # Panics
+    ///
+    /// Executing benchmark runs panics if the closure does not call the callback exactly once.
+    #[must_use]
+    pub fn metrics<F>(mut self, measure: F) -> Self
+    where
+        F: Fn(WorkDistribution, &mut dyn FnMut()) -> CustomMetrics + Send + Sync + 'static,
+    {
+        self.measure = Some(Arc::new(measure));
+        self
+    }
+
+    /// Applies the specified memory pressure to the system while the runs are executed.
+    ///
+    /// The memory is allocated and touched before each benchmark starts and is released after
+    /// each benchmark has completed, so the allocation itself is not part of the measured
+    /// duration.
+    ///
+    /// The benchmarks are named after the work distribution mode with an `Under` suffix describing
+    /// the memory pressure.
+    ///
+    /// If any of the memory regions selected for the memory pressure has no processors available
+    /// to the current process, all runs are skipped.
+    #[must_use]
+    pub fn memory_pressure(mut self, memory_pressure: MemoryPressure) -> Self {
+        self.memory_pressure = Some(memory_pressure);
+        self
+    }
+
+    /// Has `profiler` collect samples only during the timed sections.
+    ///
+    /// Each benchmark is a separate profiling session with a consistent name, intended as the stem
+    /// of the output file names. Sampling is resumed when the first worker enters a timed section
+    /// and paused when the last worker leaves one, so preparing payloads, spawning and
+    /// synchronizing workers and cleaning caches are excluded from the samples. In cold-start
+    /// runs, the timed section is the entire lifecycle of the workers, as that is what cold-start
+    /// runs measure.
+    ///
+    /// Note that the samples also include the iterations executed by Criterion during warm-up. The
+    /// profiler is not used when the benchmarks are only listed or executed as tests.
+    #[must_use]
+    pub fn profiler(mut self, profiler: impl Profiler) -> Self {
+        self.profiler = Some(Arc::new(profiler));
+        self
+    }
+
+    /// Moves every worker to a different processor midway through processing each payload, as
+    /// described by `migration`.
+    ///
+    /// This quantifies the cost of migrating work between processors while its data is hot in the
+    /// caches of the original processor, as happens with work stealing schedulers. Comparing the
+    /// results with the regular runs of the same work distribution shows the cost of the
+    /// migration.
+    ///
+    /// The workers are moved at the first [`Checkpoint`] of each payload, so the payload must
+    /// [process its work in chunks][Payload::process_chunked] - typically, a payload for migration
+    /// runs reports a single checkpoint at the midpoint of its work. The time it takes to move the
+    /// worker is counted as part of the chunk that follows the checkpoint. Workers are moved back
+    /// to their original processors after each payload, outside the measured duration.
+    ///
+    /// The migration targets are selected for every batch of iterations, avoiding the processors
+    /// of all the workers.
+    ///
+    /// The benchmarks are named after the work distribution mode with a `MigratingTo` suffix
+    /// naming the migration.
+    ///
+    /// If the system hardware topology does not have enough processors to move the workers to,
+    /// all runs are skipped.
+    #[must_use]
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.migration = Some(migration);
+        self
+    }
+
+    /// Gives the raw samples of each benchmark to `on_samples`.
+    ///
+    /// The callback is called on the current thread once each benchmark has completed, with the
+    /// same samples that were reported to Criterion, allowing custom statistics to be calculated
+    /// without parsing the output files of Criterion. The samples also include the samples
+    /// measured by Criterion during warm-up.
+    ///
+    /// The callback is not called for benchmarks that are skipped.
+    #[must_use]
+    pub fn on_samples(mut self, on_samples: impl FnMut(RunSamples) + 'static) -> Self {
+        self.on_samples = Some(RefCell::new(Box::new(on_samples)));
+        self
+    }
+
+    /// Executes a number of benchmark runs for a specific payload type with this configuration,
+    /// using the specified work distribution modes.
+    ///
+    /// See [`execute_runs()`] for the meaning of `BATCH_SIZE`.
+    pub fn execute<P: Payload, const BATCH_SIZE: u64>(
+        &self,
+        c: &mut Criterion,
+        work_distributions: &[WorkDistribution],
+    ) {
+        execute_runs_with_config::<P, BATCH_SIZE>(c, work_distributions, self);
+    }
+}
+
+impl fmt::Debug for RunConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunConfig")
+            .field("mode", &self.mode)
+            .field("interference", &self.interference)
+            .field("memory_pressure", &self.memory_pressure)
+            .field("migration", &self.migration)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Name of the environment variable that can be used to only execute scenarios with specific tags.
+///
+/// The value is a comma-separated list of tags. A scenario is executed if any of its
+/// [tags][Payload::tags] is in the list. If the variable is not set, all scenarios are executed.
+pub const TAG_FILTER_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_TAGS";
+
+/// Name of the environment variable that can be used to avoid specific processors when selecting
+/// the processors for the workers, such as the processors that handle network interrupts.
+///
+/// The value is a list of processor IDs in the [cpulist](https://docs.rs/cpulist) format (e.g.
+/// `0,16-17`). When multiple selections of processors are valid for a work distribution, the
+/// selection avoids the listed processors. If the work distribution cannot be satisfied without
+/// the listed processors, they are used anyway. Whether the processors were avoided is reported
+/// together with the reference selection of each benchmark.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not a valid cpulist.
+pub const AVOID_PROCESSORS_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_AVOID_PROCESSORS";
+
+/// Name of the environment variable that can be used to avoid the processors that handle a
+/// disproportionate share of the device interrupts of the system (e.g. from network adapters)
+/// when selecting the processors for the workers.
+///
+/// The value is a positive number, the threshold given to
+/// [`ProcessorSetBuilder::excluding_irq_heavy()`]: a processor is avoided if it has handled more
+/// than this many times the average number of device interrupts per processor (e.g. `2.0`). The
+/// processors are identified once, before the benchmark runs start, and are avoided in the same
+/// way as the processors listed in [`AVOID_PROCESSORS_ENV_VAR`], in addition to them.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not a positive finite number.
+pub const AVOID_IRQ_HEAVY_PROCESSORS_ENV_VAR: &str =
+    "MANY_CPUS_BENCHMARKING_AVOID_IRQ_HEAVY_PROCESSORS";
+
+/// Name of the environment variable that can be used to fix the frequency of the processors
+/// during benchmark runs, removing the variance caused by processors boosting to different
+/// frequencies in different iterations.
+///
+/// The value is a frequency in kHz (e.g. `2000000` for 2 GHz), which must be supported by the
+/// processors. While benchmark runs are executing, the processors that the workers may use
+/// (honoring [`PROCESSORS_ENV_VAR`]) use the `performance` frequency governor with both the
+/// minimum and maximum frequency set to this value. The processors listed in
+/// [`AVOID_PROCESSORS_ENV_VAR`] (including the processors of the coordinating thread) are left
+/// unchanged, unless the workers may not use any other processors. The original settings are
+/// restored when the benchmark runs are finished.
+///
+/// The frequency is not changed when the benchmarks are only listed or executed as tests.
+///
+/// This is only supported on Linux and requires write access to the processor frequency settings
+/// in `/sys/devices/system/cpu/cpu*/cpufreq/`, which typically means running the benchmarks as
+/// root.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not a valid frequency or the frequency
+/// settings cannot be changed.
+pub const FIXED_FREQUENCY_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_FIXED_FREQUENCY_KHZ";
+
+/// Name of the environment variable that can be used to pin the coordinating thread (the thread
+/// that executes the benchmark runs and waits for the workers) away from the workers, so it does
+/// not compete with them for processor time.
+///
+/// The value is either a list of processor IDs in the [cpulist](https://docs.rs/cpulist) format
+/// (e.g. `0,16-17`) or `region:` followed by a memory region ID (e.g. `region:1`), in which case
+/// all processors of that memory region are used. While benchmark runs are executing, the
+/// coordinating thread is pinned to these processors and the workers avoid them in the same way
+/// as the processors listed in [`AVOID_PROCESSORS_ENV_VAR`]. The original processor affinity of
+/// the coordinating thread is restored when the benchmark runs are finished.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not valid or none of the designated
+/// processors are available to the current process.
+pub const COORDINATOR_PROCESSORS_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_COORDINATOR_PROCESSORS";
+
+/// Name of the environment variable that can be used to only execute specific work distributions.
+///
+/// The value is a comma-separated list of work distributions, named as in the benchmark names
+/// (e.g. `PinnedMemoryRegionPairs,PinnedNearMemoryRegion(0)`). Of the work distributions
+/// requested by the caller, only those in the list are executed. If the variable is not set or is
+/// blank, all the requested work distributions are executed.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the list contains a name that is not a valid work
+/// distribution.
+pub const DISTRIBUTIONS_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_DISTRIBUTIONS";
+
+/// Name of the environment variable that can be used to override the payload multiplier (the
+/// `BATCH_SIZE` generic parameter of [`execute_runs()`] and its variants).
+///
+/// The value is a positive integer. Cold-start runs ignore the override, as each of their
+/// iterations always starts from scratch.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not a positive integer.
+pub const PAYLOAD_MULTIPLIER_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_PAYLOAD_MULTIPLIER";
+
+/// Name of the environment variable that can be used to make the selection of processors for the
+/// workers deterministic.
+///
+/// The value is an integer seed. Each iteration still selects different processors but the
+/// sequence of selections is derived from the seed, so executing the same benchmarks with the
+/// same seed on the same system selects the same processors in the same order. See
+/// [`ProcessorSetBuilder::with_seed()`].
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not a valid 64-bit unsigned integer.
+pub const SEED_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_SEED";
+
+/// Name of the environment variable that can be used to restrict the workers to specific
+/// processors.
+///
+/// The value is a list of processor IDs in the [cpulist](https://docs.rs/cpulist) format (e.g.
+/// `0-15`). Unlike with [`AVOID_PROCESSORS_ENV_VAR`], the workers never use any other processors,
+/// so work distributions that cannot be satisfied with the listed processors are skipped.
+///
+/// # Panics
+///
+/// Executing benchmark runs panics if the value is not a valid cpulist.
+pub const PROCESSORS_ENV_VAR: &str = "MANY_CPUS_BENCHMARKING_PROCESSORS";
+
+fn execute_runs_with_config<P: Payload, const BATCH_SIZE: u64>(
+    c: &mut Criterion,
+    work_distributions: &[WorkDistribution],
+    config: &RunConfig,
+) {
+    let tag_filter = env::var(TAG_FILTER_ENV_VAR).ok();
+
+    if !is_selected_by_tags(P::tags(), tag_filter.as_deref()) {
+        if !is_fake_run() {
+            // Be silent if it is a fake run, to avoid confusing the test runner.
+            eprintln!(
+                "Skipping {} - its tags do not match the {TAG_FILTER_ENV_VAR} filter.",
+                type_name::<P>()
+            );
+        }
+
+        return;
+    }
+
+    let mut avoided_processors =
+        parse_avoided_processors(env::var(AVOID_PROCESSORS_ENV_VAR).ok().as_deref());
+
+    if let Some(threshold) =
+        parse_irq_heavy_threshold(env::var(AVOID_IRQ_HEAVY_PROCESSORS_ENV_VAR).ok().as_deref())
+    {
+        let irq_heavy_processors = irq_heavy_processors(threshold);
+
+        if !is_fake_run() {
+            eprintln!(
+                "Avoiding interrupt-heavy processors {}.",
+                cpulist::emit(irq_heavy_processors.iter().copied())
+            );
+        }
+
+        avoided_processors.extend(irq_heavy_processors);
+        avoided_processors.sort_unstable();
+        avoided_processors.dedup();
+    }
+
+    let distribution_filter =
+        parse_distribution_filter(env::var(DISTRIBUTIONS_ENV_VAR).ok().as_deref());
+
+    let batch_size = match config.mode {
+        MeasurementMode::SteadyState => {
+            parse_payload_multiplier(env::var(PAYLOAD_MULTIPLIER_ENV_VAR).ok().as_deref())
+                .unwrap_or(BATCH_SIZE)
+        }
+        // Each iteration starts from scratch, so there is nothing to batch.
+        MeasurementMode::ColdStart => 1,
+    };
+
+    let seed = parse_seed(env::var(SEED_ENV_VAR).ok().as_deref());
+
+    let allowed_processors = parse_allowed_processors(env::var(PROCESSORS_ENV_VAR).ok().as_deref());
+
+    // Restores the original affinity of the current thread when dropped at the end of the runs,
+    // also if a benchmark panics, so later benchmarks in the same binary are not affected.
+    let _coordinator_pin = parse_coordinator_placement(
+        env::var(COORDINATOR_PROCESSORS_ENV_VAR).ok().as_deref(),
+    )
+    .map(|placement| {
+        let coordinator_processors = placement.resolve();
+
+        if !is_fake_run() {
+            eprintln!(
+                "Pinning the coordinating thread to processors {}.",
+                cpulist::emit(
+                    coordinator_processors
+                        .processors()
+                        .iter()
+                        .map(Processor::id)
+                )
+            );
+        }
+
+        let pin = coordinator_processors.pin_current_thread_scoped();
+
+        // The workers stay away from the coordinator whenever the work distribution allows.
+        avoided_processors.extend(
+            coordinator_processors
+                .processors()
+                .iter()
+                .map(Processor::id),
+        );
+        avoided_processors.sort_unstable();
+        avoided_processors.dedup();
+
+        pin
+    });
+
+    let selector = ProcessorSelector::new(avoided_processors, allowed_processors, seed);
+
+    // Restores the original frequency settings when dropped at the end of the runs. Fake runs
+    // only list or smoke-test the benchmarks, so we leave the system settings alone for them.
+    let _frequency_pin = parse_fixed_frequency(env::var(FIXED_FREQUENCY_ENV_VAR).ok().as_deref())
+        .filter(|_| !is_fake_run())
+        .map(|frequency_khz| {
+            let processors = selector.frequency_pinned_processors();
+
+            eprintln!(
+                "Fixing the frequency of processors {} at {frequency_khz} kHz.",
+                cpulist::emit(processors.processors().iter().map(Processor::id))
+            );
+
+            FrequencyPin::new(&processors, frequency_khz)
+        });
+
+    // Every completed benchmark is exported into the results file of the machine, if enabled.
+    #[cfg(feature = "export")]
+    let exporter = Exporter::from_env();
+
+    let mut g = new_benchmark_group::<P>(c);
+
+    for &distribution in work_distributions {
+        if !is_selected_by_distributions(distribution, distribution_filter.as_deref()) {
+            continue;
+        }
+
+        execute_run::<P>(
+            &mut g,
+            distribution,
+            config,
+            &selector,
+            batch_size,
+            #[cfg(feature = "export")]
+            exporter.as_ref(),
+        );
+    }
+
+    g.finish();
+}
+
+/// Whether a scenario with the given tags is selected by the tag filter,
+/// given in the format of the `TAG_FILTER_ENV_VAR` environment variable.
+fn is_selected_by_tags(tags: &[&str], tag_filter: Option<&str>) -> bool {
+    let Some(tag_filter) = tag_filter else {
+        // No filter means everything is selected.
+        return true;
+    };
+
+    tag_filter
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .any(|t| tags.contains(&t))
+}
+
+/// Parses the processors to avoid, given in the format of the `AVOID_PROCESSORS_ENV_VAR`
+/// environment variable.
+fn parse_avoided_processors(avoided_processors: Option<&str>) -> Vec<ProcessorId> {
+    let Some(avoided_processors) = avoided_processors else {
+        // Nothing to avoid.
+        return Vec::new();
+    };
+
+    cpulist::parse(avoided_processors.trim()).unwrap_or_else(|e| {
+        panic!("{AVOID_PROCESSORS_ENV_VAR} must be a valid cpulist: {e}");
+    })
+}
+
+/// Parses the interrupt threshold of the processors to avoid, given in the format of the
+/// `AVOID_IRQ_HEAVY_PROCESSORS_ENV_VAR` environment variable.
+fn parse_irq_heavy_threshold(threshold: Option<&str>) -> Option<f64> {
+    let threshold = threshold?.trim();
+
+    if threshold.is_empty() {
+        return None;
+    }
+
+    let threshold: f64 = threshold.parse().unwrap_or_else(|e| {
+        panic!("{AVOID_IRQ_HEAVY_PROCESSORS_ENV_VAR} must be a positive number: {e}");
+    });
+
+    assert!(
+        threshold.is_finite() && threshold > 0.0,
+        "{AVOID_IRQ_HEAVY_PROCESSORS_ENV_VAR} must be a positive number, got {threshold}"
+    );
+
+    Some(threshold)
+}
+
+/// The processors that have handled more than `threshold` times the average number of device
+/// interrupts per processor.
+fn irq_heavy_processors(threshold: f64) -> Vec<ProcessorId> {
+    let quiet_processors = ProcessorSet::builder()
+        .excluding_irq_heavy(threshold)
+        .take_all()
+        .map(|set| set.processors().iter().map(Processor::id).collect_vec())
+        .unwrap_or_default();
+
+    ProcessorSet::builder()
+        .filter(|p| !quiet_processors.contains(&p.id()))
+        .take_all()
+        .map(|set| set.processors().iter().map(Processor::id).collect_vec())
+        .unwrap_or_default()
+}
+
+/// Parses the distribution filter, given in the format of the `DISTRIBUTIONS_ENV_VAR`
+/// environment variable.
+///
+/// The work distributions are kept by name, as device addresses cannot be turned back into
+/// the `&'static str` that the work distributions reference.
+fn parse_distribution_filter(distribution_filter: Option<&str>) -> Option<Vec<String>> {
+    let distribution_filter = distribution_filter?.trim();
+
+    if distribution_filter.is_empty() {
+        return None;
+    }
+
+    Some(
+        distribution_filter
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(|d| {
+                assert!(
+                    WorkDistribution::is_name(d),
+                    "{DISTRIBUTIONS_ENV_VAR} must only contain valid work distributions, not '{d}'"
+                );
+
+                d.to_string()
+            })
+            .collect(),
+    )
+}
+
+/// Whether a work distribution is selected by the parsed distribution filter.
+fn is_selected_by_distributions(
+    distribution: WorkDistribution,
+    distribution_filter: Option<&[String]>,
+) -> bool {
+    let Some(distribution_filter) = distribution_filter else {
+        // No filter means everything is selected.
+        return true;
+    };
+
+    let name = distribution.to_string();
+    distribution_filter.iter().any(|d| *d == name)
+}
+
+/// Parses the payload multiplier, given in the format of the `PAYLOAD_MULTIPLIER_ENV_VAR`
+/// environment variable.
+fn parse_payload_multiplier(multiplier: Option<&str>) -> Option<u64> {
+    let multiplier = multiplier?.trim();
+
+    if multiplier.is_empty() {
+        return None;
+    }
+
+    let multiplier: NonZero<u64> = multiplier.parse().unwrap_or_else(|e| {
+        panic!("{PAYLOAD_MULTIPLIER_ENV_VAR} must be a positive integer: {e}");
+    });
+
+    Some(multiplier.get())
+}
+
+/// Parses the seed of the processor selection, given in the format of the `SEED_ENV_VAR`
+/// environment variable.
+fn parse_seed(seed: Option<&str>) -> Option<u64> {
+    let seed = seed?.trim();
+
+    if seed.is_empty() {
+        return None;
+    }
+
+    Some(seed.parse().unwrap_or_else(|e| {
+        panic!("{SEED_ENV_VAR} must be a 64-bit unsigned integer: {e}");
+    }))
+}
+
+/// Parses the processors the workers are restricted to, given in the format of the
+/// `PROCESSORS_ENV_VAR` environment variable.
+fn parse_allowed_processors(allowed_processors: Option<&str>) -> Option<Vec<ProcessorId>> {
+    let allowed_processors = allowed_processors?.trim();
+
+    if allowed_processors.is_empty() {
+        return None;
+    }
+
+    Some(cpulist::parse(allowed_processors).unwrap_or_else(|e| {
+        panic!("{PROCESSORS_ENV_VAR} must be a valid cpulist: {e}");
+    }))
+}
+
+/// Where the coordinating thread is pinned, given in the format of the
+/// `COORDINATOR_PROCESSORS_ENV_VAR` environment variable.
+#[derive(Debug, Eq, PartialEq)]
+enum CoordinatorPlacement {
+    Processors(Vec<ProcessorId>),
+    MemoryRegion(MemoryRegionId),
+}
+
+impl CoordinatorPlacement {
+    /// The processors available to the current process that the coordinating thread is pinned to.
+    fn resolve(&self) -> ProcessorSet {
+        let builder = ProcessorSet::builder();
+
+        let builder = match self {
+            Self::Processors(processor_ids) => builder.filter(|p| processor_ids.contains(&p.id())),
+            Self::MemoryRegion(memory_region_id) => {
+                builder.filter(|p| p.memory_region_id() == *memory_region_id)
+            }
+        };
+
+        builder.take_all().unwrap_or_else(|| {
+            panic!(
+                "{COORDINATOR_PROCESSORS_ENV_VAR} must designate at least one processor available to the current process"
+            )
+        })
+    }
+}
+
+const COORDINATOR_MEMORY_REGION_PREFIX: &str = "region:";
+
+/// Parses where to pin the coordinating thread, given in the format of the
+/// `COORDINATOR_PROCESSORS_ENV_VAR` environment variable.
+fn parse_coordinator_placement(placement: Option<&str>) -> Option<CoordinatorPlacement> {
+    let placement = placement?.trim();
+
+    if placement.is_empty() {
+        return None;
+    }
+
+    if let Some(memory_region_id) = placement.strip_prefix(COORDINATOR_MEMORY_REGION_PREFIX) {
+        return Some(CoordinatorPlacement::MemoryRegion(
+            memory_region_id.trim().parse().unwrap_or_else(|e| {
+                panic!(
+                    "{COORDINATOR_PROCESSORS_ENV_VAR} must contain a valid memory region ID: {e}"
+                )
+            }),
+        ));
+    }
+
+    Some(CoordinatorPlacement::Processors(
+        cpulist::parse(placement).unwrap_or_else(|e| {
+            panic!("{COORDINATOR_PROCESSORS_ENV_VAR} must be a valid cpulist or memory region: {e}")
+        }),
+    ))
+}
+
+/// Parses the frequency to fix the processors at, given in the format of the
+/// `FIXED_FREQUENCY_ENV_VAR` environment variable.
+fn parse_fixed_frequency(frequency_khz: Option<&str>) -> Option<u64> {
+    let frequency_khz = frequency_khz?.trim();
+
+    if frequency_khz.is_empty() {
+        return None;
+    }
+
+    Some(frequency_khz.parse().unwrap_or_else(|e| {
+        panic!("{FIXED_FREQUENCY_ENV_VAR} must be a frequency in kHz: {e}");
+    }))
+}
+
+fn new_benchmark_group<P: Payload>(c: &mut Criterion) -> BenchmarkGroup<'_, WallTime> {
     let mut g = c.benchmark_group(type_name::<P>());
 
     // Many-processor benchmarks can be slow and clearing processor caches adds extra overhead
@@ -46,30 +763,63 @@ pub fn execute_runs<P: Payload, const BATCH_SIZE: u64>(
     // is not really something we care about here - we just want the basic duration scoring.
     g.sampling_mode(SamplingMode::Flat);
 
-    for &distribution in work_distributions {
-        execute_run::<P, BATCH_SIZE>(&mut g, distribution);
-    }
+    g
+}
 
-    g.finish();
+// Only ever called on the thread that executes the runs, one benchmark at a time.
+type SamplesFn = RefCell<Box<dyn FnMut(RunSamples)>>;
+
+type MeasureFn = Arc<dyn Fn(WorkDistribution, &mut dyn FnMut()) -> CustomMetrics + Send + Sync>;
+
+/// Describes the background payload that generates interference during a run.
+#[derive(Clone, Copy, Debug)]
+struct InterferenceSpec {
+    distribution: WorkDistribution,
+
+    /// Starts the interference workers on the given processors. The payload type is
+    /// erased here to avoid spreading an extra generic parameter through the run logic.
+    start: fn(&[(ProcessorSet, ProcessorSet)], WorkDistribution) -> Interference,
+}
+
+/// What part of the benchmark lifecycle is included in the measured duration.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum MeasurementMode {
+    /// Only the processing of the payloads is measured, with workers already started,
+    /// pinned and the payloads prepared.
+    #[default]
+    SteadyState,
+
+    /// Everything from spawning the workers to the end of processing is measured,
+    /// with the workers torn down after every iteration.
+    ColdStart,
 }
 
 /// In some execution modes, we are only executing to list the benchmarks or to perform a dummy
 /// run of a single iteration to test that it works. In these cases, we do not want to emit any
 /// additional output to stderr because it will confuse the test runner.
-fn is_fake_run() -> bool {
+pub(crate) fn is_fake_run() -> bool {
     // --test is used by cargo test
     // --exact is used by nextest
     // --list is used by both
     env::args().any(|a| a == "--test" || a == "--list" || a == "--exact")
 }
 
-fn execute_run<P: Payload, const BATCH_SIZE: u64>(
+fn execute_run<P: Payload>(
     g: &mut BenchmarkGroup<'_, WallTime>,
     work_distribution: WorkDistribution,
+    config: &RunConfig,
+    selector: &ProcessorSelector,
+    max_batch_size: u64,
+    #[cfg(feature = "export")] exporter: Option<&Exporter>,
 ) {
+    let mode = config.mode;
+    let interference = config.interference;
+
     // Probe whether we even have enough processors for this run. If not, just skip.
     // This is just a sample - we throw this selection away after we verify we can generate it.
-    let Some(sample_processor_selection) = get_processor_set_pairs(work_distribution) else {
+    let Some((sample_processor_selection, sample_avoids_processors)) =
+        select_processor_set_pairs(work_distribution, selector)
+    else {
         if !is_fake_run() {
             // Be silent if it is a fake run, to avoid confusing the test runner.
             eprintln!("Skipping {work_distribution} - system hardware topology is not compatible.");
@@ -78,6 +828,38 @@ fn execute_run<P: Payload, const BATCH_SIZE: u64>(
         return;
     };
 
+    let sample_interference_selection = match interference {
+        Some(interference) => {
+            let Some((selection, _)) =
+                select_processor_set_pairs(interference.distribution, selector)
+            else {
+                if !is_fake_run() {
+                    eprintln!(
+                        "Skipping {work_distribution} - system hardware topology is not compatible with interference distribution {}.",
+                        interference.distribution
+                    );
+                }
+
+                return;
+            };
+
+            selection
+        }
+        None => Vec::new(),
+    };
+
+    if let Some(migration) = config.migration {
+        if select_migration_targets(&sample_processor_selection, migration, selector).is_none() {
+            if !is_fake_run() {
+                eprintln!(
+                    "Skipping {work_distribution} - system hardware topology is not compatible with migration to {migration}."
+                );
+            }
+
+            return;
+        }
+    }
+
     // Writing to stderr during listing/testing leads to test runner errors because it expects
     // a special protocol to be spoken, so we only emit this debug output during actual execution.
     if !is_fake_run() {
@@ -90,35 +872,239 @@ fn execute_run<P: Payload, const BATCH_SIZE: u64>(
 
             eprintln!("{work_distribution} reference selection: ({cpulist1}) & ({cpulist2})");
         }
+
+        if !selector.avoided_processors.is_empty() {
+            let avoided_cpulist = cpulist::emit(selector.avoided_processors.iter().copied());
+
+            if sample_avoids_processors {
+                eprintln!(
+                    "{work_distribution} reference selection avoids processors {avoided_cpulist}"
+                );
+            } else {
+                eprintln!(
+                    "{work_distribution} reference selection cannot avoid processors {avoided_cpulist} - system hardware topology is not compatible without them."
+                );
+            }
+        }
+
+        for (processor_set_1, processor_set_2) in sample_interference_selection {
+            let cpulist1 = cpulist::emit(processor_set_1.processors().iter().map(Processor::id));
+            let cpulist2 = cpulist::emit(processor_set_2.processors().iter().map(Processor::id));
+
+            eprintln!(
+                "{work_distribution} reference interference selection: ({cpulist1}) & ({cpulist2})"
+            );
+        }
+    }
+
+    let mut benchmark_name = match mode {
+        MeasurementMode::SteadyState => work_distribution.to_string(),
+        MeasurementMode::ColdStart => format!("{work_distribution}ColdStart"),
+    };
+
+    if let Some(interference) = interference {
+        benchmark_name = format!("{benchmark_name}Under{}", interference.distribution);
+    }
+
+    if let Some(memory_pressure) = &config.memory_pressure {
+        benchmark_name = format!("{benchmark_name}Under{memory_pressure}");
+    }
+
+    if let Some(migration) = config.migration {
+        benchmark_name = format!("{benchmark_name}MigratingTo{migration}");
+    }
+
+    let migration = config.migration;
+
+    // The memory pressure (if any) is applied until the end of the benchmark, when it drops.
+    // Fake runs only list or smoke-test the benchmarks, so we do not allocate anything for them.
+    let memory_pressure = match &config.memory_pressure {
+        Some(memory_pressure) if !is_fake_run() => {
+            let Some(applied) = memory_pressure.apply() else {
+                eprintln!(
+                    "Skipping {work_distribution} - system hardware topology is not compatible with {memory_pressure}."
+                );
+
+                return;
+            };
+
+            Some(applied)
+        }
+        _ => None,
+    };
+
+    // Each benchmark starts with a clean slate of metrics.
+    let metrics = config.measure.clone().map(|measure| MetricsCollector {
+        measure,
+        totals: Arc::new(Mutex::new(MetricTotals::new())),
+    });
+
+    let batch_metrics = metrics.as_ref();
+
+    // Only payloads that process their work in chunks report anything here.
+    let chunk_stats = Arc::new(Mutex::new(ChunkStats::new()));
+
+    let batch_chunk_stats = &chunk_stats;
+
+    // Each benchmark is a separate profiling session. Fake runs only list or smoke-test the
+    // benchmarks, so there is nothing worth profiling in them.
+    let profiler = config
+        .profiler
+        .clone()
+        .filter(|_| !is_fake_run())
+        .map(|profiler| {
+            Arc::new(ProfilerGate::new(
+                profiler,
+                type_name::<P>(),
+                &benchmark_name,
+            ))
+        });
+
+    let batch_profiler = profiler.as_ref();
+
+    // Every call to our measurement routine by Criterion is one sample.
+    let samples = RefCell::new(Vec::new());
+
+    let batch_samples = &samples;
+
+    if let Some(profiler) = batch_profiler {
+        profiler.start();
     }
 
-    g.bench_function(work_distribution.to_string(), |b| {
+    g.bench_function(&benchmark_name, |b| {
         b.iter_custom(move |iters| {
             let mut total_duration = Duration::ZERO;
 
             let mut iters_remaining = iters;
 
             while iters_remaining > 0 {
-                let batch_size = iters_remaining.min(BATCH_SIZE);
+                let batch_size = iters_remaining.min(max_batch_size);
 
                 iters_remaining = iters_remaining
                     .checked_sub(batch_size)
                     .expect("we used min() above to ensure we do not consume more iterations than remaining");
 
                 // Each batch uses the same selection of processors.
-                let processor_set_pairs = get_processor_set_pairs(work_distribution)
+                let (processor_set_pairs, _) = select_processor_set_pairs(work_distribution, selector)
                     .expect("we already validated that we have the right topology");
 
-                let batch_duration = BenchmarkBatch::new::<P>(&processor_set_pairs, work_distribution, batch_size)
-                    .wait();
+                // Each worker is migrated to its own target (if any) in every iteration of the batch.
+                let migration_targets = migration.map(|migration| {
+                    select_migration_targets(&processor_set_pairs, migration, selector)
+                        .expect("we already validated that we have the right topology")
+                });
+
+                // The interference (if any) is running until the end of the batch, when it drops.
+                let _interference = interference.map(|interference| {
+                    let (interference_pairs, _) = select_processor_set_pairs(interference.distribution, selector)
+                        .expect("we already validated that we have the right topology");
+
+                    (interference.start)(&interference_pairs, interference.distribution)
+                });
+
+                let batch_duration = match mode {
+                    MeasurementMode::SteadyState => {
+                        BenchmarkBatch::new::<P>(&processor_set_pairs, migration_targets.as_deref(), work_distribution, batch_size, mode, batch_metrics, batch_chunk_stats, batch_profiler)
+                            .wait()
+                    }
+                    MeasurementMode::ColdStart => {
+                        // The workers themselves only know about the processing step, so
+                        // we measure the whole lifecycle from the outside.
+                        let _profiled = batch_profiler.map(|profiler| profiler.enter());
+
+                        let start = Instant::now();
+
+                        // The workers do not profile their processing step, as we already do.
+                        BenchmarkBatch::new::<P>(&processor_set_pairs, migration_targets.as_deref(), work_distribution, batch_size, mode, batch_metrics, batch_chunk_stats, None)
+                            .wait();
+
+                        start.elapsed()
+                    }
+                };
 
                 total_duration = total_duration.checked_add(batch_duration)
                     .expect("duration overflow is unfathomable within our spacetime boundaries");
             }
 
+            batch_samples.borrow_mut().push(Sample::new(iters, total_duration));
+
             total_duration
         });
     });
+
+    drop(memory_pressure);
+
+    if let Some(profiler) = profiler {
+        profiler.stop();
+    }
+
+    if let Some(metrics) = &metrics {
+        if !is_fake_run() {
+            eprintln!("{benchmark_name} custom metrics (mean per iteration): {metrics}");
+        }
+    }
+
+    let chunk_stats = chunk_stats.lock().unwrap();
+
+    if !chunk_stats.is_empty() && !is_fake_run() {
+        eprintln!("{benchmark_name} chunk durations: {chunk_stats}");
+    }
+
+    if migration.is_some() && chunk_stats.is_empty() && !is_fake_run() {
+        eprintln!(
+            "{benchmark_name} did not migrate any workers - {} does not report any checkpoints.",
+            type_name::<P>()
+        );
+    }
+
+    let metric_means = metrics.as_ref().map_or_else(BTreeMap::new, |metrics| {
+        metrics.totals.lock().unwrap().means()
+    });
+
+    let run_samples = RunSamples::new(
+        type_name::<P>(),
+        benchmark_name,
+        work_distribution,
+        samples.into_inner(),
+        metric_means,
+    );
+
+    #[cfg(feature = "export")]
+    if let Some(exporter) = exporter {
+        exporter.export(&run_samples);
+    }
+
+    if let Some(on_samples) = &config.on_samples {
+        (*on_samples.borrow_mut())(run_samples);
+    }
+}
+
+/// Selects the processor that each worker is migrated to, in the same shape as the processor set
+/// pairs of the workers, with no two workers migrated to the same processor or to a processor
+/// of any worker.
+///
+/// Returns `None` if there are not enough suitable processors.
+fn select_migration_targets(
+    processor_set_pairs: &[(ProcessorSet, ProcessorSet)],
+    migration: Migration,
+    selector: &ProcessorSelector,
+) -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
+    let mut occupied = processor_set_pairs
+        .iter()
+        .flat_map(|(set1, set2)| set1.processors().iter().chain(set2.processors().iter()))
+        .cloned()
+        .collect_vec();
+
+    let mut select = |processor_set: &ProcessorSet| {
+        let target = migration.target(selector.builder(), processor_set, &occupied)?;
+        occupied.extend(target.processors().iter().cloned());
+        Some(target)
+    };
+
+    processor_set_pairs
+        .iter()
+        .map(|(set1, set2)| Some((select(set1)?, select(set2)?)))
+        .collect()
 }
 
 /// Identifies how many worker thread pairs we need to use in the benchmark, based on the hardware
@@ -126,10 +1112,11 @@ fn execute_run<P: Payload, const BATCH_SIZE: u64>(
 ///
 /// All work distributions use the same number of pairs as the reference scenario, for
 /// optimal comparability between different distributions.
-fn calculate_worker_pair_count() -> NonZero<usize> {
+fn calculate_worker_pair_count(selector: &ProcessorSelector) -> NonZero<usize> {
     // One pair for every memory region. That's it.
     NonZero::new(
-        ProcessorSet::builder()
+        selector
+            .builder()
             .performance_processors_only()
             .take_all()
             .expect("must have at least one processor")
@@ -144,6 +1131,98 @@ fn calculate_worker_pair_count() -> NonZero<usize> {
 
 const ONE_PROCESSOR: NonZero<usize> = nz!(1);
 
+/// Selects the processors for the workers, honoring the constraints configured via environment
+/// variables.
+///
+/// Only used on the thread that executes the runs.
+struct ProcessorSelector {
+    /// Used only if the work distribution cannot be satisfied without them.
+    avoided_processors: Vec<ProcessorId>,
+
+    /// If set, the workers never use any other processors.
+    allowed_processors: Option<Vec<ProcessorId>>,
+
+    /// If set, every random choice is derived from this, making the selections deterministic.
+    rng: Option<RefCell<StdRng>>,
+}
+
+impl ProcessorSelector {
+    fn new(
+        avoided_processors: Vec<ProcessorId>,
+        allowed_processors: Option<Vec<ProcessorId>>,
+        seed: Option<u64>,
+    ) -> Self {
+        Self {
+            avoided_processors,
+            allowed_processors,
+            rng: seed.map(|seed| RefCell::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    /// A builder that only considers the allowed processors and is seeded if we are seeded.
+    fn builder(&self) -> ProcessorSetBuilder {
+        self.seeded(self.allowed_builder())
+    }
+
+    /// A builder that only considers the allowed processors, without consuming any randomness.
+    fn allowed_builder(&self) -> ProcessorSetBuilder {
+        let builder = ProcessorSet::builder();
+
+        match &self.allowed_processors {
+            Some(allowed_processors) => builder.filter(|p| allowed_processors.contains(&p.id())),
+            None => builder,
+        }
+    }
+
+    /// The processors whose frequency is fixed: all the processors the workers are allowed to
+    /// use, except the avoided ones. If every allowed processor is avoided, the avoided ones are
+    /// used anyway, just like when selecting the processors for the workers.
+    fn frequency_pinned_processors(&self) -> ProcessorSet {
+        self.allowed_builder()
+            .filter(|p| !self.avoided_processors.contains(&p.id()))
+            .take_all()
+            .or_else(|| self.allowed_builder().take_all())
+            .unwrap_or_else(|| {
+                panic!("{PROCESSORS_ENV_VAR} must designate at least one processor available to the current process")
+            })
+    }
+
+    /// Seeds the builder if we are seeded, so its choices are deterministic.
+    fn seeded(&self, builder: ProcessorSetBuilder) -> ProcessorSetBuilder {
+        match &self.rng {
+            Some(rng) => builder.with_seed(rng.borrow_mut().next_u64()),
+            None => builder,
+        }
+    }
+
+    /// Shuffles the items, deterministically if we are seeded.
+    fn shuffle<T>(&self, items: &mut [T]) {
+        match &self.rng {
+            Some(rng) => items.shuffle(&mut *rng.borrow_mut()),
+            None => items.shuffle(&mut rng()),
+        }
+    }
+}
+
+/// Obtains the processor pairs to use for one iteration of the benchmark, avoiding the avoided
+/// processors of the selector if the distribution can be satisfied without them.
+///
+/// Returns the pairs together with whether the avoided processors were avoided.
+fn select_processor_set_pairs(
+    distribution: WorkDistribution,
+    selector: &ProcessorSelector,
+) -> Option<(Vec<(ProcessorSet, ProcessorSet)>, bool)> {
+    if !selector.avoided_processors.is_empty() {
+        if let Some(pairs) =
+            get_processor_set_pairs(distribution, &selector.avoided_processors, selector)
+        {
+            return Some((pairs, true));
+        }
+    }
+
+    get_processor_set_pairs(distribution, &[], selector).map(|pairs| (pairs, false))
+}
+
 /// Obtains the processor pairs to use for one iteration of the benchmark. We pick different
 /// processors for different iterations to help average out any differences in performance
 /// that may exist due to competing workloads.
@@ -151,16 +1230,24 @@ const ONE_PROCESSOR: NonZero<usize> = nz!(1);
 /// For the "pinned" variants, this returns for each pair of workers a pair of single-processor
 /// `ProcessorSet`s. For the "unpinned" variants, this returns for each pair of workers a pair of
 /// many-processor `ProcessorSet`s.
+///
+/// Processors in `avoided_processors` are never selected. Returns `None` if the distribution
+/// cannot be satisfied with the remaining processors.
 fn get_processor_set_pairs(
     distribution: WorkDistribution,
+    avoided_processors: &[ProcessorId],
+    selector: &ProcessorSelector,
 ) -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
-    let worker_pair_count = calculate_worker_pair_count();
+    // The pair count is independent of the avoided processors, to maintain comparability.
+    let worker_pair_count = calculate_worker_pair_count(selector);
 
-    // If the system has efficiency processors, we do not want them.
-    let candidates = ProcessorSet::builder()
+    // If the system has efficiency processors, we do not want them. There is always at least
+    // one performance processor but it may be avoided or not allowed.
+    let candidates = selector
+        .builder()
         .performance_processors_only()
-        .take_all()
-        .expect("there must be at least one performance processor on any system, by definition");
+        .filter(|p| !avoided_processors.contains(&p.id()))
+        .take_all()?;
 
     match distribution {
         WorkDistribution::PinnedMemoryRegionPairs => {
@@ -171,8 +1258,8 @@ fn get_processor_set_pairs(
             }
 
             // We start by picking the first item in each pair.
-            let first_processors = candidates
-                .to_builder()
+            let first_processors = selector
+                .seeded(candidates.to_builder())
                 .different_memory_regions()
                 .take(worker_pair_count)?;
 
@@ -185,8 +1272,8 @@ fn get_processor_set_pairs(
                 .processors()
                 .iter()
                 .filter_map(|p| {
-                    candidates
-                        .to_builder()
+                    selector
+                        .seeded(candidates.to_builder())
                         .except([p])
                         .filter(|c| c.memory_region_id() == p.memory_region_id())
                         .take(ONE_PROCESSOR)
@@ -220,12 +1307,24 @@ fn get_processor_set_pairs(
                     .collect_vec(),
             )
         }
+        WorkDistribution::PinnedNearestRemoteRegion => get_remote_region_pairs(
+            &candidates,
+            worker_pair_count,
+            RemoteRegion::Nearest,
+            selector,
+        ),
+        WorkDistribution::PinnedFarthestRemoteRegion => get_remote_region_pairs(
+            &candidates,
+            worker_pair_count,
+            RemoteRegion::Farthest,
+            selector,
+        ),
         WorkDistribution::PinnedSameMemoryRegion => {
             // We start by picking the first item in each pair. We still distribute the pairs
             // across all memory regions to even out the load and any hardware differences, even
             // though we do not actually care about crossing memory regions during operation.
-            let first_processors = candidates
-                .to_builder()
+            let first_processors = selector
+                .seeded(candidates.to_builder())
                 .different_memory_regions()
                 .take(worker_pair_count)?;
 
@@ -238,8 +1337,8 @@ fn get_processor_set_pairs(
                 .processors()
                 .iter()
                 .filter_map(|p| {
-                    candidates
-                        .to_builder()
+                    selector
+                        .seeded(candidates.to_builder())
                         .except([p])
                         .filter(|c| c.memory_region_id() == p.memory_region_id())
                         .take(ONE_PROCESSOR)
@@ -269,8 +1368,8 @@ fn get_processor_set_pairs(
             // Here we do not care at all which processors are selected - work is
             // local on every processor, so just pick arbitrary pairs.
             Some(
-                candidates
-                    .to_builder()
+                selector
+                    .seeded(candidates.to_builder())
                     .take(
                         NonZero::new(
                             worker_pair_count
@@ -304,8 +1403,8 @@ fn get_processor_set_pairs(
             // To maintain comparability between distributions and avoid structural randomness,
             // we pick one processor from each NUMA node - the same logic as with region-pairs.
             // We start by picking the first item in each pair.
-            let processors = candidates
-                .to_builder()
+            let processors = selector
+                .seeded(candidates.to_builder())
                 .different_memory_regions()
                 .take(worker_pair_count)?;
 
@@ -334,8 +1433,8 @@ fn get_processor_set_pairs(
             }
 
             // We start by picking the first one of each pair.
-            let first_processors = candidates
-                .to_builder()
+            let first_processors = selector
+                .seeded(candidates.to_builder())
                 .different_memory_regions()
                 .take(worker_pair_count)?;
 
@@ -353,8 +1452,8 @@ fn get_processor_set_pairs(
                 .processors()
                 .into_iter()
                 .map(|p| {
-                    candidates
-                        .to_builder()
+                    selector
+                        .seeded(candidates.to_builder())
                         .filter(|c| c.memory_region_id() == p.memory_region_id())
                         .take_all()
                         .expect("must have at least one processor in every active memory region")
@@ -381,8 +1480,8 @@ fn get_processor_set_pairs(
             // We start by picking the first item in each pair. We still distribute the pairs
             // across all memory regions to even out the load and any hardware differences, even
             // though we do not actually care about crossing memory regions during operation.
-            let first_processors = candidates
-                .to_builder()
+            let first_processors = selector
+                .seeded(candidates.to_builder())
                 .different_memory_regions()
                 .take(worker_pair_count)?;
 
@@ -395,8 +1494,8 @@ fn get_processor_set_pairs(
                 .processors()
                 .iter()
                 .filter_map(|p| {
-                    candidates
-                        .to_builder()
+                    selector
+                        .seeded(candidates.to_builder())
                         .except([p])
                         .filter(|c| c.memory_region_id() == p.memory_region_id())
                         .take(ONE_PROCESSOR)
@@ -428,8 +1527,8 @@ fn get_processor_set_pairs(
                 pairs_single
                     .into_iter()
                     .map(|(p1, p2)| {
-                        let remaining = candidates
-                            .to_builder()
+                        let remaining = selector
+                            .seeded(candidates.to_builder())
                             .except([&p1, &p2])
                             .filter(|c| c.memory_region_id() == p1.memory_region_id())
                             .take_all();
@@ -446,7 +1545,7 @@ fn get_processor_set_pairs(
                             Some(remaining) => {
                                 let mut remaining_processors =
                                     remaining.processors().into_iter().cloned().collect_vec();
-                                remaining_processors.shuffle(&mut rng());
+                                selector.shuffle(&mut remaining_processors);
 
                                 #[expect(
                                     clippy::integer_division,
@@ -491,8 +1590,8 @@ fn get_processor_set_pairs(
             // We start by picking the first item in each pair. We still distribute the pairs
             // across all memory regions to even out the load and any hardware differences, even
             // though we do not actually care about crossing memory regions during operation.
-            let first_processors = candidates
-                .to_builder()
+            let first_processors = selector
+                .seeded(candidates.to_builder())
                 .different_memory_regions()
                 .take(worker_pair_count)?;
 
@@ -510,8 +1609,8 @@ fn get_processor_set_pairs(
                 .processors()
                 .into_iter()
                 .map(|p| {
-                    candidates
-                        .to_builder()
+                    selector
+                        .seeded(candidates.to_builder())
                         .filter(|c| c.memory_region_id() == p.memory_region_id())
                         .take_all()
                         .expect("must have at least one processor in every active memory region")
@@ -523,6 +1622,315 @@ fn get_processor_set_pairs(
 
             Some(first_processor_sets.into_iter().zip(partners).collect_vec())
         }
+        WorkDistribution::PinnedPerformancePairs => get_efficiency_class_pairs(
+            worker_pair_count,
+            EfficiencyClass::Performance,
+            EfficiencyClass::Performance,
+            avoided_processors,
+            selector,
+        ),
+        WorkDistribution::PinnedEfficiencyPairs => get_efficiency_class_pairs(
+            worker_pair_count,
+            EfficiencyClass::Efficiency,
+            EfficiencyClass::Efficiency,
+            avoided_processors,
+            selector,
+        ),
+        WorkDistribution::PinnedMixedEfficiencyPairs => get_efficiency_class_pairs(
+            worker_pair_count,
+            EfficiencyClass::Performance,
+            EfficiencyClass::Efficiency,
+            avoided_processors,
+            selector,
+        ),
+        WorkDistribution::PinnedNearMemoryRegion(memory_region_id) => get_pinned_pairs(
+            selector.seeded(
+                candidates
+                    .to_builder()
+                    .filter(|p| p.memory_region_id() == memory_region_id),
+            ),
+            worker_pair_count,
+        ),
+        WorkDistribution::PinnedFarFromMemoryRegion(memory_region_id) => get_pinned_pairs(
+            selector.seeded(
+                candidates
+                    .to_builder()
+                    .filter(|p| p.memory_region_id() != memory_region_id),
+            ),
+            worker_pair_count,
+        ),
+        WorkDistribution::PinnedOneNearDevice(pci_address) => {
+            let device_region = HardwareInfo::pci_device_memory_region_id(pci_address)?;
+
+            get_disjoint_pinned_pairs(
+                selector.seeded(
+                    candidates
+                        .to_builder()
+                        .filter(|p| p.memory_region_id() == device_region),
+                ),
+                selector.seeded(
+                    candidates
+                        .to_builder()
+                        .filter(|p| p.memory_region_id() != device_region),
+                ),
+                worker_pair_count,
+            )
+        }
+        WorkDistribution::PinnedFarFromDevice(pci_address) => {
+            let device_region = HardwareInfo::pci_device_memory_region_id(pci_address)?;
+
+            get_pinned_pairs(
+                selector.seeded(
+                    candidates
+                        .to_builder()
+                        .filter(|p| p.memory_region_id() != device_region),
+                ),
+                worker_pair_count,
+            )
+        }
+    }
+}
+
+/// Which remote memory region a memory region partners with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RemoteRegion {
+    Nearest,
+    Farthest,
+}
+
+/// Picks one pair of single-processor `ProcessorSet`s for each memory region of the candidates,
+/// with the second member of each pair being in the nearest or farthest remote memory region.
+///
+/// Returns `None` if there is only one memory region or some memory region does not have enough
+/// candidates to fill the pairs.
+fn get_remote_region_pairs(
+    candidates: &ProcessorSet,
+    worker_pair_count: NonZero<usize>,
+    remote_region: RemoteRegion,
+    selector: &ProcessorSelector,
+) -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
+    // Same as with PinnedMemoryRegionPairs, one pair means one memory region, so there is
+    // nothing remote to partner with.
+    if worker_pair_count.get() == 1 {
+        return None;
+    }
+
+    let first_processors = selector
+        .seeded(candidates.to_builder())
+        .different_memory_regions()
+        .take(worker_pair_count)?;
+
+    // This must logically match our pair count because we have one pair per memory region
+    // and expect to get one processor from each memory region with performance processors.
+    assert_eq!(first_processors.len(), worker_pair_count.get());
+
+    let memory_regions = first_processors
+        .processors()
+        .iter()
+        .map(Processor::memory_region_id)
+        .collect_vec();
+
+    // Several memory regions may partner with the same remote memory region, so we need to
+    // make sure that every partner is a different processor.
+    let mut used_processors = first_processors
+        .processors()
+        .iter()
+        .map(Processor::id)
+        .collect_vec();
+
+    first_processors
+        .processors()
+        .iter()
+        .map(|p1| {
+            let partner_region = pick_remote_region(
+                p1.memory_region_id(),
+                &memory_regions,
+                remote_region,
+                HardwareInfo::memory_region_distance,
+            )?;
+
+            let partner = selector
+                .seeded(candidates.to_builder())
+                .filter(|c| {
+                    c.memory_region_id() == partner_region && !used_processors.contains(&c.id())
+                })
+                .take(ONE_PROCESSOR)?;
+
+            used_processors.extend(partner.processors().iter().map(Processor::id));
+
+            Some((
+                ProcessorSet::from_processors(nonempty![p1.clone()]),
+                partner,
+            ))
+        })
+        .collect()
+}
+
+/// Picks the nearest or farthest memory region from `memory_regions` that is not `from`, as
+/// measured by `distance`, breaking ties by picking the numerically lowest memory region.
+///
+/// Returns `None` if there is no memory region other than `from`.
+fn pick_remote_region(
+    from: MemoryRegionId,
+    memory_regions: &[MemoryRegionId],
+    remote_region: RemoteRegion,
+    distance: impl Fn(MemoryRegionId, MemoryRegionId) -> u32,
+) -> Option<MemoryRegionId> {
+    let remote_regions = memory_regions.iter().copied().filter(|&to| to != from);
+
+    match remote_region {
+        RemoteRegion::Nearest => remote_regions.min_by_key(|&to| (distance(from, to), to)),
+        RemoteRegion::Farthest => {
+            remote_regions.max_by_key(|&to| (distance(from, to), Reverse(to)))
+        }
+    }
+}
+
+/// Picks `worker_pair_count` pairs of single-processor `ProcessorSet`s from the candidates
+/// matched by the builder, with no processor being used more than once.
+///
+/// Returns `None` if there are not enough candidates to fill all the pairs.
+fn get_pinned_pairs(
+    builder: ProcessorSetBuilder,
+    worker_pair_count: NonZero<usize>,
+) -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
+    Some(
+        builder
+            .take(
+                NonZero::new(
+                    worker_pair_count
+                        .get()
+                        .checked_mul(2)
+                        .expect("no system will ever have that many processors"),
+                )
+                .expect("* 2 cannot make a number zero"),
+            )?
+            .processors()
+            .iter()
+            .cloned()
+            .tuples()
+            .map(|(p1, p2)| {
+                let set1: ProcessorSet = p1.into();
+                let set2: ProcessorSet = p2.into();
+
+                (set1, set2)
+            })
+            .collect_vec(),
+    )
+}
+
+/// Picks `worker_pair_count` pairs of single-processor `ProcessorSet`s, with the first member of
+/// each pair being a processor of `first_class` and the second a processor of `second_class`.
+///
+/// Returns `None` if the system does not have enough processors of the requested classes that
+/// are not in `avoided_processors`.
+fn get_efficiency_class_pairs(
+    worker_pair_count: NonZero<usize>,
+    first_class: EfficiencyClass,
+    second_class: EfficiencyClass,
+    avoided_processors: &[ProcessorId],
+    selector: &ProcessorSelector,
+) -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
+    let candidates_of_class = |efficiency_class| {
+        processors_of_class(efficiency_class, selector)
+            .filter(|p| !avoided_processors.contains(&p.id()))
+    };
+
+    if first_class == second_class {
+        // Both members of the pair come from the same pool, so we take them all in one go
+        // to ensure that no processor is used twice.
+        return get_pinned_pairs(candidates_of_class(first_class), worker_pair_count);
+    }
+
+    // The pools are disjoint, so each side can be picked independently.
+    get_disjoint_pinned_pairs(
+        candidates_of_class(first_class),
+        candidates_of_class(second_class),
+        worker_pair_count,
+    )
+}
+
+/// Picks `worker_pair_count` pairs of single-processor `ProcessorSet`s, with the first member of
+/// each pair matched by `first` and the second by `second`. The builders must not match any of
+/// the same processors.
+///
+/// Returns `None` if either builder does not match enough processors to fill all the pairs.
+fn get_disjoint_pinned_pairs(
+    first: ProcessorSetBuilder,
+    second: ProcessorSetBuilder,
+    worker_pair_count: NonZero<usize>,
+) -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
+    let first_processors = first.take(worker_pair_count)?;
+    let second_processors = second.take(worker_pair_count)?;
+
+    Some(
+        first_processors
+            .processors()
+            .iter()
+            .cloned()
+            .zip(second_processors.processors().iter().cloned())
+            .map(|(p1, p2)| {
+                let set1: ProcessorSet = p1.into();
+                let set2: ProcessorSet = p2.into();
+
+                (set1, set2)
+            })
+            .collect_vec(),
+    )
+}
+
+/// Returns a builder that only considers the processors of the given efficiency class that the
+/// selector allows.
+fn processors_of_class(
+    efficiency_class: EfficiencyClass,
+    selector: &ProcessorSelector,
+) -> ProcessorSetBuilder {
+    match efficiency_class {
+        EfficiencyClass::Performance => selector.builder().performance_processors_only(),
+        EfficiencyClass::Efficiency => selector.builder().efficiency_processors_only(),
+    }
+}
+
+/// Creates the channels used to exchange prepared payloads between the two workers in a pair.
+///
+/// Depending on the distribution, each worker either sends to its partner or to itself.
+#[expect(
+    clippy::type_complexity,
+    reason = "only used internally, so we accept it as cost of doing business"
+)]
+fn connect_payload_channels<T>(
+    distribution: WorkDistribution,
+) -> (
+    (mpsc::Sender<T>, mpsc::Receiver<T>),
+    (mpsc::Sender<T>, mpsc::Receiver<T>),
+) {
+    let (c1_tx, c1_rx) = mpsc::channel();
+    let (c2_tx, c2_rx) = mpsc::channel();
+
+    match distribution {
+        WorkDistribution::PinnedMemoryRegionPairs
+        | WorkDistribution::PinnedNearestRemoteRegion
+        | WorkDistribution::PinnedFarthestRemoteRegion
+        | WorkDistribution::PinnedSameMemoryRegion
+        | WorkDistribution::PinnedSameProcessor
+        | WorkDistribution::UnpinnedMemoryRegionPairs
+        | WorkDistribution::ConstrainedSameMemoryRegion
+        | WorkDistribution::PinnedPerformancePairs
+        | WorkDistribution::PinnedEfficiencyPairs
+        | WorkDistribution::PinnedMixedEfficiencyPairs
+        | WorkDistribution::PinnedNearMemoryRegion(_)
+        | WorkDistribution::PinnedFarFromMemoryRegion(_)
+        | WorkDistribution::PinnedOneNearDevice(_)
+        | WorkDistribution::PinnedFarFromDevice(_) => {
+            // Worker 1 will send to worker 2 and vice versa.
+            ((c1_tx, c2_rx), (c2_tx, c1_rx))
+        }
+        WorkDistribution::PinnedSelf
+        | WorkDistribution::UnpinnedSelf
+        | WorkDistribution::UnpinnedPerMemoryRegionSelf => {
+            // Worker 1 will send to itself and worker 2 will send to itself.
+            ((c1_tx, c1_rx), (c2_tx, c2_rx))
+        }
     }
 }
 
@@ -532,10 +1940,19 @@ struct BenchmarkBatch {
 }
 
 impl BenchmarkBatch {
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "only used internally, so we accept it as cost of doing business"
+    )]
     fn new<P: Payload>(
         processor_set_pairs: &[(ProcessorSet, ProcessorSet)],
+        migration_targets: Option<&[(ProcessorSet, ProcessorSet)]>,
         distribution: WorkDistribution,
         batch_size: u64,
+        mode: MeasurementMode,
+        metrics: Option<&MetricsCollector>,
+        chunk_stats: &Arc<Mutex<ChunkStats>>,
+        profiler: Option<&Arc<ProfilerGate>>,
     ) -> Self {
         assert_ne!(processor_set_pairs.len(), 0);
 
@@ -557,9 +1974,20 @@ impl BenchmarkBatch {
 
         let mut join_handles = Vec::with_capacity(worker_count);
 
-        for processor_set_pair in processor_set_pairs {
+        for (index, processor_set_pair) in processor_set_pairs.iter().enumerate() {
             let (processor_set_1, processor_set_2) = processor_set_pair;
 
+            let (migration_target_1, migration_target_2) = match migration_targets {
+                Some(targets) => {
+                    let (target1, target2) = targets
+                        .get(index)
+                        .expect("there is a pair of migration targets for every pair of workers");
+
+                    (Some(target1.clone()), Some(target2.clone()))
+                }
+                None => (None, None),
+            };
+
             // We generate the payload instances here (but do not prepare them yet).
             let (payloads1, payloads2) = repeat_with(|| P::new_pair()).take(batch_size).unzip();
 
@@ -574,25 +2002,7 @@ impl BenchmarkBatch {
 
             // We use these to deliver a prepared payload to the worker meant to process it.
             // Depending on the mode, we either wire up the channels to themselves or each other.
-            let (c1_tx, c1_rx) = mpsc::channel::<Vec<P>>();
-            let (c2_tx, c2_rx) = mpsc::channel::<Vec<P>>();
-
-            let ((tx1, rx1), (tx2, rx2)) = match distribution {
-                WorkDistribution::PinnedMemoryRegionPairs
-                | WorkDistribution::PinnedSameMemoryRegion
-                | WorkDistribution::PinnedSameProcessor
-                | WorkDistribution::UnpinnedMemoryRegionPairs
-                | WorkDistribution::ConstrainedSameMemoryRegion => {
-                    // Worker 1 will send to worker 2 and vice versa.
-                    ((c1_tx, c2_rx), (c2_tx, c1_rx))
-                }
-                WorkDistribution::PinnedSelf
-                | WorkDistribution::UnpinnedSelf
-                | WorkDistribution::UnpinnedPerMemoryRegionSelf => {
-                    // Worker 1 will send to itself and worker 2 will send to itself.
-                    ((c1_tx, c1_rx), (c2_tx, c2_rx))
-                }
-            };
+            let ((tx1, rx1), (tx2, rx2)) = connect_payload_channels::<Vec<P>>(distribution);
 
             // We stuff everything in a bag. Whichever thread starts first takes the first group.
             let bag = Arc::new(Mutex::new(vec![
@@ -602,13 +2012,25 @@ impl BenchmarkBatch {
 
             join_handles.push(Self::spawn_worker(
                 processor_set_1,
+                migration_target_1,
                 Arc::clone(&ready_signal),
                 Arc::clone(&bag),
+                distribution,
+                mode,
+                metrics.cloned(),
+                Arc::clone(chunk_stats),
+                profiler.cloned(),
             ));
             join_handles.push(Self::spawn_worker(
                 processor_set_2,
+                migration_target_2,
                 Arc::clone(&ready_signal),
                 Arc::clone(&bag),
+                distribution,
+                mode,
+                metrics.cloned(),
+                Arc::clone(chunk_stats),
+                profiler.cloned(),
             ));
         }
 
@@ -649,10 +2071,12 @@ impl BenchmarkBatch {
 
     #[expect(
         clippy::type_complexity,
+        clippy::too_many_arguments,
         reason = "only used once, so we accept it as cost of doing business"
     )]
     fn spawn_worker<P: Payload>(
         processor_set: &ProcessorSet,
+        migration_target: Option<ProcessorSet>,
         ready_signal: Arc<Barrier>,
         payload_bag: Arc<
             Mutex<
@@ -664,8 +2088,22 @@ impl BenchmarkBatch {
                 )>,
             >,
         >,
+        distribution: WorkDistribution,
+        #[cfg_attr(
+            any(test, debug_assertions),
+            expect(
+                unused_variables,
+                reason = "only used to decide whether to clean caches"
+            )
+        )]
+        mode: MeasurementMode,
+        metrics: Option<MetricsCollector>,
+        chunk_stats: Arc<Mutex<ChunkStats>>,
+        profiler: Option<Arc<ProfilerGate>>,
     ) -> JoinHandle<Duration> {
         processor_set.spawn_thread({
+            let processor_set = processor_set.clone();
+
             move |_| {
                 let (payloads_tx, payloads_rx, mut payloads, mut payload_barriers) =
                     payload_bag.lock().unwrap().pop().unwrap();
@@ -681,14 +2119,24 @@ impl BenchmarkBatch {
 
                 // We skip this when in debug/test builds because it is expensive - this is only
                 // important for real benchmarks, not testing (we do test it separately, of course).
+                // Cold-start runs are measured as a whole, so we cannot do anything expensive here.
                 #[cfg(all(not(test), not(debug_assertions)))]
-                crate::cache::clean_caches();
+                if mode == MeasurementMode::SteadyState {
+                    crate::cache::clean_caches();
+                }
 
                 // This signal is set when all workers have completed the "prepare" step.
                 ready_signal.wait();
 
                 let mut total_duration = Duration::ZERO;
 
+                // We collect metrics locally and only merge them into the shared totals at the end,
+                // to avoid any contention between the workers while the benchmark is running.
+                let mut local_metric_totals = MetricTotals::new();
+                let mut local_chunk_stats = ChunkStats::new();
+
+                let mut checkpoint = Checkpoint::timed(P::timeout()).migrating_to(migration_target);
+
                 for payload in &mut payloads {
                     // We need to synchronize with other workers before starting on each payload
                     // because we want each worker to access the same payload at the same time
@@ -700,11 +2148,49 @@ impl BenchmarkBatch {
 
                     payload.prepare_local();
 
-                    let start = Instant::now();
+                    let elapsed = match &metrics {
+                        Some(metrics) => {
+                            let mut elapsed = None;
+
+                            let custom_metrics = (metrics.measure)(distribution, &mut || {
+                                assert!(
+                                    elapsed.is_none(),
+                                    "the measurement closure must call the timed callback exactly once but called it more than once"
+                                );
+
+                                let _profiled = profiler.as_ref().map(|profiler| profiler.enter());
+
+                                let start = Instant::now();
+                                checkpoint.start();
+
+                                payload.process_chunked(&mut checkpoint);
+
+                                elapsed = Some(start.elapsed());
+                            });
+
+                            local_metric_totals.add(custom_metrics);
+
+                            elapsed.expect("the measurement closure must call the timed callback exactly once but did not call it")
+                        }
+                        None => {
+                            let _profiled = profiler.as_ref().map(|profiler| profiler.enter());
+
+                            let start = Instant::now();
+                            checkpoint.start();
+
+                            payload.process_chunked(&mut checkpoint);
+
+                            start.elapsed()
+                        }
+                    };
+
+                    local_chunk_stats.add_iteration(&mut checkpoint);
 
-                    payload.process();
+                    if checkpoint.is_migrated() {
+                        // Every payload starts from the original processor.
+                        processor_set.pin_current_thread_to();
+                    }
 
-                    let elapsed = start.elapsed();
                     total_duration = total_duration.checked_add(elapsed).expect(
                         "duration overflow is unfathomable within our spacetime boundaries",
                     );
@@ -714,6 +2200,12 @@ impl BenchmarkBatch {
                 // measure any of the "drop" overhead above, during the benchmark iterations.
                 drop(payloads);
 
+                if let Some(metrics) = metrics {
+                    metrics.totals.lock().unwrap().merge(local_metric_totals);
+                }
+
+                chunk_stats.lock().unwrap().merge(local_chunk_stats);
+
                 total_duration
             }
         })
@@ -731,3 +2223,731 @@ impl Drop for BenchmarkBatch {
         }
     }
 }
+
+/// Background workers that repeatedly process payloads until stopped, to generate interference
+/// for the measured workers. The workers are stopped when this is dropped.
+#[derive(Debug)]
+struct Interference {
+    stop: Arc<AtomicBool>,
+    join_handles: Box<[JoinHandle<()>]>,
+}
+
+impl Interference {
+    /// Spawns the interference workers and returns once all of them have prepared their payloads.
+    fn start<N: Payload>(
+        processor_set_pairs: &[(ProcessorSet, ProcessorSet)],
+        distribution: WorkDistribution,
+    ) -> Self {
+        assert_ne!(processor_set_pairs.len(), 0);
+
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let worker_count = processor_set_pairs
+            .len()
+            .checked_mul(2)
+            .expect("we will never have so many processors that we overflow usize");
+
+        let workers_plus_coordinator = worker_count.checked_add(1).expect(
+            "we will never have so many processors that we overflow usize, even if we add one",
+        );
+
+        let ready_signal = Arc::new(Barrier::new(workers_plus_coordinator));
+
+        let mut join_handles = Vec::with_capacity(worker_count);
+
+        for (processor_set_1, processor_set_2) in processor_set_pairs {
+            let (payload1, payload2) = N::new_pair();
+
+            let ((tx1, rx1), (tx2, rx2)) = connect_payload_channels::<N>(distribution);
+
+            // Same as with the measured workers, whichever thread starts first takes the first group.
+            let bag = Arc::new(Mutex::new(vec![(tx1, rx1, payload1), (tx2, rx2, payload2)]));
+
+            for processor_set in [processor_set_1, processor_set_2] {
+                let ready_signal = Arc::clone(&ready_signal);
+                let bag = Arc::clone(&bag);
+                let stop = Arc::clone(&stop);
+
+                join_handles.push(processor_set.spawn_thread(move |_| {
+                    let (payload_tx, payload_rx, mut payload) = bag.lock().unwrap().pop().unwrap();
+
+                    payload.prepare();
+
+                    // Potentially trade payloads with the other worker in the pair.
+                    payload_tx.send(payload).unwrap();
+                    let mut payload = payload_rx.recv().unwrap();
+
+                    ready_signal.wait();
+
+                    while !stop.load(Ordering::Relaxed) {
+                        payload.prepare_local();
+                        payload.process();
+                    }
+                }));
+            }
+        }
+
+        ready_signal.wait();
+
+        Self {
+            stop,
+            join_handles: join_handles.into_boxed_slice(),
+        }
+    }
+}
+
+impl Drop for Interference {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        let join_handles = mem::replace(&mut self.join_handles, Box::new([]));
+
+        for handle in join_handles {
+            // If an interference worker panicked, we bring the panic to the main thread.
+            handle.join().unwrap();
+        }
+    }
+}
+
+/// Invokes the user-defined measurement closure and accumulates the metrics it returns.
+#[derive(Clone)]
+struct MetricsCollector {
+    measure: MeasureFn,
+    totals: Arc<Mutex<MetricTotals>>,
+}
+
+impl fmt::Display for MetricsCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.totals.lock().unwrap())
+    }
+}
+
+/// The sum of all the values reported for each custom metric and how many values were reported.
+#[derive(Debug, Default)]
+struct MetricTotals {
+    totals: BTreeMap<&'static str, (f64, u64)>,
+}
+
+impl MetricTotals {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, metrics: CustomMetrics) {
+        for (name, value) in metrics {
+            let (sum, count) = self.totals.entry(name).or_insert((0.0, 0));
+
+            *sum += value;
+            *count = count
+                .checked_add(1)
+                .expect("metric count overflow is unfathomable within our spacetime boundaries");
+        }
+    }
+
+    fn merge(&mut self, other: Self) {
+        for (name, (other_sum, other_count)) in other.totals {
+            let (sum, count) = self.totals.entry(name).or_insert((0.0, 0));
+
+            *sum += other_sum;
+            *count = count
+                .checked_add(other_count)
+                .expect("metric count overflow is unfathomable within our spacetime boundaries");
+        }
+    }
+
+    /// The mean of the values reported for each custom metric.
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "unavoidable but also unlikely since typical values will be in safe bounds"
+    )]
+    fn means(&self) -> BTreeMap<&'static str, f64> {
+        self.totals
+            .iter()
+            .map(|(&name, &(sum, count))| (name, sum / count as f64))
+            .collect()
+    }
+}
+
+impl fmt::Display for MetricTotals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let means = self
+            .means()
+            .into_iter()
+            .map(|(name, mean)| format!("{name}={mean}"))
+            .join(", ");
+
+        write!(f, "{means}")
+    }
+}
+
+#[allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_precision_loss,
+    clippy::float_cmp,
+    reason = "we need not worry in tests"
+)]
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        sync::atomic::AtomicUsize,
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn tag_filter_absent_selects_all() {
+        assert!(is_selected_by_tags(&[], None));
+        assert!(is_selected_by_tags(&["bandwidth"], None));
+    }
+
+    #[test]
+    fn tag_filter_matches_any_tag() {
+        assert!(is_selected_by_tags(
+            &["bandwidth", "collaborative"],
+            Some("latency,collaborative")
+        ));
+        assert!(is_selected_by_tags(
+            &["latency"],
+            Some(" bandwidth , latency ")
+        ));
+    }
+
+    #[test]
+    fn avoided_processors_parsed_from_cpulist() {
+        assert!(parse_avoided_processors(None).is_empty());
+        assert!(parse_avoided_processors(Some("")).is_empty());
+        assert_eq!(parse_avoided_processors(Some(" 0,16-17 ")), vec![0, 16, 17]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn avoided_processors_rejects_invalid_cpulist() {
+        parse_avoided_processors(Some("foo"));
+    }
+
+    #[test]
+    fn irq_heavy_threshold_parsed_as_positive_number() {
+        assert_eq!(parse_irq_heavy_threshold(None), None);
+        assert_eq!(parse_irq_heavy_threshold(Some(" ")), None);
+        assert_eq!(parse_irq_heavy_threshold(Some("2\n")), Some(2.0));
+        assert_eq!(parse_irq_heavy_threshold(Some(" 1.5 ")), Some(1.5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn irq_heavy_threshold_rejects_zero() {
+        parse_irq_heavy_threshold(Some("0"));
+    }
+
+    #[test]
+    fn irq_heavy_processors_are_available_processors() {
+        let all_processors = ProcessorSet::builder().take_all().unwrap();
+
+        // With a threshold this high, nothing can be interrupt-heavy.
+        assert!(irq_heavy_processors(f64::MAX).is_empty());
+
+        for processor_id in irq_heavy_processors(1.0) {
+            assert!(
+                all_processors
+                    .processors()
+                    .iter()
+                    .any(|p| p.id() == processor_id)
+            );
+        }
+    }
+
+    #[test]
+    fn coordinator_placement_parsed_from_cpulist_or_region() {
+        assert_eq!(parse_coordinator_placement(None), None);
+        assert_eq!(parse_coordinator_placement(Some(" ")), None);
+        assert_eq!(
+            parse_coordinator_placement(Some(" 0,16-17 ")),
+            Some(CoordinatorPlacement::Processors(vec![0, 16, 17]))
+        );
+        assert_eq!(
+            parse_coordinator_placement(Some("region:1\n")),
+            Some(CoordinatorPlacement::MemoryRegion(1))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn coordinator_placement_rejects_invalid_region() {
+        parse_coordinator_placement(Some("region:foo"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn coordinator_placement_rejects_invalid_cpulist() {
+        parse_coordinator_placement(Some("foo"));
+    }
+
+    #[test]
+    fn fixed_frequency_parsed_as_khz() {
+        assert_eq!(parse_fixed_frequency(None), None);
+        assert_eq!(parse_fixed_frequency(Some(" ")), None);
+        assert_eq!(parse_fixed_frequency(Some("2000000\n")), Some(2_000_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_frequency_rejects_invalid_value() {
+        parse_fixed_frequency(Some("2GHz"));
+    }
+
+    #[test]
+    fn distribution_filter_matches_names() {
+        let is_selected = |distribution, filter: Option<&str>| {
+            is_selected_by_distributions(distribution, parse_distribution_filter(filter).as_deref())
+        };
+
+        assert!(is_selected(WorkDistribution::PinnedSelf, None));
+        assert!(is_selected(
+            WorkDistribution::PinnedSelf,
+            Some("UnpinnedSelf, PinnedSelf")
+        ));
+        assert!(is_selected(
+            WorkDistribution::PinnedNearMemoryRegion(1),
+            Some("PinnedNearMemoryRegion(1)")
+        ));
+        assert!(!is_selected(
+            WorkDistribution::PinnedNearMemoryRegion(0),
+            Some("PinnedNearMemoryRegion(1)")
+        ));
+        assert!(is_selected(
+            WorkDistribution::PinnedOneNearDevice("0000:3b:00.0"),
+            Some("PinnedOneNearDevice(0000:3b:00.0)")
+        ));
+        assert!(!is_selected(
+            WorkDistribution::PinnedFarFromDevice("0000:3b:00.0"),
+            Some("PinnedOneNearDevice(0000:3b:00.0)")
+        ));
+        assert!(!is_selected(
+            WorkDistribution::PinnedSelf,
+            Some("UnpinnedSelf, ")
+        ));
+
+        // A blank filter is the same as no filter.
+        assert_eq!(parse_distribution_filter(Some("")), None);
+        assert_eq!(parse_distribution_filter(Some(" \n")), None);
+        assert!(is_selected(WorkDistribution::PinnedSelf, Some(" ")));
+    }
+
+    #[test]
+    #[should_panic]
+    fn distribution_filter_rejects_unknown_names() {
+        parse_distribution_filter(Some("PinnedSelf,PinnedSelves"));
+    }
+
+    #[test]
+    fn payload_multiplier_parsed_as_positive_integer() {
+        assert_eq!(parse_payload_multiplier(None), None);
+        assert_eq!(parse_payload_multiplier(Some(" ")), None);
+        assert_eq!(parse_payload_multiplier(Some("10\n")), Some(10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn payload_multiplier_rejects_zero() {
+        parse_payload_multiplier(Some("0"));
+    }
+
+    #[test]
+    fn seed_parsed_as_integer() {
+        assert_eq!(parse_seed(None), None);
+        assert_eq!(parse_seed(Some(" ")), None);
+        assert_eq!(parse_seed(Some(" 1234 ")), Some(1234));
+    }
+
+    #[test]
+    #[should_panic]
+    fn seed_rejects_invalid_value() {
+        parse_seed(Some("-1"));
+    }
+
+    #[test]
+    fn allowed_processors_parsed_from_cpulist() {
+        assert_eq!(parse_allowed_processors(None), None);
+        assert_eq!(parse_allowed_processors(Some(" ")), None);
+        assert_eq!(
+            parse_allowed_processors(Some(" 0,16-17 ")),
+            Some(vec![0, 16, 17])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn allowed_processors_rejects_invalid_cpulist() {
+        parse_allowed_processors(Some("foo"));
+    }
+
+    #[test]
+    fn seeded_selector_is_deterministic() {
+        let select = || {
+            let selector = ProcessorSelector::new(Vec::new(), None, Some(42));
+
+            (0..3)
+                .map(|_| {
+                    select_processor_set_pairs(WorkDistribution::PinnedSelf, &selector).map(
+                        |(pairs, _)| {
+                            pairs
+                                .iter()
+                                .map(|(set1, set2)| {
+                                    (
+                                        set1.processors().first().id(),
+                                        set2.processors().first().id(),
+                                    )
+                                })
+                                .collect_vec()
+                        },
+                    )
+                })
+                .collect_vec()
+        };
+
+        assert_eq!(select(), select());
+    }
+
+    #[test]
+    fn selector_only_uses_allowed_processors() {
+        let allowed = ProcessorSet::default().processors().first().id();
+        let selector = ProcessorSelector::new(Vec::new(), Some(vec![allowed]), None);
+
+        let candidates = selector.builder().take_all().unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates.processors().first().id(), allowed);
+    }
+
+    #[cfg(not(miri))] // Talks to the real platform.
+    #[test]
+    fn device_distributions_skipped_for_unknown_device() {
+        let selector = ProcessorSelector::new(Vec::new(), None, None);
+
+        // There is no PCI domain this large, so the device cannot exist.
+        for distribution in [
+            WorkDistribution::PinnedOneNearDevice("ffff0:00:00.0"),
+            WorkDistribution::PinnedFarFromDevice("ffff0:00:00.0"),
+        ] {
+            assert!(select_processor_set_pairs(distribution, &selector).is_none());
+        }
+    }
+
+    #[test]
+    fn mixed_efficiency_pairs_have_one_processor_of_each_class() {
+        let selector = ProcessorSelector::new(Vec::new(), None, None);
+
+        let pairs = get_efficiency_class_pairs(
+            nz!(1),
+            EfficiencyClass::Performance,
+            EfficiencyClass::Efficiency,
+            &[],
+            &selector,
+        );
+
+        let Some(pairs) = pairs else {
+            // This is only possible if the system has no efficiency processors at all.
+            assert!(
+                ProcessorSet::builder()
+                    .efficiency_processors_only()
+                    .take_all()
+                    .is_none()
+            );
+            return;
+        };
+
+        assert_eq!(pairs.len(), 1);
+
+        for (set1, set2) in pairs {
+            assert_eq!(set1.len(), 1);
+            assert_eq!(set2.len(), 1);
+
+            assert_eq!(
+                set1.processors().first().efficiency_class(),
+                EfficiencyClass::Performance
+            );
+            assert_eq!(
+                set2.processors().first().efficiency_class(),
+                EfficiencyClass::Efficiency
+            );
+        }
+    }
+
+    /// The processor pairs of the `PinnedSelf` distribution, or `None` if the system does not have
+    /// enough processors for it (e.g. a single-processor system).
+    fn pinned_self_pairs() -> Option<Vec<(ProcessorSet, ProcessorSet)>> {
+        let selector = ProcessorSelector::new(Vec::new(), None, None);
+
+        select_processor_set_pairs(WorkDistribution::PinnedSelf, &selector).map(|(pairs, _)| pairs)
+    }
+
+    /// How many payloads of a `CountingPayload` have been prepared and processed.
+    struct PayloadCounts {
+        prepared: AtomicUsize,
+        processed: AtomicUsize,
+    }
+
+    impl PayloadCounts {
+        const fn new() -> Self {
+            Self {
+                prepared: AtomicUsize::new(0),
+                processed: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    // Each test that counts payloads uses its own counts, so tests executing in parallel do not
+    // see each other's payloads.
+    const COLD_START_COUNTS: usize = 0;
+    const INTERFERENCE_COUNTS: usize = 1;
+    const METRICS_COUNTS: usize = 2;
+
+    static PAYLOAD_COUNTS: [PayloadCounts; 3] = [const { PayloadCounts::new() }; 3];
+
+    /// A payload that does nothing but count how many times it is prepared and processed, in the
+    /// `PAYLOAD_COUNTS` entry with index `COUNTS`.
+    struct CountingPayload<const COUNTS: usize>;
+
+    impl<const COUNTS: usize> CountingPayload<COUNTS> {
+        fn counts() -> &'static PayloadCounts {
+            PAYLOAD_COUNTS
+                .get(COUNTS)
+                .expect("every test uses the index of an existing entry")
+        }
+
+        fn prepared() -> usize {
+            Self::counts().prepared.load(Ordering::Relaxed)
+        }
+
+        fn processed() -> usize {
+            Self::counts().processed.load(Ordering::Relaxed)
+        }
+    }
+
+    impl<const COUNTS: usize> Payload for CountingPayload<COUNTS> {
+        fn new_pair() -> (Self, Self) {
+            (Self, Self)
+        }
+
+        fn prepare(&mut self) {
+            Self::counts().prepared.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn process(&mut self) {
+            Self::counts().processed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn cold_start_batch_prepares_and_processes_every_payload() {
+        let Some(pairs) = pinned_self_pairs() else {
+            // Not enough processors for the distribution, so there is nothing to verify.
+            return;
+        };
+
+        let worker_count = pairs.len() * 2;
+
+        for iteration in 1..=2 {
+            // Every cold-start iteration spawns fresh workers that prepare their payloads
+            // from scratch before processing them.
+            BenchmarkBatch::new::<CountingPayload<COLD_START_COUNTS>>(
+                &pairs,
+                None,
+                WorkDistribution::PinnedSelf,
+                1,
+                MeasurementMode::ColdStart,
+                None,
+                &Arc::new(Mutex::new(ChunkStats::new())),
+                None,
+            )
+            .wait();
+
+            assert_eq!(
+                CountingPayload::<COLD_START_COUNTS>::prepared(),
+                worker_count * iteration
+            );
+            assert_eq!(
+                CountingPayload::<COLD_START_COUNTS>::processed(),
+                worker_count * iteration
+            );
+        }
+    }
+
+    #[test]
+    fn interference_stops_workers_on_drop() {
+        let Some(pairs) = pinned_self_pairs() else {
+            // Not enough processors for the distribution, so there is nothing to verify.
+            return;
+        };
+
+        let interference = Interference::start::<CountingPayload<INTERFERENCE_COUNTS>>(
+            &pairs,
+            WorkDistribution::PinnedSelf,
+        );
+
+        // The workers keep processing until stopped.
+        while CountingPayload::<INTERFERENCE_COUNTS>::processed() == 0 {
+            thread::yield_now();
+        }
+
+        drop(interference);
+
+        // Dropping waits for the workers to exit, so nothing is processed afterwards.
+        let processed = CountingPayload::<INTERFERENCE_COUNTS>::processed();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(
+            CountingPayload::<INTERFERENCE_COUNTS>::processed(),
+            processed
+        );
+    }
+
+    #[test]
+    fn metric_totals_add_and_merge() {
+        let mut totals = MetricTotals::new();
+        totals.add(vec![("a", 1.0), ("b", 10.0)]);
+        totals.add(vec![("a", 3.0)]);
+
+        let mut other = MetricTotals::new();
+        other.add(vec![("a", 5.0), ("c", 7.0)]);
+
+        totals.merge(other);
+
+        assert_eq!(totals.totals.get("a"), Some(&(9.0, 3)));
+        assert_eq!(totals.totals.get("b"), Some(&(10.0, 1)));
+        assert_eq!(totals.totals.get("c"), Some(&(7.0, 1)));
+    }
+
+    #[test]
+    fn metric_totals_display_means() {
+        let mut totals = MetricTotals::new();
+        totals.add(vec![("misses", 2.0), ("bytes", 100.0)]);
+        totals.add(vec![("misses", 4.0), ("bytes", 300.0)]);
+
+        assert_eq!(totals.to_string(), "bytes=200, misses=3");
+        assert_eq!(MetricTotals::new().to_string(), "");
+    }
+
+    #[test]
+    fn metric_totals_means() {
+        let mut totals = MetricTotals::new();
+        totals.add(vec![("misses", 2.0), ("bytes", 100.0)]);
+        totals.add(vec![("misses", 5.0)]);
+
+        assert_eq!(
+            totals.means(),
+            BTreeMap::from([("bytes", 100.0), ("misses", 3.5)])
+        );
+        assert!(MetricTotals::new().means().is_empty());
+    }
+
+    #[test]
+    fn metrics_collected_once_per_iteration_of_every_worker() {
+        let Some(pairs) = pinned_self_pairs() else {
+            // Not enough processors for the distribution, so there is nothing to verify.
+            return;
+        };
+
+        let metrics = MetricsCollector {
+            measure: Arc::new(|_: WorkDistribution, timed: &mut dyn FnMut()| {
+                timed();
+                vec![("calls", 1.0), ("value", 4.0)]
+            }),
+            totals: Arc::new(Mutex::new(MetricTotals::new())),
+        };
+
+        let batch_size = 3;
+
+        BenchmarkBatch::new::<CountingPayload<METRICS_COUNTS>>(
+            &pairs,
+            None,
+            WorkDistribution::PinnedSelf,
+            batch_size,
+            MeasurementMode::SteadyState,
+            Some(&metrics),
+            &Arc::new(Mutex::new(ChunkStats::new())),
+            None,
+        )
+        .wait();
+
+        let iterations = (pairs.len() * 2) as u64 * batch_size;
+
+        let totals = metrics.totals.lock().unwrap();
+
+        assert_eq!(
+            totals.totals.get("calls"),
+            Some(&(iterations as f64, iterations))
+        );
+
+        // The mean divides by the number of iterations, not by the number of workers.
+        assert_eq!(totals.to_string(), "calls=1, value=4");
+    }
+
+    #[test]
+    fn metrics_closure_must_call_timed_section() {
+        let Some(pairs) = pinned_self_pairs() else {
+            // Not enough processors for the distribution, so there is nothing to verify.
+            return;
+        };
+
+        let metrics = MetricsCollector {
+            measure: Arc::new(|_: WorkDistribution, _: &mut dyn FnMut()| Vec::new()),
+            totals: Arc::new(Mutex::new(MetricTotals::new())),
+        };
+
+        // The panic of the workers is propagated to the thread waiting for them.
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            BenchmarkBatch::new::<CountingPayload<METRICS_COUNTS>>(
+                &pairs,
+                None,
+                WorkDistribution::PinnedSelf,
+                1,
+                MeasurementMode::SteadyState,
+                Some(&metrics),
+                &Arc::new(Mutex::new(ChunkStats::new())),
+                None,
+            )
+            .wait();
+        }))
+        .unwrap_err();
+    }
+
+    #[test]
+    fn remote_region_picked_by_distance() {
+        // Region 0 is 12 away from region 1, 20 away from region 2 and 30 away from region 3.
+        // Regions 1 and 3 are equally far from region 2.
+        fn distance(from: MemoryRegionId, to: MemoryRegionId) -> u32 {
+            match (from.min(to), from.max(to)) {
+                (a, b) if a == b => 10,
+                (0, 1) => 12,
+                (0, 2) => 20,
+                (0, 3) => 30,
+                (1, 2) | (2, 3) => 25,
+                _ => 40,
+            }
+        }
+
+        let regions = [0, 1, 2, 3];
+
+        let nearest = |from| pick_remote_region(from, &regions, RemoteRegion::Nearest, distance);
+        let farthest = |from| pick_remote_region(from, &regions, RemoteRegion::Farthest, distance);
+
+        assert_eq!(nearest(0), Some(1));
+        assert_eq!(farthest(0), Some(3));
+        assert_eq!(nearest(2), Some(0));
+        assert_eq!(farthest(2), Some(1));
+
+        assert_eq!(
+            pick_remote_region(0, &[0], RemoteRegion::Nearest, distance),
+            None
+        );
+    }
+
+    #[test]
+    fn tag_filter_rejects_non_matching() {
+        assert!(!is_selected_by_tags(&["bandwidth"], Some("latency")));
+        assert!(!is_selected_by_tags(&[], Some("latency")));
+        assert!(!is_selected_by_tags(&["bandwidth"], Some("")));
+    }
+}
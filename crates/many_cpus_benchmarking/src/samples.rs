@@ -0,0 +1,106 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::WorkDistribution;
+
+/// The raw measurements of one benchmark (one work distribution of one payload type), as
+/// reported to Criterion, together with the details of the benchmark they belong to.
+///
+/// This allows custom statistics to be calculated from the measurements without parsing the
+/// output files of Criterion.
+///
+/// See [`RunConfig::on_samples()`][crate::RunConfig::on_samples].
+#[derive(Clone, Debug)]
+pub struct RunSamples {
+    payload_type: &'static str,
+    benchmark_name: String,
+    work_distribution: WorkDistribution,
+    samples: Vec<Sample>,
+    metrics: BTreeMap<&'static str, f64>,
+}
+
+impl RunSamples {
+    pub(crate) fn new(
+        payload_type: &'static str,
+        benchmark_name: String,
+        work_distribution: WorkDistribution,
+        samples: Vec<Sample>,
+        metrics: BTreeMap<&'static str, f64>,
+    ) -> Self {
+        Self {
+            payload_type,
+            benchmark_name,
+            work_distribution,
+            samples,
+            metrics,
+        }
+    }
+
+    /// The name of the payload type, which is also the name of the Criterion benchmark group.
+    #[must_use]
+    pub fn payload_type(&self) -> &'static str {
+        self.payload_type
+    }
+
+    /// The name of the benchmark within the Criterion benchmark group (e.g.
+    /// `PinnedMemoryRegionPairsColdStart`).
+    #[must_use]
+    pub fn benchmark_name(&self) -> &str {
+        &self.benchmark_name
+    }
+
+    /// The work distribution the benchmark was executed with.
+    #[must_use]
+    pub fn work_distribution(&self) -> WorkDistribution {
+        self.work_distribution
+    }
+
+    /// The samples of the benchmark, in the order they were measured.
+    ///
+    /// This includes the samples measured by Criterion during warm-up, which come first.
+    #[must_use]
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// The mean per iteration of each [custom metric][crate::RunConfig::metrics] of the
+    /// benchmark, by metric name.
+    ///
+    /// Like the samples, this includes the iterations executed by Criterion during warm-up.
+    /// This is empty if no custom metrics were collected.
+    #[must_use]
+    pub fn metrics(&self) -> &BTreeMap<&'static str, f64> {
+        &self.metrics
+    }
+}
+
+/// One measurement reported to Criterion, covering a number of iterations of a benchmark.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "export", derive(serde::Deserialize, serde::Serialize))]
+pub struct Sample {
+    iterations: u64,
+    duration: Duration,
+}
+
+impl Sample {
+    pub(crate) fn new(iterations: u64, duration: Duration) -> Self {
+        Self {
+            iterations,
+            duration,
+        }
+    }
+
+    /// How many iterations the sample consists of.
+    #[must_use]
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The measured duration of all the iterations of the sample together.
+    ///
+    /// What is included in the duration depends on the kind of benchmark run - for example,
+    /// [cold-start runs][crate::RunConfig::cold_start] also include spawning the workers.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    any::{Any, TypeId},
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use many_cpus::{HardwareTracker, MemoryRegionId, ProcessorSet};
+
+use crate::Payload;
+
+/// A benchmark scenario in which all workers access the same read-only data set (e.g. a shared
+/// in-memory index), in addition to their own per-pair state. Executed via [`SharedDataPayload`].
+///
+/// The data set is prepared only once and kept for the rest of the process lifetime, with the
+/// [placement][SharedDataPlacement] controlling which memory region (or regions) it lives in.
+pub trait SharedDataScenario: Sized + Send + 'static {
+    /// The read-only data set shared by the workers.
+    type Data: Send + Sync + 'static;
+
+    /// Controls which memory region (or regions) the data set lives in.
+    ///
+    /// By default, the data set is [replicated][SharedDataPlacement::PerMemoryRegion]
+    /// in every memory region.
+    #[must_use]
+    fn placement() -> SharedDataPlacement {
+        SharedDataPlacement::PerMemoryRegion
+    }
+
+    /// Creates the data set. This will be called on a thread in the memory region the data set
+    /// is placed in, so memory allocated (and initialized) here is typically allocated in that
+    /// memory region. This is not counted as part of the benchmark time span.
+    fn new_data() -> Self::Data;
+
+    /// Creates the per-pair state of one worker pair in one benchmark iteration. This will be
+    /// called on the main thread.
+    fn new_pair() -> (Self, Self);
+
+    /// Performs any initialization of the per-pair state required, see [`Payload::prepare()`].
+    fn prepare(&mut self) {}
+
+    /// Processes the per-pair state, with access to the data set placed according to the
+    /// placement of the scenario. The iteration is complete when this returns for all workers.
+    fn process(&mut self, data: &Self::Data);
+
+    /// Tags that categorize the benchmark scenario, see [`Payload::tags()`].
+    #[must_use]
+    fn tags() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// Where the data set of a [`SharedDataScenario`] lives.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SharedDataPlacement {
+    /// The data set is replicated in every memory region, with each worker accessing the replica
+    /// in the memory region of the processor it is executing on.
+    PerMemoryRegion,
+
+    /// The data set is kept in one memory region, with all workers accessing it from there no
+    /// matter where they are executing.
+    ///
+    /// The memory region must have processors available to the current process, otherwise the
+    /// data set cannot be created in it and the benchmark panics.
+    InMemoryRegion(MemoryRegionId),
+}
+
+/// A [`Payload`] that executes a [`SharedDataScenario`], providing each worker with the data set
+/// shared by all workers in addition to the per-pair state created by the scenario.
+///
+/// The data set of a worker is resolved on the thread of the worker when the worker is ready to
+/// process the payload (see [`Payload::prepare_local()`]), so with
+/// [`SharedDataPlacement::PerMemoryRegion`], each worker uses the replica in its own memory
+/// region.
+///
+/// # Example
+///
+/// ```rust ignore (benchmark)
+/// struct LookupInIndex {
+///     keys: Vec<u64>,
+/// }
+///
+/// impl SharedDataScenario for LookupInIndex {
+///     type Data = HashMap<u64, u64>;
+///
+///     fn placement() -> SharedDataPlacement {
+///         SharedDataPlacement::InMemoryRegion(0)
+///     }
+///
+///     fn new_data() -> HashMap<u64, u64> {
+///         (0..10_000_000).map(|key| (key, key * 2)).collect()
+///     }
+///
+///     fn new_pair() -> (Self, Self) {
+///         let keys = || (0..10_000).map(|key| key * 997 % 10_000_000).collect();
+///         (Self { keys: keys() }, Self { keys: keys() })
+///     }
+///
+///     fn process(&mut self, data: &HashMap<u64, u64>) {
+///         for key in &self.keys {
+///             black_box(data.get(key));
+///         }
+///     }
+/// }
+///
+/// fn entrypoint(c: &mut Criterion) {
+///     execute_runs::<SharedDataPayload<LookupInIndex>, 1>(c, WorkDistribution::all());
+/// }
+/// ```
+pub struct SharedDataPayload<S: SharedDataScenario> {
+    scenario: S,
+
+    // Resolved on the thread of the worker in `prepare_local()`.
+    data: Option<Arc<S::Data>>,
+}
+
+impl<S: SharedDataScenario> SharedDataPayload<S> {
+    fn new(scenario: S) -> Self {
+        Self {
+            scenario,
+            data: None,
+        }
+    }
+}
+
+impl<S: SharedDataScenario> Payload for SharedDataPayload<S> {
+    fn new_pair() -> (Self, Self) {
+        let (scenario1, scenario2) = S::new_pair();
+
+        (Self::new(scenario1), Self::new(scenario2))
+    }
+
+    fn prepare(&mut self) {
+        self.scenario.prepare();
+    }
+
+    fn prepare_local(&mut self) {
+        self.data = Some(shared_data::<S>());
+    }
+
+    fn process(&mut self) {
+        self.scenario.process(
+            self.data
+                .as_ref()
+                .expect("prepare_local() is always called before process()"),
+        );
+    }
+
+    fn tags() -> &'static [&'static str] {
+        S::tags()
+    }
+}
+
+impl<S: SharedDataScenario> fmt::Debug for SharedDataPayload<S> {
+    #[cfg_attr(test, mutants::skip)] // We have no API contract for this.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedDataPayload")
+            .field("placement", &S::placement())
+            .field("has_data", &self.data.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+// Values inside are type-occluded `Arc<S::Data>`, with the type of `S` given by the key.
+type SharedDataCell = Arc<OnceLock<Arc<dyn Any + Send + Sync>>>;
+
+// The data sets of all the scenarios executed by the process, keyed by the type of the scenario
+// and the memory region the data set lives in. They are kept for the lifetime of the process,
+// so every data set is only prepared once, even if the scenario is executed many times.
+static SHARED_DATA: Mutex<BTreeMap<(TypeId, MemoryRegionId), SharedDataCell>> =
+    Mutex::new(BTreeMap::new());
+
+/// Returns the data set of the scenario to use from the current thread, creating it if this is
+/// the first time it is used.
+fn shared_data<S: SharedDataScenario>() -> Arc<S::Data> {
+    let placement = S::placement();
+
+    let memory_region_id = match placement {
+        SharedDataPlacement::PerMemoryRegion => HardwareTracker::current_memory_region_id(),
+        SharedDataPlacement::InMemoryRegion(memory_region_id) => memory_region_id,
+    };
+
+    // We only hold the lock for the lookup, so creating one data set does not block workers
+    // that use data sets that already exist.
+    let cell = Arc::clone(
+        SHARED_DATA
+            .lock()
+            .unwrap()
+            .entry((TypeId::of::<S>(), memory_region_id))
+            .or_default(),
+    );
+
+    // Even for replicas, the current thread may not be pinned to its memory region (e.g. an
+    // unpinned worker), so every data set is created on a thread that stays in the right place.
+    let data = cell.get_or_init(|| new_data_in_memory_region::<S>(memory_region_id));
+
+    Arc::clone(data)
+        .downcast::<S::Data>()
+        .expect("the key determines the type of the data set")
+}
+
+/// Creates the data set of the scenario on a thread pinned to the processors of the memory region.
+fn new_data_in_memory_region<S: SharedDataScenario>(
+    memory_region_id: MemoryRegionId,
+) -> Arc<dyn Any + Send + Sync> {
+    let processors = ProcessorSet::builder()
+        .filter(|p| p.memory_region_id() == memory_region_id)
+        .take_all()
+        .unwrap_or_else(|| {
+            panic!(
+                "cannot place shared data in memory region {memory_region_id} because it has no processors available to the current process"
+            )
+        });
+
+    processors
+        .spawn_thread(|_| Arc::new(S::new_data()) as Arc<dyn Any + Send + Sync>)
+        .join()
+        .expect("creating the shared data panicked")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use super::*;
+
+    static DATA_CREATED: AtomicUsize = AtomicUsize::new(0);
+
+    struct SumData {
+        sum: u64,
+    }
+
+    impl SharedDataScenario for SumData {
+        type Data = Vec<u64>;
+
+        fn new_data() -> Vec<u64> {
+            DATA_CREATED.fetch_add(1, Ordering::Relaxed);
+            vec![1, 2, 3]
+        }
+
+        fn new_pair() -> (Self, Self) {
+            (Self { sum: 0 }, Self { sum: 0 })
+        }
+
+        fn process(&mut self, data: &Vec<u64>) {
+            self.sum = data.iter().sum();
+        }
+
+        fn tags() -> &'static [&'static str] {
+            &["shared"]
+        }
+    }
+
+    #[test]
+    fn data_prepared_once_and_shared() {
+        let (mut first, second) = SharedDataPayload::<SumData>::new_pair();
+
+        // Pin to one processor, so both workers are in the same memory region.
+        let processor = ProcessorSet::default().processors().first().clone();
+        let processors = ProcessorSet::from_processor(processor);
+
+        let second = processors
+            .spawn_thread(move |_| {
+                let mut second = second;
+                second.prepare();
+                second.prepare_local();
+                second.process();
+                second
+            })
+            .join()
+            .unwrap();
+
+        processors
+            .spawn_thread(move |_| {
+                first.prepare();
+                first.prepare_local();
+                first.process();
+
+                assert!(Arc::ptr_eq(
+                    first.data.as_ref().unwrap(),
+                    second.data.as_ref().unwrap()
+                ));
+                assert_eq!(first.scenario.sum, 6);
+                assert_eq!(second.scenario.sum, 6);
+            })
+            .join()
+            .unwrap();
+
+        // Further payloads reuse the same data set, even on other threads.
+        thread::spawn(move || {
+            let (mut third, _) = SharedDataPayload::<SumData>::new_pair();
+            processors.pin_current_thread_to();
+            third.prepare_local();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(DATA_CREATED.load(Ordering::Relaxed), 1);
+        assert_eq!(SharedDataPayload::<SumData>::tags(), &["shared"]);
+    }
+}
@@ -1,4 +1,5 @@
 use derive_more::Display;
+use many_cpus::MemoryRegionId;
 
 /// How work is distributed among processors during a benchmark run.
 ///
@@ -26,6 +27,32 @@ pub enum WorkDistribution {
     /// this distribution will be skipped if the system only has a single memory region.
     PinnedMemoryRegionPairs,
 
+    /// Like `PinnedMemoryRegionPairs` but instead of partnering with the numerically neighboring
+    /// memory region, each memory region partners with the remote memory region nearest to it,
+    /// according to the memory region distances reported by the platform.
+    ///
+    /// Compared to `PinnedFarthestRemoteRegion`, this shows the best case of accessing memory
+    /// in a different memory region. Ties are broken by picking the numerically lowest memory
+    /// region.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. Benchmark runs with this distribution
+    /// will be skipped if the system only has a single memory region.
+    PinnedNearestRemoteRegion,
+
+    /// Like `PinnedMemoryRegionPairs` but instead of partnering with the numerically neighboring
+    /// memory region, each memory region partners with the remote memory region farthest from it,
+    /// according to the memory region distances reported by the platform.
+    ///
+    /// Compared to `PinnedNearestRemoteRegion`, this shows the worst case of accessing memory
+    /// in a different memory region. Ties are broken by picking the numerically lowest memory
+    /// region.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. Benchmark runs with this distribution
+    /// will be skipped if the system only has a single memory region.
+    PinnedFarthestRemoteRegion,
+
     /// Each worker in a pair is spawned in the same memory region.
     ///
     /// Each pair will work together, processing one payload between the two members. Different
@@ -110,6 +137,111 @@ pub enum WorkDistribution {
     /// set. If the benchmark logic requires two collaborating workers, you cannot use this work
     /// distribution as it would likely end in a deadlock due to lack of a partner.
     UnpinnedPerMemoryRegionSelf,
+
+    /// Both workers in each pair are pinned to performance processors, picked arbitrarily
+    /// from all the performance processors on the system.
+    ///
+    /// Each pair will work together, processing one payload between the two members.
+    ///
+    /// This serves as the baseline to compare `PinnedEfficiencyPairs` and
+    /// `PinnedMixedEfficiencyPairs` against on systems with heterogeneous processors.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. There will be a minimum of one pair.
+    PinnedPerformancePairs,
+
+    /// Both workers in each pair are pinned to efficiency processors, picked arbitrarily
+    /// from all the efficiency processors on the system.
+    ///
+    /// Each pair will work together, processing one payload between the two members.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. There will be a minimum of one pair.
+    ///
+    /// This option can only be used if the system has enough efficiency processors. Benchmark runs
+    /// with this distribution will be skipped on systems where all processors are performance
+    /// processors.
+    PinnedEfficiencyPairs,
+
+    /// In each pair, one worker is pinned to a performance processor and the other to an
+    /// efficiency processor, both picked arbitrarily from the processors of that class.
+    ///
+    /// Each pair will work together, processing one payload between the two members. This shows
+    /// the effects of a slow partner on collaborative work, as well as the cost of moving data
+    /// between the different processor types.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. There will be a minimum of one pair.
+    ///
+    /// This option can only be used if the system has enough efficiency processors. Benchmark runs
+    /// with this distribution will be skipped on systems where all processors are performance
+    /// processors.
+    PinnedMixedEfficiencyPairs,
+
+    /// Both workers in each pair are pinned to processors in the specified memory region.
+    ///
+    /// This is meant for scenarios that touch buffers owned by a device such as a network adapter
+    /// or storage controller, where the memory region is the one the device is attached to (on
+    /// Linux, this can be read from `/sys/bus/pci/devices/<address>/numa_node`). To have the
+    /// memory region looked up from the device address, use `PinnedOneNearDevice` or
+    /// `PinnedFarFromDevice` instead.
+    ///
+    /// Each pair will work together, processing one payload between the two members.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. Benchmark runs with this distribution
+    /// will be skipped if the memory region does not have enough processors for all the pairs.
+    #[display("PinnedNearMemoryRegion({_0})")]
+    PinnedNearMemoryRegion(MemoryRegionId),
+
+    /// Both workers in each pair are pinned to processors outside the specified memory region.
+    ///
+    /// This is the counterpart to `PinnedNearMemoryRegion`, used to compare the performance of
+    /// device-related work done near the device with the same work done far from the device.
+    ///
+    /// Each pair will work together, processing one payload between the two members.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. Benchmark runs with this distribution
+    /// will be skipped if the system does not have enough processors outside the memory region.
+    #[display("PinnedFarFromMemoryRegion({_0})")]
+    PinnedFarFromMemoryRegion(MemoryRegionId),
+
+    /// In each pair, one worker is pinned to a processor in the memory region that the PCI device
+    /// with the specified address (e.g. `0000:3b:00.0`) is attached to and the other worker is
+    /// pinned to a processor in a different memory region.
+    ///
+    /// This shows the cost of handing off device-related work (e.g. packets received by a network
+    /// adapter) from a worker near the device to a worker far from it. Payloads are not assigned
+    /// to a specific worker, so a payload that needs to know which side it is on can compare
+    /// `HardwareTracker::current_memory_region_id()` with
+    /// `HardwareInfo::pci_device_memory_region_id()` in [`Payload::prepare_local()`].
+    ///
+    /// Each pair will work together, processing one payload between the two members.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. Benchmark runs with this distribution
+    /// will be skipped if the memory region of the device is not reported by the platform or
+    /// there are not enough processors both in and outside that memory region.
+    ///
+    /// [`Payload::prepare_local()`]: crate::Payload::prepare_local
+    #[display("PinnedOneNearDevice({_0})")]
+    PinnedOneNearDevice(&'static str),
+
+    /// Both workers in each pair are pinned to processors outside the memory region that the PCI
+    /// device with the specified address (e.g. `0000:3b:00.0`) is attached to.
+    ///
+    /// This is the counterpart to `PinnedOneNearDevice`, showing the cost of device-related work
+    /// when no worker is near the device.
+    ///
+    /// Each pair will work together, processing one payload between the two members.
+    ///
+    /// The number of pairs will match the number that would have been used with
+    /// `PinnedMemoryRegionPairs`, for optimal comparability. Benchmark runs with this distribution
+    /// will be skipped if the memory region of the device is not reported by the platform or
+    /// there are not enough processors outside that memory region.
+    #[display("PinnedFarFromDevice({_0})")]
+    PinnedFarFromDevice(&'static str),
 }
 
 impl WorkDistribution {
@@ -118,6 +250,8 @@ impl WorkDistribution {
     pub fn all() -> &'static [Self] {
         &[
             Self::PinnedMemoryRegionPairs,
+            Self::PinnedNearestRemoteRegion,
+            Self::PinnedFarthestRemoteRegion,
             Self::PinnedSameMemoryRegion,
             Self::PinnedSameProcessor,
             Self::PinnedSelf,
@@ -125,6 +259,9 @@ impl WorkDistribution {
             Self::ConstrainedSameMemoryRegion,
             Self::UnpinnedSelf,
             Self::UnpinnedPerMemoryRegionSelf,
+            Self::PinnedPerformancePairs,
+            Self::PinnedEfficiencyPairs,
+            Self::PinnedMixedEfficiencyPairs,
         ]
     }
 
@@ -134,10 +271,15 @@ impl WorkDistribution {
     pub fn all_without_self() -> &'static [Self] {
         &[
             Self::PinnedMemoryRegionPairs,
+            Self::PinnedNearestRemoteRegion,
+            Self::PinnedFarthestRemoteRegion,
             Self::PinnedSameMemoryRegion,
             Self::PinnedSameProcessor,
             Self::UnpinnedMemoryRegionPairs,
             Self::ConstrainedSameMemoryRegion,
+            Self::PinnedPerformancePairs,
+            Self::PinnedEfficiencyPairs,
+            Self::PinnedMixedEfficiencyPairs,
         ]
     }
 
@@ -148,12 +290,17 @@ impl WorkDistribution {
     pub fn all_with_unique_processors() -> &'static [Self] {
         &[
             Self::PinnedMemoryRegionPairs,
+            Self::PinnedNearestRemoteRegion,
+            Self::PinnedFarthestRemoteRegion,
             Self::PinnedSameMemoryRegion,
             Self::PinnedSelf,
             Self::UnpinnedMemoryRegionPairs,
             Self::ConstrainedSameMemoryRegion,
             Self::UnpinnedSelf,
             Self::UnpinnedPerMemoryRegionSelf,
+            Self::PinnedPerformancePairs,
+            Self::PinnedEfficiencyPairs,
+            Self::PinnedMixedEfficiencyPairs,
         ]
     }
 
@@ -165,9 +312,61 @@ impl WorkDistribution {
     pub fn all_with_unique_processors_without_self() -> &'static [Self] {
         &[
             Self::PinnedMemoryRegionPairs,
+            Self::PinnedNearestRemoteRegion,
+            Self::PinnedFarthestRemoteRegion,
             Self::PinnedSameMemoryRegion,
             Self::UnpinnedMemoryRegionPairs,
             Self::ConstrainedSameMemoryRegion,
+            Self::PinnedPerformancePairs,
+            Self::PinnedEfficiencyPairs,
+            Self::PinnedMixedEfficiencyPairs,
         ]
     }
+
+    /// Whether the name identifies a work distribution, as displayed in the benchmark names
+    /// (e.g. `PinnedSelf`, `PinnedNearMemoryRegion(1)` or `PinnedOneNearDevice(0000:3b:00.0)`).
+    pub(crate) fn is_name(name: &str) -> bool {
+        if Self::all().iter().any(|d| d.to_string() == name) {
+            return true;
+        }
+
+        let Some((kind, argument)) = name.strip_suffix(')').and_then(|name| name.split_once('('))
+        else {
+            return false;
+        };
+
+        match kind {
+            "PinnedNearMemoryRegion" | "PinnedFarFromMemoryRegion" => {
+                argument.parse::<MemoryRegionId>().is_ok()
+            }
+            "PinnedOneNearDevice" | "PinnedFarFromDevice" => !argument.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_name_accepts_display() {
+        for distribution in WorkDistribution::all().iter().chain(&[
+            WorkDistribution::PinnedNearMemoryRegion(3),
+            WorkDistribution::PinnedFarFromMemoryRegion(0),
+            WorkDistribution::PinnedOneNearDevice("0000:3b:00.0"),
+            WorkDistribution::PinnedFarFromDevice("0000:3b:00.0"),
+        ]) {
+            assert!(WorkDistribution::is_name(&distribution.to_string()));
+        }
+    }
+
+    #[test]
+    fn is_name_rejects_unknown_names() {
+        assert!(!WorkDistribution::is_name("PinnedSelves"));
+        assert!(!WorkDistribution::is_name("PinnedSelf(1)"));
+        assert!(!WorkDistribution::is_name("PinnedNearMemoryRegion"));
+        assert!(!WorkDistribution::is_name("PinnedNearMemoryRegion(x)"));
+        assert!(!WorkDistribution::is_name("PinnedOneNearDevice()"));
+    }
 }